@@ -0,0 +1,34 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! grpcio-channelz provides an implementation of the channelz service, which
+//! exposes the runtime state of channels, subchannels, servers and sockets, like
+//! their connectivity states, call counts and the time of the last message. For
+//! the design, see https://github.com/grpc/proposal/blob/master/A14-channelz.md.
+//!
+//! ### Usage
+//!
+//! gRPC Core tracks all channels and servers unless channelz is disabled on them.
+//! ```ignore
+//! use grpcio_channelz::{create_channelz, ChannelzService};
+//!
+//! let server = ServerBuilder::new(env)
+//!     .register_service(create_channelz(ChannelzService::new()))
+//!     .bind("127.0.0.1", 0)
+//!     .build()
+//!     .unwrap();
+//! let ch = ChannelBuilder::new(env).connect(addr);
+//! ```
+//!
+//! The same data is also available in process by [`grpcio::channelz`].
+//! ```ignore
+//! use grpcio_channelz::proto::GetTopChannelsRequest;
+//!
+//! let client = ChannelzClient::new(ch);
+//! let resp = client.get_top_channels(&GetTopChannelsRequest::default()).unwrap();
+//! ```
+
+pub mod proto;
+mod service;
+
+pub use self::proto::{create_channelz, Channelz, ChannelzClient};
+pub use self::service::ChannelzService;
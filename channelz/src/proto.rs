@@ -0,0 +1,19 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+#[cfg(feature = "prost-codec")]
+mod reexports {
+    include!("proto/prost/grpc.channelz.v1.rs");
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod protobuf {
+    pub mod channelz;
+    pub mod channelz_grpc;
+}
+#[cfg(feature = "protobuf-codec")]
+mod reexports {
+    pub use super::protobuf::channelz::*;
+    pub use super::protobuf::channelz_grpc::*;
+}
+
+pub use self::reexports::*;
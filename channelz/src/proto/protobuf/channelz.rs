@@ -0,0 +1,11819 @@
+// This file is generated by rust-protobuf 2.28.0. Do not edit
+// @generated
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_imports)]
+#![allow(unused_results)]
+//! Generated file from `grpc/channelz/channelz.proto`
+
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Channel {
+    // message fields
+    pub field_ref: ::protobuf::SingularPtrField<ChannelRef>,
+    pub data: ::protobuf::SingularPtrField<ChannelData>,
+    pub channel_ref: ::protobuf::RepeatedField<ChannelRef>,
+    pub subchannel_ref: ::protobuf::RepeatedField<SubchannelRef>,
+    pub socket_ref: ::protobuf::RepeatedField<SocketRef>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Channel {
+    fn default() -> &'a Channel {
+        <Channel as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Channel {
+    pub fn new() -> Channel {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.ChannelRef ref = 1;
+
+
+    pub fn get_field_ref(&self) -> &ChannelRef {
+        self.field_ref.as_ref().unwrap_or_else(|| <ChannelRef as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_field_ref(&mut self) {
+        self.field_ref.clear();
+    }
+
+    pub fn has_field_ref(&self) -> bool {
+        self.field_ref.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_field_ref(&mut self, v: ChannelRef) {
+        self.field_ref = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_field_ref(&mut self) -> &mut ChannelRef {
+        if self.field_ref.is_none() {
+            self.field_ref.set_default();
+        }
+        self.field_ref.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_field_ref(&mut self) -> ChannelRef {
+        self.field_ref.take().unwrap_or_else(|| ChannelRef::new())
+    }
+
+    // .grpc.channelz.v1.ChannelData data = 2;
+
+
+    pub fn get_data(&self) -> &ChannelData {
+        self.data.as_ref().unwrap_or_else(|| <ChannelData as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.data.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ChannelData) {
+        self.data = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ChannelData {
+        if self.data.is_none() {
+            self.data.set_default();
+        }
+        self.data.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ChannelData {
+        self.data.take().unwrap_or_else(|| ChannelData::new())
+    }
+
+    // repeated .grpc.channelz.v1.ChannelRef channel_ref = 3;
+
+
+    pub fn get_channel_ref(&self) -> &[ChannelRef] {
+        &self.channel_ref
+    }
+    pub fn clear_channel_ref(&mut self) {
+        self.channel_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel_ref(&mut self, v: ::protobuf::RepeatedField<ChannelRef>) {
+        self.channel_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_channel_ref(&mut self) -> &mut ::protobuf::RepeatedField<ChannelRef> {
+        &mut self.channel_ref
+    }
+
+    // Take field
+    pub fn take_channel_ref(&mut self) -> ::protobuf::RepeatedField<ChannelRef> {
+        ::std::mem::replace(&mut self.channel_ref, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .grpc.channelz.v1.SubchannelRef subchannel_ref = 4;
+
+
+    pub fn get_subchannel_ref(&self) -> &[SubchannelRef] {
+        &self.subchannel_ref
+    }
+    pub fn clear_subchannel_ref(&mut self) {
+        self.subchannel_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel_ref(&mut self, v: ::protobuf::RepeatedField<SubchannelRef>) {
+        self.subchannel_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_subchannel_ref(&mut self) -> &mut ::protobuf::RepeatedField<SubchannelRef> {
+        &mut self.subchannel_ref
+    }
+
+    // Take field
+    pub fn take_subchannel_ref(&mut self) -> ::protobuf::RepeatedField<SubchannelRef> {
+        ::std::mem::replace(&mut self.subchannel_ref, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .grpc.channelz.v1.SocketRef socket_ref = 5;
+
+
+    pub fn get_socket_ref(&self) -> &[SocketRef] {
+        &self.socket_ref
+    }
+    pub fn clear_socket_ref(&mut self) {
+        self.socket_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket_ref(&mut self, v: ::protobuf::RepeatedField<SocketRef>) {
+        self.socket_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_socket_ref(&mut self) -> &mut ::protobuf::RepeatedField<SocketRef> {
+        &mut self.socket_ref
+    }
+
+    // Take field
+    pub fn take_socket_ref(&mut self) -> ::protobuf::RepeatedField<SocketRef> {
+        ::std::mem::replace(&mut self.socket_ref, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for Channel {
+    fn is_initialized(&self) -> bool {
+        for v in &self.field_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.data {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.channel_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.subchannel_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.socket_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.field_ref)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.data)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.channel_ref)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.subchannel_ref)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.socket_ref)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.field_ref.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.channel_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.subchannel_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.socket_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.field_ref.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.channel_ref {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.subchannel_ref {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.socket_ref {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Channel {
+        Channel::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelRef>>(
+                "ref",
+                |m: &Channel| { &m.field_ref },
+                |m: &mut Channel| { &mut m.field_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelData>>(
+                "data",
+                |m: &Channel| { &m.data },
+                |m: &mut Channel| { &mut m.data },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelRef>>(
+                "channel_ref",
+                |m: &Channel| { &m.channel_ref },
+                |m: &mut Channel| { &mut m.channel_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SubchannelRef>>(
+                "subchannel_ref",
+                |m: &Channel| { &m.subchannel_ref },
+                |m: &mut Channel| { &mut m.subchannel_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketRef>>(
+                "socket_ref",
+                |m: &Channel| { &m.socket_ref },
+                |m: &mut Channel| { &mut m.socket_ref },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Channel>(
+                "Channel",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Channel {
+        static instance: ::protobuf::rt::LazyV2<Channel> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Channel::new)
+    }
+}
+
+impl ::protobuf::Clear for Channel {
+    fn clear(&mut self) {
+        self.field_ref.clear();
+        self.data.clear();
+        self.channel_ref.clear();
+        self.subchannel_ref.clear();
+        self.socket_ref.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Channel {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Channel {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Subchannel {
+    // message fields
+    pub field_ref: ::protobuf::SingularPtrField<SubchannelRef>,
+    pub data: ::protobuf::SingularPtrField<ChannelData>,
+    pub channel_ref: ::protobuf::RepeatedField<ChannelRef>,
+    pub subchannel_ref: ::protobuf::RepeatedField<SubchannelRef>,
+    pub socket_ref: ::protobuf::RepeatedField<SocketRef>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Subchannel {
+    fn default() -> &'a Subchannel {
+        <Subchannel as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Subchannel {
+    pub fn new() -> Subchannel {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.SubchannelRef ref = 1;
+
+
+    pub fn get_field_ref(&self) -> &SubchannelRef {
+        self.field_ref.as_ref().unwrap_or_else(|| <SubchannelRef as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_field_ref(&mut self) {
+        self.field_ref.clear();
+    }
+
+    pub fn has_field_ref(&self) -> bool {
+        self.field_ref.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_field_ref(&mut self, v: SubchannelRef) {
+        self.field_ref = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_field_ref(&mut self) -> &mut SubchannelRef {
+        if self.field_ref.is_none() {
+            self.field_ref.set_default();
+        }
+        self.field_ref.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_field_ref(&mut self) -> SubchannelRef {
+        self.field_ref.take().unwrap_or_else(|| SubchannelRef::new())
+    }
+
+    // .grpc.channelz.v1.ChannelData data = 2;
+
+
+    pub fn get_data(&self) -> &ChannelData {
+        self.data.as_ref().unwrap_or_else(|| <ChannelData as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.data.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ChannelData) {
+        self.data = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ChannelData {
+        if self.data.is_none() {
+            self.data.set_default();
+        }
+        self.data.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ChannelData {
+        self.data.take().unwrap_or_else(|| ChannelData::new())
+    }
+
+    // repeated .grpc.channelz.v1.ChannelRef channel_ref = 3;
+
+
+    pub fn get_channel_ref(&self) -> &[ChannelRef] {
+        &self.channel_ref
+    }
+    pub fn clear_channel_ref(&mut self) {
+        self.channel_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel_ref(&mut self, v: ::protobuf::RepeatedField<ChannelRef>) {
+        self.channel_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_channel_ref(&mut self) -> &mut ::protobuf::RepeatedField<ChannelRef> {
+        &mut self.channel_ref
+    }
+
+    // Take field
+    pub fn take_channel_ref(&mut self) -> ::protobuf::RepeatedField<ChannelRef> {
+        ::std::mem::replace(&mut self.channel_ref, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .grpc.channelz.v1.SubchannelRef subchannel_ref = 4;
+
+
+    pub fn get_subchannel_ref(&self) -> &[SubchannelRef] {
+        &self.subchannel_ref
+    }
+    pub fn clear_subchannel_ref(&mut self) {
+        self.subchannel_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel_ref(&mut self, v: ::protobuf::RepeatedField<SubchannelRef>) {
+        self.subchannel_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_subchannel_ref(&mut self) -> &mut ::protobuf::RepeatedField<SubchannelRef> {
+        &mut self.subchannel_ref
+    }
+
+    // Take field
+    pub fn take_subchannel_ref(&mut self) -> ::protobuf::RepeatedField<SubchannelRef> {
+        ::std::mem::replace(&mut self.subchannel_ref, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .grpc.channelz.v1.SocketRef socket_ref = 5;
+
+
+    pub fn get_socket_ref(&self) -> &[SocketRef] {
+        &self.socket_ref
+    }
+    pub fn clear_socket_ref(&mut self) {
+        self.socket_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket_ref(&mut self, v: ::protobuf::RepeatedField<SocketRef>) {
+        self.socket_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_socket_ref(&mut self) -> &mut ::protobuf::RepeatedField<SocketRef> {
+        &mut self.socket_ref
+    }
+
+    // Take field
+    pub fn take_socket_ref(&mut self) -> ::protobuf::RepeatedField<SocketRef> {
+        ::std::mem::replace(&mut self.socket_ref, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for Subchannel {
+    fn is_initialized(&self) -> bool {
+        for v in &self.field_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.data {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.channel_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.subchannel_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.socket_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.field_ref)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.data)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.channel_ref)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.subchannel_ref)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.socket_ref)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.field_ref.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.channel_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.subchannel_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.socket_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.field_ref.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.channel_ref {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.subchannel_ref {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.socket_ref {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Subchannel {
+        Subchannel::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SubchannelRef>>(
+                "ref",
+                |m: &Subchannel| { &m.field_ref },
+                |m: &mut Subchannel| { &mut m.field_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelData>>(
+                "data",
+                |m: &Subchannel| { &m.data },
+                |m: &mut Subchannel| { &mut m.data },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelRef>>(
+                "channel_ref",
+                |m: &Subchannel| { &m.channel_ref },
+                |m: &mut Subchannel| { &mut m.channel_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SubchannelRef>>(
+                "subchannel_ref",
+                |m: &Subchannel| { &m.subchannel_ref },
+                |m: &mut Subchannel| { &mut m.subchannel_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketRef>>(
+                "socket_ref",
+                |m: &Subchannel| { &m.socket_ref },
+                |m: &mut Subchannel| { &mut m.socket_ref },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Subchannel>(
+                "Subchannel",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Subchannel {
+        static instance: ::protobuf::rt::LazyV2<Subchannel> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Subchannel::new)
+    }
+}
+
+impl ::protobuf::Clear for Subchannel {
+    fn clear(&mut self) {
+        self.field_ref.clear();
+        self.data.clear();
+        self.channel_ref.clear();
+        self.subchannel_ref.clear();
+        self.socket_ref.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Subchannel {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Subchannel {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ChannelConnectivityState {
+    // message fields
+    pub state: ChannelConnectivityState_State,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ChannelConnectivityState {
+    fn default() -> &'a ChannelConnectivityState {
+        <ChannelConnectivityState as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ChannelConnectivityState {
+    pub fn new() -> ChannelConnectivityState {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.ChannelConnectivityState.State state = 1;
+
+
+    pub fn get_state(&self) -> ChannelConnectivityState_State {
+        self.state
+    }
+    pub fn clear_state(&mut self) {
+        self.state = ChannelConnectivityState_State::UNKNOWN;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_state(&mut self, v: ChannelConnectivityState_State) {
+        self.state = v;
+    }
+}
+
+impl ::protobuf::Message for ChannelConnectivityState {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.state, 1, &mut self.unknown_fields)?
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.state != ChannelConnectivityState_State::UNKNOWN {
+            my_size += ::protobuf::rt::enum_size(1, self.state);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.state != ChannelConnectivityState_State::UNKNOWN {
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&self.state))?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ChannelConnectivityState {
+        ChannelConnectivityState::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<ChannelConnectivityState_State>>(
+                "state",
+                |m: &ChannelConnectivityState| { &m.state },
+                |m: &mut ChannelConnectivityState| { &mut m.state },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ChannelConnectivityState>(
+                "ChannelConnectivityState",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ChannelConnectivityState {
+        static instance: ::protobuf::rt::LazyV2<ChannelConnectivityState> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ChannelConnectivityState::new)
+    }
+}
+
+impl ::protobuf::Clear for ChannelConnectivityState {
+    fn clear(&mut self) {
+        self.state = ChannelConnectivityState_State::UNKNOWN;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ChannelConnectivityState {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelConnectivityState {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum ChannelConnectivityState_State {
+    UNKNOWN = 0,
+    IDLE = 1,
+    CONNECTING = 2,
+    READY = 3,
+    TRANSIENT_FAILURE = 4,
+    SHUTDOWN = 5,
+}
+
+impl ::protobuf::ProtobufEnum for ChannelConnectivityState_State {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<ChannelConnectivityState_State> {
+        match value {
+            0 => ::std::option::Option::Some(ChannelConnectivityState_State::UNKNOWN),
+            1 => ::std::option::Option::Some(ChannelConnectivityState_State::IDLE),
+            2 => ::std::option::Option::Some(ChannelConnectivityState_State::CONNECTING),
+            3 => ::std::option::Option::Some(ChannelConnectivityState_State::READY),
+            4 => ::std::option::Option::Some(ChannelConnectivityState_State::TRANSIENT_FAILURE),
+            5 => ::std::option::Option::Some(ChannelConnectivityState_State::SHUTDOWN),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [ChannelConnectivityState_State] = &[
+            ChannelConnectivityState_State::UNKNOWN,
+            ChannelConnectivityState_State::IDLE,
+            ChannelConnectivityState_State::CONNECTING,
+            ChannelConnectivityState_State::READY,
+            ChannelConnectivityState_State::TRANSIENT_FAILURE,
+            ChannelConnectivityState_State::SHUTDOWN,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<ChannelConnectivityState_State>("ChannelConnectivityState.State", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for ChannelConnectivityState_State {
+}
+
+impl ::std::default::Default for ChannelConnectivityState_State {
+    fn default() -> Self {
+        ChannelConnectivityState_State::UNKNOWN
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelConnectivityState_State {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ChannelData {
+    // message fields
+    pub state: ::protobuf::SingularPtrField<ChannelConnectivityState>,
+    pub target: ::std::string::String,
+    pub trace: ::protobuf::SingularPtrField<ChannelTrace>,
+    pub calls_started: i64,
+    pub calls_succeeded: i64,
+    pub calls_failed: i64,
+    pub last_call_started_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ChannelData {
+    fn default() -> &'a ChannelData {
+        <ChannelData as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ChannelData {
+    pub fn new() -> ChannelData {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.ChannelConnectivityState state = 1;
+
+
+    pub fn get_state(&self) -> &ChannelConnectivityState {
+        self.state.as_ref().unwrap_or_else(|| <ChannelConnectivityState as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_state(&mut self) {
+        self.state.clear();
+    }
+
+    pub fn has_state(&self) -> bool {
+        self.state.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_state(&mut self, v: ChannelConnectivityState) {
+        self.state = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_state(&mut self) -> &mut ChannelConnectivityState {
+        if self.state.is_none() {
+            self.state.set_default();
+        }
+        self.state.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_state(&mut self) -> ChannelConnectivityState {
+        self.state.take().unwrap_or_else(|| ChannelConnectivityState::new())
+    }
+
+    // string target = 2;
+
+
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+    pub fn clear_target(&mut self) {
+        self.target.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_target(&mut self, v: ::std::string::String) {
+        self.target = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_target(&mut self) -> &mut ::std::string::String {
+        &mut self.target
+    }
+
+    // Take field
+    pub fn take_target(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.target, ::std::string::String::new())
+    }
+
+    // .grpc.channelz.v1.ChannelTrace trace = 3;
+
+
+    pub fn get_trace(&self) -> &ChannelTrace {
+        self.trace.as_ref().unwrap_or_else(|| <ChannelTrace as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_trace(&mut self) {
+        self.trace.clear();
+    }
+
+    pub fn has_trace(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_trace(&mut self, v: ChannelTrace) {
+        self.trace = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_trace(&mut self) -> &mut ChannelTrace {
+        if self.trace.is_none() {
+            self.trace.set_default();
+        }
+        self.trace.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_trace(&mut self) -> ChannelTrace {
+        self.trace.take().unwrap_or_else(|| ChannelTrace::new())
+    }
+
+    // int64 calls_started = 4;
+
+
+    pub fn get_calls_started(&self) -> i64 {
+        self.calls_started
+    }
+    pub fn clear_calls_started(&mut self) {
+        self.calls_started = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_started(&mut self, v: i64) {
+        self.calls_started = v;
+    }
+
+    // int64 calls_succeeded = 5;
+
+
+    pub fn get_calls_succeeded(&self) -> i64 {
+        self.calls_succeeded
+    }
+    pub fn clear_calls_succeeded(&mut self) {
+        self.calls_succeeded = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_succeeded(&mut self, v: i64) {
+        self.calls_succeeded = v;
+    }
+
+    // int64 calls_failed = 6;
+
+
+    pub fn get_calls_failed(&self) -> i64 {
+        self.calls_failed
+    }
+    pub fn clear_calls_failed(&mut self) {
+        self.calls_failed = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_failed(&mut self, v: i64) {
+        self.calls_failed = v;
+    }
+
+    // .google.protobuf.Timestamp last_call_started_timestamp = 7;
+
+
+    pub fn get_last_call_started_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_call_started_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_call_started_timestamp(&mut self) {
+        self.last_call_started_timestamp.clear();
+    }
+
+    pub fn has_last_call_started_timestamp(&self) -> bool {
+        self.last_call_started_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_call_started_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_call_started_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_call_started_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_call_started_timestamp.is_none() {
+            self.last_call_started_timestamp.set_default();
+        }
+        self.last_call_started_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_call_started_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_call_started_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+}
+
+impl ::protobuf::Message for ChannelData {
+    fn is_initialized(&self) -> bool {
+        for v in &self.state {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.trace {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.last_call_started_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.state)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.target)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.trace)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_started = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_succeeded = tmp;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_failed = tmp;
+                },
+                7 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_call_started_timestamp)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.state.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if !self.target.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.target);
+        }
+        if let Some(ref v) = self.trace.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if self.calls_started != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.calls_started, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.calls_succeeded != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.calls_succeeded, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.calls_failed != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.calls_failed, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.last_call_started_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.state.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if !self.target.is_empty() {
+            os.write_string(2, &self.target)?;
+        }
+        if let Some(ref v) = self.trace.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if self.calls_started != 0 {
+            os.write_int64(4, self.calls_started)?;
+        }
+        if self.calls_succeeded != 0 {
+            os.write_int64(5, self.calls_succeeded)?;
+        }
+        if self.calls_failed != 0 {
+            os.write_int64(6, self.calls_failed)?;
+        }
+        if let Some(ref v) = self.last_call_started_timestamp.as_ref() {
+            os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ChannelData {
+        ChannelData::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelConnectivityState>>(
+                "state",
+                |m: &ChannelData| { &m.state },
+                |m: &mut ChannelData| { &mut m.state },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "target",
+                |m: &ChannelData| { &m.target },
+                |m: &mut ChannelData| { &mut m.target },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelTrace>>(
+                "trace",
+                |m: &ChannelData| { &m.trace },
+                |m: &mut ChannelData| { &mut m.trace },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_started",
+                |m: &ChannelData| { &m.calls_started },
+                |m: &mut ChannelData| { &mut m.calls_started },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_succeeded",
+                |m: &ChannelData| { &m.calls_succeeded },
+                |m: &mut ChannelData| { &mut m.calls_succeeded },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_failed",
+                |m: &ChannelData| { &m.calls_failed },
+                |m: &mut ChannelData| { &mut m.calls_failed },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_call_started_timestamp",
+                |m: &ChannelData| { &m.last_call_started_timestamp },
+                |m: &mut ChannelData| { &mut m.last_call_started_timestamp },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ChannelData>(
+                "ChannelData",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ChannelData {
+        static instance: ::protobuf::rt::LazyV2<ChannelData> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ChannelData::new)
+    }
+}
+
+impl ::protobuf::Clear for ChannelData {
+    fn clear(&mut self) {
+        self.state.clear();
+        self.target.clear();
+        self.trace.clear();
+        self.calls_started = 0;
+        self.calls_succeeded = 0;
+        self.calls_failed = 0;
+        self.last_call_started_timestamp.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ChannelData {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelData {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ChannelTraceEvent {
+    // message fields
+    pub description: ::std::string::String,
+    pub severity: ChannelTraceEvent_Severity,
+    pub timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    // message oneof groups
+    pub child_ref: ::std::option::Option<ChannelTraceEvent_oneof_child_ref>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ChannelTraceEvent {
+    fn default() -> &'a ChannelTraceEvent {
+        <ChannelTraceEvent as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum ChannelTraceEvent_oneof_child_ref {
+    channel_ref(ChannelRef),
+    subchannel_ref(SubchannelRef),
+}
+
+impl ChannelTraceEvent {
+    pub fn new() -> ChannelTraceEvent {
+        ::std::default::Default::default()
+    }
+
+    // string description = 1;
+
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+    pub fn clear_description(&mut self) {
+        self.description.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_description(&mut self, v: ::std::string::String) {
+        self.description = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_description(&mut self) -> &mut ::std::string::String {
+        &mut self.description
+    }
+
+    // Take field
+    pub fn take_description(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.description, ::std::string::String::new())
+    }
+
+    // .grpc.channelz.v1.ChannelTraceEvent.Severity severity = 2;
+
+
+    pub fn get_severity(&self) -> ChannelTraceEvent_Severity {
+        self.severity
+    }
+    pub fn clear_severity(&mut self) {
+        self.severity = ChannelTraceEvent_Severity::CT_UNKNOWN;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_severity(&mut self, v: ChannelTraceEvent_Severity) {
+        self.severity = v;
+    }
+
+    // .google.protobuf.Timestamp timestamp = 3;
+
+
+    pub fn get_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp.clear();
+    }
+
+    pub fn has_timestamp(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.timestamp.is_none() {
+            self.timestamp.set_default();
+        }
+        self.timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // .grpc.channelz.v1.ChannelRef channel_ref = 4;
+
+
+    pub fn get_channel_ref(&self) -> &ChannelRef {
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(ref v)) => v,
+            _ => <ChannelRef as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_channel_ref(&mut self) {
+        self.child_ref = ::std::option::Option::None;
+    }
+
+    pub fn has_channel_ref(&self) -> bool {
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel_ref(&mut self, v: ChannelRef) {
+        self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_channel_ref(&mut self) -> &mut ChannelRef {
+        if let ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(_)) = self.child_ref {
+        } else {
+            self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(ChannelRef::new()));
+        }
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_channel_ref(&mut self) -> ChannelRef {
+        if self.has_channel_ref() {
+            match self.child_ref.take() {
+                ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ChannelRef::new()
+        }
+    }
+
+    // .grpc.channelz.v1.SubchannelRef subchannel_ref = 5;
+
+
+    pub fn get_subchannel_ref(&self) -> &SubchannelRef {
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(ref v)) => v,
+            _ => <SubchannelRef as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_subchannel_ref(&mut self) {
+        self.child_ref = ::std::option::Option::None;
+    }
+
+    pub fn has_subchannel_ref(&self) -> bool {
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel_ref(&mut self, v: SubchannelRef) {
+        self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_subchannel_ref(&mut self) -> &mut SubchannelRef {
+        if let ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(_)) = self.child_ref {
+        } else {
+            self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(SubchannelRef::new()));
+        }
+        match self.child_ref {
+            ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_subchannel_ref(&mut self) -> SubchannelRef {
+        if self.has_subchannel_ref() {
+            match self.child_ref.take() {
+                ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            SubchannelRef::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for ChannelTraceEvent {
+    fn is_initialized(&self) -> bool {
+        for v in &self.timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        if let Some(ChannelTraceEvent_oneof_child_ref::channel_ref(ref v)) = self.child_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(ref v)) = self.child_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.description)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.severity, 2, &mut self.unknown_fields)?
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.timestamp)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::channel_ref(is.read_message()?));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.child_ref = ::std::option::Option::Some(ChannelTraceEvent_oneof_child_ref::subchannel_ref(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.description.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.description);
+        }
+        if self.severity != ChannelTraceEvent_Severity::CT_UNKNOWN {
+            my_size += ::protobuf::rt::enum_size(2, self.severity);
+        }
+        if let Some(ref v) = self.timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let ::std::option::Option::Some(ref v) = self.child_ref {
+            match v {
+                &ChannelTraceEvent_oneof_child_ref::channel_ref(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &ChannelTraceEvent_oneof_child_ref::subchannel_ref(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.description.is_empty() {
+            os.write_string(1, &self.description)?;
+        }
+        if self.severity != ChannelTraceEvent_Severity::CT_UNKNOWN {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(&self.severity))?;
+        }
+        if let Some(ref v) = self.timestamp.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let ::std::option::Option::Some(ref v) = self.child_ref {
+            match v {
+                &ChannelTraceEvent_oneof_child_ref::channel_ref(ref v) => {
+                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &ChannelTraceEvent_oneof_child_ref::subchannel_ref(ref v) => {
+                    os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ChannelTraceEvent {
+        ChannelTraceEvent::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "description",
+                |m: &ChannelTraceEvent| { &m.description },
+                |m: &mut ChannelTraceEvent| { &mut m.description },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<ChannelTraceEvent_Severity>>(
+                "severity",
+                |m: &ChannelTraceEvent| { &m.severity },
+                |m: &mut ChannelTraceEvent| { &mut m.severity },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "timestamp",
+                |m: &ChannelTraceEvent| { &m.timestamp },
+                |m: &mut ChannelTraceEvent| { &mut m.timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, ChannelRef>(
+                "channel_ref",
+                ChannelTraceEvent::has_channel_ref,
+                ChannelTraceEvent::get_channel_ref,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, SubchannelRef>(
+                "subchannel_ref",
+                ChannelTraceEvent::has_subchannel_ref,
+                ChannelTraceEvent::get_subchannel_ref,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ChannelTraceEvent>(
+                "ChannelTraceEvent",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ChannelTraceEvent {
+        static instance: ::protobuf::rt::LazyV2<ChannelTraceEvent> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ChannelTraceEvent::new)
+    }
+}
+
+impl ::protobuf::Clear for ChannelTraceEvent {
+    fn clear(&mut self) {
+        self.description.clear();
+        self.severity = ChannelTraceEvent_Severity::CT_UNKNOWN;
+        self.timestamp.clear();
+        self.child_ref = ::std::option::Option::None;
+        self.child_ref = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ChannelTraceEvent {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelTraceEvent {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum ChannelTraceEvent_Severity {
+    CT_UNKNOWN = 0,
+    CT_INFO = 1,
+    CT_WARNING = 2,
+    CT_ERROR = 3,
+}
+
+impl ::protobuf::ProtobufEnum for ChannelTraceEvent_Severity {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<ChannelTraceEvent_Severity> {
+        match value {
+            0 => ::std::option::Option::Some(ChannelTraceEvent_Severity::CT_UNKNOWN),
+            1 => ::std::option::Option::Some(ChannelTraceEvent_Severity::CT_INFO),
+            2 => ::std::option::Option::Some(ChannelTraceEvent_Severity::CT_WARNING),
+            3 => ::std::option::Option::Some(ChannelTraceEvent_Severity::CT_ERROR),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [ChannelTraceEvent_Severity] = &[
+            ChannelTraceEvent_Severity::CT_UNKNOWN,
+            ChannelTraceEvent_Severity::CT_INFO,
+            ChannelTraceEvent_Severity::CT_WARNING,
+            ChannelTraceEvent_Severity::CT_ERROR,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<ChannelTraceEvent_Severity>("ChannelTraceEvent.Severity", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for ChannelTraceEvent_Severity {
+}
+
+impl ::std::default::Default for ChannelTraceEvent_Severity {
+    fn default() -> Self {
+        ChannelTraceEvent_Severity::CT_UNKNOWN
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelTraceEvent_Severity {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ChannelTrace {
+    // message fields
+    pub num_events_logged: i64,
+    pub creation_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    pub events: ::protobuf::RepeatedField<ChannelTraceEvent>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ChannelTrace {
+    fn default() -> &'a ChannelTrace {
+        <ChannelTrace as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ChannelTrace {
+    pub fn new() -> ChannelTrace {
+        ::std::default::Default::default()
+    }
+
+    // int64 num_events_logged = 1;
+
+
+    pub fn get_num_events_logged(&self) -> i64 {
+        self.num_events_logged
+    }
+    pub fn clear_num_events_logged(&mut self) {
+        self.num_events_logged = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_num_events_logged(&mut self, v: i64) {
+        self.num_events_logged = v;
+    }
+
+    // .google.protobuf.Timestamp creation_timestamp = 2;
+
+
+    pub fn get_creation_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.creation_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_creation_timestamp(&mut self) {
+        self.creation_timestamp.clear();
+    }
+
+    pub fn has_creation_timestamp(&self) -> bool {
+        self.creation_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_creation_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.creation_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_creation_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.creation_timestamp.is_none() {
+            self.creation_timestamp.set_default();
+        }
+        self.creation_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_creation_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.creation_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // repeated .grpc.channelz.v1.ChannelTraceEvent events = 3;
+
+
+    pub fn get_events(&self) -> &[ChannelTraceEvent] {
+        &self.events
+    }
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_events(&mut self, v: ::protobuf::RepeatedField<ChannelTraceEvent>) {
+        self.events = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_events(&mut self) -> &mut ::protobuf::RepeatedField<ChannelTraceEvent> {
+        &mut self.events
+    }
+
+    // Take field
+    pub fn take_events(&mut self) -> ::protobuf::RepeatedField<ChannelTraceEvent> {
+        ::std::mem::replace(&mut self.events, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for ChannelTrace {
+    fn is_initialized(&self) -> bool {
+        for v in &self.creation_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.events {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.num_events_logged = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.creation_timestamp)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.events)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.num_events_logged != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.num_events_logged, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.creation_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.events {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.num_events_logged != 0 {
+            os.write_int64(1, self.num_events_logged)?;
+        }
+        if let Some(ref v) = self.creation_timestamp.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.events {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ChannelTrace {
+        ChannelTrace::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "num_events_logged",
+                |m: &ChannelTrace| { &m.num_events_logged },
+                |m: &mut ChannelTrace| { &mut m.num_events_logged },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "creation_timestamp",
+                |m: &ChannelTrace| { &m.creation_timestamp },
+                |m: &mut ChannelTrace| { &mut m.creation_timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelTraceEvent>>(
+                "events",
+                |m: &ChannelTrace| { &m.events },
+                |m: &mut ChannelTrace| { &mut m.events },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ChannelTrace>(
+                "ChannelTrace",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ChannelTrace {
+        static instance: ::protobuf::rt::LazyV2<ChannelTrace> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ChannelTrace::new)
+    }
+}
+
+impl ::protobuf::Clear for ChannelTrace {
+    fn clear(&mut self) {
+        self.num_events_logged = 0;
+        self.creation_timestamp.clear();
+        self.events.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ChannelTrace {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelTrace {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ChannelRef {
+    // message fields
+    pub channel_id: i64,
+    pub name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ChannelRef {
+    fn default() -> &'a ChannelRef {
+        <ChannelRef as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ChannelRef {
+    pub fn new() -> ChannelRef {
+        ::std::default::Default::default()
+    }
+
+    // int64 channel_id = 1;
+
+
+    pub fn get_channel_id(&self) -> i64 {
+        self.channel_id
+    }
+    pub fn clear_channel_id(&mut self) {
+        self.channel_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel_id(&mut self, v: i64) {
+        self.channel_id = v;
+    }
+
+    // string name = 2;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for ChannelRef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.channel_id = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.channel_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.channel_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.channel_id != 0 {
+            os.write_int64(1, self.channel_id)?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(2, &self.name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ChannelRef {
+        ChannelRef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "channel_id",
+                |m: &ChannelRef| { &m.channel_id },
+                |m: &mut ChannelRef| { &mut m.channel_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &ChannelRef| { &m.name },
+                |m: &mut ChannelRef| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ChannelRef>(
+                "ChannelRef",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ChannelRef {
+        static instance: ::protobuf::rt::LazyV2<ChannelRef> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ChannelRef::new)
+    }
+}
+
+impl ::protobuf::Clear for ChannelRef {
+    fn clear(&mut self) {
+        self.channel_id = 0;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ChannelRef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ChannelRef {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SubchannelRef {
+    // message fields
+    pub subchannel_id: i64,
+    pub name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SubchannelRef {
+    fn default() -> &'a SubchannelRef {
+        <SubchannelRef as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SubchannelRef {
+    pub fn new() -> SubchannelRef {
+        ::std::default::Default::default()
+    }
+
+    // int64 subchannel_id = 7;
+
+
+    pub fn get_subchannel_id(&self) -> i64 {
+        self.subchannel_id
+    }
+    pub fn clear_subchannel_id(&mut self) {
+        self.subchannel_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel_id(&mut self, v: i64) {
+        self.subchannel_id = v;
+    }
+
+    // string name = 8;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for SubchannelRef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.subchannel_id = tmp;
+                },
+                8 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.subchannel_id != 0 {
+            my_size += ::protobuf::rt::value_size(7, self.subchannel_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(8, &self.name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.subchannel_id != 0 {
+            os.write_int64(7, self.subchannel_id)?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(8, &self.name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SubchannelRef {
+        SubchannelRef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "subchannel_id",
+                |m: &SubchannelRef| { &m.subchannel_id },
+                |m: &mut SubchannelRef| { &mut m.subchannel_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &SubchannelRef| { &m.name },
+                |m: &mut SubchannelRef| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SubchannelRef>(
+                "SubchannelRef",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SubchannelRef {
+        static instance: ::protobuf::rt::LazyV2<SubchannelRef> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SubchannelRef::new)
+    }
+}
+
+impl ::protobuf::Clear for SubchannelRef {
+    fn clear(&mut self) {
+        self.subchannel_id = 0;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SubchannelRef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SubchannelRef {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketRef {
+    // message fields
+    pub socket_id: i64,
+    pub name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketRef {
+    fn default() -> &'a SocketRef {
+        <SocketRef as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketRef {
+    pub fn new() -> SocketRef {
+        ::std::default::Default::default()
+    }
+
+    // int64 socket_id = 3;
+
+
+    pub fn get_socket_id(&self) -> i64 {
+        self.socket_id
+    }
+    pub fn clear_socket_id(&mut self) {
+        self.socket_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket_id(&mut self, v: i64) {
+        self.socket_id = v;
+    }
+
+    // string name = 4;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for SocketRef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.socket_id = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.socket_id != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.socket_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.socket_id != 0 {
+            os.write_int64(3, self.socket_id)?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(4, &self.name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketRef {
+        SocketRef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "socket_id",
+                |m: &SocketRef| { &m.socket_id },
+                |m: &mut SocketRef| { &mut m.socket_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &SocketRef| { &m.name },
+                |m: &mut SocketRef| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketRef>(
+                "SocketRef",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketRef {
+        static instance: ::protobuf::rt::LazyV2<SocketRef> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketRef::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketRef {
+    fn clear(&mut self) {
+        self.socket_id = 0;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketRef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketRef {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ServerRef {
+    // message fields
+    pub server_id: i64,
+    pub name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ServerRef {
+    fn default() -> &'a ServerRef {
+        <ServerRef as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ServerRef {
+    pub fn new() -> ServerRef {
+        ::std::default::Default::default()
+    }
+
+    // int64 server_id = 5;
+
+
+    pub fn get_server_id(&self) -> i64 {
+        self.server_id
+    }
+    pub fn clear_server_id(&mut self) {
+        self.server_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_server_id(&mut self, v: i64) {
+        self.server_id = v;
+    }
+
+    // string name = 6;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for ServerRef {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.server_id = tmp;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.server_id != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.server_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.server_id != 0 {
+            os.write_int64(5, self.server_id)?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(6, &self.name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ServerRef {
+        ServerRef::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "server_id",
+                |m: &ServerRef| { &m.server_id },
+                |m: &mut ServerRef| { &mut m.server_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &ServerRef| { &m.name },
+                |m: &mut ServerRef| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ServerRef>(
+                "ServerRef",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ServerRef {
+        static instance: ::protobuf::rt::LazyV2<ServerRef> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ServerRef::new)
+    }
+}
+
+impl ::protobuf::Clear for ServerRef {
+    fn clear(&mut self) {
+        self.server_id = 0;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ServerRef {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ServerRef {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Server {
+    // message fields
+    pub field_ref: ::protobuf::SingularPtrField<ServerRef>,
+    pub data: ::protobuf::SingularPtrField<ServerData>,
+    pub listen_socket: ::protobuf::RepeatedField<SocketRef>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Server {
+    fn default() -> &'a Server {
+        <Server as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Server {
+    pub fn new() -> Server {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.ServerRef ref = 1;
+
+
+    pub fn get_field_ref(&self) -> &ServerRef {
+        self.field_ref.as_ref().unwrap_or_else(|| <ServerRef as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_field_ref(&mut self) {
+        self.field_ref.clear();
+    }
+
+    pub fn has_field_ref(&self) -> bool {
+        self.field_ref.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_field_ref(&mut self, v: ServerRef) {
+        self.field_ref = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_field_ref(&mut self) -> &mut ServerRef {
+        if self.field_ref.is_none() {
+            self.field_ref.set_default();
+        }
+        self.field_ref.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_field_ref(&mut self) -> ServerRef {
+        self.field_ref.take().unwrap_or_else(|| ServerRef::new())
+    }
+
+    // .grpc.channelz.v1.ServerData data = 2;
+
+
+    pub fn get_data(&self) -> &ServerData {
+        self.data.as_ref().unwrap_or_else(|| <ServerData as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.data.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ServerData) {
+        self.data = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ServerData {
+        if self.data.is_none() {
+            self.data.set_default();
+        }
+        self.data.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ServerData {
+        self.data.take().unwrap_or_else(|| ServerData::new())
+    }
+
+    // repeated .grpc.channelz.v1.SocketRef listen_socket = 3;
+
+
+    pub fn get_listen_socket(&self) -> &[SocketRef] {
+        &self.listen_socket
+    }
+    pub fn clear_listen_socket(&mut self) {
+        self.listen_socket.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_listen_socket(&mut self, v: ::protobuf::RepeatedField<SocketRef>) {
+        self.listen_socket = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_listen_socket(&mut self) -> &mut ::protobuf::RepeatedField<SocketRef> {
+        &mut self.listen_socket
+    }
+
+    // Take field
+    pub fn take_listen_socket(&mut self) -> ::protobuf::RepeatedField<SocketRef> {
+        ::std::mem::replace(&mut self.listen_socket, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for Server {
+    fn is_initialized(&self) -> bool {
+        for v in &self.field_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.data {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.listen_socket {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.field_ref)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.data)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.listen_socket)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.field_ref.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.listen_socket {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.field_ref.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.listen_socket {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Server {
+        Server::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ServerRef>>(
+                "ref",
+                |m: &Server| { &m.field_ref },
+                |m: &mut Server| { &mut m.field_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ServerData>>(
+                "data",
+                |m: &Server| { &m.data },
+                |m: &mut Server| { &mut m.data },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketRef>>(
+                "listen_socket",
+                |m: &Server| { &m.listen_socket },
+                |m: &mut Server| { &mut m.listen_socket },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Server>(
+                "Server",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Server {
+        static instance: ::protobuf::rt::LazyV2<Server> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Server::new)
+    }
+}
+
+impl ::protobuf::Clear for Server {
+    fn clear(&mut self) {
+        self.field_ref.clear();
+        self.data.clear();
+        self.listen_socket.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Server {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ServerData {
+    // message fields
+    pub trace: ::protobuf::SingularPtrField<ChannelTrace>,
+    pub calls_started: i64,
+    pub calls_succeeded: i64,
+    pub calls_failed: i64,
+    pub last_call_started_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ServerData {
+    fn default() -> &'a ServerData {
+        <ServerData as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ServerData {
+    pub fn new() -> ServerData {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.ChannelTrace trace = 1;
+
+
+    pub fn get_trace(&self) -> &ChannelTrace {
+        self.trace.as_ref().unwrap_or_else(|| <ChannelTrace as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_trace(&mut self) {
+        self.trace.clear();
+    }
+
+    pub fn has_trace(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_trace(&mut self, v: ChannelTrace) {
+        self.trace = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_trace(&mut self) -> &mut ChannelTrace {
+        if self.trace.is_none() {
+            self.trace.set_default();
+        }
+        self.trace.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_trace(&mut self) -> ChannelTrace {
+        self.trace.take().unwrap_or_else(|| ChannelTrace::new())
+    }
+
+    // int64 calls_started = 2;
+
+
+    pub fn get_calls_started(&self) -> i64 {
+        self.calls_started
+    }
+    pub fn clear_calls_started(&mut self) {
+        self.calls_started = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_started(&mut self, v: i64) {
+        self.calls_started = v;
+    }
+
+    // int64 calls_succeeded = 3;
+
+
+    pub fn get_calls_succeeded(&self) -> i64 {
+        self.calls_succeeded
+    }
+    pub fn clear_calls_succeeded(&mut self) {
+        self.calls_succeeded = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_succeeded(&mut self, v: i64) {
+        self.calls_succeeded = v;
+    }
+
+    // int64 calls_failed = 4;
+
+
+    pub fn get_calls_failed(&self) -> i64 {
+        self.calls_failed
+    }
+    pub fn clear_calls_failed(&mut self) {
+        self.calls_failed = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_calls_failed(&mut self, v: i64) {
+        self.calls_failed = v;
+    }
+
+    // .google.protobuf.Timestamp last_call_started_timestamp = 5;
+
+
+    pub fn get_last_call_started_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_call_started_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_call_started_timestamp(&mut self) {
+        self.last_call_started_timestamp.clear();
+    }
+
+    pub fn has_last_call_started_timestamp(&self) -> bool {
+        self.last_call_started_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_call_started_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_call_started_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_call_started_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_call_started_timestamp.is_none() {
+            self.last_call_started_timestamp.set_default();
+        }
+        self.last_call_started_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_call_started_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_call_started_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+}
+
+impl ::protobuf::Message for ServerData {
+    fn is_initialized(&self) -> bool {
+        for v in &self.trace {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.last_call_started_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.trace)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_started = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_succeeded = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.calls_failed = tmp;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_call_started_timestamp)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.trace.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if self.calls_started != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.calls_started, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.calls_succeeded != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.calls_succeeded, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.calls_failed != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.calls_failed, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.last_call_started_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.trace.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if self.calls_started != 0 {
+            os.write_int64(2, self.calls_started)?;
+        }
+        if self.calls_succeeded != 0 {
+            os.write_int64(3, self.calls_succeeded)?;
+        }
+        if self.calls_failed != 0 {
+            os.write_int64(4, self.calls_failed)?;
+        }
+        if let Some(ref v) = self.last_call_started_timestamp.as_ref() {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ServerData {
+        ServerData::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ChannelTrace>>(
+                "trace",
+                |m: &ServerData| { &m.trace },
+                |m: &mut ServerData| { &mut m.trace },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_started",
+                |m: &ServerData| { &m.calls_started },
+                |m: &mut ServerData| { &mut m.calls_started },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_succeeded",
+                |m: &ServerData| { &m.calls_succeeded },
+                |m: &mut ServerData| { &mut m.calls_succeeded },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "calls_failed",
+                |m: &ServerData| { &m.calls_failed },
+                |m: &mut ServerData| { &mut m.calls_failed },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_call_started_timestamp",
+                |m: &ServerData| { &m.last_call_started_timestamp },
+                |m: &mut ServerData| { &mut m.last_call_started_timestamp },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ServerData>(
+                "ServerData",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ServerData {
+        static instance: ::protobuf::rt::LazyV2<ServerData> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ServerData::new)
+    }
+}
+
+impl ::protobuf::Clear for ServerData {
+    fn clear(&mut self) {
+        self.trace.clear();
+        self.calls_started = 0;
+        self.calls_succeeded = 0;
+        self.calls_failed = 0;
+        self.last_call_started_timestamp.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ServerData {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ServerData {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Socket {
+    // message fields
+    pub field_ref: ::protobuf::SingularPtrField<SocketRef>,
+    pub data: ::protobuf::SingularPtrField<SocketData>,
+    pub local: ::protobuf::SingularPtrField<Address>,
+    pub remote: ::protobuf::SingularPtrField<Address>,
+    pub security: ::protobuf::SingularPtrField<Security>,
+    pub remote_name: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Socket {
+    fn default() -> &'a Socket {
+        <Socket as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Socket {
+    pub fn new() -> Socket {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.SocketRef ref = 1;
+
+
+    pub fn get_field_ref(&self) -> &SocketRef {
+        self.field_ref.as_ref().unwrap_or_else(|| <SocketRef as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_field_ref(&mut self) {
+        self.field_ref.clear();
+    }
+
+    pub fn has_field_ref(&self) -> bool {
+        self.field_ref.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_field_ref(&mut self, v: SocketRef) {
+        self.field_ref = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_field_ref(&mut self) -> &mut SocketRef {
+        if self.field_ref.is_none() {
+            self.field_ref.set_default();
+        }
+        self.field_ref.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_field_ref(&mut self) -> SocketRef {
+        self.field_ref.take().unwrap_or_else(|| SocketRef::new())
+    }
+
+    // .grpc.channelz.v1.SocketData data = 2;
+
+
+    pub fn get_data(&self) -> &SocketData {
+        self.data.as_ref().unwrap_or_else(|| <SocketData as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.data.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: SocketData) {
+        self.data = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut SocketData {
+        if self.data.is_none() {
+            self.data.set_default();
+        }
+        self.data.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> SocketData {
+        self.data.take().unwrap_or_else(|| SocketData::new())
+    }
+
+    // .grpc.channelz.v1.Address local = 3;
+
+
+    pub fn get_local(&self) -> &Address {
+        self.local.as_ref().unwrap_or_else(|| <Address as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_local(&mut self) {
+        self.local.clear();
+    }
+
+    pub fn has_local(&self) -> bool {
+        self.local.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_local(&mut self, v: Address) {
+        self.local = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_local(&mut self) -> &mut Address {
+        if self.local.is_none() {
+            self.local.set_default();
+        }
+        self.local.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_local(&mut self) -> Address {
+        self.local.take().unwrap_or_else(|| Address::new())
+    }
+
+    // .grpc.channelz.v1.Address remote = 4;
+
+
+    pub fn get_remote(&self) -> &Address {
+        self.remote.as_ref().unwrap_or_else(|| <Address as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_remote(&mut self) {
+        self.remote.clear();
+    }
+
+    pub fn has_remote(&self) -> bool {
+        self.remote.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_remote(&mut self, v: Address) {
+        self.remote = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_remote(&mut self) -> &mut Address {
+        if self.remote.is_none() {
+            self.remote.set_default();
+        }
+        self.remote.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_remote(&mut self) -> Address {
+        self.remote.take().unwrap_or_else(|| Address::new())
+    }
+
+    // .grpc.channelz.v1.Security security = 5;
+
+
+    pub fn get_security(&self) -> &Security {
+        self.security.as_ref().unwrap_or_else(|| <Security as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_security(&mut self) {
+        self.security.clear();
+    }
+
+    pub fn has_security(&self) -> bool {
+        self.security.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_security(&mut self, v: Security) {
+        self.security = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_security(&mut self) -> &mut Security {
+        if self.security.is_none() {
+            self.security.set_default();
+        }
+        self.security.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_security(&mut self) -> Security {
+        self.security.take().unwrap_or_else(|| Security::new())
+    }
+
+    // string remote_name = 6;
+
+
+    pub fn get_remote_name(&self) -> &str {
+        &self.remote_name
+    }
+    pub fn clear_remote_name(&mut self) {
+        self.remote_name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_remote_name(&mut self, v: ::std::string::String) {
+        self.remote_name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_remote_name(&mut self) -> &mut ::std::string::String {
+        &mut self.remote_name
+    }
+
+    // Take field
+    pub fn take_remote_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.remote_name, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for Socket {
+    fn is_initialized(&self) -> bool {
+        for v in &self.field_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.data {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.local {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.remote {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.security {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.field_ref)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.data)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.local)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.remote)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.security)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.remote_name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.field_ref.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.local.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.remote.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.security.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if !self.remote_name.is_empty() {
+            my_size += ::protobuf::rt::string_size(6, &self.remote_name);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.field_ref.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.data.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.local.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.remote.as_ref() {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.security.as_ref() {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if !self.remote_name.is_empty() {
+            os.write_string(6, &self.remote_name)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Socket {
+        Socket::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketRef>>(
+                "ref",
+                |m: &Socket| { &m.field_ref },
+                |m: &mut Socket| { &mut m.field_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketData>>(
+                "data",
+                |m: &Socket| { &m.data },
+                |m: &mut Socket| { &mut m.data },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Address>>(
+                "local",
+                |m: &Socket| { &m.local },
+                |m: &mut Socket| { &mut m.local },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Address>>(
+                "remote",
+                |m: &Socket| { &m.remote },
+                |m: &mut Socket| { &mut m.remote },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Security>>(
+                "security",
+                |m: &Socket| { &m.security },
+                |m: &mut Socket| { &mut m.security },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "remote_name",
+                |m: &Socket| { &m.remote_name },
+                |m: &mut Socket| { &mut m.remote_name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Socket>(
+                "Socket",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Socket {
+        static instance: ::protobuf::rt::LazyV2<Socket> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Socket::new)
+    }
+}
+
+impl ::protobuf::Clear for Socket {
+    fn clear(&mut self) {
+        self.field_ref.clear();
+        self.data.clear();
+        self.local.clear();
+        self.remote.clear();
+        self.security.clear();
+        self.remote_name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Socket {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Socket {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketData {
+    // message fields
+    pub streams_started: i64,
+    pub streams_succeeded: i64,
+    pub streams_failed: i64,
+    pub messages_sent: i64,
+    pub messages_received: i64,
+    pub keep_alives_sent: i64,
+    pub last_local_stream_created_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    pub last_remote_stream_created_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    pub last_message_sent_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    pub last_message_received_timestamp: ::protobuf::SingularPtrField<::protobuf::well_known_types::Timestamp>,
+    pub local_flow_control_window: ::protobuf::SingularPtrField<::protobuf::well_known_types::Int64Value>,
+    pub remote_flow_control_window: ::protobuf::SingularPtrField<::protobuf::well_known_types::Int64Value>,
+    pub option: ::protobuf::RepeatedField<SocketOption>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketData {
+    fn default() -> &'a SocketData {
+        <SocketData as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketData {
+    pub fn new() -> SocketData {
+        ::std::default::Default::default()
+    }
+
+    // int64 streams_started = 1;
+
+
+    pub fn get_streams_started(&self) -> i64 {
+        self.streams_started
+    }
+    pub fn clear_streams_started(&mut self) {
+        self.streams_started = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_streams_started(&mut self, v: i64) {
+        self.streams_started = v;
+    }
+
+    // int64 streams_succeeded = 2;
+
+
+    pub fn get_streams_succeeded(&self) -> i64 {
+        self.streams_succeeded
+    }
+    pub fn clear_streams_succeeded(&mut self) {
+        self.streams_succeeded = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_streams_succeeded(&mut self, v: i64) {
+        self.streams_succeeded = v;
+    }
+
+    // int64 streams_failed = 3;
+
+
+    pub fn get_streams_failed(&self) -> i64 {
+        self.streams_failed
+    }
+    pub fn clear_streams_failed(&mut self) {
+        self.streams_failed = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_streams_failed(&mut self, v: i64) {
+        self.streams_failed = v;
+    }
+
+    // int64 messages_sent = 4;
+
+
+    pub fn get_messages_sent(&self) -> i64 {
+        self.messages_sent
+    }
+    pub fn clear_messages_sent(&mut self) {
+        self.messages_sent = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_messages_sent(&mut self, v: i64) {
+        self.messages_sent = v;
+    }
+
+    // int64 messages_received = 5;
+
+
+    pub fn get_messages_received(&self) -> i64 {
+        self.messages_received
+    }
+    pub fn clear_messages_received(&mut self) {
+        self.messages_received = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_messages_received(&mut self, v: i64) {
+        self.messages_received = v;
+    }
+
+    // int64 keep_alives_sent = 6;
+
+
+    pub fn get_keep_alives_sent(&self) -> i64 {
+        self.keep_alives_sent
+    }
+    pub fn clear_keep_alives_sent(&mut self) {
+        self.keep_alives_sent = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_keep_alives_sent(&mut self, v: i64) {
+        self.keep_alives_sent = v;
+    }
+
+    // .google.protobuf.Timestamp last_local_stream_created_timestamp = 7;
+
+
+    pub fn get_last_local_stream_created_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_local_stream_created_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_local_stream_created_timestamp(&mut self) {
+        self.last_local_stream_created_timestamp.clear();
+    }
+
+    pub fn has_last_local_stream_created_timestamp(&self) -> bool {
+        self.last_local_stream_created_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_local_stream_created_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_local_stream_created_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_local_stream_created_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_local_stream_created_timestamp.is_none() {
+            self.last_local_stream_created_timestamp.set_default();
+        }
+        self.last_local_stream_created_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_local_stream_created_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_local_stream_created_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // .google.protobuf.Timestamp last_remote_stream_created_timestamp = 8;
+
+
+    pub fn get_last_remote_stream_created_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_remote_stream_created_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_remote_stream_created_timestamp(&mut self) {
+        self.last_remote_stream_created_timestamp.clear();
+    }
+
+    pub fn has_last_remote_stream_created_timestamp(&self) -> bool {
+        self.last_remote_stream_created_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_remote_stream_created_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_remote_stream_created_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_remote_stream_created_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_remote_stream_created_timestamp.is_none() {
+            self.last_remote_stream_created_timestamp.set_default();
+        }
+        self.last_remote_stream_created_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_remote_stream_created_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_remote_stream_created_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // .google.protobuf.Timestamp last_message_sent_timestamp = 9;
+
+
+    pub fn get_last_message_sent_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_message_sent_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_message_sent_timestamp(&mut self) {
+        self.last_message_sent_timestamp.clear();
+    }
+
+    pub fn has_last_message_sent_timestamp(&self) -> bool {
+        self.last_message_sent_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_message_sent_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_message_sent_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_message_sent_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_message_sent_timestamp.is_none() {
+            self.last_message_sent_timestamp.set_default();
+        }
+        self.last_message_sent_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_message_sent_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_message_sent_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // .google.protobuf.Timestamp last_message_received_timestamp = 10;
+
+
+    pub fn get_last_message_received_timestamp(&self) -> &::protobuf::well_known_types::Timestamp {
+        self.last_message_received_timestamp.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Timestamp as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_last_message_received_timestamp(&mut self) {
+        self.last_message_received_timestamp.clear();
+    }
+
+    pub fn has_last_message_received_timestamp(&self) -> bool {
+        self.last_message_received_timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_message_received_timestamp(&mut self, v: ::protobuf::well_known_types::Timestamp) {
+        self.last_message_received_timestamp = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_last_message_received_timestamp(&mut self) -> &mut ::protobuf::well_known_types::Timestamp {
+        if self.last_message_received_timestamp.is_none() {
+            self.last_message_received_timestamp.set_default();
+        }
+        self.last_message_received_timestamp.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_last_message_received_timestamp(&mut self) -> ::protobuf::well_known_types::Timestamp {
+        self.last_message_received_timestamp.take().unwrap_or_else(|| ::protobuf::well_known_types::Timestamp::new())
+    }
+
+    // .google.protobuf.Int64Value local_flow_control_window = 11;
+
+
+    pub fn get_local_flow_control_window(&self) -> &::protobuf::well_known_types::Int64Value {
+        self.local_flow_control_window.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Int64Value as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_local_flow_control_window(&mut self) {
+        self.local_flow_control_window.clear();
+    }
+
+    pub fn has_local_flow_control_window(&self) -> bool {
+        self.local_flow_control_window.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_local_flow_control_window(&mut self, v: ::protobuf::well_known_types::Int64Value) {
+        self.local_flow_control_window = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_local_flow_control_window(&mut self) -> &mut ::protobuf::well_known_types::Int64Value {
+        if self.local_flow_control_window.is_none() {
+            self.local_flow_control_window.set_default();
+        }
+        self.local_flow_control_window.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_local_flow_control_window(&mut self) -> ::protobuf::well_known_types::Int64Value {
+        self.local_flow_control_window.take().unwrap_or_else(|| ::protobuf::well_known_types::Int64Value::new())
+    }
+
+    // .google.protobuf.Int64Value remote_flow_control_window = 12;
+
+
+    pub fn get_remote_flow_control_window(&self) -> &::protobuf::well_known_types::Int64Value {
+        self.remote_flow_control_window.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Int64Value as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_remote_flow_control_window(&mut self) {
+        self.remote_flow_control_window.clear();
+    }
+
+    pub fn has_remote_flow_control_window(&self) -> bool {
+        self.remote_flow_control_window.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_remote_flow_control_window(&mut self, v: ::protobuf::well_known_types::Int64Value) {
+        self.remote_flow_control_window = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_remote_flow_control_window(&mut self) -> &mut ::protobuf::well_known_types::Int64Value {
+        if self.remote_flow_control_window.is_none() {
+            self.remote_flow_control_window.set_default();
+        }
+        self.remote_flow_control_window.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_remote_flow_control_window(&mut self) -> ::protobuf::well_known_types::Int64Value {
+        self.remote_flow_control_window.take().unwrap_or_else(|| ::protobuf::well_known_types::Int64Value::new())
+    }
+
+    // repeated .grpc.channelz.v1.SocketOption option = 13;
+
+
+    pub fn get_option(&self) -> &[SocketOption] {
+        &self.option
+    }
+    pub fn clear_option(&mut self) {
+        self.option.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_option(&mut self, v: ::protobuf::RepeatedField<SocketOption>) {
+        self.option = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_option(&mut self) -> &mut ::protobuf::RepeatedField<SocketOption> {
+        &mut self.option
+    }
+
+    // Take field
+    pub fn take_option(&mut self) -> ::protobuf::RepeatedField<SocketOption> {
+        ::std::mem::replace(&mut self.option, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for SocketData {
+    fn is_initialized(&self) -> bool {
+        for v in &self.last_local_stream_created_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.last_remote_stream_created_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.last_message_sent_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.last_message_received_timestamp {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.local_flow_control_window {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.remote_flow_control_window {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.option {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.streams_started = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.streams_succeeded = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.streams_failed = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.messages_sent = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.messages_received = tmp;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.keep_alives_sent = tmp;
+                },
+                7 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_local_stream_created_timestamp)?;
+                },
+                8 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_remote_stream_created_timestamp)?;
+                },
+                9 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_message_sent_timestamp)?;
+                },
+                10 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.last_message_received_timestamp)?;
+                },
+                11 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.local_flow_control_window)?;
+                },
+                12 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.remote_flow_control_window)?;
+                },
+                13 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.option)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.streams_started != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.streams_started, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.streams_succeeded != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.streams_succeeded, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.streams_failed != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.streams_failed, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.messages_sent != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.messages_sent, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.messages_received != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.messages_received, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.keep_alives_sent != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.keep_alives_sent, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.last_local_stream_created_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.last_remote_stream_created_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.last_message_sent_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.last_message_received_timestamp.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.local_flow_control_window.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        if let Some(ref v) = self.remote_flow_control_window.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.option {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.streams_started != 0 {
+            os.write_int64(1, self.streams_started)?;
+        }
+        if self.streams_succeeded != 0 {
+            os.write_int64(2, self.streams_succeeded)?;
+        }
+        if self.streams_failed != 0 {
+            os.write_int64(3, self.streams_failed)?;
+        }
+        if self.messages_sent != 0 {
+            os.write_int64(4, self.messages_sent)?;
+        }
+        if self.messages_received != 0 {
+            os.write_int64(5, self.messages_received)?;
+        }
+        if self.keep_alives_sent != 0 {
+            os.write_int64(6, self.keep_alives_sent)?;
+        }
+        if let Some(ref v) = self.last_local_stream_created_timestamp.as_ref() {
+            os.write_tag(7, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.last_remote_stream_created_timestamp.as_ref() {
+            os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.last_message_sent_timestamp.as_ref() {
+            os.write_tag(9, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.last_message_received_timestamp.as_ref() {
+            os.write_tag(10, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.local_flow_control_window.as_ref() {
+            os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        if let Some(ref v) = self.remote_flow_control_window.as_ref() {
+            os.write_tag(12, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.option {
+            os.write_tag(13, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketData {
+        SocketData::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "streams_started",
+                |m: &SocketData| { &m.streams_started },
+                |m: &mut SocketData| { &mut m.streams_started },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "streams_succeeded",
+                |m: &SocketData| { &m.streams_succeeded },
+                |m: &mut SocketData| { &mut m.streams_succeeded },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "streams_failed",
+                |m: &SocketData| { &m.streams_failed },
+                |m: &mut SocketData| { &mut m.streams_failed },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "messages_sent",
+                |m: &SocketData| { &m.messages_sent },
+                |m: &mut SocketData| { &mut m.messages_sent },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "messages_received",
+                |m: &SocketData| { &m.messages_received },
+                |m: &mut SocketData| { &mut m.messages_received },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "keep_alives_sent",
+                |m: &SocketData| { &m.keep_alives_sent },
+                |m: &mut SocketData| { &mut m.keep_alives_sent },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_local_stream_created_timestamp",
+                |m: &SocketData| { &m.last_local_stream_created_timestamp },
+                |m: &mut SocketData| { &mut m.last_local_stream_created_timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_remote_stream_created_timestamp",
+                |m: &SocketData| { &m.last_remote_stream_created_timestamp },
+                |m: &mut SocketData| { &mut m.last_remote_stream_created_timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_message_sent_timestamp",
+                |m: &SocketData| { &m.last_message_sent_timestamp },
+                |m: &mut SocketData| { &mut m.last_message_sent_timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Timestamp>>(
+                "last_message_received_timestamp",
+                |m: &SocketData| { &m.last_message_received_timestamp },
+                |m: &mut SocketData| { &mut m.last_message_received_timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Int64Value>>(
+                "local_flow_control_window",
+                |m: &SocketData| { &m.local_flow_control_window },
+                |m: &mut SocketData| { &mut m.local_flow_control_window },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Int64Value>>(
+                "remote_flow_control_window",
+                |m: &SocketData| { &m.remote_flow_control_window },
+                |m: &mut SocketData| { &mut m.remote_flow_control_window },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketOption>>(
+                "option",
+                |m: &SocketData| { &m.option },
+                |m: &mut SocketData| { &mut m.option },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketData>(
+                "SocketData",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketData {
+        static instance: ::protobuf::rt::LazyV2<SocketData> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketData::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketData {
+    fn clear(&mut self) {
+        self.streams_started = 0;
+        self.streams_succeeded = 0;
+        self.streams_failed = 0;
+        self.messages_sent = 0;
+        self.messages_received = 0;
+        self.keep_alives_sent = 0;
+        self.last_local_stream_created_timestamp.clear();
+        self.last_remote_stream_created_timestamp.clear();
+        self.last_message_sent_timestamp.clear();
+        self.last_message_received_timestamp.clear();
+        self.local_flow_control_window.clear();
+        self.remote_flow_control_window.clear();
+        self.option.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketData {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketData {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Address {
+    // message oneof groups
+    pub address: ::std::option::Option<Address_oneof_address>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Address {
+    fn default() -> &'a Address {
+        <Address as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum Address_oneof_address {
+    tcpip_address(Address_TcpIpAddress),
+    uds_address(Address_UdsAddress),
+    other_address(Address_OtherAddress),
+}
+
+impl Address {
+    pub fn new() -> Address {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Address.TcpIpAddress tcpip_address = 1;
+
+
+    pub fn get_tcpip_address(&self) -> &Address_TcpIpAddress {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::tcpip_address(ref v)) => v,
+            _ => <Address_TcpIpAddress as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_tcpip_address(&mut self) {
+        self.address = ::std::option::Option::None;
+    }
+
+    pub fn has_tcpip_address(&self) -> bool {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::tcpip_address(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpip_address(&mut self, v: Address_TcpIpAddress) {
+        self.address = ::std::option::Option::Some(Address_oneof_address::tcpip_address(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_tcpip_address(&mut self) -> &mut Address_TcpIpAddress {
+        if let ::std::option::Option::Some(Address_oneof_address::tcpip_address(_)) = self.address {
+        } else {
+            self.address = ::std::option::Option::Some(Address_oneof_address::tcpip_address(Address_TcpIpAddress::new()));
+        }
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::tcpip_address(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_tcpip_address(&mut self) -> Address_TcpIpAddress {
+        if self.has_tcpip_address() {
+            match self.address.take() {
+                ::std::option::Option::Some(Address_oneof_address::tcpip_address(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Address_TcpIpAddress::new()
+        }
+    }
+
+    // .grpc.channelz.v1.Address.UdsAddress uds_address = 2;
+
+
+    pub fn get_uds_address(&self) -> &Address_UdsAddress {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::uds_address(ref v)) => v,
+            _ => <Address_UdsAddress as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_uds_address(&mut self) {
+        self.address = ::std::option::Option::None;
+    }
+
+    pub fn has_uds_address(&self) -> bool {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::uds_address(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_uds_address(&mut self, v: Address_UdsAddress) {
+        self.address = ::std::option::Option::Some(Address_oneof_address::uds_address(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_uds_address(&mut self) -> &mut Address_UdsAddress {
+        if let ::std::option::Option::Some(Address_oneof_address::uds_address(_)) = self.address {
+        } else {
+            self.address = ::std::option::Option::Some(Address_oneof_address::uds_address(Address_UdsAddress::new()));
+        }
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::uds_address(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_uds_address(&mut self) -> Address_UdsAddress {
+        if self.has_uds_address() {
+            match self.address.take() {
+                ::std::option::Option::Some(Address_oneof_address::uds_address(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Address_UdsAddress::new()
+        }
+    }
+
+    // .grpc.channelz.v1.Address.OtherAddress other_address = 3;
+
+
+    pub fn get_other_address(&self) -> &Address_OtherAddress {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::other_address(ref v)) => v,
+            _ => <Address_OtherAddress as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_other_address(&mut self) {
+        self.address = ::std::option::Option::None;
+    }
+
+    pub fn has_other_address(&self) -> bool {
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::other_address(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_other_address(&mut self, v: Address_OtherAddress) {
+        self.address = ::std::option::Option::Some(Address_oneof_address::other_address(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_other_address(&mut self) -> &mut Address_OtherAddress {
+        if let ::std::option::Option::Some(Address_oneof_address::other_address(_)) = self.address {
+        } else {
+            self.address = ::std::option::Option::Some(Address_oneof_address::other_address(Address_OtherAddress::new()));
+        }
+        match self.address {
+            ::std::option::Option::Some(Address_oneof_address::other_address(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_other_address(&mut self) -> Address_OtherAddress {
+        if self.has_other_address() {
+            match self.address.take() {
+                ::std::option::Option::Some(Address_oneof_address::other_address(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Address_OtherAddress::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for Address {
+    fn is_initialized(&self) -> bool {
+        if let Some(Address_oneof_address::tcpip_address(ref v)) = self.address {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Address_oneof_address::uds_address(ref v)) = self.address {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Address_oneof_address::other_address(ref v)) = self.address {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.address = ::std::option::Option::Some(Address_oneof_address::tcpip_address(is.read_message()?));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.address = ::std::option::Option::Some(Address_oneof_address::uds_address(is.read_message()?));
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.address = ::std::option::Option::Some(Address_oneof_address::other_address(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let ::std::option::Option::Some(ref v) = self.address {
+            match v {
+                &Address_oneof_address::tcpip_address(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Address_oneof_address::uds_address(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Address_oneof_address::other_address(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let ::std::option::Option::Some(ref v) = self.address {
+            match v {
+                &Address_oneof_address::tcpip_address(ref v) => {
+                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Address_oneof_address::uds_address(ref v) => {
+                    os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Address_oneof_address::other_address(ref v) => {
+                    os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Address {
+        Address::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Address_TcpIpAddress>(
+                "tcpip_address",
+                Address::has_tcpip_address,
+                Address::get_tcpip_address,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Address_UdsAddress>(
+                "uds_address",
+                Address::has_uds_address,
+                Address::get_uds_address,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Address_OtherAddress>(
+                "other_address",
+                Address::has_other_address,
+                Address::get_other_address,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Address>(
+                "Address",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Address {
+        static instance: ::protobuf::rt::LazyV2<Address> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Address::new)
+    }
+}
+
+impl ::protobuf::Clear for Address {
+    fn clear(&mut self) {
+        self.address = ::std::option::Option::None;
+        self.address = ::std::option::Option::None;
+        self.address = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Address {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Address {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Address_TcpIpAddress {
+    // message fields
+    pub ip_address: ::std::vec::Vec<u8>,
+    pub port: i32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Address_TcpIpAddress {
+    fn default() -> &'a Address_TcpIpAddress {
+        <Address_TcpIpAddress as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Address_TcpIpAddress {
+    pub fn new() -> Address_TcpIpAddress {
+        ::std::default::Default::default()
+    }
+
+    // bytes ip_address = 1;
+
+
+    pub fn get_ip_address(&self) -> &[u8] {
+        &self.ip_address
+    }
+    pub fn clear_ip_address(&mut self) {
+        self.ip_address.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ip_address(&mut self, v: ::std::vec::Vec<u8>) {
+        self.ip_address = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_ip_address(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.ip_address
+    }
+
+    // Take field
+    pub fn take_ip_address(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.ip_address, ::std::vec::Vec::new())
+    }
+
+    // int32 port = 2;
+
+
+    pub fn get_port(&self) -> i32 {
+        self.port
+    }
+    pub fn clear_port(&mut self) {
+        self.port = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_port(&mut self, v: i32) {
+        self.port = v;
+    }
+}
+
+impl ::protobuf::Message for Address_TcpIpAddress {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.ip_address)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int32()?;
+                    self.port = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.ip_address.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.ip_address);
+        }
+        if self.port != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.port, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.ip_address.is_empty() {
+            os.write_bytes(1, &self.ip_address)?;
+        }
+        if self.port != 0 {
+            os.write_int32(2, self.port)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Address_TcpIpAddress {
+        Address_TcpIpAddress::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "ip_address",
+                |m: &Address_TcpIpAddress| { &m.ip_address },
+                |m: &mut Address_TcpIpAddress| { &mut m.ip_address },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "port",
+                |m: &Address_TcpIpAddress| { &m.port },
+                |m: &mut Address_TcpIpAddress| { &mut m.port },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Address_TcpIpAddress>(
+                "Address.TcpIpAddress",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Address_TcpIpAddress {
+        static instance: ::protobuf::rt::LazyV2<Address_TcpIpAddress> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Address_TcpIpAddress::new)
+    }
+}
+
+impl ::protobuf::Clear for Address_TcpIpAddress {
+    fn clear(&mut self) {
+        self.ip_address.clear();
+        self.port = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Address_TcpIpAddress {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Address_TcpIpAddress {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Address_UdsAddress {
+    // message fields
+    pub filename: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Address_UdsAddress {
+    fn default() -> &'a Address_UdsAddress {
+        <Address_UdsAddress as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Address_UdsAddress {
+    pub fn new() -> Address_UdsAddress {
+        ::std::default::Default::default()
+    }
+
+    // string filename = 1;
+
+
+    pub fn get_filename(&self) -> &str {
+        &self.filename
+    }
+    pub fn clear_filename(&mut self) {
+        self.filename.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_filename(&mut self, v: ::std::string::String) {
+        self.filename = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_filename(&mut self) -> &mut ::std::string::String {
+        &mut self.filename
+    }
+
+    // Take field
+    pub fn take_filename(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.filename, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for Address_UdsAddress {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.filename)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.filename.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.filename);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.filename.is_empty() {
+            os.write_string(1, &self.filename)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Address_UdsAddress {
+        Address_UdsAddress::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "filename",
+                |m: &Address_UdsAddress| { &m.filename },
+                |m: &mut Address_UdsAddress| { &mut m.filename },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Address_UdsAddress>(
+                "Address.UdsAddress",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Address_UdsAddress {
+        static instance: ::protobuf::rt::LazyV2<Address_UdsAddress> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Address_UdsAddress::new)
+    }
+}
+
+impl ::protobuf::Clear for Address_UdsAddress {
+    fn clear(&mut self) {
+        self.filename.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Address_UdsAddress {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Address_UdsAddress {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Address_OtherAddress {
+    // message fields
+    pub name: ::std::string::String,
+    pub value: ::protobuf::SingularPtrField<::protobuf::well_known_types::Any>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Address_OtherAddress {
+    fn default() -> &'a Address_OtherAddress {
+        <Address_OtherAddress as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Address_OtherAddress {
+    pub fn new() -> Address_OtherAddress {
+        ::std::default::Default::default()
+    }
+
+    // string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+
+    // .google.protobuf.Any value = 2;
+
+
+    pub fn get_value(&self) -> &::protobuf::well_known_types::Any {
+        self.value.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Any as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_value(&mut self) {
+        self.value.clear();
+    }
+
+    pub fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_value(&mut self, v: ::protobuf::well_known_types::Any) {
+        self.value = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_value(&mut self) -> &mut ::protobuf::well_known_types::Any {
+        if self.value.is_none() {
+            self.value.set_default();
+        }
+        self.value.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_value(&mut self) -> ::protobuf::well_known_types::Any {
+        self.value.take().unwrap_or_else(|| ::protobuf::well_known_types::Any::new())
+    }
+}
+
+impl ::protobuf::Message for Address_OtherAddress {
+    fn is_initialized(&self) -> bool {
+        for v in &self.value {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.value)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.name);
+        }
+        if let Some(ref v) = self.value.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.name.is_empty() {
+            os.write_string(1, &self.name)?;
+        }
+        if let Some(ref v) = self.value.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Address_OtherAddress {
+        Address_OtherAddress::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &Address_OtherAddress| { &m.name },
+                |m: &mut Address_OtherAddress| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Any>>(
+                "value",
+                |m: &Address_OtherAddress| { &m.value },
+                |m: &mut Address_OtherAddress| { &mut m.value },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Address_OtherAddress>(
+                "Address.OtherAddress",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Address_OtherAddress {
+        static instance: ::protobuf::rt::LazyV2<Address_OtherAddress> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Address_OtherAddress::new)
+    }
+}
+
+impl ::protobuf::Clear for Address_OtherAddress {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.value.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Address_OtherAddress {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Address_OtherAddress {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Security {
+    // message oneof groups
+    pub model: ::std::option::Option<Security_oneof_model>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Security {
+    fn default() -> &'a Security {
+        <Security as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum Security_oneof_model {
+    tls(Security_Tls),
+    other(Security_OtherSecurity),
+}
+
+impl Security {
+    pub fn new() -> Security {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Security.Tls tls = 1;
+
+
+    pub fn get_tls(&self) -> &Security_Tls {
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::tls(ref v)) => v,
+            _ => <Security_Tls as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_tls(&mut self) {
+        self.model = ::std::option::Option::None;
+    }
+
+    pub fn has_tls(&self) -> bool {
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::tls(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tls(&mut self, v: Security_Tls) {
+        self.model = ::std::option::Option::Some(Security_oneof_model::tls(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_tls(&mut self) -> &mut Security_Tls {
+        if let ::std::option::Option::Some(Security_oneof_model::tls(_)) = self.model {
+        } else {
+            self.model = ::std::option::Option::Some(Security_oneof_model::tls(Security_Tls::new()));
+        }
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::tls(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_tls(&mut self) -> Security_Tls {
+        if self.has_tls() {
+            match self.model.take() {
+                ::std::option::Option::Some(Security_oneof_model::tls(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Security_Tls::new()
+        }
+    }
+
+    // .grpc.channelz.v1.Security.OtherSecurity other = 2;
+
+
+    pub fn get_other(&self) -> &Security_OtherSecurity {
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::other(ref v)) => v,
+            _ => <Security_OtherSecurity as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_other(&mut self) {
+        self.model = ::std::option::Option::None;
+    }
+
+    pub fn has_other(&self) -> bool {
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::other(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_other(&mut self, v: Security_OtherSecurity) {
+        self.model = ::std::option::Option::Some(Security_oneof_model::other(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_other(&mut self) -> &mut Security_OtherSecurity {
+        if let ::std::option::Option::Some(Security_oneof_model::other(_)) = self.model {
+        } else {
+            self.model = ::std::option::Option::Some(Security_oneof_model::other(Security_OtherSecurity::new()));
+        }
+        match self.model {
+            ::std::option::Option::Some(Security_oneof_model::other(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_other(&mut self) -> Security_OtherSecurity {
+        if self.has_other() {
+            match self.model.take() {
+                ::std::option::Option::Some(Security_oneof_model::other(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Security_OtherSecurity::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for Security {
+    fn is_initialized(&self) -> bool {
+        if let Some(Security_oneof_model::tls(ref v)) = self.model {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Security_oneof_model::other(ref v)) = self.model {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.model = ::std::option::Option::Some(Security_oneof_model::tls(is.read_message()?));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.model = ::std::option::Option::Some(Security_oneof_model::other(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let ::std::option::Option::Some(ref v) = self.model {
+            match v {
+                &Security_oneof_model::tls(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Security_oneof_model::other(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let ::std::option::Option::Some(ref v) = self.model {
+            match v {
+                &Security_oneof_model::tls(ref v) => {
+                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Security_oneof_model::other(ref v) => {
+                    os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Security {
+        Security::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Security_Tls>(
+                "tls",
+                Security::has_tls,
+                Security::get_tls,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Security_OtherSecurity>(
+                "other",
+                Security::has_other,
+                Security::get_other,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Security>(
+                "Security",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Security {
+        static instance: ::protobuf::rt::LazyV2<Security> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Security::new)
+    }
+}
+
+impl ::protobuf::Clear for Security {
+    fn clear(&mut self) {
+        self.model = ::std::option::Option::None;
+        self.model = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Security {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Security {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Security_Tls {
+    // message fields
+    pub local_certificate: ::std::vec::Vec<u8>,
+    pub remote_certificate: ::std::vec::Vec<u8>,
+    // message oneof groups
+    pub cipher_suite: ::std::option::Option<Security_Tls_oneof_cipher_suite>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Security_Tls {
+    fn default() -> &'a Security_Tls {
+        <Security_Tls as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum Security_Tls_oneof_cipher_suite {
+    standard_name(::std::string::String),
+    other_name(::std::string::String),
+}
+
+impl Security_Tls {
+    pub fn new() -> Security_Tls {
+        ::std::default::Default::default()
+    }
+
+    // string standard_name = 1;
+
+
+    pub fn get_standard_name(&self) -> &str {
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(ref v)) => v,
+            _ => "",
+        }
+    }
+    pub fn clear_standard_name(&mut self) {
+        self.cipher_suite = ::std::option::Option::None;
+    }
+
+    pub fn has_standard_name(&self) -> bool {
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_standard_name(&mut self, v: ::std::string::String) {
+        self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_standard_name(&mut self) -> &mut ::std::string::String {
+        if let ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(_)) = self.cipher_suite {
+        } else {
+            self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(::std::string::String::new()));
+        }
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_standard_name(&mut self) -> ::std::string::String {
+        if self.has_standard_name() {
+            match self.cipher_suite.take() {
+                ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::string::String::new()
+        }
+    }
+
+    // string other_name = 2;
+
+
+    pub fn get_other_name(&self) -> &str {
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(ref v)) => v,
+            _ => "",
+        }
+    }
+    pub fn clear_other_name(&mut self) {
+        self.cipher_suite = ::std::option::Option::None;
+    }
+
+    pub fn has_other_name(&self) -> bool {
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_other_name(&mut self, v: ::std::string::String) {
+        self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_other_name(&mut self) -> &mut ::std::string::String {
+        if let ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(_)) = self.cipher_suite {
+        } else {
+            self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(::std::string::String::new()));
+        }
+        match self.cipher_suite {
+            ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_other_name(&mut self) -> ::std::string::String {
+        if self.has_other_name() {
+            match self.cipher_suite.take() {
+                ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::string::String::new()
+        }
+    }
+
+    // bytes local_certificate = 3;
+
+
+    pub fn get_local_certificate(&self) -> &[u8] {
+        &self.local_certificate
+    }
+    pub fn clear_local_certificate(&mut self) {
+        self.local_certificate.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_local_certificate(&mut self, v: ::std::vec::Vec<u8>) {
+        self.local_certificate = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_local_certificate(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.local_certificate
+    }
+
+    // Take field
+    pub fn take_local_certificate(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.local_certificate, ::std::vec::Vec::new())
+    }
+
+    // bytes remote_certificate = 4;
+
+
+    pub fn get_remote_certificate(&self) -> &[u8] {
+        &self.remote_certificate
+    }
+    pub fn clear_remote_certificate(&mut self) {
+        self.remote_certificate.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_remote_certificate(&mut self, v: ::std::vec::Vec<u8>) {
+        self.remote_certificate = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_remote_certificate(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.remote_certificate
+    }
+
+    // Take field
+    pub fn take_remote_certificate(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.remote_certificate, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for Security_Tls {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::standard_name(is.read_string()?));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.cipher_suite = ::std::option::Option::Some(Security_Tls_oneof_cipher_suite::other_name(is.read_string()?));
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.local_certificate)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.remote_certificate)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.local_certificate.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(3, &self.local_certificate);
+        }
+        if !self.remote_certificate.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(4, &self.remote_certificate);
+        }
+        if let ::std::option::Option::Some(ref v) = self.cipher_suite {
+            match v {
+                &Security_Tls_oneof_cipher_suite::standard_name(ref v) => {
+                    my_size += ::protobuf::rt::string_size(1, &v);
+                },
+                &Security_Tls_oneof_cipher_suite::other_name(ref v) => {
+                    my_size += ::protobuf::rt::string_size(2, &v);
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.local_certificate.is_empty() {
+            os.write_bytes(3, &self.local_certificate)?;
+        }
+        if !self.remote_certificate.is_empty() {
+            os.write_bytes(4, &self.remote_certificate)?;
+        }
+        if let ::std::option::Option::Some(ref v) = self.cipher_suite {
+            match v {
+                &Security_Tls_oneof_cipher_suite::standard_name(ref v) => {
+                    os.write_string(1, v)?;
+                },
+                &Security_Tls_oneof_cipher_suite::other_name(ref v) => {
+                    os.write_string(2, v)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Security_Tls {
+        Security_Tls::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_string_accessor::<_>(
+                "standard_name",
+                Security_Tls::has_standard_name,
+                Security_Tls::get_standard_name,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_string_accessor::<_>(
+                "other_name",
+                Security_Tls::has_other_name,
+                Security_Tls::get_other_name,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "local_certificate",
+                |m: &Security_Tls| { &m.local_certificate },
+                |m: &mut Security_Tls| { &mut m.local_certificate },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "remote_certificate",
+                |m: &Security_Tls| { &m.remote_certificate },
+                |m: &mut Security_Tls| { &mut m.remote_certificate },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Security_Tls>(
+                "Security.Tls",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Security_Tls {
+        static instance: ::protobuf::rt::LazyV2<Security_Tls> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Security_Tls::new)
+    }
+}
+
+impl ::protobuf::Clear for Security_Tls {
+    fn clear(&mut self) {
+        self.cipher_suite = ::std::option::Option::None;
+        self.cipher_suite = ::std::option::Option::None;
+        self.local_certificate.clear();
+        self.remote_certificate.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Security_Tls {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Security_Tls {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Security_OtherSecurity {
+    // message fields
+    pub name: ::std::string::String,
+    pub value: ::protobuf::SingularPtrField<::protobuf::well_known_types::Any>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Security_OtherSecurity {
+    fn default() -> &'a Security_OtherSecurity {
+        <Security_OtherSecurity as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Security_OtherSecurity {
+    pub fn new() -> Security_OtherSecurity {
+        ::std::default::Default::default()
+    }
+
+    // string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+
+    // .google.protobuf.Any value = 2;
+
+
+    pub fn get_value(&self) -> &::protobuf::well_known_types::Any {
+        self.value.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Any as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_value(&mut self) {
+        self.value.clear();
+    }
+
+    pub fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_value(&mut self, v: ::protobuf::well_known_types::Any) {
+        self.value = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_value(&mut self) -> &mut ::protobuf::well_known_types::Any {
+        if self.value.is_none() {
+            self.value.set_default();
+        }
+        self.value.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_value(&mut self) -> ::protobuf::well_known_types::Any {
+        self.value.take().unwrap_or_else(|| ::protobuf::well_known_types::Any::new())
+    }
+}
+
+impl ::protobuf::Message for Security_OtherSecurity {
+    fn is_initialized(&self) -> bool {
+        for v in &self.value {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.value)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.name);
+        }
+        if let Some(ref v) = self.value.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.name.is_empty() {
+            os.write_string(1, &self.name)?;
+        }
+        if let Some(ref v) = self.value.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Security_OtherSecurity {
+        Security_OtherSecurity::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &Security_OtherSecurity| { &m.name },
+                |m: &mut Security_OtherSecurity| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Any>>(
+                "value",
+                |m: &Security_OtherSecurity| { &m.value },
+                |m: &mut Security_OtherSecurity| { &mut m.value },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Security_OtherSecurity>(
+                "Security.OtherSecurity",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Security_OtherSecurity {
+        static instance: ::protobuf::rt::LazyV2<Security_OtherSecurity> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Security_OtherSecurity::new)
+    }
+}
+
+impl ::protobuf::Clear for Security_OtherSecurity {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.value.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Security_OtherSecurity {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Security_OtherSecurity {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketOption {
+    // message fields
+    pub name: ::std::string::String,
+    pub value: ::std::string::String,
+    pub additional: ::protobuf::SingularPtrField<::protobuf::well_known_types::Any>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketOption {
+    fn default() -> &'a SocketOption {
+        <SocketOption as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketOption {
+    pub fn new() -> SocketOption {
+        ::std::default::Default::default()
+    }
+
+    // string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+
+    // string value = 2;
+
+
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+    pub fn clear_value(&mut self) {
+        self.value.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_value(&mut self, v: ::std::string::String) {
+        self.value = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_value(&mut self) -> &mut ::std::string::String {
+        &mut self.value
+    }
+
+    // Take field
+    pub fn take_value(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.value, ::std::string::String::new())
+    }
+
+    // .google.protobuf.Any additional = 3;
+
+
+    pub fn get_additional(&self) -> &::protobuf::well_known_types::Any {
+        self.additional.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Any as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_additional(&mut self) {
+        self.additional.clear();
+    }
+
+    pub fn has_additional(&self) -> bool {
+        self.additional.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_additional(&mut self, v: ::protobuf::well_known_types::Any) {
+        self.additional = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_additional(&mut self) -> &mut ::protobuf::well_known_types::Any {
+        if self.additional.is_none() {
+            self.additional.set_default();
+        }
+        self.additional.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_additional(&mut self) -> ::protobuf::well_known_types::Any {
+        self.additional.take().unwrap_or_else(|| ::protobuf::well_known_types::Any::new())
+    }
+}
+
+impl ::protobuf::Message for SocketOption {
+    fn is_initialized(&self) -> bool {
+        for v in &self.additional {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.value)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.additional)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.name);
+        }
+        if !self.value.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.value);
+        }
+        if let Some(ref v) = self.additional.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.name.is_empty() {
+            os.write_string(1, &self.name)?;
+        }
+        if !self.value.is_empty() {
+            os.write_string(2, &self.value)?;
+        }
+        if let Some(ref v) = self.additional.as_ref() {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketOption {
+        SocketOption::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &SocketOption| { &m.name },
+                |m: &mut SocketOption| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "value",
+                |m: &SocketOption| { &m.value },
+                |m: &mut SocketOption| { &mut m.value },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Any>>(
+                "additional",
+                |m: &SocketOption| { &m.additional },
+                |m: &mut SocketOption| { &mut m.additional },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketOption>(
+                "SocketOption",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketOption {
+        static instance: ::protobuf::rt::LazyV2<SocketOption> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketOption::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketOption {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.value.clear();
+        self.additional.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketOption {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketOption {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketOptionTimeout {
+    // message fields
+    pub duration: ::protobuf::SingularPtrField<::protobuf::well_known_types::Duration>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketOptionTimeout {
+    fn default() -> &'a SocketOptionTimeout {
+        <SocketOptionTimeout as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketOptionTimeout {
+    pub fn new() -> SocketOptionTimeout {
+        ::std::default::Default::default()
+    }
+
+    // .google.protobuf.Duration duration = 1;
+
+
+    pub fn get_duration(&self) -> &::protobuf::well_known_types::Duration {
+        self.duration.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Duration as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_duration(&mut self) {
+        self.duration.clear();
+    }
+
+    pub fn has_duration(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_duration(&mut self, v: ::protobuf::well_known_types::Duration) {
+        self.duration = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_duration(&mut self) -> &mut ::protobuf::well_known_types::Duration {
+        if self.duration.is_none() {
+            self.duration.set_default();
+        }
+        self.duration.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_duration(&mut self) -> ::protobuf::well_known_types::Duration {
+        self.duration.take().unwrap_or_else(|| ::protobuf::well_known_types::Duration::new())
+    }
+}
+
+impl ::protobuf::Message for SocketOptionTimeout {
+    fn is_initialized(&self) -> bool {
+        for v in &self.duration {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.duration)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.duration.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.duration.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketOptionTimeout {
+        SocketOptionTimeout::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Duration>>(
+                "duration",
+                |m: &SocketOptionTimeout| { &m.duration },
+                |m: &mut SocketOptionTimeout| { &mut m.duration },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketOptionTimeout>(
+                "SocketOptionTimeout",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketOptionTimeout {
+        static instance: ::protobuf::rt::LazyV2<SocketOptionTimeout> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketOptionTimeout::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketOptionTimeout {
+    fn clear(&mut self) {
+        self.duration.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketOptionTimeout {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketOptionTimeout {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketOptionLinger {
+    // message fields
+    pub active: bool,
+    pub duration: ::protobuf::SingularPtrField<::protobuf::well_known_types::Duration>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketOptionLinger {
+    fn default() -> &'a SocketOptionLinger {
+        <SocketOptionLinger as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketOptionLinger {
+    pub fn new() -> SocketOptionLinger {
+        ::std::default::Default::default()
+    }
+
+    // bool active = 1;
+
+
+    pub fn get_active(&self) -> bool {
+        self.active
+    }
+    pub fn clear_active(&mut self) {
+        self.active = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_active(&mut self, v: bool) {
+        self.active = v;
+    }
+
+    // .google.protobuf.Duration duration = 2;
+
+
+    pub fn get_duration(&self) -> &::protobuf::well_known_types::Duration {
+        self.duration.as_ref().unwrap_or_else(|| <::protobuf::well_known_types::Duration as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_duration(&mut self) {
+        self.duration.clear();
+    }
+
+    pub fn has_duration(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_duration(&mut self, v: ::protobuf::well_known_types::Duration) {
+        self.duration = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_duration(&mut self) -> &mut ::protobuf::well_known_types::Duration {
+        if self.duration.is_none() {
+            self.duration.set_default();
+        }
+        self.duration.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_duration(&mut self) -> ::protobuf::well_known_types::Duration {
+        self.duration.take().unwrap_or_else(|| ::protobuf::well_known_types::Duration::new())
+    }
+}
+
+impl ::protobuf::Message for SocketOptionLinger {
+    fn is_initialized(&self) -> bool {
+        for v in &self.duration {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.active = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.duration)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.active != false {
+            my_size += 2;
+        }
+        if let Some(ref v) = self.duration.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.active != false {
+            os.write_bool(1, self.active)?;
+        }
+        if let Some(ref v) = self.duration.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketOptionLinger {
+        SocketOptionLinger::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "active",
+                |m: &SocketOptionLinger| { &m.active },
+                |m: &mut SocketOptionLinger| { &mut m.active },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<::protobuf::well_known_types::Duration>>(
+                "duration",
+                |m: &SocketOptionLinger| { &m.duration },
+                |m: &mut SocketOptionLinger| { &mut m.duration },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketOptionLinger>(
+                "SocketOptionLinger",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketOptionLinger {
+        static instance: ::protobuf::rt::LazyV2<SocketOptionLinger> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketOptionLinger::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketOptionLinger {
+    fn clear(&mut self) {
+        self.active = false;
+        self.duration.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketOptionLinger {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketOptionLinger {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct SocketOptionTcpInfo {
+    // message fields
+    pub tcpi_state: u32,
+    pub tcpi_ca_state: u32,
+    pub tcpi_retransmits: u32,
+    pub tcpi_probes: u32,
+    pub tcpi_backoff: u32,
+    pub tcpi_options: u32,
+    pub tcpi_snd_wscale: u32,
+    pub tcpi_rcv_wscale: u32,
+    pub tcpi_rto: u32,
+    pub tcpi_ato: u32,
+    pub tcpi_snd_mss: u32,
+    pub tcpi_rcv_mss: u32,
+    pub tcpi_unacked: u32,
+    pub tcpi_sacked: u32,
+    pub tcpi_lost: u32,
+    pub tcpi_retrans: u32,
+    pub tcpi_fackets: u32,
+    pub tcpi_last_data_sent: u32,
+    pub tcpi_last_ack_sent: u32,
+    pub tcpi_last_data_recv: u32,
+    pub tcpi_last_ack_recv: u32,
+    pub tcpi_pmtu: u32,
+    pub tcpi_rcv_ssthresh: u32,
+    pub tcpi_rtt: u32,
+    pub tcpi_rttvar: u32,
+    pub tcpi_snd_ssthresh: u32,
+    pub tcpi_snd_cwnd: u32,
+    pub tcpi_advmss: u32,
+    pub tcpi_reordering: u32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a SocketOptionTcpInfo {
+    fn default() -> &'a SocketOptionTcpInfo {
+        <SocketOptionTcpInfo as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl SocketOptionTcpInfo {
+    pub fn new() -> SocketOptionTcpInfo {
+        ::std::default::Default::default()
+    }
+
+    // uint32 tcpi_state = 1;
+
+
+    pub fn get_tcpi_state(&self) -> u32 {
+        self.tcpi_state
+    }
+    pub fn clear_tcpi_state(&mut self) {
+        self.tcpi_state = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_state(&mut self, v: u32) {
+        self.tcpi_state = v;
+    }
+
+    // uint32 tcpi_ca_state = 2;
+
+
+    pub fn get_tcpi_ca_state(&self) -> u32 {
+        self.tcpi_ca_state
+    }
+    pub fn clear_tcpi_ca_state(&mut self) {
+        self.tcpi_ca_state = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_ca_state(&mut self, v: u32) {
+        self.tcpi_ca_state = v;
+    }
+
+    // uint32 tcpi_retransmits = 3;
+
+
+    pub fn get_tcpi_retransmits(&self) -> u32 {
+        self.tcpi_retransmits
+    }
+    pub fn clear_tcpi_retransmits(&mut self) {
+        self.tcpi_retransmits = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_retransmits(&mut self, v: u32) {
+        self.tcpi_retransmits = v;
+    }
+
+    // uint32 tcpi_probes = 4;
+
+
+    pub fn get_tcpi_probes(&self) -> u32 {
+        self.tcpi_probes
+    }
+    pub fn clear_tcpi_probes(&mut self) {
+        self.tcpi_probes = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_probes(&mut self, v: u32) {
+        self.tcpi_probes = v;
+    }
+
+    // uint32 tcpi_backoff = 5;
+
+
+    pub fn get_tcpi_backoff(&self) -> u32 {
+        self.tcpi_backoff
+    }
+    pub fn clear_tcpi_backoff(&mut self) {
+        self.tcpi_backoff = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_backoff(&mut self, v: u32) {
+        self.tcpi_backoff = v;
+    }
+
+    // uint32 tcpi_options = 6;
+
+
+    pub fn get_tcpi_options(&self) -> u32 {
+        self.tcpi_options
+    }
+    pub fn clear_tcpi_options(&mut self) {
+        self.tcpi_options = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_options(&mut self, v: u32) {
+        self.tcpi_options = v;
+    }
+
+    // uint32 tcpi_snd_wscale = 7;
+
+
+    pub fn get_tcpi_snd_wscale(&self) -> u32 {
+        self.tcpi_snd_wscale
+    }
+    pub fn clear_tcpi_snd_wscale(&mut self) {
+        self.tcpi_snd_wscale = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_snd_wscale(&mut self, v: u32) {
+        self.tcpi_snd_wscale = v;
+    }
+
+    // uint32 tcpi_rcv_wscale = 8;
+
+
+    pub fn get_tcpi_rcv_wscale(&self) -> u32 {
+        self.tcpi_rcv_wscale
+    }
+    pub fn clear_tcpi_rcv_wscale(&mut self) {
+        self.tcpi_rcv_wscale = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rcv_wscale(&mut self, v: u32) {
+        self.tcpi_rcv_wscale = v;
+    }
+
+    // uint32 tcpi_rto = 9;
+
+
+    pub fn get_tcpi_rto(&self) -> u32 {
+        self.tcpi_rto
+    }
+    pub fn clear_tcpi_rto(&mut self) {
+        self.tcpi_rto = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rto(&mut self, v: u32) {
+        self.tcpi_rto = v;
+    }
+
+    // uint32 tcpi_ato = 10;
+
+
+    pub fn get_tcpi_ato(&self) -> u32 {
+        self.tcpi_ato
+    }
+    pub fn clear_tcpi_ato(&mut self) {
+        self.tcpi_ato = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_ato(&mut self, v: u32) {
+        self.tcpi_ato = v;
+    }
+
+    // uint32 tcpi_snd_mss = 11;
+
+
+    pub fn get_tcpi_snd_mss(&self) -> u32 {
+        self.tcpi_snd_mss
+    }
+    pub fn clear_tcpi_snd_mss(&mut self) {
+        self.tcpi_snd_mss = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_snd_mss(&mut self, v: u32) {
+        self.tcpi_snd_mss = v;
+    }
+
+    // uint32 tcpi_rcv_mss = 12;
+
+
+    pub fn get_tcpi_rcv_mss(&self) -> u32 {
+        self.tcpi_rcv_mss
+    }
+    pub fn clear_tcpi_rcv_mss(&mut self) {
+        self.tcpi_rcv_mss = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rcv_mss(&mut self, v: u32) {
+        self.tcpi_rcv_mss = v;
+    }
+
+    // uint32 tcpi_unacked = 13;
+
+
+    pub fn get_tcpi_unacked(&self) -> u32 {
+        self.tcpi_unacked
+    }
+    pub fn clear_tcpi_unacked(&mut self) {
+        self.tcpi_unacked = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_unacked(&mut self, v: u32) {
+        self.tcpi_unacked = v;
+    }
+
+    // uint32 tcpi_sacked = 14;
+
+
+    pub fn get_tcpi_sacked(&self) -> u32 {
+        self.tcpi_sacked
+    }
+    pub fn clear_tcpi_sacked(&mut self) {
+        self.tcpi_sacked = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_sacked(&mut self, v: u32) {
+        self.tcpi_sacked = v;
+    }
+
+    // uint32 tcpi_lost = 15;
+
+
+    pub fn get_tcpi_lost(&self) -> u32 {
+        self.tcpi_lost
+    }
+    pub fn clear_tcpi_lost(&mut self) {
+        self.tcpi_lost = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_lost(&mut self, v: u32) {
+        self.tcpi_lost = v;
+    }
+
+    // uint32 tcpi_retrans = 16;
+
+
+    pub fn get_tcpi_retrans(&self) -> u32 {
+        self.tcpi_retrans
+    }
+    pub fn clear_tcpi_retrans(&mut self) {
+        self.tcpi_retrans = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_retrans(&mut self, v: u32) {
+        self.tcpi_retrans = v;
+    }
+
+    // uint32 tcpi_fackets = 17;
+
+
+    pub fn get_tcpi_fackets(&self) -> u32 {
+        self.tcpi_fackets
+    }
+    pub fn clear_tcpi_fackets(&mut self) {
+        self.tcpi_fackets = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_fackets(&mut self, v: u32) {
+        self.tcpi_fackets = v;
+    }
+
+    // uint32 tcpi_last_data_sent = 18;
+
+
+    pub fn get_tcpi_last_data_sent(&self) -> u32 {
+        self.tcpi_last_data_sent
+    }
+    pub fn clear_tcpi_last_data_sent(&mut self) {
+        self.tcpi_last_data_sent = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_last_data_sent(&mut self, v: u32) {
+        self.tcpi_last_data_sent = v;
+    }
+
+    // uint32 tcpi_last_ack_sent = 19;
+
+
+    pub fn get_tcpi_last_ack_sent(&self) -> u32 {
+        self.tcpi_last_ack_sent
+    }
+    pub fn clear_tcpi_last_ack_sent(&mut self) {
+        self.tcpi_last_ack_sent = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_last_ack_sent(&mut self, v: u32) {
+        self.tcpi_last_ack_sent = v;
+    }
+
+    // uint32 tcpi_last_data_recv = 20;
+
+
+    pub fn get_tcpi_last_data_recv(&self) -> u32 {
+        self.tcpi_last_data_recv
+    }
+    pub fn clear_tcpi_last_data_recv(&mut self) {
+        self.tcpi_last_data_recv = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_last_data_recv(&mut self, v: u32) {
+        self.tcpi_last_data_recv = v;
+    }
+
+    // uint32 tcpi_last_ack_recv = 21;
+
+
+    pub fn get_tcpi_last_ack_recv(&self) -> u32 {
+        self.tcpi_last_ack_recv
+    }
+    pub fn clear_tcpi_last_ack_recv(&mut self) {
+        self.tcpi_last_ack_recv = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_last_ack_recv(&mut self, v: u32) {
+        self.tcpi_last_ack_recv = v;
+    }
+
+    // uint32 tcpi_pmtu = 22;
+
+
+    pub fn get_tcpi_pmtu(&self) -> u32 {
+        self.tcpi_pmtu
+    }
+    pub fn clear_tcpi_pmtu(&mut self) {
+        self.tcpi_pmtu = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_pmtu(&mut self, v: u32) {
+        self.tcpi_pmtu = v;
+    }
+
+    // uint32 tcpi_rcv_ssthresh = 23;
+
+
+    pub fn get_tcpi_rcv_ssthresh(&self) -> u32 {
+        self.tcpi_rcv_ssthresh
+    }
+    pub fn clear_tcpi_rcv_ssthresh(&mut self) {
+        self.tcpi_rcv_ssthresh = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rcv_ssthresh(&mut self, v: u32) {
+        self.tcpi_rcv_ssthresh = v;
+    }
+
+    // uint32 tcpi_rtt = 24;
+
+
+    pub fn get_tcpi_rtt(&self) -> u32 {
+        self.tcpi_rtt
+    }
+    pub fn clear_tcpi_rtt(&mut self) {
+        self.tcpi_rtt = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rtt(&mut self, v: u32) {
+        self.tcpi_rtt = v;
+    }
+
+    // uint32 tcpi_rttvar = 25;
+
+
+    pub fn get_tcpi_rttvar(&self) -> u32 {
+        self.tcpi_rttvar
+    }
+    pub fn clear_tcpi_rttvar(&mut self) {
+        self.tcpi_rttvar = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_rttvar(&mut self, v: u32) {
+        self.tcpi_rttvar = v;
+    }
+
+    // uint32 tcpi_snd_ssthresh = 26;
+
+
+    pub fn get_tcpi_snd_ssthresh(&self) -> u32 {
+        self.tcpi_snd_ssthresh
+    }
+    pub fn clear_tcpi_snd_ssthresh(&mut self) {
+        self.tcpi_snd_ssthresh = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_snd_ssthresh(&mut self, v: u32) {
+        self.tcpi_snd_ssthresh = v;
+    }
+
+    // uint32 tcpi_snd_cwnd = 27;
+
+
+    pub fn get_tcpi_snd_cwnd(&self) -> u32 {
+        self.tcpi_snd_cwnd
+    }
+    pub fn clear_tcpi_snd_cwnd(&mut self) {
+        self.tcpi_snd_cwnd = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_snd_cwnd(&mut self, v: u32) {
+        self.tcpi_snd_cwnd = v;
+    }
+
+    // uint32 tcpi_advmss = 28;
+
+
+    pub fn get_tcpi_advmss(&self) -> u32 {
+        self.tcpi_advmss
+    }
+    pub fn clear_tcpi_advmss(&mut self) {
+        self.tcpi_advmss = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_advmss(&mut self, v: u32) {
+        self.tcpi_advmss = v;
+    }
+
+    // uint32 tcpi_reordering = 29;
+
+
+    pub fn get_tcpi_reordering(&self) -> u32 {
+        self.tcpi_reordering
+    }
+    pub fn clear_tcpi_reordering(&mut self) {
+        self.tcpi_reordering = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_tcpi_reordering(&mut self, v: u32) {
+        self.tcpi_reordering = v;
+    }
+}
+
+impl ::protobuf::Message for SocketOptionTcpInfo {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_state = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_ca_state = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_retransmits = tmp;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_probes = tmp;
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_backoff = tmp;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_options = tmp;
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_snd_wscale = tmp;
+                },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rcv_wscale = tmp;
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rto = tmp;
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_ato = tmp;
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_snd_mss = tmp;
+                },
+                12 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rcv_mss = tmp;
+                },
+                13 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_unacked = tmp;
+                },
+                14 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_sacked = tmp;
+                },
+                15 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_lost = tmp;
+                },
+                16 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_retrans = tmp;
+                },
+                17 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_fackets = tmp;
+                },
+                18 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_last_data_sent = tmp;
+                },
+                19 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_last_ack_sent = tmp;
+                },
+                20 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_last_data_recv = tmp;
+                },
+                21 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_last_ack_recv = tmp;
+                },
+                22 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_pmtu = tmp;
+                },
+                23 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rcv_ssthresh = tmp;
+                },
+                24 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rtt = tmp;
+                },
+                25 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_rttvar = tmp;
+                },
+                26 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_snd_ssthresh = tmp;
+                },
+                27 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_snd_cwnd = tmp;
+                },
+                28 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_advmss = tmp;
+                },
+                29 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tcpi_reordering = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.tcpi_state != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.tcpi_state, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_ca_state != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.tcpi_ca_state, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_retransmits != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.tcpi_retransmits, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_probes != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.tcpi_probes, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_backoff != 0 {
+            my_size += ::protobuf::rt::value_size(5, self.tcpi_backoff, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_options != 0 {
+            my_size += ::protobuf::rt::value_size(6, self.tcpi_options, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_snd_wscale != 0 {
+            my_size += ::protobuf::rt::value_size(7, self.tcpi_snd_wscale, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rcv_wscale != 0 {
+            my_size += ::protobuf::rt::value_size(8, self.tcpi_rcv_wscale, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rto != 0 {
+            my_size += ::protobuf::rt::value_size(9, self.tcpi_rto, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_ato != 0 {
+            my_size += ::protobuf::rt::value_size(10, self.tcpi_ato, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_snd_mss != 0 {
+            my_size += ::protobuf::rt::value_size(11, self.tcpi_snd_mss, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rcv_mss != 0 {
+            my_size += ::protobuf::rt::value_size(12, self.tcpi_rcv_mss, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_unacked != 0 {
+            my_size += ::protobuf::rt::value_size(13, self.tcpi_unacked, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_sacked != 0 {
+            my_size += ::protobuf::rt::value_size(14, self.tcpi_sacked, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_lost != 0 {
+            my_size += ::protobuf::rt::value_size(15, self.tcpi_lost, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_retrans != 0 {
+            my_size += ::protobuf::rt::value_size(16, self.tcpi_retrans, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_fackets != 0 {
+            my_size += ::protobuf::rt::value_size(17, self.tcpi_fackets, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_last_data_sent != 0 {
+            my_size += ::protobuf::rt::value_size(18, self.tcpi_last_data_sent, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_last_ack_sent != 0 {
+            my_size += ::protobuf::rt::value_size(19, self.tcpi_last_ack_sent, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_last_data_recv != 0 {
+            my_size += ::protobuf::rt::value_size(20, self.tcpi_last_data_recv, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_last_ack_recv != 0 {
+            my_size += ::protobuf::rt::value_size(21, self.tcpi_last_ack_recv, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_pmtu != 0 {
+            my_size += ::protobuf::rt::value_size(22, self.tcpi_pmtu, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rcv_ssthresh != 0 {
+            my_size += ::protobuf::rt::value_size(23, self.tcpi_rcv_ssthresh, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rtt != 0 {
+            my_size += ::protobuf::rt::value_size(24, self.tcpi_rtt, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_rttvar != 0 {
+            my_size += ::protobuf::rt::value_size(25, self.tcpi_rttvar, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_snd_ssthresh != 0 {
+            my_size += ::protobuf::rt::value_size(26, self.tcpi_snd_ssthresh, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_snd_cwnd != 0 {
+            my_size += ::protobuf::rt::value_size(27, self.tcpi_snd_cwnd, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_advmss != 0 {
+            my_size += ::protobuf::rt::value_size(28, self.tcpi_advmss, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.tcpi_reordering != 0 {
+            my_size += ::protobuf::rt::value_size(29, self.tcpi_reordering, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.tcpi_state != 0 {
+            os.write_uint32(1, self.tcpi_state)?;
+        }
+        if self.tcpi_ca_state != 0 {
+            os.write_uint32(2, self.tcpi_ca_state)?;
+        }
+        if self.tcpi_retransmits != 0 {
+            os.write_uint32(3, self.tcpi_retransmits)?;
+        }
+        if self.tcpi_probes != 0 {
+            os.write_uint32(4, self.tcpi_probes)?;
+        }
+        if self.tcpi_backoff != 0 {
+            os.write_uint32(5, self.tcpi_backoff)?;
+        }
+        if self.tcpi_options != 0 {
+            os.write_uint32(6, self.tcpi_options)?;
+        }
+        if self.tcpi_snd_wscale != 0 {
+            os.write_uint32(7, self.tcpi_snd_wscale)?;
+        }
+        if self.tcpi_rcv_wscale != 0 {
+            os.write_uint32(8, self.tcpi_rcv_wscale)?;
+        }
+        if self.tcpi_rto != 0 {
+            os.write_uint32(9, self.tcpi_rto)?;
+        }
+        if self.tcpi_ato != 0 {
+            os.write_uint32(10, self.tcpi_ato)?;
+        }
+        if self.tcpi_snd_mss != 0 {
+            os.write_uint32(11, self.tcpi_snd_mss)?;
+        }
+        if self.tcpi_rcv_mss != 0 {
+            os.write_uint32(12, self.tcpi_rcv_mss)?;
+        }
+        if self.tcpi_unacked != 0 {
+            os.write_uint32(13, self.tcpi_unacked)?;
+        }
+        if self.tcpi_sacked != 0 {
+            os.write_uint32(14, self.tcpi_sacked)?;
+        }
+        if self.tcpi_lost != 0 {
+            os.write_uint32(15, self.tcpi_lost)?;
+        }
+        if self.tcpi_retrans != 0 {
+            os.write_uint32(16, self.tcpi_retrans)?;
+        }
+        if self.tcpi_fackets != 0 {
+            os.write_uint32(17, self.tcpi_fackets)?;
+        }
+        if self.tcpi_last_data_sent != 0 {
+            os.write_uint32(18, self.tcpi_last_data_sent)?;
+        }
+        if self.tcpi_last_ack_sent != 0 {
+            os.write_uint32(19, self.tcpi_last_ack_sent)?;
+        }
+        if self.tcpi_last_data_recv != 0 {
+            os.write_uint32(20, self.tcpi_last_data_recv)?;
+        }
+        if self.tcpi_last_ack_recv != 0 {
+            os.write_uint32(21, self.tcpi_last_ack_recv)?;
+        }
+        if self.tcpi_pmtu != 0 {
+            os.write_uint32(22, self.tcpi_pmtu)?;
+        }
+        if self.tcpi_rcv_ssthresh != 0 {
+            os.write_uint32(23, self.tcpi_rcv_ssthresh)?;
+        }
+        if self.tcpi_rtt != 0 {
+            os.write_uint32(24, self.tcpi_rtt)?;
+        }
+        if self.tcpi_rttvar != 0 {
+            os.write_uint32(25, self.tcpi_rttvar)?;
+        }
+        if self.tcpi_snd_ssthresh != 0 {
+            os.write_uint32(26, self.tcpi_snd_ssthresh)?;
+        }
+        if self.tcpi_snd_cwnd != 0 {
+            os.write_uint32(27, self.tcpi_snd_cwnd)?;
+        }
+        if self.tcpi_advmss != 0 {
+            os.write_uint32(28, self.tcpi_advmss)?;
+        }
+        if self.tcpi_reordering != 0 {
+            os.write_uint32(29, self.tcpi_reordering)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> SocketOptionTcpInfo {
+        SocketOptionTcpInfo::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_state",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_state },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_state },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_ca_state",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_ca_state },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_ca_state },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_retransmits",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_retransmits },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_retransmits },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_probes",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_probes },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_probes },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_backoff",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_backoff },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_backoff },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_options",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_options },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_options },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_snd_wscale",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_snd_wscale },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_snd_wscale },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rcv_wscale",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rcv_wscale },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rcv_wscale },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rto",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rto },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rto },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_ato",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_ato },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_ato },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_snd_mss",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_snd_mss },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_snd_mss },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rcv_mss",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rcv_mss },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rcv_mss },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_unacked",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_unacked },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_unacked },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_sacked",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_sacked },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_sacked },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_lost",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_lost },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_lost },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_retrans",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_retrans },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_retrans },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_fackets",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_fackets },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_fackets },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_last_data_sent",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_last_data_sent },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_last_data_sent },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_last_ack_sent",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_last_ack_sent },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_last_ack_sent },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_last_data_recv",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_last_data_recv },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_last_data_recv },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_last_ack_recv",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_last_ack_recv },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_last_ack_recv },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_pmtu",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_pmtu },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_pmtu },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rcv_ssthresh",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rcv_ssthresh },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rcv_ssthresh },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rtt",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rtt },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rtt },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_rttvar",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_rttvar },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_rttvar },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_snd_ssthresh",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_snd_ssthresh },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_snd_ssthresh },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_snd_cwnd",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_snd_cwnd },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_snd_cwnd },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_advmss",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_advmss },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_advmss },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tcpi_reordering",
+                |m: &SocketOptionTcpInfo| { &m.tcpi_reordering },
+                |m: &mut SocketOptionTcpInfo| { &mut m.tcpi_reordering },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<SocketOptionTcpInfo>(
+                "SocketOptionTcpInfo",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static SocketOptionTcpInfo {
+        static instance: ::protobuf::rt::LazyV2<SocketOptionTcpInfo> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(SocketOptionTcpInfo::new)
+    }
+}
+
+impl ::protobuf::Clear for SocketOptionTcpInfo {
+    fn clear(&mut self) {
+        self.tcpi_state = 0;
+        self.tcpi_ca_state = 0;
+        self.tcpi_retransmits = 0;
+        self.tcpi_probes = 0;
+        self.tcpi_backoff = 0;
+        self.tcpi_options = 0;
+        self.tcpi_snd_wscale = 0;
+        self.tcpi_rcv_wscale = 0;
+        self.tcpi_rto = 0;
+        self.tcpi_ato = 0;
+        self.tcpi_snd_mss = 0;
+        self.tcpi_rcv_mss = 0;
+        self.tcpi_unacked = 0;
+        self.tcpi_sacked = 0;
+        self.tcpi_lost = 0;
+        self.tcpi_retrans = 0;
+        self.tcpi_fackets = 0;
+        self.tcpi_last_data_sent = 0;
+        self.tcpi_last_ack_sent = 0;
+        self.tcpi_last_data_recv = 0;
+        self.tcpi_last_ack_recv = 0;
+        self.tcpi_pmtu = 0;
+        self.tcpi_rcv_ssthresh = 0;
+        self.tcpi_rtt = 0;
+        self.tcpi_rttvar = 0;
+        self.tcpi_snd_ssthresh = 0;
+        self.tcpi_snd_cwnd = 0;
+        self.tcpi_advmss = 0;
+        self.tcpi_reordering = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for SocketOptionTcpInfo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for SocketOptionTcpInfo {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetTopChannelsRequest {
+    // message fields
+    pub start_channel_id: i64,
+    pub max_results: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetTopChannelsRequest {
+    fn default() -> &'a GetTopChannelsRequest {
+        <GetTopChannelsRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetTopChannelsRequest {
+    pub fn new() -> GetTopChannelsRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 start_channel_id = 1;
+
+
+    pub fn get_start_channel_id(&self) -> i64 {
+        self.start_channel_id
+    }
+    pub fn clear_start_channel_id(&mut self) {
+        self.start_channel_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_start_channel_id(&mut self, v: i64) {
+        self.start_channel_id = v;
+    }
+
+    // int64 max_results = 2;
+
+
+    pub fn get_max_results(&self) -> i64 {
+        self.max_results
+    }
+    pub fn clear_max_results(&mut self) {
+        self.max_results = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_results(&mut self, v: i64) {
+        self.max_results = v;
+    }
+}
+
+impl ::protobuf::Message for GetTopChannelsRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.start_channel_id = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.max_results = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.start_channel_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.start_channel_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.max_results != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.max_results, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.start_channel_id != 0 {
+            os.write_int64(1, self.start_channel_id)?;
+        }
+        if self.max_results != 0 {
+            os.write_int64(2, self.max_results)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetTopChannelsRequest {
+        GetTopChannelsRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "start_channel_id",
+                |m: &GetTopChannelsRequest| { &m.start_channel_id },
+                |m: &mut GetTopChannelsRequest| { &mut m.start_channel_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "max_results",
+                |m: &GetTopChannelsRequest| { &m.max_results },
+                |m: &mut GetTopChannelsRequest| { &mut m.max_results },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetTopChannelsRequest>(
+                "GetTopChannelsRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetTopChannelsRequest {
+        static instance: ::protobuf::rt::LazyV2<GetTopChannelsRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetTopChannelsRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetTopChannelsRequest {
+    fn clear(&mut self) {
+        self.start_channel_id = 0;
+        self.max_results = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetTopChannelsRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetTopChannelsRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetTopChannelsResponse {
+    // message fields
+    pub channel: ::protobuf::RepeatedField<Channel>,
+    pub end: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetTopChannelsResponse {
+    fn default() -> &'a GetTopChannelsResponse {
+        <GetTopChannelsResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetTopChannelsResponse {
+    pub fn new() -> GetTopChannelsResponse {
+        ::std::default::Default::default()
+    }
+
+    // repeated .grpc.channelz.v1.Channel channel = 1;
+
+
+    pub fn get_channel(&self) -> &[Channel] {
+        &self.channel
+    }
+    pub fn clear_channel(&mut self) {
+        self.channel.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel(&mut self, v: ::protobuf::RepeatedField<Channel>) {
+        self.channel = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_channel(&mut self) -> &mut ::protobuf::RepeatedField<Channel> {
+        &mut self.channel
+    }
+
+    // Take field
+    pub fn take_channel(&mut self) -> ::protobuf::RepeatedField<Channel> {
+        ::std::mem::replace(&mut self.channel, ::protobuf::RepeatedField::new())
+    }
+
+    // bool end = 2;
+
+
+    pub fn get_end(&self) -> bool {
+        self.end
+    }
+    pub fn clear_end(&mut self) {
+        self.end = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_end(&mut self, v: bool) {
+        self.end = v;
+    }
+}
+
+impl ::protobuf::Message for GetTopChannelsResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.channel {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.channel)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.end = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.channel {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.end != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.channel {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.end != false {
+            os.write_bool(2, self.end)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetTopChannelsResponse {
+        GetTopChannelsResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Channel>>(
+                "channel",
+                |m: &GetTopChannelsResponse| { &m.channel },
+                |m: &mut GetTopChannelsResponse| { &mut m.channel },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "end",
+                |m: &GetTopChannelsResponse| { &m.end },
+                |m: &mut GetTopChannelsResponse| { &mut m.end },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetTopChannelsResponse>(
+                "GetTopChannelsResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetTopChannelsResponse {
+        static instance: ::protobuf::rt::LazyV2<GetTopChannelsResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetTopChannelsResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetTopChannelsResponse {
+    fn clear(&mut self) {
+        self.channel.clear();
+        self.end = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetTopChannelsResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetTopChannelsResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServersRequest {
+    // message fields
+    pub start_server_id: i64,
+    pub max_results: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServersRequest {
+    fn default() -> &'a GetServersRequest {
+        <GetServersRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServersRequest {
+    pub fn new() -> GetServersRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 start_server_id = 1;
+
+
+    pub fn get_start_server_id(&self) -> i64 {
+        self.start_server_id
+    }
+    pub fn clear_start_server_id(&mut self) {
+        self.start_server_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_start_server_id(&mut self, v: i64) {
+        self.start_server_id = v;
+    }
+
+    // int64 max_results = 2;
+
+
+    pub fn get_max_results(&self) -> i64 {
+        self.max_results
+    }
+    pub fn clear_max_results(&mut self) {
+        self.max_results = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_results(&mut self, v: i64) {
+        self.max_results = v;
+    }
+}
+
+impl ::protobuf::Message for GetServersRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.start_server_id = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.max_results = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.start_server_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.start_server_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.max_results != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.max_results, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.start_server_id != 0 {
+            os.write_int64(1, self.start_server_id)?;
+        }
+        if self.max_results != 0 {
+            os.write_int64(2, self.max_results)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServersRequest {
+        GetServersRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "start_server_id",
+                |m: &GetServersRequest| { &m.start_server_id },
+                |m: &mut GetServersRequest| { &mut m.start_server_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "max_results",
+                |m: &GetServersRequest| { &m.max_results },
+                |m: &mut GetServersRequest| { &mut m.max_results },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServersRequest>(
+                "GetServersRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServersRequest {
+        static instance: ::protobuf::rt::LazyV2<GetServersRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServersRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServersRequest {
+    fn clear(&mut self) {
+        self.start_server_id = 0;
+        self.max_results = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServersRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServersRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServersResponse {
+    // message fields
+    pub server: ::protobuf::RepeatedField<Server>,
+    pub end: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServersResponse {
+    fn default() -> &'a GetServersResponse {
+        <GetServersResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServersResponse {
+    pub fn new() -> GetServersResponse {
+        ::std::default::Default::default()
+    }
+
+    // repeated .grpc.channelz.v1.Server server = 1;
+
+
+    pub fn get_server(&self) -> &[Server] {
+        &self.server
+    }
+    pub fn clear_server(&mut self) {
+        self.server.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_server(&mut self, v: ::protobuf::RepeatedField<Server>) {
+        self.server = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_server(&mut self) -> &mut ::protobuf::RepeatedField<Server> {
+        &mut self.server
+    }
+
+    // Take field
+    pub fn take_server(&mut self) -> ::protobuf::RepeatedField<Server> {
+        ::std::mem::replace(&mut self.server, ::protobuf::RepeatedField::new())
+    }
+
+    // bool end = 2;
+
+
+    pub fn get_end(&self) -> bool {
+        self.end
+    }
+    pub fn clear_end(&mut self) {
+        self.end = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_end(&mut self, v: bool) {
+        self.end = v;
+    }
+}
+
+impl ::protobuf::Message for GetServersResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.server {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.server)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.end = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.server {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.end != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.server {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.end != false {
+            os.write_bool(2, self.end)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServersResponse {
+        GetServersResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Server>>(
+                "server",
+                |m: &GetServersResponse| { &m.server },
+                |m: &mut GetServersResponse| { &mut m.server },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "end",
+                |m: &GetServersResponse| { &m.end },
+                |m: &mut GetServersResponse| { &mut m.end },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServersResponse>(
+                "GetServersResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServersResponse {
+        static instance: ::protobuf::rt::LazyV2<GetServersResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServersResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServersResponse {
+    fn clear(&mut self) {
+        self.server.clear();
+        self.end = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServersResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServersResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServerRequest {
+    // message fields
+    pub server_id: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServerRequest {
+    fn default() -> &'a GetServerRequest {
+        <GetServerRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServerRequest {
+    pub fn new() -> GetServerRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 server_id = 1;
+
+
+    pub fn get_server_id(&self) -> i64 {
+        self.server_id
+    }
+    pub fn clear_server_id(&mut self) {
+        self.server_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_server_id(&mut self, v: i64) {
+        self.server_id = v;
+    }
+}
+
+impl ::protobuf::Message for GetServerRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.server_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.server_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.server_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.server_id != 0 {
+            os.write_int64(1, self.server_id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServerRequest {
+        GetServerRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "server_id",
+                |m: &GetServerRequest| { &m.server_id },
+                |m: &mut GetServerRequest| { &mut m.server_id },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServerRequest>(
+                "GetServerRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServerRequest {
+        static instance: ::protobuf::rt::LazyV2<GetServerRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServerRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServerRequest {
+    fn clear(&mut self) {
+        self.server_id = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServerRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServerRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServerResponse {
+    // message fields
+    pub server: ::protobuf::SingularPtrField<Server>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServerResponse {
+    fn default() -> &'a GetServerResponse {
+        <GetServerResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServerResponse {
+    pub fn new() -> GetServerResponse {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Server server = 1;
+
+
+    pub fn get_server(&self) -> &Server {
+        self.server.as_ref().unwrap_or_else(|| <Server as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_server(&mut self) {
+        self.server.clear();
+    }
+
+    pub fn has_server(&self) -> bool {
+        self.server.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_server(&mut self, v: Server) {
+        self.server = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_server(&mut self) -> &mut Server {
+        if self.server.is_none() {
+            self.server.set_default();
+        }
+        self.server.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_server(&mut self) -> Server {
+        self.server.take().unwrap_or_else(|| Server::new())
+    }
+}
+
+impl ::protobuf::Message for GetServerResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.server {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.server)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.server.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.server.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServerResponse {
+        GetServerResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Server>>(
+                "server",
+                |m: &GetServerResponse| { &m.server },
+                |m: &mut GetServerResponse| { &mut m.server },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServerResponse>(
+                "GetServerResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServerResponse {
+        static instance: ::protobuf::rt::LazyV2<GetServerResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServerResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServerResponse {
+    fn clear(&mut self) {
+        self.server.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServerResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServerResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServerSocketsRequest {
+    // message fields
+    pub server_id: i64,
+    pub start_socket_id: i64,
+    pub max_results: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServerSocketsRequest {
+    fn default() -> &'a GetServerSocketsRequest {
+        <GetServerSocketsRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServerSocketsRequest {
+    pub fn new() -> GetServerSocketsRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 server_id = 1;
+
+
+    pub fn get_server_id(&self) -> i64 {
+        self.server_id
+    }
+    pub fn clear_server_id(&mut self) {
+        self.server_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_server_id(&mut self, v: i64) {
+        self.server_id = v;
+    }
+
+    // int64 start_socket_id = 2;
+
+
+    pub fn get_start_socket_id(&self) -> i64 {
+        self.start_socket_id
+    }
+    pub fn clear_start_socket_id(&mut self) {
+        self.start_socket_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_start_socket_id(&mut self, v: i64) {
+        self.start_socket_id = v;
+    }
+
+    // int64 max_results = 3;
+
+
+    pub fn get_max_results(&self) -> i64 {
+        self.max_results
+    }
+    pub fn clear_max_results(&mut self) {
+        self.max_results = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_max_results(&mut self, v: i64) {
+        self.max_results = v;
+    }
+}
+
+impl ::protobuf::Message for GetServerSocketsRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.server_id = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.start_socket_id = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.max_results = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.server_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.server_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.start_socket_id != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.start_socket_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.max_results != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.max_results, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.server_id != 0 {
+            os.write_int64(1, self.server_id)?;
+        }
+        if self.start_socket_id != 0 {
+            os.write_int64(2, self.start_socket_id)?;
+        }
+        if self.max_results != 0 {
+            os.write_int64(3, self.max_results)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServerSocketsRequest {
+        GetServerSocketsRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "server_id",
+                |m: &GetServerSocketsRequest| { &m.server_id },
+                |m: &mut GetServerSocketsRequest| { &mut m.server_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "start_socket_id",
+                |m: &GetServerSocketsRequest| { &m.start_socket_id },
+                |m: &mut GetServerSocketsRequest| { &mut m.start_socket_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "max_results",
+                |m: &GetServerSocketsRequest| { &m.max_results },
+                |m: &mut GetServerSocketsRequest| { &mut m.max_results },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServerSocketsRequest>(
+                "GetServerSocketsRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServerSocketsRequest {
+        static instance: ::protobuf::rt::LazyV2<GetServerSocketsRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServerSocketsRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServerSocketsRequest {
+    fn clear(&mut self) {
+        self.server_id = 0;
+        self.start_socket_id = 0;
+        self.max_results = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServerSocketsRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServerSocketsRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetServerSocketsResponse {
+    // message fields
+    pub socket_ref: ::protobuf::RepeatedField<SocketRef>,
+    pub end: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetServerSocketsResponse {
+    fn default() -> &'a GetServerSocketsResponse {
+        <GetServerSocketsResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetServerSocketsResponse {
+    pub fn new() -> GetServerSocketsResponse {
+        ::std::default::Default::default()
+    }
+
+    // repeated .grpc.channelz.v1.SocketRef socket_ref = 1;
+
+
+    pub fn get_socket_ref(&self) -> &[SocketRef] {
+        &self.socket_ref
+    }
+    pub fn clear_socket_ref(&mut self) {
+        self.socket_ref.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket_ref(&mut self, v: ::protobuf::RepeatedField<SocketRef>) {
+        self.socket_ref = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_socket_ref(&mut self) -> &mut ::protobuf::RepeatedField<SocketRef> {
+        &mut self.socket_ref
+    }
+
+    // Take field
+    pub fn take_socket_ref(&mut self) -> ::protobuf::RepeatedField<SocketRef> {
+        ::std::mem::replace(&mut self.socket_ref, ::protobuf::RepeatedField::new())
+    }
+
+    // bool end = 2;
+
+
+    pub fn get_end(&self) -> bool {
+        self.end
+    }
+    pub fn clear_end(&mut self) {
+        self.end = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_end(&mut self, v: bool) {
+        self.end = v;
+    }
+}
+
+impl ::protobuf::Message for GetServerSocketsResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.socket_ref {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.socket_ref)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.end = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.socket_ref {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.end != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.socket_ref {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.end != false {
+            os.write_bool(2, self.end)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetServerSocketsResponse {
+        GetServerSocketsResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<SocketRef>>(
+                "socket_ref",
+                |m: &GetServerSocketsResponse| { &m.socket_ref },
+                |m: &mut GetServerSocketsResponse| { &mut m.socket_ref },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "end",
+                |m: &GetServerSocketsResponse| { &m.end },
+                |m: &mut GetServerSocketsResponse| { &mut m.end },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetServerSocketsResponse>(
+                "GetServerSocketsResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetServerSocketsResponse {
+        static instance: ::protobuf::rt::LazyV2<GetServerSocketsResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetServerSocketsResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetServerSocketsResponse {
+    fn clear(&mut self) {
+        self.socket_ref.clear();
+        self.end = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetServerSocketsResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetServerSocketsResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetChannelRequest {
+    // message fields
+    pub channel_id: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetChannelRequest {
+    fn default() -> &'a GetChannelRequest {
+        <GetChannelRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetChannelRequest {
+    pub fn new() -> GetChannelRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 channel_id = 1;
+
+
+    pub fn get_channel_id(&self) -> i64 {
+        self.channel_id
+    }
+    pub fn clear_channel_id(&mut self) {
+        self.channel_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel_id(&mut self, v: i64) {
+        self.channel_id = v;
+    }
+}
+
+impl ::protobuf::Message for GetChannelRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.channel_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.channel_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.channel_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.channel_id != 0 {
+            os.write_int64(1, self.channel_id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetChannelRequest {
+        GetChannelRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "channel_id",
+                |m: &GetChannelRequest| { &m.channel_id },
+                |m: &mut GetChannelRequest| { &mut m.channel_id },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetChannelRequest>(
+                "GetChannelRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetChannelRequest {
+        static instance: ::protobuf::rt::LazyV2<GetChannelRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetChannelRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetChannelRequest {
+    fn clear(&mut self) {
+        self.channel_id = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetChannelRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetChannelRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetChannelResponse {
+    // message fields
+    pub channel: ::protobuf::SingularPtrField<Channel>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetChannelResponse {
+    fn default() -> &'a GetChannelResponse {
+        <GetChannelResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetChannelResponse {
+    pub fn new() -> GetChannelResponse {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Channel channel = 1;
+
+
+    pub fn get_channel(&self) -> &Channel {
+        self.channel.as_ref().unwrap_or_else(|| <Channel as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_channel(&mut self) {
+        self.channel.clear();
+    }
+
+    pub fn has_channel(&self) -> bool {
+        self.channel.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_channel(&mut self, v: Channel) {
+        self.channel = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_channel(&mut self) -> &mut Channel {
+        if self.channel.is_none() {
+            self.channel.set_default();
+        }
+        self.channel.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_channel(&mut self) -> Channel {
+        self.channel.take().unwrap_or_else(|| Channel::new())
+    }
+}
+
+impl ::protobuf::Message for GetChannelResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.channel {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.channel)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.channel.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.channel.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetChannelResponse {
+        GetChannelResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Channel>>(
+                "channel",
+                |m: &GetChannelResponse| { &m.channel },
+                |m: &mut GetChannelResponse| { &mut m.channel },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetChannelResponse>(
+                "GetChannelResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetChannelResponse {
+        static instance: ::protobuf::rt::LazyV2<GetChannelResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetChannelResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetChannelResponse {
+    fn clear(&mut self) {
+        self.channel.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetChannelResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetChannelResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetSubchannelRequest {
+    // message fields
+    pub subchannel_id: i64,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetSubchannelRequest {
+    fn default() -> &'a GetSubchannelRequest {
+        <GetSubchannelRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetSubchannelRequest {
+    pub fn new() -> GetSubchannelRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 subchannel_id = 1;
+
+
+    pub fn get_subchannel_id(&self) -> i64 {
+        self.subchannel_id
+    }
+    pub fn clear_subchannel_id(&mut self) {
+        self.subchannel_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel_id(&mut self, v: i64) {
+        self.subchannel_id = v;
+    }
+}
+
+impl ::protobuf::Message for GetSubchannelRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.subchannel_id = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.subchannel_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.subchannel_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.subchannel_id != 0 {
+            os.write_int64(1, self.subchannel_id)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetSubchannelRequest {
+        GetSubchannelRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "subchannel_id",
+                |m: &GetSubchannelRequest| { &m.subchannel_id },
+                |m: &mut GetSubchannelRequest| { &mut m.subchannel_id },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetSubchannelRequest>(
+                "GetSubchannelRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetSubchannelRequest {
+        static instance: ::protobuf::rt::LazyV2<GetSubchannelRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetSubchannelRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetSubchannelRequest {
+    fn clear(&mut self) {
+        self.subchannel_id = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetSubchannelRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetSubchannelRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetSubchannelResponse {
+    // message fields
+    pub subchannel: ::protobuf::SingularPtrField<Subchannel>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetSubchannelResponse {
+    fn default() -> &'a GetSubchannelResponse {
+        <GetSubchannelResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetSubchannelResponse {
+    pub fn new() -> GetSubchannelResponse {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Subchannel subchannel = 1;
+
+
+    pub fn get_subchannel(&self) -> &Subchannel {
+        self.subchannel.as_ref().unwrap_or_else(|| <Subchannel as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_subchannel(&mut self) {
+        self.subchannel.clear();
+    }
+
+    pub fn has_subchannel(&self) -> bool {
+        self.subchannel.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subchannel(&mut self, v: Subchannel) {
+        self.subchannel = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_subchannel(&mut self) -> &mut Subchannel {
+        if self.subchannel.is_none() {
+            self.subchannel.set_default();
+        }
+        self.subchannel.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_subchannel(&mut self) -> Subchannel {
+        self.subchannel.take().unwrap_or_else(|| Subchannel::new())
+    }
+}
+
+impl ::protobuf::Message for GetSubchannelResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.subchannel {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.subchannel)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.subchannel.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.subchannel.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetSubchannelResponse {
+        GetSubchannelResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Subchannel>>(
+                "subchannel",
+                |m: &GetSubchannelResponse| { &m.subchannel },
+                |m: &mut GetSubchannelResponse| { &mut m.subchannel },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetSubchannelResponse>(
+                "GetSubchannelResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetSubchannelResponse {
+        static instance: ::protobuf::rt::LazyV2<GetSubchannelResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetSubchannelResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetSubchannelResponse {
+    fn clear(&mut self) {
+        self.subchannel.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetSubchannelResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetSubchannelResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetSocketRequest {
+    // message fields
+    pub socket_id: i64,
+    pub summary: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetSocketRequest {
+    fn default() -> &'a GetSocketRequest {
+        <GetSocketRequest as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetSocketRequest {
+    pub fn new() -> GetSocketRequest {
+        ::std::default::Default::default()
+    }
+
+    // int64 socket_id = 1;
+
+
+    pub fn get_socket_id(&self) -> i64 {
+        self.socket_id
+    }
+    pub fn clear_socket_id(&mut self) {
+        self.socket_id = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket_id(&mut self, v: i64) {
+        self.socket_id = v;
+    }
+
+    // bool summary = 2;
+
+
+    pub fn get_summary(&self) -> bool {
+        self.summary
+    }
+    pub fn clear_summary(&mut self) {
+        self.summary = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_summary(&mut self, v: bool) {
+        self.summary = v;
+    }
+}
+
+impl ::protobuf::Message for GetSocketRequest {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int64()?;
+                    self.socket_id = tmp;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.summary = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.socket_id != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.socket_id, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.summary != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.socket_id != 0 {
+            os.write_int64(1, self.socket_id)?;
+        }
+        if self.summary != false {
+            os.write_bool(2, self.summary)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetSocketRequest {
+        GetSocketRequest::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt64>(
+                "socket_id",
+                |m: &GetSocketRequest| { &m.socket_id },
+                |m: &mut GetSocketRequest| { &mut m.socket_id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "summary",
+                |m: &GetSocketRequest| { &m.summary },
+                |m: &mut GetSocketRequest| { &mut m.summary },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetSocketRequest>(
+                "GetSocketRequest",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetSocketRequest {
+        static instance: ::protobuf::rt::LazyV2<GetSocketRequest> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetSocketRequest::new)
+    }
+}
+
+impl ::protobuf::Clear for GetSocketRequest {
+    fn clear(&mut self) {
+        self.socket_id = 0;
+        self.summary = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetSocketRequest {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetSocketRequest {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct GetSocketResponse {
+    // message fields
+    pub socket: ::protobuf::SingularPtrField<Socket>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a GetSocketResponse {
+    fn default() -> &'a GetSocketResponse {
+        <GetSocketResponse as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl GetSocketResponse {
+    pub fn new() -> GetSocketResponse {
+        ::std::default::Default::default()
+    }
+
+    // .grpc.channelz.v1.Socket socket = 1;
+
+
+    pub fn get_socket(&self) -> &Socket {
+        self.socket.as_ref().unwrap_or_else(|| <Socket as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_socket(&mut self) {
+        self.socket.clear();
+    }
+
+    pub fn has_socket(&self) -> bool {
+        self.socket.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_socket(&mut self, v: Socket) {
+        self.socket = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_socket(&mut self) -> &mut Socket {
+        if self.socket.is_none() {
+            self.socket.set_default();
+        }
+        self.socket.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_socket(&mut self) -> Socket {
+        self.socket.take().unwrap_or_else(|| Socket::new())
+    }
+}
+
+impl ::protobuf::Message for GetSocketResponse {
+    fn is_initialized(&self) -> bool {
+        for v in &self.socket {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.socket)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(ref v) = self.socket.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(ref v) = self.socket.as_ref() {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> GetSocketResponse {
+        GetSocketResponse::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Socket>>(
+                "socket",
+                |m: &GetSocketResponse| { &m.socket },
+                |m: &mut GetSocketResponse| { &mut m.socket },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<GetSocketResponse>(
+                "GetSocketResponse",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static GetSocketResponse {
+        static instance: ::protobuf::rt::LazyV2<GetSocketResponse> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(GetSocketResponse::new)
+    }
+}
+
+impl ::protobuf::Clear for GetSocketResponse {
+    fn clear(&mut self) {
+        self.socket.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for GetSocketResponse {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for GetSocketResponse {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+static file_descriptor_proto_data: &'static [u8] = b"\
+    \n\x1cgrpc/channelz/channelz.proto\x12\x10grpc.channelz.v1\x1a\x19google\
+    /protobuf/any.proto\x1a\x1egoogle/protobuf/duration.proto\x1a\x1fgoogle/\
+    protobuf/timestamp.proto\x1a\x1egoogle/protobuf/wrappers.proto\"\xaf\x02\
+    \n\x07Channel\x12.\n\x03ref\x18\x01\x20\x01(\x0b2\x1c.grpc.channelz.v1.C\
+    hannelRefR\x03ref\x121\n\x04data\x18\x02\x20\x01(\x0b2\x1d.grpc.channelz\
+    .v1.ChannelDataR\x04data\x12=\n\x0bchannel_ref\x18\x03\x20\x03(\x0b2\x1c\
+    .grpc.channelz.v1.ChannelRefR\nchannelRef\x12F\n\x0esubchannel_ref\x18\
+    \x04\x20\x03(\x0b2\x1f.grpc.channelz.v1.SubchannelRefR\rsubchannelRef\
+    \x12:\n\nsocket_ref\x18\x05\x20\x03(\x0b2\x1b.grpc.channelz.v1.SocketRef\
+    R\tsocketRef\"\xb5\x02\n\nSubchannel\x121\n\x03ref\x18\x01\x20\x01(\x0b2\
+    \x1f.grpc.channelz.v1.SubchannelRefR\x03ref\x121\n\x04data\x18\x02\x20\
+    \x01(\x0b2\x1d.grpc.channelz.v1.ChannelDataR\x04data\x12=\n\x0bchannel_r\
+    ef\x18\x03\x20\x03(\x0b2\x1c.grpc.channelz.v1.ChannelRefR\nchannelRef\
+    \x12F\n\x0esubchannel_ref\x18\x04\x20\x03(\x0b2\x1f.grpc.channelz.v1.Sub\
+    channelRefR\rsubchannelRef\x12:\n\nsocket_ref\x18\x05\x20\x03(\x0b2\x1b.\
+    grpc.channelz.v1.SocketRefR\tsocketRef\"\xc2\x01\n\x18ChannelConnectivit\
+    yState\x12F\n\x05state\x18\x01\x20\x01(\x0e20.grpc.channelz.v1.ChannelCo\
+    nnectivityState.StateR\x05state\"^\n\x05State\x12\x0b\n\x07UNKNOWN\x10\0\
+    \x12\x08\n\x04IDLE\x10\x01\x12\x0e\n\nCONNECTING\x10\x02\x12\t\n\x05READ\
+    Y\x10\x03\x12\x15\n\x11TRANSIENT_FAILURE\x10\x04\x12\x0c\n\x08SHUTDOWN\
+    \x10\x05\"\xe9\x02\n\x0bChannelData\x12@\n\x05state\x18\x01\x20\x01(\x0b\
+    2*.grpc.channelz.v1.ChannelConnectivityStateR\x05state\x12\x16\n\x06targ\
+    et\x18\x02\x20\x01(\tR\x06target\x124\n\x05trace\x18\x03\x20\x01(\x0b2\
+    \x1e.grpc.channelz.v1.ChannelTraceR\x05trace\x12#\n\rcalls_started\x18\
+    \x04\x20\x01(\x03R\x0ccallsStarted\x12'\n\x0fcalls_succeeded\x18\x05\x20\
+    \x01(\x03R\x0ecallsSucceeded\x12!\n\x0ccalls_failed\x18\x06\x20\x01(\x03\
+    R\x0bcallsFailed\x12Y\n\x1blast_call_started_timestamp\x18\x07\x20\x01(\
+    \x0b2\x1a.google.protobuf.TimestampR\x18lastCallStartedTimestamp\"\x98\
+    \x03\n\x11ChannelTraceEvent\x12\x20\n\x0bdescription\x18\x01\x20\x01(\tR\
+    \x0bdescription\x12H\n\x08severity\x18\x02\x20\x01(\x0e2,.grpc.channelz.\
+    v1.ChannelTraceEvent.SeverityR\x08severity\x128\n\ttimestamp\x18\x03\x20\
+    \x01(\x0b2\x1a.google.protobuf.TimestampR\ttimestamp\x12?\n\x0bchannel_r\
+    ef\x18\x04\x20\x01(\x0b2\x1c.grpc.channelz.v1.ChannelRefH\0R\nchannelRef\
+    \x12H\n\x0esubchannel_ref\x18\x05\x20\x01(\x0b2\x1f.grpc.channelz.v1.Sub\
+    channelRefH\0R\rsubchannelRef\"E\n\x08Severity\x12\x0e\n\nCT_UNKNOWN\x10\
+    \0\x12\x0b\n\x07CT_INFO\x10\x01\x12\x0e\n\nCT_WARNING\x10\x02\x12\x0c\n\
+    \x08CT_ERROR\x10\x03B\x0b\n\tchild_ref\"\xc2\x01\n\x0cChannelTrace\x12*\
+    \n\x11num_events_logged\x18\x01\x20\x01(\x03R\x0fnumEventsLogged\x12I\n\
+    \x12creation_timestamp\x18\x02\x20\x01(\x0b2\x1a.google.protobuf.Timesta\
+    mpR\x11creationTimestamp\x12;\n\x06events\x18\x03\x20\x03(\x0b2#.grpc.ch\
+    annelz.v1.ChannelTraceEventR\x06events\"c\n\nChannelRef\x12\x1d\n\nchann\
+    el_id\x18\x01\x20\x01(\x03R\tchannelId\x12\x12\n\x04name\x18\x02\x20\x01\
+    (\tR\x04nameJ\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\x05\x10\
+    \x06J\x04\x08\x06\x10\x07J\x04\x08\x07\x10\x08J\x04\x08\x08\x10\t\"l\n\r\
+    SubchannelRef\x12#\n\rsubchannel_id\x18\x07\x20\x01(\x03R\x0csubchannelI\
+    d\x12\x12\n\x04name\x18\x08\x20\x01(\tR\x04nameJ\x04\x08\x01\x10\x02J\
+    \x04\x08\x02\x10\x03J\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\
+    \x05\x10\x06J\x04\x08\x06\x10\x07\"`\n\tSocketRef\x12\x1b\n\tsocket_id\
+    \x18\x03\x20\x01(\x03R\x08socketId\x12\x12\n\x04name\x18\x04\x20\x01(\tR\
+    \x04nameJ\x04\x08\x01\x10\x02J\x04\x08\x02\x10\x03J\x04\x08\x05\x10\x06J\
+    \x04\x08\x06\x10\x07J\x04\x08\x07\x10\x08J\x04\x08\x08\x10\t\"`\n\tServe\
+    rRef\x12\x1b\n\tserver_id\x18\x05\x20\x01(\x03R\x08serverId\x12\x12\n\
+    \x04name\x18\x06\x20\x01(\tR\x04nameJ\x04\x08\x01\x10\x02J\x04\x08\x02\
+    \x10\x03J\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\x07\x10\x08J\
+    \x04\x08\x08\x10\t\"\xab\x01\n\x06Server\x12-\n\x03ref\x18\x01\x20\x01(\
+    \x0b2\x1b.grpc.channelz.v1.ServerRefR\x03ref\x120\n\x04data\x18\x02\x20\
+    \x01(\x0b2\x1c.grpc.channelz.v1.ServerDataR\x04data\x12@\n\rlisten_socke\
+    t\x18\x03\x20\x03(\x0b2\x1b.grpc.channelz.v1.SocketRefR\x0clistenSocket\
+    \"\x8e\x02\n\nServerData\x124\n\x05trace\x18\x01\x20\x01(\x0b2\x1e.grpc.\
+    channelz.v1.ChannelTraceR\x05trace\x12#\n\rcalls_started\x18\x02\x20\x01\
+    (\x03R\x0ccallsStarted\x12'\n\x0fcalls_succeeded\x18\x03\x20\x01(\x03R\
+    \x0ecallsSucceeded\x12!\n\x0ccalls_failed\x18\x04\x20\x01(\x03R\x0bcalls\
+    Failed\x12Y\n\x1blast_call_started_timestamp\x18\x05\x20\x01(\x0b2\x1a.g\
+    oogle.protobuf.TimestampR\x18lastCallStartedTimestamp\"\xa6\x02\n\x06Soc\
+    ket\x12-\n\x03ref\x18\x01\x20\x01(\x0b2\x1b.grpc.channelz.v1.SocketRefR\
+    \x03ref\x120\n\x04data\x18\x02\x20\x01(\x0b2\x1c.grpc.channelz.v1.Socket\
+    DataR\x04data\x12/\n\x05local\x18\x03\x20\x01(\x0b2\x19.grpc.channelz.v1\
+    .AddressR\x05local\x121\n\x06remote\x18\x04\x20\x01(\x0b2\x19.grpc.chann\
+    elz.v1.AddressR\x06remote\x126\n\x08security\x18\x05\x20\x01(\x0b2\x1a.g\
+    rpc.channelz.v1.SecurityR\x08security\x12\x1f\n\x0bremote_name\x18\x06\
+    \x20\x01(\tR\nremoteName\"\x83\x07\n\nSocketData\x12'\n\x0fstreams_start\
+    ed\x18\x01\x20\x01(\x03R\x0estreamsStarted\x12+\n\x11streams_succeeded\
+    \x18\x02\x20\x01(\x03R\x10streamsSucceeded\x12%\n\x0estreams_failed\x18\
+    \x03\x20\x01(\x03R\rstreamsFailed\x12#\n\rmessages_sent\x18\x04\x20\x01(\
+    \x03R\x0cmessagesSent\x12+\n\x11messages_received\x18\x05\x20\x01(\x03R\
+    \x10messagesReceived\x12(\n\x10keep_alives_sent\x18\x06\x20\x01(\x03R\
+    \x0ekeepAlivesSent\x12h\n#last_local_stream_created_timestamp\x18\x07\
+    \x20\x01(\x0b2\x1a.google.protobuf.TimestampR\x1flastLocalStreamCreatedT\
+    imestamp\x12j\n$last_remote_stream_created_timestamp\x18\x08\x20\x01(\
+    \x0b2\x1a.google.protobuf.TimestampR\x20lastRemoteStreamCreatedTimestamp\
+    \x12Y\n\x1blast_message_sent_timestamp\x18\t\x20\x01(\x0b2\x1a.google.pr\
+    otobuf.TimestampR\x18lastMessageSentTimestamp\x12a\n\x1flast_message_rec\
+    eived_timestamp\x18\n\x20\x01(\x0b2\x1a.google.protobuf.TimestampR\x1cla\
+    stMessageReceivedTimestamp\x12V\n\x19local_flow_control_window\x18\x0b\
+    \x20\x01(\x0b2\x1b.google.protobuf.Int64ValueR\x16localFlowControlWindow\
+    \x12X\n\x1aremote_flow_control_window\x18\x0c\x20\x01(\x0b2\x1b.google.p\
+    rotobuf.Int64ValueR\x17remoteFlowControlWindow\x126\n\x06option\x18\r\
+    \x20\x03(\x0b2\x1e.grpc.channelz.v1.SocketOptionR\x06option\"\xb8\x03\n\
+    \x07Address\x12M\n\rtcpip_address\x18\x01\x20\x01(\x0b2&.grpc.channelz.v\
+    1.Address.TcpIpAddressH\0R\x0ctcpipAddress\x12G\n\x0buds_address\x18\x02\
+    \x20\x01(\x0b2$.grpc.channelz.v1.Address.UdsAddressH\0R\nudsAddress\x12M\
+    \n\rother_address\x18\x03\x20\x01(\x0b2&.grpc.channelz.v1.Address.OtherA\
+    ddressH\0R\x0cotherAddress\x1aA\n\x0cTcpIpAddress\x12\x1d\n\nip_address\
+    \x18\x01\x20\x01(\x0cR\tipAddress\x12\x12\n\x04port\x18\x02\x20\x01(\x05\
+    R\x04port\x1a(\n\nUdsAddress\x12\x1a\n\x08filename\x18\x01\x20\x01(\tR\
+    \x08filename\x1aN\n\x0cOtherAddress\x12\x12\n\x04name\x18\x01\x20\x01(\t\
+    R\x04name\x12*\n\x05value\x18\x02\x20\x01(\x0b2\x14.google.protobuf.AnyR\
+    \x05valueB\t\n\x07address\"\x96\x03\n\x08Security\x122\n\x03tls\x18\x01\
+    \x20\x01(\x0b2\x1e.grpc.channelz.v1.Security.TlsH\0R\x03tls\x12@\n\x05ot\
+    her\x18\x02\x20\x01(\x0b2(.grpc.channelz.v1.Security.OtherSecurityH\0R\
+    \x05other\x1a\xb9\x01\n\x03Tls\x12%\n\rstandard_name\x18\x01\x20\x01(\tH\
+    \0R\x0cstandardName\x12\x1f\n\nother_name\x18\x02\x20\x01(\tH\0R\totherN\
+    ame\x12+\n\x11local_certificate\x18\x03\x20\x01(\x0cR\x10localCertificat\
+    e\x12-\n\x12remote_certificate\x18\x04\x20\x01(\x0cR\x11remoteCertificat\
+    eB\x0e\n\x0ccipher_suite\x1aO\n\rOtherSecurity\x12\x12\n\x04name\x18\x01\
+    \x20\x01(\tR\x04name\x12*\n\x05value\x18\x02\x20\x01(\x0b2\x14.google.pr\
+    otobuf.AnyR\x05valueB\x07\n\x05model\"n\n\x0cSocketOption\x12\x12\n\x04n\
+    ame\x18\x01\x20\x01(\tR\x04name\x12\x14\n\x05value\x18\x02\x20\x01(\tR\
+    \x05value\x124\n\nadditional\x18\x03\x20\x01(\x0b2\x14.google.protobuf.A\
+    nyR\nadditional\"L\n\x13SocketOptionTimeout\x125\n\x08duration\x18\x01\
+    \x20\x01(\x0b2\x19.google.protobuf.DurationR\x08duration\"c\n\x12SocketO\
+    ptionLinger\x12\x16\n\x06active\x18\x01\x20\x01(\x08R\x06active\x125\n\
+    \x08duration\x18\x02\x20\x01(\x0b2\x19.google.protobuf.DurationR\x08dura\
+    tion\"\xb2\x08\n\x13SocketOptionTcpInfo\x12\x1d\n\ntcpi_state\x18\x01\
+    \x20\x01(\rR\ttcpiState\x12\"\n\rtcpi_ca_state\x18\x02\x20\x01(\rR\x0btc\
+    piCaState\x12)\n\x10tcpi_retransmits\x18\x03\x20\x01(\rR\x0ftcpiRetransm\
+    its\x12\x1f\n\x0btcpi_probes\x18\x04\x20\x01(\rR\ntcpiProbes\x12!\n\x0ct\
+    cpi_backoff\x18\x05\x20\x01(\rR\x0btcpiBackoff\x12!\n\x0ctcpi_options\
+    \x18\x06\x20\x01(\rR\x0btcpiOptions\x12&\n\x0ftcpi_snd_wscale\x18\x07\
+    \x20\x01(\rR\rtcpiSndWscale\x12&\n\x0ftcpi_rcv_wscale\x18\x08\x20\x01(\r\
+    R\rtcpiRcvWscale\x12\x19\n\x08tcpi_rto\x18\t\x20\x01(\rR\x07tcpiRto\x12\
+    \x19\n\x08tcpi_ato\x18\n\x20\x01(\rR\x07tcpiAto\x12\x20\n\x0ctcpi_snd_ms\
+    s\x18\x0b\x20\x01(\rR\ntcpiSndMss\x12\x20\n\x0ctcpi_rcv_mss\x18\x0c\x20\
+    \x01(\rR\ntcpiRcvMss\x12!\n\x0ctcpi_unacked\x18\r\x20\x01(\rR\x0btcpiUna\
+    cked\x12\x1f\n\x0btcpi_sacked\x18\x0e\x20\x01(\rR\ntcpiSacked\x12\x1b\n\
+    \ttcpi_lost\x18\x0f\x20\x01(\rR\x08tcpiLost\x12!\n\x0ctcpi_retrans\x18\
+    \x10\x20\x01(\rR\x0btcpiRetrans\x12!\n\x0ctcpi_fackets\x18\x11\x20\x01(\
+    \rR\x0btcpiFackets\x12-\n\x13tcpi_last_data_sent\x18\x12\x20\x01(\rR\x10\
+    tcpiLastDataSent\x12+\n\x12tcpi_last_ack_sent\x18\x13\x20\x01(\rR\x0ftcp\
+    iLastAckSent\x12-\n\x13tcpi_last_data_recv\x18\x14\x20\x01(\rR\x10tcpiLa\
+    stDataRecv\x12+\n\x12tcpi_last_ack_recv\x18\x15\x20\x01(\rR\x0ftcpiLastA\
+    ckRecv\x12\x1b\n\ttcpi_pmtu\x18\x16\x20\x01(\rR\x08tcpiPmtu\x12*\n\x11tc\
+    pi_rcv_ssthresh\x18\x17\x20\x01(\rR\x0ftcpiRcvSsthresh\x12\x19\n\x08tcpi\
+    _rtt\x18\x18\x20\x01(\rR\x07tcpiRtt\x12\x1f\n\x0btcpi_rttvar\x18\x19\x20\
+    \x01(\rR\ntcpiRttvar\x12*\n\x11tcpi_snd_ssthresh\x18\x1a\x20\x01(\rR\x0f\
+    tcpiSndSsthresh\x12\"\n\rtcpi_snd_cwnd\x18\x1b\x20\x01(\rR\x0btcpiSndCwn\
+    d\x12\x1f\n\x0btcpi_advmss\x18\x1c\x20\x01(\rR\ntcpiAdvmss\x12'\n\x0ftcp\
+    i_reordering\x18\x1d\x20\x01(\rR\x0etcpiReordering\"b\n\x15GetTopChannel\
+    sRequest\x12(\n\x10start_channel_id\x18\x01\x20\x01(\x03R\x0estartChanne\
+    lId\x12\x1f\n\x0bmax_results\x18\x02\x20\x01(\x03R\nmaxResults\"_\n\x16G\
+    etTopChannelsResponse\x123\n\x07channel\x18\x01\x20\x03(\x0b2\x19.grpc.c\
+    hannelz.v1.ChannelR\x07channel\x12\x10\n\x03end\x18\x02\x20\x01(\x08R\
+    \x03end\"\\\n\x11GetServersRequest\x12&\n\x0fstart_server_id\x18\x01\x20\
+    \x01(\x03R\rstartServerId\x12\x1f\n\x0bmax_results\x18\x02\x20\x01(\x03R\
+    \nmaxResults\"X\n\x12GetServersResponse\x120\n\x06server\x18\x01\x20\x03\
+    (\x0b2\x18.grpc.channelz.v1.ServerR\x06server\x12\x10\n\x03end\x18\x02\
+    \x20\x01(\x08R\x03end\"/\n\x10GetServerRequest\x12\x1b\n\tserver_id\x18\
+    \x01\x20\x01(\x03R\x08serverId\"E\n\x11GetServerResponse\x120\n\x06serve\
+    r\x18\x01\x20\x01(\x0b2\x18.grpc.channelz.v1.ServerR\x06server\"\x7f\n\
+    \x17GetServerSocketsRequest\x12\x1b\n\tserver_id\x18\x01\x20\x01(\x03R\
+    \x08serverId\x12&\n\x0fstart_socket_id\x18\x02\x20\x01(\x03R\rstartSocke\
+    tId\x12\x1f\n\x0bmax_results\x18\x03\x20\x01(\x03R\nmaxResults\"h\n\x18G\
+    etServerSocketsResponse\x12:\n\nsocket_ref\x18\x01\x20\x03(\x0b2\x1b.grp\
+    c.channelz.v1.SocketRefR\tsocketRef\x12\x10\n\x03end\x18\x02\x20\x01(\
+    \x08R\x03end\"2\n\x11GetChannelRequest\x12\x1d\n\nchannel_id\x18\x01\x20\
+    \x01(\x03R\tchannelId\"I\n\x12GetChannelResponse\x123\n\x07channel\x18\
+    \x01\x20\x01(\x0b2\x19.grpc.channelz.v1.ChannelR\x07channel\";\n\x14GetS\
+    ubchannelRequest\x12#\n\rsubchannel_id\x18\x01\x20\x01(\x03R\x0csubchann\
+    elId\"U\n\x15GetSubchannelResponse\x12<\n\nsubchannel\x18\x01\x20\x01(\
+    \x0b2\x1c.grpc.channelz.v1.SubchannelR\nsubchannel\"I\n\x10GetSocketRequ\
+    est\x12\x1b\n\tsocket_id\x18\x01\x20\x01(\x03R\x08socketId\x12\x18\n\x07\
+    summary\x18\x02\x20\x01(\x08R\x07summary\"E\n\x11GetSocketResponse\x120\
+    \n\x06socket\x18\x01\x20\x01(\x0b2\x18.grpc.channelz.v1.SocketR\x06socke\
+    t2\x9a\x05\n\x08Channelz\x12c\n\x0eGetTopChannels\x12'.grpc.channelz.v1.\
+    GetTopChannelsRequest\x1a(.grpc.channelz.v1.GetTopChannelsResponse\x12W\
+    \n\nGetServers\x12#.grpc.channelz.v1.GetServersRequest\x1a$.grpc.channel\
+    z.v1.GetServersResponse\x12T\n\tGetServer\x12\".grpc.channelz.v1.GetServ\
+    erRequest\x1a#.grpc.channelz.v1.GetServerResponse\x12i\n\x10GetServerSoc\
+    kets\x12).grpc.channelz.v1.GetServerSocketsRequest\x1a*.grpc.channelz.v1\
+    .GetServerSocketsResponse\x12W\n\nGetChannel\x12#.grpc.channelz.v1.GetCh\
+    annelRequest\x1a$.grpc.channelz.v1.GetChannelResponse\x12`\n\rGetSubchan\
+    nel\x12&.grpc.channelz.v1.GetSubchannelRequest\x1a'.grpc.channelz.v1.Get\
+    SubchannelResponse\x12T\n\tGetSocket\x12\".grpc.channelz.v1.GetSocketReq\
+    uest\x1a#.grpc.channelz.v1.GetSocketResponseBX\n\x13io.grpc.channelz.v1B\
+    \rChannelzProtoP\x01Z0google.golang.org/grpc/channelz/grpc_channelz_v1J\
+    \x90\xbd\x01\n\x07\x12\x05\x15\0\xb3\x04\x01\n\xfc\x06\n\x01\x0c\x12\x03\
+    \x15\0\x122\xb7\x04\x20Copyright\x202018\x20The\x20gRPC\x20Authors\n\n\
+    \x20Licensed\x20under\x20the\x20Apache\x20License,\x20Version\x202.0\x20\
+    (the\x20\"License\");\n\x20you\x20may\x20not\x20use\x20this\x20file\x20e\
+    xcept\x20in\x20compliance\x20with\x20the\x20License.\n\x20You\x20may\x20\
+    obtain\x20a\x20copy\x20of\x20the\x20License\x20at\n\n\x20\x20\x20\x20\
+    \x20http://www.apache.org/licenses/LICENSE-2.0\n\n\x20Unless\x20required\
+    \x20by\x20applicable\x20law\x20or\x20agreed\x20to\x20in\x20writing,\x20s\
+    oftware\n\x20distributed\x20under\x20the\x20License\x20is\x20distributed\
+    \x20on\x20an\x20\"AS\x20IS\"\x20BASIS,\n\x20WITHOUT\x20WARRANTIES\x20OR\
+    \x20CONDITIONS\x20OF\x20ANY\x20KIND,\x20either\x20express\x20or\x20impli\
+    ed.\n\x20See\x20the\x20License\x20for\x20the\x20specific\x20language\x20\
+    governing\x20permissions\x20and\n\x20limitations\x20under\x20the\x20Lice\
+    nse.\n2\xb7\x02\x20This\x20file\x20defines\x20an\x20interface\x20for\x20\
+    exporting\x20monitoring\x20information\n\x20out\x20of\x20gRPC\x20servers\
+    .\x20\x20See\x20the\x20full\x20design\x20at\n\x20https://github.com/grpc\
+    /proposal/blob/master/A14-channelz.md\n\n\x20The\x20canonical\x20version\
+    \x20of\x20this\x20proto\x20can\x20be\x20found\x20at\n\x20https://github.\
+    com/grpc/grpc-proto/blob/master/grpc/channelz/v1/channelz.proto\n\n\x08\
+    \n\x01\x02\x12\x03\x17\0\x19\n\t\n\x02\x03\0\x12\x03\x19\0#\n\t\n\x02\
+    \x03\x01\x12\x03\x1a\0(\n\t\n\x02\x03\x02\x12\x03\x1b\0)\n\t\n\x02\x03\
+    \x03\x12\x03\x1c\0(\n\x08\n\x01\x08\x12\x03\x1e\0G\n\t\n\x02\x08\x0b\x12\
+    \x03\x1e\0G\n\x08\n\x01\x08\x12\x03\x1f\0\"\n\t\n\x02\x08\n\x12\x03\x1f\
+    \0\"\n\x08\n\x01\x08\x12\x03\x20\0,\n\t\n\x02\x08\x01\x12\x03\x20\0,\n\
+    \x08\n\x01\x08\x12\x03!\0.\n\t\n\x02\x08\x08\x12\x03!\0.\nR\n\x02\x04\0\
+    \x12\x04$\08\x01\x1aF\x20Channel\x20is\x20a\x20logical\x20grouping\x20of\
+    \x20channels,\x20subchannels,\x20and\x20sockets.\n\n\n\n\x03\x04\0\x01\
+    \x12\x03$\x08\x0f\nD\n\x04\x04\0\x02\0\x12\x03&\x02\x15\x1a7\x20The\x20i\
+    dentifier\x20for\x20this\x20channel.\x20This\x20should\x20bet\x20set.\n\
+    \n\x0c\n\x05\x04\0\x02\0\x06\x12\x03&\x02\x0c\n\x0c\n\x05\x04\0\x02\0\
+    \x01\x12\x03&\r\x10\n\x0c\n\x05\x04\0\x02\0\x03\x12\x03&\x13\x14\nq\n\
+    \x04\x04\0\x02\x01\x12\x03(\x02\x17\x1a\x20\x20Data\x20specific\x20to\
+    \x20this\x20channel.\n\"B\x20At\x20most\x20one\x20of\x20'channel_ref+sub\
+    channel_ref'\x20and\x20'socket'\x20is\x20set.\n\n\x0c\n\x05\x04\0\x02\
+    \x01\x06\x12\x03(\x02\r\n\x0c\n\x05\x04\0\x02\x01\x01\x12\x03(\x0e\x12\n\
+    \x0c\n\x05\x04\0\x02\x01\x03\x12\x03(\x15\x16\n\xbf\x01\n\x04\x04\0\x02\
+    \x02\x12\x03.\x02&\x1a\xb1\x01\x20There\x20are\x20no\x20ordering\x20guar\
+    antees\x20on\x20the\x20order\x20of\x20channel\x20refs.\n\x20There\x20may\
+    \x20not\x20be\x20cycles\x20in\x20the\x20ref\x20graph.\n\x20A\x20channel\
+    \x20ref\x20may\x20be\x20present\x20in\x20more\x20than\x20one\x20channel\
+    \x20or\x20subchannel.\n\n\x0c\n\x05\x04\0\x02\x02\x04\x12\x03.\x02\n\n\
+    \x0c\n\x05\x04\0\x02\x02\x06\x12\x03.\x0b\x15\n\x0c\n\x05\x04\0\x02\x02\
+    \x01\x12\x03.\x16!\n\x0c\n\x05\x04\0\x02\x02\x03\x12\x03.$%\n\x88\x02\n\
+    \x04\x04\0\x02\x03\x12\x034\x02,\x1a\xfa\x01\x20At\x20most\x20one\x20of\
+    \x20'channel_ref+subchannel_ref'\x20and\x20'socket'\x20is\x20set.\n\x20T\
+    here\x20are\x20no\x20ordering\x20guarantees\x20on\x20the\x20order\x20of\
+    \x20subchannel\x20refs.\n\x20There\x20may\x20not\x20be\x20cycles\x20in\
+    \x20the\x20ref\x20graph.\n\x20A\x20sub\x20channel\x20ref\x20may\x20be\
+    \x20present\x20in\x20more\x20than\x20one\x20channel\x20or\x20subchannel.\
+    \n\n\x0c\n\x05\x04\0\x02\x03\x04\x12\x034\x02\n\n\x0c\n\x05\x04\0\x02\
+    \x03\x06\x12\x034\x0b\x18\n\x0c\n\x05\x04\0\x02\x03\x01\x12\x034\x19'\n\
+    \x0c\n\x05\x04\0\x02\x03\x03\x12\x034*+\nH\n\x04\x04\0\x02\x04\x12\x037\
+    \x02$\x1a;\x20There\x20are\x20no\x20ordering\x20guarantees\x20on\x20the\
+    \x20order\x20of\x20sockets.\n\n\x0c\n\x05\x04\0\x02\x04\x04\x12\x037\x02\
+    \n\n\x0c\n\x05\x04\0\x02\x04\x06\x12\x037\x0b\x14\n\x0c\n\x05\x04\0\x02\
+    \x04\x01\x12\x037\x15\x1f\n\x0c\n\x05\x04\0\x02\x04\x03\x12\x037\"#\n\
+    \x8a\x01\n\x02\x04\x01\x12\x04<\0P\x01\x1a~\x20Subchannel\x20is\x20a\x20\
+    logical\x20grouping\x20of\x20channels,\x20subchannels,\x20and\x20sockets\
+    .\n\x20A\x20subchannel\x20is\x20load\x20balanced\x20over\x20by\x20it's\
+    \x20ancestor\n\n\n\n\x03\x04\x01\x01\x12\x03<\x08\x12\n/\n\x04\x04\x01\
+    \x02\0\x12\x03>\x02\x18\x1a\"\x20The\x20identifier\x20for\x20this\x20cha\
+    nnel.\n\n\x0c\n\x05\x04\x01\x02\0\x06\x12\x03>\x02\x0f\n\x0c\n\x05\x04\
+    \x01\x02\0\x01\x12\x03>\x10\x13\n\x0c\n\x05\x04\x01\x02\0\x03\x12\x03>\
+    \x16\x17\nq\n\x04\x04\x01\x02\x01\x12\x03@\x02\x17\x1a\x20\x20Data\x20sp\
+    ecific\x20to\x20this\x20channel.\n\"B\x20At\x20most\x20one\x20of\x20'cha\
+    nnel_ref+subchannel_ref'\x20and\x20'socket'\x20is\x20set.\n\n\x0c\n\x05\
+    \x04\x01\x02\x01\x06\x12\x03@\x02\r\n\x0c\n\x05\x04\x01\x02\x01\x01\x12\
+    \x03@\x0e\x12\n\x0c\n\x05\x04\x01\x02\x01\x03\x12\x03@\x15\x16\n\xbf\x01\
+    \n\x04\x04\x01\x02\x02\x12\x03F\x02&\x1a\xb1\x01\x20There\x20are\x20no\
+    \x20ordering\x20guarantees\x20on\x20the\x20order\x20of\x20channel\x20ref\
+    s.\n\x20There\x20may\x20not\x20be\x20cycles\x20in\x20the\x20ref\x20graph\
+    .\n\x20A\x20channel\x20ref\x20may\x20be\x20present\x20in\x20more\x20than\
+    \x20one\x20channel\x20or\x20subchannel.\n\n\x0c\n\x05\x04\x01\x02\x02\
+    \x04\x12\x03F\x02\n\n\x0c\n\x05\x04\x01\x02\x02\x06\x12\x03F\x0b\x15\n\
+    \x0c\n\x05\x04\x01\x02\x02\x01\x12\x03F\x16!\n\x0c\n\x05\x04\x01\x02\x02\
+    \x03\x12\x03F$%\n\x88\x02\n\x04\x04\x01\x02\x03\x12\x03L\x02,\x1a\xfa\
+    \x01\x20At\x20most\x20one\x20of\x20'channel_ref+subchannel_ref'\x20and\
+    \x20'socket'\x20is\x20set.\n\x20There\x20are\x20no\x20ordering\x20guaran\
+    tees\x20on\x20the\x20order\x20of\x20subchannel\x20refs.\n\x20There\x20ma\
+    y\x20not\x20be\x20cycles\x20in\x20the\x20ref\x20graph.\n\x20A\x20sub\x20\
+    channel\x20ref\x20may\x20be\x20present\x20in\x20more\x20than\x20one\x20c\
+    hannel\x20or\x20subchannel.\n\n\x0c\n\x05\x04\x01\x02\x03\x04\x12\x03L\
+    \x02\n\n\x0c\n\x05\x04\x01\x02\x03\x06\x12\x03L\x0b\x18\n\x0c\n\x05\x04\
+    \x01\x02\x03\x01\x12\x03L\x19'\n\x0c\n\x05\x04\x01\x02\x03\x03\x12\x03L*\
+    +\nH\n\x04\x04\x01\x02\x04\x12\x03O\x02$\x1a;\x20There\x20are\x20no\x20o\
+    rdering\x20guarantees\x20on\x20the\x20order\x20of\x20sockets.\n\n\x0c\n\
+    \x05\x04\x01\x02\x04\x04\x12\x03O\x02\n\n\x0c\n\x05\x04\x01\x02\x04\x06\
+    \x12\x03O\x0b\x14\n\x0c\n\x05\x04\x01\x02\x04\x01\x12\x03O\x15\x1f\n\x0c\
+    \n\x05\x04\x01\x02\x04\x03\x12\x03O\"#\n\x95\x01\n\x02\x04\x02\x12\x04T\
+    \0^\x01\x1a\x88\x01\x20These\x20come\x20from\x20the\x20specified\x20stat\
+    es\x20in\x20this\x20document:\n\x20https://github.com/grpc/grpc/blob/mas\
+    ter/doc/connectivity-semantics-and-api.md\n\n\n\n\x03\x04\x02\x01\x12\
+    \x03T\x08\x20\n\x0c\n\x04\x04\x02\x04\0\x12\x04U\x02\\\x03\n\x0c\n\x05\
+    \x04\x02\x04\0\x01\x12\x03U\x07\x0c\n\r\n\x06\x04\x02\x04\0\x02\0\x12\
+    \x03V\x04\x10\n\x0e\n\x07\x04\x02\x04\0\x02\0\x01\x12\x03V\x04\x0b\n\x0e\
+    \n\x07\x04\x02\x04\0\x02\0\x02\x12\x03V\x0e\x0f\n\r\n\x06\x04\x02\x04\0\
+    \x02\x01\x12\x03W\x04\r\n\x0e\n\x07\x04\x02\x04\0\x02\x01\x01\x12\x03W\
+    \x04\x08\n\x0e\n\x07\x04\x02\x04\0\x02\x01\x02\x12\x03W\x0b\x0c\n\r\n\
+    \x06\x04\x02\x04\0\x02\x02\x12\x03X\x04\x13\n\x0e\n\x07\x04\x02\x04\0\
+    \x02\x02\x01\x12\x03X\x04\x0e\n\x0e\n\x07\x04\x02\x04\0\x02\x02\x02\x12\
+    \x03X\x11\x12\n\r\n\x06\x04\x02\x04\0\x02\x03\x12\x03Y\x04\x0e\n\x0e\n\
+    \x07\x04\x02\x04\0\x02\x03\x01\x12\x03Y\x04\t\n\x0e\n\x07\x04\x02\x04\0\
+    \x02\x03\x02\x12\x03Y\x0c\r\n\r\n\x06\x04\x02\x04\0\x02\x04\x12\x03Z\x04\
+    \x1a\n\x0e\n\x07\x04\x02\x04\0\x02\x04\x01\x12\x03Z\x04\x15\n\x0e\n\x07\
+    \x04\x02\x04\0\x02\x04\x02\x12\x03Z\x18\x19\n\r\n\x06\x04\x02\x04\0\x02\
+    \x05\x12\x03[\x04\x11\n\x0e\n\x07\x04\x02\x04\0\x02\x05\x01\x12\x03[\x04\
+    \x0c\n\x0e\n\x07\x04\x02\x04\0\x02\x05\x02\x12\x03[\x0f\x10\n\x0b\n\x04\
+    \x04\x02\x02\0\x12\x03]\x02\x12\n\x0c\n\x05\x04\x02\x02\0\x06\x12\x03]\
+    \x02\x07\n\x0c\n\x05\x04\x02\x02\0\x01\x12\x03]\x08\r\n\x0c\n\x05\x04\
+    \x02\x02\0\x03\x12\x03]\x10\x11\nO\n\x02\x04\x03\x12\x04a\0u\x01\x1aC\
+    \x20Channel\x20data\x20is\x20data\x20related\x20to\x20a\x20specific\x20C\
+    hannel\x20or\x20Subchannel.\n\n\n\n\x03\x04\x03\x01\x12\x03a\x08\x13\nm\
+    \n\x04\x04\x03\x02\0\x12\x03d\x02%\x1a`\x20The\x20connectivity\x20state\
+    \x20of\x20the\x20channel\x20or\x20subchannel.\x20\x20Implementations\n\
+    \x20should\x20always\x20set\x20this.\n\n\x0c\n\x05\x04\x03\x02\0\x06\x12\
+    \x03d\x02\x1a\n\x0c\n\x05\x04\x03\x02\0\x01\x12\x03d\x1b\x20\n\x0c\n\x05\
+    \x04\x03\x02\0\x03\x12\x03d#$\nU\n\x04\x04\x03\x02\x01\x12\x03g\x02\x14\
+    \x1aH\x20The\x20target\x20this\x20channel\x20originally\x20tried\x20to\
+    \x20connect\x20to.\x20\x20May\x20be\x20absent\n\n\x0c\n\x05\x04\x03\x02\
+    \x01\x05\x12\x03g\x02\x08\n\x0c\n\x05\x04\x03\x02\x01\x01\x12\x03g\t\x0f\
+    \n\x0c\n\x05\x04\x03\x02\x01\x03\x12\x03g\x12\x13\nG\n\x04\x04\x03\x02\
+    \x02\x12\x03j\x02\x19\x1a:\x20A\x20trace\x20of\x20recent\x20events\x20on\
+    \x20the\x20channel.\x20\x20May\x20be\x20absent.\n\n\x0c\n\x05\x04\x03\
+    \x02\x02\x06\x12\x03j\x02\x0e\n\x0c\n\x05\x04\x03\x02\x02\x01\x12\x03j\
+    \x0f\x14\n\x0c\n\x05\x04\x03\x02\x02\x03\x12\x03j\x17\x18\n9\n\x04\x04\
+    \x03\x02\x03\x12\x03m\x02\x1a\x1a,\x20The\x20number\x20of\x20calls\x20st\
+    arted\x20on\x20the\x20channel\n\n\x0c\n\x05\x04\x03\x02\x03\x05\x12\x03m\
+    \x02\x07\n\x0c\n\x05\x04\x03\x02\x03\x01\x12\x03m\x08\x15\n\x0c\n\x05\
+    \x04\x03\x02\x03\x03\x12\x03m\x18\x19\nH\n\x04\x04\x03\x02\x04\x12\x03o\
+    \x02\x1c\x1a;\x20The\x20number\x20of\x20calls\x20that\x20have\x20complet\
+    ed\x20with\x20an\x20OK\x20status\n\n\x0c\n\x05\x04\x03\x02\x04\x05\x12\
+    \x03o\x02\x07\n\x0c\n\x05\x04\x03\x02\x04\x01\x12\x03o\x08\x17\n\x0c\n\
+    \x05\x04\x03\x02\x04\x03\x12\x03o\x1a\x1b\nK\n\x04\x04\x03\x02\x05\x12\
+    \x03q\x02\x19\x1a>\x20The\x20number\x20of\x20calls\x20that\x20have\x20co\
+    mpleted\x20with\x20a\x20non-OK\x20status\n\n\x0c\n\x05\x04\x03\x02\x05\
+    \x05\x12\x03q\x02\x07\n\x0c\n\x05\x04\x03\x02\x05\x01\x12\x03q\x08\x14\n\
+    \x0c\n\x05\x04\x03\x02\x05\x03\x12\x03q\x17\x18\n?\n\x04\x04\x03\x02\x06\
+    \x12\x03t\x02<\x1a2\x20The\x20last\x20time\x20a\x20call\x20was\x20starte\
+    d\x20on\x20the\x20channel.\n\n\x0c\n\x05\x04\x03\x02\x06\x06\x12\x03t\
+    \x02\x1b\n\x0c\n\x05\x04\x03\x02\x06\x01\x12\x03t\x1c7\n\x0c\n\x05\x04\
+    \x03\x02\x06\x03\x12\x03t:;\n\xa0\x01\n\x02\x04\x04\x12\x05y\0\x8f\x01\
+    \x01\x1a\x92\x01\x20A\x20trace\x20event\x20is\x20an\x20interesting\x20th\
+    ing\x20that\x20happened\x20to\x20a\x20channel\x20or\n\x20subchannel,\x20\
+    such\x20as\x20creation,\x20address\x20resolution,\x20subchannel\x20creat\
+    ion,\x20etc.\n\n\n\n\x03\x04\x04\x01\x12\x03y\x08\x19\n3\n\x04\x04\x04\
+    \x02\0\x12\x03{\x02\x19\x1a&\x20High\x20level\x20description\x20of\x20th\
+    e\x20event.\n\n\x0c\n\x05\x04\x04\x02\0\x05\x12\x03{\x02\x08\n\x0c\n\x05\
+    \x04\x04\x02\0\x01\x12\x03{\t\x14\n\x0c\n\x05\x04\x04\x02\0\x03\x12\x03{\
+    \x17\x18\n?\n\x04\x04\x04\x04\0\x12\x05}\x02\x82\x01\x03\x1a0\x20The\x20\
+    supported\x20severity\x20levels\x20of\x20trace\x20events.\n\n\x0c\n\x05\
+    \x04\x04\x04\0\x01\x12\x03}\x07\x0f\n\r\n\x06\x04\x04\x04\0\x02\0\x12\
+    \x03~\x04\x13\n\x0e\n\x07\x04\x04\x04\0\x02\0\x01\x12\x03~\x04\x0e\n\x0e\
+    \n\x07\x04\x04\x04\0\x02\0\x02\x12\x03~\x11\x12\n\r\n\x06\x04\x04\x04\0\
+    \x02\x01\x12\x03\x7f\x04\x10\n\x0e\n\x07\x04\x04\x04\0\x02\x01\x01\x12\
+    \x03\x7f\x04\x0b\n\x0e\n\x07\x04\x04\x04\0\x02\x01\x02\x12\x03\x7f\x0e\
+    \x0f\n\x0e\n\x06\x04\x04\x04\0\x02\x02\x12\x04\x80\x01\x04\x13\n\x0f\n\
+    \x07\x04\x04\x04\0\x02\x02\x01\x12\x04\x80\x01\x04\x0e\n\x0f\n\x07\x04\
+    \x04\x04\0\x02\x02\x02\x12\x04\x80\x01\x11\x12\n\x0e\n\x06\x04\x04\x04\0\
+    \x02\x03\x12\x04\x81\x01\x04\x11\n\x0f\n\x07\x04\x04\x04\0\x02\x03\x01\
+    \x12\x04\x81\x01\x04\x0c\n\x0f\n\x07\x04\x04\x04\0\x02\x03\x02\x12\x04\
+    \x81\x01\x0f\x10\n/\n\x04\x04\x04\x02\x01\x12\x04\x84\x01\x02\x18\x1a!\
+    \x20the\x20severity\x20of\x20the\x20trace\x20event\n\n\r\n\x05\x04\x04\
+    \x02\x01\x06\x12\x04\x84\x01\x02\n\n\r\n\x05\x04\x04\x02\x01\x01\x12\x04\
+    \x84\x01\x0b\x13\n\r\n\x05\x04\x04\x02\x01\x03\x12\x04\x84\x01\x16\x17\n\
+    )\n\x04\x04\x04\x02\x02\x12\x04\x86\x01\x02*\x1a\x1b\x20When\x20this\x20\
+    event\x20occurred.\n\n\r\n\x05\x04\x04\x02\x02\x06\x12\x04\x86\x01\x02\
+    \x1b\n\r\n\x05\x04\x04\x02\x02\x01\x12\x04\x86\x01\x1c%\n\r\n\x05\x04\
+    \x04\x02\x02\x03\x12\x04\x86\x01()\n\xdd\x01\n\x04\x04\x04\x08\0\x12\x06\
+    \x8b\x01\x02\x8e\x01\x03\x1a\xcc\x01\x20ref\x20of\x20referenced\x20chann\
+    el\x20or\x20subchannel.\n\x20Optional,\x20only\x20present\x20if\x20this\
+    \x20event\x20refers\x20to\x20a\x20child\x20object.\x20For\x20example,\n\
+    \x20this\x20field\x20would\x20be\x20filled\x20if\x20this\x20trace\x20eve\
+    nt\x20was\x20for\x20a\x20subchannel\x20being\n\x20created.\n\n\r\n\x05\
+    \x04\x04\x08\0\x01\x12\x04\x8b\x01\x08\x11\n\x0c\n\x04\x04\x04\x02\x03\
+    \x12\x04\x8c\x01\x04\x1f\n\r\n\x05\x04\x04\x02\x03\x06\x12\x04\x8c\x01\
+    \x04\x0e\n\r\n\x05\x04\x04\x02\x03\x01\x12\x04\x8c\x01\x0f\x1a\n\r\n\x05\
+    \x04\x04\x02\x03\x03\x12\x04\x8c\x01\x1d\x1e\n\x0c\n\x04\x04\x04\x02\x04\
+    \x12\x04\x8d\x01\x04%\n\r\n\x05\x04\x04\x02\x04\x06\x12\x04\x8d\x01\x04\
+    \x11\n\r\n\x05\x04\x04\x02\x04\x01\x12\x04\x8d\x01\x12\x20\n\r\n\x05\x04\
+    \x04\x02\x04\x03\x12\x04\x8d\x01#$\n\\\n\x02\x04\x05\x12\x06\x92\x01\0\
+    \x9b\x01\x01\x1aN\x20ChannelTrace\x20represents\x20the\x20recent\x20even\
+    ts\x20that\x20have\x20occurred\x20on\x20the\x20channel.\n\n\x0b\n\x03\
+    \x04\x05\x01\x12\x04\x92\x01\x08\x14\n\xb5\x01\n\x04\x04\x05\x02\0\x12\
+    \x04\x96\x01\x02\x1e\x1a\xa6\x01\x20Number\x20of\x20events\x20ever\x20lo\
+    gged\x20in\x20this\x20tracing\x20object.\x20This\x20can\x20differ\x20fro\
+    m\n\x20events.size()\x20because\x20events\x20can\x20be\x20overwritten\
+    \x20or\x20garbage\x20collected\x20by\n\x20implementations.\n\n\r\n\x05\
+    \x04\x05\x02\0\x05\x12\x04\x96\x01\x02\x07\n\r\n\x05\x04\x05\x02\0\x01\
+    \x12\x04\x96\x01\x08\x19\n\r\n\x05\x04\x05\x02\0\x03\x12\x04\x96\x01\x1c\
+    \x1d\n3\n\x04\x04\x05\x02\x01\x12\x04\x98\x01\x023\x1a%\x20Time\x20that\
+    \x20this\x20channel\x20was\x20created.\n\n\r\n\x05\x04\x05\x02\x01\x06\
+    \x12\x04\x98\x01\x02\x1b\n\r\n\x05\x04\x05\x02\x01\x01\x12\x04\x98\x01\
+    \x1c.\n\r\n\x05\x04\x05\x02\x01\x03\x12\x04\x98\x0112\nB\n\x04\x04\x05\
+    \x02\x02\x12\x04\x9a\x01\x02(\x1a4\x20List\x20of\x20events\x20that\x20ha\
+    ve\x20occurred\x20on\x20this\x20channel.\n\n\r\n\x05\x04\x05\x02\x02\x04\
+    \x12\x04\x9a\x01\x02\n\n\r\n\x05\x04\x05\x02\x02\x06\x12\x04\x9a\x01\x0b\
+    \x1c\n\r\n\x05\x04\x05\x02\x02\x01\x12\x04\x9a\x01\x1d#\n\r\n\x05\x04\
+    \x05\x02\x02\x03\x12\x04\x9a\x01&'\n7\n\x02\x04\x06\x12\x06\x9e\x01\0\
+    \xa5\x01\x01\x1a)\x20ChannelRef\x20is\x20a\x20reference\x20to\x20a\x20Ch\
+    annel.\n\n\x0b\n\x03\x04\x06\x01\x12\x04\x9e\x01\x08\x12\nT\n\x04\x04\
+    \x06\x02\0\x12\x04\xa0\x01\x02\x17\x1aF\x20The\x20globally\x20unique\x20\
+    id\x20for\x20this\x20channel.\x20\x20Must\x20be\x20a\x20positive\x20numb\
+    er.\n\n\r\n\x05\x04\x06\x02\0\x05\x12\x04\xa0\x01\x02\x07\n\r\n\x05\x04\
+    \x06\x02\0\x01\x12\x04\xa0\x01\x08\x12\n\r\n\x05\x04\x06\x02\0\x03\x12\
+    \x04\xa0\x01\x15\x16\n=\n\x04\x04\x06\x02\x01\x12\x04\xa2\x01\x02\x12\
+    \x1a/\x20An\x20optional\x20name\x20associated\x20with\x20the\x20channel.\
+    \n\n\r\n\x05\x04\x06\x02\x01\x05\x12\x04\xa2\x01\x02\x08\n\r\n\x05\x04\
+    \x06\x02\x01\x01\x12\x04\xa2\x01\t\r\n\r\n\x05\x04\x06\x02\x01\x03\x12\
+    \x04\xa2\x01\x10\x11\nE\n\x03\x04\x06\t\x12\x04\xa4\x01\x02\x1c\x1a8\x20\
+    Intentionally\x20don't\x20use\x20field\x20numbers\x20from\x20other\x20re\
+    fs.\n\n\x0c\n\x04\x04\x06\t\0\x12\x04\xa4\x01\x0b\x0c\n\r\n\x05\x04\x06\
+    \t\0\x01\x12\x04\xa4\x01\x0b\x0c\n\r\n\x05\x04\x06\t\0\x02\x12\x04\xa4\
+    \x01\x0b\x0c\n\x0c\n\x04\x04\x06\t\x01\x12\x04\xa4\x01\x0e\x0f\n\r\n\x05\
+    \x04\x06\t\x01\x01\x12\x04\xa4\x01\x0e\x0f\n\r\n\x05\x04\x06\t\x01\x02\
+    \x12\x04\xa4\x01\x0e\x0f\n\x0c\n\x04\x04\x06\t\x02\x12\x04\xa4\x01\x11\
+    \x12\n\r\n\x05\x04\x06\t\x02\x01\x12\x04\xa4\x01\x11\x12\n\r\n\x05\x04\
+    \x06\t\x02\x02\x12\x04\xa4\x01\x11\x12\n\x0c\n\x04\x04\x06\t\x03\x12\x04\
+    \xa4\x01\x14\x15\n\r\n\x05\x04\x06\t\x03\x01\x12\x04\xa4\x01\x14\x15\n\r\
+    \n\x05\x04\x06\t\x03\x02\x12\x04\xa4\x01\x14\x15\n\x0c\n\x04\x04\x06\t\
+    \x04\x12\x04\xa4\x01\x17\x18\n\r\n\x05\x04\x06\t\x04\x01\x12\x04\xa4\x01\
+    \x17\x18\n\r\n\x05\x04\x06\t\x04\x02\x12\x04\xa4\x01\x17\x18\n\x0c\n\x04\
+    \x04\x06\t\x05\x12\x04\xa4\x01\x1a\x1b\n\r\n\x05\x04\x06\t\x05\x01\x12\
+    \x04\xa4\x01\x1a\x1b\n\r\n\x05\x04\x06\t\x05\x02\x12\x04\xa4\x01\x1a\x1b\
+    \n=\n\x02\x04\x07\x12\x06\xa8\x01\0\xaf\x01\x01\x1a/\x20SubchannelRef\
+    \x20is\x20a\x20reference\x20to\x20a\x20Subchannel.\n\n\x0b\n\x03\x04\x07\
+    \x01\x12\x04\xa8\x01\x08\x15\nW\n\x04\x04\x07\x02\0\x12\x04\xaa\x01\x02\
+    \x1a\x1aI\x20The\x20globally\x20unique\x20id\x20for\x20this\x20subchanne\
+    l.\x20\x20Must\x20be\x20a\x20positive\x20number.\n\n\r\n\x05\x04\x07\x02\
+    \0\x05\x12\x04\xaa\x01\x02\x07\n\r\n\x05\x04\x07\x02\0\x01\x12\x04\xaa\
+    \x01\x08\x15\n\r\n\x05\x04\x07\x02\0\x03\x12\x04\xaa\x01\x18\x19\n@\n\
+    \x04\x04\x07\x02\x01\x12\x04\xac\x01\x02\x12\x1a2\x20An\x20optional\x20n\
+    ame\x20associated\x20with\x20the\x20subchannel.\n\n\r\n\x05\x04\x07\x02\
+    \x01\x05\x12\x04\xac\x01\x02\x08\n\r\n\x05\x04\x07\x02\x01\x01\x12\x04\
+    \xac\x01\t\r\n\r\n\x05\x04\x07\x02\x01\x03\x12\x04\xac\x01\x10\x11\nE\n\
+    \x03\x04\x07\t\x12\x04\xae\x01\x02\x1c\x1a8\x20Intentionally\x20don't\
+    \x20use\x20field\x20numbers\x20from\x20other\x20refs.\n\n\x0c\n\x04\x04\
+    \x07\t\0\x12\x04\xae\x01\x0b\x0c\n\r\n\x05\x04\x07\t\0\x01\x12\x04\xae\
+    \x01\x0b\x0c\n\r\n\x05\x04\x07\t\0\x02\x12\x04\xae\x01\x0b\x0c\n\x0c\n\
+    \x04\x04\x07\t\x01\x12\x04\xae\x01\x0e\x0f\n\r\n\x05\x04\x07\t\x01\x01\
+    \x12\x04\xae\x01\x0e\x0f\n\r\n\x05\x04\x07\t\x01\x02\x12\x04\xae\x01\x0e\
+    \x0f\n\x0c\n\x04\x04\x07\t\x02\x12\x04\xae\x01\x11\x12\n\r\n\x05\x04\x07\
+    \t\x02\x01\x12\x04\xae\x01\x11\x12\n\r\n\x05\x04\x07\t\x02\x02\x12\x04\
+    \xae\x01\x11\x12\n\x0c\n\x04\x04\x07\t\x03\x12\x04\xae\x01\x14\x15\n\r\n\
+    \x05\x04\x07\t\x03\x01\x12\x04\xae\x01\x14\x15\n\r\n\x05\x04\x07\t\x03\
+    \x02\x12\x04\xae\x01\x14\x15\n\x0c\n\x04\x04\x07\t\x04\x12\x04\xae\x01\
+    \x17\x18\n\r\n\x05\x04\x07\t\x04\x01\x12\x04\xae\x01\x17\x18\n\r\n\x05\
+    \x04\x07\t\x04\x02\x12\x04\xae\x01\x17\x18\n\x0c\n\x04\x04\x07\t\x05\x12\
+    \x04\xae\x01\x1a\x1b\n\r\n\x05\x04\x07\t\x05\x01\x12\x04\xae\x01\x1a\x1b\
+    \n\r\n\x05\x04\x07\t\x05\x02\x12\x04\xae\x01\x1a\x1b\n5\n\x02\x04\x08\
+    \x12\x06\xb2\x01\0\xb9\x01\x01\x1a'\x20SocketRef\x20is\x20a\x20reference\
+    \x20to\x20a\x20Socket.\n\n\x0b\n\x03\x04\x08\x01\x12\x04\xb2\x01\x08\x11\
+    \nS\n\x04\x04\x08\x02\0\x12\x04\xb4\x01\x02\x16\x1aE\x20The\x20globally\
+    \x20unique\x20id\x20for\x20this\x20socket.\x20\x20Must\x20be\x20a\x20pos\
+    itive\x20number.\n\n\r\n\x05\x04\x08\x02\0\x05\x12\x04\xb4\x01\x02\x07\n\
+    \r\n\x05\x04\x08\x02\0\x01\x12\x04\xb4\x01\x08\x11\n\r\n\x05\x04\x08\x02\
+    \0\x03\x12\x04\xb4\x01\x14\x15\n<\n\x04\x04\x08\x02\x01\x12\x04\xb6\x01\
+    \x02\x12\x1a.\x20An\x20optional\x20name\x20associated\x20with\x20the\x20\
+    socket.\n\n\r\n\x05\x04\x08\x02\x01\x05\x12\x04\xb6\x01\x02\x08\n\r\n\
+    \x05\x04\x08\x02\x01\x01\x12\x04\xb6\x01\t\r\n\r\n\x05\x04\x08\x02\x01\
+    \x03\x12\x04\xb6\x01\x10\x11\nE\n\x03\x04\x08\t\x12\x04\xb8\x01\x02\x1c\
+    \x1a8\x20Intentionally\x20don't\x20use\x20field\x20numbers\x20from\x20ot\
+    her\x20refs.\n\n\x0c\n\x04\x04\x08\t\0\x12\x04\xb8\x01\x0b\x0c\n\r\n\x05\
+    \x04\x08\t\0\x01\x12\x04\xb8\x01\x0b\x0c\n\r\n\x05\x04\x08\t\0\x02\x12\
+    \x04\xb8\x01\x0b\x0c\n\x0c\n\x04\x04\x08\t\x01\x12\x04\xb8\x01\x0e\x0f\n\
+    \r\n\x05\x04\x08\t\x01\x01\x12\x04\xb8\x01\x0e\x0f\n\r\n\x05\x04\x08\t\
+    \x01\x02\x12\x04\xb8\x01\x0e\x0f\n\x0c\n\x04\x04\x08\t\x02\x12\x04\xb8\
+    \x01\x11\x12\n\r\n\x05\x04\x08\t\x02\x01\x12\x04\xb8\x01\x11\x12\n\r\n\
+    \x05\x04\x08\t\x02\x02\x12\x04\xb8\x01\x11\x12\n\x0c\n\x04\x04\x08\t\x03\
+    \x12\x04\xb8\x01\x14\x15\n\r\n\x05\x04\x08\t\x03\x01\x12\x04\xb8\x01\x14\
+    \x15\n\r\n\x05\x04\x08\t\x03\x02\x12\x04\xb8\x01\x14\x15\n\x0c\n\x04\x04\
+    \x08\t\x04\x12\x04\xb8\x01\x17\x18\n\r\n\x05\x04\x08\t\x04\x01\x12\x04\
+    \xb8\x01\x17\x18\n\r\n\x05\x04\x08\t\x04\x02\x12\x04\xb8\x01\x17\x18\n\
+    \x0c\n\x04\x04\x08\t\x05\x12\x04\xb8\x01\x1a\x1b\n\r\n\x05\x04\x08\t\x05\
+    \x01\x12\x04\xb8\x01\x1a\x1b\n\r\n\x05\x04\x08\t\x05\x02\x12\x04\xb8\x01\
+    \x1a\x1b\n5\n\x02\x04\t\x12\x06\xbc\x01\0\xc3\x01\x01\x1a'\x20ServerRef\
+    \x20is\x20a\x20reference\x20to\x20a\x20Server.\n\n\x0b\n\x03\x04\t\x01\
+    \x12\x04\xbc\x01\x08\x11\nY\n\x04\x04\t\x02\0\x12\x04\xbe\x01\x02\x16\
+    \x1aK\x20A\x20globally\x20unique\x20identifier\x20for\x20this\x20server.\
+    \x20\x20Must\x20be\x20a\x20positive\x20number.\n\n\r\n\x05\x04\t\x02\0\
+    \x05\x12\x04\xbe\x01\x02\x07\n\r\n\x05\x04\t\x02\0\x01\x12\x04\xbe\x01\
+    \x08\x11\n\r\n\x05\x04\t\x02\0\x03\x12\x04\xbe\x01\x14\x15\n<\n\x04\x04\
+    \t\x02\x01\x12\x04\xc0\x01\x02\x12\x1a.\x20An\x20optional\x20name\x20ass\
+    ociated\x20with\x20the\x20server.\n\n\r\n\x05\x04\t\x02\x01\x05\x12\x04\
+    \xc0\x01\x02\x08\n\r\n\x05\x04\t\x02\x01\x01\x12\x04\xc0\x01\t\r\n\r\n\
+    \x05\x04\t\x02\x01\x03\x12\x04\xc0\x01\x10\x11\nE\n\x03\x04\t\t\x12\x04\
+    \xc2\x01\x02\x1c\x1a8\x20Intentionally\x20don't\x20use\x20field\x20numbe\
+    rs\x20from\x20other\x20refs.\n\n\x0c\n\x04\x04\t\t\0\x12\x04\xc2\x01\x0b\
+    \x0c\n\r\n\x05\x04\t\t\0\x01\x12\x04\xc2\x01\x0b\x0c\n\r\n\x05\x04\t\t\0\
+    \x02\x12\x04\xc2\x01\x0b\x0c\n\x0c\n\x04\x04\t\t\x01\x12\x04\xc2\x01\x0e\
+    \x0f\n\r\n\x05\x04\t\t\x01\x01\x12\x04\xc2\x01\x0e\x0f\n\r\n\x05\x04\t\t\
+    \x01\x02\x12\x04\xc2\x01\x0e\x0f\n\x0c\n\x04\x04\t\t\x02\x12\x04\xc2\x01\
+    \x11\x12\n\r\n\x05\x04\t\t\x02\x01\x12\x04\xc2\x01\x11\x12\n\r\n\x05\x04\
+    \t\t\x02\x02\x12\x04\xc2\x01\x11\x12\n\x0c\n\x04\x04\t\t\x03\x12\x04\xc2\
+    \x01\x14\x15\n\r\n\x05\x04\t\t\x03\x01\x12\x04\xc2\x01\x14\x15\n\r\n\x05\
+    \x04\t\t\x03\x02\x12\x04\xc2\x01\x14\x15\n\x0c\n\x04\x04\t\t\x04\x12\x04\
+    \xc2\x01\x17\x18\n\r\n\x05\x04\t\t\x04\x01\x12\x04\xc2\x01\x17\x18\n\r\n\
+    \x05\x04\t\t\x04\x02\x12\x04\xc2\x01\x17\x18\n\x0c\n\x04\x04\t\t\x05\x12\
+    \x04\xc2\x01\x1a\x1b\n\r\n\x05\x04\t\t\x05\x01\x12\x04\xc2\x01\x1a\x1b\n\
+    \r\n\x05\x04\t\t\x05\x02\x12\x04\xc2\x01\x1a\x1b\ng\n\x02\x04\n\x12\x06\
+    \xc7\x01\0\xd0\x01\x01\x1aY\x20Server\x20represents\x20a\x20single\x20se\
+    rver.\x20\x20There\x20may\x20be\x20multiple\x20servers\x20in\x20a\x20sin\
+    gle\n\x20program.\n\n\x0b\n\x03\x04\n\x01\x12\x04\xc7\x01\x08\x0e\nA\n\
+    \x04\x04\n\x02\0\x12\x04\xc9\x01\x02\x14\x1a3\x20The\x20identifier\x20fo\
+    r\x20a\x20Server.\x20\x20This\x20should\x20be\x20set.\n\n\r\n\x05\x04\n\
+    \x02\0\x06\x12\x04\xc9\x01\x02\x0b\n\r\n\x05\x04\n\x02\0\x01\x12\x04\xc9\
+    \x01\x0c\x0f\n\r\n\x05\x04\n\x02\0\x03\x12\x04\xc9\x01\x12\x13\n2\n\x04\
+    \x04\n\x02\x01\x12\x04\xcb\x01\x02\x16\x1a$\x20The\x20associated\x20data\
+    \x20of\x20the\x20Server.\n\n\r\n\x05\x04\n\x02\x01\x06\x12\x04\xcb\x01\
+    \x02\x0c\n\r\n\x05\x04\n\x02\x01\x01\x12\x04\xcb\x01\r\x11\n\r\n\x05\x04\
+    \n\x02\x01\x03\x12\x04\xcb\x01\x14\x15\nu\n\x04\x04\n\x02\x02\x12\x04\
+    \xcf\x01\x02'\x1ag\x20The\x20sockets\x20that\x20the\x20server\x20is\x20l\
+    istening\x20on.\x20\x20There\x20are\x20no\x20ordering\n\x20guarantees.\
+    \x20\x20This\x20may\x20be\x20absent.\n\n\r\n\x05\x04\n\x02\x02\x04\x12\
+    \x04\xcf\x01\x02\n\n\r\n\x05\x04\n\x02\x02\x06\x12\x04\xcf\x01\x0b\x14\n\
+    \r\n\x05\x04\n\x02\x02\x01\x12\x04\xcf\x01\x15\"\n\r\n\x05\x04\n\x02\x02\
+    \x03\x12\x04\xcf\x01%&\n9\n\x02\x04\x0b\x12\x06\xd3\x01\0\xe0\x01\x01\
+    \x1a+\x20ServerData\x20is\x20data\x20for\x20a\x20specific\x20Server.\n\n\
+    \x0b\n\x03\x04\x0b\x01\x12\x04\xd3\x01\x08\x12\nG\n\x04\x04\x0b\x02\0\
+    \x12\x04\xd5\x01\x02\x19\x1a9\x20A\x20trace\x20of\x20recent\x20events\
+    \x20on\x20the\x20server.\x20\x20May\x20be\x20absent.\n\n\r\n\x05\x04\x0b\
+    \x02\0\x06\x12\x04\xd5\x01\x02\x0e\n\r\n\x05\x04\x0b\x02\0\x01\x12\x04\
+    \xd5\x01\x0f\x14\n\r\n\x05\x04\x0b\x02\0\x03\x12\x04\xd5\x01\x17\x18\nB\
+    \n\x04\x04\x0b\x02\x01\x12\x04\xd8\x01\x02\x1a\x1a4\x20The\x20number\x20\
+    of\x20incoming\x20calls\x20started\x20on\x20the\x20server\n\n\r\n\x05\
+    \x04\x0b\x02\x01\x05\x12\x04\xd8\x01\x02\x07\n\r\n\x05\x04\x0b\x02\x01\
+    \x01\x12\x04\xd8\x01\x08\x15\n\r\n\x05\x04\x0b\x02\x01\x03\x12\x04\xd8\
+    \x01\x18\x19\nR\n\x04\x04\x0b\x02\x02\x12\x04\xda\x01\x02\x1c\x1aD\x20Th\
+    e\x20number\x20of\x20incoming\x20calls\x20that\x20have\x20completed\x20w\
+    ith\x20an\x20OK\x20status\n\n\r\n\x05\x04\x0b\x02\x02\x05\x12\x04\xda\
+    \x01\x02\x07\n\r\n\x05\x04\x0b\x02\x02\x01\x12\x04\xda\x01\x08\x17\n\r\n\
+    \x05\x04\x0b\x02\x02\x03\x12\x04\xda\x01\x1a\x1b\nW\n\x04\x04\x0b\x02\
+    \x03\x12\x04\xdc\x01\x02\x19\x1aI\x20The\x20number\x20of\x20incoming\x20\
+    calls\x20that\x20have\x20a\x20completed\x20with\x20a\x20non-OK\x20status\
+    \n\n\r\n\x05\x04\x0b\x02\x03\x05\x12\x04\xdc\x01\x02\x07\n\r\n\x05\x04\
+    \x0b\x02\x03\x01\x12\x04\xdc\x01\x08\x14\n\r\n\x05\x04\x0b\x02\x03\x03\
+    \x12\x04\xdc\x01\x17\x18\n?\n\x04\x04\x0b\x02\x04\x12\x04\xdf\x01\x02<\
+    \x1a1\x20The\x20last\x20time\x20a\x20call\x20was\x20started\x20on\x20the\
+    \x20server.\n\n\r\n\x05\x04\x0b\x02\x04\x06\x12\x04\xdf\x01\x02\x1b\n\r\
+    \n\x05\x04\x0b\x02\x04\x01\x12\x04\xdf\x01\x1c7\n\r\n\x05\x04\x0b\x02\
+    \x04\x03\x12\x04\xdf\x01:;\nN\n\x02\x04\x0c\x12\x06\xe3\x01\0\xf4\x01\
+    \x01\x1a@\x20Information\x20about\x20an\x20actual\x20connection.\x20\x20\
+    Pronounced\x20\"sock-ay\".\n\n\x0b\n\x03\x04\x0c\x01\x12\x04\xe3\x01\x08\
+    \x0e\n.\n\x04\x04\x0c\x02\0\x12\x04\xe5\x01\x02\x14\x1a\x20\x20The\x20id\
+    entifier\x20for\x20the\x20Socket.\n\n\r\n\x05\x04\x0c\x02\0\x06\x12\x04\
+    \xe5\x01\x02\x0b\n\r\n\x05\x04\x0c\x02\0\x01\x12\x04\xe5\x01\x0c\x0f\n\r\
+    \n\x05\x04\x0c\x02\0\x03\x12\x04\xe5\x01\x12\x13\n-\n\x04\x04\x0c\x02\
+    \x01\x12\x04\xe8\x01\x02\x16\x1a\x1f\x20Data\x20specific\x20to\x20this\
+    \x20Socket.\n\n\r\n\x05\x04\x0c\x02\x01\x06\x12\x04\xe8\x01\x02\x0c\n\r\
+    \n\x05\x04\x0c\x02\x01\x01\x12\x04\xe8\x01\r\x11\n\r\n\x05\x04\x0c\x02\
+    \x01\x03\x12\x04\xe8\x01\x14\x15\n*\n\x04\x04\x0c\x02\x02\x12\x04\xea\
+    \x01\x02\x14\x1a\x1c\x20The\x20locally\x20bound\x20address.\n\n\r\n\x05\
+    \x04\x0c\x02\x02\x06\x12\x04\xea\x01\x02\t\n\r\n\x05\x04\x0c\x02\x02\x01\
+    \x12\x04\xea\x01\n\x0f\n\r\n\x05\x04\x0c\x02\x02\x03\x12\x04\xea\x01\x12\
+    \x13\n9\n\x04\x04\x0c\x02\x03\x12\x04\xec\x01\x02\x15\x1a+\x20The\x20rem\
+    ote\x20bound\x20address.\x20\x20May\x20be\x20absent.\n\n\r\n\x05\x04\x0c\
+    \x02\x03\x06\x12\x04\xec\x01\x02\t\n\r\n\x05\x04\x0c\x02\x03\x01\x12\x04\
+    \xec\x01\n\x10\n\r\n\x05\x04\x0c\x02\x03\x03\x12\x04\xec\x01\x13\x14\nz\
+    \n\x04\x04\x0c\x02\x04\x12\x04\xef\x01\x02\x18\x1al\x20Security\x20detai\
+    ls\x20for\x20this\x20socket.\x20\x20May\x20be\x20absent\x20if\x20not\x20\
+    available,\x20or\n\x20there\x20is\x20no\x20security\x20on\x20the\x20sock\
+    et.\n\n\r\n\x05\x04\x0c\x02\x04\x06\x12\x04\xef\x01\x02\n\n\r\n\x05\x04\
+    \x0c\x02\x04\x01\x12\x04\xef\x01\x0b\x13\n\r\n\x05\x04\x0c\x02\x04\x03\
+    \x12\x04\xef\x01\x16\x17\nr\n\x04\x04\x0c\x02\x05\x12\x04\xf3\x01\x02\
+    \x19\x1ad\x20Optional,\x20represents\x20the\x20name\x20of\x20the\x20remo\
+    te\x20endpoint,\x20if\x20different\x20than\n\x20the\x20original\x20targe\
+    t\x20name.\n\n\r\n\x05\x04\x0c\x02\x05\x05\x12\x04\xf3\x01\x02\x08\n\r\n\
+    \x05\x04\x0c\x02\x05\x01\x12\x04\xf3\x01\t\x14\n\r\n\x05\x04\x0c\x02\x05\
+    \x03\x12\x04\xf3\x01\x17\x18\n\xd0\x01\n\x02\x04\r\x12\x06\xf9\x01\0\xa6\
+    \x02\x01\x1a\xc1\x01\x20SocketData\x20is\x20data\x20associated\x20for\
+    \x20a\x20specific\x20Socket.\x20\x20The\x20fields\x20present\n\x20are\
+    \x20specific\x20to\x20the\x20implementation,\x20so\x20there\x20may\x20be\
+    \x20minor\x20differences\x20in\n\x20the\x20semantics.\x20\x20(e.g.\x20fl\
+    ow\x20control\x20windows)\n\n\x0b\n\x03\x04\r\x01\x12\x04\xf9\x01\x08\
+    \x12\n=\n\x04\x04\r\x02\0\x12\x04\xfb\x01\x02\x1c\x1a/\x20The\x20number\
+    \x20of\x20streams\x20that\x20have\x20been\x20started.\n\n\r\n\x05\x04\r\
+    \x02\0\x05\x12\x04\xfb\x01\x02\x07\n\r\n\x05\x04\r\x02\0\x01\x12\x04\xfb\
+    \x01\x08\x17\n\r\n\x05\x04\r\x02\0\x03\x12\x04\xfb\x01\x1a\x1b\n\xa4\x01\
+    \n\x04\x04\r\x02\x01\x12\x04\xff\x01\x02\x1e\x1a\x95\x01\x20The\x20numbe\
+    r\x20of\x20streams\x20that\x20have\x20ended\x20successfully:\n\x20On\x20\
+    client\x20side,\x20received\x20frame\x20with\x20eos\x20bit\x20set;\n\x20\
+    On\x20server\x20side,\x20sent\x20frame\x20with\x20eos\x20bit\x20set.\n\n\
+    \r\n\x05\x04\r\x02\x01\x05\x12\x04\xff\x01\x02\x07\n\r\n\x05\x04\r\x02\
+    \x01\x01\x12\x04\xff\x01\x08\x19\n\r\n\x05\x04\r\x02\x01\x03\x12\x04\xff\
+    \x01\x1c\x1d\n\xc6\x01\n\x04\x04\r\x02\x02\x12\x04\x83\x02\x02\x1b\x1a\
+    \xb7\x01\x20The\x20number\x20of\x20streams\x20that\x20have\x20ended\x20u\
+    nsuccessfully:\n\x20On\x20client\x20side,\x20ended\x20without\x20receivi\
+    ng\x20frame\x20with\x20eos\x20bit\x20set;\n\x20On\x20server\x20side,\x20\
+    ended\x20without\x20sending\x20frame\x20with\x20eos\x20bit\x20set.\n\n\r\
+    \n\x05\x04\r\x02\x02\x05\x12\x04\x83\x02\x02\x07\n\r\n\x05\x04\r\x02\x02\
+    \x01\x12\x04\x83\x02\x08\x16\n\r\n\x05\x04\r\x02\x02\x03\x12\x04\x83\x02\
+    \x19\x1a\nM\n\x04\x04\r\x02\x03\x12\x04\x85\x02\x02\x1a\x1a?\x20The\x20n\
+    umber\x20of\x20grpc\x20messages\x20successfully\x20sent\x20on\x20this\
+    \x20socket.\n\n\r\n\x05\x04\r\x02\x03\x05\x12\x04\x85\x02\x02\x07\n\r\n\
+    \x05\x04\r\x02\x03\x01\x12\x04\x85\x02\x08\x15\n\r\n\x05\x04\r\x02\x03\
+    \x03\x12\x04\x85\x02\x18\x19\nD\n\x04\x04\r\x02\x04\x12\x04\x87\x02\x02\
+    \x1e\x1a6\x20The\x20number\x20of\x20grpc\x20messages\x20received\x20on\
+    \x20this\x20socket.\n\n\r\n\x05\x04\r\x02\x04\x05\x12\x04\x87\x02\x02\
+    \x07\n\r\n\x05\x04\r\x02\x04\x01\x12\x04\x87\x02\x08\x19\n\r\n\x05\x04\r\
+    \x02\x04\x03\x12\x04\x87\x02\x1c\x1d\nj\n\x04\x04\r\x02\x05\x12\x04\x8b\
+    \x02\x02\x1d\x1a\\\x20The\x20number\x20of\x20keep\x20alives\x20sent.\x20\
+    \x20This\x20is\x20typically\x20implemented\x20with\x20HTTP/2\n\x20ping\
+    \x20messages.\n\n\r\n\x05\x04\r\x02\x05\x05\x12\x04\x8b\x02\x02\x07\n\r\
+    \n\x05\x04\r\x02\x05\x01\x12\x04\x8b\x02\x08\x18\n\r\n\x05\x04\r\x02\x05\
+    \x03\x12\x04\x8b\x02\x1b\x1c\na\n\x04\x04\r\x02\x06\x12\x04\x8f\x02\x02D\
+    \x1aS\x20The\x20last\x20time\x20a\x20stream\x20was\x20created\x20by\x20t\
+    his\x20endpoint.\x20\x20Usually\x20unset\x20for\n\x20servers.\n\n\r\n\
+    \x05\x04\r\x02\x06\x06\x12\x04\x8f\x02\x02\x1b\n\r\n\x05\x04\r\x02\x06\
+    \x01\x12\x04\x8f\x02\x1c?\n\r\n\x05\x04\r\x02\x06\x03\x12\x04\x8f\x02BC\
+    \ng\n\x04\x04\r\x02\x07\x12\x04\x92\x02\x02E\x1aY\x20The\x20last\x20time\
+    \x20a\x20stream\x20was\x20created\x20by\x20the\x20remote\x20endpoint.\
+    \x20\x20Usually\x20unset\n\x20for\x20clients.\n\n\r\n\x05\x04\r\x02\x07\
+    \x06\x12\x04\x92\x02\x02\x1b\n\r\n\x05\x04\r\x02\x07\x01\x12\x04\x92\x02\
+    \x1c@\n\r\n\x05\x04\r\x02\x07\x03\x12\x04\x92\x02CD\nB\n\x04\x04\r\x02\
+    \x08\x12\x04\x95\x02\x02<\x1a4\x20The\x20last\x20time\x20a\x20message\
+    \x20was\x20sent\x20by\x20this\x20endpoint.\n\n\r\n\x05\x04\r\x02\x08\x06\
+    \x12\x04\x95\x02\x02\x1b\n\r\n\x05\x04\r\x02\x08\x01\x12\x04\x95\x02\x1c\
+    7\n\r\n\x05\x04\r\x02\x08\x03\x12\x04\x95\x02:;\nF\n\x04\x04\r\x02\t\x12\
+    \x04\x97\x02\x02A\x1a8\x20The\x20last\x20time\x20a\x20message\x20was\x20\
+    received\x20by\x20this\x20endpoint.\n\n\r\n\x05\x04\r\x02\t\x06\x12\x04\
+    \x97\x02\x02\x1b\n\r\n\x05\x04\r\x02\t\x01\x12\x04\x97\x02\x1c;\n\r\n\
+    \x05\x04\r\x02\t\x03\x12\x04\x97\x02>@\n\xdb\x01\n\x04\x04\r\x02\n\x12\
+    \x04\x9c\x02\x02<\x1a\xcc\x01\x20The\x20amount\x20of\x20window,\x20grant\
+    ed\x20to\x20the\x20local\x20endpoint\x20by\x20the\x20remote\x20endpoint.\
+    \n\x20This\x20may\x20be\x20slightly\x20out\x20of\x20date\x20due\x20to\
+    \x20network\x20latency.\x20\x20This\x20does\x20NOT\n\x20include\x20strea\
+    m\x20level\x20or\x20TCP\x20level\x20flow\x20control\x20info.\n\n\r\n\x05\
+    \x04\r\x02\n\x06\x12\x04\x9c\x02\x02\x1c\n\r\n\x05\x04\r\x02\n\x01\x12\
+    \x04\x9c\x02\x1d6\n\r\n\x05\x04\r\x02\n\x03\x12\x04\x9c\x029;\n\xdb\x01\
+    \n\x04\x04\r\x02\x0b\x12\x04\xa1\x02\x02>\x1a\xcc\x01\x20The\x20amount\
+    \x20of\x20window,\x20granted\x20to\x20the\x20remote\x20endpoint\x20by\
+    \x20the\x20local\x20endpoint.\n\x20This\x20may\x20be\x20slightly\x20out\
+    \x20of\x20date\x20due\x20to\x20network\x20latency.\x20\x20This\x20does\
+    \x20NOT\n\x20include\x20stream\x20level\x20or\x20TCP\x20level\x20flow\
+    \x20control\x20info.\n\n\r\n\x05\x04\r\x02\x0b\x06\x12\x04\xa1\x02\x02\
+    \x1c\n\r\n\x05\x04\r\x02\x0b\x01\x12\x04\xa1\x02\x1e8\n\r\n\x05\x04\r\
+    \x02\x0b\x03\x12\x04\xa1\x02;=\nk\n\x04\x04\r\x02\x0c\x12\x04\xa5\x02\
+    \x02$\x1a]\x20Socket\x20options\x20set\x20on\x20this\x20socket.\x20\x20M\
+    ay\x20be\x20absent\x20if\x20'summary'\x20is\x20set\n\x20on\x20GetSocketR\
+    equest.\n\n\r\n\x05\x04\r\x02\x0c\x04\x12\x04\xa5\x02\x02\n\n\r\n\x05\
+    \x04\r\x02\x0c\x06\x12\x04\xa5\x02\x0b\x17\n\r\n\x05\x04\r\x02\x0c\x01\
+    \x12\x04\xa5\x02\x18\x1e\n\r\n\x05\x04\r\x02\x0c\x03\x12\x04\xa5\x02!#\n\
+    I\n\x02\x04\x0e\x12\x06\xa9\x02\0\xc2\x02\x01\x1a;\x20Address\x20represe\
+    nts\x20the\x20address\x20used\x20to\x20create\x20the\x20socket.\n\n\x0b\
+    \n\x03\x04\x0e\x01\x12\x04\xa9\x02\x08\x0f\n\x0e\n\x04\x04\x0e\x03\0\x12\
+    \x06\xaa\x02\x02\xb0\x02\x03\n\r\n\x05\x04\x0e\x03\0\x01\x12\x04\xaa\x02\
+    \n\x16\nk\n\x06\x04\x0e\x03\0\x02\0\x12\x04\xad\x02\x04\x19\x1a[\x20Eith\
+    er\x20the\x20IPv4\x20or\x20IPv6\x20address\x20in\x20bytes.\x20\x20Will\
+    \x20be\x20either\x204\x20bytes\x20or\x2016\n\x20bytes\x20in\x20length.\n\
+    \n\x0f\n\x07\x04\x0e\x03\0\x02\0\x05\x12\x04\xad\x02\x04\t\n\x0f\n\x07\
+    \x04\x0e\x03\0\x02\0\x01\x12\x04\xad\x02\n\x14\n\x0f\n\x07\x04\x0e\x03\0\
+    \x02\0\x03\x12\x04\xad\x02\x17\x18\n2\n\x06\x04\x0e\x03\0\x02\x01\x12\
+    \x04\xaf\x02\x04\x13\x1a\"\x200-64k,\x20or\x20-1\x20if\x20not\x20appropr\
+    iate.\n\n\x0f\n\x07\x04\x0e\x03\0\x02\x01\x05\x12\x04\xaf\x02\x04\t\n\
+    \x0f\n\x07\x04\x0e\x03\0\x02\x01\x01\x12\x04\xaf\x02\n\x0e\n\x0f\n\x07\
+    \x04\x0e\x03\0\x02\x01\x03\x12\x04\xaf\x02\x11\x12\n/\n\x04\x04\x0e\x03\
+    \x01\x12\x06\xb2\x02\x02\xb4\x02\x03\x1a\x1f\x20A\x20Unix\x20Domain\x20S\
+    ocket\x20address.\n\n\r\n\x05\x04\x0e\x03\x01\x01\x12\x04\xb2\x02\n\x14\
+    \n\x0e\n\x06\x04\x0e\x03\x01\x02\0\x12\x04\xb3\x02\x04\x18\n\x0f\n\x07\
+    \x04\x0e\x03\x01\x02\0\x05\x12\x04\xb3\x02\x04\n\n\x0f\n\x07\x04\x0e\x03\
+    \x01\x02\0\x01\x12\x04\xb3\x02\x0b\x13\n\x0f\n\x07\x04\x0e\x03\x01\x02\0\
+    \x03\x12\x04\xb3\x02\x16\x17\n5\n\x04\x04\x0e\x03\x02\x12\x06\xb6\x02\
+    \x02\xbb\x02\x03\x1a%\x20An\x20address\x20type\x20not\x20included\x20abo\
+    ve.\n\n\r\n\x05\x04\x0e\x03\x02\x01\x12\x04\xb6\x02\n\x16\nU\n\x06\x04\
+    \x0e\x03\x02\x02\0\x12\x04\xb8\x02\x04\x14\x1aE\x20The\x20human\x20reada\
+    ble\x20version\x20of\x20the\x20value.\x20\x20This\x20value\x20should\x20\
+    be\x20set.\n\n\x0f\n\x07\x04\x0e\x03\x02\x02\0\x05\x12\x04\xb8\x02\x04\n\
+    \n\x0f\n\x07\x04\x0e\x03\x02\x02\0\x01\x12\x04\xb8\x02\x0b\x0f\n\x0f\n\
+    \x07\x04\x0e\x03\x02\x02\0\x03\x12\x04\xb8\x02\x12\x13\n-\n\x06\x04\x0e\
+    \x03\x02\x02\x01\x12\x04\xba\x02\x04\"\x1a\x1d\x20The\x20actual\x20addre\
+    ss\x20message.\n\n\x0f\n\x07\x04\x0e\x03\x02\x02\x01\x06\x12\x04\xba\x02\
+    \x04\x17\n\x0f\n\x07\x04\x0e\x03\x02\x02\x01\x01\x12\x04\xba\x02\x18\x1d\
+    \n\x0f\n\x07\x04\x0e\x03\x02\x02\x01\x03\x12\x04\xba\x02\x20!\n\x0e\n\
+    \x04\x04\x0e\x08\0\x12\x06\xbd\x02\x02\xc1\x02\x03\n\r\n\x05\x04\x0e\x08\
+    \0\x01\x12\x04\xbd\x02\x08\x0f\n\x0c\n\x04\x04\x0e\x02\0\x12\x04\xbe\x02\
+    \x04#\n\r\n\x05\x04\x0e\x02\0\x06\x12\x04\xbe\x02\x04\x10\n\r\n\x05\x04\
+    \x0e\x02\0\x01\x12\x04\xbe\x02\x11\x1e\n\r\n\x05\x04\x0e\x02\0\x03\x12\
+    \x04\xbe\x02!\"\n\x0c\n\x04\x04\x0e\x02\x01\x12\x04\xbf\x02\x04\x1f\n\r\
+    \n\x05\x04\x0e\x02\x01\x06\x12\x04\xbf\x02\x04\x0e\n\r\n\x05\x04\x0e\x02\
+    \x01\x01\x12\x04\xbf\x02\x0f\x1a\n\r\n\x05\x04\x0e\x02\x01\x03\x12\x04\
+    \xbf\x02\x1d\x1e\n\x0c\n\x04\x04\x0e\x02\x02\x12\x04\xc0\x02\x04#\n\r\n\
+    \x05\x04\x0e\x02\x02\x06\x12\x04\xc0\x02\x04\x10\n\r\n\x05\x04\x0e\x02\
+    \x02\x01\x12\x04\xc0\x02\x11\x1e\n\r\n\x05\x04\x0e\x02\x02\x03\x12\x04\
+    \xc0\x02!\"\nK\n\x02\x04\x0f\x12\x06\xc5\x02\0\xde\x02\x01\x1a=\x20Secur\
+    ity\x20represents\x20details\x20about\x20how\x20secure\x20the\x20socket\
+    \x20is.\n\n\x0b\n\x03\x04\x0f\x01\x12\x04\xc5\x02\x08\x10\n\x0e\n\x04\
+    \x04\x0f\x03\0\x12\x06\xc6\x02\x02\xd3\x02\x03\n\r\n\x05\x04\x0f\x03\0\
+    \x01\x12\x04\xc6\x02\n\r\n\x10\n\x06\x04\x0f\x03\0\x08\0\x12\x06\xc7\x02\
+    \x04\xce\x02\x05\n\x0f\n\x07\x04\x0f\x03\0\x08\0\x01\x12\x04\xc7\x02\n\
+    \x16\no\n\x06\x04\x0f\x03\0\x02\0\x12\x04\xca\x02\x06\x1f\x1a_\x20The\
+    \x20cipher\x20suite\x20name\x20in\x20the\x20RFC\x204346\x20format:\n\x20\
+    https://tools.ietf.org/html/rfc4346#appendix-C\n\n\x0f\n\x07\x04\x0f\x03\
+    \0\x02\0\x05\x12\x04\xca\x02\x06\x0c\n\x0f\n\x07\x04\x0f\x03\0\x02\0\x01\
+    \x12\x04\xca\x02\r\x1a\n\x0f\n\x07\x04\x0f\x03\0\x02\0\x03\x12\x04\xca\
+    \x02\x1d\x1e\ne\n\x06\x04\x0f\x03\0\x02\x01\x12\x04\xcd\x02\x06\x1c\x1aU\
+    \x20Some\x20other\x20way\x20to\x20describe\x20the\x20cipher\x20suite\x20\
+    if\n\x20the\x20RFC\x204346\x20name\x20is\x20not\x20available.\n\n\x0f\n\
+    \x07\x04\x0f\x03\0\x02\x01\x05\x12\x04\xcd\x02\x06\x0c\n\x0f\n\x07\x04\
+    \x0f\x03\0\x02\x01\x01\x12\x04\xcd\x02\r\x17\n\x0f\n\x07\x04\x0f\x03\0\
+    \x02\x01\x03\x12\x04\xcd\x02\x1a\x1b\n8\n\x06\x04\x0f\x03\0\x02\x02\x12\
+    \x04\xd0\x02\x04\x20\x1a(\x20the\x20certificate\x20used\x20by\x20this\
+    \x20endpoint.\n\n\x0f\n\x07\x04\x0f\x03\0\x02\x02\x05\x12\x04\xd0\x02\
+    \x04\t\n\x0f\n\x07\x04\x0f\x03\0\x02\x02\x01\x12\x04\xd0\x02\n\x1b\n\x0f\
+    \n\x07\x04\x0f\x03\0\x02\x02\x03\x12\x04\xd0\x02\x1e\x1f\n>\n\x06\x04\
+    \x0f\x03\0\x02\x03\x12\x04\xd2\x02\x04!\x1a.\x20the\x20certificate\x20us\
+    ed\x20by\x20the\x20remote\x20endpoint.\n\n\x0f\n\x07\x04\x0f\x03\0\x02\
+    \x03\x05\x12\x04\xd2\x02\x04\t\n\x0f\n\x07\x04\x0f\x03\0\x02\x03\x01\x12\
+    \x04\xd2\x02\n\x1c\n\x0f\n\x07\x04\x0f\x03\0\x02\x03\x03\x12\x04\xd2\x02\
+    \x1f\x20\n\x0e\n\x04\x04\x0f\x03\x01\x12\x06\xd4\x02\x02\xd9\x02\x03\n\r\
+    \n\x05\x04\x0f\x03\x01\x01\x12\x04\xd4\x02\n\x17\n:\n\x06\x04\x0f\x03\
+    \x01\x02\0\x12\x04\xd6\x02\x04\x14\x1a*\x20The\x20human\x20readable\x20v\
+    ersion\x20of\x20the\x20value.\n\n\x0f\n\x07\x04\x0f\x03\x01\x02\0\x05\
+    \x12\x04\xd6\x02\x04\n\n\x0f\n\x07\x04\x0f\x03\x01\x02\0\x01\x12\x04\xd6\
+    \x02\x0b\x0f\n\x0f\n\x07\x04\x0f\x03\x01\x02\0\x03\x12\x04\xd6\x02\x12\
+    \x13\n6\n\x06\x04\x0f\x03\x01\x02\x01\x12\x04\xd8\x02\x04\"\x1a&\x20The\
+    \x20actual\x20security\x20details\x20message.\n\n\x0f\n\x07\x04\x0f\x03\
+    \x01\x02\x01\x06\x12\x04\xd8\x02\x04\x17\n\x0f\n\x07\x04\x0f\x03\x01\x02\
+    \x01\x01\x12\x04\xd8\x02\x18\x1d\n\x0f\n\x07\x04\x0f\x03\x01\x02\x01\x03\
+    \x12\x04\xd8\x02\x20!\n\x0e\n\x04\x04\x0f\x08\0\x12\x06\xda\x02\x02\xdd\
+    \x02\x03\n\r\n\x05\x04\x0f\x08\0\x01\x12\x04\xda\x02\x08\r\n\x0c\n\x04\
+    \x04\x0f\x02\0\x12\x04\xdb\x02\x04\x10\n\r\n\x05\x04\x0f\x02\0\x06\x12\
+    \x04\xdb\x02\x04\x07\n\r\n\x05\x04\x0f\x02\0\x01\x12\x04\xdb\x02\x08\x0b\
+    \n\r\n\x05\x04\x0f\x02\0\x03\x12\x04\xdb\x02\x0e\x0f\n\x0c\n\x04\x04\x0f\
+    \x02\x01\x12\x04\xdc\x02\x04\x1c\n\r\n\x05\x04\x0f\x02\x01\x06\x12\x04\
+    \xdc\x02\x04\x11\n\r\n\x05\x04\x0f\x02\x01\x01\x12\x04\xdc\x02\x12\x17\n\
+    \r\n\x05\x04\x0f\x02\x01\x03\x12\x04\xdc\x02\x1a\x1b\n\x84\x01\n\x02\x04\
+    \x10\x12\x06\xe2\x02\0\xec\x02\x01\x1av\x20SocketOption\x20represents\
+    \x20socket\x20options\x20for\x20a\x20socket.\x20\x20Specifically,\x20the\
+    se\n\x20are\x20the\x20options\x20returned\x20by\x20getsockopt().\n\n\x0b\
+    \n\x03\x04\x10\x01\x12\x04\xe2\x02\x08\x14\ny\n\x04\x04\x10\x02\0\x12\
+    \x04\xe5\x02\x02\x12\x1ak\x20The\x20full\x20name\x20of\x20the\x20socket\
+    \x20option.\x20\x20Typically\x20this\x20will\x20be\x20the\x20upper\x20ca\
+    se\n\x20name,\x20such\x20as\x20\"SO_REUSEPORT\".\n\n\r\n\x05\x04\x10\x02\
+    \0\x05\x12\x04\xe5\x02\x02\x08\n\r\n\x05\x04\x10\x02\0\x01\x12\x04\xe5\
+    \x02\t\r\n\r\n\x05\x04\x10\x02\0\x03\x12\x04\xe5\x02\x10\x11\nr\n\x04\
+    \x04\x10\x02\x01\x12\x04\xe8\x02\x02\x13\x1ad\x20The\x20human\x20readabl\
+    e\x20value\x20of\x20this\x20socket\x20option.\x20\x20At\x20least\x20one\
+    \x20of\x20value\x20or\n\x20additional\x20will\x20be\x20set.\n\n\r\n\x05\
+    \x04\x10\x02\x01\x05\x12\x04\xe8\x02\x02\x08\n\r\n\x05\x04\x10\x02\x01\
+    \x01\x12\x04\xe8\x02\t\x0e\n\r\n\x05\x04\x10\x02\x01\x03\x12\x04\xe8\x02\
+    \x11\x12\nu\n\x04\x04\x10\x02\x02\x12\x04\xeb\x02\x02%\x1ag\x20Additiona\
+    l\x20data\x20associated\x20with\x20the\x20socket\x20option.\x20\x20At\
+    \x20least\x20one\x20of\x20value\n\x20or\x20additional\x20will\x20be\x20s\
+    et.\n\n\r\n\x05\x04\x10\x02\x02\x06\x12\x04\xeb\x02\x02\x15\n\r\n\x05\
+    \x04\x10\x02\x02\x01\x12\x04\xeb\x02\x16\x20\n\r\n\x05\x04\x10\x02\x02\
+    \x03\x12\x04\xeb\x02#$\nv\n\x02\x04\x11\x12\x06\xf0\x02\0\xf2\x02\x01\
+    \x1ah\x20For\x20use\x20with\x20SocketOption's\x20additional\x20field.\
+    \x20\x20This\x20is\x20primarily\x20used\x20for\n\x20SO_RCVTIMEO\x20and\
+    \x20SO_SNDTIMEO\n\n\x0b\n\x03\x04\x11\x01\x12\x04\xf0\x02\x08\x1b\n\x0c\
+    \n\x04\x04\x11\x02\0\x12\x04\xf1\x02\x02(\n\r\n\x05\x04\x11\x02\0\x06\
+    \x12\x04\xf1\x02\x02\x1a\n\r\n\x05\x04\x11\x02\0\x01\x12\x04\xf1\x02\x1b\
+    #\n\r\n\x05\x04\x11\x02\0\x03\x12\x04\xf1\x02&'\ne\n\x02\x04\x12\x12\x06\
+    \xf6\x02\0\xfb\x02\x01\x1aW\x20For\x20use\x20with\x20SocketOption's\x20a\
+    dditional\x20field.\x20\x20This\x20is\x20primarily\x20used\x20for\n\x20S\
+    O_LINGER.\n\n\x0b\n\x03\x04\x12\x01\x12\x04\xf6\x02\x08\x1a\n6\n\x04\x04\
+    \x12\x02\0\x12\x04\xf8\x02\x02\x12\x1a(\x20active\x20maps\x20to\x20`stru\
+    ct\x20linger.l_onoff`\n\n\r\n\x05\x04\x12\x02\0\x05\x12\x04\xf8\x02\x02\
+    \x06\n\r\n\x05\x04\x12\x02\0\x01\x12\x04\xf8\x02\x07\r\n\r\n\x05\x04\x12\
+    \x02\0\x03\x12\x04\xf8\x02\x10\x11\n9\n\x04\x04\x12\x02\x01\x12\x04\xfa\
+    \x02\x02(\x1a+\x20duration\x20maps\x20to\x20`struct\x20linger.l_linger`\
+    \n\n\r\n\x05\x04\x12\x02\x01\x06\x12\x04\xfa\x02\x02\x1a\n\r\n\x05\x04\
+    \x12\x02\x01\x01\x12\x04\xfa\x02\x1b#\n\r\n\x05\x04\x12\x02\x01\x03\x12\
+    \x04\xfa\x02&'\nb\n\x02\x04\x13\x12\x06\xff\x02\0\xa2\x03\x01\x1aT\x20Fo\
+    r\x20use\x20with\x20SocketOption's\x20additional\x20field.\x20\x20Tcp\
+    \x20info\x20for\n\x20SOL_TCP\x20and\x20TCP_INFO.\n\n\x0b\n\x03\x04\x13\
+    \x01\x12\x04\xff\x02\x08\x1b\n\x0c\n\x04\x04\x13\x02\0\x12\x04\x80\x03\
+    \x02\x18\n\r\n\x05\x04\x13\x02\0\x05\x12\x04\x80\x03\x02\x08\n\r\n\x05\
+    \x04\x13\x02\0\x01\x12\x04\x80\x03\t\x13\n\r\n\x05\x04\x13\x02\0\x03\x12\
+    \x04\x80\x03\x16\x17\n\x0c\n\x04\x04\x13\x02\x01\x12\x04\x82\x03\x02\x1b\
+    \n\r\n\x05\x04\x13\x02\x01\x05\x12\x04\x82\x03\x02\x08\n\r\n\x05\x04\x13\
+    \x02\x01\x01\x12\x04\x82\x03\t\x16\n\r\n\x05\x04\x13\x02\x01\x03\x12\x04\
+    \x82\x03\x19\x1a\n\x0c\n\x04\x04\x13\x02\x02\x12\x04\x83\x03\x02\x1e\n\r\
+    \n\x05\x04\x13\x02\x02\x05\x12\x04\x83\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x02\x01\x12\x04\x83\x03\t\x19\n\r\n\x05\x04\x13\x02\x02\x03\x12\x04\x83\
+    \x03\x1c\x1d\n\x0c\n\x04\x04\x13\x02\x03\x12\x04\x84\x03\x02\x19\n\r\n\
+    \x05\x04\x13\x02\x03\x05\x12\x04\x84\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x03\x01\x12\x04\x84\x03\t\x14\n\r\n\x05\x04\x13\x02\x03\x03\x12\x04\x84\
+    \x03\x17\x18\n\x0c\n\x04\x04\x13\x02\x04\x12\x04\x85\x03\x02\x1a\n\r\n\
+    \x05\x04\x13\x02\x04\x05\x12\x04\x85\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x04\x01\x12\x04\x85\x03\t\x15\n\r\n\x05\x04\x13\x02\x04\x03\x12\x04\x85\
+    \x03\x18\x19\n\x0c\n\x04\x04\x13\x02\x05\x12\x04\x86\x03\x02\x1a\n\r\n\
+    \x05\x04\x13\x02\x05\x05\x12\x04\x86\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x05\x01\x12\x04\x86\x03\t\x15\n\r\n\x05\x04\x13\x02\x05\x03\x12\x04\x86\
+    \x03\x18\x19\n\x0c\n\x04\x04\x13\x02\x06\x12\x04\x87\x03\x02\x1d\n\r\n\
+    \x05\x04\x13\x02\x06\x05\x12\x04\x87\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x06\x01\x12\x04\x87\x03\t\x18\n\r\n\x05\x04\x13\x02\x06\x03\x12\x04\x87\
+    \x03\x1b\x1c\n\x0c\n\x04\x04\x13\x02\x07\x12\x04\x88\x03\x02\x1d\n\r\n\
+    \x05\x04\x13\x02\x07\x05\x12\x04\x88\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x07\x01\x12\x04\x88\x03\t\x18\n\r\n\x05\x04\x13\x02\x07\x03\x12\x04\x88\
+    \x03\x1b\x1c\n\x0c\n\x04\x04\x13\x02\x08\x12\x04\x8a\x03\x02\x16\n\r\n\
+    \x05\x04\x13\x02\x08\x05\x12\x04\x8a\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x08\x01\x12\x04\x8a\x03\t\x11\n\r\n\x05\x04\x13\x02\x08\x03\x12\x04\x8a\
+    \x03\x14\x15\n\x0c\n\x04\x04\x13\x02\t\x12\x04\x8b\x03\x02\x17\n\r\n\x05\
+    \x04\x13\x02\t\x05\x12\x04\x8b\x03\x02\x08\n\r\n\x05\x04\x13\x02\t\x01\
+    \x12\x04\x8b\x03\t\x11\n\r\n\x05\x04\x13\x02\t\x03\x12\x04\x8b\x03\x14\
+    \x16\n\x0c\n\x04\x04\x13\x02\n\x12\x04\x8c\x03\x02\x1b\n\r\n\x05\x04\x13\
+    \x02\n\x05\x12\x04\x8c\x03\x02\x08\n\r\n\x05\x04\x13\x02\n\x01\x12\x04\
+    \x8c\x03\t\x15\n\r\n\x05\x04\x13\x02\n\x03\x12\x04\x8c\x03\x18\x1a\n\x0c\
+    \n\x04\x04\x13\x02\x0b\x12\x04\x8d\x03\x02\x1b\n\r\n\x05\x04\x13\x02\x0b\
+    \x05\x12\x04\x8d\x03\x02\x08\n\r\n\x05\x04\x13\x02\x0b\x01\x12\x04\x8d\
+    \x03\t\x15\n\r\n\x05\x04\x13\x02\x0b\x03\x12\x04\x8d\x03\x18\x1a\n\x0c\n\
+    \x04\x04\x13\x02\x0c\x12\x04\x8f\x03\x02\x1b\n\r\n\x05\x04\x13\x02\x0c\
+    \x05\x12\x04\x8f\x03\x02\x08\n\r\n\x05\x04\x13\x02\x0c\x01\x12\x04\x8f\
+    \x03\t\x15\n\r\n\x05\x04\x13\x02\x0c\x03\x12\x04\x8f\x03\x18\x1a\n\x0c\n\
+    \x04\x04\x13\x02\r\x12\x04\x90\x03\x02\x1a\n\r\n\x05\x04\x13\x02\r\x05\
+    \x12\x04\x90\x03\x02\x08\n\r\n\x05\x04\x13\x02\r\x01\x12\x04\x90\x03\t\
+    \x14\n\r\n\x05\x04\x13\x02\r\x03\x12\x04\x90\x03\x17\x19\n\x0c\n\x04\x04\
+    \x13\x02\x0e\x12\x04\x91\x03\x02\x18\n\r\n\x05\x04\x13\x02\x0e\x05\x12\
+    \x04\x91\x03\x02\x08\n\r\n\x05\x04\x13\x02\x0e\x01\x12\x04\x91\x03\t\x12\
+    \n\r\n\x05\x04\x13\x02\x0e\x03\x12\x04\x91\x03\x15\x17\n\x0c\n\x04\x04\
+    \x13\x02\x0f\x12\x04\x92\x03\x02\x1b\n\r\n\x05\x04\x13\x02\x0f\x05\x12\
+    \x04\x92\x03\x02\x08\n\r\n\x05\x04\x13\x02\x0f\x01\x12\x04\x92\x03\t\x15\
+    \n\r\n\x05\x04\x13\x02\x0f\x03\x12\x04\x92\x03\x18\x1a\n\x0c\n\x04\x04\
+    \x13\x02\x10\x12\x04\x93\x03\x02\x1b\n\r\n\x05\x04\x13\x02\x10\x05\x12\
+    \x04\x93\x03\x02\x08\n\r\n\x05\x04\x13\x02\x10\x01\x12\x04\x93\x03\t\x15\
+    \n\r\n\x05\x04\x13\x02\x10\x03\x12\x04\x93\x03\x18\x1a\n\x0c\n\x04\x04\
+    \x13\x02\x11\x12\x04\x95\x03\x02\"\n\r\n\x05\x04\x13\x02\x11\x05\x12\x04\
+    \x95\x03\x02\x08\n\r\n\x05\x04\x13\x02\x11\x01\x12\x04\x95\x03\t\x1c\n\r\
+    \n\x05\x04\x13\x02\x11\x03\x12\x04\x95\x03\x1f!\n\x0c\n\x04\x04\x13\x02\
+    \x12\x12\x04\x96\x03\x02!\n\r\n\x05\x04\x13\x02\x12\x05\x12\x04\x96\x03\
+    \x02\x08\n\r\n\x05\x04\x13\x02\x12\x01\x12\x04\x96\x03\t\x1b\n\r\n\x05\
+    \x04\x13\x02\x12\x03\x12\x04\x96\x03\x1e\x20\n\x0c\n\x04\x04\x13\x02\x13\
+    \x12\x04\x97\x03\x02\"\n\r\n\x05\x04\x13\x02\x13\x05\x12\x04\x97\x03\x02\
+    \x08\n\r\n\x05\x04\x13\x02\x13\x01\x12\x04\x97\x03\t\x1c\n\r\n\x05\x04\
+    \x13\x02\x13\x03\x12\x04\x97\x03\x1f!\n\x0c\n\x04\x04\x13\x02\x14\x12\
+    \x04\x98\x03\x02!\n\r\n\x05\x04\x13\x02\x14\x05\x12\x04\x98\x03\x02\x08\
+    \n\r\n\x05\x04\x13\x02\x14\x01\x12\x04\x98\x03\t\x1b\n\r\n\x05\x04\x13\
+    \x02\x14\x03\x12\x04\x98\x03\x1e\x20\n\x0c\n\x04\x04\x13\x02\x15\x12\x04\
+    \x9a\x03\x02\x18\n\r\n\x05\x04\x13\x02\x15\x05\x12\x04\x9a\x03\x02\x08\n\
+    \r\n\x05\x04\x13\x02\x15\x01\x12\x04\x9a\x03\t\x12\n\r\n\x05\x04\x13\x02\
+    \x15\x03\x12\x04\x9a\x03\x15\x17\n\x0c\n\x04\x04\x13\x02\x16\x12\x04\x9b\
+    \x03\x02\x20\n\r\n\x05\x04\x13\x02\x16\x05\x12\x04\x9b\x03\x02\x08\n\r\n\
+    \x05\x04\x13\x02\x16\x01\x12\x04\x9b\x03\t\x1a\n\r\n\x05\x04\x13\x02\x16\
+    \x03\x12\x04\x9b\x03\x1d\x1f\n\x0c\n\x04\x04\x13\x02\x17\x12\x04\x9c\x03\
+    \x02\x17\n\r\n\x05\x04\x13\x02\x17\x05\x12\x04\x9c\x03\x02\x08\n\r\n\x05\
+    \x04\x13\x02\x17\x01\x12\x04\x9c\x03\t\x11\n\r\n\x05\x04\x13\x02\x17\x03\
+    \x12\x04\x9c\x03\x14\x16\n\x0c\n\x04\x04\x13\x02\x18\x12\x04\x9d\x03\x02\
+    \x1a\n\r\n\x05\x04\x13\x02\x18\x05\x12\x04\x9d\x03\x02\x08\n\r\n\x05\x04\
+    \x13\x02\x18\x01\x12\x04\x9d\x03\t\x14\n\r\n\x05\x04\x13\x02\x18\x03\x12\
+    \x04\x9d\x03\x17\x19\n\x0c\n\x04\x04\x13\x02\x19\x12\x04\x9e\x03\x02\x20\
+    \n\r\n\x05\x04\x13\x02\x19\x05\x12\x04\x9e\x03\x02\x08\n\r\n\x05\x04\x13\
+    \x02\x19\x01\x12\x04\x9e\x03\t\x1a\n\r\n\x05\x04\x13\x02\x19\x03\x12\x04\
+    \x9e\x03\x1d\x1f\n\x0c\n\x04\x04\x13\x02\x1a\x12\x04\x9f\x03\x02\x1c\n\r\
+    \n\x05\x04\x13\x02\x1a\x05\x12\x04\x9f\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x1a\x01\x12\x04\x9f\x03\t\x16\n\r\n\x05\x04\x13\x02\x1a\x03\x12\x04\x9f\
+    \x03\x19\x1b\n\x0c\n\x04\x04\x13\x02\x1b\x12\x04\xa0\x03\x02\x1a\n\r\n\
+    \x05\x04\x13\x02\x1b\x05\x12\x04\xa0\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x1b\x01\x12\x04\xa0\x03\t\x14\n\r\n\x05\x04\x13\x02\x1b\x03\x12\x04\xa0\
+    \x03\x17\x19\n\x0c\n\x04\x04\x13\x02\x1c\x12\x04\xa1\x03\x02\x1e\n\r\n\
+    \x05\x04\x13\x02\x1c\x05\x12\x04\xa1\x03\x02\x08\n\r\n\x05\x04\x13\x02\
+    \x1c\x01\x12\x04\xa1\x03\t\x18\n\r\n\x05\x04\x13\x02\x1c\x03\x12\x04\xa1\
+    \x03\x1b\x1d\nh\n\x02\x06\0\x12\x06\xa6\x03\0\xb6\x03\x01\x1aZ\x20Channe\
+    lz\x20is\x20a\x20service\x20exposed\x20by\x20gRPC\x20servers\x20that\x20\
+    provides\x20detailed\x20debug\n\x20information.\n\n\x0b\n\x03\x06\0\x01\
+    \x12\x04\xa6\x03\x08\x10\n\x9c\x01\n\x04\x06\0\x02\0\x12\x04\xa9\x03\x02\
+    M\x1a\x8d\x01\x20Gets\x20all\x20root\x20channels\x20(i.e.\x20channels\
+    \x20the\x20application\x20has\x20directly\n\x20created).\x20This\x20does\
+    \x20not\x20include\x20subchannels\x20nor\x20non-top\x20level\x20channels\
+    .\n\n\r\n\x05\x06\0\x02\0\x01\x12\x04\xa9\x03\x06\x14\n\r\n\x05\x06\0\
+    \x02\0\x02\x12\x04\xa9\x03\x15*\n\r\n\x05\x06\0\x02\0\x03\x12\x04\xa9\
+    \x035K\n;\n\x04\x06\0\x02\x01\x12\x04\xab\x03\x02A\x1a-\x20Gets\x20all\
+    \x20servers\x20that\x20exist\x20in\x20the\x20process.\n\n\r\n\x05\x06\0\
+    \x02\x01\x01\x12\x04\xab\x03\x06\x10\n\r\n\x05\x06\0\x02\x01\x02\x12\x04\
+    \xab\x03\x11\"\n\r\n\x05\x06\0\x02\x01\x03\x12\x04\xab\x03-?\nB\n\x04\
+    \x06\0\x02\x02\x12\x04\xad\x03\x02>\x1a4\x20Returns\x20a\x20single\x20Se\
+    rver,\x20or\x20else\x20a\x20NOT_FOUND\x20code.\n\n\r\n\x05\x06\0\x02\x02\
+    \x01\x12\x04\xad\x03\x06\x0f\n\r\n\x05\x06\0\x02\x02\x02\x12\x04\xad\x03\
+    \x10\x20\n\r\n\x05\x06\0\x02\x02\x03\x12\x04\xad\x03+<\nB\n\x04\x06\0\
+    \x02\x03\x12\x04\xaf\x03\x02S\x1a4\x20Gets\x20all\x20server\x20sockets\
+    \x20that\x20exist\x20in\x20the\x20process.\n\n\r\n\x05\x06\0\x02\x03\x01\
+    \x12\x04\xaf\x03\x06\x16\n\r\n\x05\x06\0\x02\x03\x02\x12\x04\xaf\x03\x17\
+    .\n\r\n\x05\x06\0\x02\x03\x03\x12\x04\xaf\x039Q\nC\n\x04\x06\0\x02\x04\
+    \x12\x04\xb1\x03\x02A\x1a5\x20Returns\x20a\x20single\x20Channel,\x20or\
+    \x20else\x20a\x20NOT_FOUND\x20code.\n\n\r\n\x05\x06\0\x02\x04\x01\x12\
+    \x04\xb1\x03\x06\x10\n\r\n\x05\x06\0\x02\x04\x02\x12\x04\xb1\x03\x11\"\n\
+    \r\n\x05\x06\0\x02\x04\x03\x12\x04\xb1\x03-?\nF\n\x04\x06\0\x02\x05\x12\
+    \x04\xb3\x03\x02J\x1a8\x20Returns\x20a\x20single\x20Subchannel,\x20or\
+    \x20else\x20a\x20NOT_FOUND\x20code.\n\n\r\n\x05\x06\0\x02\x05\x01\x12\
+    \x04\xb3\x03\x06\x13\n\r\n\x05\x06\0\x02\x05\x02\x12\x04\xb3\x03\x14(\n\
+    \r\n\x05\x06\0\x02\x05\x03\x12\x04\xb3\x033H\nA\n\x04\x06\0\x02\x06\x12\
+    \x04\xb5\x03\x02>\x1a3\x20Returns\x20a\x20single\x20Socket\x20or\x20else\
+    \x20a\x20NOT_FOUND\x20code.\n\n\r\n\x05\x06\0\x02\x06\x01\x12\x04\xb5\
+    \x03\x06\x0f\n\r\n\x05\x06\0\x02\x06\x02\x12\x04\xb5\x03\x10\x20\n\r\n\
+    \x05\x06\0\x02\x06\x03\x12\x04\xb5\x03+<\n\x0c\n\x02\x04\x14\x12\x06\xb8\
+    \x03\0\xc4\x03\x01\n\x0b\n\x03\x04\x14\x01\x12\x04\xb8\x03\x08\x1d\n\x95\
+    \x02\n\x04\x04\x14\x02\0\x12\x04\xbe\x03\x02\x1d\x1a\x86\x02\x20start_ch\
+    annel_id\x20indicates\x20that\x20only\x20channels\x20at\x20or\x20above\
+    \x20this\x20id\x20should\x20be\n\x20included\x20in\x20the\x20results.\n\
+    \x20To\x20request\x20the\x20first\x20page,\x20this\x20should\x20be\x20se\
+    t\x20to\x200.\x20To\x20request\n\x20subsequent\x20pages,\x20the\x20clien\
+    t\x20generates\x20this\x20value\x20by\x20adding\x201\x20to\n\x20the\x20h\
+    ighest\x20seen\x20result\x20ID.\n\n\r\n\x05\x04\x14\x02\0\x05\x12\x04\
+    \xbe\x03\x02\x07\n\r\n\x05\x04\x14\x02\0\x01\x12\x04\xbe\x03\x08\x18\n\r\
+    \n\x05\x04\x14\x02\0\x03\x12\x04\xbe\x03\x1b\x1c\n\xbd\x01\n\x04\x04\x14\
+    \x02\x01\x12\x04\xc3\x03\x02\x18\x1a\xae\x01\x20If\x20non-zero,\x20the\
+    \x20server\x20will\x20return\x20a\x20page\x20of\x20results\x20containing\
+    \n\x20at\x20most\x20this\x20many\x20items.\x20If\x20zero,\x20the\x20serv\
+    er\x20will\x20choose\x20a\n\x20reasonable\x20page\x20size.\x20\x20Must\
+    \x20never\x20be\x20negative.\n\n\r\n\x05\x04\x14\x02\x01\x05\x12\x04\xc3\
+    \x03\x02\x07\n\r\n\x05\x04\x14\x02\x01\x01\x12\x04\xc3\x03\x08\x13\n\r\n\
+    \x05\x04\x14\x02\x01\x03\x12\x04\xc3\x03\x16\x17\n\x0c\n\x02\x04\x15\x12\
+    \x06\xc6\x03\0\xcf\x03\x01\n\x0b\n\x03\x04\x15\x01\x12\x04\xc6\x03\x08\
+    \x1e\n\xb8\x01\n\x04\x04\x15\x02\0\x12\x04\xca\x03\x02\x1f\x1a\xa9\x01\
+    \x20list\x20of\x20channels\x20that\x20the\x20connection\x20detail\x20ser\
+    vice\x20knows\x20about.\x20\x20Sorted\x20in\n\x20ascending\x20channel_id\
+    \x20order.\n\x20Must\x20contain\x20at\x20least\x201\x20result,\x20otherw\
+    ise\x20'end'\x20must\x20be\x20true.\n\n\r\n\x05\x04\x15\x02\0\x04\x12\
+    \x04\xca\x03\x02\n\n\r\n\x05\x04\x15\x02\0\x06\x12\x04\xca\x03\x0b\x12\n\
+    \r\n\x05\x04\x15\x02\0\x01\x12\x04\xca\x03\x13\x1a\n\r\n\x05\x04\x15\x02\
+    \0\x03\x12\x04\xca\x03\x1d\x1e\n\xae\x01\n\x04\x04\x15\x02\x01\x12\x04\
+    \xce\x03\x02\x0f\x1a\x9f\x01\x20If\x20set,\x20indicates\x20that\x20the\
+    \x20list\x20of\x20channels\x20is\x20the\x20final\x20list.\x20\x20Request\
+    ing\n\x20more\x20channels\x20can\x20only\x20return\x20more\x20if\x20they\
+    \x20are\x20created\x20after\x20this\x20RPC\n\x20completes.\n\n\r\n\x05\
+    \x04\x15\x02\x01\x05\x12\x04\xce\x03\x02\x06\n\r\n\x05\x04\x15\x02\x01\
+    \x01\x12\x04\xce\x03\x07\n\n\r\n\x05\x04\x15\x02\x01\x03\x12\x04\xce\x03\
+    \r\x0e\n\x0c\n\x02\x04\x16\x12\x06\xd1\x03\0\xdd\x03\x01\n\x0b\n\x03\x04\
+    \x16\x01\x12\x04\xd1\x03\x08\x19\n\x91\x02\n\x04\x04\x16\x02\0\x12\x04\
+    \xd7\x03\x02\x1c\x1a\x82\x02\x20start_server_id\x20indicates\x20that\x20\
+    only\x20servers\x20at\x20or\x20above\x20this\x20id\x20should\x20be\n\x20\
+    included\x20in\x20the\x20results.\n\x20To\x20request\x20the\x20first\x20\
+    page,\x20this\x20must\x20be\x20set\x20to\x200.\x20To\x20request\n\x20sub\
+    sequent\x20pages,\x20the\x20client\x20generates\x20this\x20value\x20by\
+    \x20adding\x201\x20to\n\x20the\x20highest\x20seen\x20result\x20ID.\n\n\r\
+    \n\x05\x04\x16\x02\0\x05\x12\x04\xd7\x03\x02\x07\n\r\n\x05\x04\x16\x02\0\
+    \x01\x12\x04\xd7\x03\x08\x17\n\r\n\x05\x04\x16\x02\0\x03\x12\x04\xd7\x03\
+    \x1a\x1b\n\xbd\x01\n\x04\x04\x16\x02\x01\x12\x04\xdc\x03\x02\x18\x1a\xae\
+    \x01\x20If\x20non-zero,\x20the\x20server\x20will\x20return\x20a\x20page\
+    \x20of\x20results\x20containing\n\x20at\x20most\x20this\x20many\x20items\
+    .\x20If\x20zero,\x20the\x20server\x20will\x20choose\x20a\n\x20reasonable\
+    \x20page\x20size.\x20\x20Must\x20never\x20be\x20negative.\n\n\r\n\x05\
+    \x04\x16\x02\x01\x05\x12\x04\xdc\x03\x02\x07\n\r\n\x05\x04\x16\x02\x01\
+    \x01\x12\x04\xdc\x03\x08\x13\n\r\n\x05\x04\x16\x02\x01\x03\x12\x04\xdc\
+    \x03\x16\x17\n\x0c\n\x02\x04\x17\x12\x06\xdf\x03\0\xe8\x03\x01\n\x0b\n\
+    \x03\x04\x17\x01\x12\x04\xdf\x03\x08\x1a\n\xb6\x01\n\x04\x04\x17\x02\0\
+    \x12\x04\xe3\x03\x02\x1d\x1a\xa7\x01\x20list\x20of\x20servers\x20that\
+    \x20the\x20connection\x20detail\x20service\x20knows\x20about.\x20\x20Sor\
+    ted\x20in\n\x20ascending\x20server_id\x20order.\n\x20Must\x20contain\x20\
+    at\x20least\x201\x20result,\x20otherwise\x20'end'\x20must\x20be\x20true.\
+    \n\n\r\n\x05\x04\x17\x02\0\x04\x12\x04\xe3\x03\x02\n\n\r\n\x05\x04\x17\
+    \x02\0\x06\x12\x04\xe3\x03\x0b\x11\n\r\n\x05\x04\x17\x02\0\x01\x12\x04\
+    \xe3\x03\x12\x18\n\r\n\x05\x04\x17\x02\0\x03\x12\x04\xe3\x03\x1b\x1c\n\
+    \xad\x01\n\x04\x04\x17\x02\x01\x12\x04\xe7\x03\x02\x0f\x1a\x9e\x01\x20If\
+    \x20set,\x20indicates\x20that\x20the\x20list\x20of\x20servers\x20is\x20t\
+    he\x20final\x20list.\x20\x20Requesting\n\x20more\x20servers\x20will\x20o\
+    nly\x20return\x20more\x20if\x20they\x20are\x20created\x20after\x20this\
+    \x20RPC\n\x20completes.\n\n\r\n\x05\x04\x17\x02\x01\x05\x12\x04\xe7\x03\
+    \x02\x06\n\r\n\x05\x04\x17\x02\x01\x01\x12\x04\xe7\x03\x07\n\n\r\n\x05\
+    \x04\x17\x02\x01\x03\x12\x04\xe7\x03\r\x0e\n\x0c\n\x02\x04\x18\x12\x06\
+    \xea\x03\0\xed\x03\x01\n\x0b\n\x03\x04\x18\x01\x12\x04\xea\x03\x08\x18\n\
+    J\n\x04\x04\x18\x02\0\x12\x04\xec\x03\x02\x16\x1a<\x20server_id\x20is\
+    \x20the\x20identifier\x20of\x20the\x20specific\x20server\x20to\x20get.\n\
+    \n\r\n\x05\x04\x18\x02\0\x05\x12\x04\xec\x03\x02\x07\n\r\n\x05\x04\x18\
+    \x02\0\x01\x12\x04\xec\x03\x08\x11\n\r\n\x05\x04\x18\x02\0\x03\x12\x04\
+    \xec\x03\x14\x15\n\x0c\n\x02\x04\x19\x12\x06\xef\x03\0\xf3\x03\x01\n\x0b\
+    \n\x03\x04\x19\x01\x12\x04\xef\x03\x08\x19\nc\n\x04\x04\x19\x02\0\x12\
+    \x04\xf2\x03\x02\x14\x1aU\x20The\x20Server\x20that\x20corresponds\x20to\
+    \x20the\x20requested\x20server_id.\x20\x20This\x20field\n\x20should\x20b\
+    e\x20set.\n\n\r\n\x05\x04\x19\x02\0\x06\x12\x04\xf2\x03\x02\x08\n\r\n\
+    \x05\x04\x19\x02\0\x01\x12\x04\xf2\x03\t\x0f\n\r\n\x05\x04\x19\x02\0\x03\
+    \x12\x04\xf2\x03\x12\x13\n\x0c\n\x02\x04\x1a\x12\x06\xf5\x03\0\x82\x04\
+    \x01\n\x0b\n\x03\x04\x1a\x01\x12\x04\xf5\x03\x08\x1f\n\x0c\n\x04\x04\x1a\
+    \x02\0\x12\x04\xf6\x03\x02\x16\n\r\n\x05\x04\x1a\x02\0\x05\x12\x04\xf6\
+    \x03\x02\x07\n\r\n\x05\x04\x1a\x02\0\x01\x12\x04\xf6\x03\x08\x11\n\r\n\
+    \x05\x04\x1a\x02\0\x03\x12\x04\xf6\x03\x14\x15\n\x91\x02\n\x04\x04\x1a\
+    \x02\x01\x12\x04\xfc\x03\x02\x1c\x1a\x82\x02\x20start_socket_id\x20indic\
+    ates\x20that\x20only\x20sockets\x20at\x20or\x20above\x20this\x20id\x20sh\
+    ould\x20be\n\x20included\x20in\x20the\x20results.\n\x20To\x20request\x20\
+    the\x20first\x20page,\x20this\x20must\x20be\x20set\x20to\x200.\x20To\x20\
+    request\n\x20subsequent\x20pages,\x20the\x20client\x20generates\x20this\
+    \x20value\x20by\x20adding\x201\x20to\n\x20the\x20highest\x20seen\x20resu\
+    lt\x20ID.\n\n\r\n\x05\x04\x1a\x02\x01\x05\x12\x04\xfc\x03\x02\x07\n\r\n\
+    \x05\x04\x1a\x02\x01\x01\x12\x04\xfc\x03\x08\x17\n\r\n\x05\x04\x1a\x02\
+    \x01\x03\x12\x04\xfc\x03\x1a\x1b\n\xbd\x01\n\x04\x04\x1a\x02\x02\x12\x04\
+    \x81\x04\x02\x18\x1a\xae\x01\x20If\x20non-zero,\x20the\x20server\x20will\
+    \x20return\x20a\x20page\x20of\x20results\x20containing\n\x20at\x20most\
+    \x20this\x20many\x20items.\x20If\x20zero,\x20the\x20server\x20will\x20ch\
+    oose\x20a\n\x20reasonable\x20page\x20size.\x20\x20Must\x20never\x20be\
+    \x20negative.\n\n\r\n\x05\x04\x1a\x02\x02\x05\x12\x04\x81\x04\x02\x07\n\
+    \r\n\x05\x04\x1a\x02\x02\x01\x12\x04\x81\x04\x08\x13\n\r\n\x05\x04\x1a\
+    \x02\x02\x03\x12\x04\x81\x04\x16\x17\n\x0c\n\x02\x04\x1b\x12\x06\x84\x04\
+    \0\x8d\x04\x01\n\x0b\n\x03\x04\x1b\x01\x12\x04\x84\x04\x08\x20\n\xba\x01\
+    \n\x04\x04\x1b\x02\0\x12\x04\x88\x04\x02$\x1a\xab\x01\x20list\x20of\x20s\
+    ocket\x20refs\x20that\x20the\x20connection\x20detail\x20service\x20knows\
+    \x20about.\x20\x20Sorted\x20in\n\x20ascending\x20socket_id\x20order.\n\
+    \x20Must\x20contain\x20at\x20least\x201\x20result,\x20otherwise\x20'end'\
+    \x20must\x20be\x20true.\n\n\r\n\x05\x04\x1b\x02\0\x04\x12\x04\x88\x04\
+    \x02\n\n\r\n\x05\x04\x1b\x02\0\x06\x12\x04\x88\x04\x0b\x14\n\r\n\x05\x04\
+    \x1b\x02\0\x01\x12\x04\x88\x04\x15\x1f\n\r\n\x05\x04\x1b\x02\0\x03\x12\
+    \x04\x88\x04\"#\n\xad\x01\n\x04\x04\x1b\x02\x01\x12\x04\x8c\x04\x02\x0f\
+    \x1a\x9e\x01\x20If\x20set,\x20indicates\x20that\x20the\x20list\x20of\x20\
+    sockets\x20is\x20the\x20final\x20list.\x20\x20Requesting\n\x20more\x20so\
+    ckets\x20will\x20only\x20return\x20more\x20if\x20they\x20are\x20created\
+    \x20after\x20this\x20RPC\n\x20completes.\n\n\r\n\x05\x04\x1b\x02\x01\x05\
+    \x12\x04\x8c\x04\x02\x06\n\r\n\x05\x04\x1b\x02\x01\x01\x12\x04\x8c\x04\
+    \x07\n\n\r\n\x05\x04\x1b\x02\x01\x03\x12\x04\x8c\x04\r\x0e\n\x0c\n\x02\
+    \x04\x1c\x12\x06\x8f\x04\0\x92\x04\x01\n\x0b\n\x03\x04\x1c\x01\x12\x04\
+    \x8f\x04\x08\x19\nL\n\x04\x04\x1c\x02\0\x12\x04\x91\x04\x02\x17\x1a>\x20\
+    channel_id\x20is\x20the\x20identifier\x20of\x20the\x20specific\x20channe\
+    l\x20to\x20get.\n\n\r\n\x05\x04\x1c\x02\0\x05\x12\x04\x91\x04\x02\x07\n\
+    \r\n\x05\x04\x1c\x02\0\x01\x12\x04\x91\x04\x08\x12\n\r\n\x05\x04\x1c\x02\
+    \0\x03\x12\x04\x91\x04\x15\x16\n\x0c\n\x02\x04\x1d\x12\x06\x94\x04\0\x98\
+    \x04\x01\n\x0b\n\x03\x04\x1d\x01\x12\x04\x94\x04\x08\x1a\ne\n\x04\x04\
+    \x1d\x02\0\x12\x04\x97\x04\x02\x16\x1aW\x20The\x20Channel\x20that\x20cor\
+    responds\x20to\x20the\x20requested\x20channel_id.\x20\x20This\x20field\n\
+    \x20should\x20be\x20set.\n\n\r\n\x05\x04\x1d\x02\0\x06\x12\x04\x97\x04\
+    \x02\t\n\r\n\x05\x04\x1d\x02\0\x01\x12\x04\x97\x04\n\x11\n\r\n\x05\x04\
+    \x1d\x02\0\x03\x12\x04\x97\x04\x14\x15\n\x0c\n\x02\x04\x1e\x12\x06\x9a\
+    \x04\0\x9d\x04\x01\n\x0b\n\x03\x04\x1e\x01\x12\x04\x9a\x04\x08\x1c\nR\n\
+    \x04\x04\x1e\x02\0\x12\x04\x9c\x04\x02\x1a\x1aD\x20subchannel_id\x20is\
+    \x20the\x20identifier\x20of\x20the\x20specific\x20subchannel\x20to\x20ge\
+    t.\n\n\r\n\x05\x04\x1e\x02\0\x05\x12\x04\x9c\x04\x02\x07\n\r\n\x05\x04\
+    \x1e\x02\0\x01\x12\x04\x9c\x04\x08\x15\n\r\n\x05\x04\x1e\x02\0\x03\x12\
+    \x04\x9c\x04\x18\x19\n\x0c\n\x02\x04\x1f\x12\x06\x9f\x04\0\xa3\x04\x01\n\
+    \x0b\n\x03\x04\x1f\x01\x12\x04\x9f\x04\x08\x1d\nk\n\x04\x04\x1f\x02\0\
+    \x12\x04\xa2\x04\x02\x1c\x1a]\x20The\x20Subchannel\x20that\x20correspond\
+    s\x20to\x20the\x20requested\x20subchannel_id.\x20\x20This\n\x20field\x20\
+    should\x20be\x20set.\n\n\r\n\x05\x04\x1f\x02\0\x06\x12\x04\xa2\x04\x02\
+    \x0c\n\r\n\x05\x04\x1f\x02\0\x01\x12\x04\xa2\x04\r\x17\n\r\n\x05\x04\x1f\
+    \x02\0\x03\x12\x04\xa2\x04\x1a\x1b\n\x0c\n\x02\x04\x20\x12\x06\xa5\x04\0\
+    \xad\x04\x01\n\x0b\n\x03\x04\x20\x01\x12\x04\xa5\x04\x08\x18\nJ\n\x04\
+    \x04\x20\x02\0\x12\x04\xa7\x04\x02\x16\x1a<\x20socket_id\x20is\x20the\
+    \x20identifier\x20of\x20the\x20specific\x20socket\x20to\x20get.\n\n\r\n\
+    \x05\x04\x20\x02\0\x05\x12\x04\xa7\x04\x02\x07\n\r\n\x05\x04\x20\x02\0\
+    \x01\x12\x04\xa7\x04\x08\x11\n\r\n\x05\x04\x20\x02\0\x03\x12\x04\xa7\x04\
+    \x14\x15\n\x9b\x01\n\x04\x04\x20\x02\x01\x12\x04\xac\x04\x02\x13\x1a\x8c\
+    \x01\x20If\x20true,\x20the\x20response\x20will\x20contain\x20only\x20hig\
+    h\x20level\x20information\n\x20that\x20is\x20inexpensive\x20to\x20obtain\
+    .\x20Fields\x20thay\x20may\x20be\x20omitted\x20are\n\x20documented.\n\n\
+    \r\n\x05\x04\x20\x02\x01\x05\x12\x04\xac\x04\x02\x06\n\r\n\x05\x04\x20\
+    \x02\x01\x01\x12\x04\xac\x04\x07\x0e\n\r\n\x05\x04\x20\x02\x01\x03\x12\
+    \x04\xac\x04\x11\x12\n\x0c\n\x02\x04!\x12\x06\xaf\x04\0\xb3\x04\x01\n\
+    \x0b\n\x03\x04!\x01\x12\x04\xaf\x04\x08\x19\nc\n\x04\x04!\x02\0\x12\x04\
+    \xb2\x04\x02\x14\x1aU\x20The\x20Socket\x20that\x20corresponds\x20to\x20t\
+    he\x20requested\x20socket_id.\x20\x20This\x20field\n\x20should\x20be\x20\
+    set.\n\n\r\n\x05\x04!\x02\0\x06\x12\x04\xb2\x04\x02\x08\n\r\n\x05\x04!\
+    \x02\0\x01\x12\x04\xb2\x04\t\x0f\n\r\n\x05\x04!\x02\0\x03\x12\x04\xb2\
+    \x04\x12\x13b\x06proto3\
+";
+
+static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
+
+fn parse_descriptor_proto() -> ::protobuf::descriptor::FileDescriptorProto {
+    ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
+}
+
+pub fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
+    file_descriptor_proto_lazy.get(|| {
+        parse_descriptor_proto()
+    })
+}
@@ -0,0 +1,486 @@
+// This file is generated. Do not edit
+// @generated
+
+// https://github.com/Manishearth/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unsafe_code)]
+#![allow(unused_imports)]
+#![allow(unused_results)]
+
+const FILE_DESCRIPTORS: &[&[u8]] = &[
+    b"\n\x1cgrpc/channelz/channelz.proto\x12\x10grpc.channelz.v1\x1a\x19google/protobuf/any.proto\x1a\x1egoogle/protobuf/duration.proto\x1a\x1fgoogle/protobuf/timestamp.proto\x1a\x1egoogle/protobuf/wrappers.proto\"\xaf\x02\n\x07Channel\x12.\n\x03ref\x18\x01 \x01(\x0b2\x1c.grpc.channelz.v1.ChannelRefR\x03ref\x121\n\x04data\x18\x02 \x01(\x0b2\x1d.grpc.channelz.v1.ChannelDataR\x04data\x12=\n\x0bchannel_ref\x18\x03 \x03(\x0b2\x1c.grpc.channelz.v1.ChannelRefR\nchannelRef\x12F\n\x0esubchannel_ref\x18\x04 \x03(\x0b2\x1f.grpc.channelz.v1.SubchannelRefR\rsubchannelRef\x12:\n\nsocket_ref\x18\x05 \x03(\x0b2\x1b.grpc.channelz.v1.SocketRefR\tsocketRef\"\xb5\x02\n\nSubchannel\x121\n\x03ref\x18\x01 \x01(\x0b2\x1f.grpc.channelz.v1.SubchannelRefR\x03ref\x121\n\x04data\x18\x02 \x01(\x0b2\x1d.grpc.channelz.v1.ChannelDataR\x04data\x12=\n\x0bchannel_ref\x18\x03 \x03(\x0b2\x1c.grpc.channelz.v1.ChannelRefR\nchannelRef\x12F\n\x0esubchannel_ref\x18\x04 \x03(\x0b2\x1f.grpc.channelz.v1.SubchannelRefR\rsubchannelRef\x12:\n\nsocket_ref\x18\x05 \x03(\x0b2\x1b.grpc.channelz.v1.SocketRefR\tsocketRef\"\xc2\x01\n\x18ChannelConnectivityState\x12F\n\x05state\x18\x01 \x01(\x0e20.grpc.channelz.v1.ChannelConnectivityState.StateR\x05state\"^\n\x05State\x12\x0b\n\x07UNKNOWN\x10\x00\x12\x08\n\x04IDLE\x10\x01\x12\x0e\n\nCONNECTING\x10\x02\x12\t\n\x05READY\x10\x03\x12\x15\n\x11TRANSIENT_FAILURE\x10\x04\x12\x0c\n\x08SHUTDOWN\x10\x05\"\xe9\x02\n\x0bChannelData\x12@\n\x05state\x18\x01 \x01(\x0b2*.grpc.channelz.v1.ChannelConnectivityStateR\x05state\x12\x16\n\x06target\x18\x02 \x01(\tR\x06target\x124\n\x05trace\x18\x03 \x01(\x0b2\x1e.grpc.channelz.v1.ChannelTraceR\x05trace\x12#\n\rcalls_started\x18\x04 \x01(\x03R\x0ccallsStarted\x12\'\n\x0fcalls_succeeded\x18\x05 \x01(\x03R\x0ecallsSucceeded\x12!\n\x0ccalls_failed\x18\x06 \x01(\x03R\x0bcallsFailed\x12Y\n\x1blast_call_started_timestamp\x18\x07 \x01(\x0b2\x1a.google.protobuf.TimestampR\x18lastCallStartedTimestamp\"\x98\x03\n\x11ChannelTraceEvent\x12 \n\x0bdescription\x18\x01 \x01(\tR\x0bdescription\x12H\n\x08severity\x18\x02 \x01(\x0e2,.grpc.channelz.v1.ChannelTraceEvent.SeverityR\x08severity\x128\n\ttimestamp\x18\x03 \x01(\x0b2\x1a.google.protobuf.TimestampR\ttimestamp\x12?\n\x0bchannel_ref\x18\x04 \x01(\x0b2\x1c.grpc.channelz.v1.ChannelRefH\x00R\nchannelRef\x12H\n\x0esubchannel_ref\x18\x05 \x01(\x0b2\x1f.grpc.channelz.v1.SubchannelRefH\x00R\rsubchannelRef\"E\n\x08Severity\x12\x0e\n\nCT_UNKNOWN\x10\x00\x12\x0b\n\x07CT_INFO\x10\x01\x12\x0e\n\nCT_WARNING\x10\x02\x12\x0c\n\x08CT_ERROR\x10\x03B\x0b\n\tchild_ref\"\xc2\x01\n\x0cChannelTrace\x12*\n\x11num_events_logged\x18\x01 \x01(\x03R\x0fnumEventsLogged\x12I\n\x12creation_timestamp\x18\x02 \x01(\x0b2\x1a.google.protobuf.TimestampR\x11creationTimestamp\x12;\n\x06events\x18\x03 \x03(\x0b2#.grpc.channelz.v1.ChannelTraceEventR\x06events\"c\n\nChannelRef\x12\x1d\n\nchannel_id\x18\x01 \x01(\x03R\tchannelId\x12\x12\n\x04name\x18\x02 \x01(\tR\x04nameJ\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\x05\x10\x06J\x04\x08\x06\x10\x07J\x04\x08\x07\x10\x08J\x04\x08\x08\x10\t\"l\n\rSubchannelRef\x12#\n\rsubchannel_id\x18\x07 \x01(\x03R\x0csubchannelId\x12\x12\n\x04name\x18\x08 \x01(\tR\x04nameJ\x04\x08\x01\x10\x02J\x04\x08\x02\x10\x03J\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\x05\x10\x06J\x04\x08\x06\x10\x07\"`\n\tSocketRef\x12\x1b\n\tsocket_id\x18\x03 \x01(\x03R\x08socketId\x12\x12\n\x04name\x18\x04 \x01(\tR\x04nameJ\x04\x08\x01\x10\x02J\x04\x08\x02\x10\x03J\x04\x08\x05\x10\x06J\x04\x08\x06\x10\x07J\x04\x08\x07\x10\x08J\x04\x08\x08\x10\t\"`\n\tServerRef\x12\x1b\n\tserver_id\x18\x05 \x01(\x03R\x08serverId\x12\x12\n\x04name\x18\x06 \x01(\tR\x04nameJ\x04\x08\x01\x10\x02J\x04\x08\x02\x10\x03J\x04\x08\x03\x10\x04J\x04\x08\x04\x10\x05J\x04\x08\x07\x10\x08J\x04\x08\x08\x10\t\"\xab\x01\n\x06Server\x12-\n\x03ref\x18\x01 \x01(\x0b2\x1b.grpc.channelz.v1.ServerRefR\x03ref\x120\n\x04data\x18\x02 \x01(\x0b2\x1c.grpc.channelz.v1.ServerDataR\x04data\x12@\n\rlisten_socket\x18\x03 \x03(\x0b2\x1b.grpc.channelz.v1.SocketRefR\x0clistenSocket\"\x8e\x02\n\nServerData\x124\n\x05trace\x18\x01 \x01(\x0b2\x1e.grpc.channelz.v1.ChannelTraceR\x05trace\x12#\n\rcalls_started\x18\x02 \x01(\x03R\x0ccallsStarted\x12\'\n\x0fcalls_succeeded\x18\x03 \x01(\x03R\x0ecallsSucceeded\x12!\n\x0ccalls_failed\x18\x04 \x01(\x03R\x0bcallsFailed\x12Y\n\x1blast_call_started_timestamp\x18\x05 \x01(\x0b2\x1a.google.protobuf.TimestampR\x18lastCallStartedTimestamp\"\xa6\x02\n\x06Socket\x12-\n\x03ref\x18\x01 \x01(\x0b2\x1b.grpc.channelz.v1.SocketRefR\x03ref\x120\n\x04data\x18\x02 \x01(\x0b2\x1c.grpc.channelz.v1.SocketDataR\x04data\x12/\n\x05local\x18\x03 \x01(\x0b2\x19.grpc.channelz.v1.AddressR\x05local\x121\n\x06remote\x18\x04 \x01(\x0b2\x19.grpc.channelz.v1.AddressR\x06remote\x126\n\x08security\x18\x05 \x01(\x0b2\x1a.grpc.channelz.v1.SecurityR\x08security\x12\x1f\n\x0bremote_name\x18\x06 \x01(\tR\nremoteName\"\x83\x07\n\nSocketData\x12\'\n\x0fstreams_started\x18\x01 \x01(\x03R\x0estreamsStarted\x12+\n\x11streams_succeeded\x18\x02 \x01(\x03R\x10streamsSucceeded\x12%\n\x0estreams_failed\x18\x03 \x01(\x03R\rstreamsFailed\x12#\n\rmessages_sent\x18\x04 \x01(\x03R\x0cmessagesSent\x12+\n\x11messages_received\x18\x05 \x01(\x03R\x10messagesReceived\x12(\n\x10keep_alives_sent\x18\x06 \x01(\x03R\x0ekeepAlivesSent\x12h\n#last_local_stream_created_timestamp\x18\x07 \x01(\x0b2\x1a.google.protobuf.TimestampR\x1flastLocalStreamCreatedTimestamp\x12j\n$last_remote_stream_created_timestamp\x18\x08 \x01(\x0b2\x1a.google.protobuf.TimestampR lastRemoteStreamCreatedTimestamp\x12Y\n\x1blast_message_sent_timestamp\x18\t \x01(\x0b2\x1a.google.protobuf.TimestampR\x18lastMessageSentTimestamp\x12a\n\x1flast_message_received_timestamp\x18\n \x01(\x0b2\x1a.google.protobuf.TimestampR\x1clastMessageReceivedTimestamp\x12V\n\x19local_flow_control_window\x18\x0b \x01(\x0b2\x1b.google.protobuf.Int64ValueR\x16localFlowControlWindow\x12X\n\x1aremote_flow_control_window\x18\x0c \x01(\x0b2\x1b.google.protobuf.Int64ValueR\x17remoteFlowControlWindow\x126\n\x06option\x18\r \x03(\x0b2\x1e.grpc.channelz.v1.SocketOptionR\x06option\"\xb8\x03\n\x07Address\x12M\n\rtcpip_address\x18\x01 \x01(\x0b2&.grpc.channelz.v1.Address.TcpIpAddressH\x00R\x0ctcpipAddress\x12G\n\x0buds_address\x18\x02 \x01(\x0b2$.grpc.channelz.v1.Address.UdsAddressH\x00R\nudsAddress\x12M\n\rother_address\x18\x03 \x01(\x0b2&.grpc.channelz.v1.Address.OtherAddressH\x00R\x0cotherAddress\x1aA\n\x0cTcpIpAddress\x12\x1d\n\nip_address\x18\x01 \x01(\x0cR\tipAddress\x12\x12\n\x04port\x18\x02 \x01(\x05R\x04port\x1a(\n\nUdsAddress\x12\x1a\n\x08filename\x18\x01 \x01(\tR\x08filename\x1aN\n\x0cOtherAddress\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\x12*\n\x05value\x18\x02 \x01(\x0b2\x14.google.protobuf.AnyR\x05valueB\t\n\x07address\"\x96\x03\n\x08Security\x122\n\x03tls\x18\x01 \x01(\x0b2\x1e.grpc.channelz.v1.Security.TlsH\x00R\x03tls\x12@\n\x05other\x18\x02 \x01(\x0b2(.grpc.channelz.v1.Security.OtherSecurityH\x00R\x05other\x1a\xb9\x01\n\x03Tls\x12%\n\rstandard_name\x18\x01 \x01(\tH\x00R\x0cstandardName\x12\x1f\n\nother_name\x18\x02 \x01(\tH\x00R\totherName\x12+\n\x11local_certificate\x18\x03 \x01(\x0cR\x10localCertificate\x12-\n\x12remote_certificate\x18\x04 \x01(\x0cR\x11remoteCertificateB\x0e\n\x0ccipher_suite\x1aO\n\rOtherSecurity\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\x12*\n\x05value\x18\x02 \x01(\x0b2\x14.google.protobuf.AnyR\x05valueB\x07\n\x05model\"n\n\x0cSocketOption\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\x12\x14\n\x05value\x18\x02 \x01(\tR\x05value\x124\n\nadditional\x18\x03 \x01(\x0b2\x14.google.protobuf.AnyR\nadditional\"L\n\x13SocketOptionTimeout\x125\n\x08duration\x18\x01 \x01(\x0b2\x19.google.protobuf.DurationR\x08duration\"c\n\x12SocketOptionLinger\x12\x16\n\x06active\x18\x01 \x01(\x08R\x06active\x125\n\x08duration\x18\x02 \x01(\x0b2\x19.google.protobuf.DurationR\x08duration\"\xb2\x08\n\x13SocketOptionTcpInfo\x12\x1d\n\ntcpi_state\x18\x01 \x01(\rR\ttcpiState\x12\"\n\rtcpi_ca_state\x18\x02 \x01(\rR\x0btcpiCaState\x12)\n\x10tcpi_retransmits\x18\x03 \x01(\rR\x0ftcpiRetransmits\x12\x1f\n\x0btcpi_probes\x18\x04 \x01(\rR\ntcpiProbes\x12!\n\x0ctcpi_backoff\x18\x05 \x01(\rR\x0btcpiBackoff\x12!\n\x0ctcpi_options\x18\x06 \x01(\rR\x0btcpiOptions\x12&\n\x0ftcpi_snd_wscale\x18\x07 \x01(\rR\rtcpiSndWscale\x12&\n\x0ftcpi_rcv_wscale\x18\x08 \x01(\rR\rtcpiRcvWscale\x12\x19\n\x08tcpi_rto\x18\t \x01(\rR\x07tcpiRto\x12\x19\n\x08tcpi_ato\x18\n \x01(\rR\x07tcpiAto\x12 \n\x0ctcpi_snd_mss\x18\x0b \x01(\rR\ntcpiSndMss\x12 \n\x0ctcpi_rcv_mss\x18\x0c \x01(\rR\ntcpiRcvMss\x12!\n\x0ctcpi_unacked\x18\r \x01(\rR\x0btcpiUnacked\x12\x1f\n\x0btcpi_sacked\x18\x0e \x01(\rR\ntcpiSacked\x12\x1b\n\ttcpi_lost\x18\x0f \x01(\rR\x08tcpiLost\x12!\n\x0ctcpi_retrans\x18\x10 \x01(\rR\x0btcpiRetrans\x12!\n\x0ctcpi_fackets\x18\x11 \x01(\rR\x0btcpiFackets\x12-\n\x13tcpi_last_data_sent\x18\x12 \x01(\rR\x10tcpiLastDataSent\x12+\n\x12tcpi_last_ack_sent\x18\x13 \x01(\rR\x0ftcpiLastAckSent\x12-\n\x13tcpi_last_data_recv\x18\x14 \x01(\rR\x10tcpiLastDataRecv\x12+\n\x12tcpi_last_ack_recv\x18\x15 \x01(\rR\x0ftcpiLastAckRecv\x12\x1b\n\ttcpi_pmtu\x18\x16 \x01(\rR\x08tcpiPmtu\x12*\n\x11tcpi_rcv_ssthresh\x18\x17 \x01(\rR\x0ftcpiRcvSsthresh\x12\x19\n\x08tcpi_rtt\x18\x18 \x01(\rR\x07tcpiRtt\x12\x1f\n\x0btcpi_rttvar\x18\x19 \x01(\rR\ntcpiRttvar\x12*\n\x11tcpi_snd_ssthresh\x18\x1a \x01(\rR\x0ftcpiSndSsthresh\x12\"\n\rtcpi_snd_cwnd\x18\x1b \x01(\rR\x0btcpiSndCwnd\x12\x1f\n\x0btcpi_advmss\x18\x1c \x01(\rR\ntcpiAdvmss\x12\'\n\x0ftcpi_reordering\x18\x1d \x01(\rR\x0etcpiReordering\"b\n\x15GetTopChannelsRequest\x12(\n\x10start_channel_id\x18\x01 \x01(\x03R\x0estartChannelId\x12\x1f\n\x0bmax_results\x18\x02 \x01(\x03R\nmaxResults\"_\n\x16GetTopChannelsResponse\x123\n\x07channel\x18\x01 \x03(\x0b2\x19.grpc.channelz.v1.ChannelR\x07channel\x12\x10\n\x03end\x18\x02 \x01(\x08R\x03end\"\\\n\x11GetServersRequest\x12&\n\x0fstart_server_id\x18\x01 \x01(\x03R\rstartServerId\x12\x1f\n\x0bmax_results\x18\x02 \x01(\x03R\nmaxResults\"X\n\x12GetServersResponse\x120\n\x06server\x18\x01 \x03(\x0b2\x18.grpc.channelz.v1.ServerR\x06server\x12\x10\n\x03end\x18\x02 \x01(\x08R\x03end\"/\n\x10GetServerRequest\x12\x1b\n\tserver_id\x18\x01 \x01(\x03R\x08serverId\"E\n\x11GetServerResponse\x120\n\x06server\x18\x01 \x01(\x0b2\x18.grpc.channelz.v1.ServerR\x06server\"\x7f\n\x17GetServerSocketsRequest\x12\x1b\n\tserver_id\x18\x01 \x01(\x03R\x08serverId\x12&\n\x0fstart_socket_id\x18\x02 \x01(\x03R\rstartSocketId\x12\x1f\n\x0bmax_results\x18\x03 \x01(\x03R\nmaxResults\"h\n\x18GetServerSocketsResponse\x12:\n\nsocket_ref\x18\x01 \x03(\x0b2\x1b.grpc.channelz.v1.SocketRefR\tsocketRef\x12\x10\n\x03end\x18\x02 \x01(\x08R\x03end\"2\n\x11GetChannelRequest\x12\x1d\n\nchannel_id\x18\x01 \x01(\x03R\tchannelId\"I\n\x12GetChannelResponse\x123\n\x07channel\x18\x01 \x01(\x0b2\x19.grpc.channelz.v1.ChannelR\x07channel\";\n\x14GetSubchannelRequest\x12#\n\rsubchannel_id\x18\x01 \x01(\x03R\x0csubchannelId\"U\n\x15GetSubchannelResponse\x12<\n\nsubchannel\x18\x01 \x01(\x0b2\x1c.grpc.channelz.v1.SubchannelR\nsubchannel\"I\n\x10GetSocketRequest\x12\x1b\n\tsocket_id\x18\x01 \x01(\x03R\x08socketId\x12\x18\n\x07summary\x18\x02 \x01(\x08R\x07summary\"E\n\x11GetSocketResponse\x120\n\x06socket\x18\x01 \x01(\x0b2\x18.grpc.channelz.v1.SocketR\x06socket2\x9a\x05\n\x08Channelz\x12c\n\x0eGetTopChannels\x12\'.grpc.channelz.v1.GetTopChannelsRequest\x1a(.grpc.channelz.v1.GetTopChannelsResponse\x12W\n\nGetServers\x12#.grpc.channelz.v1.GetServersRequest\x1a$.grpc.channelz.v1.GetServersResponse\x12T\n\tGetServer\x12\".grpc.channelz.v1.GetServerRequest\x1a#.grpc.channelz.v1.GetServerResponse\x12i\n\x10GetServerSockets\x12).grpc.channelz.v1.GetServerSocketsRequest\x1a*.grpc.channelz.v1.GetServerSocketsResponse\x12W\n\nGetChannel\x12#.grpc.channelz.v1.GetChannelRequest\x1a$.grpc.channelz.v1.GetChannelResponse\x12`\n\rGetSubchannel\x12&.grpc.channelz.v1.GetSubchannelRequest\x1a\'.grpc.channelz.v1.GetSubchannelResponse\x12T\n\tGetSocket\x12\".grpc.channelz.v1.GetSocketRequest\x1a#.grpc.channelz.v1.GetSocketResponseBX\n\x13io.grpc.channelz.v1B\rChannelzProtoP\x01Z0google.golang.org/grpc/channelz/grpc_channelz_v1b\x06proto3",
+    b"\n\x19google/protobuf/any.proto\x12\x0fgoogle.protobuf\"6\n\x03Any\x12\x19\n\x08type_url\x18\x01 \x01(\tR\x07typeUrl\x12\x14\n\x05value\x18\x02 \x01(\x0cR\x05valueBv\n\x13com.google.protobufB\x08AnyProtoP\x01Z,google.golang.org/protobuf/types/known/anypb\xa2\x02\x03GPB\xaa\x02\x1eGoogle.Protobuf.WellKnownTypesb\x06proto3",
+    b"\n\x1egoogle/protobuf/duration.proto\x12\x0fgoogle.protobuf\":\n\x08Duration\x12\x18\n\x07seconds\x18\x01 \x01(\x03R\x07seconds\x12\x14\n\x05nanos\x18\x02 \x01(\x05R\x05nanosB\x83\x01\n\x13com.google.protobufB\rDurationProtoP\x01Z1google.golang.org/protobuf/types/known/durationpb\xf8\x01\x01\xa2\x02\x03GPB\xaa\x02\x1eGoogle.Protobuf.WellKnownTypesb\x06proto3",
+    b"\n\x1fgoogle/protobuf/timestamp.proto\x12\x0fgoogle.protobuf\";\n\tTimestamp\x12\x18\n\x07seconds\x18\x01 \x01(\x03R\x07seconds\x12\x14\n\x05nanos\x18\x02 \x01(\x05R\x05nanosB\x85\x01\n\x13com.google.protobufB\x0eTimestampProtoP\x01Z2google.golang.org/protobuf/types/known/timestamppb\xf8\x01\x01\xa2\x02\x03GPB\xaa\x02\x1eGoogle.Protobuf.WellKnownTypesb\x06proto3",
+    b"\n\x1egoogle/protobuf/wrappers.proto\x12\x0fgoogle.protobuf\"#\n\x0bDoubleValue\x12\x14\n\x05value\x18\x01 \x01(\x01R\x05value\"\"\n\nFloatValue\x12\x14\n\x05value\x18\x01 \x01(\x02R\x05value\"\"\n\nInt64Value\x12\x14\n\x05value\x18\x01 \x01(\x03R\x05value\"#\n\x0bUInt64Value\x12\x14\n\x05value\x18\x01 \x01(\x04R\x05value\"\"\n\nInt32Value\x12\x14\n\x05value\x18\x01 \x01(\x05R\x05value\"#\n\x0bUInt32Value\x12\x14\n\x05value\x18\x01 \x01(\rR\x05value\"!\n\tBoolValue\x12\x14\n\x05value\x18\x01 \x01(\x08R\x05value\"#\n\x0bStringValue\x12\x14\n\x05value\x18\x01 \x01(\tR\x05value\"\"\n\nBytesValue\x12\x14\n\x05value\x18\x01 \x01(\x0cR\x05valueB\x83\x01\n\x13com.google.protobufB\rWrappersProtoP\x01Z1google.golang.org/protobuf/types/known/wrapperspb\xf8\x01\x01\xa2\x02\x03GPB\xaa\x02\x1eGoogle.Protobuf.WellKnownTypesb\x06proto3",
+];
+
+const METHOD_CHANNELZ_GET_TOP_CHANNELS: ::grpcio::Method<
+    super::channelz::GetTopChannelsRequest,
+    super::channelz::GetTopChannelsResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetTopChannels",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_SERVERS: ::grpcio::Method<
+    super::channelz::GetServersRequest,
+    super::channelz::GetServersResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetServers",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_SERVER: ::grpcio::Method<
+    super::channelz::GetServerRequest,
+    super::channelz::GetServerResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetServer",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_SERVER_SOCKETS: ::grpcio::Method<
+    super::channelz::GetServerSocketsRequest,
+    super::channelz::GetServerSocketsResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetServerSockets",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_CHANNEL: ::grpcio::Method<
+    super::channelz::GetChannelRequest,
+    super::channelz::GetChannelResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetChannel",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_SUBCHANNEL: ::grpcio::Method<
+    super::channelz::GetSubchannelRequest,
+    super::channelz::GetSubchannelResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetSubchannel",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+const METHOD_CHANNELZ_GET_SOCKET: ::grpcio::Method<
+    super::channelz::GetSocketRequest,
+    super::channelz::GetSocketResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Unary,
+    name: "/grpc.channelz.v1.Channelz/GetSocket",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+#[derive(Clone)]
+pub struct ChannelzClient {
+    client: ::grpcio::Client,
+}
+
+impl ChannelzClient {
+    pub fn new(channel: ::grpcio::Channel) -> Self {
+        ChannelzClient {
+            client: ::grpcio::Client::new(channel),
+        }
+    }
+
+    pub fn with_pool(pool: ::grpcio::ChannelPool) -> Self {
+        ChannelzClient {
+            client: ::grpcio::Client::with_pool(pool),
+        }
+    }
+
+    pub fn get_top_channels_opt(
+        &self,
+        req: &super::channelz::GetTopChannelsRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetTopChannelsResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_TOP_CHANNELS, req, opt)
+    }
+
+    pub fn get_top_channels(
+        &self,
+        req: &super::channelz::GetTopChannelsRequest,
+    ) -> ::grpcio::Result<super::channelz::GetTopChannelsResponse> {
+        self.get_top_channels_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_top_channels_async_opt(
+        &self,
+        req: &super::channelz::GetTopChannelsRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetTopChannelsResponse>>
+    {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_TOP_CHANNELS, req, opt)
+    }
+
+    pub fn get_top_channels_async(
+        &self,
+        req: &super::channelz::GetTopChannelsRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetTopChannelsResponse>>
+    {
+        self.get_top_channels_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_servers_opt(
+        &self,
+        req: &super::channelz::GetServersRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetServersResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_SERVERS, req, opt)
+    }
+
+    pub fn get_servers(
+        &self,
+        req: &super::channelz::GetServersRequest,
+    ) -> ::grpcio::Result<super::channelz::GetServersResponse> {
+        self.get_servers_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_servers_async_opt(
+        &self,
+        req: &super::channelz::GetServersRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServersResponse>> {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_SERVERS, req, opt)
+    }
+
+    pub fn get_servers_async(
+        &self,
+        req: &super::channelz::GetServersRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServersResponse>> {
+        self.get_servers_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_server_opt(
+        &self,
+        req: &super::channelz::GetServerRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetServerResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_SERVER, req, opt)
+    }
+
+    pub fn get_server(
+        &self,
+        req: &super::channelz::GetServerRequest,
+    ) -> ::grpcio::Result<super::channelz::GetServerResponse> {
+        self.get_server_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_server_async_opt(
+        &self,
+        req: &super::channelz::GetServerRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServerResponse>> {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_SERVER, req, opt)
+    }
+
+    pub fn get_server_async(
+        &self,
+        req: &super::channelz::GetServerRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServerResponse>> {
+        self.get_server_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_server_sockets_opt(
+        &self,
+        req: &super::channelz::GetServerSocketsRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetServerSocketsResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_SERVER_SOCKETS, req, opt)
+    }
+
+    pub fn get_server_sockets(
+        &self,
+        req: &super::channelz::GetServerSocketsRequest,
+    ) -> ::grpcio::Result<super::channelz::GetServerSocketsResponse> {
+        self.get_server_sockets_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_server_sockets_async_opt(
+        &self,
+        req: &super::channelz::GetServerSocketsRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServerSocketsResponse>>
+    {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_SERVER_SOCKETS, req, opt)
+    }
+
+    pub fn get_server_sockets_async(
+        &self,
+        req: &super::channelz::GetServerSocketsRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetServerSocketsResponse>>
+    {
+        self.get_server_sockets_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_channel_opt(
+        &self,
+        req: &super::channelz::GetChannelRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetChannelResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_CHANNEL, req, opt)
+    }
+
+    pub fn get_channel(
+        &self,
+        req: &super::channelz::GetChannelRequest,
+    ) -> ::grpcio::Result<super::channelz::GetChannelResponse> {
+        self.get_channel_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_channel_async_opt(
+        &self,
+        req: &super::channelz::GetChannelRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetChannelResponse>> {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_CHANNEL, req, opt)
+    }
+
+    pub fn get_channel_async(
+        &self,
+        req: &super::channelz::GetChannelRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetChannelResponse>> {
+        self.get_channel_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_subchannel_opt(
+        &self,
+        req: &super::channelz::GetSubchannelRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetSubchannelResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_SUBCHANNEL, req, opt)
+    }
+
+    pub fn get_subchannel(
+        &self,
+        req: &super::channelz::GetSubchannelRequest,
+    ) -> ::grpcio::Result<super::channelz::GetSubchannelResponse> {
+        self.get_subchannel_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_subchannel_async_opt(
+        &self,
+        req: &super::channelz::GetSubchannelRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetSubchannelResponse>>
+    {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_SUBCHANNEL, req, opt)
+    }
+
+    pub fn get_subchannel_async(
+        &self,
+        req: &super::channelz::GetSubchannelRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetSubchannelResponse>>
+    {
+        self.get_subchannel_async_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_socket_opt(
+        &self,
+        req: &super::channelz::GetSocketRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<super::channelz::GetSocketResponse> {
+        self.client
+            .unary_call(&METHOD_CHANNELZ_GET_SOCKET, req, opt)
+    }
+
+    pub fn get_socket(
+        &self,
+        req: &super::channelz::GetSocketRequest,
+    ) -> ::grpcio::Result<super::channelz::GetSocketResponse> {
+        self.get_socket_opt(req, ::grpcio::CallOption::default())
+    }
+
+    pub fn get_socket_async_opt(
+        &self,
+        req: &super::channelz::GetSocketRequest,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetSocketResponse>> {
+        self.client
+            .unary_call_async(&METHOD_CHANNELZ_GET_SOCKET, req, opt)
+    }
+
+    pub fn get_socket_async(
+        &self,
+        req: &super::channelz::GetSocketRequest,
+    ) -> ::grpcio::Result<::grpcio::ClientUnaryReceiver<super::channelz::GetSocketResponse>> {
+        self.get_socket_async_opt(req, ::grpcio::CallOption::default())
+    }
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: ::futures::Future<Output = ()> + Send + 'static,
+    {
+        self.client.spawn(f)
+    }
+}
+
+pub trait Channelz {
+    fn get_top_channels(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetTopChannelsRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetTopChannelsResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_servers(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetServersRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetServersResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_server(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetServerRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetServerResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_server_sockets(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetServerSocketsRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetServerSocketsResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_channel(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetChannelRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetChannelResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_subchannel(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetSubchannelRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetSubchannelResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+    fn get_socket(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _req: super::channelz::GetSocketRequest,
+        sink: ::grpcio::UnarySink<super::channelz::GetSocketResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+}
+
+pub fn create_channelz<S: Channelz + Send + Clone + 'static>(s: S) -> ::grpcio::Service {
+    let mut builder = ::grpcio::ServiceBuilder::new();
+    let mut instance = s.clone();
+    builder = builder
+        .add_unary_handler(&METHOD_CHANNELZ_GET_TOP_CHANNELS, move |ctx, req, resp| {
+            instance.get_top_channels(ctx, req, resp)
+        });
+    let mut instance = s.clone();
+    builder = builder.add_unary_handler(&METHOD_CHANNELZ_GET_SERVERS, move |ctx, req, resp| {
+        instance.get_servers(ctx, req, resp)
+    });
+    let mut instance = s.clone();
+    builder = builder.add_unary_handler(&METHOD_CHANNELZ_GET_SERVER, move |ctx, req, resp| {
+        instance.get_server(ctx, req, resp)
+    });
+    let mut instance = s.clone();
+    builder = builder.add_unary_handler(
+        &METHOD_CHANNELZ_GET_SERVER_SOCKETS,
+        move |ctx, req, resp| instance.get_server_sockets(ctx, req, resp),
+    );
+    let mut instance = s.clone();
+    builder = builder.add_unary_handler(&METHOD_CHANNELZ_GET_CHANNEL, move |ctx, req, resp| {
+        instance.get_channel(ctx, req, resp)
+    });
+    let mut instance = s.clone();
+    builder = builder.add_unary_handler(&METHOD_CHANNELZ_GET_SUBCHANNEL, move |ctx, req, resp| {
+        instance.get_subchannel(ctx, req, resp)
+    });
+    let mut instance = s;
+    builder = builder.add_unary_handler(&METHOD_CHANNELZ_GET_SOCKET, move |ctx, req, resp| {
+        instance.get_socket(ctx, req, resp)
+    });
+    builder = builder.add_file_descriptors(FILE_DESCRIPTORS);
+    builder.build()
+}
@@ -0,0 +1,752 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use crate::proto::{
+    Channelz, GetChannelRequest, GetChannelResponse, GetServerRequest, GetServerResponse,
+    GetServerSocketsRequest, GetServerSocketsResponse, GetServersRequest, GetServersResponse,
+    GetSocketRequest, GetSocketResponse, GetSubchannelRequest, GetSubchannelResponse,
+    GetTopChannelsRequest, GetTopChannelsResponse,
+};
+use futures::FutureExt;
+use grpcio::channelz;
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, UnarySink};
+use log::debug;
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Splits `t` into the seconds and nanos of a protobuf timestamp.
+fn seconds_nanos(t: SystemTime) -> (i64, i32) {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => (d.as_secs() as i64, d.subsec_nanos() as i32),
+        Err(e) => {
+            // Nanos are always non-negative, so round the seconds down.
+            let d = e.duration();
+            let (seconds, nanos) = (-(d.as_secs() as i64), d.subsec_nanos() as i32);
+            if nanos == 0 {
+                (seconds, nanos)
+            } else {
+                (seconds - 1, 1_000_000_000 - nanos)
+            }
+        }
+    }
+}
+
+fn ip_octets(ip: IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec(),
+    }
+}
+
+#[cfg(feature = "prost-codec")]
+mod codec {
+    use super::{ip_octets, seconds_nanos};
+    use crate::proto::{
+        self, address, channel_connectivity_state::State, channel_trace_event, security,
+        ChannelRef, GetChannelResponse, GetServerResponse, GetServerSocketsResponse,
+        GetServersResponse, GetSocketResponse, GetSubchannelResponse, GetTopChannelsResponse,
+        ServerRef, SubchannelRef,
+    };
+    use grpcio::channelz::{self, TraceSeverity};
+    use grpcio::ConnectivityState;
+    use prost_types::Timestamp;
+    use std::time::SystemTime;
+
+    fn timestamp(t: Option<SystemTime>) -> Option<Timestamp> {
+        let (seconds, nanos) = seconds_nanos(t?);
+        Some(Timestamp { seconds, nanos })
+    }
+
+    fn trace(t: channelz::ChannelTrace) -> proto::ChannelTrace {
+        let events = t
+            .events
+            .into_iter()
+            .map(|e| {
+                let severity = match e.severity {
+                    TraceSeverity::Unknown => channel_trace_event::Severity::CtUnknown,
+                    TraceSeverity::Info => channel_trace_event::Severity::CtInfo,
+                    TraceSeverity::Warning => channel_trace_event::Severity::CtWarning,
+                    TraceSeverity::Error => channel_trace_event::Severity::CtError,
+                };
+                let child_ref = match (e.channel_ref, e.subchannel_ref) {
+                    (Some(id), _) => {
+                        Some(channel_trace_event::ChildRef::ChannelRef(channel_ref(id)))
+                    }
+                    (None, Some(id)) => Some(channel_trace_event::ChildRef::SubchannelRef(
+                        subchannel_ref(id),
+                    )),
+                    (None, None) => None,
+                };
+                proto::ChannelTraceEvent {
+                    description: e.description,
+                    severity: severity as i32,
+                    timestamp: timestamp(e.timestamp),
+                    child_ref,
+                }
+            })
+            .collect();
+        proto::ChannelTrace {
+            num_events_logged: t.num_events_logged,
+            creation_timestamp: timestamp(t.creation_timestamp),
+            events,
+        }
+    }
+
+    fn channel_ref(channel_id: i64) -> ChannelRef {
+        ChannelRef {
+            channel_id,
+            name: String::new(),
+        }
+    }
+
+    fn subchannel_ref(subchannel_id: i64) -> SubchannelRef {
+        SubchannelRef {
+            subchannel_id,
+            name: String::new(),
+        }
+    }
+
+    fn socket_ref(r: channelz::SocketRef) -> proto::SocketRef {
+        proto::SocketRef {
+            socket_id: r.id,
+            name: r.name,
+        }
+    }
+
+    fn channel_data(d: channelz::ChannelData) -> proto::ChannelData {
+        let state = d.state.map(|s| {
+            let state = match s {
+                ConnectivityState::GRPC_CHANNEL_IDLE => State::Idle,
+                ConnectivityState::GRPC_CHANNEL_CONNECTING => State::Connecting,
+                ConnectivityState::GRPC_CHANNEL_READY => State::Ready,
+                ConnectivityState::GRPC_CHANNEL_TRANSIENT_FAILURE => State::TransientFailure,
+                ConnectivityState::GRPC_CHANNEL_SHUTDOWN => State::Shutdown,
+            };
+            proto::ChannelConnectivityState {
+                state: state as i32,
+            }
+        });
+        proto::ChannelData {
+            state,
+            target: d.target,
+            trace: d.trace.map(trace),
+            calls_started: d.calls_started,
+            calls_succeeded: d.calls_succeeded,
+            calls_failed: d.calls_failed,
+            last_call_started_timestamp: timestamp(d.last_call_started_timestamp),
+        }
+    }
+
+    fn channel(c: channelz::ChannelInfo) -> proto::Channel {
+        proto::Channel {
+            r#ref: Some(channel_ref(c.id)),
+            data: Some(channel_data(c.data)),
+            channel_ref: c.channel_refs.into_iter().map(channel_ref).collect(),
+            subchannel_ref: c.subchannel_refs.into_iter().map(subchannel_ref).collect(),
+            socket_ref: c.socket_refs.into_iter().map(socket_ref).collect(),
+        }
+    }
+
+    fn subchannel(c: channelz::ChannelInfo) -> proto::Subchannel {
+        proto::Subchannel {
+            r#ref: Some(subchannel_ref(c.id)),
+            data: Some(channel_data(c.data)),
+            channel_ref: c.channel_refs.into_iter().map(channel_ref).collect(),
+            subchannel_ref: c.subchannel_refs.into_iter().map(subchannel_ref).collect(),
+            socket_ref: c.socket_refs.into_iter().map(socket_ref).collect(),
+        }
+    }
+
+    fn server(s: channelz::ServerInfo) -> proto::Server {
+        let d = s.data;
+        proto::Server {
+            r#ref: Some(ServerRef {
+                server_id: s.id,
+                name: String::new(),
+            }),
+            data: Some(proto::ServerData {
+                trace: d.trace.map(trace),
+                calls_started: d.calls_started,
+                calls_succeeded: d.calls_succeeded,
+                calls_failed: d.calls_failed,
+                last_call_started_timestamp: timestamp(d.last_call_started_timestamp),
+            }),
+            listen_socket: s.listen_sockets.into_iter().map(socket_ref).collect(),
+        }
+    }
+
+    fn address(a: channelz::Address) -> proto::Address {
+        let address = match a {
+            channelz::Address::Tcp(addr) => address::Address::TcpipAddress(address::TcpIpAddress {
+                ip_address: ip_octets(addr.ip()),
+                port: i32::from(addr.port()),
+            }),
+            channelz::Address::Uds(filename) => {
+                address::Address::UdsAddress(address::UdsAddress { filename })
+            }
+            channelz::Address::Other(name) => {
+                address::Address::OtherAddress(address::OtherAddress { name, value: None })
+            }
+        };
+        proto::Address {
+            address: Some(address),
+        }
+    }
+
+    fn socket(s: channelz::SocketInfo) -> proto::Socket {
+        let d = s.data;
+        let security = s.security.map(|sec| {
+            let model = match sec {
+                channelz::Security::Tls(tls) => {
+                    let cipher_suite = match (tls.standard_name, tls.other_name) {
+                        (Some(name), _) => Some(security::tls::CipherSuite::StandardName(name)),
+                        (None, Some(name)) => Some(security::tls::CipherSuite::OtherName(name)),
+                        (None, None) => None,
+                    };
+                    security::Model::Tls(security::Tls {
+                        cipher_suite,
+                        local_certificate: tls.local_certificate,
+                        remote_certificate: tls.remote_certificate,
+                    })
+                }
+                channelz::Security::Other(name) => {
+                    security::Model::Other(security::OtherSecurity { name, value: None })
+                }
+            };
+            proto::Security { model: Some(model) }
+        });
+        proto::Socket {
+            r#ref: Some(proto::SocketRef {
+                socket_id: s.id,
+                name: s.name,
+            }),
+            data: Some(proto::SocketData {
+                streams_started: d.streams_started,
+                streams_succeeded: d.streams_succeeded,
+                streams_failed: d.streams_failed,
+                messages_sent: d.messages_sent,
+                messages_received: d.messages_received,
+                keep_alives_sent: d.keep_alives_sent,
+                last_local_stream_created_timestamp: timestamp(
+                    d.last_local_stream_created_timestamp,
+                ),
+                last_remote_stream_created_timestamp: timestamp(
+                    d.last_remote_stream_created_timestamp,
+                ),
+                last_message_sent_timestamp: timestamp(d.last_message_sent_timestamp),
+                last_message_received_timestamp: timestamp(d.last_message_received_timestamp),
+                local_flow_control_window: d.local_flow_control_window,
+                remote_flow_control_window: d.remote_flow_control_window,
+                option: vec![],
+            }),
+            local: s.local.map(address),
+            remote: s.remote.map(address),
+            security,
+            remote_name: s.remote_name,
+        }
+    }
+
+    pub fn top_channels_response(
+        channels: Vec<channelz::ChannelInfo>,
+        end: bool,
+    ) -> GetTopChannelsResponse {
+        GetTopChannelsResponse {
+            channel: channels.into_iter().map(channel).collect(),
+            end,
+        }
+    }
+
+    pub fn servers_response(servers: Vec<channelz::ServerInfo>, end: bool) -> GetServersResponse {
+        GetServersResponse {
+            server: servers.into_iter().map(server).collect(),
+            end,
+        }
+    }
+
+    pub fn server_response(s: channelz::ServerInfo) -> GetServerResponse {
+        GetServerResponse {
+            server: Some(server(s)),
+        }
+    }
+
+    pub fn server_sockets_response(
+        refs: Vec<channelz::SocketRef>,
+        end: bool,
+    ) -> GetServerSocketsResponse {
+        GetServerSocketsResponse {
+            socket_ref: refs.into_iter().map(socket_ref).collect(),
+            end,
+        }
+    }
+
+    pub fn channel_response(c: channelz::ChannelInfo) -> GetChannelResponse {
+        GetChannelResponse {
+            channel: Some(channel(c)),
+        }
+    }
+
+    pub fn subchannel_response(c: channelz::ChannelInfo) -> GetSubchannelResponse {
+        GetSubchannelResponse {
+            subchannel: Some(subchannel(c)),
+        }
+    }
+
+    pub fn socket_response(s: channelz::SocketInfo) -> GetSocketResponse {
+        GetSocketResponse {
+            socket: Some(socket(s)),
+        }
+    }
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod codec {
+    use super::{ip_octets, seconds_nanos};
+    use crate::proto::{
+        self, Address_OtherAddress, Address_TcpIpAddress, Address_UdsAddress,
+        Address_oneof_address, ChannelConnectivityState_State as State, ChannelRef,
+        ChannelTraceEvent_Severity as Severity, ChannelTraceEvent_oneof_child_ref as ChildRef,
+        GetChannelResponse, GetServerResponse, GetServerSocketsResponse, GetServersResponse,
+        GetSocketResponse, GetSubchannelResponse, GetTopChannelsResponse, Security_OtherSecurity,
+        Security_Tls, Security_Tls_oneof_cipher_suite as CipherSuite, Security_oneof_model,
+        ServerRef, SubchannelRef,
+    };
+    use grpcio::channelz::{self, TraceSeverity};
+    use grpcio::ConnectivityState;
+    use protobuf::well_known_types::{Int64Value, Timestamp};
+    use std::time::SystemTime;
+
+    fn timestamp(t: Option<SystemTime>) -> Option<Timestamp> {
+        let (seconds, nanos) = seconds_nanos(t?);
+        Some(Timestamp {
+            seconds,
+            nanos,
+            ..Default::default()
+        })
+    }
+
+    fn int64_value(value: Option<i64>) -> Option<Int64Value> {
+        Some(Int64Value {
+            value: value?,
+            ..Default::default()
+        })
+    }
+
+    fn trace(t: channelz::ChannelTrace) -> proto::ChannelTrace {
+        let events: Vec<_> = t
+            .events
+            .into_iter()
+            .map(|e| {
+                let severity = match e.severity {
+                    TraceSeverity::Unknown => Severity::CT_UNKNOWN,
+                    TraceSeverity::Info => Severity::CT_INFO,
+                    TraceSeverity::Warning => Severity::CT_WARNING,
+                    TraceSeverity::Error => Severity::CT_ERROR,
+                };
+                let child_ref = match (e.channel_ref, e.subchannel_ref) {
+                    (Some(id), _) => Some(ChildRef::channel_ref(channel_ref(id))),
+                    (None, Some(id)) => Some(ChildRef::subchannel_ref(subchannel_ref(id))),
+                    (None, None) => None,
+                };
+                proto::ChannelTraceEvent {
+                    description: e.description,
+                    severity,
+                    timestamp: timestamp(e.timestamp).into(),
+                    child_ref,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        proto::ChannelTrace {
+            num_events_logged: t.num_events_logged,
+            creation_timestamp: timestamp(t.creation_timestamp).into(),
+            events: events.into(),
+            ..Default::default()
+        }
+    }
+
+    fn channel_ref(channel_id: i64) -> ChannelRef {
+        ChannelRef {
+            channel_id,
+            ..Default::default()
+        }
+    }
+
+    fn subchannel_ref(subchannel_id: i64) -> SubchannelRef {
+        SubchannelRef {
+            subchannel_id,
+            ..Default::default()
+        }
+    }
+
+    fn socket_ref(r: channelz::SocketRef) -> proto::SocketRef {
+        proto::SocketRef {
+            socket_id: r.id,
+            name: r.name,
+            ..Default::default()
+        }
+    }
+
+    fn channel_data(d: channelz::ChannelData) -> proto::ChannelData {
+        let state = d.state.map(|s| {
+            let state = match s {
+                ConnectivityState::GRPC_CHANNEL_IDLE => State::IDLE,
+                ConnectivityState::GRPC_CHANNEL_CONNECTING => State::CONNECTING,
+                ConnectivityState::GRPC_CHANNEL_READY => State::READY,
+                ConnectivityState::GRPC_CHANNEL_TRANSIENT_FAILURE => State::TRANSIENT_FAILURE,
+                ConnectivityState::GRPC_CHANNEL_SHUTDOWN => State::SHUTDOWN,
+            };
+            proto::ChannelConnectivityState {
+                state,
+                ..Default::default()
+            }
+        });
+        proto::ChannelData {
+            state: state.into(),
+            target: d.target,
+            trace: d.trace.map(trace).into(),
+            calls_started: d.calls_started,
+            calls_succeeded: d.calls_succeeded,
+            calls_failed: d.calls_failed,
+            last_call_started_timestamp: timestamp(d.last_call_started_timestamp).into(),
+            ..Default::default()
+        }
+    }
+
+    fn channel(c: channelz::ChannelInfo) -> proto::Channel {
+        proto::Channel {
+            field_ref: Some(channel_ref(c.id)).into(),
+            data: Some(channel_data(c.data)).into(),
+            channel_ref: c.channel_refs.into_iter().map(channel_ref).collect(),
+            subchannel_ref: c.subchannel_refs.into_iter().map(subchannel_ref).collect(),
+            socket_ref: c.socket_refs.into_iter().map(socket_ref).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn subchannel(c: channelz::ChannelInfo) -> proto::Subchannel {
+        proto::Subchannel {
+            field_ref: Some(subchannel_ref(c.id)).into(),
+            data: Some(channel_data(c.data)).into(),
+            channel_ref: c.channel_refs.into_iter().map(channel_ref).collect(),
+            subchannel_ref: c.subchannel_refs.into_iter().map(subchannel_ref).collect(),
+            socket_ref: c.socket_refs.into_iter().map(socket_ref).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn server(s: channelz::ServerInfo) -> proto::Server {
+        let d = s.data;
+        let server_ref = ServerRef {
+            server_id: s.id,
+            ..Default::default()
+        };
+        let data = proto::ServerData {
+            trace: d.trace.map(trace).into(),
+            calls_started: d.calls_started,
+            calls_succeeded: d.calls_succeeded,
+            calls_failed: d.calls_failed,
+            last_call_started_timestamp: timestamp(d.last_call_started_timestamp).into(),
+            ..Default::default()
+        };
+        proto::Server {
+            field_ref: Some(server_ref).into(),
+            data: Some(data).into(),
+            listen_socket: s.listen_sockets.into_iter().map(socket_ref).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn address(a: channelz::Address) -> proto::Address {
+        let address = match a {
+            channelz::Address::Tcp(addr) => {
+                Address_oneof_address::tcpip_address(Address_TcpIpAddress {
+                    ip_address: ip_octets(addr.ip()),
+                    port: i32::from(addr.port()),
+                    ..Default::default()
+                })
+            }
+            channelz::Address::Uds(filename) => {
+                Address_oneof_address::uds_address(Address_UdsAddress {
+                    filename,
+                    ..Default::default()
+                })
+            }
+            channelz::Address::Other(name) => {
+                Address_oneof_address::other_address(Address_OtherAddress {
+                    name,
+                    ..Default::default()
+                })
+            }
+        };
+        proto::Address {
+            address: Some(address),
+            ..Default::default()
+        }
+    }
+
+    fn socket(s: channelz::SocketInfo) -> proto::Socket {
+        let d = s.data;
+        let security = s.security.map(|sec| {
+            let model = match sec {
+                channelz::Security::Tls(tls) => {
+                    let cipher_suite = match (tls.standard_name, tls.other_name) {
+                        (Some(name), _) => Some(CipherSuite::standard_name(name)),
+                        (None, Some(name)) => Some(CipherSuite::other_name(name)),
+                        (None, None) => None,
+                    };
+                    Security_oneof_model::tls(Security_Tls {
+                        cipher_suite,
+                        local_certificate: tls.local_certificate,
+                        remote_certificate: tls.remote_certificate,
+                        ..Default::default()
+                    })
+                }
+                channelz::Security::Other(name) => {
+                    Security_oneof_model::other(Security_OtherSecurity {
+                        name,
+                        ..Default::default()
+                    })
+                }
+            };
+            proto::Security {
+                model: Some(model),
+                ..Default::default()
+            }
+        });
+        let socket_ref = proto::SocketRef {
+            socket_id: s.id,
+            name: s.name,
+            ..Default::default()
+        };
+        let data = proto::SocketData {
+            streams_started: d.streams_started,
+            streams_succeeded: d.streams_succeeded,
+            streams_failed: d.streams_failed,
+            messages_sent: d.messages_sent,
+            messages_received: d.messages_received,
+            keep_alives_sent: d.keep_alives_sent,
+            last_local_stream_created_timestamp: timestamp(d.last_local_stream_created_timestamp)
+                .into(),
+            last_remote_stream_created_timestamp: timestamp(d.last_remote_stream_created_timestamp)
+                .into(),
+            last_message_sent_timestamp: timestamp(d.last_message_sent_timestamp).into(),
+            last_message_received_timestamp: timestamp(d.last_message_received_timestamp).into(),
+            local_flow_control_window: int64_value(d.local_flow_control_window).into(),
+            remote_flow_control_window: int64_value(d.remote_flow_control_window).into(),
+            ..Default::default()
+        };
+        proto::Socket {
+            field_ref: Some(socket_ref).into(),
+            data: Some(data).into(),
+            local: s.local.map(address).into(),
+            remote: s.remote.map(address).into(),
+            security: security.into(),
+            remote_name: s.remote_name,
+            ..Default::default()
+        }
+    }
+
+    pub fn top_channels_response(
+        channels: Vec<channelz::ChannelInfo>,
+        end: bool,
+    ) -> GetTopChannelsResponse {
+        GetTopChannelsResponse {
+            channel: channels.into_iter().map(channel).collect(),
+            end,
+            ..Default::default()
+        }
+    }
+
+    pub fn servers_response(servers: Vec<channelz::ServerInfo>, end: bool) -> GetServersResponse {
+        GetServersResponse {
+            server: servers.into_iter().map(server).collect(),
+            end,
+            ..Default::default()
+        }
+    }
+
+    pub fn server_response(s: channelz::ServerInfo) -> GetServerResponse {
+        GetServerResponse {
+            server: Some(server(s)).into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn server_sockets_response(
+        refs: Vec<channelz::SocketRef>,
+        end: bool,
+    ) -> GetServerSocketsResponse {
+        GetServerSocketsResponse {
+            socket_ref: refs.into_iter().map(socket_ref).collect(),
+            end,
+            ..Default::default()
+        }
+    }
+
+    pub fn channel_response(c: channelz::ChannelInfo) -> GetChannelResponse {
+        GetChannelResponse {
+            channel: Some(channel(c)).into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn subchannel_response(c: channelz::ChannelInfo) -> GetSubchannelResponse {
+        GetSubchannelResponse {
+            subchannel: Some(subchannel(c)).into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn socket_response(s: channelz::SocketInfo) -> GetSocketResponse {
+        GetSocketResponse {
+            socket: Some(socket(s)).into(),
+            ..Default::default()
+        }
+    }
+}
+
+use self::codec::{
+    channel_response, server_response, server_sockets_response, servers_response, socket_response,
+    subchannel_response, top_channels_response,
+};
+
+/// Truncates a page to `max_results` if it's positive.
+fn limit<T>(mut items: Vec<T>, end: bool, max_results: i64) -> (Vec<T>, bool) {
+    if max_results > 0 && items.len() as i64 > max_results {
+        items.truncate(max_results as usize);
+        return (items, false);
+    }
+    (items, end)
+}
+
+fn reply<T>(ctx: RpcContext, sink: UnarySink<T>, resp: Option<T>, not_found: &str) {
+    let f = match resp {
+        Some(resp) => sink.success(resp),
+        None => sink.fail(RpcStatus::with_message(
+            RpcStatusCode::NOT_FOUND,
+            not_found.to_owned(),
+        )),
+    };
+    ctx.spawn(f.map(|res| {
+        if let Err(e) = res {
+            debug!("channelz: failed to reply: {:?}", e);
+        }
+    }))
+}
+
+/// An implementation of the `Channelz` service, which serves the channelz data of
+/// all channels and servers in the process.
+///
+/// Channels and servers built with channelz disabled are not tracked, see
+/// [`grpcio::ChannelBuilder::enable_channelz`] and
+/// [`grpcio::ServerBuilder::enable_channelz`].
+#[derive(Clone, Default)]
+pub struct ChannelzService;
+
+impl ChannelzService {
+    pub fn new() -> ChannelzService {
+        ChannelzService
+    }
+}
+
+impl Channelz for ChannelzService {
+    fn get_top_channels(
+        &mut self,
+        ctx: RpcContext,
+        req: GetTopChannelsRequest,
+        sink: UnarySink<GetTopChannelsResponse>,
+    ) {
+        let (channels, end) = channelz::get_top_channels(req.start_channel_id);
+        let (channels, end) = limit(channels, end, req.max_results);
+        reply(ctx, sink, Some(top_channels_response(channels, end)), "")
+    }
+
+    fn get_servers(
+        &mut self,
+        ctx: RpcContext,
+        req: GetServersRequest,
+        sink: UnarySink<GetServersResponse>,
+    ) {
+        let (servers, end) = channelz::get_servers(req.start_server_id);
+        let (servers, end) = limit(servers, end, req.max_results);
+        reply(ctx, sink, Some(servers_response(servers, end)), "")
+    }
+
+    fn get_server(
+        &mut self,
+        ctx: RpcContext,
+        req: GetServerRequest,
+        sink: UnarySink<GetServerResponse>,
+    ) {
+        let resp = channelz::get_server(req.server_id).map(server_response);
+        reply(ctx, sink, resp, "No object found for the given server id")
+    }
+
+    fn get_server_sockets(
+        &mut self,
+        ctx: RpcContext,
+        req: GetServerSocketsRequest,
+        sink: UnarySink<GetServerSocketsResponse>,
+    ) {
+        let resp =
+            channelz::get_server_sockets(req.server_id, req.start_socket_id, req.max_results)
+                .map(|(refs, end)| server_sockets_response(refs, end));
+        reply(ctx, sink, resp, "No object found for the given server id")
+    }
+
+    fn get_channel(
+        &mut self,
+        ctx: RpcContext,
+        req: GetChannelRequest,
+        sink: UnarySink<GetChannelResponse>,
+    ) {
+        let resp = channelz::get_channel(req.channel_id).map(channel_response);
+        reply(ctx, sink, resp, "No object found for the given channel id")
+    }
+
+    fn get_subchannel(
+        &mut self,
+        ctx: RpcContext,
+        req: GetSubchannelRequest,
+        sink: UnarySink<GetSubchannelResponse>,
+    ) {
+        let resp = channelz::get_subchannel(req.subchannel_id).map(subchannel_response);
+        reply(
+            ctx,
+            sink,
+            resp,
+            "No object found for the given subchannel id",
+        )
+    }
+
+    fn get_socket(
+        &mut self,
+        ctx: RpcContext,
+        req: GetSocketRequest,
+        sink: UnarySink<GetSocketResponse>,
+    ) {
+        // gRPC Core always returns the full data, which is cheap enough, so
+        // `summary` is ignored.
+        let resp = channelz::get_socket(req.socket_id).map(socket_response);
+        reply(ctx, sink, resp, "No object found for the given socket id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_timestamp() {
+        let t = UNIX_EPOCH + Duration::new(10, 5);
+        assert_eq!(seconds_nanos(t), (10, 5));
+        let t = UNIX_EPOCH - Duration::new(10, 5);
+        assert_eq!(seconds_nanos(t), (-11, 999_999_995));
+        let t = UNIX_EPOCH - Duration::new(10, 0);
+        assert_eq!(seconds_nanos(t), (-10, 0));
+
+        assert_eq!(limit(vec![1, 2, 3], true, 0), (vec![1, 2, 3], true));
+        assert_eq!(limit(vec![1, 2, 3], true, 2), (vec![1, 2], false));
+        assert_eq!(limit(vec![1, 2, 3], false, 3), (vec![1, 2, 3], false));
+    }
+}
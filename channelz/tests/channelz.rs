@@ -0,0 +1,160 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use grpcio::*;
+use grpcio_channelz::proto::*;
+use grpcio_channelz::*;
+use std::sync::Arc;
+
+#[cfg(feature = "prost-codec")]
+mod codec {
+    use grpcio_channelz::proto::channel_connectivity_state::State;
+    use grpcio_channelz::proto::*;
+
+    pub fn channel_request(channel_id: i64) -> GetChannelRequest {
+        GetChannelRequest { channel_id }
+    }
+
+    pub fn subchannel_request(subchannel_id: i64) -> GetSubchannelRequest {
+        GetSubchannelRequest { subchannel_id }
+    }
+
+    pub fn socket_request(socket_id: i64) -> GetSocketRequest {
+        GetSocketRequest {
+            socket_id,
+            summary: false,
+        }
+    }
+
+    pub fn server_sockets_request(server_id: i64) -> GetServerSocketsRequest {
+        GetServerSocketsRequest {
+            server_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_ready(data: &ChannelData) -> bool {
+        data.state.as_ref().unwrap().state == State::Ready as i32
+    }
+
+    pub fn server_ref_id(s: &Server) -> i64 {
+        s.r#ref.as_ref().unwrap().server_id
+    }
+
+    pub fn tcp_address(a: Address) -> (Vec<u8>, i32) {
+        match a.address.unwrap() {
+            address::Address::TcpipAddress(addr) => (addr.ip_address, addr.port),
+            a => panic!("unexpected address {:?}", a),
+        }
+    }
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod codec {
+    use grpcio_channelz::proto::*;
+
+    pub fn channel_request(channel_id: i64) -> GetChannelRequest {
+        GetChannelRequest {
+            channel_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn subchannel_request(subchannel_id: i64) -> GetSubchannelRequest {
+        GetSubchannelRequest {
+            subchannel_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn socket_request(socket_id: i64) -> GetSocketRequest {
+        GetSocketRequest {
+            socket_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn server_sockets_request(server_id: i64) -> GetServerSocketsRequest {
+        GetServerSocketsRequest {
+            server_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_ready(data: &ChannelData) -> bool {
+        data.state.as_ref().unwrap().state == ChannelConnectivityState_State::READY
+    }
+
+    pub fn server_ref_id(s: &Server) -> i64 {
+        s.field_ref.as_ref().unwrap().server_id
+    }
+
+    pub fn tcp_address(a: Address) -> (Vec<u8>, i32) {
+        match a.address.unwrap() {
+            Address_oneof_address::tcpip_address(addr) => (addr.ip_address, addr.port),
+            a => panic!("unexpected address {:?}", a),
+        }
+    }
+}
+
+use self::codec::*;
+
+#[test]
+fn test_channelz() {
+    let env = Arc::new(Environment::new(1));
+    let mut server = ServerBuilder::new(env.clone())
+        .enable_channelz(true)
+        .register_service(create_channelz(ChannelzService::new()))
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let server_id = server.channelz_id().unwrap();
+    let (_, port) = server.bind_addrs().next().unwrap();
+
+    let target = format!("127.0.0.1:{}", port);
+    let ch = ChannelBuilder::new(env)
+        .enable_channelz(true)
+        .connect(&target);
+    let channel_id = ch.channelz_id().unwrap();
+    let client = ChannelzClient::new(ch.clone());
+
+    // The call itself is counted when it's started.
+    let resp = client.get_channel(&channel_request(channel_id)).unwrap();
+    let data = resp.channel.unwrap().data.unwrap();
+    assert_eq!(data.target, target);
+    assert!(is_ready(&data));
+    assert_eq!(data.calls_started, 1);
+
+    let info = ch.channelz().unwrap();
+    assert_eq!(info.id, channel_id);
+    assert_eq!(info.data.calls_succeeded, 1);
+    assert_eq!(info.subchannel_refs.len(), 1);
+    let subchannel_id = info.subchannel_refs[0];
+    let resp = client
+        .get_subchannel(&subchannel_request(subchannel_id))
+        .unwrap();
+    let subchannel = resp.subchannel.unwrap();
+    assert_eq!(subchannel.socket_ref.len(), 1);
+    let socket_id = subchannel.socket_ref[0].socket_id;
+    let resp = client.get_socket(&socket_request(socket_id)).unwrap();
+    let socket = resp.socket.unwrap();
+    assert!(socket.data.unwrap().messages_sent > 0);
+    let remote = tcp_address(socket.remote.unwrap());
+    assert_eq!(remote, (vec![127, 0, 0, 1], i32::from(port)));
+
+    let resp = client.get_servers(&GetServersRequest::default()).unwrap();
+    assert!(resp.end);
+    assert!(resp.server.iter().any(|s| server_ref_id(s) == server_id));
+    let resp = client
+        .get_server_sockets(&server_sockets_request(server_id))
+        .unwrap();
+    assert_eq!(resp.socket_ref.len(), 1);
+    assert_eq!(server.channelz_sockets().len(), 1);
+    assert!(server.channelz().unwrap().data.calls_started >= 4);
+
+    let err = client.get_channel(&channel_request(-1)).unwrap_err();
+    match err {
+        Error::RpcFailure(s) => assert_eq!(s.code(), RpcStatusCode::NOT_FOUND),
+        e => panic!("unexpected error {:?}", e),
+    }
+}
@@ -21,12 +21,13 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 
 use protobuf::compiler_plugin;
 use protobuf::descriptor::*;
 use protobuf::descriptorx::*;
+use protobuf::Message;
 
 struct CodeWriter<'a> {
     writer: &'a mut (dyn Write + 'a),
@@ -587,6 +588,14 @@ impl<'a> ServiceGen<'a> {
                 });
             });
 
+            w.write_line("");
+
+            w.pub_fn("with_pool(pool: ::grpcio::ChannelPool) -> Self", |w| {
+                w.expr_block(&self.client_name(), |w| {
+                    w.field_entry("client", "::grpcio::Client::with_pool(pool)");
+                });
+            });
+
             for method in &self.methods {
                 w.write_line("");
                 method.write_client(w);
@@ -625,6 +634,7 @@ impl<'a> ServiceGen<'a> {
             w.write_line("let mut instance = s;");
             self.methods[self.methods.len() - 1].write_bind(w);
 
+            w.write_line("builder = builder.add_file_descriptors(FILE_DESCRIPTORS);");
             w.write_line("builder.build()");
         });
     }
@@ -648,8 +658,45 @@ impl<'a> ServiceGen<'a> {
     }
 }
 
+/// Serializes `file` and all its dependencies, so that the services defined by
+/// `file` can be described without other files.
+fn file_descriptor_set(
+    file: &FileDescriptorProto,
+    files_map: &HashMap<&str, &FileDescriptorProto>,
+) -> Vec<Vec<u8>> {
+    let mut set = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![file];
+    while let Some(f) = pending.pop() {
+        if !visited.insert(f.get_name()) {
+            continue;
+        }
+        let mut f = f.clone();
+        // Source code info is only useful for code generators.
+        f.clear_source_code_info();
+        set.push(f.write_to_bytes().unwrap());
+        for dep in f.get_dependency().iter().rev() {
+            if let Some(dep) = files_map.get(dep.as_str()) {
+                pending.push(dep);
+            }
+        }
+    }
+    set
+}
+
+fn write_file_descriptors(w: &mut CodeWriter, descriptors: &[Vec<u8>]) {
+    w.write_line("const FILE_DESCRIPTORS: &[&[u8]] = &[");
+    w.indented(|w| {
+        for d in descriptors {
+            w.write_line(format!("{},", util::to_byte_string_literal(d)));
+        }
+    });
+    w.write_line("];");
+}
+
 fn gen_file(
     file: &FileDescriptorProto,
+    files_map: &HashMap<&str, &FileDescriptorProto>,
     root_scope: &RootScope,
 ) -> Option<compiler_plugin::GenResult> {
     if file.get_service().is_empty() {
@@ -663,6 +710,9 @@ fn gen_file(
         let mut w = CodeWriter::new(&mut v);
         w.write_generated();
 
+        w.write_line("");
+        write_file_descriptors(&mut w, &file_descriptor_set(file, files_map));
+
         for service in file.get_service() {
             w.write_line("");
             ServiceGen::new(service, file, root_scope).write(&mut w);
@@ -693,7 +743,7 @@ pub fn gen(
             continue;
         }
 
-        results.extend(gen_file(file, &root_scope).into_iter());
+        results.extend(gen_file(file, &files_map, &root_scope).into_iter());
     }
 
     results
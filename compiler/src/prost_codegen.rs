@@ -1,5 +1,6 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::collections::HashSet;
 use std::io::{Error, ErrorKind, Read};
 use std::path::Path;
 use std::{env, fs, io, process::Command, str};
@@ -7,9 +8,9 @@ use std::{env, fs, io, process::Command, str};
 use derive_new::new;
 use prost::Message;
 use prost_build::{protoc, protoc_include, Config, Method, Service, ServiceGenerator};
-use prost_types::FileDescriptorSet;
+use prost_types::{FileDescriptorProto, FileDescriptorSet};
 
-use crate::util::{fq_grpc, to_snake_case, MethodType};
+use crate::util::{fq_grpc, to_byte_string_literal, to_snake_case, MethodType};
 
 /// Returns the names of all packages compiled.
 pub fn compile_protos<P>(protos: &[P], includes: &[P], out_dir: &str) -> io::Result<Vec<String>>
@@ -17,7 +18,6 @@ where
     P: AsRef<Path>,
 {
     let mut prost_config = Config::new();
-    prost_config.service_generator(Box::new(Generator));
     prost_config.out_dir(out_dir);
 
     // Create a file descriptor set for the protocol files.
@@ -63,6 +63,10 @@ where
     packages.sort();
     packages.dedup();
 
+    prost_config.service_generator(Box::new(Generator {
+        files: descriptor_set.file,
+    }));
+
     // FIXME(https://github.com/danburkert/prost/pull/155)
     // Unfortunately we have to forget the above work and use `compile_protos` to
     // actually generate the Rust code.
@@ -71,13 +75,52 @@ where
     Ok(packages)
 }
 
-struct Generator;
+struct Generator {
+    files: Vec<FileDescriptorProto>,
+}
+
+impl Generator {
+    /// Serializes the file that defines `service` and all its dependencies, so
+    /// that the service can be described without other files.
+    fn file_descriptor_set(&self, service: &Service) -> Vec<Vec<u8>> {
+        let defines_service = |f: &&FileDescriptorProto| {
+            f.package() == service.package
+                && f.service.iter().any(|s| s.name() == service.proto_name)
+        };
+        let mut set = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending: Vec<_> = self
+            .files
+            .iter()
+            .find(defines_service)
+            .into_iter()
+            .collect();
+        while let Some(f) = pending.pop() {
+            if !visited.insert(f.name()) {
+                continue;
+            }
+            let mut f = f.clone();
+            // Source code info is only useful for code generators.
+            f.source_code_info = None;
+            let mut buf = Vec::with_capacity(f.encoded_len());
+            f.encode(&mut buf).unwrap();
+            set.push(buf);
+            for dep in f.dependency.iter().rev() {
+                if let Some(dep) = self.files.iter().find(|f| f.name() == dep) {
+                    pending.push(dep);
+                }
+            }
+        }
+        set
+    }
+}
 
 impl ServiceGenerator for Generator {
     fn generate(&mut self, service: Service, buf: &mut String) {
+        let descriptors = self.file_descriptor_set(&service);
         generate_methods(&service, buf);
         generate_client(&service, buf);
-        generate_server(&service, buf);
+        generate_server(&service, &descriptors, buf);
     }
 }
 
@@ -180,6 +223,10 @@ fn generate_ctor(client_name: &str, buf: &mut String) {
     buf.push_str(client_name);
     buf.push_str(" { client: ::grpcio::Client::new(channel) }");
     buf.push_str("}\n");
+    buf.push_str("pub fn with_pool(pool: ::grpcio::ChannelPool) -> Self { ");
+    buf.push_str(client_name);
+    buf.push_str(" { client: ::grpcio::Client::with_pool(pool) }");
+    buf.push_str("}\n");
 }
 
 fn generate_client_methods(service: &Service, buf: &mut String) {
@@ -445,7 +492,7 @@ fn generate_spawn(buf: &mut String) {
     );
 }
 
-fn generate_server(service: &Service, buf: &mut String) {
+fn generate_server(service: &Service, descriptors: &[Vec<u8>], buf: &mut String) {
     buf.push_str("pub trait ");
     buf.push_str(&service.name);
     buf.push_str(" {\n");
@@ -473,6 +520,14 @@ fn generate_server(service: &Service, buf: &mut String) {
         buf,
     );
 
+    buf.push_str("const FILE_DESCRIPTORS: &[&[u8]] = &[");
+    for d in descriptors {
+        buf.push_str(&to_byte_string_literal(d));
+        buf.push_str(", ");
+    }
+    buf.push_str("];\n");
+    buf.push_str("builder = builder.add_file_descriptors(FILE_DESCRIPTORS);\n");
+
     buf.push_str("builder.build()\n");
     buf.push_str("}\n");
 }
@@ -1,5 +1,6 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::ascii;
 use std::fmt;
 use std::str;
 
@@ -85,6 +86,17 @@ pub fn fq_grpc(item: &str) -> String {
     format!("::grpcio::{}", item)
 }
 
+/// Formats `data` as a byte string literal, like `b"\n\x05hello"`.
+pub fn to_byte_string_literal(data: &[u8]) -> String {
+    let mut literal = String::with_capacity(data.len() + 3);
+    literal.push_str("b\"");
+    for b in data {
+        literal.extend(ascii::escape_default(*b).map(char::from));
+    }
+    literal.push('"');
+    literal
+}
+
 pub enum MethodType {
     Unary,
     ClientStreaming,
@@ -130,6 +142,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_byte_string_literal() {
+        let cases: Vec<(&[u8], &str)> = vec![
+            (b"", r#"b"""#),
+            (b"abc", r#"b"abc""#),
+            (b"\n\x05a\"\\'\xff", r#"b"\n\x05a\"\\\'\xff""#),
+        ];
+
+        for (origin, exp) in cases {
+            assert_eq!(super::to_byte_string_literal(origin), exp);
+        }
+    }
+
     #[test]
     #[cfg(feature = "protobuf-codec")]
     fn test_camel_name() {
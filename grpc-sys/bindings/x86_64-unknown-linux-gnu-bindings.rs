@@ -170,6 +170,7 @@ pub const GRPC_DEFAULT_SSL_ROOTS_FILE_PATH_ENV_VAR: &'static [u8; 33usize] =
 pub const GRPC_GOOGLE_CREDENTIALS_ENV_VAR: &'static [u8; 31usize] =
     b"GOOGLE_APPLICATION_CREDENTIALS\0";
 pub const GRPC_METADATA_CREDENTIALS_PLUGIN_SYNC_MAX: u32 = 4;
+pub const GRPCWRAP_ARG_PEER_CHECKS: &'static [u8; 21usize] = b"grpcwrap.peer_checks\0";
 #[repr(u32)]
 #[doc = " The various compression algorithms supported by gRPC (not sorted by"]
 #[doc = " compression level)"]
@@ -3993,7 +3994,8 @@ extern "C" {
         ctx: *mut grpcwrap_batch_context,
         send_buffer: *mut grpc_slice,
         write_flags: u32,
-        send_empty_initial_metadata: i32,
+        initial_metadata: *mut grpc_metadata_array,
+        send_initial_metadata: i32,
         tag: *mut ::std::os::raw::c_void,
     ) -> grpc_call_error;
 }
@@ -4011,7 +4013,8 @@ extern "C" {
         status_details: *const ::std::os::raw::c_char,
         status_details_len: usize,
         trailing_metadata: *mut grpc_metadata_array,
-        send_empty_initial_metadata: i32,
+        initial_metadata: *mut grpc_metadata_array,
+        send_initial_metadata: i32,
         optional_send_buffer: *mut grpc_slice,
         write_flags: u32,
         tag: *mut ::std::os::raw::c_void,
@@ -4057,3 +4060,110 @@ extern "C" {
         tag: *mut ::std::os::raw::c_void,
     ) -> grpc_call_error;
 }
+#[doc = " An address pushed to a response generator. `ip` holds `ip_len` bytes"]
+#[doc = "(4 for IPv4, 16 for IPv6) in network order, `port` is in host order."]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct grpcwrap_socket_address {
+    pub ip: [u8; 16usize],
+    pub ip_len: usize,
+    pub port: u16,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct grpcwrap_response_generator {
+    _unused: [u8; 0],
+}
+extern "C" {
+    #[doc = " Creates a response generator feeding the resolver of a channel created"]
+    #[doc = "with target \"fake:///<name>\"."]
+    pub fn grpcwrap_response_generator_create() -> *mut grpcwrap_response_generator;
+}
+extern "C" {
+    pub fn grpcwrap_response_generator_unref(generator: *mut grpcwrap_response_generator);
+}
+extern "C" {
+    #[doc = " Sets the channel arg that binds a channel's resolver to the generator."]
+    #[doc = "A generator must only be bound to one channel."]
+    pub fn grpcwrap_channel_args_set_response_generator(
+        args: *mut grpc_channel_args,
+        index: usize,
+        generator: *mut grpcwrap_response_generator,
+    );
+}
+extern "C" {
+    #[doc = " Pushes a resolution result to the channel bound to the generator."]
+    #[doc = "`service_config` may be null. Returns 0 if an address or the service"]
+    #[doc = "config is invalid, in which case nothing is pushed."]
+    pub fn grpcwrap_response_generator_set_response(
+        generator: *mut grpcwrap_response_generator,
+        addrs: *const grpcwrap_socket_address,
+        count: usize,
+        service_config: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    #[doc = " Returns 1 if TLS versions can be set on SSL credentials, otherwise 0."]
+    pub fn grpcwrap_tls_versions_supported() -> ::std::os::raw::c_int;
+}
+extern "C" {
+    #[doc = " Sets the TLS versions of credentials created by"]
+    #[doc = "grpc_ssl_credentials_create_ex. Returns 0 if it's not supported or the"]
+    #[doc = "credentials are not SSL ones."]
+    pub fn grpcwrap_ssl_credentials_set_tls_versions(
+        creds: *mut grpc_channel_credentials,
+        min_tls_version: grpc_tls_version,
+        max_tls_version: grpc_tls_version,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    #[doc = " Sets the TLS versions of credentials created by"]
+    #[doc = "grpc_ssl_server_credentials_create_with_options. Returns 0 if it's not"]
+    #[doc = "supported or the credentials are not SSL ones."]
+    pub fn grpcwrap_ssl_server_credentials_set_tls_versions(
+        creds: *mut grpc_server_credentials,
+        min_tls_version: grpc_tls_version,
+        max_tls_version: grpc_tls_version,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    #[doc = " Gets the channelz id of a channel. Returns 0 if channelz is disabled."]
+    pub fn grpcwrap_channel_get_channelz_id(channel: *mut grpc_channel) -> isize;
+}
+extern "C" {
+    #[doc = " Gets the channelz id of a server. Returns 0 if channelz is disabled."]
+    pub fn grpcwrap_server_get_channelz_id(server: *mut grpc_server) -> isize;
+}
+#[doc = " Checks the peer of a connection whose security handshake is done. Returns"]
+#[doc = "null to accept the peer, otherwise the reason of rejecting it, which is"]
+#[doc = "allocated by gpr_malloc."]
+pub type grpcwrap_peer_check = ::std::option::Option<
+    unsafe extern "C" fn(
+        user_data: *mut ::std::os::raw::c_void,
+        ctx: *mut grpc_auth_context,
+    ) -> *mut ::std::os::raw::c_char,
+>;
+#[doc = " The process function of grpc_auth_metadata_processor."]
+pub type grpcwrap_auth_metadata_process = ::std::option::Option<
+    unsafe extern "C" fn(
+        state: *mut ::std::os::raw::c_void,
+        context: *mut grpc_auth_context,
+        md: *const grpc_metadata,
+        num_md: usize,
+        cb: grpc_process_auth_metadata_done_cb,
+        user_data: *mut ::std::os::raw::c_void,
+    ),
+>;
+extern "C" {
+    #[doc = " Sets the checks of peers, which run during the handshakes of connections."]
+    #[doc = "Servers are checked on channels with the pointer argument"]
+    #[doc = "GRPCWRAP_ARG_PEER_CHECKS, clients are checked on ports added with server"]
+    #[doc = "credentials whose auth metadata processor has `process`. It must be called"]
+    #[doc = "at most once, preferably before grpc_init. Returns 0 if it's not"]
+    #[doc = "supported."]
+    pub fn grpcwrap_set_peer_checks(
+        check_server: grpcwrap_peer_check,
+        check_client: grpcwrap_peer_check,
+        process: grpcwrap_auth_metadata_process,
+    ) -> ::std::os::raw::c_int;
+}
@@ -231,6 +231,14 @@ fn build_grpc(cc: &mut cc::Build, library: &str) {
     }
 
     cc.include("grpc/include");
+    // Lets grpc_wrap.cc reach core internals such as the fake resolver, which
+    // are only usable when linking against the bundled sources.
+    cc.include("grpc");
+    cc.include("grpc/third_party/abseil-cpp");
+    cc.include("grpc/third_party/upb");
+    cc.include("grpc/src/core/ext/upb-generated");
+    cc.define("GRPCIO_SYS_CORE_INTERNALS", None);
+    cc.flag_if_supported("-Wno-unused-parameter");
 }
 
 fn figure_ssl_path(build_dir: &str) {
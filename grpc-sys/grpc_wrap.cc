@@ -50,6 +50,28 @@
 #include <grpc/grpc_security.h>
 #endif
 
+// Core internals are only reachable when grpc is built from the bundled
+// sources, see `build_grpc` in build.rs.
+#ifdef GRPCIO_SYS_CORE_INTERNALS
+#include "src/core/ext/filters/client_channel/resolver/fake/fake_resolver.h"
+#include "src/core/lib/channel/channelz.h"
+#include "src/core/lib/iomgr/exec_ctx.h"
+#include "src/core/lib/iomgr/sockaddr.h"
+#include "src/core/lib/iomgr/socket_utils.h"
+#include "src/core/lib/surface/channel.h"
+#include "src/core/lib/surface/server.h"
+#ifdef GRPC_SYS_SECURE
+#include "src/core/lib/channel/channel_args.h"
+#include "src/core/lib/channel/handshaker.h"
+#include "src/core/lib/channel/handshaker_registry.h"
+#include "src/core/lib/security/context/security_context.h"
+#include "src/core/lib/security/credentials/credentials.h"
+#include "src/core/lib/security/credentials/ssl/ssl_credentials.h"
+#include "src/core/lib/security/security_connector/security_connector.h"
+#include "src/core/lib/slice/slice_internal.h"
+#endif
+#endif
+
 #include <string.h>
 
 #ifdef GPR_WINDOWS
@@ -641,19 +663,27 @@ GPR_EXPORT grpc_call_error GPR_CALLTYPE grpcwrap_call_recv_initial_metadata(
 
 GPR_EXPORT grpc_call_error GPR_CALLTYPE grpcwrap_call_send_message(
     grpc_call* call, grpcwrap_batch_context* ctx, grpc_slice* send_buffer,
-    uint32_t write_flags, int32_t send_empty_initial_metadata, void* tag) {
+    uint32_t write_flags, grpc_metadata_array* initial_metadata,
+    int32_t send_initial_metadata, void* tag) {
   /* TODO: don't use magic number */
   grpc_op ops[2];
   memset(ops, 0, sizeof(ops));
-  size_t nops = send_empty_initial_metadata ? 2 : 1;
+  size_t nops = send_initial_metadata ? 2 : 1;
   ops[0].op = GRPC_OP_SEND_MESSAGE;
   ctx->send_message = grpc_raw_byte_buffer_create(send_buffer, 1);
   ops[0].data.send_message.send_message = ctx->send_message;
   ops[0].flags = write_flags;
   ops[0].reserved = nullptr;
-  ops[1].op = GRPC_OP_SEND_INITIAL_METADATA;
-  ops[1].flags = 0;
-  ops[1].reserved = nullptr;
+  if (send_initial_metadata) {
+    ops[1].op = GRPC_OP_SEND_INITIAL_METADATA;
+    grpcwrap_metadata_array_move(&(ctx->send_initial_metadata),
+                                 initial_metadata);
+    ops[1].data.send_initial_metadata.count = ctx->send_initial_metadata.count;
+    ops[1].data.send_initial_metadata.metadata =
+        ctx->send_initial_metadata.metadata;
+    ops[1].flags = 0;
+    ops[1].reserved = nullptr;
+  }
 
   return grpc_call_start_batch(call, ops, nops, tag, nullptr);
 }
@@ -673,7 +703,8 @@ grpcwrap_call_send_close_from_client(grpc_call* call, void* tag) {
 GPR_EXPORT grpc_call_error GPR_CALLTYPE grpcwrap_call_send_status_from_server(
     grpc_call* call, grpcwrap_batch_context* ctx, grpc_status_code status_code,
     const char* status_details, size_t status_details_len,
-    grpc_metadata_array* trailing_metadata, int32_t send_empty_initial_metadata,
+    grpc_metadata_array* trailing_metadata,
+    grpc_metadata_array* initial_metadata, int32_t send_initial_metadata,
     grpc_slice* optional_send_buffer, uint32_t write_flags, void* tag) {
   /* TODO: don't use magic number */
   grpc_op ops[3];
@@ -700,8 +731,14 @@ GPR_EXPORT grpc_call_error GPR_CALLTYPE grpcwrap_call_send_status_from_server(
     ops[nops].reserved = nullptr;
     nops++;
   }
-  if (send_empty_initial_metadata) {
+  if (send_initial_metadata) {
     ops[nops].op = GRPC_OP_SEND_INITIAL_METADATA;
+    grpcwrap_metadata_array_move(&(ctx->send_initial_metadata),
+                                 initial_metadata);
+    ops[nops].data.send_initial_metadata.count =
+        ctx->send_initial_metadata.count;
+    ops[nops].data.send_initial_metadata.metadata =
+        ctx->send_initial_metadata.metadata;
     ops[nops].flags = 0;
     ops[nops].reserved = nullptr;
     nops++;
@@ -773,3 +810,371 @@ grpcwrap_server_request_call(grpc_server* server, grpc_completion_queue* cq,
   return grpc_server_request_call(server, &(ctx->call), &(ctx->call_details),
                                   &(ctx->request_metadata), cq, cq, tag);
 }
+
+/* Name resolution */
+
+/** An address pushed to a response generator. `ip` holds `ip_len` bytes
+    (4 for IPv4, 16 for IPv6) in network order, `port` is in host order. */
+typedef struct {
+  uint8_t ip[16];
+  size_t ip_len;
+  uint16_t port;
+} grpcwrap_socket_address;
+
+typedef struct grpcwrap_response_generator grpcwrap_response_generator;
+
+#ifdef GRPCIO_SYS_CORE_INTERNALS
+
+static grpc_core::FakeResolverResponseGenerator* to_generator(
+    grpcwrap_response_generator* generator) {
+  return reinterpret_cast<grpc_core::FakeResolverResponseGenerator*>(
+      generator);
+}
+
+/** Creates a response generator feeding the resolver of a channel created
+    with target "fake:///<name>". */
+GPR_EXPORT grpcwrap_response_generator* GPR_CALLTYPE
+grpcwrap_response_generator_create() {
+  auto generator =
+      grpc_core::MakeRefCounted<grpc_core::FakeResolverResponseGenerator>();
+  return reinterpret_cast<grpcwrap_response_generator*>(generator.release());
+}
+
+GPR_EXPORT void GPR_CALLTYPE
+grpcwrap_response_generator_unref(grpcwrap_response_generator* generator) {
+  grpc_core::ExecCtx exec_ctx;
+  to_generator(generator)->Unref();
+}
+
+/** Sets the channel arg that binds a channel's resolver to the generator.
+    A generator must only be bound to one channel. */
+GPR_EXPORT void GPR_CALLTYPE grpcwrap_channel_args_set_response_generator(
+    grpc_channel_args* args, size_t index,
+    grpcwrap_response_generator* generator) {
+  grpc_arg arg = grpc_core::FakeResolverResponseGenerator::MakeChannelArg(
+      to_generator(generator));
+  grpcwrap_channel_args_set_pointer_vtable(args, index, arg.key,
+                                           arg.value.pointer.p,
+                                           arg.value.pointer.vtable);
+}
+
+/** Pushes a resolution result to the channel bound to the generator.
+    `service_config` may be null. Returns 0 if an address or the service
+    config is invalid, in which case nothing is pushed. */
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_response_generator_set_response(
+    grpcwrap_response_generator* generator,
+    const grpcwrap_socket_address* addrs, size_t count,
+    const char* service_config) {
+  grpc_core::ExecCtx exec_ctx;
+  grpc_core::Resolver::Result result;
+  for (size_t i = 0; i < count; i++) {
+    grpc_resolved_address addr;
+    memset(&addr, 0, sizeof(addr));
+    if (addrs[i].ip_len == 4) {
+      grpc_sockaddr_in* in = reinterpret_cast<grpc_sockaddr_in*>(addr.addr);
+      in->sin_family = GRPC_AF_INET;
+      memcpy(&in->sin_addr, addrs[i].ip, 4);
+      in->sin_port = grpc_htons(addrs[i].port);
+      addr.len = static_cast<socklen_t>(sizeof(grpc_sockaddr_in));
+    } else if (addrs[i].ip_len == 16) {
+      grpc_sockaddr_in6* in6 = reinterpret_cast<grpc_sockaddr_in6*>(addr.addr);
+      in6->sin6_family = GRPC_AF_INET6;
+      memcpy(&in6->sin6_addr, addrs[i].ip, 16);
+      in6->sin6_port = grpc_htons(addrs[i].port);
+      addr.len = static_cast<socklen_t>(sizeof(grpc_sockaddr_in6));
+    } else {
+      return 0;
+    }
+    result.addresses.emplace_back(addr.addr, addr.len, nullptr);
+  }
+  if (service_config != nullptr) {
+    result.service_config = grpc_core::ServiceConfig::Create(
+        nullptr, service_config, &result.service_config_error);
+    if (result.service_config_error != GRPC_ERROR_NONE) {
+      return 0;
+    }
+  }
+  to_generator(generator)->SetResponse(std::move(result));
+  return 1;
+}
+
+#else
+
+// Stubs for prebuilt libraries, `grpcwrap_response_generator_create` returning
+// null tells the caller that core resolvers can't be fed.
+
+GPR_EXPORT grpcwrap_response_generator* GPR_CALLTYPE
+grpcwrap_response_generator_create() {
+  return nullptr;
+}
+
+GPR_EXPORT void GPR_CALLTYPE
+grpcwrap_response_generator_unref(grpcwrap_response_generator*) {}
+
+GPR_EXPORT void GPR_CALLTYPE grpcwrap_channel_args_set_response_generator(
+    grpc_channel_args*, size_t, grpcwrap_response_generator*) {
+  GPR_ASSERT(false);
+}
+
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_response_generator_set_response(
+    grpcwrap_response_generator*, const grpcwrap_socket_address*, size_t,
+    const char*) {
+  return 0;
+}
+
+#endif
+
+#ifdef GRPCIO_SYS_CORE_INTERNALS
+
+/** Gets the channelz id of a channel. Returns 0 if channelz is disabled. */
+GPR_EXPORT intptr_t GPR_CALLTYPE
+grpcwrap_channel_get_channelz_id(grpc_channel* channel) {
+  grpc_core::channelz::ChannelNode* node =
+      grpc_channel_get_channelz_node(channel);
+  return node == nullptr ? 0 : node->uuid();
+}
+
+/** Gets the channelz id of a server. Returns 0 if channelz is disabled. */
+GPR_EXPORT intptr_t GPR_CALLTYPE
+grpcwrap_server_get_channelz_id(grpc_server* server) {
+  grpc_core::channelz::ServerNode* node =
+      grpc_core::Server::FromC(server)->channelz_node();
+  return node == nullptr ? 0 : node->uuid();
+}
+
+#else
+
+GPR_EXPORT intptr_t GPR_CALLTYPE
+grpcwrap_channel_get_channelz_id(grpc_channel*) {
+  return 0;
+}
+
+GPR_EXPORT intptr_t GPR_CALLTYPE
+grpcwrap_server_get_channelz_id(grpc_server*) {
+  return 0;
+}
+
+#endif
+
+#ifdef GRPC_SYS_SECURE
+
+#ifdef GRPCIO_SYS_CORE_INTERNALS
+
+/** Returns 1 if TLS versions can be set on SSL credentials, otherwise 0. */
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_tls_versions_supported() { return 1; }
+
+/** Sets the TLS versions of credentials created by
+    grpc_ssl_credentials_create_ex. Returns 0 if it's not supported or the
+    credentials are not SSL ones. */
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_ssl_credentials_set_tls_versions(
+    grpc_channel_credentials* creds, grpc_tls_version min_tls_version,
+    grpc_tls_version max_tls_version) {
+  if (strcmp(creds->type(), GRPC_CHANNEL_CREDENTIALS_TYPE_SSL) != 0) {
+    return 0;
+  }
+  grpc_ssl_credentials* ssl = static_cast<grpc_ssl_credentials*>(creds);
+  ssl->set_min_tls_version(min_tls_version);
+  ssl->set_max_tls_version(max_tls_version);
+  return 1;
+}
+
+/** Sets the TLS versions of credentials created by
+    grpc_ssl_server_credentials_create_with_options. Returns 0 if it's not
+    supported or the credentials are not SSL ones. */
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_ssl_server_credentials_set_tls_versions(
+    grpc_server_credentials* creds, grpc_tls_version min_tls_version,
+    grpc_tls_version max_tls_version) {
+  if (strcmp(creds->type(), GRPC_CHANNEL_CREDENTIALS_TYPE_SSL) != 0) {
+    return 0;
+  }
+  grpc_ssl_server_credentials* ssl =
+      static_cast<grpc_ssl_server_credentials*>(creds);
+  ssl->set_min_tls_version(min_tls_version);
+  ssl->set_max_tls_version(max_tls_version);
+  return 1;
+}
+
+#else
+
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_tls_versions_supported() { return 0; }
+
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_ssl_credentials_set_tls_versions(
+    grpc_channel_credentials*, grpc_tls_version, grpc_tls_version) {
+  return 0;
+}
+
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_ssl_server_credentials_set_tls_versions(
+    grpc_server_credentials*, grpc_tls_version, grpc_tls_version) {
+  return 0;
+}
+
+#endif
+
+/* Peer checks */
+
+/** Checks the peer of a connection whose security handshake is done. Returns
+    null to accept the peer, otherwise the reason of rejecting it, which is
+    allocated by gpr_malloc. */
+typedef char* (*grpcwrap_peer_check)(void* user_data, grpc_auth_context* ctx);
+
+/** The process function of grpc_auth_metadata_processor. */
+typedef void (*grpcwrap_auth_metadata_process)(
+    void* state, grpc_auth_context* context, const grpc_metadata* md,
+    size_t num_md, grpc_process_auth_metadata_done_cb cb, void* user_data);
+
+/** The pointer argument of a channel that is passed to the check of servers. */
+#define GRPCWRAP_ARG_PEER_CHECKS "grpcwrap.peer_checks"
+
+#ifdef GRPCIO_SYS_CORE_INTERNALS
+
+static grpcwrap_peer_check g_check_server = nullptr;
+static grpcwrap_peer_check g_check_client = nullptr;
+static grpcwrap_auth_metadata_process g_process = nullptr;
+
+/** Finds the user data of the check of servers in the args of a channel. */
+static void* find_server_checks(const grpc_channel_args* args) {
+  const grpc_arg* arg = grpc_channel_args_find(args, GRPCWRAP_ARG_PEER_CHECKS);
+  if (arg == nullptr || arg->type != GRPC_ARG_POINTER) {
+    return nullptr;
+  }
+  return arg->value.pointer.p;
+}
+
+/** Finds the user data of the check of clients, which is the state of the
+    auth metadata processor of the server credentials the port is added with,
+    if its processor is the one passed to grpcwrap_set_peer_checks. */
+static void* find_client_checks(const grpc_channel_args* args) {
+  grpc_security_connector* sc = grpc_security_connector_find_in_args(args);
+  if (sc == nullptr) {
+    return nullptr;
+  }
+  const grpc_server_credentials* creds =
+      static_cast<grpc_server_security_connector*>(sc)->server_creds();
+  if (creds == nullptr || creds->auth_metadata_processor().process !=
+                              g_process) {
+    return nullptr;
+  }
+  return creds->auth_metadata_processor().state;
+}
+
+namespace {
+
+/** Runs after the security handshaker, which puts the auth context of the
+    connection in the args, and fails the handshake if the peer is
+    rejected. */
+class PeerCheckHandshaker : public grpc_core::Handshaker {
+ public:
+  explicit PeerCheckHandshaker(bool is_client) : is_client_(is_client) {}
+
+  void Shutdown(grpc_error_handle why) override { GRPC_ERROR_UNREF(why); }
+
+  void DoHandshake(grpc_tcp_server_acceptor* /*acceptor*/,
+                   grpc_closure* on_handshake_done,
+                   grpc_core::HandshakerArgs* args) override {
+    grpc_error_handle error = GRPC_ERROR_NONE;
+    char* reason = nullptr;
+    void* user_data = is_client_ ? find_server_checks(args->args)
+                                 : find_client_checks(args->args);
+    grpc_auth_context* ctx = grpc_find_auth_context_in_args(args->args);
+    if (user_data == nullptr) {
+      // Nothing to check.
+    } else if (ctx == nullptr) {
+      reason = gpr_strdup("no auth context is found");
+    } else if (is_client_) {
+      reason = g_check_server(user_data, ctx);
+    } else {
+      reason = g_check_client(user_data, ctx);
+    }
+    if (reason != nullptr) {
+      error = grpc_error_set_int(GRPC_ERROR_CREATE_FROM_COPIED_STRING(reason),
+                                 GRPC_ERROR_INT_GRPC_STATUS,
+                                 GRPC_STATUS_UNAVAILABLE);
+      gpr_free(reason);
+      // Cleans up like other handshakers do when failing.
+      grpc_endpoint_shutdown(args->endpoint, GRPC_ERROR_REF(error));
+      grpc_endpoint_destroy(args->endpoint);
+      args->endpoint = nullptr;
+      grpc_channel_args_destroy(args->args);
+      args->args = nullptr;
+      grpc_slice_buffer_destroy_internal(args->read_buffer);
+      gpr_free(args->read_buffer);
+      args->read_buffer = nullptr;
+    }
+    grpc_core::ExecCtx::Run(DEBUG_LOCATION, on_handshake_done, error);
+  }
+
+  const char* name() const override { return "grpcwrap_peer_check"; }
+
+ private:
+  bool is_client_;
+};
+
+class PeerCheckHandshakerFactory : public grpc_core::HandshakerFactory {
+ public:
+  explicit PeerCheckHandshakerFactory(bool is_client)
+      : is_client_(is_client) {}
+
+  void AddHandshakers(const grpc_channel_args* args,
+                      grpc_pollset_set* /*interested_parties*/,
+                      grpc_core::HandshakeManager* handshake_mgr) override {
+    void* user_data = is_client_ ? find_server_checks(args)
+                                 : find_client_checks(args);
+    if (user_data != nullptr) {
+      handshake_mgr->Add(
+          grpc_core::MakeRefCounted<PeerCheckHandshaker>(is_client_));
+    }
+  }
+
+  ~PeerCheckHandshakerFactory() override = default;
+
+ private:
+  bool is_client_;
+};
+
+}  // namespace
+
+/** Appends the handshakers, which run after the security handshakers as
+    those are registered before plugins are initialized. */
+static void register_peer_check_handshakers() {
+  grpc_core::HandshakerRegistry::RegisterHandshakerFactory(
+      false, grpc_core::HANDSHAKER_CLIENT,
+      std::unique_ptr<grpc_core::HandshakerFactory>(
+          new PeerCheckHandshakerFactory(true)));
+  grpc_core::HandshakerRegistry::RegisterHandshakerFactory(
+      false, grpc_core::HANDSHAKER_SERVER,
+      std::unique_ptr<grpc_core::HandshakerFactory>(
+          new PeerCheckHandshakerFactory(false)));
+}
+
+/** Sets the checks of peers, which run during the handshakes of connections.
+    Servers are checked on channels with the pointer argument
+    GRPCWRAP_ARG_PEER_CHECKS, clients are checked on ports added with server
+    credentials whose auth metadata processor has `process`. It must be called
+    at most once, preferably before grpc_init. Returns 0 if it's not
+    supported. */
+GPR_EXPORT int GPR_CALLTYPE
+grpcwrap_set_peer_checks(grpcwrap_peer_check check_server,
+                         grpcwrap_peer_check check_client,
+                         grpcwrap_auth_metadata_process process) {
+  g_check_server = check_server;
+  g_check_client = check_client;
+  g_process = process;
+  // Handshakers are registered again whenever grpc is initialized from
+  // scratch, which clears the registry.
+  grpc_register_plugin(register_peer_check_handshakers, nullptr);
+  if (grpc_is_initialized()) {
+    register_peer_check_handshakers();
+  }
+  return 1;
+}
+
+#else
+
+GPR_EXPORT int GPR_CALLTYPE grpcwrap_set_peer_checks(
+    grpcwrap_peer_check, grpcwrap_peer_check, grpcwrap_auth_metadata_process) {
+  return 0;
+}
+
+#endif
+
+#endif
@@ -16,6 +16,10 @@
 #![allow(unused_imports)]
 #![allow(unused_results)]
 
+const FILE_DESCRIPTORS: &[&[u8]] = &[
+    b"\n\x1bgrpc/health/v1/health.proto\x12\x0egrpc.health.v1\".\n\x12HealthCheckRequest\x12\x18\n\x07service\x18\x01 \x01(\tR\x07service\"\xb1\x01\n\x13HealthCheckResponse\x12I\n\x06status\x18\x01 \x01(\x0e21.grpc.health.v1.HealthCheckResponse.ServingStatusR\x06status\"O\n\rServingStatus\x12\x0b\n\x07UNKNOWN\x10\x00\x12\x0b\n\x07SERVING\x10\x01\x12\x0f\n\x0bNOT_SERVING\x10\x02\x12\x13\n\x0fSERVICE_UNKNOWN\x10\x032\xae\x01\n\x06Health\x12P\n\x05Check\x12\".grpc.health.v1.HealthCheckRequest\x1a#.grpc.health.v1.HealthCheckResponse\x12R\n\x05Watch\x12\".grpc.health.v1.HealthCheckRequest\x1a#.grpc.health.v1.HealthCheckResponse0\x01Ba\n\x11io.grpc.health.v1B\x0bHealthProtoP\x01Z,google.golang.org/grpc/health/grpc_health_v1\xaa\x02\x0eGrpc.Health.V1b\x06proto3",
+];
+
 const METHOD_HEALTH_CHECK: ::grpcio::Method<super::health::HealthCheckRequest, super::health::HealthCheckResponse> = ::grpcio::Method {
     ty: ::grpcio::MethodType::Unary,
     name: "/grpc.health.v1.Health/Check",
@@ -42,6 +46,12 @@ impl HealthClient {
         }
     }
 
+    pub fn with_pool(pool: ::grpcio::ChannelPool) -> Self {
+        HealthClient {
+            client: ::grpcio::Client::with_pool(pool),
+        }
+    }
+
     pub fn check_opt(&self, req: &super::health::HealthCheckRequest, opt: ::grpcio::CallOption) -> ::grpcio::Result<super::health::HealthCheckResponse> {
         self.client.unary_call(&METHOD_HEALTH_CHECK, req, opt)
     }
@@ -89,5 +99,6 @@ pub fn create_health<S: Health + Send + Clone + 'static>(s: S) -> ::grpcio::Serv
     builder = builder.add_server_streaming_handler(&METHOD_HEALTH_WATCH, move |ctx, req, resp| {
         instance.watch(ctx, req, resp)
     });
+    builder = builder.add_file_descriptors(FILE_DESCRIPTORS);
     builder.build()
 }
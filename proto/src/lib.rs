@@ -127,6 +127,8 @@ pub mod google {
     pub mod rpc {
         include!(concat!(env!("OUT_DIR"), "/rpc/mod.rs"));
 
+        #[cfg(feature = "protobuf-codec")]
+        pub use self::error_details::*;
         #[cfg(feature = "protobuf-codec")]
         pub use self::status::*;
 
@@ -135,4 +137,5 @@ pub mod google {
     }
 }
 
+pub mod rich_error;
 pub mod util;
@@ -0,0 +1,181 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Helpers for the rich error model.
+//!
+//! Servers can attach typed details, like the standard ones defined in
+//! `google/rpc/error_details.proto`, to a `google.rpc.Status`, which is carried
+//! by [`RpcStatus`] to the client side. See also
+//! https://grpc.io/docs/guides/error/#richer-error-model.
+//!
+//! [`RpcStatus`]: grpcio::RpcStatus
+
+use crate::google::rpc::*;
+use grpcio::{Result, RpcStatus, RpcStatusCode};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+#[cfg(feature = "prost-codec")]
+use prost_types::Any;
+#[cfg(all(feature = "protobuf-codec", not(feature = "prost-codec")))]
+use protobuf::well_known_types::Any;
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// A message that can be packed into the details of a `google.rpc.Status`.
+///
+/// It's implemented for all protobuf messages automatically.
+#[cfg(all(feature = "protobuf-codec", not(feature = "prost-codec")))]
+pub trait StatusDetail: protobuf::Message {
+    /// Returns the fully qualified name of the message type, for example
+    /// `google.rpc.ErrorInfo`.
+    fn type_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        Self::descriptor_static().full_name()
+    }
+}
+
+#[cfg(all(feature = "protobuf-codec", not(feature = "prost-codec")))]
+impl<M: protobuf::Message> StatusDetail for M {}
+
+/// A message that can be packed into the details of a `google.rpc.Status`.
+///
+/// It's implemented for the types in `google/rpc/error_details.proto`, implement
+/// it for your own messages to use them as details.
+#[cfg(feature = "prost-codec")]
+pub trait StatusDetail: prost::Message + Default {
+    /// Returns the fully qualified name of the message type, for example
+    /// `google.rpc.ErrorInfo`.
+    fn type_name() -> &'static str;
+}
+
+#[cfg(feature = "prost-codec")]
+macro_rules! impl_status_detail {
+    ($($t:ty => $name:expr,)+) => {
+        $(
+            impl StatusDetail for $t {
+                fn type_name() -> &'static str {
+                    $name
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "prost-codec")]
+impl_status_detail! {
+    RetryInfo => "google.rpc.RetryInfo",
+    DebugInfo => "google.rpc.DebugInfo",
+    QuotaFailure => "google.rpc.QuotaFailure",
+    ErrorInfo => "google.rpc.ErrorInfo",
+    PreconditionFailure => "google.rpc.PreconditionFailure",
+    BadRequest => "google.rpc.BadRequest",
+    RequestInfo => "google.rpc.RequestInfo",
+    ResourceInfo => "google.rpc.ResourceInfo",
+    Help => "google.rpc.Help",
+    LocalizedMessage => "google.rpc.LocalizedMessage",
+}
+
+fn type_url<M: StatusDetail>() -> String {
+    format!("{}{}", TYPE_URL_PREFIX, M::type_name())
+}
+
+/// Checks if the type url refers to `M`, the host part of the url is ignored.
+fn is_type<M: StatusDetail>(type_url: &str) -> bool {
+    match type_url.rfind('/') {
+        Some(pos) => &type_url[pos + 1..] == M::type_name(),
+        None => type_url == M::type_name(),
+    }
+}
+
+#[cfg(all(feature = "protobuf-codec", not(feature = "prost-codec")))]
+fn pack<M: StatusDetail>(detail: &M) -> Result<Any> {
+    let mut any = Any::default();
+    any.set_type_url(type_url::<M>());
+    any.set_value(detail.write_to_bytes()?);
+    Ok(any)
+}
+
+#[cfg(all(feature = "protobuf-codec", not(feature = "prost-codec")))]
+fn unpack<M: StatusDetail>(any: &Any) -> Result<M> {
+    Ok(M::parse_from_bytes(&any.value)?)
+}
+
+#[cfg(feature = "prost-codec")]
+fn pack<M: StatusDetail>(detail: &M) -> Result<Any> {
+    let mut value = Vec::with_capacity(detail.encoded_len());
+    detail.encode(&mut value).unwrap();
+    Ok(Any {
+        type_url: type_url::<M>(),
+        value,
+    })
+}
+
+#[cfg(feature = "prost-codec")]
+fn unpack<M: StatusDetail>(any: &Any) -> Result<M> {
+    Ok(M::decode(any.value.as_slice())?)
+}
+
+impl Status {
+    /// Creates a status with the given code and message.
+    pub fn with_message(code: RpcStatusCode, message: String) -> Status {
+        Status {
+            code: code.into(),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// Extracts the `google.rpc.Status` carried by `status`.
+    ///
+    /// Unlike `TryFrom`, a status that carries no details is also accepted, in which
+    /// case a `google.rpc.Status` with the same code and message is returned.
+    pub fn from_rpc_status(status: &RpcStatus) -> Result<Status> {
+        if status.details().is_empty() {
+            return Ok(Status::with_message(
+                status.code(),
+                status.message().to_owned(),
+            ));
+        }
+        Status::try_from(status.clone())
+    }
+
+    /// Packs `detail` into `google.protobuf.Any` and appends it to the details.
+    pub fn pack_detail<M: StatusDetail>(&mut self, detail: &M) -> Result<()> {
+        self.details.push(pack(detail)?);
+        Ok(())
+    }
+
+    /// Returns the first detail of type `M`.
+    ///
+    /// `None` is returned if there is no such detail.
+    pub fn unpack_detail<M: StatusDetail>(&self) -> Result<Option<M>> {
+        match self.details.iter().find(|any| is_type::<M>(&any.type_url)) {
+            Some(any) => unpack(any).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all details of type `M`, in the order they were added.
+    pub fn unpack_details<M: StatusDetail>(&self) -> Result<Vec<M>> {
+        self.details
+            .iter()
+            .filter(|any| is_type::<M>(&any.type_url))
+            .map(unpack)
+            .collect()
+    }
+}
+
+impl RetryInfo {
+    /// Returns the retry delay suggested by the server.
+    ///
+    /// `None` is returned if the delay is absent or negative.
+    pub fn delay(&self) -> Option<Duration> {
+        let d = self.retry_delay.as_ref()?;
+        if d.seconds < 0 || d.nanos < 0 {
+            return None;
+        }
+        Some(Duration::new(d.seconds as u64, d.nanos as u32))
+    }
+}
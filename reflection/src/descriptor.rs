@@ -0,0 +1,325 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[cfg(feature = "prost-codec")]
+mod codec {
+    pub use prost_types::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        ServiceDescriptorProto,
+    };
+
+    pub fn parse(data: &[u8]) -> Result<FileDescriptorProto, String> {
+        prost::Message::decode(data).map_err(|e: prost::DecodeError| e.to_string())
+    }
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod codec {
+    pub use protobuf::descriptor::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        ServiceDescriptorProto,
+    };
+    use protobuf::descriptor::{
+        EnumValueDescriptorProto, MethodDescriptorProto, OneofDescriptorProto,
+    };
+    use protobuf::Message;
+
+    pub fn parse(data: &[u8]) -> Result<FileDescriptorProto, String> {
+        FileDescriptorProto::parse_from_bytes(data).map_err(|e| e.to_string())
+    }
+
+    /// Accessors named after the ones generated by prost.
+    pub trait Named {
+        fn name(&self) -> &str;
+    }
+
+    macro_rules! impl_named {
+        ($($t:ty),*) => {
+            $(impl Named for $t {
+                fn name(&self) -> &str {
+                    self.get_name()
+                }
+            })*
+        };
+    }
+
+    impl_named!(
+        DescriptorProto,
+        EnumDescriptorProto,
+        EnumValueDescriptorProto,
+        FieldDescriptorProto,
+        FileDescriptorProto,
+        MethodDescriptorProto,
+        OneofDescriptorProto,
+        ServiceDescriptorProto
+    );
+
+    pub trait FileExt {
+        fn package(&self) -> &str;
+    }
+
+    impl FileExt for FileDescriptorProto {
+        fn package(&self) -> &str {
+            self.get_package()
+        }
+    }
+
+    pub trait FieldExt {
+        fn extendee(&self) -> &str;
+        fn number(&self) -> i32;
+    }
+
+    impl FieldExt for FieldDescriptorProto {
+        fn extendee(&self) -> &str {
+            self.get_extendee()
+        }
+
+        fn number(&self) -> i32 {
+            self.get_number()
+        }
+    }
+}
+
+use self::codec::*;
+
+struct File {
+    data: &'static [u8],
+    dependencies: Vec<String>,
+}
+
+/// The symbols defined by a file.
+#[derive(Default)]
+struct Symbols {
+    names: Vec<String>,
+    /// (extendee, number) of extensions.
+    extensions: Vec<(String, i32)>,
+}
+
+fn full_name(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+impl Symbols {
+    fn add_extension(&mut self, scope: &str, ext: &FieldDescriptorProto) {
+        self.names.push(full_name(scope, ext.name()));
+        let extendee = ext.extendee().trim_start_matches('.').to_owned();
+        self.extensions.push((extendee, ext.number()));
+    }
+
+    fn add_enum(&mut self, scope: &str, e: &EnumDescriptorProto) {
+        self.names.push(full_name(scope, e.name()));
+        // Values of an enum are siblings of the enum type.
+        for value in &e.value {
+            self.names.push(full_name(scope, value.name()));
+        }
+    }
+
+    fn add_message(&mut self, scope: &str, msg: &DescriptorProto) {
+        let name = full_name(scope, msg.name());
+        for field in &msg.field {
+            self.names.push(full_name(&name, field.name()));
+        }
+        for oneof in &msg.oneof_decl {
+            self.names.push(full_name(&name, oneof.name()));
+        }
+        for nested in &msg.nested_type {
+            self.add_message(&name, nested);
+        }
+        for e in &msg.enum_type {
+            self.add_enum(&name, e);
+        }
+        for ext in &msg.extension {
+            self.add_extension(&name, ext);
+        }
+        self.names.push(name);
+    }
+
+    fn add_service(&mut self, scope: &str, service: &ServiceDescriptorProto) {
+        let name = full_name(scope, service.name());
+        for method in &service.method {
+            self.names.push(full_name(&name, method.name()));
+        }
+        self.names.push(name);
+    }
+}
+
+/// An index of serialized `FileDescriptorProto`s.
+#[derive(Default)]
+pub struct DescriptorPool {
+    files: HashMap<String, File>,
+    /// Full name of symbols -> the file defining it.
+    symbols: HashMap<String, String>,
+    /// Full name of extendee -> extension number -> the file defining it.
+    extensions: HashMap<String, BTreeMap<i32, String>>,
+}
+
+impl DescriptorPool {
+    /// Adds a file to the pool. Files that have been added before are ignored.
+    pub fn add_file(&mut self, data: &'static [u8]) -> Result<(), String> {
+        let file = codec::parse(data)?;
+        let (name, package) = (file.name(), file.package());
+        if name.is_empty() {
+            return Err("file name is missing".to_owned());
+        }
+        if self.files.contains_key(name) {
+            return Ok(());
+        }
+        let mut symbols = Symbols::default();
+        for msg in &file.message_type {
+            symbols.add_message(package, msg);
+        }
+        for e in &file.enum_type {
+            symbols.add_enum(package, e);
+        }
+        for service in &file.service {
+            symbols.add_service(package, service);
+        }
+        for ext in &file.extension {
+            symbols.add_extension(package, ext);
+        }
+
+        if !package.is_empty() {
+            // Packages are valid symbols too, they may be defined by multiple files.
+            let mut scope = package;
+            loop {
+                self.symbols
+                    .entry(scope.to_owned())
+                    .or_insert_with(|| name.to_owned());
+                match scope.rfind('.') {
+                    Some(pos) => scope = &scope[..pos],
+                    None => break,
+                }
+            }
+        }
+        for symbol in symbols.names {
+            self.symbols.insert(symbol, name.to_owned());
+        }
+        for (extendee, number) in symbols.extensions {
+            self.extensions
+                .entry(extendee)
+                .or_default()
+                .insert(number, name.to_owned());
+        }
+        let dependencies = file.dependency.to_vec();
+        self.files
+            .insert(name.to_owned(), File { data, dependencies });
+        Ok(())
+    }
+
+    /// Gets the file and all its dependencies. The file comes first, and missing
+    /// dependencies are skipped.
+    fn file_with_dependencies(&self, name: &str) -> Option<Vec<&'static [u8]>> {
+        self.files.get(name)?;
+        let mut res = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![name];
+        while let Some(name) = stack.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            if let Some(file) = self.files.get(name) {
+                res.push(file.data);
+                stack.extend(file.dependencies.iter().rev().map(|d| d.as_str()));
+            }
+        }
+        Some(res)
+    }
+
+    pub fn file_by_filename(&self, name: &str) -> Option<Vec<&'static [u8]>> {
+        self.file_with_dependencies(name)
+    }
+
+    pub fn file_containing_symbol(&self, symbol: &str) -> Option<Vec<&'static [u8]>> {
+        self.file_with_dependencies(self.symbols.get(symbol)?)
+    }
+
+    pub fn file_containing_extension(
+        &self,
+        containing_type: &str,
+        number: i32,
+    ) -> Option<Vec<&'static [u8]>> {
+        let file = self.extensions.get(containing_type)?.get(&number)?;
+        self.file_with_dependencies(file)
+    }
+
+    /// Gets the numbers of all known extensions of `containing_type`, or `None` if
+    /// the type is unknown.
+    pub fn extension_numbers(&self, containing_type: &str) -> Option<Vec<i32>> {
+        self.symbols.get(containing_type)?;
+        Some(match self.extensions.get(containing_type) {
+            Some(exts) => exts.keys().copied().collect(),
+            None => vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a.proto, which defines `test.a.Foo` and `test.a.Svc`.
+    const A: &[u8] = b"\n\x07a.proto\x12\x06test.a\"W\n\x03Foo\x12\x0e\n\x02id\x18\x01 \x01(\x05R\x02id\x12\x14\n\x04name\x18\x02 \x01(\tH\x00R\x04name\x1a\x05\n\x03Bar\"\x12\n\x04Kind\x12\n\n\x06KIND_A\x10\x00*\x05\x08d\x10\xc9\x01B\x08\n\x06choice2'\n\x03Svc\x12 \n\x04Call\x12\x0b.test.a.Foo\x1a\x0b.test.a.Foo";
+    /// b.proto, which imports a.proto and missing.proto, and extends `test.a.Foo`.
+    const B: &[u8] = b"\n\x07b.proto\x12\x06test.b\x1a\x07a.proto\x1a\rmissing.proto\"3\n\x05Scope2*\n\nnested_ext\x12\x0b.test.a.Foo\x18e \x01(\x05R\tnestedExt:\x1d\n\x03ext\x12\x0b.test.a.Foo\x18d \x01(\x05R\x03ext";
+
+    #[test]
+    fn test_descriptor_pool() {
+        let mut pool = DescriptorPool::default();
+        pool.add_file(A).unwrap();
+        pool.add_file(B).unwrap();
+        pool.add_file(A).unwrap();
+        assert!(pool.add_file(b"\x12\x01a").is_err());
+        assert!(pool.add_file(b"\x0a\x05a").is_err());
+
+        for symbol in &[
+            "test",
+            "test.a",
+            "test.a.Foo",
+            "test.a.Foo.id",
+            "test.a.Foo.name",
+            "test.a.Foo.Bar",
+            "test.a.Foo.Kind",
+            "test.a.Foo.KIND_A",
+            "test.a.Foo.choice",
+            "test.a.Svc",
+            "test.a.Svc.Call",
+        ] {
+            assert_eq!(
+                pool.file_containing_symbol(symbol),
+                Some(vec![A]),
+                "{}",
+                symbol
+            );
+        }
+        for symbol in &["test.b.ext", "test.b.Scope", "test.b.Scope.nested_ext"] {
+            assert_eq!(
+                pool.file_containing_symbol(symbol),
+                Some(vec![B, A]),
+                "{}",
+                symbol
+            );
+        }
+        for symbol in &["", "test.a.Foo.Kind.KIND_A", "test.a.Baz", "test.a.Call"] {
+            assert_eq!(pool.file_containing_symbol(symbol), None, "{}", symbol);
+        }
+
+        assert_eq!(pool.file_by_filename("a.proto"), Some(vec![A]));
+        assert_eq!(pool.file_by_filename("b.proto"), Some(vec![B, A]));
+        assert_eq!(pool.file_by_filename("missing.proto"), None);
+
+        assert_eq!(
+            pool.file_containing_extension("test.a.Foo", 101),
+            Some(vec![B, A])
+        );
+        assert_eq!(pool.file_containing_extension("test.a.Foo", 102), None);
+        assert_eq!(pool.file_containing_extension("test.a.Bar", 100), None);
+        assert_eq!(pool.extension_numbers("test.a.Foo"), Some(vec![100, 101]));
+        assert_eq!(pool.extension_numbers("test.a.Foo.Bar"), Some(vec![]));
+        assert_eq!(pool.extension_numbers("test.a.Baz"), None);
+    }
+}
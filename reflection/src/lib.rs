@@ -0,0 +1,42 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! grpcio-reflection provides an implementation of the server reflection service,
+//! which lets tools like `grpcurl` list the services of a server and query their
+//! definitions at runtime. For the protocol, see
+//! https://github.com/grpc/grpc/blob/master/doc/server-reflection.md.
+//!
+//! ### Usage
+//!
+//! The service describes the services registered to the server builder before it's
+//! created, so it should be registered last.
+//! ```ignore
+//! use grpcio_reflection::{create_server_reflection, ReflectionService};
+//!
+//! let builder = ServerBuilder::new(env).register_service(create_greeter(greeter));
+//! let reflection = ReflectionService::new(&builder);
+//! let builder = builder.register_service(create_server_reflection(reflection));
+//! ```
+//! Definitions are served from the file descriptors embedded by grpcio-compiler, so
+//! the services should be generated by a compiler that supports it.
+//!
+//! Client can query the service by a duplex stream, for example with prost-codec:
+//! ```ignore
+//! use grpcio_reflection::proto::server_reflection_request::MessageRequest;
+//! use grpcio_reflection::proto::ServerReflectionRequest;
+//!
+//! let client = ServerReflectionClient::new(ch);
+//! let (mut tx, mut rx) = client.server_reflection_info().unwrap();
+//! let req = ServerReflectionRequest {
+//!     message_request: Some(MessageRequest::ListServices(String::new())),
+//!     ..Default::default()
+//! };
+//! tx.send((req, WriteFlags::default())).await.unwrap();
+//! let resp = rx.try_next().await.unwrap().unwrap();
+//! ```
+
+mod descriptor;
+pub mod proto;
+mod service;
+
+pub use self::proto::{create_server_reflection, ServerReflection, ServerReflectionClient};
+pub use self::service::ReflectionService;
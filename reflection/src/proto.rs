@@ -0,0 +1,19 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+#[cfg(feature = "prost-codec")]
+mod reexports {
+    include!("proto/prost/grpc.reflection.v1alpha.rs");
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod protobuf {
+    pub mod reflection;
+    pub mod reflection_grpc;
+}
+#[cfg(feature = "protobuf-codec")]
+mod reexports {
+    pub use super::protobuf::reflection::*;
+    pub use super::protobuf::reflection_grpc::*;
+}
+
+pub use self::reexports::*;
@@ -0,0 +1,162 @@
+/// The message sent by the client when calling ServerReflectionInfo method.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServerReflectionRequest {
+    #[prost(string, tag="1")]
+    pub host: ::prost::alloc::string::String,
+    /// To use reflection service, the client should set one of the following
+    /// fields in message_request. The server distinguishes requests by their
+    /// defined field and then handles them using corresponding methods.
+    #[prost(oneof="server_reflection_request::MessageRequest", tags="3, 4, 5, 6, 7")]
+    pub message_request: ::core::option::Option<server_reflection_request::MessageRequest>,
+}
+/// Nested message and enum types in `ServerReflectionRequest`.
+pub mod server_reflection_request {
+    /// To use reflection service, the client should set one of the following
+    /// fields in message_request. The server distinguishes requests by their
+    /// defined field and then handles them using corresponding methods.
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum MessageRequest {
+        /// Find a proto file by the file name.
+        #[prost(string, tag="3")]
+        FileByFilename(::prost::alloc::string::String),
+        /// Find the proto file that declares the given fully-qualified symbol name.
+        /// This field should be a fully-qualified symbol name
+        /// (e.g. <package>.<service>[.<method>] or <package>.<type>).
+        #[prost(string, tag="4")]
+        FileContainingSymbol(::prost::alloc::string::String),
+        /// Find the proto file which defines an extension extending the given
+        /// message type with the given field number.
+        #[prost(message, tag="5")]
+        FileContainingExtension(super::ExtensionRequest),
+        /// Finds the tag numbers used by all known extensions of extendee_type, and
+        /// appends them to ExtensionNumberResponse in an undefined order.
+        /// Its corresponding method is best-effort: it's not guaranteed that the
+        /// reflection service will implement this method, and it's not guaranteed
+        /// that this method will provide all extensions. Returns
+        /// StatusCode::UNIMPLEMENTED if it's not implemented.
+        /// This field should be a fully-qualified type name. The format is
+        /// <package>.<type>
+        #[prost(string, tag="6")]
+        AllExtensionNumbersOfType(::prost::alloc::string::String),
+        /// List the full names of registered services. The content will not be
+        /// checked.
+        #[prost(string, tag="7")]
+        ListServices(::prost::alloc::string::String),
+    }
+}
+/// The type name and extension number sent by the client when requesting
+/// file_containing_extension.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtensionRequest {
+    /// Fully-qualified type name. The format should be <package>.<type>
+    #[prost(string, tag="1")]
+    pub containing_type: ::prost::alloc::string::String,
+    #[prost(int32, tag="2")]
+    pub extension_number: i32,
+}
+/// The message sent by the server to answer ServerReflectionInfo method.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServerReflectionResponse {
+    #[prost(string, tag="1")]
+    pub valid_host: ::prost::alloc::string::String,
+    #[prost(message, optional, tag="2")]
+    pub original_request: ::core::option::Option<ServerReflectionRequest>,
+    /// The server sets one of the following fields according to the
+    /// message_request in the request.
+    #[prost(oneof="server_reflection_response::MessageResponse", tags="4, 5, 6, 7")]
+    pub message_response: ::core::option::Option<server_reflection_response::MessageResponse>,
+}
+/// Nested message and enum types in `ServerReflectionResponse`.
+pub mod server_reflection_response {
+    /// The server sets one of the following fields according to the
+    /// message_request in the request.
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum MessageResponse {
+        /// This message is used to answer file_by_filename, file_containing_symbol,
+        /// file_containing_extension requests with transitive dependencies.
+        /// As the repeated label is not allowed in oneof fields, we use a
+        /// FileDescriptorResponse message to encapsulate the repeated fields.
+        /// The reflection service is allowed to avoid sending FileDescriptorProtos
+        /// that were previously sent in response to earlier requests in the stream.
+        #[prost(message, tag="4")]
+        FileDescriptorResponse(super::FileDescriptorResponse),
+        /// This message is used to answer all_extension_numbers_of_type requests.
+        #[prost(message, tag="5")]
+        AllExtensionNumbersResponse(super::ExtensionNumberResponse),
+        /// This message is used to answer list_services requests.
+        #[prost(message, tag="6")]
+        ListServicesResponse(super::ListServiceResponse),
+        /// This message is used when an error occurs.
+        #[prost(message, tag="7")]
+        ErrorResponse(super::ErrorResponse),
+    }
+}
+/// Serialized FileDescriptorProto messages sent by the server answering
+/// a file_by_filename, file_containing_symbol, or file_containing_extension
+/// request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileDescriptorResponse {
+    /// Serialized FileDescriptorProto messages. We avoid taking a dependency on
+    /// descriptor.proto, which uses proto2 only features, by making them opaque
+    /// bytes instead.
+    #[prost(bytes="vec", repeated, tag="1")]
+    pub file_descriptor_proto: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// A list of extension numbers sent by the server answering
+/// all_extension_numbers_of_type request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtensionNumberResponse {
+    /// Full name of the base type, including the package name. The format
+    /// is <package>.<type>
+    #[prost(string, tag="1")]
+    pub base_type_name: ::prost::alloc::string::String,
+    #[prost(int32, repeated, tag="2")]
+    pub extension_number: ::prost::alloc::vec::Vec<i32>,
+}
+/// A list of ServiceResponse sent by the server answering list_services request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListServiceResponse {
+    /// The information of each service may be expanded in the future, so we use
+    /// ServiceResponse message to encapsulate it.
+    #[prost(message, repeated, tag="1")]
+    pub service: ::prost::alloc::vec::Vec<ServiceResponse>,
+}
+/// The information of a single service used by ListServiceResponse to answer
+/// list_services request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ServiceResponse {
+    /// Full name of a registered service, including its package name. The format
+    /// is <package>.<service>
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+}
+/// The error code and error message sent by the server when an error occurs.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ErrorResponse {
+    /// This field uses the error codes defined in grpc::StatusCode.
+    #[prost(int32, tag="1")]
+    pub error_code: i32,
+    #[prost(string, tag="2")]
+    pub error_message: ::prost::alloc::string::String,
+}
+const METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO: ::grpcio::Method<ServerReflectionRequest, ServerReflectionResponse> = ::grpcio::Method{ty: ::grpcio::MethodType::Duplex, name: "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo", req_mar: ::grpcio::Marshaller { ser: ::grpcio::pr_ser, de: ::grpcio::pr_de }, resp_mar: ::grpcio::Marshaller { ser: ::grpcio::pr_ser, de: ::grpcio::pr_de }, };
+#[derive(Clone)]
+pub struct ServerReflectionClient { client: ::grpcio::Client }
+impl ServerReflectionClient {
+pub fn new(channel: ::grpcio::Channel) -> Self { ServerReflectionClient { client: ::grpcio::Client::new(channel) }}
+pub fn with_pool(pool: ::grpcio::ChannelPool) -> Self { ServerReflectionClient { client: ::grpcio::Client::with_pool(pool) }}
+pub fn server_reflection_info_opt(&self, opt: ::grpcio::CallOption) -> ::grpcio::Result<(::grpcio::ClientDuplexSender<ServerReflectionRequest>,::grpcio::ClientDuplexReceiver<ServerReflectionResponse>,)> { self.client.duplex_streaming(&METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO, opt) }
+pub fn server_reflection_info(&self) -> ::grpcio::Result<(::grpcio::ClientDuplexSender<ServerReflectionRequest>,::grpcio::ClientDuplexReceiver<ServerReflectionResponse>,)> { self.server_reflection_info_opt(::grpcio::CallOption::default()) }
+pub fn spawn<F>(&self, f: F) where F: ::futures::Future<Output = ()> + Send + 'static {self.client.spawn(f)}
+}
+pub trait ServerReflection {
+fn server_reflection_info(&mut self, ctx: ::grpcio::RpcContext, _stream: ::grpcio::RequestStream<ServerReflectionRequest>, sink: ::grpcio::DuplexSink<ServerReflectionResponse>) { grpcio::unimplemented_call!(ctx, sink) }
+}
+pub fn create_server_reflection<S: ServerReflection + Send + Clone + 'static>(s: S) -> ::grpcio::Service {
+let mut builder = ::grpcio::ServiceBuilder::new();
+let mut instance = s;
+builder = builder.add_duplex_streaming_handler(&METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO, move |ctx, req, resp| instance.server_reflection_info(ctx, req, resp));
+const FILE_DESCRIPTORS: &[&[u8]] = &[b"\n(grpc/reflection/v1alpha/reflection.proto\x12\x17grpc.reflection.v1alpha\"\xf8\x02\n\x17ServerReflectionRequest\x12\x12\n\x04host\x18\x01 \x01(\tR\x04host\x12*\n\x10file_by_filename\x18\x03 \x01(\tH\x00R\x0efileByFilename\x126\n\x16file_containing_symbol\x18\x04 \x01(\tH\x00R\x14fileContainingSymbol\x12g\n\x19file_containing_extension\x18\x05 \x01(\x0b2).grpc.reflection.v1alpha.ExtensionRequestH\x00R\x17fileContainingExtension\x12B\n\x1dall_extension_numbers_of_type\x18\x06 \x01(\tH\x00R\x19allExtensionNumbersOfType\x12%\n\rlist_services\x18\x07 \x01(\tH\x00R\x0clistServicesB\x11\n\x0fmessage_request\"f\n\x10ExtensionRequest\x12\'\n\x0fcontaining_type\x18\x01 \x01(\tR\x0econtainingType\x12)\n\x10extension_number\x18\x02 \x01(\x05R\x0fextensionNumber\"\xc7\x04\n\x18ServerReflectionResponse\x12\x1d\n\nvalid_host\x18\x01 \x01(\tR\tvalidHost\x12[\n\x10original_request\x18\x02 \x01(\x0b20.grpc.reflection.v1alpha.ServerReflectionRequestR\x0foriginalRequest\x12k\n\x18file_descriptor_response\x18\x04 \x01(\x0b2/.grpc.reflection.v1alpha.FileDescriptorResponseH\x00R\x16fileDescriptorResponse\x12w\n\x1eall_extension_numbers_response\x18\x05 \x01(\x0b20.grpc.reflection.v1alpha.ExtensionNumberResponseH\x00R\x1ballExtensionNumbersResponse\x12d\n\x16list_services_response\x18\x06 \x01(\x0b2,.grpc.reflection.v1alpha.ListServiceResponseH\x00R\x14listServicesResponse\x12O\n\x0eerror_response\x18\x07 \x01(\x0b2&.grpc.reflection.v1alpha.ErrorResponseH\x00R\rerrorResponseB\x12\n\x10message_response\"L\n\x16FileDescriptorResponse\x122\n\x15file_descriptor_proto\x18\x01 \x03(\x0cR\x13fileDescriptorProto\"j\n\x17ExtensionNumberResponse\x12$\n\x0ebase_type_name\x18\x01 \x01(\tR\x0cbaseTypeName\x12)\n\x10extension_number\x18\x02 \x03(\x05R\x0fextensionNumber\"Y\n\x13ListServiceResponse\x12B\n\x07service\x18\x01 \x03(\x0b2(.grpc.reflection.v1alpha.ServiceResponseR\x07service\"%\n\x0fServiceResponse\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\"S\n\rErrorResponse\x12\x1d\n\nerror_code\x18\x01 \x01(\x05R\terrorCode\x12#\n\rerror_message\x18\x02 \x01(\tR\x0cerrorMessage2\x93\x01\n\x10ServerReflection\x12\x7f\n\x14ServerReflectionInfo\x120.grpc.reflection.v1alpha.ServerReflectionRequest\x1a1.grpc.reflection.v1alpha.ServerReflectionResponse(\x010\x01b\x06proto3", ];
+builder = builder.add_file_descriptors(FILE_DESCRIPTORS);
+builder.build()
+}
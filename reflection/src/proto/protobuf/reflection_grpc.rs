@@ -0,0 +1,105 @@
+// This file is generated. Do not edit
+// @generated
+
+// https://github.com/Manishearth/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+#![allow(box_pointers)]
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unsafe_code)]
+#![allow(unused_imports)]
+#![allow(unused_results)]
+
+const FILE_DESCRIPTORS: &[&[u8]] = &[
+    b"\n(grpc/reflection/v1alpha/reflection.proto\x12\x17grpc.reflection.v1alpha\"\xf8\x02\n\x17ServerReflectionRequest\x12\x12\n\x04host\x18\x01 \x01(\tR\x04host\x12*\n\x10file_by_filename\x18\x03 \x01(\tH\x00R\x0efileByFilename\x126\n\x16file_containing_symbol\x18\x04 \x01(\tH\x00R\x14fileContainingSymbol\x12g\n\x19file_containing_extension\x18\x05 \x01(\x0b2).grpc.reflection.v1alpha.ExtensionRequestH\x00R\x17fileContainingExtension\x12B\n\x1dall_extension_numbers_of_type\x18\x06 \x01(\tH\x00R\x19allExtensionNumbersOfType\x12%\n\rlist_services\x18\x07 \x01(\tH\x00R\x0clistServicesB\x11\n\x0fmessage_request\"f\n\x10ExtensionRequest\x12\'\n\x0fcontaining_type\x18\x01 \x01(\tR\x0econtainingType\x12)\n\x10extension_number\x18\x02 \x01(\x05R\x0fextensionNumber\"\xc7\x04\n\x18ServerReflectionResponse\x12\x1d\n\nvalid_host\x18\x01 \x01(\tR\tvalidHost\x12[\n\x10original_request\x18\x02 \x01(\x0b20.grpc.reflection.v1alpha.ServerReflectionRequestR\x0foriginalRequest\x12k\n\x18file_descriptor_response\x18\x04 \x01(\x0b2/.grpc.reflection.v1alpha.FileDescriptorResponseH\x00R\x16fileDescriptorResponse\x12w\n\x1eall_extension_numbers_response\x18\x05 \x01(\x0b20.grpc.reflection.v1alpha.ExtensionNumberResponseH\x00R\x1ballExtensionNumbersResponse\x12d\n\x16list_services_response\x18\x06 \x01(\x0b2,.grpc.reflection.v1alpha.ListServiceResponseH\x00R\x14listServicesResponse\x12O\n\x0eerror_response\x18\x07 \x01(\x0b2&.grpc.reflection.v1alpha.ErrorResponseH\x00R\rerrorResponseB\x12\n\x10message_response\"L\n\x16FileDescriptorResponse\x122\n\x15file_descriptor_proto\x18\x01 \x03(\x0cR\x13fileDescriptorProto\"j\n\x17ExtensionNumberResponse\x12$\n\x0ebase_type_name\x18\x01 \x01(\tR\x0cbaseTypeName\x12)\n\x10extension_number\x18\x02 \x03(\x05R\x0fextensionNumber\"Y\n\x13ListServiceResponse\x12B\n\x07service\x18\x01 \x03(\x0b2(.grpc.reflection.v1alpha.ServiceResponseR\x07service\"%\n\x0fServiceResponse\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\"S\n\rErrorResponse\x12\x1d\n\nerror_code\x18\x01 \x01(\x05R\terrorCode\x12#\n\rerror_message\x18\x02 \x01(\tR\x0cerrorMessage2\x93\x01\n\x10ServerReflection\x12\x7f\n\x14ServerReflectionInfo\x120.grpc.reflection.v1alpha.ServerReflectionRequest\x1a1.grpc.reflection.v1alpha.ServerReflectionResponse(\x010\x01b\x06proto3",
+];
+
+const METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO: ::grpcio::Method<
+    super::reflection::ServerReflectionRequest,
+    super::reflection::ServerReflectionResponse,
+> = ::grpcio::Method {
+    ty: ::grpcio::MethodType::Duplex,
+    name: "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo",
+    req_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+    resp_mar: ::grpcio::Marshaller {
+        ser: ::grpcio::pb_ser,
+        de: ::grpcio::pb_de,
+    },
+};
+
+#[derive(Clone)]
+pub struct ServerReflectionClient {
+    client: ::grpcio::Client,
+}
+
+impl ServerReflectionClient {
+    pub fn new(channel: ::grpcio::Channel) -> Self {
+        ServerReflectionClient {
+            client: ::grpcio::Client::new(channel),
+        }
+    }
+
+    pub fn with_pool(pool: ::grpcio::ChannelPool) -> Self {
+        ServerReflectionClient {
+            client: ::grpcio::Client::with_pool(pool),
+        }
+    }
+
+    pub fn server_reflection_info_opt(
+        &self,
+        opt: ::grpcio::CallOption,
+    ) -> ::grpcio::Result<(
+        ::grpcio::ClientDuplexSender<super::reflection::ServerReflectionRequest>,
+        ::grpcio::ClientDuplexReceiver<super::reflection::ServerReflectionResponse>,
+    )> {
+        self.client
+            .duplex_streaming(&METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO, opt)
+    }
+
+    pub fn server_reflection_info(
+        &self,
+    ) -> ::grpcio::Result<(
+        ::grpcio::ClientDuplexSender<super::reflection::ServerReflectionRequest>,
+        ::grpcio::ClientDuplexReceiver<super::reflection::ServerReflectionResponse>,
+    )> {
+        self.server_reflection_info_opt(::grpcio::CallOption::default())
+    }
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: ::futures::Future<Output = ()> + Send + 'static,
+    {
+        self.client.spawn(f)
+    }
+}
+
+pub trait ServerReflection {
+    fn server_reflection_info(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        _stream: ::grpcio::RequestStream<super::reflection::ServerReflectionRequest>,
+        sink: ::grpcio::DuplexSink<super::reflection::ServerReflectionResponse>,
+    ) {
+        grpcio::unimplemented_call!(ctx, sink)
+    }
+}
+
+pub fn create_server_reflection<S: ServerReflection + Send + Clone + 'static>(
+    s: S,
+) -> ::grpcio::Service {
+    let mut builder = ::grpcio::ServiceBuilder::new();
+    let mut instance = s;
+    builder = builder.add_duplex_streaming_handler(
+        &METHOD_SERVER_REFLECTION_SERVER_REFLECTION_INFO,
+        move |ctx, req, resp| instance.server_reflection_info(ctx, req, resp),
+    );
+    builder = builder.add_file_descriptors(FILE_DESCRIPTORS);
+    builder.build()
+}
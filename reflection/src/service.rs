@@ -0,0 +1,261 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use crate::descriptor::DescriptorPool;
+use crate::proto::{
+    create_server_reflection, ServerReflection, ServerReflectionRequest, ServerReflectionResponse,
+};
+use futures::{FutureExt, SinkExt, TryStreamExt};
+use grpcio::{DuplexSink, RequestStream, RpcContext, RpcStatusCode, ServerBuilder, WriteFlags};
+use log::{debug, warn};
+use std::sync::Arc;
+
+const SERVICE_NAME: &str = "grpc.reflection.v1alpha.ServerReflection";
+
+/// A request of the reflection service, independent of the codec.
+enum Query<'a> {
+    FileByFilename(&'a str),
+    FileContainingSymbol(&'a str),
+    FileContainingExtension(&'a str, i32),
+    AllExtensionNumbersOfType(&'a str),
+    ListServices,
+}
+
+#[cfg(feature = "prost-codec")]
+mod codec {
+    use super::Query;
+    use crate::proto::server_reflection_request::MessageRequest;
+    pub use crate::proto::server_reflection_response::MessageResponse;
+    use crate::proto::{
+        ErrorResponse, ExtensionNumberResponse, FileDescriptorResponse, ListServiceResponse,
+        ServerReflectionRequest, ServerReflectionResponse, ServiceResponse,
+    };
+    use grpcio::RpcStatusCode;
+
+    pub fn query(req: &ServerReflectionRequest) -> Option<Query<'_>> {
+        Some(match req.message_request.as_ref()? {
+            MessageRequest::FileByFilename(name) => Query::FileByFilename(name),
+            MessageRequest::FileContainingSymbol(symbol) => Query::FileContainingSymbol(symbol),
+            MessageRequest::FileContainingExtension(ext) => {
+                Query::FileContainingExtension(&ext.containing_type, ext.extension_number)
+            }
+            MessageRequest::AllExtensionNumbersOfType(ty) => Query::AllExtensionNumbersOfType(ty),
+            MessageRequest::ListServices(_) => Query::ListServices,
+        })
+    }
+
+    pub fn file_response(files: Vec<&'static [u8]>) -> MessageResponse {
+        MessageResponse::FileDescriptorResponse(FileDescriptorResponse {
+            file_descriptor_proto: files.into_iter().map(|f| f.to_vec()).collect(),
+        })
+    }
+
+    pub fn extension_numbers_response(ty: &str, numbers: Vec<i32>) -> MessageResponse {
+        MessageResponse::AllExtensionNumbersResponse(ExtensionNumberResponse {
+            base_type_name: ty.to_owned(),
+            extension_number: numbers,
+        })
+    }
+
+    pub fn list_services_response(services: &[String]) -> MessageResponse {
+        MessageResponse::ListServicesResponse(ListServiceResponse {
+            service: services
+                .iter()
+                .map(|name| ServiceResponse { name: name.clone() })
+                .collect(),
+        })
+    }
+
+    pub fn error_response(code: RpcStatusCode, message: &str) -> MessageResponse {
+        MessageResponse::ErrorResponse(ErrorResponse {
+            error_code: code.into(),
+            error_message: message.to_owned(),
+        })
+    }
+
+    pub fn response(
+        req: ServerReflectionRequest,
+        resp: MessageResponse,
+    ) -> ServerReflectionResponse {
+        ServerReflectionResponse {
+            valid_host: req.host.clone(),
+            original_request: Some(req),
+            message_response: Some(resp),
+        }
+    }
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod codec {
+    use super::Query;
+    pub use crate::proto::ServerReflectionResponse_oneof_message_response as MessageResponse;
+    use crate::proto::{
+        ErrorResponse, ExtensionNumberResponse, FileDescriptorResponse, ListServiceResponse,
+        ServerReflectionRequest, ServerReflectionRequest_oneof_message_request as MessageRequest,
+        ServerReflectionResponse, ServiceResponse,
+    };
+    use grpcio::RpcStatusCode;
+
+    pub fn query(req: &ServerReflectionRequest) -> Option<Query<'_>> {
+        Some(match req.message_request.as_ref()? {
+            MessageRequest::file_by_filename(name) => Query::FileByFilename(name),
+            MessageRequest::file_containing_symbol(symbol) => Query::FileContainingSymbol(symbol),
+            MessageRequest::file_containing_extension(ext) => {
+                Query::FileContainingExtension(&ext.containing_type, ext.extension_number)
+            }
+            MessageRequest::all_extension_numbers_of_type(ty) => {
+                Query::AllExtensionNumbersOfType(ty)
+            }
+            MessageRequest::list_services(_) => Query::ListServices,
+        })
+    }
+
+    pub fn file_response(files: Vec<&'static [u8]>) -> MessageResponse {
+        MessageResponse::file_descriptor_response(FileDescriptorResponse {
+            file_descriptor_proto: files.into_iter().map(|f| f.to_vec()).collect(),
+            ..Default::default()
+        })
+    }
+
+    pub fn extension_numbers_response(ty: &str, numbers: Vec<i32>) -> MessageResponse {
+        MessageResponse::all_extension_numbers_response(ExtensionNumberResponse {
+            base_type_name: ty.to_owned(),
+            extension_number: numbers,
+            ..Default::default()
+        })
+    }
+
+    pub fn list_services_response(services: &[String]) -> MessageResponse {
+        MessageResponse::list_services_response(ListServiceResponse {
+            service: services
+                .iter()
+                .map(|name| ServiceResponse {
+                    name: name.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    pub fn error_response(code: RpcStatusCode, message: &str) -> MessageResponse {
+        MessageResponse::error_response(ErrorResponse {
+            error_code: code.into(),
+            error_message: message.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    pub fn response(
+        req: ServerReflectionRequest,
+        resp: MessageResponse,
+    ) -> ServerReflectionResponse {
+        ServerReflectionResponse {
+            valid_host: req.host.clone(),
+            original_request: Some(req).into(),
+            message_response: Some(resp),
+            ..Default::default()
+        }
+    }
+}
+
+use self::codec::{
+    error_response, extension_numbers_response, file_response, list_services_response,
+    MessageResponse,
+};
+
+/// An implementation of the `ServerReflection` service, which describes the
+/// services registered before it's created.
+#[derive(Clone)]
+pub struct ReflectionService {
+    pool: Arc<DescriptorPool>,
+    services: Arc<Vec<String>>,
+}
+
+impl ReflectionService {
+    /// Creates a reflection service for the services registered to `builder`,
+    /// including itself.
+    ///
+    /// Services without file descriptors, like the ones generated by an older
+    /// compiler, are still listed, but their definitions can't be queried.
+    pub fn new(builder: &ServerBuilder) -> ReflectionService {
+        // The reflection service describes itself too.
+        let reflection = create_server_reflection(ReflectionService {
+            pool: Arc::default(),
+            services: Arc::default(),
+        });
+        let mut pool = DescriptorPool::default();
+        for data in builder
+            .file_descriptors()
+            .iter()
+            .chain(reflection.file_descriptors())
+        {
+            if let Err(e) = pool.add_file(data) {
+                warn!("reflection: ignore invalid file descriptor: {}", e);
+            }
+        }
+        let mut services: Vec<_> = builder
+            .service_names()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect();
+        if !services.iter().any(|s| s == SERVICE_NAME) {
+            services.push(SERVICE_NAME.to_owned());
+        }
+        ReflectionService {
+            pool: Arc::new(pool),
+            services: Arc::new(services),
+        }
+    }
+
+    fn respond(&self, query: Query<'_>) -> MessageResponse {
+        let (files, not_found) = match query {
+            Query::FileByFilename(name) => (self.pool.file_by_filename(name), "File not found."),
+            Query::FileContainingSymbol(symbol) => (
+                self.pool.file_containing_symbol(symbol),
+                "Symbol not found.",
+            ),
+            Query::FileContainingExtension(ty, number) => (
+                self.pool.file_containing_extension(ty, number),
+                "Extension not found.",
+            ),
+            Query::AllExtensionNumbersOfType(ty) => {
+                return match self.pool.extension_numbers(ty) {
+                    Some(numbers) => extension_numbers_response(ty, numbers),
+                    None => error_response(RpcStatusCode::NOT_FOUND, "Type not found."),
+                };
+            }
+            Query::ListServices => return list_services_response(&self.services),
+        };
+        match files {
+            Some(files) => file_response(files),
+            None => error_response(RpcStatusCode::NOT_FOUND, not_found),
+        }
+    }
+}
+
+impl ServerReflection for ReflectionService {
+    fn server_reflection_info(
+        &mut self,
+        ctx: RpcContext,
+        mut stream: RequestStream<ServerReflectionRequest>,
+        mut sink: DuplexSink<ServerReflectionResponse>,
+    ) {
+        let service = self.clone();
+        let f = async move {
+            while let Some(req) = stream.try_next().await? {
+                let message_response = match codec::query(&req) {
+                    Some(q) => service.respond(q),
+                    None => error_response(RpcStatusCode::UNIMPLEMENTED, ""),
+                };
+                let resp = codec::response(req, message_response);
+                sink.send((resp, WriteFlags::default())).await?;
+            }
+            sink.close().await
+        };
+        ctx.spawn(f.map(|res: grpcio::Result<()>| {
+            if let Err(e) = res {
+                debug!("reflection: stream is aborted: {:?}", e);
+            }
+        }))
+    }
+}
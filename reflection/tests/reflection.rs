@@ -0,0 +1,222 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use futures::executor::block_on;
+use futures::prelude::*;
+use grpcio::*;
+use grpcio_reflection::proto::*;
+use grpcio_reflection::*;
+use std::sync::Arc;
+
+const REFLECTION_FILE: &str = "grpc/reflection/v1alpha/reflection.proto";
+
+/// The answers of the reflection service, independent of the codec.
+#[derive(Debug, PartialEq)]
+enum Answer {
+    Files(Vec<Vec<u8>>),
+    ExtensionNumbers(String, Vec<i32>),
+    Services(Vec<String>),
+    Error(i32),
+}
+
+#[cfg(feature = "prost-codec")]
+mod codec {
+    use super::Answer;
+    pub use grpcio_reflection::proto::server_reflection_request::MessageRequest;
+    use grpcio_reflection::proto::server_reflection_response::MessageResponse;
+    use grpcio_reflection::proto::*;
+
+    pub fn file_by_filename(name: &str) -> MessageRequest {
+        MessageRequest::FileByFilename(name.to_owned())
+    }
+
+    pub fn file_containing_symbol(symbol: &str) -> MessageRequest {
+        MessageRequest::FileContainingSymbol(symbol.to_owned())
+    }
+
+    pub fn file_containing_extension(ty: &str, number: i32) -> MessageRequest {
+        MessageRequest::FileContainingExtension(ExtensionRequest {
+            containing_type: ty.to_owned(),
+            extension_number: number,
+        })
+    }
+
+    pub fn all_extension_numbers_of_type(ty: &str) -> MessageRequest {
+        MessageRequest::AllExtensionNumbersOfType(ty.to_owned())
+    }
+
+    pub fn list_services() -> MessageRequest {
+        MessageRequest::ListServices(String::new())
+    }
+
+    pub fn request(r: MessageRequest) -> ServerReflectionRequest {
+        ServerReflectionRequest {
+            host: "localhost".to_owned(),
+            message_request: Some(r),
+        }
+    }
+
+    pub fn answer(resp: ServerReflectionResponse) -> Answer {
+        match resp.message_response.unwrap() {
+            MessageResponse::FileDescriptorResponse(r) => Answer::Files(r.file_descriptor_proto),
+            MessageResponse::AllExtensionNumbersResponse(r) => {
+                Answer::ExtensionNumbers(r.base_type_name, r.extension_number)
+            }
+            MessageResponse::ListServicesResponse(r) => {
+                Answer::Services(r.service.into_iter().map(|s| s.name).collect())
+            }
+            MessageResponse::ErrorResponse(r) => Answer::Error(r.error_code),
+        }
+    }
+}
+
+#[cfg(feature = "protobuf-codec")]
+mod codec {
+    use super::Answer;
+    pub use grpcio_reflection::proto::ServerReflectionRequest_oneof_message_request as MessageRequest;
+    use grpcio_reflection::proto::ServerReflectionResponse_oneof_message_response as MessageResponse;
+    use grpcio_reflection::proto::*;
+
+    pub fn file_by_filename(name: &str) -> MessageRequest {
+        MessageRequest::file_by_filename(name.to_owned())
+    }
+
+    pub fn file_containing_symbol(symbol: &str) -> MessageRequest {
+        MessageRequest::file_containing_symbol(symbol.to_owned())
+    }
+
+    pub fn file_containing_extension(ty: &str, number: i32) -> MessageRequest {
+        MessageRequest::file_containing_extension(ExtensionRequest {
+            containing_type: ty.to_owned(),
+            extension_number: number,
+            ..Default::default()
+        })
+    }
+
+    pub fn all_extension_numbers_of_type(ty: &str) -> MessageRequest {
+        MessageRequest::all_extension_numbers_of_type(ty.to_owned())
+    }
+
+    pub fn list_services() -> MessageRequest {
+        MessageRequest::list_services(String::new())
+    }
+
+    pub fn request(r: MessageRequest) -> ServerReflectionRequest {
+        ServerReflectionRequest {
+            host: "localhost".to_owned(),
+            message_request: Some(r),
+            ..Default::default()
+        }
+    }
+
+    pub fn answer(resp: ServerReflectionResponse) -> Answer {
+        match resp.message_response.unwrap() {
+            MessageResponse::file_descriptor_response(r) => {
+                Answer::Files(r.file_descriptor_proto.into_vec())
+            }
+            MessageResponse::all_extension_numbers_response(r) => {
+                Answer::ExtensionNumbers(r.base_type_name, r.extension_number)
+            }
+            MessageResponse::list_services_response(r) => {
+                Answer::Services(r.service.into_iter().map(|s| s.name).collect())
+            }
+            MessageResponse::error_response(r) => Answer::Error(r.error_code),
+        }
+    }
+}
+
+use codec::*;
+
+fn setup() -> (Server, ServerReflectionClient) {
+    let env = Arc::new(Environment::new(1));
+    let builder = ServerBuilder::new(env.clone());
+    let service = ReflectionService::new(&builder);
+    let mut server = builder
+        .register_service(create_server_reflection(service))
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let (_, port) = server.bind_addrs().next().unwrap();
+
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    (server, ServerReflectionClient::new(ch))
+}
+
+fn query(client: &ServerReflectionClient, reqs: Vec<MessageRequest>) -> Vec<Answer> {
+    let (mut tx, rx) = client.server_reflection_info().unwrap();
+    block_on(async move {
+        for r in reqs {
+            tx.send((request(r), WriteFlags::default())).await.unwrap();
+        }
+        tx.close().await.unwrap();
+        rx.map_ok(|resp| {
+            assert_eq!(resp.valid_host, "localhost");
+            answer(resp)
+        })
+        .try_collect()
+        .await
+        .unwrap()
+    })
+}
+
+#[track_caller]
+fn assert_file(answer: &Answer) {
+    match answer {
+        Answer::Files(files) => {
+            assert_eq!(files.len(), 1);
+            let name = &files[0][2..2 + REFLECTION_FILE.len()];
+            assert_eq!(name, REFLECTION_FILE.as_bytes());
+        }
+        a => panic!("unexpected answer {:?}", a),
+    }
+}
+
+#[track_caller]
+fn assert_not_found(answer: &Answer) {
+    let code: i32 = RpcStatusCode::NOT_FOUND.into();
+    assert_eq!(*answer, Answer::Error(code));
+}
+
+#[test]
+fn test_list_services() {
+    let (_server, client) = setup();
+    let answers = query(&client, vec![list_services()]);
+    let expected = vec!["grpc.reflection.v1alpha.ServerReflection".to_owned()];
+    assert_eq!(answers, vec![Answer::Services(expected)]);
+}
+
+#[test]
+fn test_file_queries() {
+    let (_server, client) = setup();
+    let answers = query(
+        &client,
+        vec![
+            file_by_filename(REFLECTION_FILE),
+            file_containing_symbol("grpc.reflection.v1alpha.ServerReflection"),
+            file_containing_symbol("grpc.reflection.v1alpha.ServerReflection.ServerReflectionInfo"),
+            file_containing_symbol("grpc.reflection.v1alpha.ServerReflectionRequest.host"),
+            file_by_filename("unknown.proto"),
+            file_containing_symbol("grpc.reflection.v1alpha.Unknown"),
+            file_containing_extension("grpc.reflection.v1alpha.ServerReflectionRequest", 100),
+            all_extension_numbers_of_type("grpc.reflection.v1alpha.Unknown"),
+        ],
+    );
+    assert_eq!(answers.len(), 8);
+    for answer in &answers[..4] {
+        assert_file(answer);
+    }
+    for answer in &answers[4..] {
+        assert_not_found(answer);
+    }
+}
+
+#[test]
+fn test_extension_numbers() {
+    let (_server, client) = setup();
+    let ty = "grpc.reflection.v1alpha.ServerReflectionRequest";
+    let answers = query(&client, vec![all_extension_numbers_of_type(ty)]);
+    assert_eq!(
+        answers,
+        vec![Answer::ExtensionNumbers(ty.to_owned(), vec![])]
+    );
+}
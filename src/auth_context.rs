@@ -3,6 +3,9 @@
 
 use std::ffi::CStr;
 use std::marker::PhantomData;
+#[cfg(feature = "secure")]
+use std::mem::ManuallyDrop;
+use std::net::IpAddr;
 use std::ptr::NonNull;
 
 use crate::grpc_sys::{
@@ -29,10 +32,31 @@ pub struct AuthContext {
 
 /// Binding to gRPC Core AuthContext
 impl AuthContext {
+    /// Collect the well-known properties into a [`PeerIdentity`].
+    pub fn peer_identity_info(&self) -> PeerIdentity {
+        PeerIdentity::from_properties(
+            self.into_iter().map(|p| (p.name(), p.value())),
+            self.peer_is_authenticated(),
+        )
+    }
+
     pub(crate) unsafe fn from_call_ptr(call: *mut grpc_call) -> Option<Self> {
         NonNull::new(grpc_sys::grpc_call_auth_context(call)).map(|ctx| AuthContext { ctx })
     }
 
+    /// Wraps a context that is owned by gRPC Core, so it must not be dropped.
+    #[cfg(feature = "secure")]
+    pub(crate) unsafe fn from_raw_borrowed(
+        ctx: *mut grpc_auth_context,
+    ) -> Option<ManuallyDrop<Self>> {
+        NonNull::new(ctx).map(|ctx| ManuallyDrop::new(AuthContext { ctx }))
+    }
+
+    #[cfg(feature = "secure")]
+    pub(crate) fn as_mut_ptr(&self) -> *mut grpc_auth_context {
+        self.ctx.as_ptr()
+    }
+
     /// The name of the property gRPC Core has chosen as main peer identity property,
     /// if any.
     pub fn peer_identity_property_name(&self) -> Option<&str> {
@@ -137,3 +161,170 @@ impl<'a> AuthProperty<'a> {
         std::str::from_utf8(self.value())
     }
 }
+
+/// The level of protection of a transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecurityLevel {
+    /// No protection.
+    None,
+    /// The integrity of the data is protected.
+    IntegrityOnly,
+    /// The data is encrypted and its integrity is protected.
+    PrivacyAndIntegrity,
+}
+
+impl SecurityLevel {
+    fn from_property(value: &str) -> Option<SecurityLevel> {
+        match value {
+            "TSI_SECURITY_NONE" => Some(SecurityLevel::None),
+            "TSI_INTEGRITY_ONLY" => Some(SecurityLevel::IntegrityOnly),
+            "TSI_PRIVACY_AND_INTEGRITY" => Some(SecurityLevel::PrivacyAndIntegrity),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "secure")]
+    pub(crate) fn to_native(self) -> grpc_sys::grpc_security_level {
+        match self {
+            SecurityLevel::None => grpc_sys::grpc_security_level::GRPC_SECURITY_MIN,
+            SecurityLevel::IntegrityOnly => grpc_sys::grpc_security_level::GRPC_INTEGRITY_ONLY,
+            SecurityLevel::PrivacyAndIntegrity => {
+                grpc_sys::grpc_security_level::GRPC_PRIVACY_AND_INTEGRITY
+            }
+        }
+    }
+}
+
+/// The identity of a peer, which is parsed from the properties of an [`AuthContext`].
+///
+/// The fields are empty if the transport doesn't provide them, for example the x509
+/// related fields are only available when the peer presents a certificate.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerIdentity {
+    authenticated: bool,
+    transport_security_type: Option<String>,
+    security_level: Option<SecurityLevel>,
+    common_name: Option<String>,
+    subject: Option<String>,
+    dns_sans: Vec<String>,
+    uri_sans: Vec<String>,
+    ip_sans: Vec<IpAddr>,
+    spiffe_id: Option<String>,
+    pem_cert: Option<String>,
+    pem_cert_chain: Option<String>,
+    properties: Vec<(String, Vec<u8>)>,
+}
+
+impl PeerIdentity {
+    pub(crate) fn from_properties<'a>(
+        properties: impl Iterator<Item = (&'a str, &'a [u8])>,
+        authenticated: bool,
+    ) -> PeerIdentity {
+        let mut id = PeerIdentity {
+            authenticated,
+            ..Default::default()
+        };
+        for (name, value) in properties {
+            let s = String::from_utf8_lossy(value).into_owned();
+            match name {
+                "transport_security_type" => id.transport_security_type = Some(s),
+                "security_level" => id.security_level = SecurityLevel::from_property(&s),
+                "x509_common_name" => id.common_name = Some(s),
+                "x509_subject_alternative_name" => {
+                    // DNS names can't contain colons, but URIs always do.
+                    if let Ok(ip) = s.parse::<IpAddr>() {
+                        id.ip_sans.push(ip);
+                    } else if s.contains(':') {
+                        id.uri_sans.push(s);
+                    } else {
+                        id.dns_sans.push(s);
+                    }
+                }
+                "peer_spiffe_id" => id.spiffe_id = Some(s),
+                "x509_pem_cert" => id.pem_cert = Some(s),
+                "x509_pem_cert_chain" => id.pem_cert_chain = Some(s),
+                _ => {}
+            }
+            id.properties.push((name.to_owned(), value.to_vec()));
+        }
+        #[cfg(feature = "secure")]
+        {
+            id.subject = id
+                .pem_cert
+                .as_ref()
+                .and_then(|cert| crate::security::cert_subject(cert.as_bytes()).ok());
+        }
+        if id.spiffe_id.is_none() {
+            let mut ids = id.uri_sans.iter().filter(|u| u.starts_with("spiffe://"));
+            if let (Some(spiffe_id), None) = (ids.next(), ids.next()) {
+                id.spiffe_id = Some(spiffe_id.clone());
+            }
+        }
+        id
+    }
+
+    /// `true` if the peer is authenticated by the transport.
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// The type of the transport security, like `ssl`, `local` or `insecure`.
+    pub fn transport_security_type(&self) -> Option<&str> {
+        self.transport_security_type.as_deref()
+    }
+
+    /// The level of protection of the transport.
+    pub fn security_level(&self) -> Option<SecurityLevel> {
+        self.security_level
+    }
+
+    /// The common name of the subject of the peer certificate.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// The subject of the peer certificate in the RFC 4514 form, like `CN=foo,O=bar`.
+    ///
+    /// It's parsed from the PEM encoded certificate, so it's only available if
+    /// `secure` feature is enabled.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// The DNS names in the subject alternative names of the peer certificate.
+    pub fn dns_sans(&self) -> &[String] {
+        &self.dns_sans
+    }
+
+    /// The URIs in the subject alternative names of the peer certificate.
+    pub fn uri_sans(&self) -> &[String] {
+        &self.uri_sans
+    }
+
+    /// The IP addresses in the subject alternative names of the peer certificate.
+    pub fn ip_sans(&self) -> &[IpAddr] {
+        &self.ip_sans
+    }
+
+    /// The SPIFFE ID of the peer, which is the only `spiffe://` URI in the subject
+    /// alternative names of the peer certificate.
+    pub fn spiffe_id(&self) -> Option<&str> {
+        self.spiffe_id.as_deref()
+    }
+
+    /// The PEM encoded peer certificate.
+    pub fn pem_cert(&self) -> Option<&str> {
+        self.pem_cert.as_deref()
+    }
+
+    /// The PEM encoded certificate chain of the peer, starting from the peer
+    /// certificate.
+    pub fn pem_cert_chain(&self) -> Option<&str> {
+        self.pem_cert_chain.as_deref()
+    }
+
+    /// All the properties of the auth context, including the ones above.
+    pub fn properties(&self) -> &[(String, Vec<u8>)] {
+        &self.properties
+    }
+}
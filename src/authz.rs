@@ -0,0 +1,605 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Declarative authorization of calls by method, peer identity and metadata.
+//!
+//! The policy follows the [gRPC authorization policy] format.
+//!
+//! [gRPC authorization policy]: https://github.com/grpc/proposal/blob/master/A43-grpc-authorization-api.md
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io};
+
+use crate::auth_context::PeerIdentity;
+use crate::call::server::RpcContext;
+use crate::call::{RpcStatus, RpcStatusCode};
+use crate::error::{Error, Result};
+use crate::json::{self, Value};
+use crate::metadata::Metadata;
+use crate::reloadable::Reloadable;
+use crate::server::{CheckResult, ServerChecker};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+fn invalid<T>(msg: impl Into<String>) -> Result<T> {
+    Err(Error::InvalidAuthorizationPolicy(msg.into()))
+}
+
+fn check_pattern(field: &str, pattern: &str) -> Result<()> {
+    if pattern.is_empty() {
+        return invalid(format!("{}: empty pattern", field));
+    }
+    let trimmed = pattern.strip_prefix('*').unwrap_or(pattern);
+    let trimmed = trimmed.strip_suffix('*').unwrap_or(trimmed);
+    if trimmed.contains('*') || (pattern.len() > 1 && trimmed.len() + 2 == pattern.len()) {
+        return invalid(format!(
+            "{}: {:?} can only have a wildcard as prefix, suffix or the whole pattern",
+            field, pattern
+        ));
+    }
+    Ok(())
+}
+
+/// Matches `value` against a pattern, which is either exact, `prefix*`,
+/// `*suffix` or `*`.
+fn matches(pattern: &str, value: &str) -> bool {
+    if pattern == "*" {
+        true
+    } else if let Some(prefix) = pattern.strip_suffix('*') {
+        value.starts_with(prefix)
+    } else if let Some(suffix) = pattern.strip_prefix('*') {
+        value.ends_with(suffix)
+    } else {
+        pattern == value
+    }
+}
+
+/// A rule that matches calls. A call matches if it matches every condition set
+/// in the rule.
+#[derive(Clone, Debug)]
+pub struct AuthorizationRule {
+    name: String,
+    principals: Vec<String>,
+    paths: Vec<String>,
+    headers: Vec<(String, Vec<String>)>,
+}
+
+impl AuthorizationRule {
+    /// Create a rule that matches all calls.
+    pub fn new<S: Into<String>>(name: S) -> AuthorizationRule {
+        AuthorizationRule {
+            name: name.into(),
+            principals: vec![],
+            paths: vec![],
+            headers: vec![],
+        }
+    }
+
+    /// Match calls from a peer that has any of the principals.
+    ///
+    /// The principals of a peer are its SPIFFE ID and, following gRFC A43, the URI
+    /// SANs of the certificate it presents, or the DNS SANs if there is no URI SAN,
+    /// or the subject if there is no SAN at all. `*` matches any authenticated peer.
+    pub fn principals<S: Into<String>>(mut self, principals: Vec<S>) -> AuthorizationRule {
+        self.principals = principals.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Match calls to any of the paths, which are fully-qualified methods like
+    /// `/package.Service/Method`.
+    pub fn paths<S: Into<String>>(mut self, paths: Vec<S>) -> AuthorizationRule {
+        self.paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Match calls that carry the metadata `key` with any of the values.
+    ///
+    /// It can be called multiple times, in which case all the metadata must match.
+    pub fn header<K: Into<String>, V: Into<String>>(
+        mut self,
+        key: K,
+        values: Vec<V>,
+    ) -> AuthorizationRule {
+        let values = values.into_iter().map(Into::into).collect();
+        self.headers.push((key.into().to_ascii_lowercase(), values));
+        self
+    }
+
+    /// Get the name of the rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return invalid("rule: name must not be empty");
+        }
+        let field = |f: &str| format!("rule {}: {}", self.name, f);
+        for p in &self.principals {
+            check_pattern(&field("principals"), p)?;
+        }
+        for p in &self.paths {
+            check_pattern(&field("paths"), p)?;
+        }
+        for (key, values) in &self.headers {
+            if key.is_empty()
+                || key.starts_with(':')
+                || key.starts_with("grpc-")
+                || key == "host"
+                || key.ends_with("-bin")
+            {
+                return invalid(format!("{}: unsupported key {:?}", field("headers"), key));
+            }
+            if values.is_empty() {
+                return invalid(format!("{}: {} has no values", field("headers"), key));
+            }
+            for v in values {
+                check_pattern(&field("headers"), v)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&self, path: &str, principals: Option<&[&str]>, headers: &Metadata) -> bool {
+        if !self.principals.is_empty() {
+            let principals = match principals {
+                Some(p) => p,
+                None => return false,
+            };
+            let matched = self
+                .principals
+                .iter()
+                .any(|pattern| pattern == "*" || principals.iter().any(|p| matches(pattern, p)));
+            if !matched {
+                return false;
+            }
+        }
+        if !self.paths.is_empty() && !self.paths.iter().any(|p| matches(p, path)) {
+            return false;
+        }
+        self.headers.iter().all(|(key, patterns)| {
+            headers
+                .iter()
+                .filter(|(k, _)| k == key)
+                .filter_map(|(_, v)| std::str::from_utf8(v).ok())
+                .any(|v| patterns.iter().any(|p| matches(p, v)))
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<AuthorizationRule> {
+        let mut rule = AuthorizationRule::new(String::new());
+        for (key, value) in object(value, "rule")? {
+            match key.as_str() {
+                "name" => rule.name = string(value, "rule: name")?.to_owned(),
+                "source" => {
+                    for (key, value) in object(value, "rule: source")? {
+                        match key.as_str() {
+                            "principals" => rule.principals = strings(value, "principals")?,
+                            _ => return invalid(format!("rule: unknown field source.{}", key)),
+                        }
+                    }
+                }
+                "request" => {
+                    for (key, value) in object(value, "rule: request")? {
+                        match key.as_str() {
+                            "paths" => rule.paths = strings(value, "paths")?,
+                            "headers" => {
+                                for header in array(value, "headers")? {
+                                    let mut k = None;
+                                    let mut values = None;
+                                    for (key, value) in object(header, "headers")? {
+                                        match key.as_str() {
+                                            "key" => k = Some(string(value, "headers: key")?),
+                                            "values" => values = Some(strings(value, "headers")?),
+                                            _ => {
+                                                return invalid(format!(
+                                                    "rule: unknown field headers.{}",
+                                                    key
+                                                ))
+                                            }
+                                        }
+                                    }
+                                    match (k, values) {
+                                        (Some(k), Some(values)) => rule = rule.header(k, values),
+                                        _ => {
+                                            return invalid("headers: key and values are required")
+                                        }
+                                    }
+                                }
+                            }
+                            _ => return invalid(format!("rule: unknown field request.{}", key)),
+                        }
+                    }
+                }
+                _ => return invalid(format!("rule: unknown field {}", key)),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+fn object<'a>(value: &'a Value, field: &str) -> Result<&'a [(String, Value)]> {
+    match value.as_object() {
+        Some(o) => Ok(o),
+        None => invalid(format!("{} must be an object", field)),
+    }
+}
+
+fn array<'a>(value: &'a Value, field: &str) -> Result<&'a [Value]> {
+    match value.as_array() {
+        Some(a) => Ok(a),
+        None => invalid(format!("{} must be an array", field)),
+    }
+}
+
+fn string<'a>(value: &'a Value, field: &str) -> Result<&'a str> {
+    match value.as_str() {
+        Some(s) => Ok(s),
+        None => invalid(format!("{} must be a string", field)),
+    }
+}
+
+fn strings(value: &Value, field: &str) -> Result<Vec<String>> {
+    array(value, field)?
+        .iter()
+        .map(|v| string(v, field).map(ToOwned::to_owned))
+        .collect()
+}
+
+/// An authorization policy.
+///
+/// A call is denied if it matches any of the deny rules. Otherwise it's allowed
+/// if it matches any of the allow rules, and denied if it matches none.
+#[derive(Clone, Debug)]
+pub struct AuthorizationPolicy {
+    name: String,
+    deny_rules: Vec<AuthorizationRule>,
+    allow_rules: Vec<AuthorizationRule>,
+}
+
+impl AuthorizationPolicy {
+    /// Create a policy that denies all calls.
+    pub fn new<S: Into<String>>(name: S) -> AuthorizationPolicy {
+        AuthorizationPolicy {
+            name: name.into(),
+            deny_rules: vec![],
+            allow_rules: vec![],
+        }
+    }
+
+    /// Add a rule to allow calls.
+    pub fn allow(mut self, rule: AuthorizationRule) -> AuthorizationPolicy {
+        self.allow_rules.push(rule);
+        self
+    }
+
+    /// Add a rule to deny calls, which takes precedence over the allow rules.
+    pub fn deny(mut self, rule: AuthorizationRule) -> AuthorizationPolicy {
+        self.deny_rules.push(rule);
+        self
+    }
+
+    /// Parse a policy in the JSON format of gRPC authorization policy.
+    pub fn from_json(json: &str) -> Result<AuthorizationPolicy> {
+        let value = match json::parse(json) {
+            Ok(v) => v,
+            Err(e) => return invalid(format!("invalid JSON: {}", e)),
+        };
+        let mut policy = AuthorizationPolicy::new(String::new());
+        let mut has_allow_rules = false;
+        for (key, value) in object(&value, "policy")? {
+            match key.as_str() {
+                "name" => policy.name = string(value, "name")?.to_owned(),
+                "allow_rules" => {
+                    has_allow_rules = true;
+                    for rule in array(value, "allow_rules")? {
+                        policy = policy.allow(AuthorizationRule::from_json(rule)?);
+                    }
+                }
+                "deny_rules" => {
+                    for rule in array(value, "deny_rules")? {
+                        policy = policy.deny(AuthorizationRule::from_json(rule)?);
+                    }
+                }
+                _ => return invalid(format!("unknown field {}", key)),
+            }
+        }
+        if !has_allow_rules {
+            return invalid("allow_rules is required");
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Get the name of the policy.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return invalid("name must not be empty");
+        }
+        for rule in self.deny_rules.iter().chain(&self.allow_rules) {
+            rule.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the rule that denies the call, or `None` if no
+    /// allow rule matches.
+    fn evaluate(
+        &self,
+        path: &str,
+        principals: Option<&[&str]>,
+        headers: &Metadata,
+    ) -> std::result::Result<(), Option<&str>> {
+        if let Some(rule) = self
+            .deny_rules
+            .iter()
+            .find(|r| r.matches(path, principals, headers))
+        {
+            return Err(Some(rule.name.as_str()));
+        }
+        if self
+            .allow_rules
+            .iter()
+            .any(|r| r.matches(path, principals, headers))
+        {
+            return Ok(());
+        }
+        Err(None)
+    }
+}
+
+/// Returns the principals of an authenticated peer.
+fn principals(id: &PeerIdentity) -> Option<Vec<&str>> {
+    if !id.is_authenticated() {
+        return None;
+    }
+    let mut principals: Vec<_> = id.spiffe_id().into_iter().collect();
+    let sans = if !id.uri_sans().is_empty() {
+        id.uri_sans()
+    } else {
+        id.dns_sans()
+    };
+    if !sans.is_empty() {
+        let sans = sans.iter().map(|s| s.as_str());
+        principals.extend(sans.filter(|s| Some(*s) != id.spiffe_id()));
+    } else {
+        principals.extend(id.subject());
+    }
+    Some(principals)
+}
+
+fn read_policy(path: &Path) -> Result<AuthorizationPolicy> {
+    let json = fs::read_to_string(path).map_err(|e: io::Error| {
+        Error::InvalidAuthorizationPolicy(format!("failed to read {}: {}", path.display(), e))
+    })?;
+    AuthorizationPolicy::from_json(&json)
+}
+
+/// A [`ServerChecker`] that authorizes calls by an [`AuthorizationPolicy`].
+///
+/// Denied calls fail with `PERMISSION_DENIED`, and an audit line is logged at
+/// info level with the policy, the matched rule, the method and the peer.
+///
+/// Clones share the policy, so it can be replaced by [`AuthorizationChecker::update`]
+/// after the checker is added to a [`ServerBuilder`].
+///
+/// [`ServerBuilder`]: crate::ServerBuilder
+#[derive(Clone)]
+pub struct AuthorizationChecker {
+    policy: Reloadable<AuthorizationPolicy>,
+}
+
+impl AuthorizationChecker {
+    /// Create a checker with the policy.
+    pub fn new(policy: AuthorizationPolicy) -> Result<AuthorizationChecker> {
+        policy.validate()?;
+        Ok(AuthorizationChecker {
+            policy: Reloadable::new(policy),
+        })
+    }
+
+    /// Create a checker with the policy in a JSON file, which is read again
+    /// at most once per `refresh_interval` when calls are checked.
+    ///
+    /// The file is read in the background, so the updated policy applies to the
+    /// calls checked after it's loaded. If the updated file is invalid, the error
+    /// is logged and the policy loaded before remains in use.
+    pub fn from_file(
+        path: impl Into<PathBuf>,
+        refresh_interval: Option<Duration>,
+    ) -> Result<AuthorizationChecker> {
+        let policy = Reloadable::from_file(
+            path.into(),
+            "authorization policy",
+            read_policy,
+            refresh_interval.unwrap_or(DEFAULT_REFRESH_INTERVAL),
+        )?;
+        Ok(AuthorizationChecker { policy })
+    }
+
+    /// Replace the policy.
+    ///
+    /// If the policy is invalid, the current one is not changed.
+    pub fn update(&self, policy: AuthorizationPolicy) -> Result<()> {
+        policy.validate()?;
+        self.policy.set(policy);
+        Ok(())
+    }
+}
+
+impl ServerChecker for AuthorizationChecker {
+    fn check(&mut self, ctx: &RpcContext) -> CheckResult {
+        let policy = self.policy.get();
+        let method = String::from_utf8_lossy(ctx.method());
+        let identity = ctx.peer_identity();
+        let principals = identity.as_ref().and_then(principals);
+        match policy.evaluate(&method, principals.as_deref(), ctx.request_headers()) {
+            Ok(()) => CheckResult::Continue,
+            Err(rule) => {
+                info!(
+                    "authorization denied: policy={}, rule={}, method={}, peer={}, principals={:?}",
+                    policy.name,
+                    rule.unwrap_or("<no allow rule matched>"),
+                    method,
+                    ctx.peer(),
+                    principals.unwrap_or_default(),
+                );
+                CheckResult::Abort(RpcStatus::with_message(
+                    RpcStatusCode::PERMISSION_DENIED,
+                    "unauthorized RPC request rejected".to_owned(),
+                ))
+            }
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn ServerChecker> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetadataBuilder;
+
+    #[test]
+    fn test_pattern() {
+        assert!(matches("*", ""));
+        assert!(matches("/pkg.Svc/*", "/pkg.Svc/Get"));
+        assert!(!matches("/pkg.Svc/*", "/pkg.Other/Get"));
+        assert!(matches("*.example.com", "a.example.com"));
+        assert!(matches("abc", "abc"));
+        assert!(!matches("abc", "abcd"));
+
+        for valid in &["*", "a*", "*a", "a"] {
+            check_pattern("f", valid).unwrap();
+        }
+        for invalid in &["", "**", "*a*", "a*b"] {
+            check_pattern("f", invalid).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_principals() {
+        let identity = |props: &[(&'static str, &'static str)]| {
+            let props = props.iter().map(|(k, v)| (*k, v.as_bytes()));
+            PeerIdentity::from_properties(props, true)
+        };
+        let san = "x509_subject_alternative_name";
+        let id = identity(&[
+            (san, "a.example.org"),
+            (san, "spiffe://example.org/a"),
+            (san, "https://example.org/a"),
+            (san, "10.0.0.1"),
+        ]);
+        assert_eq!(
+            principals(&id).unwrap(),
+            ["spiffe://example.org/a", "https://example.org/a"]
+        );
+        let id = identity(&[(san, "a.example.org"), (san, "b.example.org")]);
+        assert_eq!(principals(&id).unwrap(), ["a.example.org", "b.example.org"]);
+        let id = identity(&[
+            ("peer_spiffe_id", "spiffe://example.org/b"),
+            (san, "b.example.org"),
+        ]);
+        assert_eq!(
+            principals(&id).unwrap(),
+            ["spiffe://example.org/b", "b.example.org"]
+        );
+        let id = PeerIdentity::from_properties(vec![(san, b"a".as_ref())].into_iter(), false);
+        assert_eq!(principals(&id), None);
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = AuthorizationPolicy::from_json(
+            r#"{
+                "name": "test",
+                "deny_rules": [{
+                    "name": "deny_admin",
+                    "request": {"paths": ["/pkg.Admin/*"]}
+                }],
+                "allow_rules": [{
+                    "name": "allow_client",
+                    "source": {"principals": ["spiffe://example.org/*", "client"]},
+                    "request": {
+                        "paths": ["/pkg.Svc/Get"],
+                        "headers": [{"key": "Tenant", "values": ["a", "b*"]}]
+                    }
+                }, {
+                    "name": "allow_health",
+                    "request": {"paths": ["/grpc.health.v1.Health/*"]}
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(policy.name(), "test");
+
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("tenant", "b1").unwrap();
+        let headers = builder.build();
+        let empty = MetadataBuilder::new().build();
+        let client: &[&str] = &["client"];
+        let spiffe: &[&str] = &["spiffe://example.org/ns/a", "other"];
+        let other: &[&str] = &["other"];
+
+        assert_eq!(
+            policy.evaluate("/pkg.Svc/Get", Some(client), &headers),
+            Ok(())
+        );
+        assert_eq!(
+            policy.evaluate("/pkg.Svc/Get", Some(spiffe), &headers),
+            Ok(())
+        );
+        assert_eq!(
+            policy.evaluate("/pkg.Svc/Get", Some(other), &headers),
+            Err(None)
+        );
+        assert_eq!(policy.evaluate("/pkg.Svc/Get", None, &headers), Err(None));
+        assert_eq!(
+            policy.evaluate("/pkg.Svc/Get", Some(client), &empty),
+            Err(None)
+        );
+        assert_eq!(
+            policy.evaluate("/pkg.Svc/Put", Some(client), &headers),
+            Err(None)
+        );
+        assert_eq!(
+            policy.evaluate("/pkg.Admin/Get", Some(client), &headers),
+            Err(Some("deny_admin"))
+        );
+        assert_eq!(
+            policy.evaluate("/grpc.health.v1.Health/Check", None, &empty),
+            Ok(())
+        );
+
+        let any = AuthorizationPolicy::new("any")
+            .allow(AuthorizationRule::new("any").principals(vec!["*"]));
+        any.validate().unwrap();
+        assert_eq!(any.evaluate("/a/b", Some(&[]), &empty), Ok(()));
+        assert_eq!(any.evaluate("/a/b", None, &empty), Err(None));
+
+        for invalid in &[
+            r#"{"name": "p"}"#,
+            r#"{"allow_rules": []}"#,
+            r#"{"name": "p", "allow_rules": [], "audit": true}"#,
+            r#"{"name": "p", "allow_rules": [{"request": {}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "source": {"ns": []}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "request": {"paths": "/a"}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "request": {"paths": ["/a*b"]}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "request": {"headers": [{"key": ":path", "values": ["a"]}]}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "request": {"headers": [{"key": "a", "values": []}]}}]}"#,
+            r#"{"name": "p", "allow_rules": [{"name": "r", "request": {"headers": [{"key": "a"}]}}]}"#,
+            "{",
+        ] {
+            match AuthorizationPolicy::from_json(invalid) {
+                Err(Error::InvalidAuthorizationPolicy(_)) => {}
+                res => panic!("{} should be invalid: {:?}", invalid, res),
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::grpc_sys;
+use futures::future;
 use futures::ready;
 use futures::sink::Sink;
 use futures::stream::Stream;
@@ -19,8 +20,14 @@ use crate::call::{check_run, Call, MessageReader, Method};
 use crate::channel::Channel;
 use crate::codec::{DeserializeFn, SerializeFn};
 use crate::error::{Error, Result};
+use crate::interceptor::ClientCallHook;
 use crate::metadata::Metadata;
-use crate::task::{BatchFuture, BatchType};
+use crate::metrics::ClientCallMetrics;
+#[cfg(feature = "secure")]
+use crate::security::CallCredentials;
+use crate::task::{BatchFuture, BatchResult, BatchType};
+#[cfg(feature = "tracing")]
+use crate::trace::TraceContext;
 
 /// Update the flag bit in res.
 #[inline]
@@ -39,6 +46,10 @@ pub struct CallOption {
     write_flags: WriteFlags,
     call_flags: u32,
     headers: Option<Metadata>,
+    #[cfg(feature = "secure")]
+    call_credentials: Option<CallCredentials>,
+    #[cfg(feature = "tracing")]
+    trace_context: Option<TraceContext>,
 }
 
 impl CallOption {
@@ -99,6 +110,45 @@ impl CallOption {
     pub fn get_headers(&self) -> Option<&Metadata> {
         self.headers.as_ref()
     }
+
+    /// Set the credentials of the call, which are used together with the call
+    /// credentials of the channel.
+    #[cfg(feature = "secure")]
+    pub fn call_credentials(mut self, creds: CallCredentials) -> CallOption {
+        self.call_credentials = Some(creds);
+        self
+    }
+
+    /// Get the credentials of the call.
+    #[cfg(feature = "secure")]
+    pub fn get_call_credentials(&self) -> Option<&CallCredentials> {
+        self.call_credentials.as_ref()
+    }
+
+    /// Set the parent trace context of the call. If it's not set, the parent is
+    /// the call being handled by server in current thread, or a new trace is
+    /// started if there is none.
+    ///
+    /// The trace headers in [`headers`] are replaced by the context of the call.
+    ///
+    /// [`headers`]: CallOption::headers
+    #[cfg(feature = "tracing")]
+    pub fn trace_context(mut self, ctx: TraceContext) -> CallOption {
+        self.trace_context = Some(ctx);
+        self
+    }
+
+    /// Get the parent trace context of the call.
+    #[cfg(feature = "tracing")]
+    pub fn get_trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
+
+    /// Replaces the trace headers with `ctx`.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn inject_trace_context(&mut self, ctx: &TraceContext) {
+        self.headers = Some(crate::trace::inject_headers(self.headers.take(), ctx));
+    }
 }
 
 impl Call {
@@ -108,9 +158,13 @@ impl Call {
         req: &Req,
         mut opt: CallOption,
     ) -> Result<ClientUnaryReceiver<Resp>> {
+        let hook = ClientCallHook::intercept(channel, method.name, method.ty, &mut opt)?;
         let call = channel.create_call(method, &opt)?;
         let mut payload = GrpcSlice::default();
         (method.req_ser())(req, &mut payload);
+        if let Some(m) = hook.as_ref().and_then(ClientCallHook::metrics) {
+            m.msg_sent(payload.len());
+        }
         let cq_f = check_run(BatchType::CheckRead, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_start_unary(
                 call.call,
@@ -124,7 +178,7 @@ impl Call {
                 tag,
             )
         });
-        Ok(ClientUnaryReceiver::new(call, cq_f, method.resp_de()))
+        Ok(ClientUnaryReceiver::new(call, cq_f, method.resp_de(), hook))
     }
 
     pub fn client_streaming<Req, Resp>(
@@ -132,6 +186,7 @@ impl Call {
         method: &Method<Req, Resp>,
         mut opt: CallOption,
     ) -> Result<(ClientCStreamSender<Req>, ClientCStreamReceiver<Resp>)> {
+        let hook = ClientCallHook::intercept(channel, method.name, method.ty, &mut opt)?;
         let call = channel.create_call(method, &opt)?;
         let cq_f = check_run(BatchType::CheckRead, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_start_client_streaming(
@@ -146,11 +201,16 @@ impl Call {
         });
 
         let share_call = Arc::new(Mutex::new(ShareCall::new(call, cq_f)));
-        let sink = ClientCStreamSender::new(share_call.clone(), method.req_ser());
+        let metrics = hook.as_ref().and_then(ClientCallHook::metrics).cloned();
+        let sink = ClientCStreamSender::new(share_call.clone(), method.req_ser(), metrics);
         let recv = ClientCStreamReceiver {
             call: share_call,
             resp_de: method.resp_de(),
             finished: false,
+            resp: None,
+            headers: Metadata::default(),
+            trailers: None,
+            hook,
         };
         Ok((sink, recv))
     }
@@ -161,9 +221,13 @@ impl Call {
         req: &Req,
         mut opt: CallOption,
     ) -> Result<ClientSStreamReceiver<Resp>> {
+        let hook = ClientCallHook::intercept(channel, method.name, method.ty, &mut opt)?;
         let call = channel.create_call(method, &opt)?;
         let mut payload = GrpcSlice::default();
         (method.req_ser())(req, &mut payload);
+        if let Some(m) = hook.as_ref().and_then(ClientCallHook::metrics) {
+            m.msg_sent(payload.len());
+        }
         let cq_f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_start_server_streaming(
                 call.call,
@@ -178,12 +242,17 @@ impl Call {
             )
         });
 
-        // TODO: handle header
-        check_run(BatchType::Finish, |ctx, tag| unsafe {
+        let headers_f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_recv_initial_metadata(call.call, ctx, tag)
         });
 
-        Ok(ClientSStreamReceiver::new(call, cq_f, method.resp_de()))
+        Ok(ClientSStreamReceiver::new(
+            call,
+            cq_f,
+            headers_f,
+            method.resp_de(),
+            hook,
+        ))
     }
 
     pub fn duplex_streaming<Req, Resp>(
@@ -191,6 +260,7 @@ impl Call {
         method: &Method<Req, Resp>,
         mut opt: CallOption,
     ) -> Result<(ClientDuplexSender<Req>, ClientDuplexReceiver<Resp>)> {
+        let hook = ClientCallHook::intercept(channel, method.name, method.ty, &mut opt)?;
         let call = channel.create_call(method, &opt)?;
         let cq_f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_start_duplex_streaming(
@@ -204,14 +274,14 @@ impl Call {
             )
         });
 
-        // TODO: handle header.
-        check_run(BatchType::Finish, |ctx, tag| unsafe {
+        let headers_f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_recv_initial_metadata(call.call, ctx, tag)
         });
 
         let share_call = Arc::new(Mutex::new(ShareCall::new(call, cq_f)));
-        let sink = ClientDuplexSender::new(share_call.clone(), method.req_ser());
-        let recv = ClientDuplexReceiver::new(share_call, method.resp_de());
+        let metrics = hook.as_ref().and_then(ClientCallHook::metrics).cloned();
+        let sink = ClientDuplexSender::new(share_call.clone(), method.req_ser(), metrics);
+        let recv = ClientDuplexReceiver::new(share_call, headers_f, method.resp_de(), hook);
         Ok((sink, recv))
     }
 }
@@ -224,14 +294,29 @@ pub struct ClientUnaryReceiver<T> {
     call: Call,
     resp_f: BatchFuture,
     resp_de: DeserializeFn<T>,
+    finished: bool,
+    resp: Option<Result<MessageReader>>,
+    headers: Metadata,
+    trailers: Option<Metadata>,
+    hook: Option<ClientCallHook>,
 }
 
 impl<T> ClientUnaryReceiver<T> {
-    fn new(call: Call, resp_f: BatchFuture, resp_de: DeserializeFn<T>) -> ClientUnaryReceiver<T> {
+    fn new(
+        call: Call,
+        resp_f: BatchFuture,
+        resp_de: DeserializeFn<T>,
+        hook: Option<ClientCallHook>,
+    ) -> ClientUnaryReceiver<T> {
         ClientUnaryReceiver {
             call,
             resp_f,
             resp_de,
+            finished: false,
+            resp: None,
+            headers: Metadata::default(),
+            trailers: None,
+            hook,
         }
     }
 
@@ -245,15 +330,65 @@ impl<T> ClientUnaryReceiver<T> {
     pub fn resp_de(&self, reader: MessageReader) -> Result<T> {
         (self.resp_de)(reader)
     }
+
+    /// Get the initial metadata (response headers) sent by the server.
+    ///
+    /// Headers of a unary call arrive together with the response, so this waits until
+    /// the call is finished. The response can still be fetched by polling the receiver
+    /// afterwards. Empty metadata is returned if the call fails before the server
+    /// sends any headers.
+    pub async fn headers(&mut self) -> &Metadata {
+        future::poll_fn(|cx| self.poll_finish(cx)).await;
+        &self.headers
+    }
+
+    /// Get the trailing metadata (response trailers) sent by the server.
+    ///
+    /// `None` is returned if the call is not finished yet. When the call fails with
+    /// [`RpcFailure`], the trailers are available here as well as from
+    /// [`RpcStatus::trailers`].
+    ///
+    /// [`RpcFailure`]: ./enum.Error.html#variant.RpcFailure
+    /// [`RpcStatus::trailers`]: ./struct.RpcStatus.html#method.trailers
+    pub fn trailers(&self) -> Option<&Metadata> {
+        self.trailers.as_ref()
+    }
+
+    fn poll_finish(&mut self, cx: &mut Context) -> Poll<()> {
+        if !self.finished {
+            let res = ready!(Pin::new(&mut self.resp_f).poll(cx));
+            self.finished = true;
+            let mut resp = match res {
+                Ok(mut r) => {
+                    let reader = r.message_reader.take().unwrap();
+                    if let Some(m) = self.hook.as_ref().and_then(ClientCallHook::metrics) {
+                        m.msg_received(reader.len());
+                    }
+                    save_metadata(r, &mut self.headers, &mut self.trailers);
+                    Ok(reader)
+                }
+                Err(e) => {
+                    save_failure_metadata(&e, &mut self.headers, &mut self.trailers);
+                    Err(e)
+                }
+            };
+            ClientCallHook::finish_with(&mut self.hook, &mut resp);
+            self.resp = Some(resp);
+        }
+        Poll::Ready(())
+    }
 }
 
 impl<T> Future for ClientUnaryReceiver<T> {
     type Output = Result<T>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
-        let data = ready!(Pin::new(&mut self.resp_f).poll(cx)?);
-        let t = self.resp_de(data.unwrap())?;
-        Poll::Ready(Ok(t))
+        ready!(self.poll_finish(cx));
+        let reader = self
+            .resp
+            .take()
+            .expect("Resolved future is not supposed to be polled again.")?;
+        Poll::Ready((self.resp_de)(reader))
     }
 }
 
@@ -269,6 +404,10 @@ pub struct ClientCStreamReceiver<T> {
     call: Arc<Mutex<ShareCall>>,
     resp_de: DeserializeFn<T>,
     finished: bool,
+    resp: Option<Result<MessageReader>>,
+    headers: Metadata,
+    trailers: Option<Metadata>,
+    hook: Option<ClientCallHook>,
 }
 
 impl<T> ClientCStreamReceiver<T> {
@@ -282,6 +421,56 @@ impl<T> ClientCStreamReceiver<T> {
     pub fn resp_de(&self, reader: MessageReader) -> Result<T> {
         (self.resp_de)(reader)
     }
+
+    /// Get the initial metadata (response headers) sent by the server.
+    ///
+    /// Headers of a client streaming call arrive together with the response, so this
+    /// waits until the call is finished. The response can still be fetched by polling
+    /// the receiver afterwards. Empty metadata is returned if the call fails before
+    /// the server sends any headers.
+    pub async fn headers(&mut self) -> &Metadata {
+        future::poll_fn(|cx| self.poll_finish(cx)).await;
+        &self.headers
+    }
+
+    /// Get the trailing metadata (response trailers) sent by the server.
+    ///
+    /// `None` is returned if the call is not finished yet. When the call fails with
+    /// [`RpcFailure`], the trailers are available here as well as from
+    /// [`RpcStatus::trailers`].
+    ///
+    /// [`RpcFailure`]: ./enum.Error.html#variant.RpcFailure
+    /// [`RpcStatus::trailers`]: ./struct.RpcStatus.html#method.trailers
+    pub fn trailers(&self) -> Option<&Metadata> {
+        self.trailers.as_ref()
+    }
+
+    fn poll_finish(&mut self, cx: &mut Context) -> Poll<()> {
+        if !self.finished {
+            let res = {
+                let mut call = self.call.lock();
+                ready!(call.poll_finish(cx))
+            };
+            self.finished = true;
+            let mut resp = match res {
+                Ok(mut r) => {
+                    let reader = r.message_reader.take().unwrap();
+                    if let Some(m) = self.hook.as_ref().and_then(ClientCallHook::metrics) {
+                        m.msg_received(reader.len());
+                    }
+                    save_metadata(r, &mut self.headers, &mut self.trailers);
+                    Ok(reader)
+                }
+                Err(e) => {
+                    save_failure_metadata(&e, &mut self.headers, &mut self.trailers);
+                    Err(e)
+                }
+            };
+            ClientCallHook::finish_with(&mut self.hook, &mut resp);
+            self.resp = Some(resp);
+        }
+        Poll::Ready(())
+    }
 }
 
 impl<T> Drop for ClientCStreamReceiver<T> {
@@ -298,13 +487,12 @@ impl<T> Future for ClientCStreamReceiver<T> {
     type Output = Result<T>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
-        let data = {
-            let mut call = self.call.lock();
-            ready!(call.poll_finish(cx)?)
-        };
-        let t = (self.resp_de)(data.unwrap())?;
-        self.finished = true;
-        Poll::Ready(Ok(t))
+        ready!(self.poll_finish(cx));
+        let reader = self
+            .resp
+            .take()
+            .expect("Resolved future is not supposed to be polled again.")?;
+        Poll::Ready((self.resp_de)(reader))
     }
 }
 
@@ -318,15 +506,21 @@ pub struct StreamingCallSink<Req> {
     sink_base: SinkBase,
     close_f: Option<BatchFuture>,
     req_ser: SerializeFn<Req>,
+    metrics: Option<ClientCallMetrics>,
 }
 
 impl<Req> StreamingCallSink<Req> {
-    fn new(call: Arc<Mutex<ShareCall>>, req_ser: SerializeFn<Req>) -> StreamingCallSink<Req> {
+    fn new(
+        call: Arc<Mutex<ShareCall>>,
+        req_ser: SerializeFn<Req>,
+        metrics: Option<ClientCallMetrics>,
+    ) -> StreamingCallSink<Req> {
         StreamingCallSink {
             call,
             sink_base: SinkBase::new(false),
             close_f: None,
             req_ser,
+            metrics,
         }
     }
 
@@ -376,7 +570,11 @@ impl<Req> Sink<(Req, WriteFlags)> for StreamingCallSink<Req> {
             call.check_alive()?;
         }
         let t = &mut *self;
-        Pin::new(&mut t.sink_base).start_send(&mut t.call, &msg, flags, t.req_ser)
+        let len = Pin::new(&mut t.sink_base).start_send(&mut t.call, &msg, flags, t.req_ser)?;
+        if let Some(m) = &t.metrics {
+            m.msg_sent(len);
+        }
+        Ok(())
     }
 
     #[inline]
@@ -427,16 +625,34 @@ struct ResponseStreamImpl<H, T> {
     read_done: bool,
     finished: bool,
     resp_de: DeserializeFn<T>,
+    headers_f: Option<BatchFuture>,
+    headers: Metadata,
+    trailers: Option<Metadata>,
+    hook: Option<ClientCallHook>,
+    // The status may be received before all messages are read, so it's kept
+    // after the hook is finished.
+    metrics: Option<ClientCallMetrics>,
 }
 
 impl<H: ShareCallHolder + Unpin, T> ResponseStreamImpl<H, T> {
-    fn new(call: H, resp_de: DeserializeFn<T>) -> ResponseStreamImpl<H, T> {
+    fn new(
+        call: H,
+        headers_f: BatchFuture,
+        resp_de: DeserializeFn<T>,
+        hook: Option<ClientCallHook>,
+    ) -> ResponseStreamImpl<H, T> {
+        let metrics = hook.as_ref().and_then(ClientCallHook::metrics).cloned();
         ResponseStreamImpl {
             call,
             msg_f: None,
             read_done: false,
             finished: false,
             resp_de,
+            headers_f: Some(headers_f),
+            headers: Metadata::default(),
+            trailers: None,
+            hook,
+            metrics,
         }
     }
 
@@ -444,22 +660,50 @@ impl<H: ShareCallHolder + Unpin, T> ResponseStreamImpl<H, T> {
         self.call.call(|c| c.call.cancel())
     }
 
+    async fn headers(&mut self) -> &Metadata {
+        if let Some(headers_f) = &mut self.headers_f {
+            let res = headers_f.await;
+            self.headers_f = None;
+            if let Ok(BatchResult {
+                initial_metadata: Some(headers),
+                ..
+            }) = res
+            {
+                self.headers = headers;
+            }
+        }
+        &self.headers
+    }
+
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<T>>> {
         if !self.finished {
             let t = &mut *self;
             let finished = &mut t.finished;
-            let _ = t.call.call(|c| {
+            let res = t.call.call(|c| {
                 let res = c.poll_finish(cx);
                 *finished = c.finished;
                 res
-            })?;
+            });
+            match res {
+                Poll::Ready(Ok(r)) => {
+                    t.trailers = Some(r.trailing_metadata.unwrap_or_default());
+                    ClientCallHook::finish_with(&mut t.hook, &mut Ok(()));
+                }
+                Poll::Ready(Err(e)) => {
+                    t.trailers = failure_trailers(&e);
+                    let mut res = Err(e);
+                    ClientCallHook::finish_with(&mut t.hook, &mut res);
+                    return Poll::Ready(Some(res));
+                }
+                Poll::Pending => {}
+            }
         }
 
         let mut bytes = None;
         loop {
             if !self.read_done {
                 if let Some(msg_f) = &mut self.msg_f {
-                    bytes = ready!(Pin::new(msg_f).poll(cx)?);
+                    bytes = ready!(Pin::new(msg_f).poll(cx)?).message_reader;
                     if bytes.is_none() {
                         self.read_done = true;
                     }
@@ -478,6 +722,9 @@ impl<H: ShareCallHolder + Unpin, T> ResponseStreamImpl<H, T> {
             let msg_f = self.call.call(|c| c.call.start_recv_message())?;
             self.msg_f = Some(msg_f);
             if let Some(data) = bytes {
+                if let Some(m) = &self.metrics {
+                    m.msg_received(data.len());
+                }
                 let msg = (self.resp_de)(data)?;
                 return Poll::Ready(Some(Ok(msg)));
             }
@@ -503,17 +750,38 @@ impl<Resp> ClientSStreamReceiver<Resp> {
     fn new(
         call: Call,
         finish_f: BatchFuture,
+        headers_f: BatchFuture,
         de: DeserializeFn<Resp>,
+        hook: Option<ClientCallHook>,
     ) -> ClientSStreamReceiver<Resp> {
         let share_call = ShareCall::new(call, finish_f);
         ClientSStreamReceiver {
-            imp: ResponseStreamImpl::new(share_call, de),
+            imp: ResponseStreamImpl::new(share_call, headers_f, de, hook),
         }
     }
 
     pub fn cancel(&mut self) {
         self.imp.cancel()
     }
+
+    /// Get the initial metadata (response headers) sent by the server.
+    ///
+    /// Empty metadata is returned if the call fails before any headers are received.
+    pub async fn headers(&mut self) -> &Metadata {
+        self.imp.headers().await
+    }
+
+    /// Get the trailing metadata (response trailers) sent by the server.
+    ///
+    /// `None` is returned if the call is not finished yet. When the call fails with
+    /// [`RpcFailure`], the trailers are available here as well as from
+    /// [`RpcStatus::trailers`].
+    ///
+    /// [`RpcFailure`]: ./enum.Error.html#variant.RpcFailure
+    /// [`RpcStatus::trailers`]: ./struct.RpcStatus.html#method.trailers
+    pub fn trailers(&self) -> Option<&Metadata> {
+        self.imp.trailers.as_ref()
+    }
 }
 
 impl<Resp> Stream for ClientSStreamReceiver<Resp> {
@@ -538,15 +806,39 @@ pub struct ClientDuplexReceiver<Resp> {
 }
 
 impl<Resp> ClientDuplexReceiver<Resp> {
-    fn new(call: Arc<Mutex<ShareCall>>, de: DeserializeFn<Resp>) -> ClientDuplexReceiver<Resp> {
+    fn new(
+        call: Arc<Mutex<ShareCall>>,
+        headers_f: BatchFuture,
+        de: DeserializeFn<Resp>,
+        hook: Option<ClientCallHook>,
+    ) -> ClientDuplexReceiver<Resp> {
         ClientDuplexReceiver {
-            imp: ResponseStreamImpl::new(call, de),
+            imp: ResponseStreamImpl::new(call, headers_f, de, hook),
         }
     }
 
     pub fn cancel(&mut self) {
         self.imp.cancel()
     }
+
+    /// Get the initial metadata (response headers) sent by the server.
+    ///
+    /// Empty metadata is returned if the call fails before any headers are received.
+    pub async fn headers(&mut self) -> &Metadata {
+        self.imp.headers().await
+    }
+
+    /// Get the trailing metadata (response trailers) sent by the server.
+    ///
+    /// `None` is returned if the call is not finished yet. When the call fails with
+    /// [`RpcFailure`], the trailers are available here as well as from
+    /// [`RpcStatus::trailers`].
+    ///
+    /// [`RpcFailure`]: ./enum.Error.html#variant.RpcFailure
+    /// [`RpcStatus::trailers`]: ./struct.RpcStatus.html#method.trailers
+    pub fn trailers(&self) -> Option<&Metadata> {
+        self.imp.trailers.as_ref()
+    }
 }
 
 impl<Resp> Drop for ClientDuplexReceiver<Resp> {
@@ -565,6 +857,33 @@ impl<Resp> Stream for ClientDuplexReceiver<Resp> {
     }
 }
 
+/// Save the metadata received by a finished batch.
+fn save_metadata(res: BatchResult, headers: &mut Metadata, trailers: &mut Option<Metadata>) {
+    if let Some(h) = res.initial_metadata {
+        *headers = h;
+    }
+    *trailers = Some(res.trailing_metadata.unwrap_or_default());
+}
+
+/// Saves the metadata of a failed call. The headers are only available if they
+/// are received in the same batch as the status.
+fn save_failure_metadata(e: &Error, headers: &mut Metadata, trailers: &mut Option<Metadata>) {
+    if let Error::RpcFailure(status) = e {
+        if let Some(h) = status.headers() {
+            *headers = h.clone();
+        }
+    }
+    *trailers = failure_trailers(e);
+}
+
+/// Get the trailers of a failed call.
+fn failure_trailers(e: &Error) -> Option<Metadata> {
+    match e {
+        Error::RpcFailure(status) => Some(status.trailers().cloned().unwrap_or_default()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -20,7 +20,8 @@ use crate::buf::{GrpcByteBuffer, GrpcByteBufferReader, GrpcSlice};
 use crate::codec::{DeserializeFn, Marshaller, SerializeFn};
 use crate::error::{Error, Result};
 use crate::grpc_sys::grpc_status_code::*;
-use crate::task::{self, BatchFuture, BatchType, CallTag};
+use crate::interceptor::ServerCallHook;
+use crate::task::{self, BatchFuture, BatchResult, BatchType, CallTag};
 
 /// An gRPC status code structure.
 /// This type contains constants for all gRPC status codes.
@@ -55,6 +56,14 @@ macro_rules! status_codes {
         $(
             pub const $konst: RpcStatusCode = RpcStatusCode($num);
         )+
+
+            /// Get the canonical name of the code, `None` if the code is unknown.
+            pub(crate) fn name(self) -> Option<&'static str> {
+                match self {
+                    $(RpcStatusCode($num) => Some(stringify!($konst)),)+
+                    RpcStatusCode(_) => None,
+                }
+            }
         }
 
         impl Debug for RpcStatusCode {
@@ -63,10 +72,7 @@ macro_rules! status_codes {
                     f,
                     "{}-{}",
                     self.0,
-                    match self {
-                        $(RpcStatusCode($num) => stringify!($konst),)+
-                        RpcStatusCode(_) => "INVALID_STATUS_CODE",
-                    }
+                    self.name().unwrap_or("INVALID_STATUS_CODE"),
                 )
             }
         }
@@ -165,6 +171,13 @@ pub struct RpcStatus {
     ///
     /// See also https://grpc.io/docs/guides/error/#richer-error-model.
     details: Vec<u8>,
+
+    /// Trailing metadata received along with the status.
+    trailers: Option<Metadata>,
+
+    /// Initial metadata received in the same batch as the status, which is kept
+    /// for the receivers of failed calls.
+    headers: Option<Metadata>,
 }
 
 impl Display for RpcStatus {
@@ -198,6 +211,8 @@ impl RpcStatus {
             code: code.into(),
             message,
             details,
+            trailers: None,
+            headers: None,
         }
     }
 
@@ -224,6 +239,26 @@ impl RpcStatus {
     pub fn details(&self) -> &[u8] {
         &self.details
     }
+
+    /// Return the trailing metadata sent by the server along with the status.
+    ///
+    /// It's only available for the status received by client.
+    pub fn trailers(&self) -> Option<&Metadata> {
+        self.trailers.as_ref()
+    }
+
+    pub(crate) fn headers(&self) -> Option<&Metadata> {
+        self.headers.as_ref()
+    }
+
+    /// Appends `msg` to the message, separated by a comma.
+    #[cfg(feature = "secure")]
+    pub(crate) fn append_message(&mut self, msg: &str) {
+        if !self.message.is_empty() {
+            self.message.push_str(", ");
+        }
+        self.message.push_str(msg);
+    }
 }
 
 pub type MessageReader = GrpcByteBufferReader;
@@ -276,11 +311,35 @@ impl BatchContext {
                     );
                 let metadata = &*(m_ptr as *const Metadata);
                 let details = metadata.search_binary_error_details().to_vec();
-                RpcStatus::with_details(status, message, details)
+                let mut status = RpcStatus::with_details(status, message, details);
+                status.trailers = self.recv_trailing_metadata();
+                status.headers = self.recv_initial_metadata();
+                status
             }
         }
     }
 
+    /// Get the initial metadata received in the batch.
+    ///
+    /// `None` is returned if there is no entry.
+    pub fn recv_initial_metadata(&self) -> Option<Metadata> {
+        unsafe {
+            let m_ptr = grpc_sys::grpcwrap_batch_context_recv_initial_metadata(self.ctx);
+            copy_metadata(&*(m_ptr as *const Metadata))
+        }
+    }
+
+    /// Get the trailing metadata received along with the status of the rpc call.
+    ///
+    /// `None` is returned if there is no entry.
+    pub fn recv_trailing_metadata(&self) -> Option<Metadata> {
+        unsafe {
+            let m_ptr =
+                grpc_sys::grpcwrap_batch_context_recv_status_on_client_trailing_metadata(self.ctx);
+            copy_metadata(&*(m_ptr as *const Metadata))
+        }
+    }
+
     /// Fetch the response bytes of the rpc call.
     pub fn recv_message(&mut self) -> Option<MessageReader> {
         let buf = self.take_recv_message()?;
@@ -288,6 +347,15 @@ impl BatchContext {
     }
 }
 
+/// Copy the received metadata, whose entries are owned by the call.
+fn copy_metadata(metadata: &Metadata) -> Option<Metadata> {
+    if metadata.is_empty() {
+        None
+    } else {
+        Some(metadata.clone())
+    }
+}
+
 impl Drop for BatchContext {
     fn drop(&mut self) {
         unsafe { grpc_sys::grpcwrap_batch_context_destroy(self.ctx) }
@@ -339,20 +407,27 @@ impl Call {
     }
 
     /// Send a message asynchronously.
+    ///
+    /// If `send_initial_meta` is true, `initial_metadata` is sent along with the message,
+    /// empty metadata is sent if it's `None`.
     pub fn start_send_message(
         &mut self,
         msg: &mut GrpcSlice,
         write_flags: u32,
-        initial_meta: bool,
+        send_initial_meta: bool,
+        initial_metadata: &mut Option<Metadata>,
     ) -> Result<BatchFuture> {
         let _cq_ref = self.cq.borrow()?;
-        let i = if initial_meta { 1 } else { 0 };
+        let i = if send_initial_meta { 1 } else { 0 };
         let f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             grpc_sys::grpcwrap_call_send_message(
                 self.call,
                 ctx,
                 msg.as_mut_ptr(),
                 write_flags,
+                initial_metadata
+                    .as_mut()
+                    .map_or_else(ptr::null_mut, |m| m as *mut _ as _),
                 i,
                 tag,
             )
@@ -390,15 +465,20 @@ impl Call {
     }
 
     /// Send a status from server.
+    ///
+    /// If `send_initial_meta` is true, `initial_metadata` is sent before the status, empty
+    /// metadata is sent if it's `None`. `trailing_metadata` is sent along with the status.
     pub fn start_send_status_from_server(
         &mut self,
         status: &RpcStatus,
-        send_empty_metadata: bool,
+        send_initial_meta: bool,
+        initial_metadata: &mut Option<Metadata>,
+        trailing_metadata: Option<Metadata>,
         payload: &mut Option<GrpcSlice>,
         write_flags: u32,
     ) -> Result<BatchFuture> {
         let _cq_ref = self.cq.borrow()?;
-        let send_empty_metadata = if send_empty_metadata { 1 } else { 0 };
+        let send_initial_meta = if send_initial_meta { 1 } else { 0 };
         let f = check_run(BatchType::Finish, |ctx, tag| unsafe {
             let (msg_ptr, msg_len) = if status.code() == RpcStatusCode::OK {
                 (ptr::null(), 0)
@@ -410,9 +490,10 @@ impl Call {
                 None => ptr::null_mut(),
             };
             let mut trailing_metadata = if status.details.is_empty() {
-                None
+                trailing_metadata
             } else {
-                let mut builder = MetadataBuilder::new();
+                let mut builder =
+                    trailing_metadata.map_or_else(MetadataBuilder::new, MetadataBuilder::from);
                 builder.set_binary_error_details(&status.details);
                 Some(builder.build())
             };
@@ -425,7 +506,10 @@ impl Call {
                 trailing_metadata
                     .as_mut()
                     .map_or_else(ptr::null_mut, |m| m as *mut _ as _),
-                send_empty_metadata,
+                initial_metadata
+                    .as_mut()
+                    .map_or_else(ptr::null_mut, |m| m as *mut _ as _),
+                send_initial_meta,
                 payload_p,
                 write_flags,
                 tag,
@@ -436,14 +520,25 @@ impl Call {
 
     /// Abort an rpc call before handler is called.
     pub fn abort(self, status: &RpcStatus) {
+        self.abort_with_hook(status, None)
+    }
+
+    /// Abort an rpc call along with the response metadata added by interceptors,
+    /// `hook` is notified once the status is sent.
+    pub fn abort_with_hook(self, status: &RpcStatus, mut hook: Option<ServerCallHook>) {
         match self.cq.borrow() {
             // Queue is shutdown, ignore.
             Err(Error::QueueShutdown) => return,
             Err(e) => panic!("unexpected error when aborting call: {:?}", e),
             _ => {}
         }
+        let mut headers = ServerCallHook::merge_headers(hook.as_ref(), None);
+        let mut trailers = ServerCallHook::merge_trailers(hook.as_ref(), None);
+        if let Some(hook) = &mut hook {
+            hook.sending(status);
+        }
         let call_ptr = self.call;
-        let tag = CallTag::abort(self);
+        let tag = CallTag::abort(self, hook);
         let (batch_ptr, tag_ptr) = box_batch_tag(tag);
 
         let code = unsafe {
@@ -458,7 +553,12 @@ impl Call {
                 status.code().into(),
                 msg_ptr as _,
                 msg_len,
-                ptr::null_mut(),
+                trailers
+                    .as_mut()
+                    .map_or_else(ptr::null_mut, |m| m as *mut _ as _),
+                headers
+                    .as_mut()
+                    .map_or_else(ptr::null_mut, |m| m as *mut _ as _),
                 1,
                 ptr::null_mut(),
                 0,
@@ -518,11 +618,11 @@ impl ShareCall {
     /// Poll if the call is still alive.
     ///
     /// If the call is still running, will register a notification for its completion.
-    fn poll_finish(&mut self, cx: &mut Context) -> Poll<Result<Option<MessageReader>>> {
+    fn poll_finish(&mut self, cx: &mut Context) -> Poll<Result<BatchResult>> {
         let res = match Pin::new(&mut self.close_f).poll(cx) {
-            Poll::Ready(Ok(reader)) => {
+            Poll::Ready(Ok(result)) => {
                 self.status = Some(RpcStatus::ok());
-                Poll::Ready(Ok(reader))
+                Poll::Ready(Ok(result))
             }
             Poll::Pending => return Poll::Pending,
             Poll::Ready(Err(Error::RpcFailure(status))) => {
@@ -603,7 +703,7 @@ impl StreamingBase {
         let mut bytes = None;
         if !self.read_done {
             if let Some(msg_f) = &mut self.msg_f {
-                bytes = ready!(Pin::new(msg_f).poll(cx)?);
+                bytes = ready!(Pin::new(msg_f).poll(cx)?).message_reader;
                 if bytes.is_none() {
                     self.read_done = true;
                 }
@@ -683,6 +783,9 @@ struct SinkBase {
     // Batch job to be executed in `poll_ready`.
     batch_f: Option<BatchFuture>,
     send_metadata: bool,
+    // Initial metadata to be sent along with the first message. Only used when `send_metadata`
+    // is true.
+    headers: Option<Metadata>,
     // Flag to indicate if enhance batch strategy. This behavior will modify the `buffer_hint` to batch
     // messages as much as possible.
     enhance_buffer_strategy: bool,
@@ -703,25 +806,28 @@ impl SinkBase {
             buf_flags: None,
             last_buf_hint: true,
             send_metadata,
+            headers: None,
             enhance_buffer_strategy: false,
         }
     }
 
+    /// Serializes `t` and starts sending it, returns the size of the serialized message.
     fn start_send<T, C: ShareCallHolder>(
         &mut self,
         call: &mut C,
         t: &T,
         flags: WriteFlags,
         ser: SerializeFn<T>,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         // temporary fix: buffer hint with send meta will not send out any metadata.
         // note: only the first message can enter this code block.
         if self.send_metadata {
             ser(t, &mut self.buffer);
+            let len = self.buffer.len();
             self.buf_flags = Some(flags);
             self.start_send_buffer_message(false, call)?;
             self.send_metadata = false;
-            return Ok(());
+            return Ok(len);
         }
 
         // If there is already a buffered message waiting to be sent, set `buffer_hint` to true to indicate
@@ -731,6 +837,7 @@ impl SinkBase {
         }
 
         ser(t, &mut self.buffer);
+        let len = self.buffer.len();
         let hint = flags.get_buffer_hint();
         self.last_buf_hint &= hint;
         self.buf_flags = Some(flags);
@@ -740,7 +847,7 @@ impl SinkBase {
             self.start_send_buffer_message(hint, call)?;
         }
 
-        Ok(())
+        Ok(len)
     }
 
     #[inline]
@@ -784,8 +891,12 @@ impl SinkBase {
         let mut flags = self.buf_flags.unwrap();
         flags = flags.buffer_hint(buffer_hint);
         let write_f = call.call(|c| {
-            c.call
-                .start_send_message(&mut self.buffer, flags.flags, self.send_metadata)
+            c.call.start_send_message(
+                &mut self.buffer,
+                flags.flags,
+                self.send_metadata,
+                &mut self.headers,
+            )
         })?;
         self.batch_f = Some(write_f);
         if !self.buffer.is_inline() {
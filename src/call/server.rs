@@ -17,7 +17,7 @@ use futures::task::{Context, Poll};
 use parking_lot::Mutex;
 
 use super::{RpcStatus, ShareCall, ShareCallHolder, WriteFlags};
-use crate::auth_context::AuthContext;
+use crate::auth_context::{AuthContext, PeerIdentity};
 use crate::buf::GrpcSlice;
 use crate::call::{
     BatchContext, Call, MessageReader, MethodType, RpcStatusCode, SinkBase, StreamingBase,
@@ -25,10 +25,14 @@ use crate::call::{
 use crate::codec::{DeserializeFn, SerializeFn};
 use crate::cq::CompletionQueue;
 use crate::error::{Error, Result};
+use crate::interceptor::{Extensions, InterceptContext, ServerCallHook, ServerInterceptors};
 use crate::metadata::Metadata;
+use crate::metrics::{CallMetrics, MetricsRecorder};
 use crate::server::ServerChecker;
 use crate::server::{BoxHandler, RequestCallContext};
 use crate::task::{BatchFuture, CallTag, Executor, Kicker};
+#[cfg(feature = "tracing")]
+use crate::trace::{self, TraceContext};
 use crate::CheckResult;
 
 /// A time point that an rpc or operation should finished before it.
@@ -58,6 +62,23 @@ impl Deadline {
     pub(crate) fn spec(self) -> gpr_timespec {
         self.spec
     }
+
+    /// Get the time left before the deadline, `None` if there is no deadline.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn remaining(self) -> Option<Duration> {
+        unsafe {
+            let inf = grpc_sys::gpr_inf_future(gpr_clock_type::GPR_CLOCK_REALTIME);
+            if grpc_sys::gpr_time_cmp(self.spec, inf) == 0 {
+                return None;
+            }
+            let now = grpc_sys::gpr_now(gpr_clock_type::GPR_CLOCK_REALTIME);
+            let left = grpc_sys::gpr_time_sub(self.spec, now);
+            if left.tv_sec < 0 {
+                return Some(Duration::from_secs(0));
+            }
+            Some(Duration::new(left.tv_sec as u64, left.tv_nsec as u32))
+        }
+    }
 }
 
 impl From<Duration> for Deadline {
@@ -95,17 +116,19 @@ impl RequestContext {
         rc: &mut RequestCallContext,
     ) -> result::Result<(), Self> {
         let checker = rc.get_checker();
+        let interceptors = rc.interceptors();
+        let recorder = rc.recorder();
         let handler = unsafe { rc.get_handler(self.method()) };
         match handler {
             Some(handler) => match handler.method_type() {
                 MethodType::Unary | MethodType::ServerStreaming => Err(self),
                 _ => {
-                    execute(self, cq, None, handler, checker);
+                    execute(self, cq, None, handler, checker, interceptors, recorder);
                     Ok(())
                 }
             },
             None => {
-                execute_unimplemented(self, cq.clone());
+                execute_unimplemented(self, cq.clone(), recorder);
                 Ok(())
             }
         }
@@ -141,7 +164,7 @@ impl RequestContext {
         self.ctx
     }
 
-    fn call(&self, cq: CompletionQueue) -> Call {
+    pub(crate) fn call(&self, cq: CompletionQueue) -> Call {
         unsafe {
             // It is okay to use a mutable pointer on a immutable reference, `self`,
             // because grpcwrap_request_call_context_ref_call is thread-safe.
@@ -158,20 +181,20 @@ impl RequestContext {
         unsafe { slice::from_raw_parts(method as _, len) }
     }
 
-    fn host(&self) -> &[u8] {
+    pub(crate) fn host(&self) -> &[u8] {
         let mut len = 0;
         let host = unsafe { grpc_sys::grpcwrap_request_call_context_host(self.ctx, &mut len) };
 
         unsafe { slice::from_raw_parts(host as _, len) }
     }
 
-    fn deadline(&self) -> Deadline {
+    pub(crate) fn deadline(&self) -> Deadline {
         let t = unsafe { grpc_sys::grpcwrap_request_call_context_deadline(self.ctx) };
 
         Deadline::new(t)
     }
 
-    fn metadata(&self) -> &Metadata {
+    pub(crate) fn metadata(&self) -> &Metadata {
         unsafe {
             let ptr = grpc_sys::grpcwrap_request_call_context_metadata_array(self.ctx);
             let arr_ptr: *const Metadata = ptr as _;
@@ -179,7 +202,7 @@ impl RequestContext {
         }
     }
 
-    fn peer(&self) -> String {
+    pub(crate) fn peer(&self) -> String {
         unsafe {
             // RequestContext always holds a reference of the call.
             let call = grpc_sys::grpcwrap_request_call_context_get_call(self.ctx);
@@ -194,7 +217,7 @@ impl RequestContext {
     }
 
     /// If the server binds in non-secure mode, this will return None
-    fn auth_context(&self) -> Option<AuthContext> {
+    pub(crate) fn auth_context(&self) -> Option<AuthContext> {
         unsafe {
             let call = grpc_sys::grpcwrap_request_call_context_get_call(self.ctx);
             AuthContext::from_call_ptr(call)
@@ -202,6 +225,9 @@ impl RequestContext {
     }
 }
 
+// The raw context is only accessed by the owner, so it's safe to be sent to other thread.
+unsafe impl Send for RequestContext {}
+
 impl Drop for RequestContext {
     fn drop(&mut self) {
         unsafe { grpc_sys::grpcwrap_request_call_context_destroy(self.ctx) }
@@ -247,9 +273,19 @@ impl UnaryRequestContext {
         reader: Option<MessageReader>,
     ) {
         let checker = rc.get_checker();
+        let interceptors = rc.interceptors();
+        let recorder = rc.recorder();
         let handler = unsafe { rc.get_handler(self.request.method()).unwrap() };
         if reader.is_some() {
-            return execute(self.request, cq, reader, handler, checker);
+            return execute(
+                self.request,
+                cq,
+                reader,
+                handler,
+                checker,
+                interceptors,
+                recorder,
+            );
         }
 
         let status = RpcStatus::with_message(RpcStatusCode::INTERNAL, "No payload".to_owned());
@@ -266,14 +302,20 @@ pub struct RequestStream<T> {
     call: Arc<Mutex<ShareCall>>,
     base: StreamingBase,
     de: DeserializeFn<T>,
+    metrics: Option<CallMetrics>,
 }
 
 impl<T> RequestStream<T> {
-    fn new(call: Arc<Mutex<ShareCall>>, de: DeserializeFn<T>) -> RequestStream<T> {
+    fn new(
+        call: Arc<Mutex<ShareCall>>,
+        de: DeserializeFn<T>,
+        metrics: Option<CallMetrics>,
+    ) -> RequestStream<T> {
         RequestStream {
             call,
             base: StreamingBase::new(None),
             de,
+            metrics,
         }
     }
 }
@@ -290,7 +332,12 @@ impl<T> Stream for RequestStream<T> {
         let t = &mut *self;
         match ready!(t.base.poll(cx, &mut t.call, false)?) {
             None => Poll::Ready(None),
-            Some(data) => Poll::Ready(Some((t.de)(data))),
+            Some(data) => {
+                if let Some(m) = &t.metrics {
+                    m.msg_received(data.len());
+                }
+                Poll::Ready(Some((t.de)(data)))
+            }
         }
     }
 }
@@ -313,6 +360,7 @@ macro_rules! impl_unary_sink {
             call: $holder,
             cq_f: Option<BatchFuture>,
             err: Option<Error>,
+            hook: Option<ServerCallHook>,
         }
 
         impl Future for $rt {
@@ -324,8 +372,12 @@ macro_rules! impl_unary_sink {
                 }
 
                 if self.cq_f.is_some() {
-                    ready!(Pin::new(self.cq_f.as_mut().unwrap()).poll(cx)?);
+                    let res = ready!(Pin::new(self.cq_f.as_mut().unwrap()).poll(cx));
                     self.cq_f.take();
+                    if let Some(hook) = self.hook.take() {
+                        hook.finish(res.is_ok());
+                    }
+                    res?;
                 }
 
                 ready!(self.call.call(|c| c.poll_finish(cx))?);
@@ -338,17 +390,41 @@ macro_rules! impl_unary_sink {
             call: Option<$holder>,
             write_flags: u32,
             ser: SerializeFn<T>,
+            headers: Option<Metadata>,
+            trailers: Option<Metadata>,
+            hook: Option<ServerCallHook>,
         }
 
         impl<T> $t<T> {
-            fn new(call: $holder, ser: SerializeFn<T>) -> $t<T> {
+            fn new(call: $holder, ser: SerializeFn<T>, hook: Option<ServerCallHook>) -> $t<T> {
                 $t {
                     call: Some(call),
                     write_flags: 0,
                     ser,
+                    headers: None,
+                    trailers: None,
+                    hook,
                 }
             }
 
+            /// Set the initial metadata (response headers) to be sent before the response.
+            ///
+            /// It never fails as the sink is consumed by sending the response, the
+            /// result is the same as the one of streaming sinks.
+            pub fn set_headers(&mut self, meta: Metadata) -> Result<()> {
+                self.headers = Some(meta);
+                Ok(())
+            }
+
+            /// Set the trailing metadata (response trailers) to be sent along with the status.
+            ///
+            /// It never fails as the sink is consumed by sending the status, the
+            /// result is the same as the one of streaming sinks.
+            pub fn set_trailers(&mut self, meta: Metadata) -> Result<()> {
+                self.trailers = Some(meta);
+                Ok(())
+            }
+
             pub fn success(self, t: T) -> $rt {
                 self.complete(RpcStatus::ok(), Some(t))
             }
@@ -365,20 +441,41 @@ macro_rules! impl_unary_sink {
                 });
 
                 let write_flags = self.write_flags;
+                let mut hook = self.hook.take();
+                let mut headers = ServerCallHook::merge_headers(hook.as_ref(), self.headers.take());
+                let trailers = ServerCallHook::merge_trailers(hook.as_ref(), self.trailers.take());
+                if let Some(hook) = &mut hook {
+                    if let (Some(m), Some(d)) = (hook.metrics(), &data) {
+                        m.msg_sent(d.len());
+                    }
+                    hook.sending(&status);
+                }
                 let res = self.call.as_mut().unwrap().call(|c| {
-                    c.call
-                        .start_send_status_from_server(&status, true, &mut data, write_flags)
+                    c.call.start_send_status_from_server(
+                        &status,
+                        true,
+                        &mut headers,
+                        trailers,
+                        &mut data,
+                        write_flags,
+                    )
                 });
 
                 let (cq_f, err) = match res {
                     Ok(f) => (Some(f), None),
-                    Err(e) => (None, Some(e)),
+                    Err(e) => {
+                        if let Some(hook) = hook.take() {
+                            hook.finish(false);
+                        }
+                        (None, Some(e))
+                    }
                 };
 
                 $rt {
                     call: self.call.take().unwrap(),
                     cq_f,
                     err,
+                    hook,
                 }
             }
         }
@@ -429,18 +526,24 @@ macro_rules! impl_stream_sink {
             base: SinkBase,
             flush_f: Option<BatchFuture>,
             status: RpcStatus,
+            trailers: Option<Metadata>,
+            hook: Option<ServerCallHook>,
             flushed: bool,
             closed: bool,
             ser: SerializeFn<T>,
         }
 
         impl<T> $t<T> {
-            fn new(call: $holder, ser: SerializeFn<T>) -> $t<T> {
+            fn new(call: $holder, ser: SerializeFn<T>, hook: Option<ServerCallHook>) -> $t<T> {
+                let mut base = SinkBase::new(true);
+                base.headers = ServerCallHook::merge_headers(hook.as_ref(), None);
                 $t {
                     call: Some(call),
-                    base: SinkBase::new(true),
+                    base,
                     flush_f: None,
                     status: RpcStatus::ok(),
+                    trailers: None,
+                    hook,
                     flushed: false,
                     closed: false,
                     ser,
@@ -465,23 +568,68 @@ macro_rules! impl_stream_sink {
                 self.status = status;
             }
 
+            /// Set the initial metadata (response headers) to be sent before the first message.
+            ///
+            /// [`RpcFinished`] is returned if the headers have already been sent
+            /// along with a message or the status.
+            ///
+            /// [`RpcFinished`]: crate::Error::RpcFinished
+            pub fn set_headers(&mut self, meta: Metadata) -> Result<()> {
+                if !self.base.send_metadata || self.flush_f.is_some() {
+                    return Err(Error::RpcFinished(None));
+                }
+                self.base.headers = ServerCallHook::merge_headers(self.hook.as_ref(), Some(meta));
+                Ok(())
+            }
+
+            /// Set the trailing metadata (response trailers) to be sent along with the status.
+            ///
+            /// [`RpcFinished`] is returned if the status has already been sent.
+            ///
+            /// [`RpcFinished`]: crate::Error::RpcFinished
+            pub fn set_trailers(&mut self, meta: Metadata) -> Result<()> {
+                if self.flush_f.is_some() {
+                    return Err(Error::RpcFinished(None));
+                }
+                self.trailers = Some(meta);
+                Ok(())
+            }
+
             pub fn fail(mut self, status: RpcStatus) -> $ft {
                 assert!(self.flush_f.is_none());
                 let send_metadata = self.base.send_metadata;
+                let mut hook = self.hook.take();
+                let headers = &mut self.base.headers;
+                let trailers = ServerCallHook::merge_trailers(hook.as_ref(), self.trailers.take());
+                if let Some(hook) = &mut hook {
+                    hook.sending(&status);
+                }
                 let res = self.call.as_mut().unwrap().call(|c| {
-                    c.call
-                        .start_send_status_from_server(&status, send_metadata, &mut None, 0)
+                    c.call.start_send_status_from_server(
+                        &status,
+                        send_metadata,
+                        headers,
+                        trailers,
+                        &mut None,
+                        0,
+                    )
                 });
 
                 let (fail_f, err) = match res {
                     Ok(f) => (Some(f), None),
-                    Err(e) => (None, Some(e)),
+                    Err(e) => {
+                        if let Some(hook) = hook.take() {
+                            hook.finish(false);
+                        }
+                        (None, Some(e))
+                    }
                 };
 
                 $ft {
                     call: self.call.take().unwrap(),
                     fail_f,
                     err,
+                    hook,
                 }
             }
         }
@@ -515,7 +663,11 @@ macro_rules! impl_stream_sink {
             #[inline]
             fn start_send(mut self: Pin<&mut Self>, (msg, flags): (T, WriteFlags)) -> Result<()> {
                 let t = &mut *self;
-                t.base.start_send(t.call.as_mut().unwrap(), &msg, flags, t.ser)
+                let len = t.base.start_send(t.call.as_mut().unwrap(), &msg, flags, t.ser)?;
+                if let Some(m) = t.hook.as_ref().and_then(ServerCallHook::metrics) {
+                    m.msg_sent(len);
+                }
+                Ok(())
             }
 
             #[inline]
@@ -534,15 +686,38 @@ macro_rules! impl_stream_sink {
                     let send_metadata = self.base.send_metadata;
                     let t = &mut *self;
                     let status = &t.status;
-                    let flush_f = t.call.as_mut().unwrap().call(|c| {
-                        c.call
-                            .start_send_status_from_server(status, send_metadata, &mut None, 0)
-                    })?;
-                    t.flush_f = Some(flush_f);
+                    let headers = &mut t.base.headers;
+                    let trailers = ServerCallHook::merge_trailers(t.hook.as_ref(), t.trailers.take());
+                    if let Some(hook) = &mut t.hook {
+                        hook.sending(status);
+                    }
+                    let res = t.call.as_mut().unwrap().call(|c| {
+                        c.call.start_send_status_from_server(
+                            status,
+                            send_metadata,
+                            headers,
+                            trailers,
+                            &mut None,
+                            0,
+                        )
+                    });
+                    match res {
+                        Ok(f) => t.flush_f = Some(f),
+                        Err(e) => {
+                            if let Some(hook) = t.hook.take() {
+                                hook.finish(false);
+                            }
+                            return Poll::Ready(Err(e));
+                        }
+                    }
                 }
 
                 if !self.flushed {
-                    ready!(Pin::new(self.flush_f.as_mut().unwrap()).poll(cx)?);
+                    let res = ready!(Pin::new(self.flush_f.as_mut().unwrap()).poll(cx));
+                    if let Some(hook) = self.hook.take() {
+                        hook.finish(res.is_ok());
+                    }
+                    res?;
                     self.flushed = true;
                 }
 
@@ -557,6 +732,7 @@ macro_rules! impl_stream_sink {
             call: $holder,
             fail_f: Option<BatchFuture>,
             err: Option<Error>,
+            hook: Option<ServerCallHook>,
         }
 
         impl Future for $ft {
@@ -576,7 +752,11 @@ macro_rules! impl_stream_sink {
                 })?;
 
                 if let Some(ref mut f) = self.fail_f {
-                    ready!(Pin::new(f).poll(cx)?);
+                    let res = ready!(Pin::new(f).poll(cx));
+                    if let Some(hook) = self.hook.take() {
+                        hook.finish(res.is_ok());
+                    }
+                    res?;
                 }
 
                 self.fail_f.take();
@@ -616,6 +796,13 @@ pub struct RpcContext<'a> {
     ctx: RequestContext,
     executor: Executor<'a>,
     deadline: Deadline,
+    request_headers: Option<Metadata>,
+    extensions: Extensions,
+    hook: Option<ServerCallHook>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    trace_context: Option<TraceContext>,
 }
 
 impl<'a> RpcContext<'a> {
@@ -624,9 +811,25 @@ impl<'a> RpcContext<'a> {
             deadline: ctx.deadline(),
             ctx,
             executor: Executor::new(cq),
+            request_headers: None,
+            extensions: Extensions::default(),
+            hook: None,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+            #[cfg(feature = "tracing")]
+            trace_context: None,
         }
     }
 
+    fn set_hook(&mut self, hook: ServerCallHook) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span = hook.span().clone();
+            self.trace_context = Some(hook.trace_context().clone());
+        }
+        self.hook = Some(hook);
+    }
+
     fn kicker(&self) -> Kicker {
         let call = self.call();
         Kicker::from_call(call)
@@ -650,7 +853,17 @@ impl<'a> RpcContext<'a> {
 
     /// Get the initial metadata sent by client.
     pub fn request_headers(&self) -> &Metadata {
-        self.ctx.metadata()
+        match &self.request_headers {
+            Some(headers) => headers,
+            None => self.ctx.metadata(),
+        }
+    }
+
+    /// Get the values passed by [`ServerInterceptor`]s.
+    ///
+    /// [`ServerInterceptor`]: crate::ServerInterceptor
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
     }
 
     pub fn peer(&self) -> String {
@@ -664,14 +877,52 @@ impl<'a> RpcContext<'a> {
         self.ctx.auth_context()
     }
 
+    /// Get the identity of the peer parsed from the [`AuthContext`].
+    ///
+    /// If the server binds in non-secure mode, this will return None
+    pub fn peer_identity(&self) -> Option<PeerIdentity> {
+        self.auth_context().map(|ctx| ctx.peer_identity_info())
+    }
+
+    /// Get the span of the call, which is closed when the status is sent.
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Get the trace context of the call, which is a child of the one sent by
+    /// client.
+    ///
+    /// Outgoing calls made in the handler or the futures given to [`spawn`]
+    /// continue the trace by default. Otherwise, pass it to
+    /// [`CallOption::trace_context`].
+    ///
+    /// [`spawn`]: RpcContext::spawn
+    /// [`CallOption::trace_context`]: crate::CallOption::trace_context
+    #[cfg(feature = "tracing")]
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
+
     /// Spawn the future into current gRPC poll thread.
     ///
     /// This can reduce a lot of context switching, but please make
     /// sure there is no heavy work in the future.
+    ///
+    /// If `tracing` feature is enabled, the future is instrumented with the span
+    /// and the trace context of the call.
     pub fn spawn<F>(&self, f: F)
     where
         F: Future<Output = ()> + Send + 'static,
     {
+        #[cfg(feature = "tracing")]
+        let f = {
+            let mut f = Box::pin(tracing::Instrument::instrument(f, self.span.clone()));
+            let mut trace_context = self.trace_context.clone();
+            futures::future::poll_fn(move |cx| {
+                trace::in_context(&mut trace_context, || f.as_mut().poll(cx))
+            })
+        };
         self.executor.spawn(f, self.kicker())
     }
 }
@@ -690,7 +941,7 @@ macro_rules! accept_call {
 
 // Helper function to call a unary handler.
 pub fn execute_unary<P, Q, F>(
-    ctx: RpcContext<'_>,
+    mut ctx: RpcContext<'_>,
     ser: SerializeFn<Q>,
     de: DeserializeFn<P>,
     payload: MessageReader,
@@ -700,6 +951,10 @@ pub fn execute_unary<P, Q, F>(
 {
     let mut call = ctx.call();
     let close_f = accept_call!(call);
+    let hook = ctx.hook.take();
+    if let Some(m) = hook.as_ref().and_then(ServerCallHook::metrics) {
+        m.msg_received(payload.len());
+    }
     let request = match de(payload) {
         Ok(f) => f,
         Err(e) => {
@@ -707,17 +962,17 @@ pub fn execute_unary<P, Q, F>(
                 RpcStatusCode::INTERNAL,
                 format!("Failed to deserialize response message: {:?}", e),
             );
-            call.abort(&status);
+            call.abort_with_hook(&status, hook);
             return;
         }
     };
-    let sink = UnarySink::new(ShareCall::new(call, close_f), ser);
+    let sink = UnarySink::new(ShareCall::new(call, close_f), ser, hook);
     f(ctx, request, sink)
 }
 
 // Helper function to call client streaming handler.
 pub fn execute_client_streaming<P, Q, F>(
-    ctx: RpcContext<'_>,
+    mut ctx: RpcContext<'_>,
     ser: SerializeFn<Q>,
     de: DeserializeFn<P>,
     f: &mut F,
@@ -728,14 +983,16 @@ pub fn execute_client_streaming<P, Q, F>(
     let close_f = accept_call!(call);
     let call = Arc::new(Mutex::new(ShareCall::new(call, close_f)));
 
-    let req_s = RequestStream::new(call.clone(), de);
-    let sink = ClientStreamingSink::new(call, ser);
+    let hook = ctx.hook.take();
+    let metrics = hook.as_ref().and_then(ServerCallHook::metrics).cloned();
+    let req_s = RequestStream::new(call.clone(), de, metrics);
+    let sink = ClientStreamingSink::new(call, ser, hook);
     f(ctx, req_s, sink)
 }
 
 // Helper function to call server streaming handler.
 pub fn execute_server_streaming<P, Q, F>(
-    ctx: RpcContext<'_>,
+    mut ctx: RpcContext<'_>,
     ser: SerializeFn<Q>,
     de: DeserializeFn<P>,
     payload: MessageReader,
@@ -745,6 +1002,10 @@ pub fn execute_server_streaming<P, Q, F>(
 {
     let mut call = ctx.call();
     let close_f = accept_call!(call);
+    let hook = ctx.hook.take();
+    if let Some(m) = hook.as_ref().and_then(ServerCallHook::metrics) {
+        m.msg_received(payload.len());
+    }
 
     let request = match de(payload) {
         Ok(t) => t,
@@ -753,18 +1014,18 @@ pub fn execute_server_streaming<P, Q, F>(
                 RpcStatusCode::INTERNAL,
                 format!("Failed to deserialize response message: {:?}", e),
             );
-            call.abort(&status);
+            call.abort_with_hook(&status, hook);
             return;
         }
     };
 
-    let sink = ServerStreamingSink::new(ShareCall::new(call, close_f), ser);
+    let sink = ServerStreamingSink::new(ShareCall::new(call, close_f), ser, hook);
     f(ctx, request, sink)
 }
 
 // Helper function to call duplex streaming handler.
 pub fn execute_duplex_streaming<P, Q, F>(
-    ctx: RpcContext<'_>,
+    mut ctx: RpcContext<'_>,
     ser: SerializeFn<Q>,
     de: DeserializeFn<P>,
     f: &mut F,
@@ -775,18 +1036,38 @@ pub fn execute_duplex_streaming<P, Q, F>(
     let close_f = accept_call!(call);
     let call = Arc::new(Mutex::new(ShareCall::new(call, close_f)));
 
-    let req_s = RequestStream::new(call.clone(), de);
-    let sink = DuplexSink::new(call, ser);
+    let hook = ctx.hook.take();
+    let metrics = hook.as_ref().and_then(ServerCallHook::metrics).cloned();
+    let req_s = RequestStream::new(call.clone(), de, metrics);
+    let sink = DuplexSink::new(call, ser, hook);
     f(ctx, req_s, sink)
 }
 
+// Undefined methods are recorded as one, so clients can't add metrics at will.
+const UNKNOWN_METHOD: &[u8] = b"/unknown/unknown";
+
 // A helper function used to handle all undefined rpc calls.
-pub fn execute_unimplemented(ctx: RequestContext, cq: CompletionQueue) {
+pub fn execute_unimplemented(
+    ctx: RequestContext,
+    cq: CompletionQueue,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
+) {
     // Suppress needless-pass-by-value.
     let ctx = ctx;
+    // The request of an undefined method is never read, like a duplex streaming call.
+    let metrics = recorder.map(|r| CallMetrics::start(r, UNKNOWN_METHOD, MethodType::Duplex));
+    let hook = if metrics.is_some() || cfg!(feature = "tracing") {
+        Some(ServerCallHook::new(
+            ServerInterceptors::default(),
+            metrics,
+            &ctx,
+        ))
+    } else {
+        None
+    };
     let mut call = ctx.call(cq);
     accept_call!(call);
-    call.abort(&RpcStatus::new(RpcStatusCode::UNIMPLEMENTED))
+    call.abort_with_hook(&RpcStatus::new(RpcStatusCode::UNIMPLEMENTED), hook)
 }
 
 // Helper function to call handler.
@@ -798,18 +1079,83 @@ fn execute(
     payload: Option<MessageReader>,
     f: &mut BoxHandler,
     mut checkers: Vec<Box<dyn ServerChecker>>,
+    interceptors: ServerInterceptors,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
 ) {
-    let rpc_ctx = RpcContext::new(ctx, cq);
+    let mut rpc_ctx = RpcContext::new(ctx, cq);
+    let metrics = recorder.map(|r| CallMetrics::start(r, rpc_ctx.method(), f.method_type()));
+    // The hook is set before checkers, so the calls rejected by them are also
+    // recorded and traced. Interceptors are added once they are going to run.
+    if metrics.is_some() || !interceptors.is_empty() || cfg!(feature = "tracing") {
+        let hook = ServerCallHook::new(ServerInterceptors::default(), metrics, &rpc_ctx.ctx);
+        rpc_ctx.set_hook(hook);
+    }
 
     for handler in checkers.iter_mut() {
         match handler.check(&rpc_ctx) {
             CheckResult::Continue => {}
             CheckResult::Abort(status) => {
-                rpc_ctx.call().abort(&status);
+                let hook = rpc_ctx.hook.take();
+                rpc_ctx.call().abort_with_hook(&status, hook);
                 return;
             }
         }
     }
 
-    f.handle(rpc_ctx, payload)
+    if interceptors.is_empty() {
+        return handle(f, rpc_ctx, payload);
+    }
+    let mut hook = rpc_ctx.hook.take().unwrap();
+    hook.set_interceptors(interceptors.clone());
+    intercept(rpc_ctx.ctx, cq, payload, f.box_clone(), interceptors, hook)
+}
+
+// Runs the interceptors in the gRPC poll thread, and calls the handler once all
+// of them are passed.
+fn intercept(
+    ctx: RequestContext,
+    cq: &CompletionQueue,
+    payload: Option<MessageReader>,
+    mut handler: BoxHandler,
+    interceptors: ServerInterceptors,
+    mut hook: ServerCallHook,
+) {
+    let kicker = Kicker::from_call(ctx.call(cq.clone()));
+    #[cfg(feature = "tracing")]
+    let span = hook.span().clone();
+    let mut ctx = InterceptContext::new(ctx);
+    let call_cq = cq.clone();
+    let f = async move {
+        for interceptor in interceptors.iter() {
+            if let CheckResult::Abort(status) = interceptor.intercept(&mut ctx).await {
+                ctx.take_response_metadata(&mut hook);
+                let call = ctx.request_ctx().call(call_cq);
+                call.abort_with_hook(&status, Some(hook));
+                return;
+            }
+        }
+
+        let (ctx, request_headers, extensions) = ctx.into_parts(&mut hook);
+        let mut rpc_ctx = RpcContext::new(ctx, &call_cq);
+        rpc_ctx.request_headers = request_headers;
+        rpc_ctx.extensions = extensions;
+        rpc_ctx.set_hook(hook);
+        handle(&mut handler, rpc_ctx, payload)
+    };
+    #[cfg(feature = "tracing")]
+    let f = tracing::Instrument::instrument(f, span);
+    Executor::new(cq).spawn(f, kicker)
+}
+
+// Calls the handler in the span and the trace context of the call.
+fn handle(handler: &mut BoxHandler, ctx: RpcContext<'_>, payload: Option<MessageReader>) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = ctx.span.clone();
+        let _enter = span.enter();
+        let mut trace_context = ctx.trace_context.clone();
+        trace::in_context(&mut trace_context, || handler.handle(ctx, payload))
+    }
+    #[cfg(not(feature = "tracing"))]
+    handler.handle(ctx, payload)
 }
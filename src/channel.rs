@@ -5,20 +5,33 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::future::Future;
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 use std::{cmp, i32, ptr};
 
 use crate::{
-    grpc_sys::{self, gpr_timespec, grpc_arg_pointer_vtable, grpc_channel, grpc_channel_args},
+    grpc_sys::{
+        self, gpr_timespec, grpc_arg, grpc_arg_pointer_vtable, grpc_arg_type, grpc_channel,
+        grpc_channel_args,
+    },
     Deadline,
 };
+use futures::stream::{self, Stream};
 use libc::{self, c_char, c_int};
+use parking_lot::{Mutex, RwLock};
 
 use crate::call::{Call, Method};
+use crate::channel_pool::{CallCounter, ChannelPool, PickPolicy, SharedInterceptor};
+use crate::channelz::{self, ChannelInfo};
 use crate::cq::CompletionQueue;
 use crate::env::Environment;
 use crate::error::Result;
+use crate::interceptor::{ClientInterceptor, ClientInterceptors};
+use crate::metrics::{ChannelMetrics, ClientMetricsRecorder};
+use crate::resolver::{self, Resolver, ResolverUpdater};
+use crate::service_config::ServiceConfig;
 use crate::task::CallTag;
 use crate::task::Kicker;
 use crate::CallOption;
@@ -50,6 +63,8 @@ enum Options {
     Integer(i32),
     String(CString),
     Pointer(ResourceQuota, *const grpc_arg_pointer_vtable),
+    #[cfg(feature = "secure")]
+    SslSessionCache(crate::SslSessionCache),
 }
 
 /// The optimization target for a [`Channel`].
@@ -73,6 +88,8 @@ pub enum LbPolicy {
 pub struct ChannelBuilder {
     env: Arc<Environment>,
     options: HashMap<Cow<'static, [u8]>, Options>,
+    interceptors: Vec<Box<dyn ClientInterceptor>>,
+    recorder: Option<Arc<dyn ClientMetricsRecorder>>,
 }
 
 impl ChannelBuilder {
@@ -81,9 +98,36 @@ impl ChannelBuilder {
         ChannelBuilder {
             env,
             options: HashMap::new(),
+            interceptors: Vec::new(),
+            recorder: None,
         }
     }
 
+    /// Add an interceptor to wrap every call made on the channel.
+    ///
+    /// Interceptors are executed in the order added. When a call is finished,
+    /// they are notified in the reverse order.
+    pub fn add_interceptor<I: ClientInterceptor + 'static>(
+        mut self,
+        interceptor: I,
+    ) -> ChannelBuilder {
+        self.interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Set the recorder of the metrics of every call made on the channel, like the
+    /// number of completed calls and their latency.
+    ///
+    /// For a [`ChannelPool`], the metrics of all its channels are recorded with
+    /// the same target.
+    pub fn metrics_recorder<R: ClientMetricsRecorder + 'static>(
+        mut self,
+        recorder: R,
+    ) -> ChannelBuilder {
+        self.recorder = Some(Arc::new(recorder));
+        self
+    }
+
     /// Set default authority to pass if none specified on call construction.
     pub fn default_authority<S: Into<Vec<u8>>>(mut self, authority: S) -> ChannelBuilder {
         let authority = CString::new(authority).unwrap();
@@ -373,6 +417,52 @@ impl ChannelBuilder {
         self
     }
 
+    /// Set the service config of the channel.
+    ///
+    /// The config will be overridden if the name resolver returns one for the target.
+    pub fn service_config(mut self, config: ServiceConfig) -> ChannelBuilder {
+        let val = CString::new(config.as_json()).unwrap();
+        self.options.insert(
+            Cow::Borrowed(grpcio_sys::GRPC_ARG_SERVICE_CONFIG),
+            Options::String(val),
+        );
+        self
+    }
+
+    /// Set whether to allow retries. Retries are configured by [`RetryPolicy`] in
+    /// the service config.
+    ///
+    /// [`RetryPolicy`]: crate::RetryPolicy
+    pub fn enable_retry(mut self, enable: bool) -> ChannelBuilder {
+        self.options.insert(
+            Cow::Borrowed(grpcio_sys::GRPC_ARG_ENABLE_RETRIES),
+            Options::Integer(enable as i32),
+        );
+        self
+    }
+
+    /// Set the max bytes of messages buffered for each call in order to retry it.
+    /// Once the limit is exceeded, the call will not be retried any more.
+    pub fn per_rpc_retry_buffer_size(mut self, bytes: i32) -> ChannelBuilder {
+        self.options.insert(
+            Cow::Borrowed(grpcio_sys::GRPC_ARG_PER_RPC_RETRY_BUFFER_SIZE),
+            Options::Integer(bytes),
+        );
+        self
+    }
+
+    /// Set whether to collect the channelz data of the channel, its subchannels and
+    /// their sockets.
+    ///
+    /// gRPC Core collects the data by default.
+    pub fn enable_channelz(mut self, enable: bool) -> ChannelBuilder {
+        self.options.insert(
+            Cow::Borrowed(grpcio_sys::GRPC_ARG_ENABLE_CHANNELZ),
+            Options::Integer(enable as i32),
+        );
+        self
+    }
+
     /// Set a raw integer configuration.
     ///
     /// This method is only for bench usage, users should use the encapsulated API instead.
@@ -424,11 +514,27 @@ impl ChannelBuilder {
                         vtable,
                     )
                 },
+                #[cfg(feature = "secure")]
+                Options::SslSessionCache(ref cache) => unsafe {
+                    let arg = cache.channel_arg();
+                    grpc_sys::grpcwrap_channel_args_set_pointer_vtable(
+                        args,
+                        i,
+                        key,
+                        arg.value.pointer.p,
+                        arg.value.pointer.vtable,
+                    )
+                },
             }
         }
         ChannelArgs { args }
     }
 
+    fn channel_metrics(&self, target: &str) -> Option<ChannelMetrics> {
+        let recorder = self.recorder.clone()?;
+        Some(ChannelMetrics::new(recorder, target))
+    }
+
     fn prepare_connect_args(&mut self) -> ChannelArgs {
         if let Entry::Vacant(e) = self.options.entry(Cow::Borrowed(
             grpcio_sys::GRPC_ARG_PRIMARY_USER_AGENT_STRING,
@@ -439,14 +545,28 @@ impl ChannelBuilder {
     }
 
     /// Build an insecure [`Channel`] that connects to a specific address.
+    ///
+    /// If the scheme of `addr` is registered by [`register_resolver`], the addresses
+    /// are resolved by the registered [`Resolver`].
+    ///
+    /// [`register_resolver`]: crate::register_resolver
     pub fn connect(mut self, addr: &str) -> Channel {
+        if let Some(resolver) = resolver::find(addr) {
+            let (ch, _) = self.connect_resolved(addr, resolver, |target, args| unsafe {
+                grpc_sys::grpc_insecure_channel_create(target.as_ptr(), args.args, ptr::null_mut())
+            });
+            return ch;
+        }
         let args = self.prepare_connect_args();
+        let metrics = self.channel_metrics(addr);
         let addr = CString::new(addr).unwrap();
         let addr_ptr = addr.as_ptr();
         let channel =
             unsafe { grpc_sys::grpc_insecure_channel_create(addr_ptr, args.args, ptr::null_mut()) };
 
-        unsafe { Channel::new(self.env.pick_cq(), self.env, channel) }
+        let ch = unsafe { Channel::new(self.env.pick_cq(), self.env, channel) };
+        ch.with_interceptors(self.interceptors)
+            .with_metrics(metrics)
     }
 
     /// Build an insecure [`Channel`] taking over an established connection from
@@ -464,25 +584,282 @@ impl ChannelBuilder {
     #[cfg(unix)]
     pub unsafe fn connect_from_fd(mut self, target: &str, fd: ::std::os::raw::c_int) -> Channel {
         let args = self.prepare_connect_args();
+        let metrics = self.channel_metrics(target);
         let target = CString::new(target).unwrap();
         let target_ptr = target.as_ptr();
         let channel = grpc_sys::grpc_insecure_channel_create_from_fd(target_ptr, fd, args.args);
 
         Channel::new(self.env.pick_cq(), self.env, channel)
+            .with_interceptors(self.interceptors)
+            .with_metrics(metrics)
+    }
+
+    /// Build a [`ChannelPool`] of `size` insecure channels that connect to a specific
+    /// address, each of which owns its connections.
+    ///
+    /// Unlike [`ChannelBuilder::connect`], the addresses are always resolved by the
+    /// builtin resolvers.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0.
+    pub fn connect_pool(mut self, addr: &str, size: usize, policy: PickPolicy) -> ChannelPool {
+        let args = self.prepare_pool_args();
+        let metrics = self.channel_metrics(addr);
+        let addr = CString::new(addr).unwrap();
+        self.build_pool(
+            size,
+            policy,
+            metrics,
+            |ch, ()| ch,
+            || unsafe {
+                let ch = grpc_sys::grpc_insecure_channel_create(
+                    addr.as_ptr(),
+                    args.args,
+                    ptr::null_mut(),
+                );
+                (ch, ())
+            },
+        )
+    }
+
+    fn prepare_pool_args(&mut self) -> ChannelArgs {
+        // Otherwise channels with the same arguments share connections.
+        self.options.insert(
+            Cow::Borrowed(grpcio_sys::GRPC_ARG_USE_LOCAL_SUBCHANNEL_POOL),
+            Options::Integer(1),
+        );
+        self.prepare_connect_args()
+    }
+
+    fn build_pool<T>(
+        self,
+        size: usize,
+        policy: PickPolicy,
+        metrics: Option<ChannelMetrics>,
+        decorate: impl Fn(Channel, T) -> Channel,
+        mut create: impl FnMut() -> (*mut grpc_channel, T),
+    ) -> ChannelPool {
+        assert!(size > 0, "a channel pool can't be empty");
+        let interceptors: Vec<Arc<dyn ClientInterceptor>> =
+            self.interceptors.into_iter().map(Arc::from).collect();
+        let mut channels = Vec::with_capacity(size);
+        let mut outstanding = Vec::with_capacity(size);
+        for _ in 0..size {
+            let counter = Arc::new(AtomicUsize::new(0));
+            let mut list: Vec<Box<dyn ClientInterceptor>> =
+                vec![Box::new(CallCounter(counter.clone()))];
+            for i in &interceptors {
+                list.push(Box::new(SharedInterceptor(i.clone())));
+            }
+            let (channel, extra) = create();
+            let ch = unsafe { Channel::new(self.env.pick_cq(), self.env.clone(), channel) };
+            let ch = ch.with_interceptors(list).with_metrics(metrics.clone());
+            channels.push(decorate(ch, extra));
+            outstanding.push(counter);
+        }
+        ChannelPool::new(channels, outstanding, policy)
+    }
+
+    /// Builds a channel whose addresses are pushed by `resolver`. The channel is
+    /// created by `create` with a target resolved by the fake resolver of grpc core,
+    /// which is fed by the [`ResolverUpdater`].
+    ///
+    /// Returns the channel and what's needed to recreate its underlying channel.
+    fn connect_resolved(
+        mut self,
+        target: &str,
+        resolver: Arc<dyn Resolver>,
+        create: impl FnOnce(&CStr, &ChannelArgs) -> *mut grpc_channel,
+    ) -> (Channel, ResolvedTarget) {
+        // Resolvers can only be registered when generators are supported.
+        let generator = ResponseGenerator::new().unwrap();
+        let args = self.prepare_connect_args();
+        let args = ChannelArgs::with_response_generator(&args, &generator);
+        let fake_target = CString::new(fake_target(target)).unwrap();
+        let channel = create(&fake_target, &args);
+        let metrics = self.channel_metrics(target);
+        let ch = unsafe { Channel::new(self.env.pick_cq(), self.env, channel) };
+        let ch = ch
+            .with_interceptors(self.interceptors)
+            .with_metrics(metrics);
+        let resolution = Arc::new(Mutex::new(Resolution {
+            generator,
+            addrs: None,
+            config: None,
+        }));
+        let resolved = ResolvedChannel {
+            channel: Arc::downgrade(&ch.inner),
+            resolution: resolution.clone(),
+        };
+        resolver.resolve(target, ResolverUpdater::new(resolved));
+        let target = ResolvedTarget {
+            target: fake_target,
+            args,
+            resolution,
+        };
+        (ch, target)
+    }
+}
+
+const ARG_RESPONSE_GENERATOR: &[u8] = b"grpc.fake_resolver.response_generator\0";
+
+/// Turns `scheme:endpoint` into a target of the fake resolver, so the endpoint
+/// is still used as the default authority.
+fn fake_target(target: &str) -> String {
+    let endpoint = match target.find(':') {
+        Some(pos) => target[pos + 1..].trim_start_matches('/'),
+        None => target,
+    };
+    format!("fake:///{}", endpoint)
+}
+
+/// Feeds resolution results to the fake resolver of a grpc channel.
+struct ResponseGenerator(*mut grpc_sys::grpcwrap_response_generator);
+
+impl ResponseGenerator {
+    /// Returns None if grpc core is built without the fake resolver, which is only
+    /// reachable when grpcio-sys builds grpc from the bundled sources.
+    fn new() -> Option<ResponseGenerator> {
+        let generator = unsafe { grpc_sys::grpcwrap_response_generator_create() };
+        if generator.is_null() {
+            None
+        } else {
+            Some(ResponseGenerator(generator))
+        }
+    }
+}
+
+/// Checks if channels can be fed by custom resolvers.
+pub(crate) fn resolver_supported() -> bool {
+    ResponseGenerator::new().is_some()
+}
+
+// The generator is thread safe and ref counted.
+unsafe impl Send for ResponseGenerator {}
+unsafe impl Sync for ResponseGenerator {}
+
+impl Drop for ResponseGenerator {
+    fn drop(&mut self) {
+        unsafe { grpc_sys::grpcwrap_response_generator_unref(self.0) }
+    }
+}
+
+/// The underlying channel of a resolved [`Channel`] is created with `target`
+/// and `args`, and fed by `resolution`.
+#[cfg_attr(not(feature = "secure"), allow(dead_code))]
+struct ResolvedTarget {
+    target: CString,
+    args: ChannelArgs,
+    resolution: Arc<Mutex<Resolution>>,
+}
+
+/// The latest resolution result of a resolved [`Channel`].
+struct Resolution {
+    generator: ResponseGenerator,
+    addrs: Option<Vec<SocketAddr>>,
+    config: Option<CString>,
+}
+
+impl Resolution {
+    /// Pushes `addrs` and `config` to the channel. Returns false if grpc core
+    /// rejects them.
+    fn push(&self, addrs: &[SocketAddr], config: Option<&CString>) -> bool {
+        let addrs: Vec<_> = addrs
+            .iter()
+            .map(|addr| {
+                let mut res = grpc_sys::grpcwrap_socket_address {
+                    ip: [0; 16],
+                    ip_len: 0,
+                    port: addr.port(),
+                };
+                match addr {
+                    SocketAddr::V4(a) => {
+                        res.ip[..4].copy_from_slice(&a.ip().octets());
+                        res.ip_len = 4;
+                    }
+                    SocketAddr::V6(a) => {
+                        res.ip.copy_from_slice(&a.ip().octets());
+                        res.ip_len = 16;
+                    }
+                }
+                res
+            })
+            .collect();
+        let config = config.map_or(ptr::null(), |c| c.as_ptr());
+        unsafe {
+            grpc_sys::grpcwrap_response_generator_set_response(
+                self.generator.0,
+                addrs.as_ptr(),
+                addrs.len(),
+                config,
+            ) != 0
+        }
+    }
+
+    /// Pushes the latest result again, which is used when the generator is
+    /// replaced along with the channel.
+    #[cfg(feature = "secure")]
+    fn replay(&self) {
+        if let Some(addrs) = &self.addrs {
+            self.push(addrs, self.config.as_ref());
+        }
+    }
+}
+
+/// A [`Channel`] whose addresses are resolved by a [`Resolver`].
+pub(crate) struct ResolvedChannel {
+    channel: Weak<ChannelInner>,
+    resolution: Arc<Mutex<Resolution>>,
+}
+
+impl ResolvedChannel {
+    pub(crate) fn update(&self, addrs: &[SocketAddr], config: Option<&ServiceConfig>) -> bool {
+        if self.channel.upgrade().is_none() {
+            return false;
+        }
+        let config = config.map(|c| CString::new(c.as_json()).unwrap());
+        let mut resolution = self.resolution.lock();
+        let config_changed = config.is_some() && config != resolution.config;
+        if !config_changed && resolution.addrs.as_deref() == Some(addrs) {
+            return true;
+        }
+        let config = config.or_else(|| resolution.config.clone());
+        if !resolution.push(addrs, config.as_ref()) {
+            error!(
+                "update of {:?} is ignored, the service config is invalid",
+                addrs
+            );
+            return true;
+        }
+        resolution.addrs = Some(addrs.to_vec());
+        resolution.config = config;
+        true
     }
 }
 
 #[cfg(feature = "secure")]
 mod secure_channel {
     use std::borrow::Cow;
-    use std::ffi::CString;
+    use std::ffi::{CStr, CString};
     use std::ptr;
+    use std::sync::{Arc, Weak};
+
+    use futures::prelude::*;
+    use parking_lot::Mutex;
 
     use crate::grpc_sys;
 
-    use crate::ChannelCredentials;
+    use crate::security::{ClientChecks, CredentialsReloader, Rejection};
+    use crate::task::{spawn_blocking, Executor};
+    use crate::{ChannelCredentials, SslSessionCache};
 
-    use super::{Channel, ChannelBuilder, Options};
+    use super::{
+        Channel, ChannelArgs, ChannelBuilder, ChannelInner, ConnectivityState, Options, Resolution,
+        ResponseGenerator,
+    };
+    use crate::channel_pool::{ChannelPool, PickPolicy};
+    use crate::resolver;
 
     const OPT_SSL_TARGET_NAME_OVERRIDE: &[u8] = b"grpc.ssl_target_name_override\0";
 
@@ -501,12 +878,80 @@ mod secure_channel {
             self
         }
 
+        /// Set the cache of TLS sessions, which can be shared by many channels to
+        /// resume sessions instead of doing full handshakes when connecting to the
+        /// same servers.
+        pub fn ssl_session_cache(mut self, cache: &SslSessionCache) -> ChannelBuilder {
+            self.options.insert(
+                Cow::Borrowed(grpcio_sys::GRPC_SSL_SESSION_CACHE_ARG),
+                Options::SslSessionCache(cache.clone()),
+            );
+            self
+        }
+
+        /// Returns the name the server is expected to have, which is the one set by
+        /// [`ChannelBuilder::override_ssl_target`] or the host of `addr`.
+        fn ssl_target_name(&self, addr: &str) -> String {
+            if let Some(Options::String(name)) = self.options.get(OPT_SSL_TARGET_NAME_OVERRIDE) {
+                return name.to_string_lossy().into_owned();
+            }
+            // Strips the scheme and the port.
+            let authority = addr.rsplit('/').next().unwrap_or(addr);
+            let host = match authority.strip_prefix('[') {
+                Some(rest) => rest.split(']').next().unwrap_or(rest),
+                None if authority.matches(':').count() == 1 => {
+                    authority.split(':').next().unwrap_or(authority)
+                }
+                None => authority,
+            };
+            host.to_owned()
+        }
+
         /// Build a secure [`Channel`] that connects to a specific address.
+        ///
+        /// If the scheme of `addr` is registered by [`register_resolver`], the
+        /// addresses are resolved by the registered [`Resolver`].
+        ///
+        /// If `creds` has a [`ChannelCredentialsFetcher`], the fetcher is asked for
+        /// updated credentials on a background thread whenever the channel is about to
+        /// reconnect. Only when the credentials change, the underlying channel is
+        /// rebuilt, which keeps serving calls on its existing connections while new
+        /// ones are established with the updated credentials. The rebuilt channel
+        /// starts over with new subchannels, so its channelz id changes and the
+        /// channelz counters and traces of the previous one are not carried over.
+        ///
+        /// [`register_resolver`]: crate::register_resolver
+        /// [`Resolver`]: crate::Resolver
+        /// [`ChannelCredentialsFetcher`]: crate::ChannelCredentialsFetcher
         pub fn secure_connect(mut self, addr: &str, mut creds: ChannelCredentials) -> Channel {
+            let target_name = self.ssl_target_name(addr);
+            let rejection = creds.new_rejection();
+            if let Some(resolver) = resolver::find(addr) {
+                let (ch, target) = self.connect_resolved(addr, resolver, |target, args| unsafe {
+                    let checked = with_client_checks(args, &creds, &target_name, &rejection);
+                    let args = checked.as_ref().unwrap_or(args);
+                    grpc_sys::grpc_secure_channel_create(
+                        creds.as_mut_ptr(),
+                        target.as_ptr(),
+                        args.args,
+                        ptr::null_mut(),
+                    )
+                });
+                let ch = ch.with_rejection(rejection);
+                if let Some(creds) = creds.reloader() {
+                    let resolution = Some(target.resolution);
+                    let (addr, args) = (Arc::new(target.target), Arc::new(target.args));
+                    Reloader::spawn(&ch, addr, args, target_name, creds, resolution);
+                }
+                return ch;
+            }
             let args = self.prepare_connect_args();
+            let metrics = self.channel_metrics(addr);
             let addr = CString::new(addr).unwrap();
             let addr_ptr = addr.as_ptr();
             let channel = unsafe {
+                let checked = with_client_checks(&args, &creds, &target_name, &rejection);
+                let args = checked.as_ref().unwrap_or(&args);
                 grpc_sys::grpc_secure_channel_create(
                     creds.as_mut_ptr(),
                     addr_ptr,
@@ -515,7 +960,190 @@ mod secure_channel {
                 )
             };
 
-            unsafe { Channel::new(self.env.pick_cq(), self.env, channel) }
+            let ch = unsafe { Channel::new(self.env.pick_cq(), self.env, channel) };
+            let ch = ch
+                .with_interceptors(self.interceptors)
+                .with_metrics(metrics)
+                .with_rejection(rejection);
+            if let Some(creds) = creds.reloader() {
+                let (addr, args) = (Arc::new(addr), Arc::new(args));
+                Reloader::spawn(&ch, addr, args, target_name, creds, None);
+            }
+            ch
+        }
+
+        /// Build a [`ChannelPool`] of `size` secure channels that connect to a specific
+        /// address, each of which owns its connections.
+        ///
+        /// Unlike [`ChannelBuilder::secure_connect`], the addresses are always resolved
+        /// by the builtin resolvers.
+        ///
+        /// # Panics
+        ///
+        /// If `size` is 0.
+        pub fn secure_connect_pool(
+            mut self,
+            addr: &str,
+            mut creds: ChannelCredentials,
+            size: usize,
+            policy: PickPolicy,
+        ) -> ChannelPool {
+            let target_name = self.ssl_target_name(addr);
+            let args = self.prepare_pool_args();
+            let metrics = self.channel_metrics(addr);
+            let addr = CString::new(addr).unwrap();
+            let with_rejection = |ch: Channel, rejection| ch.with_rejection(rejection);
+            let pool = self.build_pool(size, policy, metrics, with_rejection, || unsafe {
+                // Every channel records its own rejection.
+                let rejection = creds.new_rejection();
+                let checked = with_client_checks(&args, &creds, &target_name, &rejection);
+                let checked_args = checked.as_ref().unwrap_or(&args);
+                let ch = grpc_sys::grpc_secure_channel_create(
+                    creds.as_mut_ptr(),
+                    addr.as_ptr(),
+                    checked_args.args,
+                    ptr::null_mut(),
+                );
+                (ch, rejection)
+            });
+            if let Some(creds) = creds.reloader() {
+                let (addr, args) = (Arc::new(addr), Arc::new(args));
+                for ch in pool.channels() {
+                    let (addr, args, name) = (addr.clone(), args.clone(), target_name.clone());
+                    Reloader::spawn(ch, addr, args, name, creds.clone(), None);
+                }
+            }
+            pool
+        }
+    }
+
+    /// Copies `args` with the checks of the server, which record why the server
+    /// is rejected to `rejection`. Returns `None` if `creds` has no checks.
+    fn with_client_checks(
+        args: &ChannelArgs,
+        creds: &ChannelCredentials,
+        target_name: &str,
+        rejection: &Option<Rejection>,
+    ) -> Option<ChannelArgs> {
+        let checks = creds.client_checks(target_name.to_owned(), rejection.as_ref()?)?;
+        let key = CStr::from_bytes_with_nul(grpc_sys::GRPCWRAP_ARG_PEER_CHECKS).unwrap();
+        Some(ChannelArgs::copy_with(Some(args), key, |args, i| unsafe {
+            ClientChecks::set_channel_arg(&checks, args, i)
+        }))
+    }
+
+    /// Replaces the underlying channel when the credentials are updated.
+    struct Reloader {
+        inner: Weak<ChannelInner>,
+        addr: Arc<CString>,
+        // The arguments without the checks of the server, which are added with
+        // the ones of the updated credentials.
+        args: Arc<ChannelArgs>,
+        target_name: String,
+        // Kept so the rebuilt channel records rejections where the calls look.
+        rejection: Option<Rejection>,
+        creds: CredentialsReloader,
+        // Set if the addresses are pushed by a resolver, in which case the new
+        // channel needs a new generator and the latest addresses.
+        resolution: Option<Arc<Mutex<Resolution>>>,
+    }
+
+    impl Reloader {
+        /// Reloads the credentials once every time the channel is going to reconnect.
+        fn spawn(
+            ch: &Channel,
+            addr: Arc<CString>,
+            args: Arc<ChannelArgs>,
+            target_name: String,
+            creds: CredentialsReloader,
+            resolution: Option<Arc<Mutex<Resolution>>>,
+        ) {
+            let kicker = match ch.create_kicker() {
+                Ok(kicker) => kicker,
+                // It's already shutdown.
+                Err(_) => return,
+            };
+            let reloader = Reloader {
+                inner: Arc::downgrade(&ch.inner),
+                addr,
+                args,
+                target_name,
+                rejection: ch.rejection().cloned(),
+                creds,
+                resolution,
+            };
+            // The first state is observed before the channel connects.
+            let mut states = Box::pin(ch.connectivity_states().skip(1));
+            let f = async move {
+                let mut reloader = reloader;
+                // Whether the channel has tried to connect since the last reload, so
+                // TRANSIENT_FAILURE followed by IDLE only reloads once.
+                let mut connected = false;
+                while let Some(state) = states.next().await {
+                    match state {
+                        ConnectivityState::GRPC_CHANNEL_CONNECTING
+                        | ConnectivityState::GRPC_CHANNEL_READY => connected = true,
+                        // There is no connection that can be disrupted.
+                        ConnectivityState::GRPC_CHANNEL_IDLE
+                        | ConnectivityState::GRPC_CHANNEL_TRANSIENT_FAILURE
+                            if connected =>
+                        {
+                            connected = false;
+                            // Fetchers may read files, which must not block the
+                            // completion queue.
+                            let f = spawn_blocking(move || {
+                                let alive = reloader.reload();
+                                (reloader, alive)
+                            });
+                            match f.await {
+                                Ok((r, true)) => reloader = r,
+                                _ => break,
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            };
+            Executor::new(ch.cq()).spawn(f, kicker);
+        }
+
+        /// Returns false if the channel has been dropped.
+        fn reload(&mut self) -> bool {
+            let inner = match self.inner.upgrade() {
+                Some(inner) => inner,
+                None => return false,
+            };
+            let mut creds = match self.creds.reload() {
+                Some(creds) => creds,
+                None => return true,
+            };
+            let mut create = |args: &ChannelArgs| unsafe {
+                let checked = with_client_checks(args, &creds, &self.target_name, &self.rejection);
+                let args = checked.as_ref().unwrap_or(args);
+                grpc_sys::grpc_secure_channel_create(
+                    creds.as_mut_ptr(),
+                    self.addr.as_ptr(),
+                    args.args,
+                    ptr::null_mut(),
+                )
+            };
+            match &self.resolution {
+                None => {
+                    inner.replace(create(&self.args));
+                }
+                Some(resolution) => {
+                    // Holds the lock so no update is pushed to the old generator
+                    // while the channel is being replaced.
+                    let mut resolution = resolution.lock();
+                    let generator = ResponseGenerator::new().unwrap();
+                    let args = ChannelArgs::with_response_generator(&self.args, &generator);
+                    let channel = create(&args);
+                    resolution.generator = generator;
+                    resolution.replay();
+                    inner.replace(channel);
+                }
+            }
+            true
         }
     }
 }
@@ -528,8 +1156,81 @@ impl ChannelArgs {
     pub fn as_ptr(&self) -> *const grpc_channel_args {
         self.args
     }
+
+    fn raw_args(&self) -> &[grpc_arg] {
+        unsafe {
+            let args = &*self.args;
+            if args.num_args == 0 {
+                return &[];
+            }
+            std::slice::from_raw_parts(args.args, args.num_args)
+        }
+    }
+
+    /// Copies `args` with the integer argument `key` set to `val`.
+    pub(crate) fn with_integer(args: Option<&ChannelArgs>, key: &[u8], val: i32) -> ChannelArgs {
+        let key = CStr::from_bytes_with_nul(key).unwrap();
+        ChannelArgs::copy_with(args, key, |args, i| unsafe {
+            grpc_sys::grpcwrap_channel_args_set_integer(args, i, key.as_ptr(), val as c_int)
+        })
+    }
+
+    /// Copies `args` with the argument binding the fake resolver to `generator`.
+    fn with_response_generator(args: &ChannelArgs, generator: &ResponseGenerator) -> ChannelArgs {
+        let key = CStr::from_bytes_with_nul(ARG_RESPONSE_GENERATOR).unwrap();
+        ChannelArgs::copy_with(Some(args), key, |args, i| unsafe {
+            grpc_sys::grpcwrap_channel_args_set_response_generator(args, i, generator.0)
+        })
+    }
+
+    /// Copies `args` without the argument `key`, then appends an argument by `set`.
+    fn copy_with(
+        args: Option<&ChannelArgs>,
+        key: &CStr,
+        set: impl FnOnce(*mut grpc_channel_args, usize),
+    ) -> ChannelArgs {
+        let old: Vec<_> = args
+            .map_or(&[][..], ChannelArgs::raw_args)
+            .iter()
+            .filter(|arg| unsafe { CStr::from_ptr(arg.key) } != key)
+            .collect();
+        unsafe {
+            let args = grpc_sys::grpcwrap_channel_args_create(old.len() + 1);
+            for (i, arg) in old.into_iter().enumerate() {
+                match arg.type_ {
+                    grpc_arg_type::GRPC_ARG_STRING => grpc_sys::grpcwrap_channel_args_set_string(
+                        args,
+                        i,
+                        arg.key,
+                        arg.value.string,
+                    ),
+                    grpc_arg_type::GRPC_ARG_INTEGER => grpc_sys::grpcwrap_channel_args_set_integer(
+                        args,
+                        i,
+                        arg.key,
+                        arg.value.integer,
+                    ),
+                    grpc_arg_type::GRPC_ARG_POINTER => {
+                        grpc_sys::grpcwrap_channel_args_set_pointer_vtable(
+                            args,
+                            i,
+                            arg.key,
+                            arg.value.pointer.p,
+                            arg.value.pointer.vtable,
+                        )
+                    }
+                }
+            }
+            set(args, (*args).num_args - 1);
+            ChannelArgs { args }
+        }
+    }
 }
 
+// The args are never modified after being built.
+unsafe impl Send for ChannelArgs {}
+unsafe impl Sync for ChannelArgs {}
+
 impl Drop for ChannelArgs {
     fn drop(&mut self) {
         unsafe { grpc_sys::grpcwrap_channel_args_destroy(self.args) }
@@ -538,7 +1239,8 @@ impl Drop for ChannelArgs {
 
 struct ChannelInner {
     _env: Arc<Environment>,
-    channel: *mut grpc_channel,
+    // It's replaced when the credentials are reloaded.
+    channel: RwLock<*mut grpc_channel>,
 }
 
 impl ChannelInner {
@@ -546,14 +1248,55 @@ impl ChannelInner {
     // changing the state.
     fn check_connectivity_state(&self, try_to_connect: bool) -> ConnectivityState {
         let should_try = if try_to_connect { 1 } else { 0 };
-        unsafe { grpc_sys::grpc_channel_check_connectivity_state(self.channel, should_try) }
+        let channel = self.channel.read();
+        unsafe { grpc_sys::grpc_channel_check_connectivity_state(*channel, should_try) }
+    }
+
+    fn watch_state_change(
+        &self,
+        cq: &CompletionQueue,
+        last_observed: ConnectivityState,
+        deadline: gpr_timespec,
+    ) -> impl Future<Output = bool> {
+        let (cq_f, prom) = CallTag::action_pair();
+        let prom_box = Box::new(prom);
+        let tag = Box::into_raw(prom_box);
+        let should_wait = if let Ok(cq_ref) = cq.borrow() {
+            let channel = self.channel.read();
+            unsafe {
+                grpcio_sys::grpc_channel_watch_connectivity_state(
+                    *channel,
+                    last_observed,
+                    deadline,
+                    cq_ref.as_ptr(),
+                    tag as *mut _,
+                )
+            }
+            true
+        } else {
+            // It's already shutdown.
+            false
+        };
+        async move { should_wait && cq_f.await.unwrap() }
+    }
+
+    // Calls that have been created keep working until they are finished.
+    #[cfg(feature = "secure")]
+    fn replace(&self, channel: *mut grpc_channel) {
+        let old = std::mem::replace(&mut *self.channel.write(), channel);
+        unsafe {
+            grpc_sys::grpc_channel_destroy(old);
+        }
     }
 }
 
+unsafe impl Send for ChannelInner {}
+unsafe impl Sync for ChannelInner {}
+
 impl Drop for ChannelInner {
     fn drop(&mut self) {
         unsafe {
-            grpc_sys::grpc_channel_destroy(self.channel);
+            grpc_sys::grpc_channel_destroy(*self.channel.get_mut());
         }
     }
 }
@@ -568,6 +1311,10 @@ impl Drop for ChannelInner {
 pub struct Channel {
     inner: Arc<ChannelInner>,
     cq: CompletionQueue,
+    interceptors: ClientInterceptors,
+    metrics: Option<ChannelMetrics>,
+    #[cfg(feature = "secure")]
+    rejection: Option<crate::security::Rejection>,
 }
 
 unsafe impl Send for Channel {}
@@ -588,11 +1335,54 @@ impl Channel {
         channel: *mut grpc_channel,
     ) -> Channel {
         Channel {
-            inner: Arc::new(ChannelInner { _env: env, channel }),
+            inner: Arc::new(ChannelInner {
+                _env: env,
+                channel: RwLock::new(channel),
+            }),
             cq,
+            interceptors: Arc::new(Vec::new()),
+            metrics: None,
+            #[cfg(feature = "secure")]
+            rejection: None,
         }
     }
 
+    fn with_interceptors(mut self, interceptors: Vec<Box<dyn ClientInterceptor>>) -> Channel {
+        self.interceptors = Arc::new(interceptors);
+        self
+    }
+
+    fn with_metrics(mut self, metrics: Option<ChannelMetrics>) -> Channel {
+        self.metrics = metrics;
+        self
+    }
+
+    #[cfg(feature = "secure")]
+    fn with_rejection(mut self, rejection: Option<crate::security::Rejection>) -> Channel {
+        self.rejection = rejection;
+        self
+    }
+
+    /// Get the channelz id of the channel.
+    ///
+    /// It's `None` if channelz is disabled by [`ChannelBuilder::enable_channelz`], or
+    /// grpcio-sys doesn't build grpc from the bundled sources. The id changes when
+    /// the underlying channel is rebuilt because the credentials are reloaded, and
+    /// the counters of the new id start from zero.
+    pub fn channelz_id(&self) -> Option<i64> {
+        let channel = self.inner.channel.read();
+        channelz::id(unsafe { grpc_sys::grpcwrap_channel_get_channelz_id(*channel) })
+    }
+
+    /// Get the channelz data of the channel, whose subchannels and sockets can be
+    /// looked up by [`channelz::get_subchannel`] and [`channelz::get_socket`].
+    ///
+    /// [`channelz::get_subchannel`]: crate::channelz::get_subchannel
+    /// [`channelz::get_socket`]: crate::channelz::get_socket
+    pub fn channelz(&self) -> Option<ChannelInfo> {
+        channelz::get_channel(self.channelz_id()?)
+    }
+
     /// If try_to_connect is true, the channel will try to establish a connection, potentially
     /// changing the state.
     pub fn check_connectivity_state(&self, try_to_connect: bool) -> ConnectivityState {
@@ -608,25 +1398,38 @@ impl Channel {
         last_observed: ConnectivityState,
         deadline: impl Into<Deadline>,
     ) -> impl Future<Output = bool> {
-        let (cq_f, prom) = CallTag::action_pair();
-        let prom_box = Box::new(prom);
-        let tag = Box::into_raw(prom_box);
-        let should_wait = if let Ok(cq_ref) = self.cq.borrow() {
-            unsafe {
-                grpcio_sys::grpc_channel_watch_connectivity_state(
-                    self.inner.channel,
-                    last_observed,
-                    deadline.into().spec(),
-                    cq_ref.as_ptr(),
-                    tag as *mut _,
-                )
-            }
-            true
-        } else {
-            // It's already shutdown.
-            false
-        };
-        async move { should_wait && cq_f.await.unwrap() }
+        self.inner
+            .watch_state_change(&self.cq, last_observed, deadline.into().spec())
+    }
+
+    /// Get a stream of the connectivity states of this channel.
+    ///
+    /// The current state is yielded first, then every state it transitions to.
+    /// The stream ends after yielding `GRPC_CHANNEL_SHUTDOWN`, or when all the
+    /// clones of the channel are dropped. It doesn't keep the channel alive.
+    pub fn connectivity_states(&self) -> impl Stream<Item = ConnectivityState> {
+        let inner = Arc::downgrade(&self.inner);
+        let cq = self.cq.clone();
+        stream::unfold((inner, cq, None), |(inner, cq, last)| async move {
+            let state = match last {
+                None => inner.upgrade()?.check_connectivity_state(false),
+                Some(ConnectivityState::GRPC_CHANNEL_SHUTDOWN) => return None,
+                Some(last) => loop {
+                    // The channel must not be held while waiting, otherwise it can't
+                    // be dropped.
+                    let deadline = gpr_timespec::inf_future();
+                    let f = inner.upgrade()?.watch_state_change(&cq, last, deadline);
+                    if !f.await {
+                        return None;
+                    }
+                    let state = inner.upgrade()?.check_connectivity_state(false);
+                    if state != last {
+                        break state;
+                    }
+                },
+            };
+            Some((state, (inner, cq, Some(state))))
+        })
     }
 
     /// Wait for this channel to be connected.
@@ -656,8 +1459,9 @@ impl Channel {
     /// Create a Kicker.
     pub(crate) fn create_kicker(&self) -> Result<Kicker> {
         let cq_ref = self.cq.borrow()?;
+        let channel = self.inner.channel.read();
         let raw_call = unsafe {
-            let ch = self.inner.channel;
+            let ch = *channel;
             let cq = cq_ref.as_ptr();
             // Do not timeout.
             let timeout = gpr_timespec::inf_future();
@@ -684,8 +1488,9 @@ impl Channel {
         opt: &CallOption,
     ) -> Result<Call> {
         let cq_ref = self.cq.borrow()?;
+        let channel = self.inner.channel.read();
         let raw_call = unsafe {
-            let ch = self.inner.channel;
+            let ch = *channel;
             let cq = cq_ref.as_ptr();
             let method_ptr = method.name.as_ptr();
             let method_len = method.name.len();
@@ -704,11 +1509,32 @@ impl Channel {
                 timeout,
             )
         };
+        let call = unsafe { Call::from_raw(raw_call, self.cq.clone()) };
 
-        unsafe { Ok(Call::from_raw(raw_call, self.cq.clone())) }
+        #[cfg(feature = "secure")]
+        if let Some(creds) = opt.get_call_credentials() {
+            let code = unsafe { grpc_sys::grpc_call_set_credentials(call.call, creds.as_ptr()) };
+            if code != grpc_sys::grpc_call_error::GRPC_CALL_OK {
+                return Err(crate::Error::CallFailure(code));
+            }
+        }
+        Ok(call)
     }
 
     pub(crate) fn cq(&self) -> &CompletionQueue {
         &self.cq
     }
+
+    pub(crate) fn interceptors(&self) -> &ClientInterceptors {
+        &self.interceptors
+    }
+
+    pub(crate) fn metrics(&self) -> Option<&ChannelMetrics> {
+        self.metrics.as_ref()
+    }
+
+    #[cfg(feature = "secure")]
+    pub(crate) fn rejection(&self) -> Option<&crate::security::Rejection> {
+        self.rejection.as_ref()
+    }
 }
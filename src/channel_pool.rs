@@ -0,0 +1,145 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::call::client::CallOption;
+use crate::call::RpcStatus;
+use crate::channel::{Channel, ConnectivityState};
+use crate::interceptor::ClientInterceptor;
+use crate::server::CheckResult;
+
+/// The policy to choose a channel from a [`ChannelPool`] for every call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickPolicy {
+    /// Use the channels in turn.
+    RoundRobin,
+    /// Use the channel with the fewest calls in flight.
+    LeastOutstanding,
+}
+
+/// The health of a channel in a [`ChannelPool`].
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionHealth {
+    state: ConnectivityState,
+    outstanding_calls: usize,
+}
+
+impl ConnectionHealth {
+    /// Get the connectivity state of the channel.
+    pub fn state(&self) -> ConnectivityState {
+        self.state
+    }
+
+    /// Get the count of the calls that are started but not finished yet.
+    pub fn outstanding_calls(&self) -> usize {
+        self.outstanding_calls
+    }
+}
+
+/// Counts the calls in flight of a channel.
+///
+/// It should be the first interceptor of the channel, so that every call it
+/// counts is guaranteed to be finished.
+pub(crate) struct CallCounter(pub Arc<AtomicUsize>);
+
+impl ClientInterceptor for CallCounter {
+    fn intercept(&self, _: &str, _: &mut CallOption) -> CheckResult {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        CheckResult::Continue
+    }
+
+    fn on_finish(&self, _: &str, _: &RpcStatus, _: Duration) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Shares an interceptor among the channels of a pool.
+pub(crate) struct SharedInterceptor(pub Arc<dyn ClientInterceptor>);
+
+impl ClientInterceptor for SharedInterceptor {
+    fn intercept(&self, method: &str, opt: &mut CallOption) -> CheckResult {
+        self.0.intercept(method, opt)
+    }
+
+    fn on_finish(&self, method: &str, status: &RpcStatus, elapsed: Duration) {
+        self.0.on_finish(method, status, elapsed)
+    }
+}
+
+struct PoolInner {
+    channels: Vec<Channel>,
+    outstanding: Vec<Arc<AtomicUsize>>,
+    policy: PickPolicy,
+    next: AtomicUsize,
+}
+
+/// A set of channels to the same target, each of which owns its connections.
+///
+/// A single channel multiplexes all calls on one HTTP/2 connection, which can be a
+/// bottleneck under high load. A pool spreads calls across several connections.
+///
+/// Use [`ChannelBuilder::connect_pool`] to build a [`ChannelPool`], and
+/// [`Client::with_pool`] to make calls on it.
+///
+/// [`ChannelBuilder::connect_pool`]: crate::ChannelBuilder::connect_pool
+/// [`Client::with_pool`]: crate::Client::with_pool
+#[derive(Clone)]
+pub struct ChannelPool {
+    inner: Arc<PoolInner>,
+}
+
+impl ChannelPool {
+    pub(crate) fn new(
+        channels: Vec<Channel>,
+        outstanding: Vec<Arc<AtomicUsize>>,
+        policy: PickPolicy,
+    ) -> ChannelPool {
+        assert!(!channels.is_empty(), "a channel pool can't be empty");
+        ChannelPool {
+            inner: Arc::new(PoolInner {
+                channels,
+                outstanding,
+                policy,
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Get all the channels in the pool.
+    pub fn channels(&self) -> &[Channel] {
+        &self.inner.channels
+    }
+
+    /// Choose a channel for a new call according to the policy.
+    pub fn pick(&self) -> &Channel {
+        let inner = &*self.inner;
+        let len = inner.channels.len();
+        let start = inner.next.fetch_add(1, Ordering::Relaxed) % len;
+        let idx = match inner.policy {
+            PickPolicy::RoundRobin => start,
+            // Scans from a rotating start so that ties are not always broken in
+            // favor of the first channel.
+            PickPolicy::LeastOutstanding => (start..len + start)
+                .map(|i| i % len)
+                .min_by_key(|i| inner.outstanding[*i].load(Ordering::Relaxed))
+                .unwrap(),
+        };
+        &inner.channels[idx]
+    }
+
+    /// Get the health of every channel in the pool, in the same order as
+    /// [`ChannelPool::channels`].
+    pub fn health(&self) -> Vec<ConnectionHealth> {
+        self.inner
+            .channels
+            .iter()
+            .zip(&self.inner.outstanding)
+            .map(|(ch, outstanding)| ConnectionHealth {
+                state: ch.check_connectivity_state(false),
+                outstanding_calls: outstanding.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
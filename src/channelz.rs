@@ -0,0 +1,607 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Introspection of the channels and servers of the process, which is based on
+//! the [channelz] data collected by gRPC Core.
+//!
+//! Channels, subchannels, servers and sockets are identified by ids. Top
+//! channels and servers can be listed by [`get_top_channels`] and [`get_servers`],
+//! then their children can be looked up by the ids referred by them. The ids of a
+//! [`Channel`] or [`Server`] are available if channelz is enabled by
+//! [`ChannelBuilder::enable_channelz`] or [`ServerBuilder::enable_channelz`].
+//!
+//! The structs mirror the messages of `grpc/channelz/v1/channelz.proto`.
+//!
+//! [channelz]: https://github.com/grpc/proposal/blob/master/A14-channelz.md
+//! [`Channel`]: crate::Channel
+//! [`Server`]: crate::Server
+//! [`ChannelBuilder::enable_channelz`]: crate::ChannelBuilder::enable_channelz
+//! [`ServerBuilder::enable_channelz`]: crate::ServerBuilder::enable_channelz
+
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::raw::c_char;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::channel::ConnectivityState;
+use crate::grpc_sys;
+use crate::json::{self, Value};
+use crate::util::{base64_decode, days_from_civil, Base64};
+
+/// The severity of a trace event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceSeverity {
+    Unknown,
+    Info,
+    Warning,
+    Error,
+}
+
+// Deriving it needs `#[default]`, which requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for TraceSeverity {
+    fn default() -> TraceSeverity {
+        TraceSeverity::Unknown
+    }
+}
+
+/// An event of interest happened to a channel, subchannel or server.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraceEvent {
+    pub description: String,
+    pub severity: TraceSeverity,
+    pub timestamp: Option<SystemTime>,
+    /// The id of the channel that the event is about, like a created child.
+    pub channel_ref: Option<i64>,
+    /// The id of the subchannel that the event is about.
+    pub subchannel_ref: Option<i64>,
+}
+
+/// The recent trace events of an entity.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelTrace {
+    /// The number of events ever logged, which may be larger than the number of
+    /// events kept.
+    pub num_events_logged: i64,
+    pub creation_timestamp: Option<SystemTime>,
+    pub events: Vec<TraceEvent>,
+}
+
+/// The statistics of a channel or subchannel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelData {
+    pub state: Option<ConnectivityState>,
+    pub target: String,
+    pub trace: Option<ChannelTrace>,
+    pub calls_started: i64,
+    pub calls_succeeded: i64,
+    pub calls_failed: i64,
+    pub last_call_started_timestamp: Option<SystemTime>,
+}
+
+/// A reference to a socket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SocketRef {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A channel or subchannel.
+///
+/// A channel owns subchannels, each of which owns the sockets connected to an
+/// address.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelInfo {
+    pub id: i64,
+    pub data: ChannelData,
+    /// The ids of the child channels.
+    pub channel_refs: Vec<i64>,
+    /// The ids of the child subchannels.
+    pub subchannel_refs: Vec<i64>,
+    /// The sockets of a subchannel. It's always empty for channels.
+    pub socket_refs: Vec<SocketRef>,
+}
+
+/// The statistics of a server.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerData {
+    pub trace: Option<ChannelTrace>,
+    pub calls_started: i64,
+    pub calls_succeeded: i64,
+    pub calls_failed: i64,
+    pub last_call_started_timestamp: Option<SystemTime>,
+}
+
+/// A server.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerInfo {
+    pub id: i64,
+    pub data: ServerData,
+    pub listen_sockets: Vec<SocketRef>,
+}
+
+/// The statistics of a socket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SocketData {
+    pub streams_started: i64,
+    pub streams_succeeded: i64,
+    pub streams_failed: i64,
+    pub messages_sent: i64,
+    pub messages_received: i64,
+    pub keep_alives_sent: i64,
+    pub last_local_stream_created_timestamp: Option<SystemTime>,
+    pub last_remote_stream_created_timestamp: Option<SystemTime>,
+    pub last_message_sent_timestamp: Option<SystemTime>,
+    pub last_message_received_timestamp: Option<SystemTime>,
+    pub local_flow_control_window: Option<i64>,
+    pub remote_flow_control_window: Option<i64>,
+}
+
+/// The address of a socket.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Address {
+    Tcp(SocketAddr),
+    /// The path of a unix domain socket.
+    Uds(String),
+    Other(String),
+}
+
+/// The TLS information of a socket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TlsSecurity {
+    /// The cipher suite name in the RFC 4346 format.
+    pub standard_name: Option<String>,
+    /// The cipher suite name that has no standard name.
+    pub other_name: Option<String>,
+    /// The DER encoded certificate sent to the peer.
+    pub local_certificate: Vec<u8>,
+    /// The DER encoded certificate received from the peer.
+    pub remote_certificate: Vec<u8>,
+}
+
+/// The security information of a socket.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Security {
+    Tls(TlsSecurity),
+    /// The name of a security model other than TLS.
+    Other(String),
+}
+
+/// A connection, or a listener of a server.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SocketInfo {
+    pub id: i64,
+    pub name: String,
+    pub data: SocketData,
+    pub local: Option<Address>,
+    pub remote: Option<Address>,
+    pub security: Option<Security>,
+    /// The target name of the peer, which is only set for client sockets.
+    pub remote_name: String,
+}
+
+/// Converts an id returned by gRPC Core, which is 0 if it's unknown.
+pub(crate) fn id(id: isize) -> Option<i64> {
+    match id {
+        0 => None,
+        id => Some(id as i64),
+    }
+}
+
+/// Calls a channelz function of gRPC Core and parses the returned JSON.
+fn query(f: impl FnOnce() -> *mut c_char) -> Option<Value> {
+    unsafe {
+        // The functions may be called before any `Environment` is created.
+        grpc_sys::grpc_init();
+        let p = f();
+        let res = if p.is_null() {
+            None
+        } else {
+            let s = CStr::from_ptr(p).to_string_lossy();
+            let v = json::parse(&s);
+            grpc_sys::gpr_free(p as _);
+            match v {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    error!("failed to parse channelz data: {}", e);
+                    None
+                }
+            }
+        };
+        grpc_sys::grpc_shutdown();
+        res
+    }
+}
+
+fn page<T>(v: Option<Value>, key: &str, parse: impl Fn(&Value) -> T) -> (Vec<T>, bool) {
+    let v = match v {
+        Some(v) => v,
+        None => return (vec![], true),
+    };
+    let items = v
+        .get(key)
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+        .iter()
+        .map(parse)
+        .collect();
+    (items, v.get("end") == Some(&Value::Bool(true)))
+}
+
+/// Get the top channels whose ids are not less than `start_channel_id`.
+///
+/// Returns the channels in the order of ids, and whether there are no more
+/// channels. At most 100 channels are returned at a time.
+pub fn get_top_channels(start_channel_id: i64) -> (Vec<ChannelInfo>, bool) {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_top_channels(start_channel_id as _) });
+    page(v, "channel", |c| parse_channel(c, "channelId"))
+}
+
+/// Get the servers whose ids are not less than `start_server_id`.
+///
+/// Returns the servers in the order of ids, and whether there are no more
+/// servers. At most 100 servers are returned at a time.
+pub fn get_servers(start_server_id: i64) -> (Vec<ServerInfo>, bool) {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_servers(start_server_id as _) });
+    page(v, "server", parse_server)
+}
+
+/// Get the server by id.
+pub fn get_server(server_id: i64) -> Option<ServerInfo> {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_server(server_id as _) })?;
+    v.get("server").map(parse_server)
+}
+
+/// Get the sockets of a server whose ids are not less than `start_socket_id`.
+///
+/// Returns the references in the order of ids, and whether there are no more
+/// sockets. If `max_results` is not positive, at most 100 references are returned.
+/// Returns `None` if the server is not found.
+pub fn get_server_sockets(
+    server_id: i64,
+    start_socket_id: i64,
+    max_results: i64,
+) -> Option<(Vec<SocketRef>, bool)> {
+    let v = query(|| unsafe {
+        grpc_sys::grpc_channelz_get_server_sockets(
+            server_id as _,
+            start_socket_id as _,
+            max_results as _,
+        )
+    })?;
+    Some(page(Some(v), "socketRef", parse_socket_ref))
+}
+
+/// Get the channel by id.
+pub fn get_channel(channel_id: i64) -> Option<ChannelInfo> {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_channel(channel_id as _) })?;
+    v.get("channel").map(|c| parse_channel(c, "channelId"))
+}
+
+/// Get the subchannel by id.
+pub fn get_subchannel(subchannel_id: i64) -> Option<ChannelInfo> {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_subchannel(subchannel_id as _) })?;
+    v.get("subchannel")
+        .map(|c| parse_channel(c, "subchannelId"))
+}
+
+/// Get the socket by id.
+pub fn get_socket(socket_id: i64) -> Option<SocketInfo> {
+    let v = query(|| unsafe { grpc_sys::grpc_channelz_get_socket(socket_id as _) })?;
+    v.get("socket").map(parse_socket)
+}
+
+/// Gets an integer, which is encoded as a string if it's int64.
+fn int(v: &Value, key: &str) -> i64 {
+    match v.get(key) {
+        Some(Value::Number(n)) => *n as i64,
+        Some(Value::String(s)) => s.parse().unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+fn string(v: &Value, key: &str) -> String {
+    v.get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+fn array<'a>(v: &'a Value, key: &str) -> &'a [Value] {
+    v.get(key).and_then(Value::as_array).unwrap_or_default()
+}
+
+/// Gets a field by its JSON name or its original name.
+fn field<'a>(v: &'a Value, json_name: &str, name: &str) -> Option<&'a Value> {
+    v.get(json_name).or_else(|| v.get(name))
+}
+
+fn bytes(v: &Value, json_name: &str, name: &str) -> Vec<u8> {
+    field(v, json_name, name)
+        .and_then(Value::as_str)
+        .and_then(|s| base64_decode(s, Base64::Standard))
+        .unwrap_or_default()
+}
+
+/// Parses a timestamp in the RFC 3339 format, like `2021-05-01T08:00:00.5Z`.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let num = |s: &str| -> Option<i64> {
+        if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    };
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = (s.get(..10)?, s.get(11..)?);
+    if s.as_bytes()[10] != b'T' {
+        return None;
+    }
+    let (year, month, day) = (
+        num(date.get(..4)?)?,
+        num(date.get(5..7)?)?,
+        num(date.get(8..)?)?,
+    );
+    let (hms, nanos) = match time.find('.') {
+        Some(pos) => {
+            let frac = time.get(pos + 1..)?;
+            if frac.len() > 9 {
+                return None;
+            }
+            (&time[..pos], num(frac)? * 10i64.pow(9 - frac.len() as u32))
+        }
+        None => (time, 0),
+    };
+    let (hour, minute, second) = (
+        num(hms.get(..2)?)?,
+        num(hms.get(3..5)?)?,
+        num(hms.get(6..)?)?,
+    );
+    let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    if secs < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::new(secs as u64, nanos as u32))
+}
+
+fn timestamp(v: &Value, key: &str) -> Option<SystemTime> {
+    v.get(key).and_then(Value::as_str).and_then(parse_timestamp)
+}
+
+fn parse_trace(v: &Value) -> ChannelTrace {
+    let events = array(v, "events")
+        .iter()
+        .map(|e| TraceEvent {
+            description: string(e, "description"),
+            severity: match e.get("severity").and_then(Value::as_str) {
+                Some("CT_INFO") => TraceSeverity::Info,
+                Some("CT_WARNING") => TraceSeverity::Warning,
+                Some("CT_ERROR") => TraceSeverity::Error,
+                _ => TraceSeverity::Unknown,
+            },
+            timestamp: timestamp(e, "timestamp"),
+            channel_ref: e.get("channelRef").map(|r| int(r, "channelId")),
+            subchannel_ref: e.get("subchannelRef").map(|r| int(r, "subchannelId")),
+        })
+        .collect();
+    ChannelTrace {
+        num_events_logged: int(v, "numEventsLogged"),
+        creation_timestamp: timestamp(v, "creationTimestamp"),
+        events,
+    }
+}
+
+fn parse_state(v: &Value) -> Option<ConnectivityState> {
+    let state = match v.get("state")?.as_str()? {
+        "IDLE" => ConnectivityState::GRPC_CHANNEL_IDLE,
+        "CONNECTING" => ConnectivityState::GRPC_CHANNEL_CONNECTING,
+        "READY" => ConnectivityState::GRPC_CHANNEL_READY,
+        "TRANSIENT_FAILURE" => ConnectivityState::GRPC_CHANNEL_TRANSIENT_FAILURE,
+        "SHUTDOWN" => ConnectivityState::GRPC_CHANNEL_SHUTDOWN,
+        _ => return None,
+    };
+    Some(state)
+}
+
+fn parse_socket_ref(v: &Value) -> SocketRef {
+    SocketRef {
+        id: int(v, "socketId"),
+        name: string(v, "name"),
+    }
+}
+
+/// Parses a channel or subchannel, whose id is named by `id_key` in the reference.
+fn parse_channel(v: &Value, id_key: &str) -> ChannelInfo {
+    let empty = Value::Object(vec![]);
+    let data = v.get("data").unwrap_or(&empty);
+    ChannelInfo {
+        id: v.get("ref").map_or(0, |r| int(r, id_key)),
+        data: ChannelData {
+            state: data.get("state").and_then(parse_state),
+            target: string(data, "target"),
+            trace: data.get("trace").map(parse_trace),
+            calls_started: int(data, "callsStarted"),
+            calls_succeeded: int(data, "callsSucceeded"),
+            calls_failed: int(data, "callsFailed"),
+            last_call_started_timestamp: timestamp(data, "lastCallStartedTimestamp"),
+        },
+        channel_refs: array(v, "channelRef")
+            .iter()
+            .map(|r| int(r, "channelId"))
+            .collect(),
+        subchannel_refs: array(v, "subchannelRef")
+            .iter()
+            .map(|r| int(r, "subchannelId"))
+            .collect(),
+        socket_refs: array(v, "socketRef").iter().map(parse_socket_ref).collect(),
+    }
+}
+
+fn parse_server(v: &Value) -> ServerInfo {
+    let empty = Value::Object(vec![]);
+    let data = v.get("data").unwrap_or(&empty);
+    ServerInfo {
+        id: v.get("ref").map_or(0, |r| int(r, "serverId")),
+        data: ServerData {
+            trace: data.get("trace").map(parse_trace),
+            calls_started: int(data, "callsStarted"),
+            calls_succeeded: int(data, "callsSucceeded"),
+            calls_failed: int(data, "callsFailed"),
+            last_call_started_timestamp: timestamp(data, "lastCallStartedTimestamp"),
+        },
+        listen_sockets: array(v, "listenSocket")
+            .iter()
+            .map(parse_socket_ref)
+            .collect(),
+    }
+}
+
+fn parse_address(v: &Value) -> Option<Address> {
+    if let Some(addr) = field(v, "tcpipAddress", "tcpip_address") {
+        let ip = bytes(addr, "ipAddress", "ip_address");
+        let ip = match ip.len() {
+            4 => IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3])),
+            16 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(&ip);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return None,
+        };
+        return Some(Address::Tcp(SocketAddr::new(ip, int(addr, "port") as u16)));
+    }
+    if let Some(addr) = field(v, "udsAddress", "uds_address") {
+        return Some(Address::Uds(string(addr, "filename")));
+    }
+    field(v, "otherAddress", "other_address").map(|addr| Address::Other(string(addr, "name")))
+}
+
+fn parse_security(v: &Value) -> Option<Security> {
+    if let Some(tls) = v.get("tls") {
+        let name = |json_name, name| {
+            field(tls, json_name, name)
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        };
+        return Some(Security::Tls(TlsSecurity {
+            standard_name: name("standardName", "standard_name"),
+            other_name: name("otherName", "other_name"),
+            local_certificate: bytes(tls, "localCertificate", "local_certificate"),
+            remote_certificate: bytes(tls, "remoteCertificate", "remote_certificate"),
+        }));
+    }
+    v.get("other")
+        .map(|other| Security::Other(string(other, "name")))
+}
+
+fn parse_socket(v: &Value) -> SocketInfo {
+    let empty = Value::Object(vec![]);
+    let r = v.get("ref").unwrap_or(&empty);
+    let data = v.get("data").unwrap_or(&empty);
+    // Flow control windows are wrapped by `google.protobuf.Int64Value`.
+    let window = |key| {
+        data.get(key).map(|w| match w {
+            Value::Object(_) => int(w, "value"),
+            _ => int(data, key),
+        })
+    };
+    SocketInfo {
+        id: int(r, "socketId"),
+        name: string(r, "name"),
+        data: SocketData {
+            streams_started: int(data, "streamsStarted"),
+            streams_succeeded: int(data, "streamsSucceeded"),
+            streams_failed: int(data, "streamsFailed"),
+            messages_sent: int(data, "messagesSent"),
+            messages_received: int(data, "messagesReceived"),
+            keep_alives_sent: int(data, "keepAlivesSent"),
+            last_local_stream_created_timestamp: timestamp(data, "lastLocalStreamCreatedTimestamp"),
+            last_remote_stream_created_timestamp: timestamp(
+                data,
+                "lastRemoteStreamCreatedTimestamp",
+            ),
+            last_message_sent_timestamp: timestamp(data, "lastMessageSentTimestamp"),
+            last_message_received_timestamp: timestamp(data, "lastMessageReceivedTimestamp"),
+            local_flow_control_window: window("localFlowControlWindow"),
+            remote_flow_control_window: window("remoteFlowControlWindow"),
+        },
+        local: v.get("local").and_then(parse_address),
+        remote: v.get("remote").and_then(parse_address),
+        security: v.get("security").and_then(parse_security),
+        remote_name: string(v, "remoteName"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let secs = |s| {
+            parse_timestamp(s).map(|t| {
+                let d = t.duration_since(UNIX_EPOCH).unwrap();
+                (d.as_secs(), d.subsec_nanos())
+            })
+        };
+        assert_eq!(secs("1970-01-01T00:00:00Z"), Some((0, 0)));
+        assert_eq!(secs("2021-05-01T08:09:10Z"), Some((1_619_856_550, 0)));
+        assert_eq!(
+            secs("2000-02-29T23:59:59.5Z"),
+            Some((951_868_799, 500_000_000))
+        );
+        assert_eq!(
+            secs("2021-05-01T08:09:10.000000001Z"),
+            Some((1_619_856_550, 1))
+        );
+        for invalid in &[
+            "",
+            "2021-05-01T08:09:10",
+            "2021-05-01 08:09:10Z",
+            "2021-05-01T08:09:10.Z",
+            "2021-05-01T08:09:10.0000000001Z",
+            "1969-12-31T23:59:59Z",
+            "2021-0a-01T08:09:10Z",
+        ] {
+            assert_eq!(secs(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_socket() {
+        let v = json::parse(
+            r#"{
+                "ref": {"socketId": "12", "name": "test"},
+                "data": {"streamsStarted": "3", "messagesSent": "5", "keepAlivesSent": 1,
+                         "lastMessageSentTimestamp": "1970-01-01T00:00:01Z",
+                         "localFlowControlWindow": {"value": "65535"}},
+                "local": {"tcpip_address": {"ip_address": "fwAAAQ==", "port": 50051}},
+                "remote": {"uds_address": {"filename": "/tmp/sock"}},
+                "security": {"tls": {"standard_name": "TLS_AES_128_GCM_SHA256",
+                                     "remote_certificate": "AAEC"}}
+            }"#,
+        )
+        .unwrap();
+        let socket = parse_socket(&v);
+        assert_eq!(socket.id, 12);
+        assert_eq!(socket.name, "test");
+        assert_eq!(socket.data.streams_started, 3);
+        assert_eq!(socket.data.messages_sent, 5);
+        assert_eq!(socket.data.keep_alives_sent, 1);
+        assert_eq!(
+            socket.data.last_message_sent_timestamp,
+            Some(UNIX_EPOCH + Duration::from_secs(1))
+        );
+        assert_eq!(socket.data.local_flow_control_window, Some(65535));
+        assert_eq!(socket.data.remote_flow_control_window, None);
+        assert_eq!(
+            socket.local,
+            Some(Address::Tcp("127.0.0.1:50051".parse().unwrap()))
+        );
+        assert_eq!(socket.remote, Some(Address::Uds("/tmp/sock".to_owned())));
+        let tls = TlsSecurity {
+            standard_name: Some("TLS_AES_128_GCM_SHA256".to_owned()),
+            remote_certificate: vec![0, 1, 2],
+            ..Default::default()
+        };
+        assert_eq!(socket.security, Some(Security::Tls(tls)));
+    }
+}
@@ -6,6 +6,7 @@ use crate::call::client::{
 };
 use crate::call::{Call, Method};
 use crate::channel::Channel;
+use crate::channel_pool::ChannelPool;
 use crate::error::Result;
 use crate::task::Executor;
 use crate::task::Kicker;
@@ -16,6 +17,8 @@ use futures::Future;
 #[derive(Clone)]
 pub struct Client {
     channel: Channel,
+    // Calls are spread across the channels of the pool if it's given.
+    pool: Option<ChannelPool>,
     // Used to kick its completion queue.
     kicker: Kicker,
 }
@@ -24,7 +27,30 @@ impl Client {
     /// Initialize a new [`Client`].
     pub fn new(channel: Channel) -> Client {
         let kicker = channel.create_kicker().unwrap();
-        Client { channel, kicker }
+        Client {
+            channel,
+            pool: None,
+            kicker,
+        }
+    }
+
+    /// Initialize a new [`Client`] that makes every call on a channel picked from
+    /// the pool.
+    pub fn with_pool(pool: ChannelPool) -> Client {
+        let channel = pool.channels()[0].clone();
+        let kicker = channel.create_kicker().unwrap();
+        Client {
+            channel,
+            pool: Some(pool),
+            kicker,
+        }
+    }
+
+    fn pick_channel(&self) -> &Channel {
+        match &self.pool {
+            Some(pool) => pool.pick(),
+            None => &self.channel,
+        }
     }
 
     /// Create a synchronized unary RPC call.
@@ -47,7 +73,7 @@ impl Client {
         req: &Req,
         opt: CallOption,
     ) -> Result<ClientUnaryReceiver<Resp>> {
-        Call::unary_async(&self.channel, method, req, opt)
+        Call::unary_async(self.pick_channel(), method, req, opt)
     }
 
     /// Create an asynchronized client streaming call.
@@ -58,7 +84,7 @@ impl Client {
         method: &Method<Req, Resp>,
         opt: CallOption,
     ) -> Result<(ClientCStreamSender<Req>, ClientCStreamReceiver<Resp>)> {
-        Call::client_streaming(&self.channel, method, opt)
+        Call::client_streaming(self.pick_channel(), method, opt)
     }
 
     /// Create an asynchronized server streaming call.
@@ -70,7 +96,7 @@ impl Client {
         req: &Req,
         opt: CallOption,
     ) -> Result<ClientSStreamReceiver<Resp>> {
-        Call::server_streaming(&self.channel, method, req, opt)
+        Call::server_streaming(self.pick_channel(), method, req, opt)
     }
 
     /// Create an asynchronized duplex streaming call.
@@ -83,7 +109,7 @@ impl Client {
         method: &Method<Req, Resp>,
         opt: CallOption,
     ) -> Result<(ClientDuplexSender<Req>, ClientDuplexReceiver<Resp>)> {
-        Call::duplex_streaming(&self.channel, method, opt)
+        Call::duplex_streaming(self.pick_channel(), method, opt)
     }
 
     /// Spawn the future into current gRPC poll thread.
@@ -85,6 +85,9 @@ impl EnvBuilder {
 
     /// Finalize the [`EnvBuilder`], build the [`Environment`] and initialize the gRPC library.
     pub fn build(self) -> Environment {
+        // Handshakers are registered when grpc is initialized.
+        #[cfg(feature = "secure")]
+        crate::security::register_peer_checks();
         unsafe {
             grpc_sys::grpc_init();
         }
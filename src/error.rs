@@ -33,6 +33,14 @@ pub enum Error {
     GoogleAuthenticationFailed,
     /// Invalid format of metadata.
     InvalidMetadata(String),
+    /// Invalid service config.
+    InvalidServiceConfig(String),
+    /// Invalid authorization policy.
+    InvalidAuthorizationPolicy(String),
+    /// Invalid JSON Web Key set.
+    InvalidJwkSet(String),
+    /// The feature isn't supported by the linked gRPC core.
+    Unsupported(String),
 }
 
 impl fmt::Display for Error {
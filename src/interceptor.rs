@@ -0,0 +1,469 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+
+use crate::auth_context::AuthContext;
+use crate::call::client::CallOption;
+use crate::call::server::{Deadline, RequestContext};
+use crate::call::{MethodType, RpcStatus, RpcStatusCode};
+use crate::channel::Channel;
+use crate::error::{Error, Result};
+use crate::metadata::{Metadata, MetadataBuilder};
+use crate::metrics::{CallMetrics, ClientCallMetrics};
+use crate::server::CheckResult;
+#[cfg(feature = "tracing")]
+use crate::trace::{self, TraceContext};
+
+/// An interceptor that wraps the handling of every incoming call.
+///
+/// Unlike [`ServerChecker`], an interceptor can wait for asynchronous work before
+/// the call is dispatched, modify the request headers, add response metadata and
+/// observe how the call finishes.
+///
+/// [`ServerChecker`]: crate::ServerChecker
+pub trait ServerInterceptor: Send + Sync {
+    /// Called before the call is dispatched to its handler.
+    ///
+    /// If it returns `Abort`, the subsequent interceptors and the handler are skipped
+    /// and the call is aborted with the given status. Response metadata added by
+    /// the previous interceptors is still sent along with the status.
+    fn intercept<'a>(&'a self, ctx: &'a mut InterceptContext) -> BoxFuture<'a, CheckResult>;
+
+    /// Called when the status of the call has been sent to the client, with the
+    /// time elapsed since the call was intercepted.
+    ///
+    /// All interceptors are notified even if the call is aborted by a previous one.
+    /// If the handler drops the sink without sending any status, or the status
+    /// fails to be sent, `CANCELLED` is reported.
+    fn on_finish(&self, _method: &[u8], _status: &RpcStatus, _elapsed: Duration) {}
+}
+
+pub(crate) type ServerInterceptors = Arc<Vec<Box<dyn ServerInterceptor>>>;
+
+/// A map of values keyed by their types, which is used by [`ServerInterceptor`]s
+/// to pass values like the authenticated identity to handlers.
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl Extensions {
+    /// Insert a value, and returns the previous value of the same type.
+    pub fn insert<T: Any + Send>(&mut self, val: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(val))
+            .and_then(|v| v.downcast().ok().map(|v| *v))
+    }
+
+    /// Get the value of type `T`.
+    pub fn get<T: Any + Send>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|v| v.downcast_ref())
+    }
+
+    /// Get the mutable reference to the value of type `T`.
+    pub fn get_mut<T: Any + Send>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|v| v.downcast_mut())
+    }
+
+    /// Remove the value of type `T`.
+    pub fn remove<T: Any + Send>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|v| v.downcast().ok().map(|v| *v))
+    }
+}
+
+/// The context of an incoming call that is visible to [`ServerInterceptor`]s.
+pub struct InterceptContext {
+    ctx: RequestContext,
+    request_headers: Option<Metadata>,
+    extensions: Extensions,
+    response_headers: MetadataBuilder,
+    response_trailers: MetadataBuilder,
+}
+
+impl InterceptContext {
+    pub(crate) fn new(ctx: RequestContext) -> InterceptContext {
+        InterceptContext {
+            ctx,
+            request_headers: None,
+            extensions: Extensions::default(),
+            response_headers: MetadataBuilder::new(),
+            response_trailers: MetadataBuilder::new(),
+        }
+    }
+
+    pub fn method(&self) -> &[u8] {
+        self.ctx.method()
+    }
+
+    pub fn host(&self) -> &[u8] {
+        self.ctx.host()
+    }
+
+    pub fn deadline(&self) -> Deadline {
+        self.ctx.deadline()
+    }
+
+    pub fn peer(&self) -> String {
+        self.ctx.peer()
+    }
+
+    /// Wrapper around the gRPC Core AuthContext
+    ///
+    /// If the server binds in non-secure mode, this will return None
+    pub fn auth_context(&self) -> Option<AuthContext> {
+        self.ctx.auth_context()
+    }
+
+    /// Get the initial metadata sent by client.
+    pub fn request_headers(&self) -> &Metadata {
+        match &self.request_headers {
+            Some(headers) => headers,
+            None => self.ctx.metadata(),
+        }
+    }
+
+    /// Replace the request headers seen by the subsequent interceptors and the handler.
+    pub fn set_request_headers(&mut self, headers: Metadata) {
+        self.request_headers = Some(headers);
+    }
+
+    /// Get the values passed to the subsequent interceptors and the handler.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Get the mutable values passed to the subsequent interceptors and the handler.
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    /// Get the builder of the headers to be sent to client.
+    ///
+    /// The entries are sent before any headers set by the handler.
+    pub fn response_headers(&mut self) -> &mut MetadataBuilder {
+        &mut self.response_headers
+    }
+
+    /// Get the builder of the trailers to be sent to client along with the status.
+    ///
+    /// The entries are sent before any trailers set by the handler.
+    pub fn response_trailers(&mut self) -> &mut MetadataBuilder {
+        &mut self.response_trailers
+    }
+
+    pub(crate) fn request_ctx(&self) -> &RequestContext {
+        &self.ctx
+    }
+
+    /// Moves the response metadata added by interceptors to `hook`.
+    pub(crate) fn take_response_metadata(&mut self, hook: &mut ServerCallHook) {
+        let headers = mem::replace(&mut self.response_headers, MetadataBuilder::new());
+        let trailers = mem::replace(&mut self.response_trailers, MetadataBuilder::new());
+        hook.headers = non_empty(headers.build());
+        hook.trailers = non_empty(trailers.build());
+    }
+
+    pub(crate) fn into_parts(
+        mut self,
+        hook: &mut ServerCallHook,
+    ) -> (RequestContext, Option<Metadata>, Extensions) {
+        self.take_response_metadata(hook);
+        (self.ctx, self.request_headers, self.extensions)
+    }
+}
+
+fn non_empty(meta: Metadata) -> Option<Metadata> {
+    if meta.is_empty() {
+        None
+    } else {
+        Some(meta)
+    }
+}
+
+/// Appends the entries of `meta` to a copy of `base`.
+fn merge(base: Option<&Metadata>, meta: Option<Metadata>) -> Option<Metadata> {
+    match (base, meta) {
+        (None, meta) => meta,
+        (Some(base), None) => Some(base.clone()),
+        (Some(base), Some(meta)) => {
+            let mut builder = MetadataBuilder::from(base.clone());
+            for (k, v) in &meta {
+                builder.add_metadata(k, v);
+            }
+            Some(builder.build())
+        }
+    }
+}
+
+/// Keeps the states of an intercepted call, and notifies interceptors and the
+/// metrics recorder when the call is finished.
+pub(crate) struct ServerCallHook {
+    interceptors: ServerInterceptors,
+    metrics: Option<CallMetrics>,
+    method: Vec<u8>,
+    start: Instant,
+    headers: Option<Metadata>,
+    trailers: Option<Metadata>,
+    status: Option<RpcStatus>,
+    finished: bool,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    trace_context: TraceContext,
+}
+
+impl ServerCallHook {
+    pub fn new(
+        interceptors: ServerInterceptors,
+        metrics: Option<CallMetrics>,
+        ctx: &RequestContext,
+    ) -> ServerCallHook {
+        #[cfg(feature = "tracing")]
+        let (span, trace_context) = trace::server_span(ctx);
+        ServerCallHook {
+            interceptors,
+            metrics,
+            method: ctx.method().to_vec(),
+            start: Instant::now(),
+            headers: None,
+            trailers: None,
+            status: None,
+            finished: false,
+            #[cfg(feature = "tracing")]
+            span,
+            #[cfg(feature = "tracing")]
+            trace_context,
+        }
+    }
+
+    /// Merges the headers added by interceptors with the ones set by handler.
+    pub fn merge_headers(
+        hook: Option<&ServerCallHook>,
+        meta: Option<Metadata>,
+    ) -> Option<Metadata> {
+        merge(hook.and_then(|h| h.headers.as_ref()), meta)
+    }
+
+    /// Merges the trailers added by interceptors with the ones set by handler.
+    pub fn merge_trailers(
+        hook: Option<&ServerCallHook>,
+        meta: Option<Metadata>,
+    ) -> Option<Metadata> {
+        merge(hook.and_then(|h| h.trailers.as_ref()), meta)
+    }
+
+    /// Sets the interceptors to notify, which is done once the call passes all
+    /// checkers.
+    pub fn set_interceptors(&mut self, interceptors: ServerInterceptors) {
+        self.interceptors = interceptors;
+    }
+
+    pub fn metrics(&self) -> Option<&CallMetrics> {
+        self.metrics.as_ref()
+    }
+
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    #[cfg(feature = "tracing")]
+    pub fn trace_context(&self) -> &TraceContext {
+        &self.trace_context
+    }
+
+    /// Remembers the status that is being sent to client.
+    pub fn sending(&mut self, status: &RpcStatus) {
+        self.status = Some(status.clone());
+    }
+
+    /// Notifies interceptors in the reverse order they are executed, `sent`
+    /// indicates whether the status is sent successfully.
+    pub fn finish(mut self, sent: bool) {
+        self.finished = true;
+        match self.status.take() {
+            Some(status) if sent => self.notify(&status),
+            _ => self.notify(&RpcStatus::new(RpcStatusCode::CANCELLED)),
+        }
+    }
+
+    fn notify(&self, status: &RpcStatus) {
+        let elapsed = self.start.elapsed();
+        for i in self.interceptors.iter().rev() {
+            i.on_finish(&self.method, status, elapsed);
+        }
+        if let Some(m) = &self.metrics {
+            m.handled(status.code(), elapsed);
+        }
+        #[cfg(feature = "tracing")]
+        trace::record_status(&self.span, status.code());
+    }
+}
+
+impl Drop for ServerCallHook {
+    fn drop(&mut self) {
+        if !self.finished {
+            // The status may still be in flight if the result of sending it is
+            // dropped before completion.
+            let status = self
+                .status
+                .take()
+                .unwrap_or_else(|| RpcStatus::new(RpcStatusCode::CANCELLED));
+            self.notify(&status);
+        }
+    }
+}
+
+/// An interceptor that wraps every call made on a [`Channel`].
+///
+/// [`Channel`]: crate::Channel
+pub trait ClientInterceptor: Send + Sync {
+    /// Called before the call is started, it can modify the options of the call,
+    /// like headers and timeout.
+    ///
+    /// If it returns `Abort`, the subsequent interceptors are skipped and the call
+    /// is not started at all. Instead the given status is returned to the caller as
+    /// an [`RpcFailure`].
+    ///
+    /// [`RpcFailure`]: crate::Error::RpcFailure
+    fn intercept(&self, method: &str, opt: &mut CallOption) -> CheckResult;
+
+    /// Called when the status of the call is received, with the time elapsed since
+    /// the call was intercepted.
+    ///
+    /// All interceptors are notified even if the call is aborted by a previous one.
+    /// If the call is dropped before its status is received, `CANCELLED` is reported.
+    fn on_finish(&self, _method: &str, _status: &RpcStatus, _elapsed: Duration) {}
+}
+
+pub(crate) type ClientInterceptors = Arc<Vec<Box<dyn ClientInterceptor>>>;
+
+/// Notifies client interceptors and the metrics recorder of the channel when
+/// the call is finished.
+pub(crate) struct ClientCallHook {
+    interceptors: ClientInterceptors,
+    metrics: Option<ClientCallMetrics>,
+    method: &'static str,
+    start: Instant,
+    finished: bool,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "secure")]
+    rejection: Option<crate::security::Rejection>,
+}
+
+impl ClientCallHook {
+    /// Runs the interceptors of the channel in order.
+    ///
+    /// `None` is returned if there is neither interceptor nor metrics recorder, the
+    /// credentials don't check the server, and `tracing` feature is disabled.
+    pub fn intercept(
+        channel: &Channel,
+        method: &'static str,
+        method_type: MethodType,
+        opt: &mut CallOption,
+    ) -> Result<Option<ClientCallHook>> {
+        let interceptors = channel.interceptors();
+        let metrics = channel.metrics().map(|m| m.start(method, method_type));
+        #[cfg(feature = "secure")]
+        let rejection = channel.rejection().cloned();
+        #[cfg(not(feature = "secure"))]
+        let rejection: Option<()> = None;
+        if interceptors.is_empty()
+            && metrics.is_none()
+            && rejection.is_none()
+            && !cfg!(feature = "tracing")
+        {
+            return Ok(None);
+        }
+        #[cfg(feature = "tracing")]
+        let (span, trace_context) = trace::client_span(method, opt.get_trace_context());
+        let hook = ClientCallHook {
+            interceptors: interceptors.clone(),
+            metrics,
+            method,
+            start: Instant::now(),
+            finished: false,
+            #[cfg(feature = "tracing")]
+            span,
+            #[cfg(feature = "secure")]
+            rejection,
+        };
+        for i in interceptors.iter() {
+            if let CheckResult::Abort(status) = i.intercept(method, opt) {
+                hook.finish(&status);
+                return Err(Error::RpcFailure(status));
+            }
+        }
+        // Interceptors may replace the headers.
+        #[cfg(feature = "tracing")]
+        opt.inject_trace_context(&trace_context);
+        Ok(Some(hook))
+    }
+
+    /// Notifies interceptors with the status of a finished call, errors that are
+    /// not sent by server are reported as `UNKNOWN`.
+    ///
+    /// If the call fails to connect because the server is rejected, the reason is
+    /// added to the status.
+    pub fn finish_with<T>(hook: &mut Option<ClientCallHook>, res: &mut Result<T>) {
+        let hook = match hook.take() {
+            Some(hook) => hook,
+            None => return,
+        };
+        #[cfg(feature = "secure")]
+        if let (Some(rejection), Err(Error::RpcFailure(status))) = (&hook.rejection, &mut *res) {
+            rejection.amend(status);
+        }
+        match res {
+            Ok(_) => hook.finish(&RpcStatus::ok()),
+            Err(Error::RpcFailure(status)) => hook.finish(status),
+            Err(e) => hook.finish(&RpcStatus::with_message(
+                RpcStatusCode::UNKNOWN,
+                e.to_string(),
+            )),
+        }
+    }
+
+    pub fn metrics(&self) -> Option<&ClientCallMetrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Notifies interceptors in the reverse order they are executed.
+    fn finish(mut self, status: &RpcStatus) {
+        self.finished = true;
+        self.notify(status);
+    }
+
+    fn notify(&self, status: &RpcStatus) {
+        let elapsed = self.start.elapsed();
+        for i in self.interceptors.iter().rev() {
+            i.on_finish(self.method, status, elapsed);
+        }
+        if let Some(m) = &self.metrics {
+            m.completed(status.code(), elapsed);
+        }
+        #[cfg(feature = "tracing")]
+        trace::record_status(&self.span, status.code());
+    }
+}
+
+impl Drop for ClientCallHook {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.notify(&RpcStatus::new(RpcStatusCode::CANCELLED));
+        }
+    }
+}
@@ -0,0 +1,300 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A small JSON reader for the configs consumed by this crate.
+
+use std::str;
+
+/// Nested values deeper than this are rejected to avoid overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Get the value of `key` if it's an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "jwt"), allow(dead_code))]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Value::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, msg: &str) -> Result<T, String> {
+        Err(format!("{} at offset {}", msg, self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), String> {
+        if self.peek() != Some(c) {
+            return self.error(&format!("expect '{}'", c as char));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, lit: &str, value: Value) -> Result<Value, String> {
+        if !self.input[self.pos..].starts_with(lit.as_bytes()) {
+            return self.error("invalid literal");
+        }
+        self.pos += lit.len();
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return self.error("too deep");
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut array = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(array));
+                }
+                loop {
+                    array.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(array));
+                        }
+                        _ => return self.error("expect ',' or ']'"),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut object = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(object));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    object.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(object));
+                        }
+                        _ => return self.error("expect ',' or '}'"),
+                    }
+                }
+            }
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => self.error("unexpected character"),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E')
+        | Some(b'0'..=b'9') = self.peek()
+        {
+            self.pos += 1;
+        }
+        // The input is valid UTF-8 as it comes from a `&str`.
+        let s = str::from_utf8(&self.input[start..self.pos]).unwrap();
+        match s.parse() {
+            Ok(n) => Ok(Value::Number(n)),
+            Err(_) => self.error("invalid number"),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = match self.input.get(self.pos..self.pos + 4) {
+            Some(digits) => digits,
+            None => return self.error("invalid escape"),
+        };
+        let digits = str::from_utf8(digits).map_err(|e| e.to_string())?;
+        match u32::from_str_radix(digits, 16) {
+            Ok(n) => {
+                self.pos += 4;
+                Ok(n)
+            }
+            Err(_) => self.error("invalid escape"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut s = String::new();
+        loop {
+            let start = self.pos;
+            while let Some(c) = self.peek() {
+                if c == b'"' || c == b'\\' || c < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            s.push_str(str::from_utf8(&self.input[start..self.pos]).unwrap());
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                Some(b'\\') => self.pos += 1,
+                _ => return self.error("unterminated string"),
+            }
+            let c = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.pos += 1;
+                    let mut code = self.hex4()?;
+                    if (0xd800..0xdc00).contains(&code)
+                        && self.input[self.pos..].starts_with(b"\\u")
+                    {
+                        self.pos += 2;
+                        let low = self.hex4()?;
+                        code =
+                            0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    }
+                    match std::char::from_u32(code) {
+                        Some(c) => {
+                            s.push(c);
+                            continue;
+                        }
+                        None => return self.error("invalid escape"),
+                    }
+                }
+                _ => return self.error("invalid escape"),
+            };
+            self.pos += 1;
+            s.push(c);
+        }
+    }
+}
+
+/// Parses a JSON document.
+pub(crate) fn parse(input: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != parser.input.len() {
+        return parser.error("trailing characters");
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let v = parse(
+            r#" {"a": [1, -2.5e1, true, false, null], "b": {"c": "x\"\\\/\n\u00e9\ud83d\ude00"}, "d": []} "#,
+        )
+        .unwrap();
+        assert_eq!(
+            v.get("a").unwrap().as_array().unwrap(),
+            &[
+                Value::Number(1.0),
+                Value::Number(-25.0),
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Null
+            ]
+        );
+        let c = v.get("b").unwrap().get("c").unwrap().as_str().unwrap();
+        assert_eq!(c, "x\"\\/\n\u{e9}\u{1f600}");
+        assert_eq!(v.get("d"), Some(&Value::Array(vec![])));
+        assert_eq!(v.get("e"), None);
+        assert_eq!(parse("\"中文\"").unwrap().as_str(), Some("中文"));
+
+        for invalid in &[
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "{a: 1}",
+            "\"abc",
+            "\"\\x\"",
+            "tru",
+            "1 2",
+            "-",
+            "\"\n\"",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+        let deep = "[".repeat(MAX_DEPTH + 2);
+        assert!(parse(&deep).is_err());
+    }
+}
@@ -0,0 +1,611 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Authentication of calls by JSON Web Tokens (JWT) in the `authorization` metadata.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::{self, BoxFuture};
+use ring::{hmac, signature};
+
+use crate::call::{RpcStatus, RpcStatusCode};
+use crate::error::{Error, Result};
+use crate::interceptor::{InterceptContext, ServerInterceptor};
+use crate::json::{self, Value};
+use crate::reloadable::Reloadable;
+use crate::server::CheckResult;
+use crate::util::{base64_decode, Base64};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+/// 9999-12-31T23:59:59Z, the largest time that JWT libraries commonly accept.
+const MAX_TIMESTAMP: f64 = 253_402_300_799.0;
+
+fn invalid<T>(msg: impl Into<String>) -> Result<T> {
+    Err(Error::InvalidJwkSet(msg.into()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Algorithm {
+    Rs256,
+    Es256,
+    Hs256,
+}
+
+impl Algorithm {
+    fn from_name(name: &str) -> Option<Algorithm> {
+        match name {
+            "RS256" => Some(Algorithm::Rs256),
+            "ES256" => Some(Algorithm::Es256),
+            "HS256" => Some(Algorithm::Hs256),
+            _ => None,
+        }
+    }
+}
+
+enum KeyData {
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    // The uncompressed point of a P-256 public key.
+    Ec(Vec<u8>),
+    Oct(hmac::Key),
+}
+
+struct Jwk {
+    kid: Option<String>,
+    alg: Algorithm,
+    data: KeyData,
+}
+
+impl Jwk {
+    /// Returns `None` if the key is not for the supported signature algorithms.
+    fn from_json(value: &Value) -> Result<Option<Jwk>> {
+        let field = |name: &str| -> Result<Option<&str>> {
+            match value.get(name) {
+                None => Ok(None),
+                Some(v) => match v.as_str() {
+                    Some(s) => Ok(Some(s)),
+                    None => invalid(format!("{} must be a string", name)),
+                },
+            }
+        };
+        let bytes = |name: &str| -> Result<Vec<u8>> {
+            match field(name)?.map(|s| base64_decode(s, Base64::UrlNoPad)) {
+                Some(Some(b)) if !b.is_empty() => Ok(b),
+                _ => invalid(format!("{} must be a non-empty base64url string", name)),
+            }
+        };
+        if value.as_object().is_none() {
+            return invalid("key must be an object");
+        }
+        if matches!(field("use")?, Some(u) if u != "sig") {
+            return Ok(None);
+        }
+        let (alg, data) = match field("kty")? {
+            Some("RSA") => {
+                let mut n = bytes("n")?;
+                // The modulus is unsigned, leading zeros are not expected by ring.
+                let zeros = n.iter().take_while(|b| **b == 0).count();
+                n.drain(..zeros);
+                (Algorithm::Rs256, KeyData::Rsa { n, e: bytes("e")? })
+            }
+            Some("EC") => {
+                if field("crv")? != Some("P-256") {
+                    return Ok(None);
+                }
+                let (x, y) = (bytes("x")?, bytes("y")?);
+                if x.len() != 32 || y.len() != 32 {
+                    return invalid("x and y of a P-256 key must be 32 bytes");
+                }
+                let mut point = vec![4];
+                point.extend_from_slice(&x);
+                point.extend_from_slice(&y);
+                (Algorithm::Es256, KeyData::Ec(point))
+            }
+            Some("oct") => (
+                Algorithm::Hs256,
+                KeyData::Oct(hmac::Key::new(hmac::HMAC_SHA256, &bytes("k")?)),
+            ),
+            Some(_) => return Ok(None),
+            None => return invalid("kty is required"),
+        };
+        if let Some(name) = field("alg")? {
+            if Algorithm::from_name(name) != Some(alg) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(Jwk {
+            kid: field("kid")?.map(ToOwned::to_owned),
+            alg,
+            data,
+        }))
+    }
+
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
+        match &self.data {
+            KeyData::Rsa { n, e } => signature::RsaPublicKeyComponents { n, e }
+                .verify(&signature::RSA_PKCS1_2048_8192_SHA256, msg, sig)
+                .is_ok(),
+            KeyData::Ec(point) => {
+                signature::UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_FIXED, point)
+                    .verify(msg, sig)
+                    .is_ok()
+            }
+            KeyData::Oct(key) => hmac::verify(key, msg, sig).is_ok(),
+        }
+    }
+}
+
+/// A set of JSON Web Keys (JWK) to verify the signatures of tokens.
+///
+/// Keys of type `RSA`, `EC` with curve `P-256` and `oct` are used for `RS256`,
+/// `ES256` and `HS256` respectively. Other keys and the keys not for signatures
+/// are ignored.
+pub struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    /// Parse a JWK set in JSON like `{"keys": [...]}`.
+    pub fn from_json(json: &str) -> Result<JwkSet> {
+        let value = match json::parse(json) {
+            Ok(v) => v,
+            Err(e) => return invalid(format!("invalid JSON: {}", e)),
+        };
+        let keys = match value.get("keys").and_then(Value::as_array) {
+            Some(keys) => keys,
+            None => return invalid("keys must be an array"),
+        };
+        let mut set = JwkSet { keys: vec![] };
+        for key in keys {
+            set.keys.extend(Jwk::from_json(key)?);
+        }
+        if set.keys.is_empty() {
+            return invalid("no supported keys");
+        }
+        Ok(set)
+    }
+}
+
+/// The claims of a validated token.
+///
+/// It's available to handlers by [`RpcContext::extensions`] if the call is
+/// authenticated by a [`JwtAuthenticator`].
+///
+/// [`RpcContext::extensions`]: crate::RpcContext::extensions
+#[derive(Clone, Debug)]
+pub struct JwtClaims {
+    payload: String,
+    claims: Value,
+}
+
+impl JwtClaims {
+    /// Get the `sub` claim.
+    pub fn subject(&self) -> Option<&str> {
+        self.get_str("sub")
+    }
+
+    /// Get the `iss` claim.
+    pub fn issuer(&self) -> Option<&str> {
+        self.get_str("iss")
+    }
+
+    /// Get the `aud` claim, which can be either a string or an array.
+    pub fn audience(&self) -> Vec<&str> {
+        match self.claims.get("aud") {
+            Some(Value::String(s)) => vec![s],
+            Some(Value::Array(a)) => a.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Get the `exp` claim.
+    pub fn expires_at(&self) -> SystemTime {
+        // It's checked to be in range by `validate`.
+        let exp = self.claims.get("exp").and_then(Value::as_f64).unwrap();
+        UNIX_EPOCH + Duration::from_secs_f64(exp)
+    }
+
+    /// Get a claim whose value is a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).and_then(Value::as_str)
+    }
+
+    /// Get the payload of the token in JSON, which can be used to read the
+    /// claims of other types.
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+}
+
+fn read_jwks(path: &Path) -> Result<JwkSet> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| Error::InvalidJwkSet(format!("failed to read {}: {}", path.display(), e)))?;
+    JwkSet::from_json(&json)
+}
+
+fn decode_segment(segment: &str, name: &str) -> std::result::Result<Vec<u8>, String> {
+    base64_decode(segment, Base64::UrlNoPad).ok_or_else(|| format!("invalid base64url in {}", name))
+}
+
+fn decode_json(segment: &str, name: &str) -> std::result::Result<(String, Value), String> {
+    let bytes = decode_segment(segment, name)?;
+    let s = String::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 in {}", name))?;
+    let value = json::parse(&s).map_err(|e| format!("invalid JSON in {}: {}", name, e))?;
+    if value.as_object().is_none() {
+        return Err(format!("{} must be an object", name));
+    }
+    Ok((s, value))
+}
+
+/// Reads a NumericDate claim, which must be a non-negative number that can be
+/// converted to `SystemTime`.
+fn timestamp(claims: &Value, name: &str) -> std::result::Result<Option<f64>, String> {
+    match claims.get(name).map(Value::as_f64) {
+        None => Ok(None),
+        Some(Some(t)) if t.is_finite() && (0.0..=MAX_TIMESTAMP).contains(&t) => Ok(Some(t)),
+        Some(Some(_)) => Err(format!("{} is out of range", name)),
+        Some(None) => Err(format!("{} must be a number", name)),
+    }
+}
+
+/// A [`ServerInterceptor`] that authenticates calls by the JSON Web Tokens in
+/// the `authorization: Bearer <token>` metadata.
+///
+/// The signature is verified by the keys in a [`JwkSet`] for `RS256`, `ES256` or
+/// `HS256`. The token must have an `exp` claim that is not expired, and `nbf`
+/// is checked if present. Calls with missing or invalid tokens fail with
+/// `UNAUTHENTICATED`. The claims of valid tokens are inserted to the extensions
+/// of the call as [`JwtClaims`].
+///
+/// Clones share the keys, so they can be replaced by [`JwtAuthenticator::update`]
+/// after the interceptor is added to a [`ServerBuilder`].
+///
+/// [`ServerBuilder`]: crate::ServerBuilder
+#[derive(Clone)]
+pub struct JwtAuthenticator {
+    keys: Reloadable<JwkSet>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway: Duration,
+}
+
+impl JwtAuthenticator {
+    /// Create an authenticator with the keys.
+    pub fn new(keys: JwkSet) -> JwtAuthenticator {
+        JwtAuthenticator {
+            keys: Reloadable::new(keys),
+            issuer: None,
+            audience: None,
+            leeway: DEFAULT_LEEWAY,
+        }
+    }
+
+    /// Create an authenticator with the keys in a JSON file, which is read
+    /// again at most once per `refresh_interval` when calls are authenticated.
+    ///
+    /// The file is read in the background, so the updated keys apply to the
+    /// calls authenticated after they are loaded. If the updated file is invalid,
+    /// the error is logged and the keys loaded before remain in use.
+    pub fn from_file(
+        path: impl Into<PathBuf>,
+        refresh_interval: Option<Duration>,
+    ) -> Result<JwtAuthenticator> {
+        let keys = Reloadable::from_file(
+            path.into(),
+            "JWKS",
+            read_jwks,
+            refresh_interval.unwrap_or(DEFAULT_REFRESH_INTERVAL),
+        )?;
+        Ok(JwtAuthenticator {
+            keys,
+            issuer: None,
+            audience: None,
+            leeway: DEFAULT_LEEWAY,
+        })
+    }
+
+    /// Require the `iss` claim to be `issuer`.
+    pub fn issuer<S: Into<String>>(mut self, issuer: S) -> JwtAuthenticator {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the `aud` claim to contain `audience`.
+    pub fn audience<S: Into<String>>(mut self, audience: S) -> JwtAuthenticator {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Set the allowed clock skew when checking `exp` and `nbf`, 60 seconds by default.
+    pub fn leeway(mut self, leeway: Duration) -> JwtAuthenticator {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Replace the keys.
+    pub fn update(&self, keys: JwkSet) {
+        self.keys.set(keys);
+    }
+
+    fn validate(&self, token: &str, now: SystemTime) -> std::result::Result<JwtClaims, String> {
+        let mut parts = token.split('.');
+        let (header, payload, sig) = match (parts.next(), parts.next(), parts.next(), parts.next())
+        {
+            (Some(h), Some(p), Some(s), None) => (h, p, s),
+            _ => return Err("malformed token".to_owned()),
+        };
+        let (_, header_value) = decode_json(header, "header")?;
+        let alg = match header_value.get("alg").and_then(Value::as_str) {
+            Some(name) => match Algorithm::from_name(name) {
+                Some(alg) => alg,
+                None => return Err(format!("unsupported algorithm {}", name)),
+            },
+            None => return Err("alg is required".to_owned()),
+        };
+        if header_value.get("crit").is_some() {
+            return Err("unsupported critical header".to_owned());
+        }
+        let kid = header_value.get("kid").and_then(Value::as_str);
+        let sig = decode_segment(sig, "signature")?;
+        let msg = &token.as_bytes()[..header.len() + 1 + payload.len()];
+        let keys = self.keys.get();
+        let mut candidates = keys
+            .keys
+            .iter()
+            .filter(|k| k.alg == alg && (kid.is_none() || k.kid.as_deref() == kid))
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err("no matching key".to_owned());
+        }
+        if !candidates.any(|k| k.verify(msg, &sig)) {
+            return Err("invalid signature".to_owned());
+        }
+
+        let (payload, claims) = decode_json(payload, "payload")?;
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let leeway = self.leeway.as_secs_f64();
+        match timestamp(&claims, "exp")? {
+            Some(exp) if now > exp + leeway => return Err("token is expired".to_owned()),
+            Some(_) => {}
+            None => return Err("exp is required".to_owned()),
+        }
+        if let Some(nbf) = timestamp(&claims, "nbf")? {
+            if now + leeway < nbf {
+                return Err("token is not valid yet".to_owned());
+            }
+        }
+        let claims = JwtClaims { payload, claims };
+        if let Some(issuer) = &self.issuer {
+            if claims.issuer() != Some(issuer.as_str()) {
+                return Err("issuer mismatch".to_owned());
+            }
+        }
+        if let Some(audience) = &self.audience {
+            if !claims.audience().contains(&audience.as_str()) {
+                return Err("audience mismatch".to_owned());
+            }
+        }
+        Ok(claims)
+    }
+
+    fn authenticate(&self, ctx: &mut InterceptContext) -> CheckResult {
+        let token = ctx
+            .request_headers()
+            .iter()
+            .find(|(k, _)| *k == "authorization")
+            .and_then(|(_, v)| std::str::from_utf8(v).ok())
+            .and_then(|v| {
+                let (scheme, token) = v.split_at(v.find(' ')?);
+                if scheme.eq_ignore_ascii_case("bearer") {
+                    Some(token.trim().to_owned())
+                } else {
+                    None
+                }
+            });
+        let res = match token {
+            Some(token) => self.validate(&token, SystemTime::now()),
+            None => Err("missing bearer token".to_owned()),
+        };
+        match res {
+            Ok(claims) => {
+                ctx.extensions_mut().insert(claims);
+                CheckResult::Continue
+            }
+            Err(e) => CheckResult::Abort(RpcStatus::with_message(
+                RpcStatusCode::UNAUTHENTICATED,
+                format!("invalid JWT: {}", e),
+            )),
+        }
+    }
+}
+
+impl ServerInterceptor for JwtAuthenticator {
+    fn intercept<'a>(&'a self, ctx: &'a mut InterceptContext) -> BoxFuture<'a, CheckResult> {
+        Box::pin(future::ready(self.authenticate(ctx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"grpc-rs-test-secret";
+
+    fn base64url_encode(data: &[u8]) -> String {
+        const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let mut s = String::new();
+        for chunk in data.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - 8 * i)));
+            for i in 0..=chunk.len() {
+                s.push(TABLE[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            }
+        }
+        s
+    }
+
+    fn sign(header: &str, payload: &str) -> String {
+        let msg = format!(
+            "{}.{}",
+            base64url_encode(header.as_bytes()),
+            base64url_encode(payload.as_bytes())
+        );
+        let key = hmac::Key::new(hmac::HMAC_SHA256, SECRET);
+        let sig = hmac::sign(&key, msg.as_bytes());
+        format!("{}.{}", msg, base64url_encode(sig.as_ref()))
+    }
+
+    fn jwks() -> JwkSet {
+        JwkSet::from_json(&format!(
+            r#"{{"keys": [
+                {{"kty": "oct", "kid": "k1", "alg": "HS256", "k": "{}"}},
+                {{"kty": "oct", "use": "enc", "k": "AAAA"}},
+                {{"kty": "OKP", "crv": "Ed25519", "x": "AAAA"}}
+            ]}}"#,
+            base64url_encode(SECRET)
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_base64url() {
+        for data in &[&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\xfe\xfd\xfc"] {
+            let encoded = base64url_encode(data);
+            let decoded = base64_decode(&encoded, Base64::UrlNoPad);
+            assert_eq!(decoded.as_deref(), Some(*data));
+        }
+    }
+
+    #[test]
+    fn test_jwk_set() {
+        assert_eq!(jwks().keys.len(), 1);
+        for invalid in &[
+            "{",
+            "{}",
+            r#"{"keys": []}"#,
+            r#"{"keys": [{"kty": "OKP"}]}"#,
+            r#"{"keys": [{"k": "AAAA"}]}"#,
+            r#"{"keys": [{"kty": "oct", "k": "!"}]}"#,
+            r#"{"keys": [{"kty": "RSA", "n": "AQAB"}]}"#,
+            r#"{"keys": [{"kty": "EC", "crv": "P-256", "x": "AQAB", "y": "AQAB"}]}"#,
+            r#"{"keys": [{"kty": "oct", "kid": 1, "k": "AAAA"}]}"#,
+        ] {
+            match JwkSet::from_json(invalid) {
+                Err(Error::InvalidJwkSet(_)) => {}
+                _ => panic!("{} should be invalid", invalid),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let auth = JwtAuthenticator::new(jwks())
+            .issuer("issuer")
+            .audience("greeter");
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let header = r#"{"alg":"HS256","kid":"k1"}"#;
+        let token = sign(
+            header,
+            r#"{"iss":"issuer","aud":["greeter","other"],"sub":"alice","exp":1000100,"role":"admin"}"#,
+        );
+        let claims = auth.validate(&token, now).unwrap();
+        assert_eq!(claims.subject(), Some("alice"));
+        assert_eq!(claims.issuer(), Some("issuer"));
+        assert_eq!(claims.audience(), vec!["greeter", "other"]);
+        assert_eq!(claims.get_str("role"), Some("admin"));
+        assert_eq!(
+            claims.expires_at(),
+            UNIX_EPOCH + Duration::from_secs(1_000_100)
+        );
+        assert!(claims.payload().contains("\"role\":\"admin\""));
+
+        // Expired within the leeway.
+        let token = sign(
+            r#"{"alg":"HS256"}"#,
+            r#"{"iss":"issuer","aud":"greeter","exp":999950,"nbf":1000050}"#,
+        );
+        auth.validate(&token, now).unwrap();
+
+        let cases = vec![
+            (
+                sign(header, r#"{"iss":"issuer","aud":"greeter","exp":999900}"#),
+                "token is expired",
+            ),
+            (
+                sign(
+                    header,
+                    r#"{"iss":"issuer","aud":"greeter","exp":1000100,"nbf":1000100}"#,
+                ),
+                "token is not valid yet",
+            ),
+            (
+                sign(header, r#"{"iss":"issuer","aud":"greeter"}"#),
+                "exp is required",
+            ),
+            (
+                sign(header, r#"{"iss":"other","aud":"greeter","exp":1000100}"#),
+                "issuer mismatch",
+            ),
+            (
+                sign(header, r#"{"iss":"issuer","aud":"other","exp":1000100}"#),
+                "audience mismatch",
+            ),
+            (
+                sign(r#"{"alg":"HS256","kid":"k2"}"#, r#"{"exp":1000100}"#),
+                "no matching key",
+            ),
+            (
+                sign(r#"{"alg":"RS256"}"#, r#"{"exp":1000100}"#),
+                "no matching key",
+            ),
+            (
+                sign(r#"{"alg":"none"}"#, r#"{"exp":1000100}"#),
+                "unsupported algorithm none",
+            ),
+            (
+                sign(r#"{"alg":"HS256","crit":["x"]}"#, r#"{"exp":1000100}"#),
+                "unsupported critical header",
+            ),
+            (
+                sign(header, r#"{"iss":"issuer","aud":"greeter","exp":1e400}"#),
+                "exp is out of range",
+            ),
+            (
+                sign(header, r#"{"iss":"issuer","aud":"greeter","exp":-1}"#),
+                "exp is out of range",
+            ),
+            (
+                sign(
+                    header,
+                    r#"{"iss":"issuer","aud":"greeter","exp":"1000100"}"#,
+                ),
+                "exp must be a number",
+            ),
+            (
+                sign(
+                    header,
+                    r#"{"iss":"issuer","aud":"greeter","exp":1000100,"nbf":-1e400}"#,
+                ),
+                "nbf is out of range",
+            ),
+            ("a.b".to_owned(), "malformed token"),
+        ];
+        for (token, err) in cases {
+            assert_eq!(auth.validate(&token, now).unwrap_err(), err);
+        }
+
+        let token = sign(header, r#"{"iss":"issuer","aud":"greeter","exp":1000100}"#);
+        let mut parts: Vec<_> = token.split('.').collect();
+        let tampered = base64url_encode(br#"{"iss":"issuer","aud":"greeter","exp":2000000}"#);
+        parts[1] = &tampered;
+        assert_eq!(
+            auth.validate(&parts.join("."), now).unwrap_err(),
+            "invalid signature"
+        );
+    }
+}
@@ -14,6 +14,11 @@ framework that puts mobile and HTTP/2 first. grpcio is built on [gRPC Core] and
 
 - **`secure`** *(enabled by default)* - Enables support for TLS encryption and some authentication
   mechanisms.
+- **`jwt`** - Enables [`JwtAuthenticator`] to authenticate calls by JSON Web Tokens.
+- **`prometheus`** - Enables [`PrometheusRecorder`] and [`PrometheusClientRecorder`] to export
+  server and client metrics to Prometheus.
+- **`tracing`** - Opens a span for every call and propagates [`TraceContext`] by the
+  `traceparent` and `tracestate` headers.
 
 */
 
@@ -27,21 +32,35 @@ use grpcio_sys as grpc_sys;
 extern crate log;
 
 mod auth_context;
+mod authz;
 mod buf;
 mod call;
 mod channel;
+mod channel_pool;
+pub mod channelz;
 mod client;
 mod codec;
 mod cq;
 mod env;
 mod error;
+mod interceptor;
+mod json;
+#[cfg(feature = "jwt")]
+mod jwt;
 mod log_util;
 mod metadata;
+mod metrics;
 mod quota;
+mod reloadable;
+mod resolver;
 #[cfg(feature = "secure")]
 mod security;
 mod server;
+mod service_config;
 mod task;
+#[cfg(feature = "tracing")]
+mod trace;
+mod util;
 
 pub use crate::buf::GrpcSlice;
 pub use crate::call::client::{
@@ -58,6 +77,7 @@ pub use crate::channel::{
     Channel, ChannelBuilder, CompressionAlgorithms, CompressionLevel, ConnectivityState, LbPolicy,
     OptTarget,
 };
+pub use crate::channel_pool::{ChannelPool, ConnectionHealth, PickPolicy};
 pub use crate::client::Client;
 
 #[cfg(feature = "protobuf-codec")]
@@ -65,21 +85,39 @@ pub use crate::codec::pb_codec::{de as pb_de, ser as pb_ser};
 #[cfg(feature = "prost-codec")]
 pub use crate::codec::pr_codec::{de as pr_de, ser as pr_ser};
 
-pub use crate::auth_context::{AuthContext, AuthProperty, AuthPropertyIter};
+pub use crate::auth_context::{
+    AuthContext, AuthProperty, AuthPropertyIter, PeerIdentity, SecurityLevel,
+};
+pub use crate::authz::{AuthorizationChecker, AuthorizationPolicy, AuthorizationRule};
 pub use crate::codec::Marshaller;
 pub use crate::env::{EnvBuilder, Environment};
 pub use crate::error::{Error, Result};
+pub use crate::interceptor::{ClientInterceptor, Extensions, InterceptContext, ServerInterceptor};
+#[cfg(feature = "jwt")]
+pub use crate::jwt::{JwkSet, JwtAuthenticator, JwtClaims};
 pub use crate::log_util::redirect_log;
 pub use crate::metadata::{Metadata, MetadataBuilder, MetadataIter};
+pub use crate::metrics::{ClientMetricsRecorder, MetricsRecorder};
+#[cfg(feature = "prometheus")]
+pub use crate::metrics::{PrometheusClientRecorder, PrometheusRecorder};
 pub use crate::quota::ResourceQuota;
+pub use crate::resolver::{register_resolver, Resolver, ResolverUpdater};
 #[cfg(feature = "secure")]
 pub use crate::security::{
-    CertificateRequestType, ChannelCredentials, ChannelCredentialsBuilder, ServerCredentials,
-    ServerCredentialsBuilder, ServerCredentialsFetcher,
+    AuthMetadataContext, AuthMetadataProcessor, AuthProcessContext, CallCredentials,
+    CertificateRequestType, CertificateRevocationList, ChannelCredentials,
+    ChannelCredentialsBuilder, ChannelCredentialsFetcher, FileWatcherCertificateProvider,
+    LocalConnectType, MetadataCredentialsPlugin, PeerCertificate, PeerVerifier, ServerCredentials,
+    ServerCredentialsBuilder, ServerCredentialsFetcher, SslSessionCache, TlsVersion,
 };
 pub use crate::server::{
     CheckResult, Server, ServerBuilder, ServerChecker, Service, ServiceBuilder, ShutdownFuture,
 };
+pub use crate::service_config::{
+    HedgingPolicy, MethodConfig, RetryPolicy, ServiceConfig, ServiceConfigBuilder,
+};
+#[cfg(feature = "tracing")]
+pub use crate::trace::TraceContext;
 
 /// A shortcut for implementing a service method by returning `UNIMPLEMENTED` status code.
 ///
@@ -2,6 +2,7 @@
 
 use crate::grpc_sys::{self, grpc_metadata, grpc_metadata_array};
 use std::borrow::Cow;
+use std::fmt::{self, Debug, Formatter};
 use std::mem::ManuallyDrop;
 use std::{mem, slice, str};
 
@@ -88,7 +89,7 @@ impl MetadataBuilder {
         Ok(self.add_metadata(&key, value.as_bytes()))
     }
 
-    fn add_metadata(&mut self, key: &str, value: &[u8]) -> &mut MetadataBuilder {
+    pub(crate) fn add_metadata(&mut self, key: &str, value: &[u8]) -> &mut MetadataBuilder {
         unsafe {
             grpc_sys::grpcwrap_metadata_array_add(
                 &mut self.arr.0,
@@ -125,6 +126,13 @@ impl MetadataBuilder {
     }
 }
 
+impl From<Metadata> for MetadataBuilder {
+    /// Create a builder that appends entries to the given metadata.
+    fn from(arr: Metadata) -> MetadataBuilder {
+        MetadataBuilder { arr }
+    }
+}
+
 /// A collection of metadata entries that can be exchanged during a call.
 ///
 /// gRPC supports these types of metadata:
@@ -235,6 +243,12 @@ impl Metadata {
     }
 }
 
+impl Default for Metadata {
+    fn default() -> Metadata {
+        Metadata::with_capacity(0)
+    }
+}
+
 impl Clone for Metadata {
     fn clone(&self) -> Metadata {
         let mut builder = MetadataBuilder::with_capacity(self.len());
@@ -246,6 +260,14 @@ impl Clone for Metadata {
     }
 }
 
+impl Debug for Metadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(k, v)| (k, String::from_utf8_lossy(v))))
+            .finish()
+    }
+}
+
 impl Drop for Metadata {
     fn drop(&mut self) {
         unsafe {
@@ -367,5 +389,11 @@ mod tests {
         let empty_metadata = MetadataBuilder::new().build();
         assert!(empty_metadata.is_empty());
         assert_eq!(empty_metadata.len(), 0);
+
+        let mut builder = MetadataBuilder::from(metadata1);
+        builder.add_str("k10", "v10").unwrap();
+        let metadata2 = builder.build();
+        assert_eq!(metadata2.len(), 11);
+        assert_eq!(metadata2.get(10), Some(("k10", &b"v10"[..])));
     }
 }
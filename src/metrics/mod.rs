@@ -0,0 +1,184 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::call::{MethodType, RpcStatusCode};
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::{PrometheusClientRecorder, PrometheusRecorder};
+
+/// Records the metrics of the calls handled by a [`Server`].
+///
+/// Methods are fully-qualified, like `/helloworld.Greeter/SayHello`. A call is
+/// in flight between [`on_started`] and [`on_handled`]. The callbacks are invoked
+/// in gRPC poll threads, so they should be cheap.
+///
+/// [`Server`]: crate::Server
+/// [`on_started`]: MetricsRecorder::on_started
+/// [`on_handled`]: MetricsRecorder::on_handled
+pub trait MetricsRecorder: Send + Sync {
+    /// Called when a call is received, before any [`ServerChecker`].
+    ///
+    /// Calls of undefined methods are reported as `/unknown/unknown` of
+    /// [`MethodType::Duplex`].
+    ///
+    /// [`ServerChecker`]: crate::ServerChecker
+    fn on_started(&self, method: &str, method_type: MethodType);
+
+    /// Called when the status of a call has been sent to the client, with the
+    /// time elapsed since the call was started.
+    ///
+    /// If the handler drops the sink without sending any status, or the status
+    /// fails to be sent, `CANCELLED` is reported.
+    fn on_handled(
+        &self,
+        method: &str,
+        method_type: MethodType,
+        code: RpcStatusCode,
+        elapsed: Duration,
+    );
+
+    /// Called when a request message of `bytes` bytes is received.
+    fn on_msg_received(&self, _method: &str, _method_type: MethodType, _bytes: usize) {}
+
+    /// Called when a response message of `bytes` bytes is sent.
+    fn on_msg_sent(&self, _method: &str, _method_type: MethodType, _bytes: usize) {}
+}
+
+/// Records the metrics of a single call.
+#[derive(Clone)]
+pub(crate) struct CallMetrics {
+    recorder: Arc<dyn MetricsRecorder>,
+    method: Arc<str>,
+    method_type: MethodType,
+}
+
+impl CallMetrics {
+    /// Records that the call is started.
+    pub fn start(
+        recorder: Arc<dyn MetricsRecorder>,
+        method: &[u8],
+        method_type: MethodType,
+    ) -> CallMetrics {
+        let method: Arc<str> = String::from_utf8_lossy(method).into();
+        recorder.on_started(&method, method_type);
+        CallMetrics {
+            recorder,
+            method,
+            method_type,
+        }
+    }
+
+    pub fn handled(&self, code: RpcStatusCode, elapsed: Duration) {
+        self.recorder
+            .on_handled(&self.method, self.method_type, code, elapsed);
+    }
+
+    pub fn msg_received(&self, bytes: usize) {
+        self.recorder
+            .on_msg_received(&self.method, self.method_type, bytes);
+    }
+
+    pub fn msg_sent(&self, bytes: usize) {
+        self.recorder
+            .on_msg_sent(&self.method, self.method_type, bytes);
+    }
+}
+
+/// Records the metrics of the calls made on a [`Channel`].
+///
+/// Every callback is given the target the channel connects to, like
+/// `127.0.0.1:20160`, and the fully-qualified method of the call.
+///
+/// [`Channel`]: crate::Channel
+pub trait ClientMetricsRecorder: Send + Sync {
+    /// Called when a call is about to be started, before any [`ClientInterceptor`].
+    ///
+    /// [`ClientInterceptor`]: crate::ClientInterceptor
+    fn on_started(&self, target: &str, method: &str, method_type: MethodType);
+
+    /// Called when the status of a call is received, with the time elapsed since
+    /// the call was started.
+    ///
+    /// Errors that are not sent by server are reported as `UNKNOWN`. If the call
+    /// is dropped before its status is received, `CANCELLED` is reported.
+    fn on_completed(
+        &self,
+        target: &str,
+        method: &str,
+        method_type: MethodType,
+        code: RpcStatusCode,
+        elapsed: Duration,
+    );
+
+    /// Called when a response message of `bytes` bytes is received.
+    fn on_msg_received(
+        &self,
+        _target: &str,
+        _method: &str,
+        _method_type: MethodType,
+        _bytes: usize,
+    ) {
+    }
+
+    /// Called when a request message of `bytes` bytes is sent.
+    fn on_msg_sent(&self, _target: &str, _method: &str, _method_type: MethodType, _bytes: usize) {}
+}
+
+/// The recorder of a channel and the target it connects to.
+#[derive(Clone)]
+pub(crate) struct ChannelMetrics {
+    recorder: Arc<dyn ClientMetricsRecorder>,
+    target: Arc<str>,
+}
+
+impl ChannelMetrics {
+    pub fn new(recorder: Arc<dyn ClientMetricsRecorder>, target: &str) -> ChannelMetrics {
+        ChannelMetrics {
+            recorder,
+            target: target.into(),
+        }
+    }
+
+    /// Records that a call is started on the channel.
+    pub fn start(&self, method: &'static str, method_type: MethodType) -> ClientCallMetrics {
+        self.recorder.on_started(&self.target, method, method_type);
+        ClientCallMetrics {
+            channel: self.clone(),
+            method,
+            method_type,
+        }
+    }
+}
+
+/// Records the metrics of a single call made by client.
+#[derive(Clone)]
+pub(crate) struct ClientCallMetrics {
+    channel: ChannelMetrics,
+    method: &'static str,
+    method_type: MethodType,
+}
+
+impl ClientCallMetrics {
+    pub fn completed(&self, code: RpcStatusCode, elapsed: Duration) {
+        let c = &self.channel;
+        c.recorder
+            .on_completed(&c.target, self.method, self.method_type, code, elapsed);
+    }
+
+    pub fn msg_received(&self, bytes: usize) {
+        let c = &self.channel;
+        c.recorder
+            .on_msg_received(&c.target, self.method, self.method_type, bytes);
+    }
+
+    pub fn msg_sent(&self, bytes: usize) {
+        let c = &self.channel;
+        c.recorder
+            .on_msg_sent(&c.target, self.method, self.method_type, bytes);
+    }
+}
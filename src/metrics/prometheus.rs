@@ -0,0 +1,369 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+
+use super::{ClientMetricsRecorder, MetricsRecorder};
+use crate::call::{MethodType, RpcStatusCode};
+
+const LABELS: &[&str] = &["grpc_type", "grpc_service", "grpc_method"];
+const HANDLED_LABELS: &[&str] = &["grpc_type", "grpc_service", "grpc_method", "grpc_code"];
+const CLIENT_LABELS: &[&str] = &["grpc_target", "grpc_type", "grpc_service", "grpc_method"];
+const CLIENT_HANDLED_LABELS: &[&str] = &[
+    "grpc_target",
+    "grpc_type",
+    "grpc_service",
+    "grpc_method",
+    "grpc_code",
+];
+
+fn counter(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    IntCounterVec::new(Opts::new(name, help), labels).unwrap()
+}
+
+fn type_label(method_type: MethodType) -> &'static str {
+    match method_type {
+        MethodType::Unary => "unary",
+        MethodType::ClientStreaming => "client_stream",
+        MethodType::ServerStreaming => "server_stream",
+        MethodType::Duplex => "bidi_stream",
+    }
+}
+
+/// Splits `/package.Service/Method` into `package.Service` and `Method`.
+fn split_method(method: &str) -> (&str, &str) {
+    let method = method.strip_prefix('/').unwrap_or(method);
+    match method.rfind('/') {
+        Some(i) => (&method[..i], &method[i + 1..]),
+        None => ("unknown", method),
+    }
+}
+
+/// A [`MetricsRecorder`] that exports the standard `grpc_server_*` metric families
+/// of Prometheus.
+///
+/// All families are labelled by `grpc_type`, `grpc_service` and `grpc_method`,
+/// `grpc_server_handled_total` is also labelled by `grpc_code`.
+#[derive(Clone)]
+pub struct PrometheusRecorder {
+    started: IntCounterVec,
+    handled: IntCounterVec,
+    in_flight: IntGaugeVec,
+    handling_seconds: HistogramVec,
+    msg_received: IntCounterVec,
+    msg_sent: IntCounterVec,
+    msg_received_bytes: IntCounterVec,
+    msg_sent_bytes: IntCounterVec,
+}
+
+impl PrometheusRecorder {
+    pub fn new() -> PrometheusRecorder {
+        PrometheusRecorder {
+            started: counter(
+                "grpc_server_started_total",
+                "Total number of RPCs started on the server.",
+                LABELS,
+            ),
+            handled: counter(
+                "grpc_server_handled_total",
+                "Total number of RPCs completed on the server, regardless of success or failure.",
+                HANDLED_LABELS,
+            ),
+            in_flight: IntGaugeVec::new(
+                Opts::new(
+                    "grpc_server_in_flight",
+                    "Number of RPCs being handled on the server.",
+                ),
+                LABELS,
+            )
+            .unwrap(),
+            handling_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "grpc_server_handling_seconds",
+                    "Histogram of response latency of RPCs handled by the server.",
+                ),
+                LABELS,
+            )
+            .unwrap(),
+            msg_received: counter(
+                "grpc_server_msg_received_total",
+                "Total number of RPC messages received on the server.",
+                LABELS,
+            ),
+            msg_sent: counter(
+                "grpc_server_msg_sent_total",
+                "Total number of RPC messages sent by the server.",
+                LABELS,
+            ),
+            msg_received_bytes: counter(
+                "grpc_server_msg_received_bytes_total",
+                "Total bytes of RPC messages received on the server.",
+                LABELS,
+            ),
+            msg_sent_bytes: counter(
+                "grpc_server_msg_sent_bytes_total",
+                "Total bytes of RPC messages sent by the server.",
+                LABELS,
+            ),
+        }
+    }
+
+    /// Registers all metric families to `registry`.
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.started.clone()))?;
+        registry.register(Box::new(self.handled.clone()))?;
+        registry.register(Box::new(self.in_flight.clone()))?;
+        registry.register(Box::new(self.handling_seconds.clone()))?;
+        registry.register(Box::new(self.msg_received.clone()))?;
+        registry.register(Box::new(self.msg_sent.clone()))?;
+        registry.register(Box::new(self.msg_received_bytes.clone()))?;
+        registry.register(Box::new(self.msg_sent_bytes.clone()))
+    }
+}
+
+impl Default for PrometheusRecorder {
+    fn default() -> PrometheusRecorder {
+        PrometheusRecorder::new()
+    }
+}
+
+impl MetricsRecorder for PrometheusRecorder {
+    fn on_started(&self, method: &str, method_type: MethodType) {
+        let (service, method) = split_method(method);
+        let labels = [type_label(method_type), service, method];
+        self.started.with_label_values(&labels).inc();
+        self.in_flight.with_label_values(&labels).inc();
+    }
+
+    fn on_handled(
+        &self,
+        method: &str,
+        method_type: MethodType,
+        code: RpcStatusCode,
+        elapsed: Duration,
+    ) {
+        let (service, method) = split_method(method);
+        let labels = [type_label(method_type), service, method];
+        self.in_flight.with_label_values(&labels).dec();
+        self.handling_seconds
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+        let code = code.name().unwrap_or("UNKNOWN");
+        self.handled
+            .with_label_values(&[labels[0], labels[1], labels[2], code])
+            .inc();
+    }
+
+    fn on_msg_received(&self, method: &str, method_type: MethodType, bytes: usize) {
+        let (service, method) = split_method(method);
+        let labels = [type_label(method_type), service, method];
+        self.msg_received.with_label_values(&labels).inc();
+        self.msg_received_bytes
+            .with_label_values(&labels)
+            .inc_by(bytes as u64);
+    }
+
+    fn on_msg_sent(&self, method: &str, method_type: MethodType, bytes: usize) {
+        let (service, method) = split_method(method);
+        let labels = [type_label(method_type), service, method];
+        self.msg_sent.with_label_values(&labels).inc();
+        self.msg_sent_bytes
+            .with_label_values(&labels)
+            .inc_by(bytes as u64);
+    }
+}
+
+/// A [`ClientMetricsRecorder`] that exports the `grpc_client_*` metric families
+/// of Prometheus.
+///
+/// All families are labelled by `grpc_target`, `grpc_type`, `grpc_service` and
+/// `grpc_method`, `grpc_client_handled_total` is also labelled by `grpc_code`.
+#[derive(Clone)]
+pub struct PrometheusClientRecorder {
+    started: IntCounterVec,
+    handled: IntCounterVec,
+    handling_seconds: HistogramVec,
+    msg_received: IntCounterVec,
+    msg_sent: IntCounterVec,
+}
+
+impl PrometheusClientRecorder {
+    pub fn new() -> PrometheusClientRecorder {
+        PrometheusClientRecorder {
+            started: counter(
+                "grpc_client_started_total",
+                "Total number of RPCs started on the client.",
+                CLIENT_LABELS,
+            ),
+            handled: counter(
+                "grpc_client_handled_total",
+                "Total number of RPCs completed by the client, regardless of success or failure.",
+                CLIENT_HANDLED_LABELS,
+            ),
+            handling_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "grpc_client_handling_seconds",
+                    "Histogram of response latency of RPCs completed by the client.",
+                ),
+                CLIENT_LABELS,
+            )
+            .unwrap(),
+            msg_received: counter(
+                "grpc_client_msg_received_total",
+                "Total number of RPC messages received by the client.",
+                CLIENT_LABELS,
+            ),
+            msg_sent: counter(
+                "grpc_client_msg_sent_total",
+                "Total number of RPC messages sent by the client.",
+                CLIENT_LABELS,
+            ),
+        }
+    }
+
+    /// Registers all metric families to `registry`.
+    pub fn register(&self, registry: &Registry) -> prometheus::Result<()> {
+        registry.register(Box::new(self.started.clone()))?;
+        registry.register(Box::new(self.handled.clone()))?;
+        registry.register(Box::new(self.handling_seconds.clone()))?;
+        registry.register(Box::new(self.msg_received.clone()))?;
+        registry.register(Box::new(self.msg_sent.clone()))
+    }
+}
+
+impl Default for PrometheusClientRecorder {
+    fn default() -> PrometheusClientRecorder {
+        PrometheusClientRecorder::new()
+    }
+}
+
+impl ClientMetricsRecorder for PrometheusClientRecorder {
+    fn on_started(&self, target: &str, method: &str, method_type: MethodType) {
+        let (service, method) = split_method(method);
+        let labels = [target, type_label(method_type), service, method];
+        self.started.with_label_values(&labels).inc();
+    }
+
+    fn on_completed(
+        &self,
+        target: &str,
+        method: &str,
+        method_type: MethodType,
+        code: RpcStatusCode,
+        elapsed: Duration,
+    ) {
+        let (service, method) = split_method(method);
+        let labels = [target, type_label(method_type), service, method];
+        self.handling_seconds
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+        let code = code.name().unwrap_or("UNKNOWN");
+        self.handled
+            .with_label_values(&[labels[0], labels[1], labels[2], labels[3], code])
+            .inc();
+    }
+
+    fn on_msg_received(&self, target: &str, method: &str, method_type: MethodType, _: usize) {
+        let (service, method) = split_method(method);
+        let labels = [target, type_label(method_type), service, method];
+        self.msg_received.with_label_values(&labels).inc();
+    }
+
+    fn on_msg_sent(&self, target: &str, method: &str, method_type: MethodType, _: usize) {
+        let (service, method) = split_method(method);
+        let labels = [target, type_label(method_type), service, method];
+        self.msg_sent.with_label_values(&labels).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_method() {
+        assert_eq!(
+            split_method("/helloworld.Greeter/SayHello"),
+            ("helloworld.Greeter", "SayHello")
+        );
+        assert_eq!(split_method("/a.b.C/D"), ("a.b.C", "D"));
+        assert_eq!(split_method("bad"), ("unknown", "bad"));
+    }
+
+    #[test]
+    fn test_prometheus_recorder() {
+        let registry = Registry::new();
+        let recorder = PrometheusRecorder::new();
+        recorder.register(&registry).unwrap();
+        let method = "/helloworld.Greeter/SayHello";
+        recorder.on_started(method, MethodType::Unary);
+        recorder.on_msg_received(method, MethodType::Unary, 10);
+        recorder.on_msg_sent(method, MethodType::Unary, 20);
+        recorder.on_handled(
+            method,
+            MethodType::Unary,
+            RpcStatusCode::OK,
+            Duration::from_millis(5),
+        );
+        let labels = ["unary", "helloworld.Greeter", "SayHello"];
+        assert_eq!(recorder.started.with_label_values(&labels).get(), 1);
+        assert_eq!(recorder.in_flight.with_label_values(&labels).get(), 0);
+        assert_eq!(
+            recorder.msg_received_bytes.with_label_values(&labels).get(),
+            10
+        );
+        assert_eq!(recorder.msg_sent_bytes.with_label_values(&labels).get(), 20);
+        let handled =
+            recorder
+                .handled
+                .with_label_values(&["unary", "helloworld.Greeter", "SayHello", "OK"]);
+        assert_eq!(handled.get(), 1);
+        let names: Vec<_> = registry
+            .gather()
+            .iter()
+            .map(|f| f.get_name().to_owned())
+            .collect();
+        assert_eq!(names.len(), 8);
+        assert!(names.iter().all(|n| n.starts_with("grpc_server_")));
+    }
+
+    #[test]
+    fn test_prometheus_client_recorder() {
+        let registry = Registry::new();
+        let recorder = PrometheusClientRecorder::new();
+        recorder.register(&registry).unwrap();
+        let (target, method) = ("127.0.0.1:20160", "/routeguide.RouteGuide/RecordRoute");
+        recorder.on_started(target, method, MethodType::ClientStreaming);
+        recorder.on_msg_sent(target, method, MethodType::ClientStreaming, 10);
+        recorder.on_msg_sent(target, method, MethodType::ClientStreaming, 10);
+        recorder.on_msg_received(target, method, MethodType::ClientStreaming, 20);
+        recorder.on_completed(
+            target,
+            method,
+            MethodType::ClientStreaming,
+            RpcStatusCode::OK,
+            Duration::from_millis(5),
+        );
+        let labels = [
+            target,
+            "client_stream",
+            "routeguide.RouteGuide",
+            "RecordRoute",
+        ];
+        assert_eq!(recorder.started.with_label_values(&labels).get(), 1);
+        assert_eq!(recorder.msg_sent.with_label_values(&labels).get(), 2);
+        assert_eq!(recorder.msg_received.with_label_values(&labels).get(), 1);
+        let handling = recorder.handling_seconds.with_label_values(&labels);
+        assert_eq!(handling.get_sample_count(), 1);
+        let handled = recorder.handled.with_label_values(&[
+            target,
+            "client_stream",
+            "routeguide.RouteGuide",
+            "RecordRoute",
+            "OK",
+        ]);
+        assert_eq!(handled.get(), 1);
+        assert_eq!(registry.gather().len(), 5);
+    }
+}
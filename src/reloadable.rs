@@ -0,0 +1,120 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::error::Result;
+use crate::task::spawn_blocking;
+
+struct Current<T> {
+    value: Arc<T>,
+    // Bumped by every manual update, so reloads started before are discarded.
+    version: u64,
+}
+
+/// Where a [`Reloadable`] is read from.
+struct Source<T> {
+    path: PathBuf,
+    // What the file holds, used in logs.
+    kind: &'static str,
+    load: fn(&Path) -> Result<T>,
+    refresh_interval: Duration,
+    last_refresh: Mutex<Instant>,
+}
+
+/// A value that can be replaced manually or reloaded from a file.
+///
+/// Clones share the value. The file is read again at most once per refresh
+/// interval when the value is got, on the thread for blocking work, so the
+/// reloaded value is seen by the later reads.
+pub(crate) struct Reloadable<T> {
+    current: Arc<RwLock<Current<T>>>,
+    source: Option<Arc<Source<T>>>,
+}
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Reloadable {
+            current: self.current.clone(),
+            source: self.source.clone(),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> Reloadable<T> {
+    pub fn new(value: T) -> Reloadable<T> {
+        Reloadable {
+            current: Arc::new(RwLock::new(Current {
+                value: Arc::new(value),
+                version: 0,
+            })),
+            source: None,
+        }
+    }
+
+    /// Reads the value from `path` by `load`, which is called again to reload it.
+    pub fn from_file(
+        path: PathBuf,
+        kind: &'static str,
+        load: fn(&Path) -> Result<T>,
+        refresh_interval: Duration,
+    ) -> Result<Reloadable<T>> {
+        let mut r = Reloadable::new(load(&path)?);
+        r.source = Some(Arc::new(Source {
+            path,
+            kind,
+            load,
+            refresh_interval,
+            last_refresh: Mutex::new(Instant::now()),
+        }));
+        Ok(r)
+    }
+
+    pub fn get(&self) -> Arc<T> {
+        self.maybe_reload();
+        self.current.read().value.clone()
+    }
+
+    pub fn set(&self, value: T) {
+        let mut current = self.current.write();
+        current.value = Arc::new(value);
+        current.version += 1;
+    }
+
+    fn maybe_reload(&self) {
+        let source = match &self.source {
+            Some(source) => source,
+            None => return,
+        };
+        // Skip if another call is checking.
+        let mut last_refresh = match source.last_refresh.try_lock() {
+            Some(l) => l,
+            None => return,
+        };
+        let now = Instant::now();
+        if now.saturating_duration_since(*last_refresh) < source.refresh_interval {
+            return;
+        }
+        *last_refresh = now;
+        let version = self.current.read().version;
+        let (current, source) = (self.current.clone(), source.clone());
+        // The result is not waited, later reads see the reloaded value.
+        drop(spawn_blocking(move || match (source.load)(&source.path) {
+            Ok(value) => {
+                let mut current = current.write();
+                if current.version == version {
+                    current.value = Arc::new(value);
+                }
+            }
+            Err(e) => warn!(
+                "failed to reload {} from {}: {}",
+                source.kind,
+                source.path.display(),
+                e
+            ),
+        }));
+    }
+}
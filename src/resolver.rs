@@ -0,0 +1,103 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::channel::{self, ResolvedChannel};
+use crate::error::{Error, Result};
+use crate::service_config::ServiceConfig;
+
+/// A name resolver that translates targets of a custom scheme into addresses.
+///
+/// Resolvers are registered by [`register_resolver`]. When a [`Channel`] is built
+/// with a target like `scheme:endpoint` whose scheme is registered, the channel
+/// doesn't resolve the target itself, instead it waits for the resolver to push
+/// addresses by [`ResolverUpdater`]. Calls made before the first update are queued
+/// until the update arrives or their deadlines are exceeded.
+///
+/// Channels are fed through the fake resolver of gRPC core, which is an internal
+/// part of core that's only reachable when grpcio-sys builds grpc from the bundled
+/// sources. Registering a resolver fails when grpc is linked from the system, for
+/// example with `GRPCIO_SYS_USE_PKG_CONFIG=1`.
+///
+/// [`Channel`]: crate::Channel
+pub trait Resolver: Send + Sync {
+    /// Starts resolving the target, which includes the scheme.
+    ///
+    /// It's called once for every channel built with the target. The resolver should
+    /// keep the updater and push the new addresses whenever they change, until
+    /// [`ResolverUpdater::update`] returns false.
+    fn resolve(&self, target: &str, updater: ResolverUpdater);
+}
+
+static RESOLVERS: Mutex<Vec<(String, Arc<dyn Resolver>)>> = parking_lot::const_mutex(Vec::new());
+
+/// Registers a resolver for the scheme, which replaces the resolver registered
+/// before for the same scheme, if any.
+///
+/// Only channels built after the registration are affected.
+///
+/// Returns [`Error::Unsupported`] if the linked gRPC core can't be fed by custom
+/// resolvers, in which case nothing is registered.
+///
+/// [`Error::Unsupported`]: crate::Error::Unsupported
+pub fn register_resolver<R: Resolver + 'static>(scheme: &str, resolver: R) -> Result<()> {
+    if !channel::resolver_supported() {
+        return Err(Error::Unsupported(
+            "custom resolvers require grpcio-sys to build grpc from source".to_owned(),
+        ));
+    }
+    let resolver: Arc<dyn Resolver> = Arc::new(resolver);
+    let mut resolvers = RESOLVERS.lock();
+    match resolvers.iter_mut().find(|(s, _)| s == scheme) {
+        Some(entry) => entry.1 = resolver,
+        None => resolvers.push((scheme.to_owned(), resolver)),
+    }
+    Ok(())
+}
+
+/// Finds the resolver registered for the scheme of `target`.
+pub(crate) fn find(target: &str) -> Option<Arc<dyn Resolver>> {
+    let pos = target.find(':')?;
+    let scheme = &target[..pos];
+    let resolvers = RESOLVERS.lock();
+    resolvers
+        .iter()
+        .find(|(s, _)| s == scheme)
+        .map(|(_, r)| r.clone())
+}
+
+/// Pushes resolved addresses to a [`Channel`].
+///
+/// [`Channel`]: crate::Channel
+#[derive(Clone)]
+pub struct ResolverUpdater {
+    channel: Arc<ResolvedChannel>,
+}
+
+impl ResolverUpdater {
+    pub(crate) fn new(channel: ResolvedChannel) -> ResolverUpdater {
+        ResolverUpdater {
+            channel: Arc::new(channel),
+        }
+    }
+
+    /// Replaces the addresses of the channel.
+    ///
+    /// If `config` is given, it replaces the service config of the channel, otherwise
+    /// the current config is kept. An update that changes neither the addresses nor
+    /// the config is ignored. The addresses are handed to the channel's load
+    /// balancing policy, so connections to the addresses that are still present
+    /// are reused.
+    ///
+    /// An empty address list makes new calls fail with `UNAVAILABLE`, unless they
+    /// are `wait_for_ready`.
+    ///
+    /// Returns false if the channel has been dropped, in which case the resolver
+    /// should stop pushing updates.
+    pub fn update(&self, addrs: &[SocketAddr], config: Option<&ServiceConfig>) -> bool {
+        self.channel.update(addrs, config)
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::ffi::CString;
+use std::mem::ManuallyDrop;
+use std::os::raw::c_void;
+use std::sync::Arc;
+use std::{ptr, slice, str};
+
+use parking_lot::RwLock;
+
+use super::peer_verifier::PeerChecks;
+use crate::auth_context::AuthContext;
+use crate::call::{RpcStatus, RpcStatusCode};
+use crate::grpc_sys::{
+    self, grpc_auth_context, grpc_auth_metadata_processor, grpc_metadata,
+    grpc_process_auth_metadata_done_cb, grpc_slice,
+};
+use crate::metadata::{Metadata, MetadataBuilder};
+
+unsafe fn slice_bytes<'a>(s: &grpc_slice) -> &'a [u8] {
+    let mut len = 0;
+    let ptr = grpc_sys::grpcwrap_slice_raw_offset(s, 0, &mut len);
+    slice::from_raw_parts(ptr as *const u8, len)
+}
+
+/// The context of an [`AuthMetadataProcessor`] for a call.
+pub struct AuthProcessContext<'a> {
+    auth_context: ManuallyDrop<AuthContext>,
+    metadata: Metadata,
+    raw_metadata: &'a [grpc_metadata],
+    consumed: Vec<grpc_metadata>,
+}
+
+impl<'a> AuthProcessContext<'a> {
+    /// Get the auth context of the call, which contains the properties of the
+    /// transport, like the certificate of the client.
+    pub fn auth_context(&self) -> &AuthContext {
+        &self.auth_context
+    }
+
+    /// Get the metadata sent by the client.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Add a property to the auth context, which is visible to handlers by
+    /// [`RpcContext::auth_context`].
+    ///
+    /// [`RpcContext::auth_context`]: crate::RpcContext::auth_context
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains NUL.
+    pub fn add_property(&mut self, name: &str, value: &[u8]) {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            grpc_sys::grpc_auth_context_add_property(
+                self.auth_context.as_mut_ptr(),
+                name.as_ptr(),
+                value.as_ptr() as _,
+                value.len(),
+            )
+        }
+    }
+
+    /// Use the properties named `name` as the peer identity, which also marks
+    /// the peer as authenticated.
+    ///
+    /// Returns `false` if there is no such property.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains NUL.
+    pub fn set_peer_identity_property_name(&mut self, name: &str) -> bool {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            grpc_sys::grpc_auth_context_set_peer_identity_property_name(
+                self.auth_context.as_mut_ptr(),
+                name.as_ptr(),
+            ) != 0
+        }
+    }
+
+    /// Remove the metadata `key` from the request headers passed to handlers,
+    /// which is useful to keep credentials out of them.
+    pub fn consume(&mut self, key: &str) {
+        for md in self.raw_metadata {
+            if unsafe { slice_bytes(&md.key) } == key.as_bytes() {
+                self.consumed.push(*md);
+            }
+        }
+    }
+}
+
+/// A processor that authenticates the calls on the server side by their metadata,
+/// like bearer tokens, before they are dispatched to handlers.
+///
+/// It's set by [`ServerCredentialsBuilder::auth_metadata_processor`].
+///
+/// [`ServerCredentialsBuilder::auth_metadata_processor`]: crate::ServerCredentialsBuilder::auth_metadata_processor
+pub trait AuthMetadataProcessor: Send + Sync {
+    /// Processes the metadata of a call.
+    ///
+    /// The identity can be recorded as properties of the auth context, so that
+    /// handlers can read it instead of parsing the metadata again. Returns an error
+    /// status to reject the call, whose code is usually `UNAUTHENTICATED` or
+    /// `PERMISSION_DENIED`. It blocks the call, so it should be light-weight.
+    fn process(&self, ctx: &mut AuthProcessContext<'_>) -> Result<(), RpcStatus>;
+}
+
+/// The auth checks on the server side. The client certificates are checked during
+/// handshakes, while the processor runs for every call.
+#[derive(Clone, Default)]
+pub(crate) struct ServerAuth {
+    checks: PeerChecks,
+    processor: Option<Arc<dyn AuthMetadataProcessor>>,
+}
+
+/// The auth checks of server credentials, which are replaced when the credentials
+/// are fetched again.
+pub(crate) type SharedServerAuth = Arc<RwLock<ServerAuth>>;
+
+impl ServerAuth {
+    pub fn new(
+        checks: PeerChecks,
+        processor: Option<Arc<dyn AuthMetadataProcessor>>,
+    ) -> ServerAuth {
+        ServerAuth { checks, processor }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty() && self.processor.is_none()
+    }
+
+    pub fn checks(&self) -> &PeerChecks {
+        &self.checks
+    }
+
+    /// Builds the processor to set on server credentials, whose state is also
+    /// where the checks of client certificates are found during handshakes.
+    pub fn auth_metadata_processor(auth: SharedServerAuth) -> grpc_auth_metadata_processor {
+        grpc_auth_metadata_processor {
+            process: Some(process_auth_metadata),
+            destroy: Some(destroy),
+            state: Box::into_raw(Box::new(auth)) as _,
+        }
+    }
+
+    unsafe fn process(
+        &self,
+        context: *mut grpc_auth_context,
+        md: &[grpc_metadata],
+        consumed: &mut Vec<grpc_metadata>,
+    ) -> Result<(), RpcStatus> {
+        let processor = match &self.processor {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let auth_context = match AuthContext::from_raw_borrowed(context) {
+            Some(ctx) => ctx,
+            None => return Ok(()),
+        };
+        let mut builder = MetadataBuilder::with_capacity(md.len());
+        for m in md {
+            if let Ok(key) = str::from_utf8(slice_bytes(&m.key)) {
+                builder.add_metadata(key, slice_bytes(&m.value));
+            }
+        }
+        let mut ctx = AuthProcessContext {
+            auth_context,
+            metadata: builder.build(),
+            raw_metadata: md,
+            consumed: vec![],
+        };
+        let res = processor.process(&mut ctx);
+        *consumed = ctx.consumed;
+        res
+    }
+}
+
+pub(super) unsafe extern "C" fn process_auth_metadata(
+    state: *mut c_void,
+    context: *mut grpc_auth_context,
+    md: *const grpc_metadata,
+    num_md: usize,
+    cb: grpc_process_auth_metadata_done_cb,
+    user_data: *mut c_void,
+) {
+    // Cloned so a fetcher can replace the checks while they are running.
+    let auth = (*(state as *const SharedServerAuth)).read().clone();
+    let md = if md.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(md, num_md)
+    };
+    let mut consumed = vec![];
+    let res = auth.process(context, md, &mut consumed);
+    let cb = cb.unwrap();
+    match res {
+        Ok(()) => cb(
+            user_data,
+            consumed.as_ptr(),
+            consumed.len(),
+            ptr::null(),
+            0,
+            RpcStatusCode::OK.into(),
+            ptr::null(),
+        ),
+        Err(status) => {
+            let msg = CString::new(status.message()).unwrap_or_default();
+            cb(
+                user_data,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                status.code().into(),
+                msg.as_ptr(),
+            )
+        }
+    }
+}
+
+unsafe extern "C" fn destroy(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut SharedServerAuth));
+}
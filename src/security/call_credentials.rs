@@ -0,0 +1,269 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::{ptr, thread};
+
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::executor::LocalPool;
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use futures::task::LocalSpawnExt;
+use parking_lot::Mutex;
+
+use crate::call::{RpcStatus, RpcStatusCode};
+use crate::grpc_sys::{
+    self, grpc_auth_metadata_context, grpc_call_credentials, grpc_credentials_plugin_metadata_cb,
+    grpc_metadata, grpc_metadata_credentials_plugin, grpc_status_code,
+};
+use crate::metadata::Metadata;
+use crate::SecurityLevel;
+
+const PLUGIN_TYPE: &[u8] = b"grpc-rs.metadata_credentials_plugin\0";
+
+/// The information of a call that is available to a [`MetadataCredentialsPlugin`].
+pub struct AuthMetadataContext {
+    service_url: String,
+    method_name: String,
+}
+
+impl AuthMetadataContext {
+    /// Get the fully qualified url of the service, for example
+    /// `https://example.com/helloworld.Greeter`.
+    pub fn service_url(&self) -> &str {
+        &self.service_url
+    }
+
+    /// Get the method name of the call, which is not fully qualified, for
+    /// example `SayHello`.
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+}
+
+/// A plugin that provides the auth metadata for every call, like bearer tokens.
+pub trait MetadataCredentialsPlugin: Send + Sync {
+    /// Fetches the metadata to be attached to the call.
+    ///
+    /// The future is polled on a background thread shared by all plugins, so it
+    /// should not block. Expensive work like refreshing tokens should be done
+    /// in the background and cached. If an error is returned, the call fails
+    /// with the given status.
+    fn get_metadata<'a>(
+        &'a self,
+        ctx: &'a AuthMetadataContext,
+    ) -> BoxFuture<'a, std::result::Result<Metadata, RpcStatus>>;
+}
+
+type Task = BoxFuture<'static, ()>;
+
+static WORKER: Mutex<Option<UnboundedSender<Task>>> = parking_lot::const_mutex(None);
+
+/// Runs the task on the worker thread.
+///
+/// gRPC requires the result of an asynchronous plugin to be delivered on a
+/// different thread than the one `get_metadata` is called on.
+fn spawn(task: Task) {
+    let mut worker = WORKER.lock();
+    let tx = worker.get_or_insert_with(|| {
+        let (tx, mut rx) = mpsc::unbounded::<Task>();
+        thread::Builder::new()
+            .name("grpc-call-creds".to_owned())
+            .spawn(move || {
+                let mut pool = LocalPool::new();
+                let spawner = pool.spawner();
+                pool.run_until(async {
+                    while let Some(task) = rx.next().await {
+                        spawner.spawn_local(task).unwrap();
+                    }
+                });
+            })
+            .unwrap();
+        tx
+    });
+    tx.unbounded_send(task).unwrap();
+}
+
+struct PluginState {
+    plugin: Arc<dyn MetadataCredentialsPlugin>,
+}
+
+/// The pointers are only used to report the result to gRPC.
+struct Callback {
+    cb: grpc_credentials_plugin_metadata_cb,
+    user_data: *mut c_void,
+}
+
+unsafe impl Send for Callback {}
+
+impl Callback {
+    fn done(self, res: std::result::Result<Metadata, RpcStatus>) {
+        let cb = self.cb.unwrap();
+        match res {
+            Ok(meta) => unsafe {
+                // gRPC takes its own references to the entries.
+                let (p, len, cap) = meta.into_raw_parts();
+                cb(
+                    self.user_data,
+                    p as *const grpc_metadata,
+                    len,
+                    grpc_status_code::GRPC_STATUS_OK,
+                    ptr::null(),
+                );
+                drop(Metadata::from_raw_parts(p, len, cap));
+            },
+            Err(status) => {
+                let code = match status.code() {
+                    RpcStatusCode::OK => RpcStatusCode::UNAUTHENTICATED,
+                    code => code,
+                };
+                let msg = CString::new(status.message()).unwrap_or_default();
+                unsafe { cb(self.user_data, ptr::null(), 0, code.into(), msg.as_ptr()) }
+            }
+        }
+    }
+}
+
+fn to_string(s: *const c_char) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(s).to_string_lossy().into_owned() }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn get_metadata(
+    state: *mut c_void,
+    context: grpc_auth_metadata_context,
+    cb: grpc_credentials_plugin_metadata_cb,
+    user_data: *mut c_void,
+    _creds_md: *mut grpc_metadata,
+    _num_creds_md: *mut usize,
+    _status: *mut grpc_status_code::Type,
+    _error_details: *mut *const c_char,
+) -> c_int {
+    let state = &*(state as *const PluginState);
+    let plugin = state.plugin.clone();
+    let ctx = AuthMetadataContext {
+        service_url: to_string(context.service_url),
+        method_name: to_string(context.method_name),
+    };
+    let callback = Callback { cb, user_data };
+    spawn(Box::pin(async move {
+        // A panicked plugin should not stop the worker.
+        let res = match AssertUnwindSafe(plugin.get_metadata(&ctx))
+            .catch_unwind()
+            .await
+        {
+            Ok(res) => res,
+            Err(_) => Err(RpcStatus::with_message(
+                RpcStatusCode::INTERNAL,
+                "metadata credentials plugin panicked".to_owned(),
+            )),
+        };
+        callback.done(res);
+    }));
+    // The result is reported asynchronously.
+    0
+}
+
+unsafe extern "C" fn debug_string(_: *mut c_void) -> *mut c_char {
+    grpc_sys::gpr_strdup(PLUGIN_TYPE.as_ptr() as *const c_char)
+}
+
+unsafe extern "C" fn destroy(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut PluginState));
+}
+
+struct RawCallCredentials(*mut grpc_call_credentials);
+
+// Call credentials are immutable and reference counted in gRPC core.
+unsafe impl Send for RawCallCredentials {}
+unsafe impl Sync for RawCallCredentials {}
+
+impl Drop for RawCallCredentials {
+    fn drop(&mut self) {
+        unsafe { grpc_sys::grpc_call_credentials_release(self.0) }
+    }
+}
+
+/// Credentials that are attached to every call, like bearer tokens.
+///
+/// They can be combined with [`ChannelCredentials`] by [`ChannelCredentials::compose`]
+/// to be used by all calls of a channel, or set for a single call by
+/// [`CallOption::call_credentials`].
+///
+/// Call credentials are only sent over connections whose security level is at
+/// least the minimum one of the credentials, which is
+/// [`SecurityLevel::PrivacyAndIntegrity`] by default.
+///
+/// [`ChannelCredentials`]: crate::ChannelCredentials
+/// [`ChannelCredentials::compose`]: crate::ChannelCredentials::compose
+/// [`CallOption::call_credentials`]: crate::CallOption::call_credentials
+#[derive(Clone)]
+pub struct CallCredentials {
+    creds: Arc<RawCallCredentials>,
+}
+
+impl CallCredentials {
+    /// Create call credentials that fetch metadata from the plugin.
+    pub fn from_plugin<P: MetadataCredentialsPlugin + 'static>(plugin: P) -> CallCredentials {
+        CallCredentials::from_plugin_with_min_security_level(
+            plugin,
+            SecurityLevel::PrivacyAndIntegrity,
+        )
+    }
+
+    /// Create call credentials that fetch metadata from the plugin, and are only
+    /// sent over connections of at least `level`.
+    ///
+    /// For example, [`LocalConnectType::LocalTcp`] connections are of
+    /// [`SecurityLevel::None`], so the credentials sent over them need to use it.
+    ///
+    /// [`LocalConnectType::LocalTcp`]: crate::LocalConnectType::LocalTcp
+    pub fn from_plugin_with_min_security_level<P: MetadataCredentialsPlugin + 'static>(
+        plugin: P,
+        level: SecurityLevel,
+    ) -> CallCredentials {
+        let state = Box::new(PluginState {
+            plugin: Arc::new(plugin),
+        });
+        let plugin = grpc_metadata_credentials_plugin {
+            get_metadata: Some(get_metadata),
+            debug_string: Some(debug_string),
+            destroy: Some(destroy),
+            state: Box::into_raw(state) as *mut c_void,
+            type_: PLUGIN_TYPE.as_ptr() as *const c_char,
+        };
+        let creds = unsafe {
+            grpc_sys::grpc_metadata_credentials_create_from_plugin(
+                plugin,
+                level.to_native(),
+                ptr::null_mut(),
+            )
+        };
+        CallCredentials {
+            creds: Arc::new(RawCallCredentials(creds)),
+        }
+    }
+
+    /// Combine two call credentials, the metadata of both are attached to calls.
+    pub fn compose(&self, other: &CallCredentials) -> CallCredentials {
+        let creds = unsafe {
+            grpc_sys::grpc_composite_call_credentials_create(
+                self.as_ptr(),
+                other.as_ptr(),
+                ptr::null_mut(),
+            )
+        };
+        CallCredentials {
+            creds: Arc::new(RawCallCredentials(creds)),
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut grpc_call_credentials {
+        self.creds.0
+    }
+}
@@ -1,15 +1,23 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error as StdError;
 use std::ffi::CString;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::{mem, ptr};
 
+use parking_lot::RwLock;
+
+use super::auth_processor::{ServerAuth, SharedServerAuth};
+use super::peer_verifier::{self, ClientChecks, PeerChecks, Rejection};
+use super::{AuthMetadataProcessor, CallCredentials, CertificateRevocationList, PeerVerifier};
 use crate::error::{Error, Result};
 use crate::grpc_sys::grpc_ssl_certificate_config_reload_status::{self, *};
 use crate::grpc_sys::grpc_ssl_client_certificate_request_type::*;
 use crate::grpc_sys::{
-    self, grpc_channel_credentials, grpc_server_credentials,
-    grpc_ssl_client_certificate_request_type, grpc_ssl_server_certificate_config,
+    self, grpc_channel_credentials, grpc_local_connect_type, grpc_server_credentials,
+    grpc_ssl_client_certificate_request_type, grpc_ssl_server_certificate_config, grpc_tls_version,
 };
 
 #[repr(u32)]
@@ -67,6 +75,99 @@ pub enum CertificateRequestType {
         GRPC_SSL_REQUEST_AND_REQUIRE_CLIENT_CERTIFICATE_AND_VERIFY as u32,
 }
 
+/// The type of connections that local credentials are applied to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LocalConnectType {
+    /// Unix domain sockets, whose addresses start with `unix:` or `unix-abstract:`.
+    ///
+    /// The connections are considered private and integral, so call credentials
+    /// can be sent over them.
+    Uds,
+    /// TCP connections to loopback addresses, like `127.0.0.1:50051`.
+    ///
+    /// The connections are not protected, so their security level is
+    /// [`SecurityLevel::None`]. Call credentials can still be sent over them if
+    /// they are created by [`CallCredentials::from_plugin_with_min_security_level`]
+    /// with that level.
+    ///
+    /// [`SecurityLevel::None`]: crate::SecurityLevel::None
+    /// [`CallCredentials::from_plugin_with_min_security_level`]: crate::CallCredentials::from_plugin_with_min_security_level
+    LocalTcp,
+}
+
+impl LocalConnectType {
+    #[inline]
+    pub(crate) fn to_native(self) -> grpc_local_connect_type {
+        match self {
+            LocalConnectType::Uds => grpc_local_connect_type::UDS,
+            LocalConnectType::LocalTcp => grpc_local_connect_type::LOCAL_TCP,
+        }
+    }
+}
+
+/// The versions of TLS that gRPC core supports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.2, the default minimum version.
+    Tls12,
+    /// TLS 1.3, the default maximum version.
+    Tls13,
+}
+
+impl TlsVersion {
+    #[inline]
+    fn to_native(self) -> grpc_tls_version {
+        match self {
+            TlsVersion::Tls12 => grpc_tls_version::TLS1_2,
+            TlsVersion::Tls13 => grpc_tls_version::TLS1_3,
+        }
+    }
+}
+
+/// The range of TLS versions configured on a builder.
+#[derive(Clone, Copy, Default, Hash)]
+struct TlsVersions {
+    min: Option<TlsVersion>,
+    max: Option<TlsVersion>,
+}
+
+impl TlsVersions {
+    /// Uses the versions of `other` if none is set.
+    fn inherit(&mut self, other: &TlsVersions) {
+        if self.min.is_none() && self.max.is_none() {
+            *self = *other;
+        }
+    }
+
+    /// Returns the range to set on credentials, `None` if the default one of gRPC
+    /// core is used.
+    fn range(&self) -> Option<(grpc_tls_version, grpc_tls_version)> {
+        if self.min.is_none() && self.max.is_none() {
+            return None;
+        }
+        let min = self.min.unwrap_or(TlsVersion::Tls12);
+        let max = self.max.unwrap_or(TlsVersion::Tls13);
+        assert!(
+            min <= max,
+            "minimum TLS version {:?} is greater than maximum {:?}",
+            min,
+            max
+        );
+        Some((min.to_native(), max.to_native()))
+    }
+}
+
+const TLS_VERSIONS_UNSUPPORTED: &str =
+    "TLS versions are only supported when grpcio-sys builds grpc from source";
+
+/// Returns an error if the linked gRPC core can't set TLS versions.
+fn require_tls_versions() -> Result<()> {
+    if unsafe { grpc_sys::grpcwrap_tls_versions_supported() } == 0 {
+        return Err(Error::Unsupported(TLS_VERSIONS_UNSUPPORTED.to_owned()));
+    }
+    Ok(())
+}
+
 /// Traits to retrieve updated SSL server certificates, private keys, and trusted CAs
 /// (for client authentication).
 pub trait ServerCredentialsFetcher {
@@ -76,9 +177,31 @@ pub trait ServerCredentialsFetcher {
     /// connection is about to be accepted. When returning `None` or error, gRPC
     /// will continue to use the previous certificates returned by the method. If no
     /// valid credentials is returned during initialization, the server will fail to start.
+    ///
+    /// The revocation list, peer verifier and auth metadata processor of the returned
+    /// builder replace the ones returned before, and apply to every call from then on.
+    /// The TLS versions apply to new connections, the ones returned before are kept
+    /// unless the builder sets its own.
     fn fetch(&self) -> std::result::Result<Option<ServerCredentialsBuilder>, Box<dyn StdError>>;
 }
 
+/// Traits to retrieve updated SSL client certificates, private keys, and trusted CAs
+/// (for server authentication).
+pub trait ChannelCredentialsFetcher: Send + Sync {
+    /// Retrieves updated credentials.
+    ///
+    /// The method will be called every time a channel using the credentials is about
+    /// to reconnect, that is when it becomes idle or fails to connect. It's called
+    /// on a background thread shared by all channels, so it may read files, but
+    /// should not block for long. When returning `None`, error or the credentials
+    /// in use, gRPC will continue to use the previous credentials without rebuilding
+    /// the channel.
+    ///
+    /// The revocation list, peer verifier and TLS versions of the original
+    /// credentials are kept unless the returned builder sets its own.
+    fn fetch(&self) -> std::result::Result<Option<ChannelCredentialsBuilder>, Box<dyn StdError>>;
+}
+
 impl CertificateRequestType {
     #[inline]
     pub(crate) fn to_native(self) -> grpc_ssl_client_certificate_request_type {
@@ -86,7 +209,7 @@ impl CertificateRequestType {
     }
 }
 
-fn clear_key_securely(key: &mut [u8]) {
+pub(crate) fn clear_key_securely(key: &mut [u8]) {
     unsafe {
         for b in key {
             ptr::write_volatile(b, 0)
@@ -94,18 +217,71 @@ fn clear_key_securely(key: &mut [u8]) {
     }
 }
 
-pub(crate) unsafe extern "C" fn server_cert_fetcher_wrapper(
+/// A [`ServerCredentialsFetcher`] bound to server credentials.
+pub(crate) struct ServerFetcher {
+    fetcher: Box<dyn ServerCredentialsFetcher + Send + Sync>,
+    auth: SharedServerAuth,
+    // Referenced by gRPC core as long as the fetcher can be called.
+    creds: *mut grpc_server_credentials,
+}
+
+// The credentials are only accessed by fetches, which are serialized by gRPC core.
+unsafe impl Send for ServerFetcher {}
+unsafe impl Sync for ServerFetcher {}
+
+impl ServerFetcher {
+    pub fn new(fetcher: Box<dyn ServerCredentialsFetcher + Send + Sync>) -> ServerFetcher {
+        ServerFetcher {
+            fetcher,
+            auth: Arc::default(),
+            creds: ptr::null_mut(),
+        }
+    }
+
+    /// Builds the credentials that retrieve certificates and checks from the fetcher.
+    ///
+    /// # Safety
+    ///
+    /// The fetcher must outlive the credentials and must not be moved.
+    pub unsafe fn build_credentials(
+        &mut self,
+        cer_request_type: CertificateRequestType,
+    ) -> ServerCredentials {
+        let opt = grpc_sys::grpc_ssl_server_credentials_create_options_using_config_fetcher(
+            cer_request_type.to_native(),
+            Some(server_cert_fetcher_wrapper),
+            self as *mut ServerFetcher as _,
+        );
+        let creds = grpc_sys::grpc_ssl_server_credentials_create_with_options(opt);
+        self.creds = creds;
+        // The checks may be returned by the fetcher later.
+        let processor = ServerAuth::auth_metadata_processor(self.auth.clone());
+        grpc_sys::grpc_server_credentials_set_auth_metadata_processor(creds, processor);
+        ServerCredentials { creds }
+    }
+}
+
+unsafe extern "C" fn server_cert_fetcher_wrapper(
     user_data: *mut std::os::raw::c_void,
     config: *mut *mut grpc_ssl_server_certificate_config,
 ) -> grpc_ssl_certificate_config_reload_status {
     if user_data.is_null() {
         panic!("fetcher user_data must be set up!");
     }
-    let f: &mut dyn ServerCredentialsFetcher =
-        (&mut *(user_data as *mut Box<dyn ServerCredentialsFetcher>)).as_mut();
-    let result = f.fetch();
+    let f = &*(user_data as *const ServerFetcher);
+    let result = f.fetcher.fetch();
     match result {
         Ok(Some(builder)) => {
+            *f.auth.write() = builder.server_auth();
+            // gRPC core reads the versions when it switches to the new config.
+            if let Some((min, max)) = builder.tls_versions.range() {
+                if grpc_sys::grpcwrap_ssl_server_credentials_set_tls_versions(f.creds, min, max)
+                    == 0
+                {
+                    warn!("{}", TLS_VERSIONS_UNSUPPORTED);
+                    return GRPC_SSL_CERTIFICATE_CONFIG_RELOAD_FAIL;
+                }
+            }
             let new_config = builder.build_config();
             *config = new_config;
         }
@@ -125,6 +301,9 @@ pub struct ServerCredentialsBuilder {
     root: Option<CString>,
     key_cert_pairs: Vec<grpcio_sys::grpc_ssl_pem_key_cert_pair>,
     cer_request_type: CertificateRequestType,
+    checks: PeerChecks,
+    processor: Option<Arc<dyn AuthMetadataProcessor>>,
+    tls_versions: TlsVersions,
 }
 
 impl ServerCredentialsBuilder {
@@ -134,6 +313,9 @@ impl ServerCredentialsBuilder {
             root: None,
             key_cert_pairs: vec![],
             cer_request_type: CertificateRequestType::DontRequestClientCertificate,
+            checks: PeerChecks::default(),
+            processor: None,
+            tls_versions: TlsVersions::default(),
         }
     }
 
@@ -165,6 +347,64 @@ impl ServerCredentialsBuilder {
         self
     }
 
+    /// Set the CRLs to reject the clients whose certificates are revoked.
+    ///
+    /// The clients are checked during handshakes, so the calls of rejected clients
+    /// fail with `UNAVAILABLE`. Returns [`Error::Unsupported`] if the linked gRPC
+    /// core can't check clients during handshakes, which requires grpcio-sys to
+    /// build grpc from source.
+    pub fn revocation_list(
+        mut self,
+        crl: CertificateRevocationList,
+    ) -> Result<ServerCredentialsBuilder> {
+        peer_verifier::require_peer_checks()?;
+        self.checks.crl = Some(crl);
+        Ok(self)
+    }
+
+    /// Set the verifier to check the certificates presented by clients.
+    ///
+    /// See [`ServerCredentialsBuilder::revocation_list`] for how clients are
+    /// rejected and the requirement.
+    pub fn peer_verifier<V: PeerVerifier + 'static>(
+        mut self,
+        verifier: V,
+    ) -> Result<ServerCredentialsBuilder> {
+        peer_verifier::require_peer_checks()?;
+        self.checks.verifier = Some(Arc::new(verifier));
+        Ok(self)
+    }
+
+    /// Set the processor to authenticate calls by their metadata.
+    ///
+    /// It runs after the checks of the client certificate.
+    pub fn auth_metadata_processor<P: AuthMetadataProcessor + 'static>(
+        mut self,
+        processor: P,
+    ) -> ServerCredentialsBuilder {
+        self.processor = Some(Arc::new(processor));
+        self
+    }
+
+    /// Set the minimum TLS version accepted by the server.
+    ///
+    /// Returns [`Error::Unsupported`] if grpc is linked from the system, setting
+    /// TLS versions requires grpcio-sys to build grpc from the bundled sources.
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Result<ServerCredentialsBuilder> {
+        require_tls_versions()?;
+        self.tls_versions.min = Some(version);
+        Ok(self)
+    }
+
+    /// Set the maximum TLS version accepted by the server.
+    ///
+    /// See [`ServerCredentialsBuilder::min_tls_version`] for the requirement.
+    pub fn max_tls_version(mut self, version: TlsVersion) -> Result<ServerCredentialsBuilder> {
+        require_tls_versions()?;
+        self.tls_versions.max = Some(version);
+        Ok(self)
+    }
+
     /// Finalize the [`ServerCredentialsBuilder`] and build the
     /// [`*mut grpcio_sys::bindings::grpc_ssl_server_certificate_config`].
     unsafe fn build_config(mut self) -> *mut grpcio_sys::grpc_ssl_server_certificate_config {
@@ -183,8 +423,23 @@ impl ServerCredentialsBuilder {
         cfg
     }
 
+    fn server_auth(&self) -> ServerAuth {
+        let mut checks = self.checks.clone();
+        checks.set_roots(self.root.as_deref());
+        ServerAuth::new(checks, self.processor.clone())
+    }
+
     /// Finalize the [`ServerCredentialsBuilder`] and build the [`ServerCredentials`].
     pub fn build(self) -> ServerCredentials {
+        let auth = self.server_auth();
+        let processor = if auth.is_empty() {
+            None
+        } else {
+            Some(ServerAuth::auth_metadata_processor(Arc::new(RwLock::new(
+                auth,
+            ))))
+        };
+        let tls_versions = self.tls_versions.range();
         let credentials = unsafe {
             let opt = grpcio_sys::grpc_ssl_server_credentials_create_options_using_config(
                 self.cer_request_type.to_native(),
@@ -192,8 +447,27 @@ impl ServerCredentialsBuilder {
             );
             grpcio_sys::grpc_ssl_server_credentials_create_with_options(opt)
         };
-
-        ServerCredentials { creds: credentials }
+        let credentials = ServerCredentials { creds: credentials };
+        if let Some((min, max)) = tls_versions {
+            let res = unsafe {
+                grpc_sys::grpcwrap_ssl_server_credentials_set_tls_versions(
+                    credentials.creds,
+                    min,
+                    max,
+                )
+            };
+            // The versions can only be set when they are supported.
+            debug_assert_ne!(res, 0);
+        }
+        if let Some(processor) = processor {
+            unsafe {
+                grpc_sys::grpc_server_credentials_set_auth_metadata_processor(
+                    credentials.creds,
+                    processor,
+                )
+            }
+        }
+        credentials
     }
 }
 
@@ -219,7 +493,16 @@ pub struct ServerCredentials {
 unsafe impl Send for ServerCredentials {}
 
 impl ServerCredentials {
-    pub(crate) unsafe fn frow_raw(creds: *mut grpc_server_credentials) -> ServerCredentials {
+    /// Create credentials for local connections of the given type, which don't
+    /// need any certificate.
+    ///
+    /// Connections of other types are rejected by the server, and the transport
+    /// security type in [`AuthContext`] is `local`.
+    ///
+    /// [`AuthContext`]: crate::AuthContext
+    pub fn local(connect_type: LocalConnectType) -> ServerCredentials {
+        let creds =
+            unsafe { grpc_sys::grpc_local_server_credentials_create(connect_type.to_native()) };
         ServerCredentials { creds }
     }
 
@@ -240,6 +523,9 @@ impl Drop for ServerCredentials {
 pub struct ChannelCredentialsBuilder {
     root: Option<CString>,
     cert_key_pair: Option<(CString, CString)>,
+    checks: PeerChecks,
+    tls_versions: TlsVersions,
+    fetcher: Option<Box<dyn ChannelCredentialsFetcher>>,
 }
 
 impl ChannelCredentialsBuilder {
@@ -248,6 +534,9 @@ impl ChannelCredentialsBuilder {
         ChannelCredentialsBuilder {
             root: None,
             cert_key_pair: None,
+            checks: PeerChecks::default(),
+            tls_versions: TlsVersions::default(),
+            fetcher: None,
         }
     }
 
@@ -272,8 +561,79 @@ impl ChannelCredentialsBuilder {
         self
     }
 
+    /// Set the CRLs to reject the servers whose certificates are revoked.
+    ///
+    /// A rejected server fails the handshake, so the calls fail with `UNAVAILABLE`
+    /// and the rejection message. Returns [`Error::Unsupported`] if the linked gRPC
+    /// core can't check servers during handshakes, which requires grpcio-sys to
+    /// build grpc from source.
+    pub fn revocation_list(
+        mut self,
+        crl: CertificateRevocationList,
+    ) -> Result<ChannelCredentialsBuilder> {
+        peer_verifier::require_peer_checks()?;
+        self.checks.crl = Some(crl);
+        Ok(self)
+    }
+
+    /// Set the verifier to check the certificate presented by the server.
+    ///
+    /// See [`ChannelCredentialsBuilder::revocation_list`] for how servers are
+    /// rejected and the requirement.
+    pub fn peer_verifier<V: PeerVerifier + 'static>(
+        mut self,
+        verifier: V,
+    ) -> Result<ChannelCredentialsBuilder> {
+        peer_verifier::require_peer_checks()?;
+        self.checks.verifier = Some(Arc::new(verifier));
+        Ok(self)
+    }
+
+    /// Set the minimum TLS version used to connect to the server.
+    ///
+    /// Returns [`Error::Unsupported`] if grpc is linked from the system, setting
+    /// TLS versions requires grpcio-sys to build grpc from the bundled sources.
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Result<ChannelCredentialsBuilder> {
+        require_tls_versions()?;
+        self.tls_versions.min = Some(version);
+        Ok(self)
+    }
+
+    /// Set the maximum TLS version used to connect to the server.
+    ///
+    /// See [`ChannelCredentialsBuilder::min_tls_version`] for the requirement.
+    pub fn max_tls_version(mut self, version: TlsVersion) -> Result<ChannelCredentialsBuilder> {
+        require_tls_versions()?;
+        self.tls_versions.max = Some(version);
+        Ok(self)
+    }
+
+    /// Set the fetcher to retrieve updated credentials.
+    ///
+    /// Channels built with the credentials are kept alive when the credentials
+    /// change, new connections use the updated credentials while the existing
+    /// ones are not affected.
+    pub fn fetcher(
+        mut self,
+        fetcher: Box<dyn ChannelCredentialsFetcher>,
+    ) -> ChannelCredentialsBuilder {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Digests what the credentials are built from, except the checks of the peer.
+    fn digest(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.root.hash(&mut hasher);
+        self.cert_key_pair.hash(&mut hasher);
+        self.tls_versions.hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// Finalize the [`ChannelCredentialsBuilder`] and build the [`ChannelCredentials`].
     pub fn build(mut self) -> ChannelCredentials {
+        let digest = self.digest();
+        self.checks.set_roots(self.root.as_deref());
         let root_ptr = self
             .root
             .take()
@@ -292,14 +652,14 @@ impl ChannelCredentialsBuilder {
                 grpcio_sys::grpc_ssl_credentials_create_ex(
                     root_ptr,
                     ptr::null_mut(),
-                    ptr::null_mut(),
+                    ptr::null(),
                     ptr::null_mut(),
                 )
             } else {
                 grpcio_sys::grpc_ssl_credentials_create_ex(
                     root_ptr,
                     &mut pair,
-                    ptr::null_mut(),
+                    ptr::null(),
                     ptr::null_mut(),
                 )
             }
@@ -319,7 +679,22 @@ impl ChannelCredentialsBuilder {
             }
         }
 
-        ChannelCredentials { creds }
+        let creds = ChannelCredentials {
+            creds,
+            call_creds: None,
+            checks: self.checks.clone(),
+            tls_versions: self.tls_versions,
+            fetcher: self.fetcher.take().map(Arc::from),
+            digest,
+        };
+        if let Some((min, max)) = self.tls_versions.range() {
+            let res = unsafe {
+                grpc_sys::grpcwrap_ssl_credentials_set_tls_versions(creds.creds, min, max)
+            };
+            // The versions can only be set when they are supported.
+            debug_assert_ne!(res, 0);
+        }
+        creds
     }
 }
 
@@ -333,10 +708,18 @@ impl Drop for ChannelCredentialsBuilder {
 
 /// Client-side SSL credentials.
 ///
-/// Use [`ChannelCredentialsBuilder`] or [`ChannelCredentials::google_default_credentials`] to
-/// build a [`ChannelCredentials`].
+/// Use [`ChannelCredentialsBuilder`], [`ChannelCredentials::google_default_credentials`] or
+/// [`ChannelCredentials::local`] to build a [`ChannelCredentials`].
 pub struct ChannelCredentials {
     creds: *mut grpc_channel_credentials,
+    // Kept to compose the credentials returned by the fetcher.
+    call_creds: Option<CallCredentials>,
+    // Kept to check the peer with the credentials returned by the fetcher.
+    checks: PeerChecks,
+    tls_versions: TlsVersions,
+    fetcher: Option<Arc<dyn ChannelCredentialsFetcher>>,
+    // Kept to skip the fetched credentials that are the same.
+    digest: u64,
 }
 
 impl ChannelCredentials {
@@ -344,6 +727,67 @@ impl ChannelCredentials {
         self.creds
     }
 
+    /// Combine with call credentials, which are attached to every call made on
+    /// the channels built with the returned credentials.
+    pub fn compose(mut self, creds: &CallCredentials) -> ChannelCredentials {
+        let composite = unsafe {
+            grpc_sys::grpc_composite_channel_credentials_create(
+                self.creds,
+                creds.as_ptr(),
+                ptr::null_mut(),
+            )
+        };
+        let call_creds = match self.call_creds.take() {
+            Some(c) => c.compose(creds),
+            None => creds.clone(),
+        };
+        ChannelCredentials {
+            creds: composite,
+            call_creds: Some(call_creds),
+            checks: mem::take(&mut self.checks),
+            tls_versions: self.tls_versions,
+            fetcher: self.fetcher.take(),
+            digest: self.digest,
+        }
+    }
+
+    pub(crate) fn reloader(&self) -> Option<CredentialsReloader> {
+        Some(CredentialsReloader {
+            fetcher: self.fetcher.clone()?,
+            call_creds: self.call_creds.clone(),
+            checks: self.checks.clone(),
+            tls_versions: self.tls_versions,
+            digest: self.digest,
+        })
+    }
+
+    /// Returns where a channel built with the credentials records why its server
+    /// is rejected, if the credentials check the server or may be reloaded with
+    /// ones that do.
+    pub(crate) fn new_rejection(&self) -> Option<Rejection> {
+        if self.checks.is_empty() && self.fetcher.is_none() {
+            return None;
+        }
+        Some(Rejection::default())
+    }
+
+    /// Returns the checks of the server to pass to a channel that expects the
+    /// server to have `target_name`, if the credentials check the server.
+    pub(crate) fn client_checks(
+        &self,
+        target_name: String,
+        rejection: &Rejection,
+    ) -> Option<Arc<ClientChecks>> {
+        if self.checks.is_empty() {
+            return None;
+        }
+        Some(Arc::new(ClientChecks::new(
+            self.checks.clone(),
+            target_name,
+            rejection.clone(),
+        )))
+    }
+
     /// Try to build a [`ChannelCredentials`] to authenticate with Google OAuth credentials.
     pub fn google_default_credentials() -> Result<ChannelCredentials> {
         // Initialize the runtime here. Because this is an associated method
@@ -356,8 +800,77 @@ impl ChannelCredentials {
         if creds.is_null() {
             Err(Error::GoogleAuthenticationFailed)
         } else {
-            Ok(ChannelCredentials { creds })
+            Ok(ChannelCredentials {
+                creds,
+                call_creds: None,
+                checks: PeerChecks::default(),
+                tls_versions: TlsVersions::default(),
+                fetcher: None,
+                digest: 0,
+            })
+        }
+    }
+
+    /// Create credentials for local connections of the given type, which don't
+    /// need any certificate.
+    ///
+    /// The channel fails to connect if the address doesn't match the type. Call
+    /// credentials composed with it are sent as long as their minimum security
+    /// level is met, see [`LocalConnectType`].
+    pub fn local(connect_type: LocalConnectType) -> ChannelCredentials {
+        let creds = unsafe { grpc_sys::grpc_local_credentials_create(connect_type.to_native()) };
+        ChannelCredentials {
+            creds,
+            call_creds: None,
+            checks: PeerChecks::default(),
+            tls_versions: TlsVersions::default(),
+            fetcher: None,
+            digest: 0,
+        }
+    }
+}
+
+/// Rebuilds [`ChannelCredentials`] with the updates returned by the fetcher.
+#[derive(Clone)]
+pub(crate) struct CredentialsReloader {
+    fetcher: Arc<dyn ChannelCredentialsFetcher>,
+    call_creds: Option<CallCredentials>,
+    checks: PeerChecks,
+    tls_versions: TlsVersions,
+    digest: u64,
+}
+
+impl CredentialsReloader {
+    /// Returns new credentials if the fetcher returns ones that differ from the
+    /// credentials in use.
+    ///
+    /// The revocation list, peer verifier and TLS versions of the original
+    /// credentials are kept unless the updated ones set their own.
+    pub fn reload(&mut self) -> Option<ChannelCredentials> {
+        let mut builder = match self.fetcher.fetch() {
+            Ok(Some(builder)) => builder,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!("channel credentials fetcher met error: {}", e);
+                return None;
+            }
+        };
+        let own_checks = !builder.checks.is_empty();
+        builder.checks.inherit(&self.checks);
+        builder.tls_versions.inherit(&self.tls_versions);
+        // The checks can't be compared, so the ones set by the fetcher are always
+        // taken as a change.
+        let digest = builder.digest();
+        if !own_checks && digest == self.digest {
+            return None;
         }
+        self.digest = digest;
+        let mut creds = builder.build();
+        creds.fetcher = Some(self.fetcher.clone());
+        Some(match &self.call_creds {
+            Some(call_creds) => creds.compose(call_creds),
+            None => creds,
+        })
     }
 }
 
@@ -0,0 +1,222 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::{fs, io};
+
+use parking_lot::{Mutex, RwLock};
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+
+use super::pem::{self, CertInfo, SubjectKey};
+
+// 1.2.840.113549.1.1.{5,11,12,13}
+const OID_SHA1_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05];
+const OID_SHA256_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+const OID_SHA384_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c];
+const OID_SHA512_WITH_RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d];
+// 1.2.840.10045.4.3.{2,3}
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_ECDSA_WITH_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+// 1.2.840.10045.3.1.7
+const OID_P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+// 1.3.132.0.34
+const OID_P384: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+/// Returns the algorithm to verify signatures of the given algorithm made by
+/// the owner of `key`.
+fn verification_algorithm(
+    signature: &[u8],
+    key: &SubjectKey,
+) -> Option<&'static dyn VerificationAlgorithm> {
+    let rsa = key.algorithm == pem::OID_RSA;
+    let curve = match &key.curve {
+        Some(curve) if key.algorithm == pem::OID_EC => curve.as_slice(),
+        _ => &[],
+    };
+    let alg: &'static dyn VerificationAlgorithm = match (signature, curve) {
+        (OID_SHA1_WITH_RSA, _) if rsa => &signature::RSA_PKCS1_2048_8192_SHA1_FOR_LEGACY_USE_ONLY,
+        (OID_SHA256_WITH_RSA, _) if rsa => &signature::RSA_PKCS1_2048_8192_SHA256,
+        (OID_SHA384_WITH_RSA, _) if rsa => &signature::RSA_PKCS1_2048_8192_SHA384,
+        (OID_SHA512_WITH_RSA, _) if rsa => &signature::RSA_PKCS1_2048_8192_SHA512,
+        (OID_ECDSA_WITH_SHA256, OID_P256) => &signature::ECDSA_P256_SHA256_ASN1,
+        (OID_ECDSA_WITH_SHA256, OID_P384) => &signature::ECDSA_P384_SHA256_ASN1,
+        (OID_ECDSA_WITH_SHA384, OID_P256) => &signature::ECDSA_P256_SHA384_ASN1,
+        (OID_ECDSA_WITH_SHA384, OID_P384) => &signature::ECDSA_P384_SHA384_ASN1,
+        _ => return None,
+    };
+    Some(alg)
+}
+
+/// A CRL of an issuer.
+struct Crl {
+    this_update: SystemTime,
+    next_update: Option<SystemTime>,
+    serials: HashSet<Vec<u8>>,
+    tbs: Vec<u8>,
+    signature_algorithm: Vec<u8>,
+    signature: Vec<u8>,
+    // The key that the signature is verified with, so it's only verified once.
+    verified_key: Mutex<Option<Vec<u8>>>,
+}
+
+impl Crl {
+    fn is_current(&self, now: SystemTime) -> bool {
+        self.this_update <= now && !matches!(self.next_update, Some(t) if t < now)
+    }
+
+    fn is_signed_by(&self, issuer: &CertInfo) -> bool {
+        let mut verified_key = self.verified_key.lock();
+        if verified_key.as_ref() == Some(&issuer.key.key) {
+            return true;
+        }
+        let alg = match verification_algorithm(&self.signature_algorithm, &issuer.key) {
+            Some(alg) => alg,
+            None => return false,
+        };
+        let key = UnparsedPublicKey::new(alg, &issuer.key.key);
+        if key.verify(&self.tbs, &self.signature).is_err() {
+            return false;
+        }
+        *verified_key = Some(issuer.key.key.clone());
+        true
+    }
+}
+
+/// The CRLs grouped by the normalized names of their issuers.
+type Revoked = HashMap<Vec<u8>, Vec<Crl>>;
+
+fn parse(crls: &[u8]) -> io::Result<Revoked> {
+    let mut revoked = Revoked::new();
+    for entries in pem::crl_entries(crls)? {
+        revoked.entry(entries.issuer).or_default().push(Crl {
+            this_update: entries.this_update,
+            next_update: entries.next_update,
+            serials: entries.serials.into_iter().collect(),
+            tbs: entries.tbs,
+            signature_algorithm: entries.signature_algorithm,
+            signature: entries.signature,
+            verified_key: Mutex::new(None),
+        });
+    }
+    Ok(revoked)
+}
+
+/// Certificate revocation lists (CRLs) used to reject revoked peer certificates.
+///
+/// Only PEM encoded `X509 CRL` blocks are recognized. Every certificate in the
+/// chain presented by the peer is checked during the handshake. A list is only
+/// trusted if its signature is verified by the key of its issuer, whose
+/// certificate is looked up by name in the chain and the root certificates of the
+/// credentials. RSA (PKCS #1 v1.5) and ECDSA (P-256 and P-384) signatures are
+/// supported.
+///
+/// A certificate is rejected if its issuer has lists but none of them is trusted,
+/// if any trusted list revokes it, or if no trusted list is current, that is the
+/// time is before `thisUpdate` or after `nextUpdate` of all of them. Certificates
+/// of issuers without lists are accepted.
+///
+/// Clones share the revoked certificates, so the credentials built with a list
+/// see the updates made by [`CertificateRevocationList::update`] and
+/// [`CertificateRevocationList::reload`].
+#[derive(Clone)]
+pub struct CertificateRevocationList {
+    dir: Option<Arc<PathBuf>>,
+    revoked: Arc<RwLock<Revoked>>,
+}
+
+impl CertificateRevocationList {
+    /// Create the lists from PEM encoded CRLs.
+    pub fn from_pem(crls: &[u8]) -> io::Result<CertificateRevocationList> {
+        Ok(CertificateRevocationList {
+            dir: None,
+            revoked: Arc::new(RwLock::new(parse(crls)?)),
+        })
+    }
+
+    /// Create the lists from all the files in a directory, each of which contains
+    /// PEM encoded CRLs.
+    pub fn from_dir(dir: impl Into<PathBuf>) -> io::Result<CertificateRevocationList> {
+        let crl = CertificateRevocationList {
+            dir: Some(Arc::new(dir.into())),
+            revoked: Arc::default(),
+        };
+        crl.reload()?;
+        Ok(crl)
+    }
+
+    /// Replace the lists with PEM encoded CRLs.
+    ///
+    /// If the CRLs are invalid, the lists are not changed.
+    pub fn update(&self, crls: &[u8]) -> io::Result<()> {
+        let revoked = parse(crls)?;
+        *self.revoked.write() = revoked;
+        Ok(())
+    }
+
+    /// Read the directory again if the lists are created by
+    /// [`CertificateRevocationList::from_dir`], otherwise do nothing.
+    ///
+    /// If any of the files are invalid, the lists are not changed.
+    pub fn reload(&self) -> io::Result<()> {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        let mut crls = vec![];
+        for entry in fs::read_dir(&**dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                crls.extend(fs::read(&path)?);
+                crls.push(b'\n');
+            }
+        }
+        self.update(&crls)
+    }
+
+    /// Checks that no certificate of the DER encoded `chain` is revoked. The
+    /// issuers are looked up in the chain and the DER encoded `roots`.
+    pub(crate) fn check(&self, chain: &[Vec<u8>], roots: &[Vec<u8>]) -> io::Result<()> {
+        let certs = chain
+            .iter()
+            .map(|der| pem::cert_info(der))
+            .collect::<io::Result<Vec<_>>>()?;
+        let denied = |msg| Err(io::Error::new(io::ErrorKind::PermissionDenied, msg));
+        let revoked = self.revoked.read();
+        let now = SystemTime::now();
+        // Only parsed when needed.
+        let mut trusted = None;
+        for cert in &certs {
+            let crls = match revoked.get(&cert.issuer) {
+                Some(crls) => crls,
+                None => continue,
+            };
+            let trusted: &Vec<_> = trusted.get_or_insert_with(|| {
+                roots
+                    .iter()
+                    .filter_map(|der| pem::cert_info(der).ok())
+                    .collect()
+            });
+            let issuers: Vec<_> = certs
+                .iter()
+                .chain(trusted)
+                .filter(|c| c.subject == cert.issuer)
+                .collect();
+            let crls: Vec<_> = crls
+                .iter()
+                .filter(|crl| issuers.iter().any(|i| crl.is_signed_by(i)))
+                .collect();
+            if crls.is_empty() {
+                return denied("no CRL of the issuer has a valid signature");
+            }
+            if crls.iter().any(|crl| crl.serials.contains(&cert.serial)) {
+                return denied("certificate is revoked");
+            }
+            if !crls.iter().any(|crl| crl.is_current(now)) {
+                return denied("CRL of the issuer is expired or not yet valid");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error as StdError;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+use parking_lot::Mutex;
+
+use super::credentials::clear_key_securely;
+use super::pem;
+use super::{
+    CertificateRequestType, ChannelCredentials, ChannelCredentialsBuilder,
+    ChannelCredentialsFetcher, ServerCredentialsBuilder, ServerCredentialsFetcher,
+};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The content of the watched files.
+#[derive(Hash)]
+struct Certificates {
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+    root: Option<Vec<u8>>,
+}
+
+impl Certificates {
+    fn digest(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn into_channel_builder(mut self) -> ChannelCredentialsBuilder {
+        let mut builder = ChannelCredentialsBuilder::new();
+        if let Some((cert, key)) = self.identity.take() {
+            builder = builder.cert(cert, key);
+        }
+        if let Some(root) = self.root.take() {
+            builder = builder.root_cert(root);
+        }
+        builder
+    }
+}
+
+impl Drop for Certificates {
+    fn drop(&mut self) {
+        if let Some((_, key)) = &mut self.identity {
+            clear_key_securely(key);
+        }
+    }
+}
+
+#[derive(Default)]
+struct WatchState {
+    last_refresh: Option<Instant>,
+    // Only the digest is kept, so that the private key is not held in memory.
+    digest: Option<u64>,
+}
+
+fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    fs::read(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to read {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// A certificate provider that watches PEM files on disk.
+///
+/// It can be used as a [`ServerCredentialsFetcher`] by [`ServerBuilder::bind_with_fetcher`],
+/// or to build [`ChannelCredentials`] by
+/// [`FileWatcherCertificateProvider::build_channel_credentials`].
+///
+/// The files are read at most once per refresh interval. Updated files are only
+/// used if they can be parsed and the private key matches the certificate, otherwise
+/// the error is logged and the credentials loaded before remain in use. Because the
+/// certificate and the key can't be replaced atomically, a mismatched pair may be
+/// observed in the middle of a rotation, in which case it's picked up by the next
+/// refresh.
+///
+/// [`ServerBuilder::bind_with_fetcher`]: crate::ServerBuilder::bind_with_fetcher
+pub struct FileWatcherCertificateProvider {
+    identity: Option<(PathBuf, PathBuf)>,
+    root: Option<PathBuf>,
+    refresh_interval: Duration,
+    state: Mutex<WatchState>,
+}
+
+impl FileWatcherCertificateProvider {
+    /// Initialize a new [`FileWatcherCertificateProvider`] that watches no files.
+    pub fn new() -> FileWatcherCertificateProvider {
+        FileWatcherCertificateProvider {
+            identity: None,
+            root: None,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Set the paths of the PEM encoded certificate chain and private key to
+    /// identify oneself. It's required by servers.
+    pub fn identity(
+        mut self,
+        cert_path: impl Into<PathBuf>,
+        key_path: impl Into<PathBuf>,
+    ) -> FileWatcherCertificateProvider {
+        self.identity = Some((cert_path.into(), key_path.into()));
+        self
+    }
+
+    /// Set the path of the PEM encoded root certificates to verify the peer's identity.
+    pub fn root_cert(mut self, path: impl Into<PathBuf>) -> FileWatcherCertificateProvider {
+        self.root = Some(path.into());
+        self
+    }
+
+    /// Set how often the files are checked for updates, 60 seconds by default.
+    pub fn refresh_interval(mut self, interval: Duration) -> FileWatcherCertificateProvider {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Build [`ChannelCredentials`] from the current files, which are reloaded
+    /// when the channels built with the credentials reconnect.
+    pub fn build_channel_credentials(self) -> io::Result<ChannelCredentials> {
+        let certs = self.read()?;
+        *self.state.lock() = WatchState {
+            last_refresh: Some(Instant::now()),
+            digest: Some(certs.digest()),
+        };
+        Ok(certs.into_channel_builder().fetcher(Box::new(self)).build())
+    }
+
+    fn read(&self) -> io::Result<Certificates> {
+        let identity = match &self.identity {
+            Some((cert_path, key_path)) => {
+                let cert = read_file(cert_path)?;
+                let mut key = read_file(key_path)?;
+                if let Err(e) = pem::check_key_pair(&cert, &key) {
+                    clear_key_securely(&mut key);
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!(
+                            "invalid identity {} {}: {}",
+                            cert_path.display(),
+                            key_path.display(),
+                            e
+                        ),
+                    ));
+                }
+                Some((cert, key))
+            }
+            None => None,
+        };
+        let root = match &self.root {
+            Some(path) => {
+                let root = read_file(path)?;
+                pem::check_certs(&root).map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("invalid root cert {}: {}", path.display(), e),
+                    )
+                })?;
+                Some(root)
+            }
+            None => None,
+        };
+        Ok(Certificates { identity, root })
+    }
+
+    /// Returns the certificates if the files are due to refresh and have changed.
+    fn load(&self) -> io::Result<Option<Certificates>> {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        if let Some(last) = state.last_refresh {
+            if now.saturating_duration_since(last) < self.refresh_interval {
+                return Ok(None);
+            }
+        }
+        // Errors are retried on the next refresh too.
+        state.last_refresh = Some(now);
+        let certs = self.read()?;
+        let digest = certs.digest();
+        if state.digest == Some(digest) {
+            return Ok(None);
+        }
+        state.digest = Some(digest);
+        Ok(Some(certs))
+    }
+}
+
+impl Default for FileWatcherCertificateProvider {
+    fn default() -> FileWatcherCertificateProvider {
+        FileWatcherCertificateProvider::new()
+    }
+}
+
+impl ServerCredentialsFetcher for FileWatcherCertificateProvider {
+    fn fetch(&self) -> Result<Option<ServerCredentialsBuilder>, Box<dyn StdError>> {
+        if self.identity.is_none() {
+            return Err("identity is required by servers".into());
+        }
+        let mut certs = match self.load()? {
+            Some(certs) => certs,
+            None => return Ok(None),
+        };
+        let (cert, key) = certs.identity.take().unwrap();
+        let mut builder = ServerCredentialsBuilder::new().add_cert(cert, key);
+        if let Some(root) = certs.root.take() {
+            // The request type is decided by `bind_with_fetcher` instead.
+            builder = builder.root_cert(root, CertificateRequestType::DontRequestClientCertificate);
+        }
+        Ok(Some(builder))
+    }
+}
+
+impl ChannelCredentialsFetcher for FileWatcherCertificateProvider {
+    fn fetch(&self) -> Result<Option<ChannelCredentialsBuilder>, Box<dyn StdError>> {
+        Ok(self.load()?.map(Certificates::into_channel_builder))
+    }
+}
@@ -1,10 +1,26 @@
 // Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
 
+mod auth_processor;
+mod call_credentials;
 mod credentials;
+mod crl;
+mod file_watcher;
+mod peer_verifier;
+mod pem;
+mod session_cache;
 
+pub use self::auth_processor::{AuthMetadataProcessor, AuthProcessContext};
+pub use self::call_credentials::{AuthMetadataContext, CallCredentials, MetadataCredentialsPlugin};
 pub use self::credentials::{
-    CertificateRequestType, ChannelCredentials, ChannelCredentialsBuilder, ServerCredentials,
-    ServerCredentialsBuilder, ServerCredentialsFetcher,
+    CertificateRequestType, ChannelCredentials, ChannelCredentialsBuilder,
+    ChannelCredentialsFetcher, LocalConnectType, ServerCredentials, ServerCredentialsBuilder,
+    ServerCredentialsFetcher, TlsVersion,
 };
+pub use self::crl::CertificateRevocationList;
+pub use self::file_watcher::FileWatcherCertificateProvider;
+pub use self::peer_verifier::{PeerCertificate, PeerVerifier};
+pub use self::session_cache::SslSessionCache;
 
-pub(crate) use self::credentials::server_cert_fetcher_wrapper;
+pub(crate) use self::credentials::{CredentialsReloader, ServerFetcher};
+pub(crate) use self::peer_verifier::{register_peer_checks, ClientChecks, Rejection};
+pub(crate) use self::pem::cert_subject;
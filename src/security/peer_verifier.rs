@@ -0,0 +1,296 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::ffi::{CStr, CString};
+use std::mem::ManuallyDrop;
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once};
+use std::{io, ptr, slice, thread};
+
+use parking_lot::Mutex;
+
+use super::auth_processor::{self, SharedServerAuth};
+use super::{pem, CertificateRevocationList};
+use crate::call::{RpcStatus, RpcStatusCode};
+use crate::error::Error;
+use crate::grpc_sys::{self, grpc_arg_pointer_vtable, grpc_auth_context, grpc_channel_args};
+
+const X509_PEM_CERT_PROPERTY_NAME: &[u8] = b"x509_pem_cert\0";
+const X509_PEM_CERT_CHAIN_PROPERTY_NAME: &[u8] = b"x509_pem_cert_chain\0";
+
+/// The certificate a peer presents during the TLS handshake.
+pub struct PeerCertificate<'a> {
+    target_name: Option<&'a str>,
+    cert: &'a [u8],
+    cert_chain: Option<&'a [u8]>,
+}
+
+impl<'a> PeerCertificate<'a> {
+    /// Get the name the client expects the server to have, which is the target
+    /// host or the one set by [`ChannelBuilder::override_ssl_target`].
+    ///
+    /// It's only available on the client side.
+    ///
+    /// [`ChannelBuilder::override_ssl_target`]: crate::ChannelBuilder::override_ssl_target
+    pub fn target_name(&self) -> Option<&str> {
+        self.target_name
+    }
+
+    /// Get the PEM encoded certificate of the peer.
+    pub fn pem_cert(&self) -> &[u8] {
+        self.cert
+    }
+
+    /// Get the PEM encoded certificate chain the peer presents, which usually
+    /// includes the intermediate certificates.
+    pub fn pem_cert_chain(&self) -> Option<&[u8]> {
+        self.cert_chain
+    }
+
+    /// Reads the certificates from the auth context of a connection, returns
+    /// `None` if the peer presents no certificate.
+    unsafe fn from_auth_context(
+        context: *const grpc_auth_context,
+        target_name: Option<&'a str>,
+    ) -> Option<PeerCertificate<'a>> {
+        Some(PeerCertificate {
+            target_name,
+            cert: find_property(context, X509_PEM_CERT_PROPERTY_NAME)?,
+            cert_chain: find_property(context, X509_PEM_CERT_CHAIN_PROPERTY_NAME),
+        })
+    }
+
+    /// Returns the DER encoded certificates of the peer, starting from its own.
+    pub(crate) fn certs_der(&self) -> io::Result<Vec<Vec<u8>>> {
+        let mut certs = pem::certs_der(self.cert)?;
+        if let Some(chain) = self.cert_chain {
+            for der in pem::certs_der(chain)? {
+                if !certs.contains(&der) {
+                    certs.push(der);
+                }
+            }
+        }
+        Ok(certs)
+    }
+}
+
+/// A custom check of the peer's certificate, which runs after the certificate
+/// is verified by the root certificates.
+pub trait PeerVerifier: Send + Sync {
+    /// Returns an error message if the peer should be rejected.
+    ///
+    /// It's called during the handshake on both sides, and a rejected peer fails
+    /// the handshake, so calls on both sides fail with `UNAVAILABLE`. A client that
+    /// rejects the server appends the message to the status messages of its calls.
+    /// It blocks the handshake, so it should be light-weight.
+    fn verify(&self, peer: &PeerCertificate<'_>) -> Result<(), String>;
+}
+
+/// The reason the server certificate was rejected in the last handshake of a
+/// channel, which is cleared once a handshake of the channel succeeds.
+///
+/// Every channel owns one, so the reason is never attached to calls of other
+/// channels.
+#[derive(Clone, Default)]
+pub(crate) struct Rejection(Arc<Mutex<Option<String>>>);
+
+impl Rejection {
+    /// Adds the reason to the status of a call that fails to connect, if the
+    /// last handshake of its channel was rejected.
+    pub fn amend(&self, status: &mut RpcStatus) {
+        if status.code() != RpcStatusCode::UNAVAILABLE {
+            return;
+        }
+        if let Some(reason) = &*self.0.lock() {
+            status.append_message(&format!("server certificate is rejected: {}", reason));
+        }
+    }
+}
+
+/// The checks configured on credentials.
+#[derive(Clone, Default)]
+pub(crate) struct PeerChecks {
+    pub crl: Option<CertificateRevocationList>,
+    pub verifier: Option<Arc<dyn PeerVerifier>>,
+    /// The DER encoded root certificates of the credentials, where the issuers
+    /// of CRLs are looked up.
+    pub roots: Arc<Vec<Vec<u8>>>,
+}
+
+impl PeerChecks {
+    fn check(&self, peer: &PeerCertificate<'_>) -> Result<(), String> {
+        if let Some(crl) = &self.crl {
+            let certs = peer.certs_der().map_err(|e| e.to_string())?;
+            crl.check(&certs, &self.roots).map_err(|e| e.to_string())?;
+        }
+        if let Some(verifier) = &self.verifier {
+            verifier.verify(peer)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the roots from the PEM encoded root certificates.
+    pub fn set_roots(&mut self, roots: Option<&CStr>) {
+        let roots = roots.and_then(|r| pem::certs_der(r.to_bytes()).ok());
+        self.roots = Arc::new(roots.unwrap_or_default());
+    }
+
+    /// Fills the checks that are not set with the ones of `other`.
+    pub fn inherit(&mut self, other: &PeerChecks) {
+        if self.crl.is_none() {
+            self.crl = other.crl.clone();
+        }
+        if self.verifier.is_none() {
+            self.verifier = other.verifier.clone();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.crl.is_none() && self.verifier.is_none()
+    }
+}
+
+/// The checks of the server that run during the handshakes of a channel, which
+/// are passed to gRPC core by a channel argument.
+pub(crate) struct ClientChecks {
+    checks: PeerChecks,
+    target_name: String,
+    rejection: Rejection,
+}
+
+impl ClientChecks {
+    pub fn new(checks: PeerChecks, target_name: String, rejection: Rejection) -> ClientChecks {
+        ClientChecks {
+            checks,
+            target_name,
+            rejection,
+        }
+    }
+
+    /// Sets the channel argument that refers to the checks.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in the bounds of `args`.
+    pub unsafe fn set_channel_arg(
+        checks: &Arc<ClientChecks>,
+        args: *mut grpc_channel_args,
+        index: usize,
+    ) {
+        grpc_sys::grpcwrap_channel_args_set_pointer_vtable(
+            args,
+            index,
+            grpc_sys::GRPCWRAP_ARG_PEER_CHECKS.as_ptr() as _,
+            &**checks as *const ClientChecks as _,
+            &CLIENT_CHECKS_VTABLE,
+        )
+    }
+}
+
+unsafe extern "C" fn copy_client_checks(p: *mut c_void) -> *mut c_void {
+    let checks = ManuallyDrop::new(Arc::from_raw(p as *const ClientChecks));
+    Arc::into_raw(Arc::clone(&checks)) as _
+}
+
+unsafe extern "C" fn destroy_client_checks(p: *mut c_void) {
+    drop(Arc::from_raw(p as *const ClientChecks));
+}
+
+unsafe extern "C" fn cmp_client_checks(p: *mut c_void, q: *mut c_void) -> c_int {
+    (p as usize).cmp(&(q as usize)) as c_int
+}
+
+static CLIENT_CHECKS_VTABLE: grpc_arg_pointer_vtable = grpc_arg_pointer_vtable {
+    copy: Some(copy_client_checks),
+    destroy: Some(destroy_client_checks),
+    cmp: Some(cmp_client_checks),
+};
+
+/// Registers the checks of peers to gRPC core, which runs them during handshakes.
+///
+/// Returns false if the linked gRPC core doesn't support it.
+pub(crate) fn register_peer_checks() -> bool {
+    static REGISTER: Once = Once::new();
+    static SUPPORTED: AtomicBool = AtomicBool::new(false);
+    REGISTER.call_once(|| {
+        let res = unsafe {
+            grpc_sys::grpcwrap_set_peer_checks(
+                Some(check_server),
+                Some(check_client),
+                Some(auth_processor::process_auth_metadata),
+            )
+        };
+        SUPPORTED.store(res != 0, Ordering::SeqCst);
+    });
+    SUPPORTED.load(Ordering::SeqCst)
+}
+
+/// Returns an error if the linked gRPC core can't check peers.
+pub(crate) fn require_peer_checks() -> crate::Result<()> {
+    if !register_peer_checks() {
+        return Err(Error::Unsupported(
+            "checking peers requires grpcio-sys to build grpc from source".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Converts the result of a check to the reason of rejection that gRPC core
+/// takes, which is null if the peer is accepted.
+fn into_reason(res: thread::Result<Result<(), String>>, peer: &str) -> *mut c_char {
+    let reason = match res {
+        Ok(Ok(())) => return ptr::null_mut(),
+        Ok(Err(reason)) => reason,
+        Err(_) => "peer check panicked".to_owned(),
+    };
+    warn!("{} certificate is rejected: {}", peer, reason);
+    let reason = CString::new(reason).unwrap_or_default();
+    unsafe { grpc_sys::gpr_strdup(reason.as_ptr()) }
+}
+
+unsafe extern "C" fn check_server(
+    user_data: *mut c_void,
+    ctx: *mut grpc_auth_context,
+) -> *mut c_char {
+    let checks = &*(user_data as *const ClientChecks);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let peer = PeerCertificate::from_auth_context(ctx, Some(&checks.target_name));
+        match peer {
+            Some(peer) => checks.checks.check(&peer),
+            None => Err("no certificate is presented".to_owned()),
+        }
+    }));
+    *checks.rejection.0.lock() = match &res {
+        Ok(Err(reason)) => Some(reason.clone()),
+        Ok(Ok(())) => None,
+        Err(_) => Some("peer check panicked".to_owned()),
+    };
+    into_reason(res, "server")
+}
+
+unsafe extern "C" fn check_client(state: *mut c_void, ctx: *mut grpc_auth_context) -> *mut c_char {
+    let auth = &*(state as *const SharedServerAuth);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        // Cloned so a fetcher can replace the checks while they are running.
+        let checks = auth.read().checks().clone();
+        // Nothing to check if no certificate is presented.
+        match PeerCertificate::from_auth_context(ctx, None) {
+            Some(peer) => checks.check(&peer),
+            None => Ok(()),
+        }
+    }));
+    into_reason(res, "client")
+}
+
+unsafe fn find_property<'a>(context: *const grpc_auth_context, name: &[u8]) -> Option<&'a [u8]> {
+    let mut iter = grpc_sys::grpc_auth_context_find_properties_by_name(context, name.as_ptr() as _);
+    let prop = grpc_sys::grpc_auth_property_iterator_next(&mut iter);
+    if prop.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(
+        (*prop).value as *const u8,
+        (*prop).value_length,
+    ))
+}
@@ -0,0 +1,47 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::sync::Arc;
+
+use crate::grpc_sys::{self, grpc_arg};
+
+struct RawCache(*mut grpc_sys::grpc_ssl_session_cache);
+
+// The cache is protected by a lock in gRPC Core.
+unsafe impl Send for RawCache {}
+unsafe impl Sync for RawCache {}
+
+impl Drop for RawCache {
+    fn drop(&mut self) {
+        unsafe { grpc_sys::grpc_ssl_session_cache_destroy(self.0) }
+    }
+}
+
+/// A LRU cache of TLS sessions on the client side.
+///
+/// Channels sharing the same cache resume the sessions established by each
+/// other, so reconnecting to a server doesn't need a full handshake. It's set
+/// by [`ChannelBuilder::ssl_session_cache`].
+///
+/// [`ChannelBuilder::ssl_session_cache`]: crate::ChannelBuilder::ssl_session_cache
+#[derive(Clone)]
+pub struct SslSessionCache {
+    raw: Arc<RawCache>,
+}
+
+impl SslSessionCache {
+    /// Create a cache that keeps at most `capacity` sessions, one for each server.
+    ///
+    /// If `capacity` is 0, the default capacity of gRPC Core is used.
+    pub fn new(capacity: usize) -> SslSessionCache {
+        let cache = unsafe { grpc_sys::grpc_ssl_session_cache_create_lru(capacity) };
+        SslSessionCache {
+            raw: Arc::new(RawCache(cache)),
+        }
+    }
+
+    /// Builds the channel argument that refers to the cache. The cache is
+    /// referenced again when the argument is copied.
+    pub(crate) fn channel_arg(&self) -> grpc_arg {
+        unsafe { grpc_sys::grpc_ssl_session_cache_create_channel_arg(self.raw.0) }
+    }
+}
@@ -5,9 +5,9 @@ use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
 use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
-use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::{ptr, str};
 
 use crate::grpc_sys::{self, grpc_call_error, grpc_server};
 use futures::future::Future;
@@ -17,9 +17,12 @@ use futures::task::{Context, Poll};
 use crate::call::server::*;
 use crate::call::{MessageReader, Method, MethodType};
 use crate::channel::ChannelArgs;
+use crate::channelz::{self, ServerInfo, SocketInfo};
 use crate::cq::CompletionQueue;
 use crate::env::Environment;
 use crate::error::{Error, Result};
+use crate::interceptor::{ServerInterceptor, ServerInterceptors};
+use crate::metrics::MetricsRecorder;
 use crate::task::{CallTag, CqFuture};
 use crate::RpcContext;
 use crate::RpcStatus;
@@ -81,14 +84,14 @@ fn join_host_port(host: &str, port: u16) -> String {
 mod imp {
     use super::join_host_port;
     use crate::grpc_sys::{self, grpc_server};
-    use crate::security::ServerCredentialsFetcher;
+    use crate::security::ServerFetcher;
     use crate::ServerCredentials;
 
     pub struct Binder {
         pub host: String,
         pub port: u16,
         cred: Option<ServerCredentials>,
-        _fetcher: Option<Box<Box<dyn ServerCredentialsFetcher + Send + Sync>>>,
+        _fetcher: Option<Box<ServerFetcher>>,
     }
 
     impl Binder {
@@ -106,7 +109,7 @@ mod imp {
             host: String,
             port: u16,
             cred: ServerCredentials,
-            _fetcher: Option<Box<Box<dyn ServerCredentialsFetcher + Send + Sync>>>,
+            _fetcher: Option<Box<ServerFetcher>>,
         ) -> Binder {
             let cred = Some(cred);
             Binder {
@@ -167,6 +170,7 @@ impl Debug for Binder {
 /// Use it to build a service which can be registered to a server.
 pub struct ServiceBuilder {
     handlers: HashMap<&'static [u8], BoxHandler>,
+    file_descriptors: Vec<&'static [u8]>,
 }
 
 impl ServiceBuilder {
@@ -174,9 +178,20 @@ impl ServiceBuilder {
     pub fn new() -> ServiceBuilder {
         ServiceBuilder {
             handlers: HashMap::new(),
+            file_descriptors: Vec::new(),
         }
     }
 
+    /// Attach the serialized `FileDescriptorProto`s that describe the service.
+    ///
+    /// The generated code attaches the file that defines the service and all its
+    /// dependencies, so that the registered services can be described by server
+    /// reflection.
+    pub fn add_file_descriptors(mut self, descriptors: &[&'static [u8]]) -> ServiceBuilder {
+        self.file_descriptors.extend_from_slice(descriptors);
+        self
+    }
+
     /// Add a unary RPC call handler.
     pub fn add_unary_handler<Req, Resp, F>(
         mut self,
@@ -264,6 +279,7 @@ impl ServiceBuilder {
     pub fn build(self) -> Service {
         Service {
             handlers: self.handlers,
+            file_descriptors: self.file_descriptors,
         }
     }
 }
@@ -275,6 +291,12 @@ pub enum CheckResult {
     Abort(RpcStatus),
 }
 
+/// A synchronous check that runs before the call is dispatched.
+///
+/// Use [`ServerInterceptor`] instead if the check needs to wait for asynchronous work,
+/// or to modify the metadata and observe the result of the call.
+///
+/// [`ServerInterceptor`]: crate::ServerInterceptor
 pub trait ServerChecker: Send {
     fn check(&mut self, ctx: &RpcContext) -> CheckResult;
     fn box_clone(&self) -> Box<dyn ServerChecker>;
@@ -291,6 +313,14 @@ impl Clone for Box<dyn ServerChecker> {
 /// Use [`ServiceBuilder`] to build a [`Service`].
 pub struct Service {
     handlers: HashMap<&'static [u8], BoxHandler>,
+    file_descriptors: Vec<&'static [u8]>,
+}
+
+impl Service {
+    /// Get the serialized `FileDescriptorProto`s attached to the service.
+    pub fn file_descriptors(&self) -> &[&'static [u8]] {
+        &self.file_descriptors
+    }
 }
 
 /// [`Server`] factory in order to configure the properties.
@@ -298,9 +328,13 @@ pub struct ServerBuilder {
     env: Arc<Environment>,
     binders: Vec<Binder>,
     args: Option<ChannelArgs>,
+    channelz: Option<bool>,
     slots_per_cq: usize,
     handlers: HashMap<&'static [u8], BoxHandler>,
+    file_descriptors: Vec<&'static [u8]>,
     checkers: Vec<Box<dyn ServerChecker>>,
+    interceptors: Vec<Box<dyn ServerInterceptor>>,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
 }
 
 impl ServerBuilder {
@@ -310,9 +344,13 @@ impl ServerBuilder {
             env,
             binders: Vec::new(),
             args: None,
+            channelz: None,
             slots_per_cq: DEFAULT_REQUEST_SLOTS_PER_CQ,
             handlers: HashMap::new(),
+            file_descriptors: Vec::new(),
             checkers: Vec::new(),
+            interceptors: Vec::new(),
+            recorder: None,
         }
     }
 
@@ -330,6 +368,17 @@ impl ServerBuilder {
         self
     }
 
+    /// Set whether to collect the channelz data of the server and its sockets.
+    ///
+    /// gRPC Core collects the data by default. It overrides the option set by
+    /// [`channel_args`].
+    ///
+    /// [`channel_args`]: ServerBuilder::channel_args
+    pub fn enable_channelz(mut self, enable: bool) -> ServerBuilder {
+        self.channelz = Some(enable);
+        self
+    }
+
     /// Set how many requests a completion queue can handle.
     pub fn requests_slot_per_cq(mut self, slots: usize) -> ServerBuilder {
         self.slots_per_cq = slots;
@@ -339,9 +388,32 @@ impl ServerBuilder {
     /// Register a service.
     pub fn register_service(mut self, service: Service) -> ServerBuilder {
         self.handlers.extend(service.handlers);
+        self.file_descriptors.extend(service.file_descriptors);
         self
     }
 
+    /// Get the full names of the registered services, like `helloworld.Greeter`.
+    pub fn service_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self
+            .handlers
+            .keys()
+            .filter_map(|path| {
+                let path = str::from_utf8(path).ok()?.strip_prefix('/')?;
+                path.rfind('/').map(|pos| &path[..pos])
+            })
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Get the serialized `FileDescriptorProto`s attached to the registered services.
+    ///
+    /// A file may appear more than once if it's shared by several services.
+    pub fn file_descriptors(&self) -> &[&'static [u8]] {
+        &self.file_descriptors
+    }
+
     /// Add a custom checker to handle some tasks before the grpc call handler starts.
     /// This allows users to operate grpc call based on the context. Users can add
     /// multiple checkers and they will be executed in the order added.
@@ -352,8 +424,34 @@ impl ServerBuilder {
         self
     }
 
+    /// Add an interceptor to wrap the handling of every incoming call.
+    ///
+    /// Interceptors are executed in the order added, after all checkers are passed.
+    /// When a call is finished, they are notified in the reverse order.
+    pub fn add_interceptor<I: ServerInterceptor + 'static>(
+        mut self,
+        interceptor: I,
+    ) -> ServerBuilder {
+        self.interceptors.push(Box::new(interceptor));
+        self
+    }
+
+    /// Set the recorder of the metrics of every incoming call, like the number of
+    /// handled calls and their handling time.
+    pub fn metrics_recorder<R: MetricsRecorder + 'static>(mut self, recorder: R) -> ServerBuilder {
+        self.recorder = Some(Arc::new(recorder));
+        self
+    }
+
     /// Finalize the [`ServerBuilder`] and build the [`Server`].
     pub fn build(mut self) -> Result<Server> {
+        if let Some(enable) = self.channelz {
+            self.args = Some(ChannelArgs::with_integer(
+                self.args.as_ref(),
+                grpc_sys::GRPC_ARG_ENABLE_CHANNELZ,
+                enable as i32,
+            ));
+        }
         let args = self
             .args
             .as_ref()
@@ -388,6 +486,8 @@ impl ServerBuilder {
                 }),
                 handlers: self.handlers,
                 checkers: self.checkers,
+                interceptors: Arc::new(self.interceptors),
+                recorder: self.recorder,
             })
         }
     }
@@ -396,10 +496,8 @@ impl ServerBuilder {
 #[cfg(feature = "secure")]
 mod secure_server {
     use super::{Binder, ServerBuilder};
-    use crate::grpc_sys;
     use crate::security::{
-        server_cert_fetcher_wrapper, CertificateRequestType, ServerCredentials,
-        ServerCredentialsFetcher,
+        CertificateRequestType, ServerCredentials, ServerCredentialsFetcher, ServerFetcher,
     };
 
     impl ServerBuilder {
@@ -428,23 +526,11 @@ mod secure_server {
             fetcher: Box<dyn ServerCredentialsFetcher + Send + Sync>,
             cer_request_type: CertificateRequestType,
         ) -> ServerBuilder {
-            let fetcher_wrap = Box::new(fetcher);
-            let fetcher_wrap_ptr = Box::into_raw(fetcher_wrap);
-            let (sc, fb) = unsafe {
-                let opt = grpc_sys::grpc_ssl_server_credentials_create_options_using_config_fetcher(
-                    cer_request_type.to_native(),
-                    Some(server_cert_fetcher_wrapper),
-                    fetcher_wrap_ptr as _,
-                );
-                (
-                    ServerCredentials::frow_raw(
-                        grpcio_sys::grpc_ssl_server_credentials_create_with_options(opt),
-                    ),
-                    Box::from_raw(fetcher_wrap_ptr),
-                )
-            };
+            // Boxed so the credentials can refer to it after it's moved to the binder.
+            let mut fetcher = Box::new(ServerFetcher::new(fetcher));
+            let sc = unsafe { fetcher.build_credentials(cer_request_type) };
             self.binders
-                .push(Binder::with_cred(host.into(), port, sc, Some(fb)));
+                .push(Binder::with_cred(host.into(), port, sc, Some(fetcher)));
             self
         }
     }
@@ -473,6 +559,8 @@ pub struct RequestCallContext {
     server: Arc<ServerCore>,
     registry: Arc<UnsafeCell<HashMap<&'static [u8], BoxHandler>>>,
     checkers: Vec<Box<dyn ServerChecker>>,
+    interceptors: ServerInterceptors,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
 }
 
 impl RequestCallContext {
@@ -487,6 +575,14 @@ impl RequestCallContext {
     pub(crate) fn get_checker(&self) -> Vec<Box<dyn ServerChecker>> {
         self.checkers.clone()
     }
+
+    pub(crate) fn interceptors(&self) -> ServerInterceptors {
+        self.interceptors.clone()
+    }
+
+    pub(crate) fn recorder(&self) -> Option<Arc<dyn MetricsRecorder>> {
+        self.recorder.clone()
+    }
 }
 
 // Apparently, its life time is guaranteed by the ref count, hence is safe to be sent
@@ -550,6 +646,8 @@ pub struct Server {
     core: Arc<ServerCore>,
     handlers: HashMap<&'static [u8], BoxHandler>,
     checkers: Vec<Box<dyn ServerChecker>>,
+    interceptors: ServerInterceptors,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
 }
 
 impl Server {
@@ -594,6 +692,8 @@ impl Server {
                     server: self.core.clone(),
                     registry: Arc::new(UnsafeCell::new(registry)),
                     checkers: self.checkers.clone(),
+                    interceptors: self.interceptors.clone(),
+                    recorder: self.recorder.clone(),
                 };
                 for _ in 0..self.core.slots_per_cq {
                     request_call(rc.clone(), cq);
@@ -607,6 +707,40 @@ impl Server {
         self.core.binders.iter().map(|b| (&b.host, b.port))
     }
 
+    /// Get the channelz id of the server.
+    ///
+    /// It's `None` if channelz is disabled by [`ServerBuilder::enable_channelz`], or
+    /// grpcio-sys doesn't build grpc from the bundled sources.
+    pub fn channelz_id(&self) -> Option<i64> {
+        channelz::id(unsafe { grpc_sys::grpcwrap_server_get_channelz_id(self.core.server) })
+    }
+
+    /// Get the channelz data of the server.
+    pub fn channelz(&self) -> Option<ServerInfo> {
+        channelz::get_server(self.channelz_id()?)
+    }
+
+    /// Get the channelz data of the sockets accepted by the server.
+    pub fn channelz_sockets(&self) -> Vec<SocketInfo> {
+        let id = match self.channelz_id() {
+            Some(id) => id,
+            None => return vec![],
+        };
+        let mut sockets = vec![];
+        let mut start = 0;
+        while let Some((refs, end)) = channelz::get_server_sockets(id, start, 0) {
+            start = match refs.last() {
+                Some(r) => r.id + 1,
+                None => break,
+            };
+            sockets.extend(refs.iter().filter_map(|r| channelz::get_socket(r.id)));
+            if end {
+                break;
+            }
+        }
+        sockets
+    }
+
     /// Add an rpc channel for an established connection represented as a file
     /// descriptor. Takes ownership of the file descriptor, closing it when
     /// channel is closed.
@@ -0,0 +1,500 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Typed builders for the [service config] consumed by gRPC C core.
+//!
+//! [service config]: https://github.com/grpc/grpc/blob/master/doc/service_config.md
+
+use std::fmt::{self, Write};
+use std::time::Duration;
+
+use crate::call::RpcStatusCode;
+use crate::error::{Error, Result};
+
+/// The upper bound of `maxTokens` in retry throttling.
+const MAX_TOKENS_LIMIT: u32 = 1000;
+
+fn invalid<T>(msg: impl Into<String>) -> Result<T> {
+    Err(Error::InvalidServiceConfig(msg.into()))
+}
+
+/// Write `s` as a JSON string.
+fn write_str(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(buf, "\\u{:04x}", c as u32).unwrap(),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Write `d` in the JSON representation of `google.protobuf.Duration`.
+fn write_duration(buf: &mut String, d: Duration) {
+    write!(buf, "\"{}.{:09}s\"", d.as_secs(), d.subsec_nanos()).unwrap();
+}
+
+fn write_codes(buf: &mut String, codes: &[RpcStatusCode]) {
+    buf.push('[');
+    for (i, code) in codes.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        write_str(buf, code.name().unwrap());
+    }
+    buf.push(']');
+}
+
+fn check_codes(field: &str, codes: &[RpcStatusCode]) -> Result<()> {
+    for code in codes {
+        match code.name() {
+            None | Some("OK") | Some("DO_NOT_USE") => {
+                return invalid(format!("{}: invalid status code {:?}", field, code));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Policy for retrying failed calls automatically.
+///
+/// See also [gRFC A6](https://github.com/grpc/proposal/blob/master/A6-client-retries.md).
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_status_codes: Vec<RpcStatusCode>,
+}
+
+impl RetryPolicy {
+    /// Create a policy that makes at most `max_attempts` attempts, including the
+    /// original one, when a call fails with any of `retryable_status_codes`.
+    ///
+    /// The backoff starts from 100ms and doubles after each attempt, up to 1s.
+    /// Note that gRPC C core treats `max_attempts` larger than 5 as 5.
+    pub fn new(max_attempts: u32, retryable_status_codes: Vec<RpcStatusCode>) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_status_codes,
+        }
+    }
+
+    /// Set the backoff before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound of backoff.
+    pub fn max_backoff(mut self, backoff: Duration) -> RetryPolicy {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the multiplier applied to the backoff after each retry.
+    pub fn backoff_multiplier(mut self, multiplier: f64) -> RetryPolicy {
+        self.backoff_multiplier = multiplier;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_attempts < 2 {
+            return invalid("retryPolicy: maxAttempts must be at least 2");
+        }
+        if self.initial_backoff == Duration::from_secs(0) {
+            return invalid("retryPolicy: initialBackoff must be greater than 0");
+        }
+        if self.max_backoff == Duration::from_secs(0) {
+            return invalid("retryPolicy: maxBackoff must be greater than 0");
+        }
+        if !self.backoff_multiplier.is_finite() || self.backoff_multiplier <= 0.0 {
+            return invalid("retryPolicy: backoffMultiplier must be greater than 0");
+        }
+        if self.retryable_status_codes.is_empty() {
+            return invalid("retryPolicy: retryableStatusCodes must not be empty");
+        }
+        check_codes("retryPolicy", &self.retryable_status_codes)
+    }
+
+    fn write_to(&self, buf: &mut String) {
+        write!(buf, "{{\"maxAttempts\":{}", self.max_attempts).unwrap();
+        buf.push_str(",\"initialBackoff\":");
+        write_duration(buf, self.initial_backoff);
+        buf.push_str(",\"maxBackoff\":");
+        write_duration(buf, self.max_backoff);
+        write!(buf, ",\"backoffMultiplier\":{}", self.backoff_multiplier).unwrap();
+        buf.push_str(",\"retryableStatusCodes\":");
+        write_codes(buf, &self.retryable_status_codes);
+        buf.push('}');
+    }
+}
+
+/// Policy for sending multiple copies of a call without waiting for a response.
+///
+/// Note that gRPC C core only parses the policy and doesn't act on it yet.
+#[derive(Clone, Debug)]
+pub struct HedgingPolicy {
+    max_attempts: u32,
+    hedging_delay: Duration,
+    non_fatal_status_codes: Vec<RpcStatusCode>,
+}
+
+impl HedgingPolicy {
+    /// Create a policy that sends at most `max_attempts` copies of a call.
+    pub fn new(max_attempts: u32) -> HedgingPolicy {
+        HedgingPolicy {
+            max_attempts,
+            hedging_delay: Duration::from_secs(0),
+            non_fatal_status_codes: vec![],
+        }
+    }
+
+    /// Set the delay between sending each copy. All copies are sent at once
+    /// by default.
+    pub fn hedging_delay(mut self, delay: Duration) -> HedgingPolicy {
+        self.hedging_delay = delay;
+        self
+    }
+
+    /// Set the status codes that don't cancel the other pending copies.
+    pub fn non_fatal_status_codes(mut self, codes: Vec<RpcStatusCode>) -> HedgingPolicy {
+        self.non_fatal_status_codes = codes;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_attempts < 2 {
+            return invalid("hedgingPolicy: maxAttempts must be at least 2");
+        }
+        check_codes("hedgingPolicy", &self.non_fatal_status_codes)
+    }
+
+    fn write_to(&self, buf: &mut String) {
+        write!(buf, "{{\"maxAttempts\":{}", self.max_attempts).unwrap();
+        buf.push_str(",\"hedgingDelay\":");
+        write_duration(buf, self.hedging_delay);
+        if !self.non_fatal_status_codes.is_empty() {
+            buf.push_str(",\"nonFatalStatusCodes\":");
+            write_codes(buf, &self.non_fatal_status_codes);
+        }
+        buf.push('}');
+    }
+}
+
+/// Configurations applied to a set of methods.
+#[derive(Clone, Debug, Default)]
+pub struct MethodConfig {
+    names: Vec<(String, Option<String>)>,
+    timeout: Option<Duration>,
+    wait_for_ready: Option<bool>,
+    retry_policy: Option<RetryPolicy>,
+    hedging_policy: Option<HedgingPolicy>,
+}
+
+impl MethodConfig {
+    pub fn new() -> MethodConfig {
+        MethodConfig::default()
+    }
+
+    /// Apply the config to all methods of `service`, which is a fully qualified
+    /// name like `helloworld.Greeter`.
+    pub fn service<S: Into<String>>(mut self, service: S) -> MethodConfig {
+        self.names.push((service.into(), None));
+        self
+    }
+
+    /// Apply the config to `method` of `service`.
+    pub fn method<S: Into<String>, M: Into<String>>(
+        mut self,
+        service: S,
+        method: M,
+    ) -> MethodConfig {
+        self.names.push((service.into(), Some(method.into())));
+        self
+    }
+
+    /// Set the default timeout of the calls. If a timeout is also set by
+    /// `CallOption`, the smaller one is used.
+    pub fn timeout(mut self, timeout: Duration) -> MethodConfig {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the default value of wait-for-ready of the calls.
+    pub fn wait_for_ready(mut self, wait_for_ready: bool) -> MethodConfig {
+        self.wait_for_ready = Some(wait_for_ready);
+        self
+    }
+
+    /// Set the retry policy. It can't be used with hedging policy.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> MethodConfig {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Set the hedging policy. It can't be used with retry policy.
+    pub fn hedging_policy(mut self, policy: HedgingPolicy) -> MethodConfig {
+        self.hedging_policy = Some(policy);
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.names.is_empty() {
+            return invalid("methodConfig: name must not be empty");
+        }
+        for (service, method) in &self.names {
+            if service.is_empty() {
+                return invalid("methodConfig: service must not be empty");
+            }
+            if method.as_deref() == Some("") {
+                return invalid(format!("methodConfig: empty method name for {}", service));
+            }
+        }
+        if self.retry_policy.is_some() && self.hedging_policy.is_some() {
+            return invalid("methodConfig: retryPolicy and hedgingPolicy can't be both set");
+        }
+        if let Some(p) = &self.retry_policy {
+            p.validate()?;
+        }
+        if let Some(p) = &self.hedging_policy {
+            p.validate()?;
+        }
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut String) {
+        buf.push_str("{\"name\":[");
+        for (i, (service, method)) in self.names.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            buf.push_str("{\"service\":");
+            write_str(buf, service);
+            if let Some(method) = method {
+                buf.push_str(",\"method\":");
+                write_str(buf, method);
+            }
+            buf.push('}');
+        }
+        buf.push(']');
+        if let Some(timeout) = self.timeout {
+            buf.push_str(",\"timeout\":");
+            write_duration(buf, timeout);
+        }
+        if let Some(wait_for_ready) = self.wait_for_ready {
+            write!(buf, ",\"waitForReady\":{}", wait_for_ready).unwrap();
+        }
+        if let Some(p) = &self.retry_policy {
+            buf.push_str(",\"retryPolicy\":");
+            p.write_to(buf);
+        }
+        if let Some(p) = &self.hedging_policy {
+            buf.push_str(",\"hedgingPolicy\":");
+            p.write_to(buf);
+        }
+        buf.push('}');
+    }
+}
+
+/// [`ServiceConfig`] factory in order to configure the properties.
+#[derive(Clone, Debug, Default)]
+pub struct ServiceConfigBuilder {
+    method_configs: Vec<MethodConfig>,
+    retry_throttling: Option<(u32, f64)>,
+}
+
+impl ServiceConfigBuilder {
+    /// Initialize a new [`ServiceConfigBuilder`].
+    pub fn new() -> ServiceConfigBuilder {
+        ServiceConfigBuilder::default()
+    }
+
+    /// Add a method config.
+    ///
+    /// A method can only be matched by one method config.
+    pub fn add_method_config(mut self, config: MethodConfig) -> ServiceConfigBuilder {
+        self.method_configs.push(config);
+        self
+    }
+
+    /// Throttle retries and hedges when too many calls fail.
+    ///
+    /// Each channel keeps `max_tokens` tokens at most. A failed call costs one token,
+    /// and a successful call adds `token_ratio` tokens. Retries are only allowed when
+    /// there are more than half of `max_tokens` tokens.
+    pub fn retry_throttling(mut self, max_tokens: u32, token_ratio: f64) -> ServiceConfigBuilder {
+        self.retry_throttling = Some((max_tokens, token_ratio));
+        self
+    }
+
+    /// Validate the configurations and finalize the [`ServiceConfig`].
+    pub fn build(self) -> Result<ServiceConfig> {
+        let mut names = Vec::new();
+        for config in &self.method_configs {
+            config.validate()?;
+            for name in &config.names {
+                if names.contains(&name) {
+                    return invalid(format!("methodConfig: duplicated name {:?}", name));
+                }
+                names.push(name);
+            }
+        }
+        if let Some((max_tokens, token_ratio)) = self.retry_throttling {
+            if max_tokens == 0 || max_tokens > MAX_TOKENS_LIMIT {
+                return invalid(format!(
+                    "retryThrottling: maxTokens must be in (0, {}]",
+                    MAX_TOKENS_LIMIT
+                ));
+            }
+            if !token_ratio.is_finite() || token_ratio <= 0.0 {
+                return invalid("retryThrottling: tokenRatio must be greater than 0");
+            }
+        }
+
+        let mut json = String::from("{\"methodConfig\":[");
+        for (i, config) in self.method_configs.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            config.write_to(&mut json);
+        }
+        json.push(']');
+        if let Some((max_tokens, token_ratio)) = self.retry_throttling {
+            write!(
+                json,
+                ",\"retryThrottling\":{{\"maxTokens\":{},\"tokenRatio\":{}}}",
+                max_tokens, token_ratio
+            )
+            .unwrap();
+        }
+        json.push('}');
+        Ok(ServiceConfig { json })
+    }
+}
+
+/// A validated service config.
+///
+/// Use [`ServiceConfigBuilder`] to build a [`ServiceConfig`], and apply it by
+/// [`ChannelBuilder::service_config`].
+///
+/// [`ChannelBuilder::service_config`]: crate::ChannelBuilder::service_config
+#[derive(Clone, Debug)]
+pub struct ServiceConfig {
+    json: String,
+}
+
+impl ServiceConfig {
+    /// Get the JSON representation of the config.
+    pub fn as_json(&self) -> &str {
+        &self.json
+    }
+}
+
+impl fmt::Display for ServiceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_config() {
+        let retry = RetryPolicy::new(3, vec![RpcStatusCode::UNAVAILABLE])
+            .initial_backoff(Duration::from_millis(10))
+            .backoff_multiplier(1.5);
+        let config = ServiceConfigBuilder::new()
+            .add_method_config(
+                MethodConfig::new()
+                    .service("helloworld.Greeter")
+                    .method("routeguide.RouteGuide", "GetFeature")
+                    .timeout(Duration::from_millis(1500))
+                    .wait_for_ready(true)
+                    .retry_policy(retry),
+            )
+            .add_method_config(
+                MethodConfig::new()
+                    .service("a\"b")
+                    .hedging_policy(HedgingPolicy::new(2)),
+            )
+            .retry_throttling(10, 0.1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.as_json(),
+            concat!(
+                r#"{"methodConfig":[{"name":[{"service":"helloworld.Greeter"},"#,
+                r#"{"service":"routeguide.RouteGuide","method":"GetFeature"}],"#,
+                r#""timeout":"1.500000000s","waitForReady":true,"#,
+                r#""retryPolicy":{"maxAttempts":3,"initialBackoff":"0.010000000s","#,
+                r#""maxBackoff":"1.000000000s","backoffMultiplier":1.5,"#,
+                r#""retryableStatusCodes":["UNAVAILABLE"]}},"#,
+                r#"{"name":[{"service":"a\"b"}],"hedgingPolicy":{"maxAttempts":2,"#,
+                r#""hedgingDelay":"0.000000000s"}}],"#,
+                r#""retryThrottling":{"maxTokens":10,"tokenRatio":0.1}}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_service_config() {
+        let retry = || RetryPolicy::new(3, vec![RpcStatusCode::UNAVAILABLE]);
+        let cases = vec![
+            ServiceConfigBuilder::new().add_method_config(MethodConfig::new()),
+            ServiceConfigBuilder::new().add_method_config(MethodConfig::new().method("a", "")),
+            ServiceConfigBuilder::new()
+                .add_method_config(MethodConfig::new().service("a"))
+                .add_method_config(MethodConfig::new().service("a")),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(retry())
+                    .hedging_policy(HedgingPolicy::new(2)),
+            ),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(RetryPolicy::new(1, vec![RpcStatusCode::UNAVAILABLE])),
+            ),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(RetryPolicy::new(3, vec![])),
+            ),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(RetryPolicy::new(3, vec![RpcStatusCode::OK])),
+            ),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(retry().initial_backoff(Duration::from_secs(0))),
+            ),
+            ServiceConfigBuilder::new().add_method_config(
+                MethodConfig::new()
+                    .service("a")
+                    .retry_policy(retry().backoff_multiplier(f64::NAN)),
+            ),
+            ServiceConfigBuilder::new().retry_throttling(0, 0.1),
+            ServiceConfigBuilder::new().retry_throttling(1001, 0.1),
+            ServiceConfigBuilder::new().retry_throttling(10, -1.0),
+        ];
+        for (i, case) in cases.into_iter().enumerate() {
+            match case.build() {
+                Err(Error::InvalidServiceConfig(_)) => {}
+                res => panic!("case {}: unexpected result {:?}", i, res),
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static WORKER: Mutex<Option<Sender<Job>>> = parking_lot::const_mutex(None);
+
+/// Runs `f` on a background thread, so blocking work like reading files doesn't
+/// stall the threads polling completion queues.
+///
+/// The jobs are run one by one on a thread shared by the whole library. The
+/// receiver is canceled if `f` panics.
+pub(crate) fn spawn_blocking<T, F>(f: F) -> oneshot::Receiver<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let job: Job = Box::new(move || {
+        // A panicked job should not stop the worker.
+        if let Ok(res) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            let _ = tx.send(res);
+        }
+    });
+    let mut worker = WORKER.lock();
+    let sender = worker.get_or_insert_with(|| {
+        let (sender, jobs) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("grpc-blocking".to_owned())
+            .spawn(move || {
+                for job in jobs {
+                    job();
+                }
+            })
+            .unwrap();
+        sender
+    });
+    sender.send(job).unwrap();
+    rx
+}
@@ -3,6 +3,7 @@
 use crate::call::server::{RequestContext, UnaryRequestContext};
 use crate::call::{BatchContext, Call};
 use crate::cq::CompletionQueue;
+use crate::interceptor::ServerCallHook;
 use crate::server::{self, RequestCallContext};
 
 pub struct Request {
@@ -68,17 +69,26 @@ impl UnaryRequest {
 pub struct Abort {
     ctx: BatchContext,
     _call: Call,
+    // Boxed to keep `CallTag` small.
+    hook: Option<Box<ServerCallHook>>,
 }
 
 impl Abort {
-    pub fn new(call: Call) -> Abort {
+    pub fn new(call: Call, hook: Option<ServerCallHook>) -> Abort {
         Abort {
             ctx: BatchContext::new(),
             _call: call,
+            hook: hook.map(Box::new),
         }
     }
 
     pub fn batch_ctx(&self) -> &BatchContext {
         &self.ctx
     }
+
+    pub fn resolve(self, success: bool) {
+        if let Some(hook) = self.hook {
+            (*hook).finish(success);
+        }
+    }
 }
@@ -1,5 +1,6 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+mod blocking;
 mod callback;
 mod executor;
 mod promise;
@@ -19,8 +20,11 @@ use crate::call::server::RequestContext;
 use crate::call::{BatchContext, Call, MessageReader};
 use crate::cq::CompletionQueue;
 use crate::error::{Error, Result};
+use crate::interceptor::ServerCallHook;
+use crate::metadata::Metadata;
 use crate::server::RequestCallContext;
 
+pub(crate) use self::blocking::spawn_blocking;
 pub(crate) use self::executor::{Executor, Kicker, UnfinishedWork};
 pub use self::promise::BatchType;
 
@@ -103,8 +107,28 @@ impl<T> Future for CqFuture<T> {
     }
 }
 
+/// The result of a batch job.
+pub struct BatchResult {
+    /// The message received, if any.
+    pub message_reader: Option<MessageReader>,
+    /// The initial metadata received, if any.
+    pub initial_metadata: Option<Metadata>,
+    /// The trailing metadata received along with the status, if any.
+    pub trailing_metadata: Option<Metadata>,
+}
+
+impl BatchResult {
+    fn empty() -> BatchResult {
+        BatchResult {
+            message_reader: None,
+            initial_metadata: None,
+            trailing_metadata: None,
+        }
+    }
+}
+
 /// Future object for batch jobs.
-pub type BatchFuture = CqFuture<Option<MessageReader>>;
+pub type BatchFuture = CqFuture<BatchResult>;
 
 /// A result holder for asynchronous execution.
 // This enum is going to be passed to FFI, so don't use trait or generic here.
@@ -140,8 +164,8 @@ impl CallTag {
     }
 
     /// Generate a CallTag for abort call before handler is called.
-    pub fn abort(call: Call) -> CallTag {
-        CallTag::Abort(Abort::new(call))
+    pub fn abort(call: Call, hook: Option<ServerCallHook>) -> CallTag {
+        CallTag::Abort(Abort::new(call, hook))
     }
 
     /// Generate a CallTag for unary request job.
@@ -175,7 +199,7 @@ impl CallTag {
             CallTag::Batch(prom) => prom.resolve(success),
             CallTag::Request(cb) => cb.resolve(cq, success),
             CallTag::UnaryRequest(cb) => cb.resolve(cq, success),
-            CallTag::Abort(_) => {}
+            CallTag::Abort(cb) => cb.resolve(success),
             CallTag::Action(prom) => prom.resolve(success),
             CallTag::Spawn(notify) => self::executor::resolve(notify, success),
         }
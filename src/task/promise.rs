@@ -3,8 +3,8 @@
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
 
-use super::Inner;
-use crate::call::{BatchContext, MessageReader, RpcStatusCode};
+use super::{BatchResult, Inner};
+use crate::call::{BatchContext, RpcStatusCode};
 use crate::error::Error;
 
 /// Batch job type.
@@ -22,11 +22,11 @@ pub enum BatchType {
 pub struct Batch {
     ty: BatchType,
     ctx: BatchContext,
-    inner: Arc<Inner<Option<MessageReader>>>,
+    inner: Arc<Inner<BatchResult>>,
 }
 
 impl Batch {
-    pub fn new(ty: BatchType, inner: Arc<Inner<Option<MessageReader>>>) -> Batch {
+    pub fn new(ty: BatchType, inner: Arc<Inner<BatchResult>>) -> Batch {
         Batch {
             ty,
             ctx: BatchContext::new(),
@@ -42,11 +42,11 @@ impl Batch {
         let task = {
             let mut guard = self.inner.lock();
             if success {
-                guard.set_result(Ok(self.ctx.recv_message()))
+                guard.set_result(Ok(take_result(&mut self.ctx)))
             } else {
                 // rely on C core to handle the failed read (e.g. deliver approriate
                 // statusCode on the clientside).
-                guard.set_result(Ok(None))
+                guard.set_result(Ok(BatchResult::empty()))
             }
         };
         task.map(|t| t.wake());
@@ -58,7 +58,7 @@ impl Batch {
             if succeed {
                 let status = self.ctx.rpc_status();
                 if status.code() == RpcStatusCode::OK {
-                    guard.set_result(Ok(None))
+                    guard.set_result(Ok(take_result(&mut self.ctx)))
                 } else {
                     guard.set_result(Err(Error::RpcFailure(status)))
                 }
@@ -74,7 +74,7 @@ impl Batch {
             let mut guard = self.inner.lock();
             let status = self.ctx.rpc_status();
             if status.code() == RpcStatusCode::OK {
-                guard.set_result(Ok(self.ctx.recv_message()))
+                guard.set_result(Ok(take_result(&mut self.ctx)))
             } else {
                 guard.set_result(Err(Error::RpcFailure(status)))
             }
@@ -98,6 +98,14 @@ impl Batch {
     }
 }
 
+fn take_result(ctx: &mut BatchContext) -> BatchResult {
+    BatchResult {
+        message_reader: ctx.recv_message(),
+        initial_metadata: ctx.recv_initial_metadata(),
+        trailing_metadata: ctx.recv_trailing_metadata(),
+    }
+}
+
 impl Debug for Batch {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "Batch [{:?}]", self.ty)
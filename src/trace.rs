@@ -0,0 +1,360 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Spans of calls and the propagation of [W3C Trace Context].
+//!
+//! [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hasher};
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::field::{self, Empty};
+use tracing::Span;
+
+use crate::call::server::RequestContext;
+use crate::call::RpcStatusCode;
+use crate::metadata::{Metadata, MetadataBuilder};
+
+const TRACEPARENT: &str = "traceparent";
+const TRACESTATE: &str = "tracestate";
+const FLAG_SAMPLED: u8 = 0x01;
+
+thread_local! {
+    // The context of the call being handled by current thread.
+    static CURRENT: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+fn random_u64() -> u64 {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    loop {
+        // Every `RandomState` is seeded differently, the sequence makes the ids
+        // differ even if the seeds are reused.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(SEQ.fetch_add(1, Ordering::Relaxed));
+        let v = hasher.finish();
+        // All-zero ids are invalid.
+        if v != 0 {
+            return v;
+        }
+    }
+}
+
+fn parse_hex(s: &str, len: usize) -> Option<u128> {
+    if s.len() != len || !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    u128::from_str_radix(s, 16).ok()
+}
+
+/// The position of a call in a distributed trace, which is carried by the
+/// `traceparent` and `tracestate` headers.
+///
+/// The server side of a call is a child of the context sent by client, or the
+/// root of a new trace if there is none. The context of a call can be got by
+/// [`RpcContext::trace_context`]. Outgoing calls made while handling the call
+/// are its children, unless another parent is given by
+/// [`CallOption::trace_context`].
+///
+/// [`RpcContext::trace_context`]: crate::RpcContext::trace_context
+/// [`CallOption::trace_context`]: crate::CallOption::trace_context
+#[derive(Clone, PartialEq)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+    flags: u8,
+    state: Option<String>,
+}
+
+impl TraceContext {
+    /// Create the root context of a new sampled trace.
+    pub fn new_root() -> TraceContext {
+        TraceContext {
+            trace_id: u128::from(random_u64()) << 64 | u128::from(random_u64()),
+            span_id: random_u64(),
+            flags: FLAG_SAMPLED,
+            state: None,
+        }
+    }
+
+    /// Create a context of the same trace whose parent is `self`.
+    pub fn child(&self) -> TraceContext {
+        TraceContext {
+            span_id: random_u64(),
+            ..self.clone()
+        }
+    }
+
+    /// Parse the values of `traceparent` and `tracestate` headers, returns `None`
+    /// if `traceparent` is invalid.
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<TraceContext> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parse_hex(parts.next()?, 2)?;
+        let trace_id = parse_hex(parts.next()?, 32)?;
+        let span_id = parse_hex(parts.next()?, 16)? as u64;
+        let flags = parse_hex(parts.next()?, 2)? as u8;
+        // Later versions may append fields, but version 0xff is invalid.
+        if version == 0xff || (version == 0 && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == 0 || span_id == 0 {
+            return None;
+        }
+        let state = tracestate.map(str::trim).filter(|s| !s.is_empty());
+        Some(TraceContext {
+            trace_id,
+            span_id,
+            flags,
+            state: state.map(ToOwned::to_owned),
+        })
+    }
+
+    /// Extract the context from `traceparent` and `tracestate` in `meta`.
+    ///
+    /// Multiple `tracestate` headers are combined into one list.
+    pub fn from_metadata(meta: &Metadata) -> Option<TraceContext> {
+        let values = |key| {
+            meta.iter()
+                .filter(move |(k, _)| k.eq_ignore_ascii_case(key))
+                .filter_map(|(_, v)| std::str::from_utf8(v).ok())
+        };
+        let traceparent = values(TRACEPARENT).next()?;
+        let states: Vec<_> = values(TRACESTATE)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        TraceContext::parse(traceparent, Some(&states.join(",")))
+    }
+
+    /// Add the context to `builder` as `traceparent` and `tracestate`.
+    pub fn inject(&self, builder: &mut MetadataBuilder) {
+        builder.add_metadata(TRACEPARENT, self.traceparent().as_bytes());
+        if let Some(state) = &self.state {
+            builder.add_metadata(TRACESTATE, state.as_bytes());
+        }
+    }
+
+    /// Get the value of the `traceparent` header.
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, self.flags
+        )
+    }
+
+    /// Get the id of the trace, which is shared by all spans of it.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// Get the id of the span the context belongs to, which becomes the parent
+    /// id of its children.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+
+    /// Check if the caller may have recorded the trace.
+    pub fn sampled(&self) -> bool {
+        self.flags & FLAG_SAMPLED != 0
+    }
+
+    /// Get the vendor specific value of the `tracestate` header.
+    pub fn trace_state(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+}
+
+impl Debug for TraceContext {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.traceparent())?;
+        if let Some(state) = &self.state {
+            write!(f, " {}", state)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the trace headers in `meta` with `ctx`.
+pub(crate) fn inject_headers(meta: Option<Metadata>, ctx: &TraceContext) -> Metadata {
+    let mut builder = MetadataBuilder::new();
+    if let Some(meta) = &meta {
+        for (k, v) in meta {
+            if !k.eq_ignore_ascii_case(TRACEPARENT) && !k.eq_ignore_ascii_case(TRACESTATE) {
+                builder.add_metadata(k, v);
+            }
+        }
+    }
+    ctx.inject(&mut builder);
+    builder.build()
+}
+
+/// Creates the span of a call handled by server, whose context is a child of the
+/// one sent by client.
+pub(crate) fn server_span(ctx: &RequestContext) -> (Span, TraceContext) {
+    let parent = TraceContext::from_metadata(ctx.metadata());
+    let trace = match &parent {
+        Some(p) => p.child(),
+        None => TraceContext::new_root(),
+    };
+    let span = tracing::info_span!(
+        "grpc.server",
+        method = %String::from_utf8_lossy(ctx.method()),
+        peer = %ctx.peer(),
+        deadline = Empty,
+        trace_id = %format_args!("{:032x}", trace.trace_id),
+        span_id = %format_args!("{:016x}", trace.span_id),
+        parent_id = Empty,
+        status = Empty,
+    );
+    if let Some(remaining) = ctx.deadline().remaining() {
+        span.record("deadline", field::debug(remaining));
+    }
+    if let Some(p) = parent {
+        span.record("parent_id", format_args!("{:016x}", p.span_id));
+    }
+    (span, trace)
+}
+
+/// Makes `ctx` the context of the call being handled while `f` runs.
+pub(crate) fn in_context<R>(ctx: &mut Option<TraceContext>, f: impl FnOnce() -> R) -> R {
+    struct Restore<'a>(&'a mut Option<TraceContext>);
+
+    impl Drop for Restore<'_> {
+        fn drop(&mut self) {
+            CURRENT.with(|c| mem::swap(&mut *c.borrow_mut(), self.0));
+        }
+    }
+
+    CURRENT.with(|c| mem::swap(&mut *c.borrow_mut(), ctx));
+    let _restore = Restore(ctx);
+    f()
+}
+
+/// Creates the span of a call made by client. The context is a child of `parent`,
+/// or the call being handled, or the root of a new trace.
+pub(crate) fn client_span(method: &str, parent: Option<&TraceContext>) -> (Span, TraceContext) {
+    let current = match parent {
+        Some(_) => None,
+        None => CURRENT.with(|c| c.borrow().clone()),
+    };
+    let parent = parent.or(current.as_ref());
+    let trace = match parent {
+        Some(p) => p.child(),
+        None => TraceContext::new_root(),
+    };
+    let span = tracing::info_span!(
+        "grpc.client",
+        method,
+        trace_id = %format_args!("{:032x}", trace.trace_id),
+        span_id = %format_args!("{:016x}", trace.span_id),
+        parent_id = Empty,
+        status = Empty,
+    );
+    if let Some(p) = parent {
+        span.record("parent_id", format_args!("{:016x}", p.span_id));
+    }
+    (span, trace)
+}
+
+/// Records the final status of a call.
+pub(crate) fn record_status(span: &Span, code: RpcStatusCode) {
+    span.record("status", field::display(code));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let tp = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let ctx = TraceContext::parse(tp, Some("congo=t61rcWkgMzE")).unwrap();
+        assert_eq!(ctx.trace_id(), 0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736);
+        assert_eq!(ctx.span_id(), 0x00f0_67aa_0ba9_02b7);
+        assert!(ctx.sampled());
+        assert_eq!(ctx.trace_state(), Some("congo=t61rcWkgMzE"));
+        assert_eq!(ctx.traceparent(), tp);
+
+        let child = ctx.child();
+        assert_eq!(child.trace_id(), ctx.trace_id());
+        assert_ne!(child.span_id(), ctx.span_id());
+        assert_eq!(child.trace_state(), ctx.trace_state());
+
+        // Later versions can be parsed as version 0.
+        let tp = "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-ff";
+        let ctx = TraceContext::parse(tp, Some("")).unwrap();
+        assert!(!ctx.sampled());
+        assert_eq!(ctx.trace_state(), None);
+
+        for tp in &[
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-00",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+            "00-+bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(TraceContext::parse(tp, None), None, "{}", tp);
+        }
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let tp = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("tracestate", "congo=t61rcWkgMzE").unwrap();
+        builder.add_str("traceparent", tp).unwrap();
+        builder.add_str("tracestate", " ").unwrap();
+        builder
+            .add_str("tracestate", "rojo=00f067aa0ba902b7")
+            .unwrap();
+        let ctx = TraceContext::from_metadata(&builder.build()).unwrap();
+        assert_eq!(ctx.traceparent(), tp);
+        assert_eq!(
+            ctx.trace_state(),
+            Some("congo=t61rcWkgMzE,rojo=00f067aa0ba902b7")
+        );
+
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("tracestate", "congo=t61rcWkgMzE").unwrap();
+        assert_eq!(TraceContext::from_metadata(&builder.build()), None);
+    }
+
+    #[test]
+    fn test_in_context() {
+        let mut a = Some(TraceContext::new_root());
+        let mut b = Some(TraceContext::new_root());
+        let (a_id, b_id) = (
+            a.as_ref().unwrap().trace_id(),
+            b.as_ref().unwrap().trace_id(),
+        );
+        in_context(&mut a, || {
+            let (_, t) = client_span("/a", None);
+            assert_eq!(t.trace_id(), a_id);
+            in_context(&mut b, || {
+                let (_, t) = client_span("/b", None);
+                assert_eq!(t.trace_id(), b_id);
+            });
+            let (_, t) = client_span("/a", b.as_ref());
+            assert_eq!(t.trace_id(), b_id);
+        });
+        assert_eq!(a.unwrap().trace_id(), a_id);
+        let (_, t) = client_span("/c", None);
+        assert_ne!(t.trace_id(), a_id);
+    }
+
+    #[test]
+    fn test_new_root() {
+        let a = TraceContext::new_root();
+        let b = TraceContext::new_root();
+        assert_ne!(a.trace_id(), b.trace_id());
+        assert!(a.sampled());
+        let parsed = TraceContext::parse(&a.traceparent(), None).unwrap();
+        assert_eq!(parsed, a);
+    }
+}
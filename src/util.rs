@@ -0,0 +1,91 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Decoding helpers shared by the parsers of PEM, JWT and channelz JSON.
+
+/// The alphabets of base64 defined in RFC 4648.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Base64 {
+    /// The standard alphabet with optional padding, as used by PEM and the JSON
+    /// mapping of protobuf.
+    Standard,
+    /// The URL and filename safe alphabet without padding, as used by JWS and JWK.
+    #[cfg_attr(not(feature = "jwt"), allow(dead_code))]
+    UrlNoPad,
+}
+
+/// Decodes `s` in the given alphabet, returns `None` if it's malformed.
+pub(crate) fn base64_decode(s: &str, alphabet: Base64) -> Option<Vec<u8>> {
+    let (s, c62, c63) = match alphabet {
+        Base64::Standard => (s.trim_end_matches('='), b'+', b'/'),
+        Base64::UrlNoPad => (s, b'-', b'_'),
+    };
+    let s = s.as_bytes();
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.chunks(4) {
+        let mut n = 0;
+        for (i, c) in chunk.iter().enumerate() {
+            let v = match *c {
+                c @ b'A'..=b'Z' => c - b'A',
+                c @ b'a'..=b'z' => c - b'a' + 26,
+                c @ b'0'..=b'9' => c - b'0' + 52,
+                c if c == c62 => 62,
+                c if c == c63 => 63,
+                _ => return None,
+            };
+            n |= u32::from(v) << (18 - 6 * i);
+        }
+        out.push((n >> 16) as u8);
+        if chunk.len() > 2 {
+            out.push((n >> 8) as u8);
+        }
+        if chunk.len() > 3 {
+            out.push(n as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Returns the days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_decode() {
+        let decode = |s| base64_decode(s, Base64::Standard);
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(decode("Zm9vYg").unwrap(), b"foob");
+        assert_eq!(decode("Zm9vYmE=").unwrap(), b"fooba");
+        assert_eq!(decode("Zm9vYmFy").unwrap(), b"foobar");
+        assert_eq!(decode("+/8=").unwrap(), b"\xfb\xff");
+        assert!(decode("Zm9vY").is_none());
+        assert!(decode("_-8").is_none());
+        assert!(decode("Zm=9").is_none());
+
+        let decode = |s| base64_decode(s, Base64::UrlNoPad);
+        assert_eq!(decode("_-8").unwrap(), b"\xff\xef");
+        assert!(decode("A").is_none());
+        assert!(decode("AA==").is_none());
+        assert!(decode("A+/A").is_none());
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+}
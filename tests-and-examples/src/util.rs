@@ -24,3 +24,12 @@ pub fn read_cert_pair(name: &str) -> Result<(String, String), io::Error> {
     fs::File::open(cert_path(name, "key"))?.read_to_string(&mut key)?;
     Ok((crt, key))
 }
+
+/// Removes the socket file of a `unix:` address when dropped.
+pub struct Defer(pub &'static str);
+
+impl Drop for Defer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0[5..]);
+    }
+}
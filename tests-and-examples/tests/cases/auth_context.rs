@@ -9,6 +9,8 @@ use std::sync::mpsc::{self, Sender};
 use std::sync::*;
 use std::time::*;
 
+#[cfg(unix)]
+use tests_and_examples::util::Defer;
 use tests_and_examples::util::{read_cert_pair, read_single_crt};
 
 #[derive(Clone)]
@@ -125,3 +127,363 @@ fn test_no_crash_on_insecure() {
         .recv_timeout(Duration::from_secs(1))
         .expect_err("Received auth context even though not authenticated");
 }
+
+#[derive(Clone)]
+struct IdentityService {
+    tx: Sender<Option<PeerIdentity>>,
+}
+
+impl Greeter for IdentityService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+        self.tx.send(ctx.peer_identity()).unwrap();
+        ctx.spawn(
+            sink.success(HelloReply::default())
+                .map_err(|e| panic!("failed to reply {:?}", e))
+                .map(|_| ()),
+        );
+    }
+}
+
+fn read_test_file(name: &str) -> String {
+    let path = format!("{}/certs/crl/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn test_peer_identity() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, rx) = mpsc::channel();
+    let service = create_greeter(IdentityService { tx });
+    let server_credentials = grpcio::ServerCredentialsBuilder::new()
+        .root_cert(
+            read_test_file("ca.pem"),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .add_cert(
+            read_test_file("server.pem").into(),
+            read_test_file("server.key").into(),
+        )
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let client_crt = read_test_file("spiffe.pem");
+    let client_credentials = ChannelCredentialsBuilder::new()
+        .root_cert(read_test_file("ca.pem").into())
+        .cert(
+            client_crt.clone().into(),
+            read_test_file("spiffe.key").into(),
+        )
+        .build();
+    let ch = ChannelBuilder::new(env)
+        .override_ssl_target("rust.test.crl.example")
+        .secure_connect(&format!("127.0.0.1:{}", port), client_credentials);
+    let client = GreeterClient::new(ch);
+    client.say_hello(&HelloRequest::default()).unwrap();
+
+    let id = rx.recv_timeout(Duration::from_secs(1)).unwrap().unwrap();
+    assert!(id.is_authenticated());
+    assert_eq!(id.transport_security_type(), Some("ssl"));
+    assert_eq!(
+        id.security_level(),
+        Some(SecurityLevel::PrivacyAndIntegrity)
+    );
+    assert_eq!(id.common_name(), Some("spiffe-client"));
+    assert_eq!(id.subject(), Some("CN=spiffe-client,O=grpc-rs"));
+    assert_eq!(id.dns_sans(), &["client.test.crl.example".to_owned()]);
+    assert_eq!(
+        id.uri_sans(),
+        &["spiffe://example.org/ns/test/sa/client".to_owned()]
+    );
+    assert_eq!(
+        id.ip_sans(),
+        &["10.0.0.1".parse::<std::net::IpAddr>().unwrap()]
+    );
+    assert_eq!(
+        id.spiffe_id(),
+        Some("spiffe://example.org/ns/test/sa/client")
+    );
+    assert_eq!(id.pem_cert(), Some(client_crt.as_str()));
+    assert!(id
+        .properties()
+        .iter()
+        .any(|(name, value)| name == "x509_common_name" && value == b"spiffe-client"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_local_credentials() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, rx) = mpsc::channel();
+    let service = create_greeter(IdentityService { tx });
+    let socket = Defer("unix:local_creds_socket");
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred(socket.0, 0, ServerCredentials::local(LocalConnectType::Uds))
+        .bind_with_cred(
+            "127.0.0.1",
+            0,
+            ServerCredentials::local(LocalConnectType::LocalTcp),
+        )
+        .build()
+        .unwrap();
+    server.start();
+    let port = server
+        .bind_addrs()
+        .find(|(host, _)| *host == "127.0.0.1")
+        .unwrap()
+        .1;
+
+    let ch = ChannelBuilder::new(env.clone())
+        .secure_connect(socket.0, ChannelCredentials::local(LocalConnectType::Uds));
+    let client = GreeterClient::new(ch);
+    client.say_hello(&HelloRequest::default()).unwrap();
+    let id = rx.recv_timeout(Duration::from_secs(1)).unwrap().unwrap();
+    assert_eq!(id.transport_security_type(), Some("local"));
+    assert_eq!(
+        id.security_level(),
+        Some(SecurityLevel::PrivacyAndIntegrity)
+    );
+
+    let ch = ChannelBuilder::new(env.clone()).secure_connect(
+        &format!("127.0.0.1:{}", port),
+        ChannelCredentials::local(LocalConnectType::LocalTcp),
+    );
+    let client = GreeterClient::new(ch);
+    client.say_hello(&HelloRequest::default()).unwrap();
+    let id = rx.recv_timeout(Duration::from_secs(1)).unwrap().unwrap();
+    assert_eq!(id.transport_security_type(), Some("local"));
+    assert_eq!(id.security_level(), Some(SecurityLevel::None));
+
+    // Insecure connections are rejected by the local credentials.
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let opt = CallOption::default().timeout(Duration::from_millis(500));
+    client
+        .say_hello_opt(&HelloRequest::default(), opt)
+        .unwrap_err();
+    rx.recv_timeout(Duration::from_millis(100)).unwrap_err();
+}
+
+#[test]
+fn test_authorization_checker() {
+    let dir = std::env::temp_dir().join(format!("grpc-rs-authz-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let policy_path = dir.join("policy.json");
+    let allow_spiffe = r#"{
+        "name": "greeter",
+        "allow_rules": [{
+            "name": "allow_spiffe",
+            "source": {"principals": ["spiffe://example.org/ns/test/*"]},
+            "request": {
+                "paths": ["/helloworld.Greeter/SayHello"],
+                "headers": [{"key": "tenant", "values": ["test"]}]
+            }
+        }]
+    }"#;
+    std::fs::write(&policy_path, allow_spiffe).unwrap();
+    let checker =
+        AuthorizationChecker::from_file(&policy_path, Some(Duration::from_millis(0))).unwrap();
+
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, _rx) = mpsc::channel();
+    let service = create_greeter(IdentityService { tx });
+    let server_credentials = grpcio::ServerCredentialsBuilder::new()
+        .root_cert(
+            read_test_file("ca.pem"),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .add_cert(
+            read_test_file("server.pem").into(),
+            read_test_file("server.key").into(),
+        )
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .add_checker(checker.clone())
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let connect = |name: &str| {
+        let client_credentials = ChannelCredentialsBuilder::new()
+            .root_cert(read_test_file("ca.pem").into())
+            .cert(
+                read_test_file(&format!("{}.pem", name)).into(),
+                read_test_file(&format!("{}.key", name)).into(),
+            )
+            .build();
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.crl.example")
+            .secure_connect(&format!("127.0.0.1:{}", port), client_credentials);
+        GreeterClient::new(ch)
+    };
+    let tenant = |value: &str| {
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("tenant", value).unwrap();
+        CallOption::default().headers(builder.build())
+    };
+    let check_denied = |res: Result<HelloReply>| match res {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::PERMISSION_DENIED),
+        res => panic!("expect permission denied, but got {:?}", res),
+    };
+
+    let spiffe = connect("spiffe");
+    let req = HelloRequest::default();
+    spiffe.say_hello_opt(&req, tenant("test")).unwrap();
+    check_denied(spiffe.say_hello_opt(&req, tenant("other")));
+    check_denied(spiffe.say_hello(&req));
+    check_denied(connect("client").say_hello_opt(&req, tenant("test")));
+
+    // Invalid policies are ignored.
+    std::fs::write(&policy_path, "{").unwrap();
+    spiffe.say_hello_opt(&req, tenant("test")).unwrap();
+
+    let deny_all = allow_spiffe.replace(
+        r#""allow_rules""#,
+        r#""deny_rules": [{"name": "deny_all"}], "allow_rules""#,
+    );
+    std::fs::write(&policy_path, deny_all).unwrap();
+    // The file is reloaded in the background, the policy applies to later calls.
+    let mut res = spiffe.say_hello_opt(&req, tenant("test"));
+    for _ in 0..50 {
+        if res.is_err() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        res = spiffe.say_hello_opt(&req, tenant("test"));
+    }
+    check_denied(res);
+
+    // The policy updated manually stays in use as long as the file can't be read.
+    std::fs::remove_dir_all(&dir).unwrap();
+    checker
+        .update(AuthorizationPolicy::new("allow_all").allow(AuthorizationRule::new("all")))
+        .unwrap();
+    connect("client").say_hello(&req).unwrap();
+}
+
+struct TokenProcessor;
+
+impl AuthMetadataProcessor for TokenProcessor {
+    fn process(&self, ctx: &mut AuthProcessContext<'_>) -> std::result::Result<(), RpcStatus> {
+        let token = ctx
+            .metadata()
+            .iter()
+            .find(|(k, _)| *k == "authorization")
+            .map(|(_, v)| v.to_vec());
+        let user = match token.as_deref() {
+            Some(b"Bearer alice-token") => "alice",
+            Some(_) => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::UNAUTHENTICATED,
+                    "invalid token".to_owned(),
+                ))
+            }
+            None => return Ok(()),
+        };
+        assert!(ctx.auth_context().peer_is_authenticated());
+        ctx.add_property("user", user.as_bytes());
+        assert!(ctx.set_peer_identity_property_name("user"));
+        assert!(!ctx.set_peer_identity_property_name("unknown"));
+        ctx.consume("authorization");
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct TokenService {
+    tx: Sender<(Vec<String>, bool)>,
+}
+
+impl Greeter for TokenService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+        let auth_context = ctx.auth_context().unwrap();
+        let identity = auth_context
+            .peer_identity()
+            .map(|p| p.value_str().unwrap().to_owned())
+            .collect();
+        let has_token = ctx
+            .request_headers()
+            .iter()
+            .any(|(k, _)| k == "authorization");
+        self.tx.send((identity, has_token)).unwrap();
+        ctx.spawn(
+            sink.success(HelloReply::default())
+                .map_err(|e| panic!("failed to reply {:?}", e))
+                .map(|_| ()),
+        );
+    }
+}
+
+#[test]
+fn test_auth_metadata_processor() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, rx) = mpsc::channel();
+    let service = create_greeter(TokenService { tx });
+    let server_credentials = grpcio::ServerCredentialsBuilder::new()
+        .root_cert(
+            read_test_file("ca.pem"),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .add_cert(
+            read_test_file("server.pem").into(),
+            read_test_file("server.key").into(),
+        )
+        .auth_metadata_processor(TokenProcessor)
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let client_credentials = ChannelCredentialsBuilder::new()
+        .root_cert(read_test_file("ca.pem").into())
+        .cert(
+            read_test_file("spiffe.pem").into(),
+            read_test_file("spiffe.key").into(),
+        )
+        .build();
+    let ch = ChannelBuilder::new(env)
+        .override_ssl_target("rust.test.crl.example")
+        .secure_connect(&format!("127.0.0.1:{}", port), client_credentials);
+    let client = GreeterClient::new(ch);
+    let token = |value: &str| {
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("authorization", value).unwrap();
+        CallOption::default().headers(builder.build())
+    };
+    let req = HelloRequest::default();
+
+    client
+        .say_hello_opt(&req, token("Bearer alice-token"))
+        .unwrap();
+    let (identity, has_token) = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(identity, vec!["alice".to_owned()]);
+    assert!(!has_token);
+
+    // Without a token, the identity is still the one from the certificate.
+    client.say_hello(&req).unwrap();
+    let (identity, has_token) = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(!identity.is_empty() && identity != vec!["alice".to_owned()]);
+    assert!(!has_token);
+
+    match client.say_hello_opt(&req, token("Bearer bob-token")) {
+        Err(Error::RpcFailure(s)) => {
+            assert_eq!(s.code(), RpcStatusCode::UNAUTHENTICATED);
+            assert_eq!(s.message(), "invalid token");
+        }
+        res => panic!("expect unauthenticated, but got {:?}", res),
+    }
+    rx.recv_timeout(Duration::from_millis(100)).unwrap_err();
+}
@@ -1,13 +1,24 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
+use futures::future::BoxFuture;
 use futures::prelude::*;
 use grpcio::{
-    CertificateRequestType, ChannelBuilder, ChannelCredentialsBuilder, EnvBuilder, RpcContext,
-    ServerBuilder, ServerCredentialsBuilder, ServerCredentialsFetcher, UnarySink,
+    AuthMetadataContext, CallCredentials, CallOption, CertificateRequestType,
+    CertificateRevocationList, ChannelBuilder, ChannelCredentials, ChannelCredentialsBuilder,
+    ChannelCredentialsFetcher, EnvBuilder, Error, FileWatcherCertificateProvider, LocalConnectType,
+    Metadata, MetadataBuilder, MetadataCredentialsPlugin, PeerCertificate, PeerVerifier, Resolver,
+    ResolverUpdater, RpcContext, RpcStatus, RpcStatusCode, SecurityLevel, ServerBuilder,
+    ServerCredentials, ServerCredentialsBuilder, ServerCredentialsFetcher, SslSessionCache,
+    TlsVersion, UnarySink,
 };
 use grpcio_proto::example::helloworld::*;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use std::{fs, thread};
+#[cfg(unix)]
+use tests_and_examples::util::Defer;
 use tests_and_examples::util::{read_cert_pair, read_single_crt};
 
 #[derive(Clone)]
@@ -161,3 +172,735 @@ fn test_reload_fail() {
         assert_eq!(reply.get_message(), "Hello world");
     }
 }
+
+struct ClientCertReload {
+    switch: Arc<AtomicBool>,
+    fetched: Arc<AtomicUsize>,
+}
+
+impl ChannelCredentialsFetcher for ClientCertReload {
+    fn fetch(&self) -> Result<Option<ChannelCredentialsBuilder>, Box<dyn std::error::Error>> {
+        self.fetched.fetch_add(1, Ordering::SeqCst);
+        if !self.switch.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        let (client1_crt, client1_key) = read_cert_pair("client1")?;
+        let new_cred = ChannelCredentialsBuilder::new()
+            .root_cert(read_single_crt("ca")?.into())
+            .cert(client1_crt.into(), client1_key.into());
+        Ok(Some(new_cred))
+    }
+}
+
+#[test]
+fn test_channel_reload() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_greeter(GreeterService);
+    let root = read_single_crt("ca").unwrap();
+    let (server1_crt, server1_key) = read_cert_pair("server1").unwrap();
+    let server_cred = ServerCredentialsBuilder::new()
+        .add_cert(server1_crt.into(), server1_key.into())
+        .root_cert(
+            root,
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred("127.0.0.1", 0, server_cred)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let addr = format!("127.0.0.1:{}", port);
+    grpcio::register_resolver("credential-test", LocalResolver(port)).unwrap();
+
+    // Channels whose addresses are pushed by resolvers are reloaded too.
+    for target in &[addr.as_str(), "credential-test:greeter"] {
+        // No client certificate is presented at first.
+        let switch = Arc::new(AtomicBool::new(false));
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let cred = ChannelCredentialsBuilder::new()
+            .root_cert(read_single_crt("ca").unwrap().into())
+            .fetcher(Box::new(ClientCertReload {
+                switch: switch.clone(),
+                fetched: fetched.clone(),
+            }))
+            .build();
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.google.fr")
+            .secure_connect(target, cred);
+        let client = GreeterClient::new(ch);
+        let mut req = HelloRequest::default();
+        req.set_name("world".to_owned());
+        client.say_hello(&req).unwrap_err();
+        for _ in 0..50 {
+            if fetched.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(fetched.load(Ordering::SeqCst) > 0, "{}", target);
+
+        // The same channel picks up the client certificate when reconnecting.
+        switch.store(true, Ordering::SeqCst);
+        let mut reply = None;
+        for _ in 0..50 {
+            if let Ok(r) = client.say_hello(&req) {
+                reply = Some(r);
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(
+            reply.expect("rpc").get_message(),
+            "Hello world",
+            "{}",
+            target
+        );
+    }
+}
+
+/// Resolves every target to the local port.
+struct LocalResolver(u16);
+
+impl Resolver for LocalResolver {
+    fn resolve(&self, _: &str, updater: ResolverUpdater) {
+        let addr = format!("127.0.0.1:{}", self.0).parse().unwrap();
+        updater.update(&[addr], None);
+    }
+}
+
+#[test]
+fn test_file_watcher_provider() {
+    let dir = std::env::temp_dir().join(format!("grpc-rs-file-watcher-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, content: String| fs::write(dir.join(name), content).unwrap();
+    let (server0_crt, server0_key) = read_cert_pair("server0").unwrap();
+    let (server1_crt, server1_key) = read_cert_pair("server1").unwrap();
+    write("ca.pem", read_single_crt("ca").unwrap());
+    write("server.pem", server0_crt);
+    write("server.key", server0_key);
+
+    let env = Arc::new(EnvBuilder::new().build());
+    let provider = FileWatcherCertificateProvider::new()
+        .identity(dir.join("server.pem"), dir.join("server.key"))
+        .refresh_interval(Duration::from_millis(0));
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(create_greeter(GreeterService))
+        .bind_with_fetcher(
+            "127.0.0.1",
+            0,
+            Box::new(provider),
+            CertificateRequestType::DontRequestClientCertificate,
+        )
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let connect = |target: &str| {
+        let cred = FileWatcherCertificateProvider::new()
+            .root_cert(dir.join("ca.pem"))
+            .build_channel_credentials()
+            .unwrap();
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target(target)
+            .secure_connect(&format!("127.0.0.1:{}", port), cred);
+        let client = GreeterClient::new(ch);
+        let mut req = HelloRequest::default();
+        req.set_name("world".to_owned());
+        client.say_hello(&req)
+    };
+    // The CN field in the certificate of server0 is "*.test.google.com.au".
+    let reply = connect("rust.test.google.com.au").expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // A key that doesn't match the certificate is ignored.
+    write("server.pem", server1_crt);
+    let reply = connect("rust.test.google.com.au").expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // The CN field in the certificate of server1 is "*.test.google.fr".
+    write("server.key", server1_key);
+    let reply = connect("rust.test.google.fr").expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+    connect("rust.test.google.com.au").unwrap_err();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn read_crl_file(name: &str) -> String {
+    let path = format!("{}/certs/crl/{}", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read_to_string(path).unwrap()
+}
+
+fn read_crls(names: &[&str]) -> String {
+    names.iter().map(|name| read_crl_file(name)).collect()
+}
+
+#[test]
+fn test_revocation_list() {
+    let env = Arc::new(EnvBuilder::new().build());
+    // Revokes nothing at first.
+    let crl =
+        CertificateRevocationList::from_pem(read_crl_file("crl_empty.pem").as_bytes()).unwrap();
+    let server_cred = ServerCredentialsBuilder::new()
+        .add_cert(
+            read_crl_file("server.pem").into(),
+            read_crl_file("server.key").into(),
+        )
+        .root_cert(
+            read_crl_file("ca.pem"),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .revocation_list(crl.clone())
+        .unwrap()
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(create_greeter(GreeterService))
+        .bind_with_cred("127.0.0.1", 0, server_cred)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let connect = |client: &str, crl: Option<CertificateRevocationList>| {
+        let mut builder = ChannelCredentialsBuilder::new()
+            .root_cert(read_crl_file("ca.pem").into())
+            .cert(
+                read_crl_file(&format!("{}.pem", client)).into(),
+                read_crl_file(&format!("{}.key", client)).into(),
+            );
+        if let Some(crl) = crl {
+            builder = builder.revocation_list(crl).unwrap();
+        }
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.crl.example")
+            .secure_connect(&format!("127.0.0.1:{}", port), builder.build());
+        let client = GreeterClient::new(ch);
+        let mut req = HelloRequest::default();
+        req.set_name("world".to_owned());
+        client.say_hello(&req)
+    };
+    let reply = connect("revoked", None).expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // The updated list applies to the existing credentials.
+    crl.update(read_crl_file("crl.pem").as_bytes()).unwrap();
+    match connect("revoked", None) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected revoked client to fail, got {:?}", res),
+    }
+    let reply = connect("client", None).expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // The certificate of the server is revoked too.
+    let dir = std::env::temp_dir().join(format!("grpc-rs-crl-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("crl.pem"), read_crl_file("crl.pem")).unwrap();
+    let client_crl = CertificateRevocationList::from_dir(&dir).unwrap();
+    match connect("client", Some(client_crl.clone())) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected revoked server to fail, got {:?}", res),
+    }
+    fs::write(dir.join("crl.pem"), read_crl_file("crl_empty.pem")).unwrap();
+    client_crl.reload().unwrap();
+    let reply = connect("client", Some(client_crl)).expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+    fs::remove_dir_all(&dir).unwrap();
+
+    // An expired list rejects every certificate of its issuer.
+    let expired =
+        CertificateRevocationList::from_pem(read_crl_file("crl_expired.pem").as_bytes()).unwrap();
+    match connect("client", Some(expired)) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected expired list to fail, got {:?}", res),
+    }
+
+    // A list that is not signed by its issuer is ignored, so are the certificates it
+    // revokes, but the issuer needs a valid list.
+    let forged =
+        CertificateRevocationList::from_pem(read_crl_file("crl_forged.pem").as_bytes()).unwrap();
+    match connect("client", Some(forged.clone())) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected forged list to fail, got {:?}", res),
+    }
+    forged
+        .update(read_crls(&["crl_forged.pem", "crl_empty.pem"]).as_bytes())
+        .unwrap();
+    let reply = connect("client", Some(forged)).expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // Every certificate in the chain is checked, the list of the intermediate CA is
+    // verified by the certificate it presents.
+    crl.update(read_crl_file("crl_intermediate_revoked.pem").as_bytes())
+        .unwrap();
+    match connect("chained", None) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected revoked intermediate CA to fail, got {:?}", res),
+    }
+    crl.update(read_crls(&["crl_empty.pem", "crl_by_intermediate.pem"]).as_bytes())
+        .unwrap();
+    let reply = connect("chained", None).expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+}
+
+struct CrlServerReload(CertificateRevocationList);
+
+impl ServerCredentialsFetcher for CrlServerReload {
+    fn fetch(&self) -> Result<Option<ServerCredentialsBuilder>, Box<dyn std::error::Error>> {
+        let builder = ServerCredentialsBuilder::new()
+            .add_cert(
+                read_crl_file("server.pem").into(),
+                read_crl_file("server.key").into(),
+            )
+            .root_cert(
+                read_crl_file("ca.pem"),
+                CertificateRequestType::DontRequestClientCertificate,
+            )
+            .revocation_list(self.0.clone())?;
+        Ok(Some(builder))
+    }
+}
+
+struct PlainClientReload(Arc<AtomicUsize>);
+
+impl ChannelCredentialsFetcher for PlainClientReload {
+    fn fetch(&self) -> Result<Option<ChannelCredentialsBuilder>, Box<dyn std::error::Error>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let builder = ChannelCredentialsBuilder::new()
+            .root_cert(read_crl_file("ca.pem").into())
+            .cert(
+                read_crl_file("client.pem").into(),
+                read_crl_file("client.key").into(),
+            );
+        Ok(Some(builder))
+    }
+}
+
+#[test]
+fn test_revocation_list_with_fetcher() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let crl = CertificateRevocationList::from_pem(read_crl_file("crl.pem").as_bytes()).unwrap();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(create_greeter(GreeterService))
+        .bind_with_fetcher(
+            "127.0.0.1",
+            0,
+            Box::new(CrlServerReload(crl.clone())),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let mut req = HelloRequest::default();
+    req.set_name("world".to_owned());
+    let connect = |cred: ChannelCredentials| {
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.crl.example")
+            .secure_connect(&format!("127.0.0.1:{}", port), cred);
+        GreeterClient::new(ch)
+    };
+    let cred = |client: &str| {
+        ChannelCredentialsBuilder::new()
+            .root_cert(read_crl_file("ca.pem").into())
+            .cert(
+                read_crl_file(&format!("{}.pem", client)).into(),
+                read_crl_file(&format!("{}.key", client)).into(),
+            )
+    };
+
+    // The server checks clients with the list returned by the fetcher.
+    let reply = connect(cred("client").build()).say_hello(&req);
+    assert_eq!(reply.expect("rpc").get_message(), "Hello world");
+    match connect(cred("revoked").build()).say_hello(&req) {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected revoked client to fail, got {:?}", res),
+    }
+
+    // The client keeps rejecting the revoked server after reloading credentials.
+    let fetched = Arc::new(AtomicUsize::new(0));
+    let client = connect(
+        cred("client")
+            .revocation_list(crl)
+            .unwrap()
+            .fetcher(Box::new(PlainClientReload(fetched.clone())))
+            .build(),
+    );
+    for i in 0..50 {
+        match client.say_hello(&req) {
+            Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+            res => panic!("expected revoked server to fail, got {:?}", res),
+        }
+        if i > 0 && fetched.load(Ordering::SeqCst) > 1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(fetched.load(Ordering::SeqCst) > 1);
+}
+
+struct PinnedCert(String);
+
+impl PeerVerifier for PinnedCert {
+    fn verify(&self, peer: &PeerCertificate<'_>) -> Result<(), String> {
+        if peer.pem_cert() != self.0.as_bytes() {
+            return Err("certificate is not pinned".to_owned());
+        }
+        Ok(())
+    }
+}
+
+struct TargetName(&'static str);
+
+impl PeerVerifier for TargetName {
+    fn verify(&self, peer: &PeerCertificate<'_>) -> Result<(), String> {
+        if peer.target_name() != Some(self.0) {
+            return Err(format!("unexpected target {:?}", peer.target_name()));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_peer_verifier() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let server_cred = ServerCredentialsBuilder::new()
+        .add_cert(
+            read_crl_file("server.pem").into(),
+            read_crl_file("server.key").into(),
+        )
+        .root_cert(
+            read_crl_file("ca.pem"),
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify,
+        )
+        .peer_verifier(PinnedCert(read_crl_file("client.pem")))
+        .unwrap()
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(create_greeter(GreeterService))
+        .bind_with_cred("127.0.0.1", 0, server_cred)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let connect = |client: &str, expected_target: &'static str| {
+        let cred = ChannelCredentialsBuilder::new()
+            .root_cert(read_crl_file("ca.pem").into())
+            .cert(
+                read_crl_file(&format!("{}.pem", client)).into(),
+                read_crl_file(&format!("{}.key", client)).into(),
+            )
+            .peer_verifier(TargetName(expected_target))
+            .unwrap()
+            .build();
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.crl.example")
+            .secure_connect(&format!("127.0.0.1:{}", port), cred);
+        let client = GreeterClient::new(ch);
+        let mut req = HelloRequest::default();
+        req.set_name("world".to_owned());
+        client.say_hello(&req)
+    };
+    let reply = connect("client", "rust.test.crl.example").expect("rpc");
+    assert_eq!(reply.get_message(), "Hello world");
+
+    // The client is rejected during the handshake, so it doesn't know why.
+    match connect("revoked", "rust.test.crl.example") {
+        Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        res => panic!("expected the client to be rejected, got {:?}", res),
+    }
+
+    match connect("client", "other.test.crl.example") {
+        Err(Error::RpcFailure(s)) => {
+            assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE);
+            assert!(s.message().contains("unexpected target"), "{:?}", s);
+        }
+        res => panic!("expected the server to be rejected, got {:?}", res),
+    }
+}
+
+#[derive(Clone)]
+struct TokenService;
+
+impl Greeter for TokenService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+        let tokens: Vec<_> = ctx
+            .request_headers()
+            .iter()
+            .filter(|(k, _)| *k == "authorization")
+            .map(|(_, v)| String::from_utf8(v.to_vec()).unwrap())
+            .collect();
+        let mut resp = HelloReply::default();
+        resp.set_message(tokens.join(","));
+        ctx.spawn(
+            sink.success(resp)
+                .map_err(|e| panic!("failed to reply {:?}", e))
+                .map(|_| ()),
+        )
+    }
+}
+
+struct TokenPlugin {
+    prefix: &'static str,
+    counter: AtomicUsize,
+}
+
+impl MetadataCredentialsPlugin for TokenPlugin {
+    fn get_metadata<'a>(
+        &'a self,
+        ctx: &'a AuthMetadataContext,
+    ) -> BoxFuture<'a, Result<Metadata, RpcStatus>> {
+        async move {
+            assert_eq!(ctx.method_name(), "SayHello");
+            assert!(
+                ctx.service_url().ends_with("/helloworld.Greeter"),
+                "{}",
+                ctx.service_url()
+            );
+            if self.prefix.is_empty() {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::UNAUTHENTICATED,
+                    "no token".to_owned(),
+                ));
+            }
+            let n = self.counter.fetch_add(1, Ordering::SeqCst);
+            let mut builder = MetadataBuilder::new();
+            builder
+                .add_str("authorization", &format!("{} {}", self.prefix, n))
+                .unwrap();
+            Ok(builder.build())
+        }
+        .boxed()
+    }
+}
+
+fn token_creds(prefix: &'static str) -> CallCredentials {
+    CallCredentials::from_plugin(TokenPlugin {
+        prefix,
+        counter: AtomicUsize::new(0),
+    })
+}
+
+#[test]
+fn test_call_credentials() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_greeter(TokenService);
+    let (server_crt, server_key) = read_cert_pair("server1").unwrap();
+    let server_credentials = ServerCredentialsBuilder::new()
+        .add_cert(server_crt.into(), server_key.into())
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let cred = ChannelCredentialsBuilder::new()
+        .root_cert(read_single_crt("ca").unwrap().into())
+        .build()
+        .compose(&token_creds("Bearer"));
+    let ch = ChannelBuilder::new(env)
+        .override_ssl_target("rust.test.google.fr")
+        .secure_connect(&format!("127.0.0.1:{}", port), cred);
+    let client = GreeterClient::new(ch);
+    let req = HelloRequest::default();
+    // Tokens are fetched for every call.
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), "Bearer 0");
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), "Bearer 1");
+
+    // Credentials of a call are used together with the ones of the channel.
+    let opt = CallOption::default().call_credentials(token_creds("Basic"));
+    let reply = client.say_hello_opt(&req, opt).unwrap();
+    assert_eq!(reply.get_message(), "Bearer 2,Basic 0");
+
+    let opt = CallOption::default().call_credentials(token_creds(""));
+    match client.say_hello_opt(&req, opt).unwrap_err() {
+        Error::RpcFailure(s) => {
+            assert_eq!(s.code(), RpcStatusCode::UNAUTHENTICATED);
+            assert!(s.message().contains("no token"), "{:?}", s);
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_local_call_credentials() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_greeter(TokenService);
+    let socket = Defer("unix:local_call_creds_socket");
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred(socket.0, 0, ServerCredentials::local(LocalConnectType::Uds))
+        .bind_with_cred(
+            "127.0.0.1",
+            0,
+            ServerCredentials::local(LocalConnectType::LocalTcp),
+        )
+        .build()
+        .unwrap();
+    server.start();
+    let port = server
+        .bind_addrs()
+        .find(|(host, _)| *host == "127.0.0.1")
+        .unwrap()
+        .1;
+
+    let cred = ChannelCredentials::local(LocalConnectType::Uds).compose(&token_creds("Bearer"));
+    let ch = ChannelBuilder::new(env.clone()).secure_connect(socket.0, cred);
+    let client = GreeterClient::new(ch);
+    let req = HelloRequest::default();
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), "Bearer 0");
+
+    // Local TCP connections are not protected, so tokens are only sent over them
+    // if they don't require any security level.
+    let addr = format!("127.0.0.1:{}", port);
+    let cred =
+        ChannelCredentials::local(LocalConnectType::LocalTcp).compose(&token_creds("Bearer"));
+    let ch = ChannelBuilder::new(env.clone()).secure_connect(&addr, cred);
+    let client = GreeterClient::new(ch);
+    client.say_hello(&req).unwrap_err();
+
+    let token = CallCredentials::from_plugin_with_min_security_level(
+        TokenPlugin {
+            prefix: "Bearer",
+            counter: AtomicUsize::new(0),
+        },
+        SecurityLevel::None,
+    );
+    let cred = ChannelCredentials::local(LocalConnectType::LocalTcp).compose(&token);
+    let ch = ChannelBuilder::new(env).secure_connect(&addr, cred);
+    let client = GreeterClient::new(ch);
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), "Bearer 0");
+}
+
+#[derive(Clone)]
+struct SessionService;
+
+impl Greeter for SessionService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+        let reused = ctx
+            .auth_context()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.name() == "ssl_session_reused")
+            .map(|p| p.value_str().unwrap().to_owned());
+        let mut resp = HelloReply::default();
+        resp.set_message(reused.unwrap_or_default());
+        ctx.spawn(
+            sink.success(resp)
+                .map_err(|e| panic!("failed to reply {:?}", e))
+                .map(|_| ()),
+        )
+    }
+}
+
+#[test]
+fn test_ssl_session_cache() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_greeter(SessionService);
+    let (server_crt, server_key) = read_cert_pair("server1").unwrap();
+    let server_credentials = ServerCredentialsBuilder::new()
+        .add_cert(server_crt.into(), server_key.into())
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let cache = SslSessionCache::new(8);
+    let connect = |cache: Option<&SslSessionCache>| {
+        let cred = ChannelCredentialsBuilder::new()
+            .root_cert(read_single_crt("ca").unwrap().into())
+            .build();
+        // Don't share connections between channels, so every channel does its
+        // own handshake.
+        let mut builder = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.google.fr")
+            .raw_cfg_int(CString::new("grpc.use_local_subchannel_pool").unwrap(), 1);
+        if let Some(cache) = cache {
+            builder = builder.ssl_session_cache(cache);
+        }
+        let ch = builder.secure_connect(&format!("127.0.0.1:{}", port), cred);
+        let reused = GreeterClient::new(ch)
+            .say_hello(&HelloRequest::default())
+            .unwrap()
+            .take_message();
+        reused == "true"
+    };
+
+    // Only the first handshake with the cache is a full one.
+    let full_handshakes = (0..3).filter(|_| !connect(Some(&cache))).count();
+    assert_eq!(full_handshakes, 1);
+    assert!(!connect(None));
+    assert!(connect(Some(&cache.clone())));
+}
+
+struct Tls12ServerReload;
+
+impl ServerCredentialsFetcher for Tls12ServerReload {
+    fn fetch(&self) -> Result<Option<ServerCredentialsBuilder>, Box<dyn std::error::Error>> {
+        let (server_crt, server_key) = read_cert_pair("server1")?;
+        let builder = ServerCredentialsBuilder::new()
+            .add_cert(server_crt.into(), server_key.into())
+            .max_tls_version(TlsVersion::Tls12)?;
+        Ok(Some(builder))
+    }
+}
+
+#[test]
+fn test_tls_versions() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (server_crt, server_key) = read_cert_pair("server1").unwrap();
+    let server_credentials = ServerCredentialsBuilder::new()
+        .add_cert(server_crt.into(), server_key.into())
+        .max_tls_version(TlsVersion::Tls12)
+        .unwrap()
+        .build();
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(create_greeter(GreeterService))
+        .bind_with_cred("127.0.0.1", 0, server_credentials)
+        .bind_with_fetcher(
+            "127.0.0.1",
+            0,
+            Box::new(Tls12ServerReload),
+            CertificateRequestType::DontRequestClientCertificate,
+        )
+        .build()
+        .unwrap();
+    server.start();
+    let ports: Vec<_> = server.bind_addrs().map(|(_, port)| port).collect();
+    assert_eq!(ports.len(), 2);
+
+    let connect = |port: u16, min_version: TlsVersion| {
+        let cred = ChannelCredentialsBuilder::new()
+            .root_cert(read_single_crt("ca").unwrap().into())
+            .min_tls_version(min_version)
+            .unwrap()
+            .build();
+        let ch = ChannelBuilder::new(env.clone())
+            .override_ssl_target("rust.test.google.fr")
+            .secure_connect(&format!("127.0.0.1:{}", port), cred);
+        let mut req = HelloRequest::default();
+        req.set_name("world".to_owned());
+        GreeterClient::new(ch).say_hello(&req)
+    };
+    for port in ports {
+        let reply = connect(port, TlsVersion::Tls12).unwrap();
+        assert_eq!(reply.get_message(), "Hello world");
+        match connect(port, TlsVersion::Tls13) {
+            Err(Error::RpcFailure(s)) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+            res => panic!("expected the handshake to fail, got {:?}", res),
+        }
+    }
+}
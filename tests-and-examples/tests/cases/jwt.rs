@@ -0,0 +1,105 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use futures::prelude::*;
+use grpcio::*;
+use grpcio_proto::example::helloworld::*;
+use std::sync::*;
+
+#[derive(Clone)]
+struct ClaimsService;
+
+impl Greeter for ClaimsService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+        let claims = ctx.extensions().get::<JwtClaims>().unwrap();
+        let mut resp = HelloReply::default();
+        resp.set_message(format!(
+            "{} {}",
+            claims.subject().unwrap(),
+            claims.get_str("role").unwrap()
+        ));
+        ctx.spawn(
+            sink.success(resp)
+                .map_err(|e| panic!("failed to reply {:?}", e))
+                .map(|_| ()),
+        );
+    }
+}
+
+fn jwt_path(name: &str) -> String {
+    format!("{}/certs/jwt/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn read_token(name: &str) -> String {
+    std::fs::read_to_string(jwt_path(name))
+        .unwrap()
+        .trim()
+        .to_owned()
+}
+
+#[test]
+fn test_jwt_authenticator() {
+    let authenticator = JwtAuthenticator::from_file(jwt_path("jwks.json"), None)
+        .unwrap()
+        .issuer("https://issuer.example")
+        .audience("greeter");
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_greeter(ClaimsService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .add_interceptor(authenticator)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let bearer = |value: String| {
+        let mut builder = MetadataBuilder::new();
+        builder.add_str("authorization", &value).unwrap();
+        CallOption::default().headers(builder.build())
+    };
+    let req = HelloRequest::default();
+
+    let resp = client
+        .say_hello_opt(&req, bearer(format!("Bearer {}", read_token("rs256.jwt"))))
+        .unwrap();
+    assert_eq!(resp.get_message(), "alice admin");
+    let resp = client
+        .say_hello_opt(&req, bearer(format!("bearer {}", read_token("es256.jwt"))))
+        .unwrap();
+    assert_eq!(resp.get_message(), "bob admin");
+
+    let cases = vec![
+        (None, "missing bearer token"),
+        (
+            Some(format!("Basic {}", read_token("rs256.jwt"))),
+            "missing bearer token",
+        ),
+        (
+            Some(format!("Bearer {}", read_token("expired.jwt"))),
+            "token is expired",
+        ),
+        (
+            Some(format!("Bearer {}", read_token("wrong_aud.jwt"))),
+            "audience mismatch",
+        ),
+        (
+            Some("Bearer a.b.c".to_owned()),
+            "invalid base64url in header",
+        ),
+    ];
+    for (value, msg) in cases {
+        let res = match value {
+            Some(value) => client.say_hello_opt(&req, bearer(value)),
+            None => client.say_hello(&req),
+        };
+        match res {
+            Err(Error::RpcFailure(s)) => {
+                assert_eq!(s.code(), RpcStatusCode::UNAUTHENTICATED);
+                assert_eq!(s.message(), format!("invalid JWT: {}", msg));
+            }
+            res => panic!("expect unauthenticated, but got {:?}", res),
+        }
+    }
+}
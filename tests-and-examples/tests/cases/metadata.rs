@@ -3,7 +3,7 @@
 use futures::*;
 use grpcio::*;
 use grpcio_proto::example::helloworld::*;
-use grpcio_proto::google::rpc::Status;
+use grpcio_proto::google::rpc::{ErrorInfo, RetryInfo, Status};
 use std::convert::TryInto;
 use std::sync::mpsc::{self, Sender};
 use std::sync::*;
@@ -19,12 +19,19 @@ impl Greeter for GreeterService {
         &mut self,
         ctx: RpcContext<'_>,
         mut req: HelloRequest,
-        sink: UnarySink<HelloReply>,
+        mut sink: UnarySink<HelloReply>,
     ) {
         for (key, value) in ctx.request_headers() {
             self.tx.send((key.to_owned(), value.to_owned())).unwrap();
         }
 
+        let mut headers = MetadataBuilder::new();
+        headers.add_str("response-header", "h1").unwrap();
+        sink.set_headers(headers.build()).unwrap();
+        let mut trailers = MetadataBuilder::new();
+        trailers.add_str("response-trailer", "t1").unwrap();
+        sink.set_trailers(trailers.build()).unwrap();
+
         if req.name == "root" {
             let mut status = Status::default();
             status.code = RpcStatusCode::INVALID_ARGUMENT.into();
@@ -39,6 +46,24 @@ impl Greeter for GreeterService {
             return;
         }
 
+        if req.name == "busy" {
+            let mut status =
+                Status::with_message(RpcStatusCode::UNAVAILABLE, "server is busy".to_owned());
+            let mut info = ErrorInfo::default();
+            info.reason = "SHARD_MOVED".to_owned();
+            info.domain = "example.com".to_owned();
+            status.pack_detail(&info).unwrap();
+            let mut retry = RetryInfo::default();
+            retry.mut_retry_delay().seconds = 3;
+            status.pack_detail(&retry).unwrap();
+            ctx.spawn(
+                sink.fail(status.try_into().unwrap())
+                    .map_err(|e| panic!("failed to report error: {:?}", e))
+                    .map(|_| ()),
+            );
+            return;
+        }
+
         let mut resp = HelloReply::default();
         resp.set_message(format!("hello {}", req.take_name()));
         ctx.spawn(
@@ -49,7 +74,10 @@ impl Greeter for GreeterService {
     }
 }
 
-// TODO: test it in interop tests once trailer is supported.
+fn find_value<'a>(metadata: &'a Metadata, key: &str) -> Option<&'a [u8]> {
+    metadata.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+}
+
 #[test]
 fn test_metadata() {
     let env = Arc::new(EnvBuilder::new().build());
@@ -112,3 +140,84 @@ fn test_rich_error() {
     let details: Option<HelloRequest> = s.details[0].unpack().unwrap();
     assert_eq!(Some(req), details);
 }
+
+/// Tests typed error details can be packed and unpacked.
+#[test]
+fn test_error_details() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, _rx) = mpsc::channel();
+    let service = create_greeter(GreeterService { tx });
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+
+    let mut req = HelloRequest::default();
+    req.set_name("busy".to_owned());
+    let s = match client.say_hello(&req) {
+        Err(grpcio::Error::RpcFailure(s)) => Status::from_rpc_status(&s).unwrap(),
+        res => panic!("expected failure, got {:?}", res),
+    };
+    assert_eq!(s.code, RpcStatusCode::UNAVAILABLE.into());
+    let info: ErrorInfo = s.unpack_detail().unwrap().unwrap();
+    assert_eq!(info.reason, "SHARD_MOVED");
+    assert_eq!(info.domain, "example.com");
+    let retry: RetryInfo = s.unpack_detail().unwrap().unwrap();
+    assert_eq!(retry.delay(), Some(Duration::from_secs(3)));
+    assert!(s.unpack_detail::<HelloRequest>().unwrap().is_none());
+
+    // A status without details can also be unpacked.
+    let s = RpcStatus::with_message(RpcStatusCode::NOT_FOUND, "missing".to_owned());
+    let s = Status::from_rpc_status(&s).unwrap();
+    assert_eq!(s.message, "missing");
+    assert!(s.unpack_details::<ErrorInfo>().unwrap().is_empty());
+}
+
+/// Tests response headers and trailers can be received by client.
+#[test]
+fn test_response_metadata() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let (tx, _rx) = mpsc::channel();
+    let service = create_greeter(GreeterService { tx });
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+
+    let mut req = HelloRequest::default();
+    req.set_name("world".to_owned());
+    let mut receiver = client.say_hello_async(&req).unwrap();
+    assert!(receiver.trailers().is_none());
+    let headers = executor::block_on(receiver.headers()).clone();
+    assert_eq!(find_value(&headers, "response-header"), Some(&b"h1"[..]));
+    let trailers = receiver.trailers().unwrap().clone();
+    assert_eq!(find_value(&trailers, "response-trailer"), Some(&b"t1"[..]));
+    let resp = executor::block_on(receiver).unwrap();
+    assert_eq!(resp.get_message(), "hello world");
+
+    // Headers and trailers should also be available when the call fails.
+    req.set_name("root".to_owned());
+    let mut receiver = client.say_hello_async(&req).unwrap();
+    let status = match executor::block_on(&mut receiver) {
+        Err(grpcio::Error::RpcFailure(s)) => s,
+        res => panic!("expected failure, got {:?}", res),
+    };
+    assert_eq!(status.code(), RpcStatusCode::INVALID_ARGUMENT);
+    assert!(!status.details().is_empty());
+    let trailers = status.trailers().unwrap();
+    assert_eq!(find_value(trailers, "response-trailer"), Some(&b"t1"[..]));
+    let trailers = receiver.trailers().unwrap();
+    assert_eq!(find_value(trailers, "response-trailer"), Some(&b"t1"[..]));
+    let headers = executor::block_on(receiver.headers());
+    assert_eq!(find_value(headers, "response-header"), Some(&b"h1"[..]));
+}
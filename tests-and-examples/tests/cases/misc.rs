@@ -282,6 +282,574 @@ impl ServerChecker for FlagChecker {
     }
 }
 
+#[derive(Clone)]
+struct UserService;
+
+impl Greeter for UserService {
+    fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, mut sink: UnarySink<HelloReply>) {
+        let user = ctx
+            .request_headers()
+            .iter()
+            .find(|(k, _)| *k == "user")
+            .map(|(_, v)| String::from_utf8(v.to_vec()).unwrap());
+        let mut headers = MetadataBuilder::new();
+        headers.add_str("service", "greeter").unwrap();
+        sink.set_headers(headers.build()).unwrap();
+        let mut resp = HelloReply::default();
+        resp.set_message(user.unwrap_or_default());
+        ctx.spawn(sink.success(resp).map(|_| ()));
+    }
+}
+
+struct NameInterceptor {
+    name: &'static str,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl ServerInterceptor for NameInterceptor {
+    fn intercept<'a>(
+        &'a self,
+        ctx: &'a mut InterceptContext,
+    ) -> future::BoxFuture<'a, CheckResult> {
+        async move {
+            Delay::new(Duration::from_millis(10)).await;
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{} intercept", self.name));
+            let token = ctx
+                .request_headers()
+                .iter()
+                .find(|(k, _)| *k == "token")
+                .map(|(_, v)| v.to_vec());
+            let deny_self = format!("deny-{}", self.name);
+            if token.as_deref() == Some(&b"deny"[..])
+                || token.as_deref() == Some(deny_self.as_bytes())
+            {
+                return CheckResult::Abort(RpcStatus::new(RpcStatusCode::PERMISSION_DENIED));
+            }
+
+            let mut headers = MetadataBuilder::from(ctx.request_headers().clone());
+            headers.add_str("user", self.name).unwrap();
+            ctx.set_request_headers(headers.build());
+            ctx.response_headers()
+                .add_str("interceptor", self.name)
+                .unwrap();
+            ctx.response_trailers()
+                .add_str("interceptor", self.name)
+                .unwrap();
+            CheckResult::Continue
+        }
+        .boxed()
+    }
+
+    fn on_finish(&self, method: &[u8], status: &RpcStatus, _: Duration) {
+        assert_eq!(method, b"/helloworld.Greeter/SayHello");
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("{} finish {}", self.name, status.code()));
+    }
+}
+
+// Server hooks are notified after the status is sent, which can be later than the
+// client receives it.
+fn wait_events(events: &Mutex<Vec<String>>, count: usize) -> Vec<String> {
+    for _ in 0..100 {
+        let events = events.lock().unwrap();
+        if events.len() >= count {
+            return events.clone();
+        }
+        drop(events);
+        thread::sleep(Duration::from_millis(10));
+    }
+    events.lock().unwrap().clone()
+}
+
+#[test]
+fn test_interceptor() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .add_interceptor(NameInterceptor {
+            name: "a",
+            events: events.clone(),
+        })
+        .add_interceptor(NameInterceptor {
+            name: "b",
+            events: events.clone(),
+        })
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let req = HelloRequest::default();
+
+    let mut receiver = client.say_hello_async(&req).unwrap();
+    let headers = block_on(receiver.headers()).clone();
+    let values: Vec<_> = headers
+        .iter()
+        .map(|(k, v)| (k.to_owned(), v.to_vec()))
+        .collect();
+    let expect = vec![
+        ("interceptor".to_owned(), b"a".to_vec()),
+        ("interceptor".to_owned(), b"b".to_vec()),
+        ("service".to_owned(), b"greeter".to_vec()),
+    ];
+    assert_eq!(values, expect);
+    let trailers = receiver.trailers().unwrap();
+    assert_eq!(trailers.len(), 2);
+    // The request headers are rewritten by both interceptors.
+    assert_eq!(block_on(receiver).unwrap().get_message(), "a");
+    assert_eq!(
+        wait_events(&events, 4),
+        vec![
+            "a intercept",
+            "b intercept",
+            "b finish 0-OK",
+            "a finish 0-OK"
+        ]
+    );
+
+    events.lock().unwrap().clear();
+    let mut builder = MetadataBuilder::new();
+    builder.add_str("token", "deny").unwrap();
+    let opt = CallOption::default().headers(builder.build());
+    let err = client.say_hello_opt(&req, opt).unwrap_err();
+    match err {
+        Error::RpcFailure(s) => assert_eq!(s.code(), RpcStatusCode::PERMISSION_DENIED),
+        e => panic!("unexpected error: {:?}", e),
+    }
+    assert_eq!(
+        wait_events(&events, 3),
+        vec![
+            "a intercept",
+            "b finish 7-PERMISSION_DENIED",
+            "a finish 7-PERMISSION_DENIED",
+        ]
+    );
+
+    // Response metadata added by previous interceptors is sent with the status.
+    events.lock().unwrap().clear();
+    let mut builder = MetadataBuilder::new();
+    builder.add_str("token", "deny-b").unwrap();
+    let opt = CallOption::default().headers(builder.build());
+    let mut receiver = client.say_hello_async_opt(&req, opt).unwrap();
+    let headers = block_on(receiver.headers()).clone();
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers.get(0), Some(("interceptor", &b"a"[..])));
+    let status = match block_on(receiver) {
+        Err(Error::RpcFailure(s)) => s,
+        res => panic!("unexpected result: {:?}", res),
+    };
+    assert_eq!(status.code(), RpcStatusCode::PERMISSION_DENIED);
+    let trailers = status.trailers().unwrap();
+    assert_eq!(trailers.len(), 1);
+    assert_eq!(trailers.get(0), Some(("interceptor", &b"a"[..])));
+    assert_eq!(
+        wait_events(&events, 4),
+        vec![
+            "a intercept",
+            "b intercept",
+            "b finish 7-PERMISSION_DENIED",
+            "a finish 7-PERMISSION_DENIED",
+        ]
+    );
+}
+
+struct EventRecorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl MetricsRecorder for EventRecorder {
+    fn on_started(&self, method: &str, _: MethodType) {
+        assert_eq!(method, "/helloworld.Greeter/SayHello");
+        self.events.lock().unwrap().push("started".to_owned());
+    }
+
+    fn on_handled(&self, _: &str, _: MethodType, code: RpcStatusCode, _: Duration) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("handled {}", code));
+    }
+
+    fn on_msg_received(&self, _: &str, _: MethodType, bytes: usize) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("received {}", bytes));
+    }
+
+    fn on_msg_sent(&self, _: &str, _: MethodType, bytes: usize) {
+        self.events.lock().unwrap().push(format!("sent {}", bytes));
+    }
+}
+
+#[test]
+fn test_server_metrics() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .metrics_recorder(EventRecorder {
+            events: events.clone(),
+        })
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env.clone()).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let mut req = HelloRequest::default();
+    req.set_name("world".to_owned());
+    let mut builder = MetadataBuilder::new();
+    builder.add_str("user", "metrics").unwrap();
+    let opt = CallOption::default().headers(builder.build());
+    assert_eq!(
+        client.say_hello_opt(&req, opt).unwrap().get_message(),
+        "metrics"
+    );
+    // Status is sent along with the response message.
+    assert_eq!(
+        wait_events(&events, 4),
+        vec!["started", "received 7", "sent 9", "handled 0-OK"]
+    );
+
+    // Aborted calls are also recorded.
+    events.lock().unwrap().clear();
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .add_interceptor(NameInterceptor {
+            name: "a",
+            events: Arc::default(),
+        })
+        .metrics_recorder(EventRecorder {
+            events: events.clone(),
+        })
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env.clone()).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let mut builder = MetadataBuilder::new();
+    builder.add_str("token", "deny").unwrap();
+    let opt = CallOption::default().headers(builder.build());
+    client.say_hello_opt(&req, opt).unwrap_err();
+    assert_eq!(
+        wait_events(&events, 2),
+        vec!["started", "handled 7-PERMISSION_DENIED"]
+    );
+
+    // So are the calls rejected by checkers and the undefined methods.
+    events.lock().unwrap().clear();
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .add_checker(FlagChecker {
+            flag: Arc::new(AtomicBool::new(true)),
+        })
+        .metrics_recorder(EventRecorder {
+            events: events.clone(),
+        })
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env.clone()).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    client.say_hello(&req).unwrap_err();
+    assert_eq!(
+        wait_events(&events, 2),
+        vec!["started", "handled 15-DATA_LOSS"]
+    );
+
+    events.lock().unwrap().clear();
+    let mut server = ServerBuilder::new(env.clone())
+        .metrics_recorder(EventRecorder {
+            events: events.clone(),
+        })
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    client.say_hello(&req).unwrap_err();
+    assert_eq!(
+        wait_events(&events, 2),
+        vec!["started", "handled 12-UNIMPLEMENTED"]
+    );
+}
+
+struct UserInterceptor {
+    deny: Arc<AtomicBool>,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl ClientInterceptor for UserInterceptor {
+    fn intercept(&self, method: &str, opt: &mut CallOption) -> CheckResult {
+        assert_eq!(method, "/helloworld.Greeter/SayHello");
+        if self.deny.load(Ordering::SeqCst) {
+            return CheckResult::Abort(RpcStatus::new(RpcStatusCode::UNAVAILABLE));
+        }
+        let mut headers = MetadataBuilder::new();
+        headers.add_str("user", "client").unwrap();
+        *opt = opt
+            .clone()
+            .headers(headers.build())
+            .timeout(Duration::from_secs(10));
+        CheckResult::Continue
+    }
+
+    fn on_finish(&self, _: &str, status: &RpcStatus, _: Duration) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("finish {}", status.code()));
+    }
+}
+
+#[test]
+fn test_client_interceptor() {
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let deny = Arc::new(AtomicBool::new(false));
+    let events = Arc::new(Mutex::new(vec![]));
+    let ch = ChannelBuilder::new(env)
+        .add_interceptor(UserInterceptor {
+            deny: deny.clone(),
+            events: events.clone(),
+        })
+        .connect(&format!("127.0.0.1:{}", port));
+    let client = GreeterClient::new(ch);
+    let req = HelloRequest::default();
+
+    // The headers are added by the interceptor.
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), "client");
+    assert_eq!(*events.lock().unwrap(), vec!["finish 0-OK"]);
+
+    deny.store(true, Ordering::SeqCst);
+    match client.say_hello(&req).unwrap_err() {
+        Error::RpcFailure(s) => assert_eq!(s.code(), RpcStatusCode::UNAVAILABLE),
+        e => panic!("unexpected error: {:?}", e),
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["finish 0-OK", "finish 14-UNAVAILABLE"]
+    );
+
+    deny.store(false, Ordering::SeqCst);
+    let receiver = client.say_hello_async(&req).unwrap();
+    drop(receiver);
+    assert_eq!(events.lock().unwrap().last().unwrap(), "finish 1-CANCELLED");
+}
+
+struct ClientEventRecorder {
+    target: String,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl ClientMetricsRecorder for ClientEventRecorder {
+    fn on_started(&self, target: &str, method: &str, _: MethodType) {
+        assert_eq!(target, self.target);
+        assert_eq!(method, "/helloworld.Greeter/SayHello");
+        self.events.lock().unwrap().push("started".to_owned());
+    }
+
+    fn on_completed(&self, _: &str, _: &str, _: MethodType, code: RpcStatusCode, _: Duration) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("completed {}", code));
+    }
+
+    fn on_msg_received(&self, _: &str, _: &str, _: MethodType, bytes: usize) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("received {}", bytes));
+    }
+
+    fn on_msg_sent(&self, _: &str, _: &str, _: MethodType, bytes: usize) {
+        self.events.lock().unwrap().push(format!("sent {}", bytes));
+    }
+}
+
+#[test]
+fn test_client_metrics() {
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(UserService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let target = format!("127.0.0.1:{}", port);
+    let events = Arc::new(Mutex::new(vec![]));
+    let ch = ChannelBuilder::new(env)
+        .metrics_recorder(ClientEventRecorder {
+            target: target.clone(),
+            events: events.clone(),
+        })
+        .connect(&target);
+    let client = GreeterClient::new(ch);
+    let mut req = HelloRequest::default();
+    req.set_name("world".to_owned());
+    let mut builder = MetadataBuilder::new();
+    builder.add_str("user", "metrics").unwrap();
+    let opt = CallOption::default().headers(builder.build());
+    assert_eq!(
+        client.say_hello_opt(&req, opt).unwrap().get_message(),
+        "metrics"
+    );
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["started", "sent 7", "received 9", "completed 0-OK"]
+    );
+
+    events.lock().unwrap().clear();
+    let receiver = client.say_hello_async(&req).unwrap();
+    drop(receiver);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["started", "sent 7", "completed 1-CANCELLED"]
+    );
+}
+
+#[derive(Clone, Default)]
+struct StaticResolver {
+    updaters: Arc<Mutex<Vec<(String, ResolverUpdater)>>>,
+}
+
+impl Resolver for StaticResolver {
+    fn resolve(&self, target: &str, updater: ResolverUpdater) {
+        self.updaters
+            .lock()
+            .unwrap()
+            .push((target.to_owned(), updater));
+    }
+}
+
+#[test]
+fn test_resolver() {
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(PeerService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+
+    let resolver = StaticResolver::default();
+    register_resolver("misc-test", resolver.clone()).unwrap();
+    let ch = ChannelBuilder::new(env).connect("misc-test:greeter");
+    let client = GreeterClient::new(ch.clone());
+    let req = HelloRequest::default();
+    // Calls are queued until any address is resolved.
+    let opt = CallOption::default().timeout(Duration::from_millis(200));
+    match client.say_hello_opt(&req, opt).unwrap_err() {
+        Error::RpcFailure(s) => assert_eq!(s.code(), RpcStatusCode::DEADLINE_EXCEEDED),
+        e => panic!("unexpected error: {:?}", e),
+    }
+
+    let (target, updater) = resolver.updaters.lock().unwrap().pop().unwrap();
+    assert_eq!(target, "misc-test:greeter");
+    let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+    assert!(updater.update(&[addr], None));
+    let peer = client.say_hello(&req).unwrap().get_message().to_owned();
+    assert!(peer.starts_with("ipv4:127.0.0.1:"), "{}", peer);
+
+    // An update that changes nothing keeps the connection.
+    assert!(updater.update(&[addr], None));
+    assert_eq!(
+        ch.check_connectivity_state(false),
+        ConnectivityState::GRPC_CHANNEL_READY
+    );
+    assert_eq!(client.say_hello(&req).unwrap().get_message(), peer.as_str());
+
+    // Updates are applied asynchronously.
+    assert!(updater.update(&[], None));
+    let mut code = RpcStatusCode::OK;
+    for _ in 0..50 {
+        match client.say_hello(&req) {
+            Err(Error::RpcFailure(s)) => {
+                code = s.code();
+                break;
+            }
+            Ok(_) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    assert_eq!(code, RpcStatusCode::UNAVAILABLE);
+
+    drop(client);
+    drop(ch);
+    assert!(!updater.update(&[addr], None));
+}
+
+#[test]
+fn test_channel_pool() {
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(PeerService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let addr = format!("127.0.0.1:{}", port);
+
+    for policy in &[PickPolicy::RoundRobin, PickPolicy::LeastOutstanding] {
+        let pool = ChannelBuilder::new(env.clone()).connect_pool(&addr, 3, *policy);
+        assert_eq!(pool.channels().len(), 3);
+        let client = GreeterClient::with_pool(pool.clone());
+        let req = HelloRequest::default();
+        let mut peers = std::collections::HashSet::new();
+        for _ in 0..6 {
+            let resp = client.say_hello(&req).unwrap();
+            peers.insert(resp.get_message().to_owned());
+        }
+        // Every channel owns a connection, so the server sees different peers.
+        assert_eq!(peers.len(), 3, "{:?} {:?}", policy, peers);
+        for health in pool.health() {
+            assert_eq!(health.state(), ConnectivityState::GRPC_CHANNEL_READY);
+            assert_eq!(health.outstanding_calls(), 0);
+        }
+
+        let receiver = client.say_hello_async(&req).unwrap();
+        let outstanding: usize = pool.health().iter().map(|h| h.outstanding_calls()).sum();
+        assert_eq!(outstanding, 1);
+        block_on(receiver).unwrap();
+        let outstanding: usize = pool.health().iter().map(|h| h.outstanding_calls()).sum();
+        assert_eq!(outstanding, 0);
+    }
+}
+
 /// Tests connectivity related API works as expected.
 #[test]
 fn test_connectivity() {
@@ -367,3 +935,148 @@ fn test_connectivity() {
         ch.wait_for_connected(Duration::from_secs(3)).await;
     });
 }
+
+#[test]
+fn test_connectivity_states() {
+    let env = Arc::new(Environment::new(2));
+    let service = create_greeter(PeerService);
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let (tx, rx) = mpsc::channel();
+    let states = ch.connectivity_states();
+    let handle = thread::spawn(move || {
+        block_on(states.for_each(|state| {
+            tx.send(state).unwrap();
+            future::ready(())
+        }))
+    });
+    let timeout = Duration::from_secs(3);
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        ConnectivityState::GRPC_CHANNEL_IDLE
+    );
+
+    ch.check_connectivity_state(true);
+    let mut state = rx.recv_timeout(timeout).unwrap();
+    if state == ConnectivityState::GRPC_CHANNEL_CONNECTING {
+        state = rx.recv_timeout(timeout).unwrap();
+    }
+    assert_eq!(state, ConnectivityState::GRPC_CHANNEL_READY);
+
+    // Shutdown will send goaway, hence the state goes to idle.
+    block_on(server.shutdown()).unwrap();
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        ConnectivityState::GRPC_CHANNEL_IDLE
+    );
+
+    // The stream ends once the channel is dropped.
+    drop(ch);
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "tracing")]
+mod trace {
+    use super::*;
+
+    struct TraceService;
+
+    impl Greeter for TraceService {
+        fn say_hello(&mut self, ctx: RpcContext<'_>, _: HelloRequest, sink: UnarySink<HelloReply>) {
+            let sent = ctx
+                .request_headers()
+                .iter()
+                .find(|(k, _)| *k == "traceparent")
+                .map(|(_, v)| String::from_utf8(v.to_vec()).unwrap());
+            let sent = TraceContext::parse(&sent.unwrap(), None).unwrap();
+            let own = ctx.trace_context().unwrap();
+            assert_eq!(own.trace_id(), sent.trace_id());
+            assert_ne!(own.span_id(), sent.span_id());
+            let mut resp = HelloReply::default();
+            resp.set_message(sent.traceparent());
+            ctx.spawn(sink.success(resp).map(|_| ()));
+        }
+    }
+
+    // Forwards the request to `client` without passing the trace context.
+    struct RelayService {
+        client: GreeterClient,
+    }
+
+    impl Greeter for RelayService {
+        fn say_hello(
+            &mut self,
+            ctx: RpcContext<'_>,
+            req: HelloRequest,
+            sink: UnarySink<HelloReply>,
+        ) {
+            let trace_id = ctx.trace_context().unwrap().trace_id();
+            let resp = self.client.say_hello_async(&req).unwrap();
+            ctx.spawn(async move {
+                let resp = resp.await.unwrap();
+                let sent = TraceContext::parse(resp.get_message(), None).unwrap();
+                assert_eq!(sent.trace_id(), trace_id);
+                sink.success(resp).await.unwrap();
+            });
+        }
+    }
+
+    #[test]
+    fn test_trace_context_propagation() {
+        let env = Arc::new(Environment::new(2));
+        let service = create_greeter(TraceService);
+        let mut server = ServerBuilder::new(env.clone())
+            .register_service(service)
+            .bind("127.0.0.1", 0)
+            .build()
+            .unwrap();
+        server.start();
+        let port = server.bind_addrs().next().unwrap().1;
+        let ch = ChannelBuilder::new(env.clone()).connect(&format!("127.0.0.1:{}", port));
+        let client = GreeterClient::new(ch);
+        let req = HelloRequest::default();
+
+        // The client span is the child of the given context.
+        let parent = TraceContext::new_root();
+        let opt = CallOption::default().trace_context(parent.clone());
+        let resp = client.say_hello_opt(&req, opt).unwrap();
+        let sent = TraceContext::parse(resp.get_message(), None).unwrap();
+        assert_eq!(sent.trace_id(), parent.trace_id());
+        assert_ne!(sent.span_id(), parent.span_id());
+
+        // Stale trace headers are replaced, and a new trace is started.
+        let mut builder = MetadataBuilder::new();
+        builder
+            .add_str("traceparent", &parent.traceparent())
+            .unwrap();
+        let opt = CallOption::default().headers(builder.build());
+        let resp = client.say_hello_opt(&req, opt).unwrap();
+        let sent = TraceContext::parse(resp.get_message(), None).unwrap();
+        assert_ne!(sent.trace_id(), parent.trace_id());
+
+        // Calls made while handling a call continue its trace.
+        let ch = ChannelBuilder::new(env.clone()).connect(&format!("127.0.0.1:{}", port));
+        let service = create_greeter(RelayService {
+            client: GreeterClient::new(ch),
+        });
+        let mut relay = ServerBuilder::new(env.clone())
+            .register_service(service)
+            .bind("127.0.0.1", 0)
+            .build()
+            .unwrap();
+        relay.start();
+        let port = relay.bind_addrs().next().unwrap().1;
+        let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+        let client = GreeterClient::new(ch);
+        let opt = CallOption::default().trace_context(parent.clone());
+        let resp = client.say_hello_opt(&req, opt).unwrap();
+        let sent = TraceContext::parse(resp.get_message(), None).unwrap();
+        assert_eq!(sent.trace_id(), parent.trace_id());
+    }
+}
@@ -3,6 +3,7 @@
 mod auth_context;
 mod cancel;
 mod credential;
+mod jwt;
 mod kick;
 mod metadata;
 mod misc;
@@ -1,6 +1,8 @@
 // Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::channel::mpsc;
 use futures::executor::block_on;
@@ -9,7 +11,8 @@ use futures::prelude::*;
 use futures::sink::SinkExt;
 use futures_timer::Delay;
 use grpcio::{
-    ChannelBuilder, ClientStreamingSink, DuplexSink, EnvBuilder, RequestStream, RpcContext,
+    ChannelBuilder, ClientMetricsRecorder, ClientStreamingSink, DuplexSink, EnvBuilder, Metadata,
+    MetadataBuilder, MethodType, MetricsRecorder, RequestStream, RpcContext, RpcStatusCode,
     ServerBuilder, ServerStreamingSink, UnarySink, WriteFlags,
 };
 use grpcio_proto::example::route_guide::*;
@@ -23,8 +26,39 @@ impl RouteGuide for RouteGuideService {
     fn get_feature(&mut self, _: RpcContext<'_>, _: Point, _: UnarySink<Feature>) {
         unimplemented!()
     }
-    fn list_features(&mut self, _: RpcContext<'_>, _: Rectangle, _: ServerStreamingSink<Feature>) {
-        unimplemented!()
+    fn list_features(
+        &mut self,
+        ctx: RpcContext<'_>,
+        _: Rectangle,
+        mut sink: ServerStreamingSink<Feature>,
+    ) {
+        let mut headers = MetadataBuilder::new();
+        headers.add_str("response-header", "h1").unwrap();
+        sink.set_headers(headers.build()).unwrap();
+        let mut trailers = MetadataBuilder::new();
+        trailers.add_str("response-trailer", "t1").unwrap();
+        sink.set_trailers(trailers.build()).unwrap();
+        let f = async move {
+            for _ in 0..2 {
+                sink.send((Feature::default(), WriteFlags::default()))
+                    .await?;
+            }
+            // Headers are sent along with the first message.
+            match sink.set_headers(MetadataBuilder::new().build()) {
+                Err(grpcio::Error::RpcFinished(None)) => {}
+                res => panic!("expected RpcFinished, got {:?}", res),
+            }
+            sink.close().await?;
+            // Trailers can't be changed once the status is sent.
+            match sink.set_trailers(MetadataBuilder::new().build()) {
+                Err(grpcio::Error::RpcFinished(None)) => {}
+                res => panic!("expected RpcFinished, got {:?}", res),
+            }
+            Ok(())
+        }
+        .map_err(|e: grpcio::Error| panic!("server got error: {:?}", e))
+        .map(|_| ());
+        ctx.spawn(f)
     }
     fn record_route(
         &mut self,
@@ -150,3 +184,117 @@ fn test_client_send_all() {
     };
     block_on(exec_test_f);
 }
+
+#[derive(Clone, Default)]
+struct CountRecorder {
+    handled: Arc<AtomicUsize>,
+    received: Arc<AtomicUsize>,
+    sent: Arc<AtomicUsize>,
+}
+
+impl MetricsRecorder for CountRecorder {
+    fn on_started(&self, _: &str, _: MethodType) {}
+
+    fn on_handled(&self, _: &str, _: MethodType, code: RpcStatusCode, _: Duration) {
+        assert_eq!(code, RpcStatusCode::OK);
+        self.handled.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_msg_received(&self, _: &str, _: MethodType, _: usize) {
+        self.received.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_msg_sent(&self, _: &str, _: MethodType, _: usize) {
+        self.sent.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl ClientMetricsRecorder for CountRecorder {
+    fn on_started(&self, _: &str, _: &str, _: MethodType) {}
+
+    fn on_completed(&self, _: &str, _: &str, _: MethodType, code: RpcStatusCode, _: Duration) {
+        assert_eq!(code, RpcStatusCode::OK);
+        self.handled.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_msg_received(&self, _: &str, _: &str, _: MethodType, _: usize) {
+        self.received.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_msg_sent(&self, _: &str, _: &str, _: MethodType, _: usize) {
+        self.sent.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_stream_metrics() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let recorder = CountRecorder::default();
+    let service = create_route_guide(RouteGuideService {});
+    let mut server = ServerBuilder::new(env.clone())
+        .metrics_recorder(recorder.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let client_recorder = CountRecorder::default();
+    let ch = ChannelBuilder::new(env)
+        .metrics_recorder(client_recorder.clone())
+        .connect(&format!("127.0.0.1:{}", port));
+    let client = RouteGuideClient::new(ch);
+
+    let (mut sink, receiver) = client.record_route().unwrap();
+    let points = (0..10).map(|i| {
+        let mut p = Point::default();
+        p.set_longitude(i);
+        Ok((p, WriteFlags::default()))
+    });
+    block_on(sink.send_all(&mut futures::stream::iter(points))).unwrap();
+    block_on(sink.close()).unwrap();
+    let summary = block_on(receiver).unwrap();
+    assert_eq!(summary.get_point_count(), 10);
+    assert_eq!(recorder.received.load(Ordering::SeqCst), 10);
+    assert_eq!(recorder.sent.load(Ordering::SeqCst), 1);
+    // The server call is handled once the status is sent, which can be later than
+    // the client receives it.
+    for _ in 0..100 {
+        if recorder.handled.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(recorder.handled.load(Ordering::SeqCst), 1);
+    assert_eq!(client_recorder.sent.load(Ordering::SeqCst), 10);
+    assert_eq!(client_recorder.received.load(Ordering::SeqCst), 1);
+    assert_eq!(client_recorder.handled.load(Ordering::SeqCst), 1);
+}
+
+fn find_value<'a>(metadata: &'a Metadata, key: &str) -> Option<&'a [u8]> {
+    metadata.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+}
+
+/// Tests headers and trailers set on a streaming sink are sent to client.
+#[test]
+fn test_stream_response_metadata() {
+    let env = Arc::new(EnvBuilder::new().build());
+    let service = create_route_guide(RouteGuideService {});
+    let mut server = ServerBuilder::new(env.clone())
+        .register_service(service)
+        .bind("127.0.0.1", 0)
+        .build()
+        .unwrap();
+    server.start();
+    let port = server.bind_addrs().next().unwrap().1;
+    let ch = ChannelBuilder::new(env).connect(&format!("127.0.0.1:{}", port));
+    let client = RouteGuideClient::new(ch);
+
+    let mut receiver = client.list_features(&Rectangle::default()).unwrap();
+    let headers = block_on(receiver.headers()).clone();
+    assert_eq!(find_value(&headers, "response-header"), Some(&b"h1"[..]));
+    let features: Vec<_> = block_on(receiver.by_ref().try_collect()).unwrap();
+    assert_eq!(features.len(), 2);
+    let trailers = receiver.trailers().unwrap();
+    assert_eq!(find_value(trailers, "response-trailer"), Some(&b"t1"[..]));
+}
@@ -139,24 +139,46 @@ fn clang_lint() {
     exec(cmd("clang-format").args(&["-i", "grpc-sys/grpc_wrap.cc"]));
 }
 
-const PROTOS: &[(&str, &[&str], &str)] = &[(
-    "grpc-sys/grpc/src/proto",
-    &["grpc/health/v1"],
-    "health/src/proto",
-)];
+const PROTOS: &[(&str, &[&str], &str)] = &[
+    (
+        "grpc-sys/grpc/src/proto",
+        &["grpc/health/v1"],
+        "health/src/proto",
+    ),
+    (
+        "grpc-sys/grpc/src/proto",
+        &["grpc/reflection/v1alpha"],
+        "reflection/src/proto",
+    ),
+    (
+        "grpc-sys/grpc/src/proto",
+        &["grpc/channelz"],
+        "channelz/src/proto",
+    ),
+];
 
-const NAMING_PATCH: &[(&str, &[(&str, &str)])] = &[(
-    "health/src/proto/protobuf/health.rs",
-    &[
-        ("HealthCheckResponse_ServingStatus", "ServingStatus"),
-        // Order is important.
-        ("NOT_SERVING", "NotServing"),
-        ("SERVICE_UNKNOWN", "ServiceUnknown"),
-        ("UNKNOWN", "Unknown"),
-        ("SERVING", "Serving"),
-        ("rustfmt_skip", "rustfmt::skip"),
-    ],
-)];
+const NAMING_PATCH: &[(&str, &[(&str, &str)])] = &[
+    (
+        "health/src/proto/protobuf/health.rs",
+        &[
+            ("HealthCheckResponse_ServingStatus", "ServingStatus"),
+            // Order is important.
+            ("NOT_SERVING", "NotServing"),
+            ("SERVICE_UNKNOWN", "ServiceUnknown"),
+            ("UNKNOWN", "Unknown"),
+            ("SERVING", "Serving"),
+            ("rustfmt_skip", "rustfmt::skip"),
+        ],
+    ),
+    (
+        "reflection/src/proto/protobuf/reflection.rs",
+        &[("rustfmt_skip", "rustfmt::skip")],
+    ),
+    (
+        "channelz/src/proto/protobuf/channelz.rs",
+        &[("rustfmt_skip", "rustfmt::skip")],
+    ),
+];
 
 fn generate_protobuf(protoc: &str, include: &str, inputs: &[&str], out_dir: &str) {
     if Path::new(out_dir).exists() {
@@ -184,6 +206,9 @@ fn generate_protobuf(protoc: &str, include: &str, inputs: &[&str], out_dir: &str
     exec(&mut c);
 
     for (path, name_fixes) in NAMING_PATCH {
+        if !path.starts_with(out_dir) {
+            continue;
+        }
         let mut content = String::new();
         File::open(path)
             .unwrap()